@@ -67,6 +67,7 @@ impl AccountsApi {
         fail_point_poem("endpoint_get_account")?;
         self.context
             .check_api_output_enabled("Get account", &accept_type)?;
+        self.context.check_account_tracked(address.0.inner())?;
 
         let context = self.context.clone();
         api_spawn_blocking(move || {
@@ -113,6 +114,7 @@ impl AccountsApi {
         fail_point_poem("endpoint_get_account_resources")?;
         self.context
             .check_api_output_enabled("Get account resources", &accept_type)?;
+        self.context.check_account_tracked(address.0.inner())?;
 
         let context = self.context.clone();
         api_spawn_blocking(move || {
@@ -156,6 +158,7 @@ impl AccountsApi {
         fail_point_poem("endpoint_get_account_balance")?;
         self.context
             .check_api_output_enabled("Get account balance", &accept_type)?;
+        self.context.check_account_tracked(address.0.inner())?;
 
         let context = self.context.clone();
         api_spawn_blocking(move || {
@@ -202,6 +205,7 @@ impl AccountsApi {
         fail_point_poem("endpoint_get_account_modules")?;
         self.context
             .check_api_output_enabled("Get account modules", &accept_type)?;
+        self.context.check_account_tracked(address.0.inner())?;
 
         let context = self.context.clone();
         api_spawn_blocking(move || {
@@ -368,6 +372,12 @@ impl Account {
         };
         let primary_fungible_store_address =
             get_paired_fa_primary_store_address(self.address.into(), fa_metadata_address);
+        // In partial state mode, only the primary APT stores of tracked accounts are stored
+        // (unless the object group is tracked, in which case all fungible stores are stored)
+        self.context.check_resource_group_tracked(
+            &primary_fungible_store_address,
+            &ObjectGroupResource::struct_tag(),
+        )?;
         if let Some(data_blob) = self.context.get_state_value_poem(
             &StateKey::resource_group(
                 &primary_fungible_store_address,
@@ -6,9 +6,10 @@ use crate::{
     accept_type::AcceptType,
     metrics,
     response::{
-        bcs_api_disabled, block_not_found_by_height, block_not_found_by_version,
-        block_pruned_by_height, json_api_disabled, version_not_found, version_pruned,
-        ForbiddenError, InternalError, NotFoundError, ServiceUnavailableError, StdApiError,
+        account_not_tracked, bcs_api_disabled, block_not_found_by_height,
+        block_not_found_by_version, block_pruned_by_height, json_api_disabled,
+        partial_state_unsupported, version_not_found, version_pruned, ForbiddenError,
+        InternalError, NotFoundError, ServiceUnavailableError, StdApiError,
     },
 };
 use anyhow::{anyhow, bail, ensure, format_err, Context as AnyhowContext, Result};
@@ -62,6 +63,7 @@ use std::{
     cmp::Reverse,
     collections::{BTreeMap, HashMap},
     ops::{Bound::Included, Deref},
+    str::FromStr,
     sync::{
         atomic::{AtomicU64, AtomicUsize, Ordering},
         Arc, RwLock, RwLockWriteGuard,
@@ -1602,6 +1604,59 @@ impl Context {
         Ok(())
     }
 
+    /// Verifies that the state of the given account is stored by the node.
+    /// Nodes in partial state mode only store the state of tracked accounts.
+    pub fn check_account_tracked<E: ForbiddenError>(
+        &self,
+        address: &AccountAddress,
+    ) -> Result<(), E> {
+        if !self
+            .node_config
+            .storage
+            .partial_state
+            .is_account_tracked(address)
+        {
+            return Err(account_not_tracked(address.to_standard_string()));
+        }
+        Ok(())
+    }
+
+    /// Verifies that the given resource group of the given account is stored by the
+    /// node. Nodes in partial state mode store the tracked resource groups of all
+    /// accounts (and all resource groups of the tracked accounts).
+    pub fn check_resource_group_tracked<E: ForbiddenError>(
+        &self,
+        address: &AccountAddress,
+        resource_group: &StructTag,
+    ) -> Result<(), E> {
+        let resource_group_tracked = self
+            .node_config
+            .storage
+            .partial_state
+            .tracked_resource_groups
+            .iter()
+            .any(|tracked_resource_group| {
+                StructTag::from_str(tracked_resource_group)
+                    .is_ok_and(|tracked_resource_group| tracked_resource_group == *resource_group)
+            });
+        if resource_group_tracked {
+            return Ok(());
+        }
+        self.check_account_tracked(address)
+    }
+
+    /// Verifies that the node stores the full state (i.e., partial state mode
+    /// is disabled). This is required by APIs that may read arbitrary state.
+    pub fn check_full_state_available<E: ForbiddenError>(
+        &self,
+        api_name: &'static str,
+    ) -> Result<(), E> {
+        if self.node_config.storage.partial_state.enabled {
+            return Err(partial_state_unsupported(api_name));
+        }
+        Ok(())
+    }
+
     pub fn last_updated_gas_schedule(&self) -> Option<u64> {
         self.gas_schedule_cache.read().unwrap().last_updated_epoch
     }
@@ -135,6 +135,7 @@ impl EventsApi {
         fail_point_poem("endpoint_get_events_by_event_handle")?;
         self.context
             .check_api_output_enabled("Get events by event handle", &accept_type)?;
+        self.context.check_account_tracked(address.0.inner())?;
         let page = Page::new(
             start.0.map(|v| v.0),
             limit.0,
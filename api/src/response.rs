@@ -628,6 +628,26 @@ pub fn api_forbidden<S: Display, E: ForbiddenError>(identifier: S, extra_help: S
     )
 }
 
+pub fn account_not_tracked<S: Display, E: ForbiddenError>(address: S) -> E {
+    E::forbidden_with_code_no_info(
+        format!(
+            "Address({}) is not tracked by this node, as it only stores partial state",
+            address
+        ),
+        AptosErrorCode::AccountNotTracked,
+    )
+}
+
+pub fn partial_state_unsupported<S: Display, E: ForbiddenError>(identifier: S) -> E {
+    E::forbidden_with_code_no_info(
+        format!(
+            "{} is not supported by this node, as it only stores partial state",
+            identifier
+        ),
+        AptosErrorCode::ApiDisabled,
+    )
+}
+
 pub fn version_not_found<E: NotFoundError>(ledger_version: u64, ledger_info: &LedgerInfo) -> E {
     build_not_found(
         "Ledger version",
@@ -116,6 +116,7 @@ impl StateApi {
         fail_point_poem("endpoint_get_account_module")?;
         self.context
             .check_api_output_enabled("Get account module", &accept_type)?;
+        self.context.check_account_tracked(address.0.inner())?;
         let api = self.clone();
         api_spawn_blocking(move || {
             api.module(&accept_type, address.0, module_name.0, ledger_version.0)
@@ -163,6 +164,7 @@ impl StateApi {
         fail_point_poem("endpoint_get_table_item")?;
         self.context
             .check_api_output_enabled("Get table item", &accept_type)?;
+        self.context.check_full_state_available("Get table item")?;
         let api = self.clone();
         api_spawn_blocking(move || {
             api.table_item(
@@ -213,6 +215,8 @@ impl StateApi {
         }
         self.context
             .check_api_output_enabled("Get raw table item", &accept_type)?;
+        self.context
+            .check_full_state_available("Get raw table item")?;
 
         let api = self.clone();
         api_spawn_blocking(move || {
@@ -260,6 +264,8 @@ impl StateApi {
         }
         self.context
             .check_api_output_enabled("Get raw state value", &accept_type)?;
+        self.context
+            .check_full_state_available("Get raw state value")?;
 
         let api = self.clone();
         api_spawn_blocking(move || api.raw_value(&accept_type, request.0, ledger_version.0)).await
@@ -286,8 +292,19 @@ impl StateApi {
             })?;
 
         let (ledger_info, ledger_version, state_view) = self.context.state_view(ledger_version)?;
-        let bytes = state_view
-            .as_converter(self.context.db.clone(), self.context.indexer_reader.clone())
+        let converter =
+            state_view.as_converter(self.context.db.clone(), self.context.indexer_reader.clone());
+
+        // In partial state mode, resources are stored for the tracked accounts, and
+        // resource group members are also stored if their group is tracked.
+        match converter.find_resource_group(&tag) {
+            Some(resource_group) => self
+                .context
+                .check_resource_group_tracked(address.inner(), &resource_group)?,
+            None => self.context.check_account_tracked(address.inner())?,
+        }
+
+        let bytes = converter
             .find_resource(&state_view, address, &tag)
             .context(format!(
                 "Failed to query DB to check for {} at {}",
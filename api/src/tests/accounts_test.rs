@@ -2,13 +2,14 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use super::new_test_context;
+use super::{new_test_context, new_test_context_with_config};
 use aptos_api_test_context::{current_function_name, find_value, TestContext};
 use aptos_api_types::{MoveModuleBytecode, MoveResource, MoveStructTag, StateKeyWrapper};
 use aptos_cached_packages::aptos_stdlib;
+use aptos_config::config::{NodeConfig, PartialStateConfig};
 use aptos_sdk::types::APTOS_COIN_TYPE_STR;
 use aptos_types::{
-    account_config::{primary_apt_store, ObjectCoreResource},
+    account_config::{aptos_test_root_address, primary_apt_store, ObjectCoreResource},
     transaction::{EntryFunction, TransactionPayload},
     AptosCoinType, CoinType,
 };
@@ -508,6 +509,101 @@ async fn test_get_account_items_limit_params() {
     assert_eq!(resp.status(), 400);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_with_partial_state() {
+    // Create a test context that only tracks the root account
+    let mut node_config = NodeConfig::default();
+    node_config.storage.partial_state = PartialStateConfig {
+        enabled: true,
+        tracked_accounts: vec![aptos_test_root_address()],
+        tracked_resource_groups: vec![],
+    };
+    let mut context = new_test_context_with_config(current_function_name!(), node_config);
+
+    // Verify that the tracked (and special) accounts are served
+    let root_address = aptos_test_root_address().to_hex_literal();
+    for address in [root_address.as_str(), "0x1"] {
+        context
+            .expect_status_code(200)
+            .get(&account_resources(address))
+            .await;
+    }
+
+    // Verify that the APT balance of the tracked account is served
+    for asset_type in [APTOS_COIN_TYPE_STR, "0xa"] {
+        context
+            .expect_status_code(200)
+            .get(&account_balance(&root_address, asset_type))
+            .await;
+    }
+
+    // Verify that untracked accounts are rejected with a clear error
+    let untracked_address = AccountAddress::random().to_hex_literal();
+    for path in [
+        format!("/accounts/{}", untracked_address),
+        account_resources(&untracked_address),
+        account_modules(&untracked_address),
+        account_balance(&untracked_address, APTOS_COIN_TYPE_STR),
+    ] {
+        let resp = context.expect_status_code(403).get(&path).await;
+        assert_eq!(resp["error_code"], "account_not_tracked");
+    }
+
+    // Verify that the (untracked) primary stores of other fungible assets are rejected
+    let resp = context
+        .expect_status_code(403)
+        .get(&account_balance(&root_address, "0xbeef"))
+        .await;
+    assert_eq!(resp["error_code"], "account_not_tracked");
+
+    // Verify that APIs reading arbitrary state are unavailable
+    let request = json!({
+        "function": "0x1::coin::decimals",
+        "arguments": Vec::<String>::new(),
+        "type_arguments": vec!["0x1::aptos_coin::AptosCoin"],
+    });
+    let resp = context.expect_status_code(403).post("/view", request).await;
+    assert_eq!(resp["error_code"], "api_disabled");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_resource_group_member_with_partial_state() {
+    // Create a test context that only tracks the object group (for all accounts)
+    let mut node_config = NodeConfig::default();
+    node_config.storage.partial_state = PartialStateConfig {
+        enabled: true,
+        tracked_accounts: vec![],
+        tracked_resource_groups: vec!["0x1::object::ObjectGroup".into()],
+    };
+    let mut context = new_test_context_with_config(current_function_name!(), node_config);
+
+    // Verify that members of the tracked group are served for untracked accounts
+    // (the object doesn't exist, so the resource isn't found, but it isn't rejected).
+    let untracked_address = AccountAddress::random().to_hex_literal();
+    let resp = context
+        .expect_status_code(404)
+        .get(&account_resource(
+            &untracked_address,
+            "0x1::object::ObjectCore",
+        ))
+        .await;
+    assert_eq!(resp["error_code"], "resource_not_found");
+
+    // Verify that other resources of untracked accounts are still rejected
+    let resp = context
+        .expect_status_code(403)
+        .get(&account_resource(
+            &untracked_address,
+            "0x1::account::Account",
+        ))
+        .await;
+    assert_eq!(resp["error_code"], "account_not_tracked");
+}
+
+fn account_resource(address: &str, resource_type: &str) -> String {
+    format!("/accounts/{}/resource/{}", address, resource_type)
+}
+
 fn account_resources(address: &str) -> String {
     format!("/accounts/{}/resources", address)
 }
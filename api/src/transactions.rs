@@ -43,7 +43,7 @@ use aptos_types::{
     AptosCoinType, CoinType,
 };
 use aptos_vm::{AptosSimulationVM, AptosVM};
-use move_core_types::{ident_str, language_storage::ModuleId, vm_status::VMStatus};
+use move_core_types::{ident_str, language_storage::ModuleId};
 use poem_openapi::{
     param::{Path, Query},
     payload::Json,
@@ -574,6 +574,8 @@ impl TransactionsApi {
         }
         self.context
             .check_api_output_enabled("Simulate transaction", &accept_type)?;
+        self.context
+            .check_full_state_available("Simulate transaction")?;
 
         let api = self.clone();
         let context = self.context.clone();
@@ -1647,19 +1649,7 @@ impl TransactionsApi {
                 for transaction in transactions.into_iter() {
                     match transaction {
                         Transaction::UserTransaction(mut user_txn) => {
-                            match &vm_status {
-                                VMStatus::Error {
-                                    message: Some(msg), ..
-                                }
-                                | VMStatus::ExecutionFailure {
-                                    message: Some(msg), ..
-                                } => {
-                                    user_txn.info.vm_status +=
-                                        format!("\nExecution failed with message: {}", msg)
-                                            .as_str();
-                                },
-                                _ => (),
-                            }
+                            user_txn.append_simulation_error_message(&vm_status);
                             user_transactions.push(user_txn);
                         },
                         _ => {
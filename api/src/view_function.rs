@@ -21,7 +21,6 @@ use aptos_bcs_utils::serialize_uleb128;
 use aptos_types::{state_store::StateView, transaction::ViewFunctionError, vm_status::StatusCode};
 use aptos_vm::AptosVM;
 use itertools::Itertools;
-use move_core_types::language_storage::TypeTag;
 use poem_openapi::{param::Query, payload::Json, ApiRequest, OpenApi};
 use std::sync::Arc;
 
@@ -36,17 +35,9 @@ pub fn convert_view_function_error(
     state_view: &impl StateView,
     context: &Context,
 ) -> (String, Option<StatusCode>) {
-    match error {
-        ViewFunctionError::MoveAbort(status, vm_error_code) => {
-            let vm_status = state_view
-                .as_converter(context.db.clone(), context.indexer_reader.clone())
-                .explain_vm_status(status, None);
-            (vm_status, *vm_error_code)
-        },
-        ViewFunctionError::ErrorMessage(message, vm_error_code) => {
-            (message.clone(), *vm_error_code)
-        },
-    }
+    state_view
+        .as_converter(context.db.clone(), context.indexer_reader.clone())
+        .explain_view_function_error(error)
 }
 
 #[derive(ApiRequest, Debug)]
@@ -85,6 +76,7 @@ impl ViewFunctionApi {
         fail_point_poem("endpoint_view_function")?;
         self.context
             .check_api_output_enabled("View function", &accept_type)?;
+        self.context.check_full_state_available("View function")?;
 
         let context = self.context.clone();
         api_spawn_blocking(move || view_request(context, accept_type, request, ledger_version))
@@ -193,31 +185,9 @@ fn view_request(
             BasicResponse::try_from_encoded((ret, &ledger_info, BasicResponseStatus::Ok))
         },
         AcceptType::Json => {
-            let return_types = state_view
+            let move_vals = state_view
                 .as_converter(context.db.clone(), context.indexer_reader.clone())
-                .function_return_types(&view_function)
-                .and_then(|tys| {
-                    tys.iter()
-                        .map(TypeTag::try_from)
-                        .collect::<anyhow::Result<Vec<_>>>()
-                })
-                .map_err(|err| {
-                    BasicErrorWith404::bad_request_with_code(
-                        err,
-                        AptosErrorCode::InternalError,
-                        &ledger_info,
-                    )
-                })?;
-
-            let move_vals = values
-                .into_iter()
-                .zip(return_types.into_iter())
-                .map(|(v, ty)| {
-                    state_view
-                        .as_converter(context.db.clone(), context.indexer_reader.clone())
-                        .try_into_move_value(&ty, &v)
-                })
-                .collect::<anyhow::Result<Vec<_>>>()
+                .try_into_view_function_values(&view_function, &values)
                 .map_err(|err| {
                     BasicErrorWith404::bad_request_with_code(
                         err,
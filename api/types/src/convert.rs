@@ -33,10 +33,10 @@ use aptos_types::{
     },
     transaction::{
         BlockEndInfo, EntryFunction, ExecutionStatus, Multisig, RawTransaction, Script,
-        SignedTransaction, TransactionAuxiliaryData,
+        SignedTransaction, TransactionAuxiliaryData, ViewFunctionError,
     },
     vm::module_metadata::get_metadata,
-    vm_status::AbortLocation,
+    vm_status::{AbortLocation, StatusCode},
     write_set::WriteOp,
 };
 use bytes::Bytes;
@@ -95,6 +95,11 @@ impl<'a, S: StateView> MoveConverter<'a, S> {
         self.inner.view_resource(tag, bytes)?.try_into()
     }
 
+    /// Returns the resource group of the given resource (if it is a resource group member)
+    pub fn find_resource_group(&self, tag: &StructTag) -> Option<StructTag> {
+        self.inner.view_resource_group_member(tag)
+    }
+
     pub fn is_resource_group(&self, tag: &StructTag) -> bool {
         if let Ok(Some(module)) = self.inner.view_module(&tag.module_id()) {
             if let Some(md) = get_metadata(&module.metadata) {
@@ -1017,6 +1022,39 @@ impl<'a, S: StateView> MoveConverter<'a, S> {
         Ok(func.return_)
     }
 
+    /// Converts the BCS encoded return values of a view function into JSON values
+    pub fn try_into_view_function_values(
+        &self,
+        function: &ViewFunction,
+        values: &[Vec<u8>],
+    ) -> Result<Vec<MoveValue>> {
+        let return_types = self
+            .function_return_types(function)?
+            .iter()
+            .map(TypeTag::try_from)
+            .collect::<Result<Vec<_>>>()?;
+        values
+            .iter()
+            .zip(return_types.iter())
+            .map(|(value, typ)| self.try_into_move_value(typ, value))
+            .collect()
+    }
+
+    /// Returns the message and the VM status code (if any) of a failed view function
+    pub fn explain_view_function_error(
+        &self,
+        error: &ViewFunctionError,
+    ) -> (String, Option<StatusCode>) {
+        match error {
+            ViewFunctionError::MoveAbort(status, vm_error_code) => {
+                (self.explain_vm_status(status, None), *vm_error_code)
+            },
+            ViewFunctionError::ErrorMessage(message, vm_error_code) => {
+                (message.clone(), *vm_error_code)
+            },
+        }
+    }
+
     pub fn convert_view_function(&self, view_request: ViewRequest) -> Result<ViewFunction> {
         let ViewRequest {
             function,
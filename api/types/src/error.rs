@@ -81,6 +81,8 @@ pub enum AptosErrorCode {
     BlockNotFound = 108,
     ///  StateValue not found at the requested version
     StateValueNotFound = 109,
+    /// Account is not tracked by the node (i.e., the node only stores partial state)
+    AccountNotTracked = 110,
 
     /// Ledger version is pruned
     VersionPruned = 200,
@@ -33,6 +33,7 @@ use aptos_types::{
         webauthn::{PartialAuthenticatorAssertionResponse, MAX_WEBAUTHN_SIGNATURE_BYTES},
         Script, SignedTransaction, TransactionOutput, TransactionWithProof,
     },
+    vm_status::VMStatus,
 };
 use bcs::to_bytes;
 use once_cell::sync::Lazy;
@@ -413,6 +414,21 @@ pub struct UserTransaction {
     pub timestamp: U64,
 }
 
+impl UserTransaction {
+    /// Appends the error message of a simulation (if any) to the VM status
+    pub fn append_simulation_error_message(&mut self, vm_status: &VMStatus) {
+        if let VMStatus::Error {
+            message: Some(msg), ..
+        }
+        | VMStatus::ExecutionFailure {
+            message: Some(msg), ..
+        } = vm_status
+        {
+            self.info.vm_status += format!("\nExecution failed with message: {}", msg).as_str();
+        }
+    }
+}
+
 /// A state checkpoint transaction
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
 pub struct StateCheckpointTransaction {
@@ -7,12 +7,13 @@ use aptos_gas_profiling::{GasProfiler, TransactionGasLog};
 use aptos_rest_client::Client;
 use aptos_types::{
     account_address::AccountAddress,
+    account_config::AccountResource,
     block_executor::{
         config::{BlockExecutorConfig, BlockExecutorConfigFromOnchain, BlockExecutorLocalConfig},
         transaction_slice_metadata::TransactionSliceMetadata,
     },
     contract_event::ContractEvent,
-    state_store::TStateView,
+    state_store::{MoveResourceExt, TStateView},
     transaction::{
         signature_verified_transaction::SignatureVerifiedTransaction, BlockOutput,
         SignedTransaction, Transaction, TransactionExecutableRef, TransactionInfo,
@@ -348,6 +349,32 @@ impl AptosDebugger {
             .await
     }
 
+    pub async fn get_account_ordered_transactions(
+        &self,
+        account: AccountAddress,
+        start_seq: u64,
+        limit: u64,
+    ) -> anyhow::Result<Vec<(Version, Transaction)>> {
+        self.debugger
+            .get_account_ordered_transactions(account, start_seq, limit)
+            .await
+    }
+
+    /// Returns the sequence number of the account right before the given version is executed.
+    pub fn get_account_sequence_number_at_version(
+        &self,
+        account: AccountAddress,
+        version: Version,
+    ) -> anyhow::Result<u64> {
+        // The state view at genesis cannot be read.
+        if version == 0 {
+            return Ok(0);
+        }
+        let state_view = self.state_view_at_version(version);
+        Ok(AccountResource::fetch_move_resource(&state_view, &account)?
+            .map_or(0, |resource| resource.sequence_number()))
+    }
+
     pub async fn get_committed_transaction_at_version(
         &self,
         version: Version,
@@ -0,0 +1,325 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{aptos_debugger::AptosDebugger, common::Target};
+use anyhow::{bail, Result};
+use aptos_gas_profiling::{AggregatedTransactionGasLogs, TransactionGasLog};
+use aptos_rest_client::Client;
+use aptos_types::{
+    account_address::AccountAddress,
+    move_utils::MemberId,
+    transaction::{SignedTransaction, Transaction, TransactionExecutableRef, Version},
+};
+use clap::Parser;
+use std::{collections::BTreeMap, num::NonZeroUsize, path::PathBuf, sync::Mutex, thread};
+use tokio::runtime::Handle;
+use url::Url;
+
+/// Replays the user transactions within a range of versions with the gas profiler, and aggregates
+/// their gas usage into merged flamegraphs and per-function cost distributions.
+///
+/// Transactions can be selected by entry function and/or sender. Transactions of senders are looked
+/// up through the index of account transactions, rather than by scanning the range of versions.
+#[derive(Parser)]
+#[clap(
+    after_help = "Backups cannot be read directly, since replaying transactions requires the \
+    state at their versions. To profile transactions from a backup, restore it to a local db \
+    first (`aptos-debugger aptos-db restore`) and pass the db with `--db-path`."
+)]
+pub struct Command {
+    #[clap(flatten)]
+    target: Target,
+
+    /// First version to replay.
+    #[clap(long)]
+    begin_version: Version,
+
+    /// Version to stop at (exclusive).
+    #[clap(long)]
+    end_version: Version,
+
+    /// Only profile calls of these entry functions, e.g. `0x1::aptos_account::transfer`.
+    #[clap(long, num_args = 0..)]
+    entry_function: Vec<MemberId>,
+
+    /// Only profile transactions sent by these accounts.
+    ///
+    /// Orderless transactions are not in the index of account transactions, and are skipped.
+    #[clap(long, num_args = 0..)]
+    sender: Vec<AccountAddress>,
+
+    /// Stop after profiling this many transactions.
+    #[clap(long)]
+    max_transactions: Option<NonZeroUsize>,
+
+    /// Number of transactions to fetch at once.
+    #[clap(long, default_value_t = 1000)]
+    batch_size: u64,
+
+    /// Number of transactions to profile in parallel. Defaults to the number of CPUs.
+    #[clap(long)]
+    concurrency_level: Option<NonZeroUsize>,
+
+    /// Directory to write the report to.
+    #[clap(long, default_value = "gas-profiling/aggregated")]
+    output_dir: PathBuf,
+}
+
+/// The gas logs of the profiled transactions.
+struct Profile<'a> {
+    debugger: &'a AptosDebugger,
+    concurrency_level: usize,
+    max_transactions: usize,
+    aggregated: AggregatedTransactionGasLogs,
+    num_failed: usize,
+}
+
+impl Profile<'_> {
+    fn is_complete(&self) -> bool {
+        self.aggregated.num_transactions() >= self.max_transactions
+    }
+
+    /// Profiles the transactions in parallel, and aggregates their gas logs in the order of their
+    /// versions until the maximum number of transactions is reached.
+    ///
+    /// Returns the version after the last aggregated transaction if the maximum was reached.
+    fn add(&mut self, mut txns: Vec<(Version, SignedTransaction)>) -> Option<Version> {
+        txns.truncate(self.max_transactions - self.aggregated.num_transactions());
+
+        for (version, result) in self.execute(txns) {
+            match result {
+                Ok(gas_log) => self.aggregated.add(&gas_log),
+                Err(err) => {
+                    println!("Failed to profile transaction {}: {:#}", version, err);
+                    self.num_failed += 1;
+                },
+            }
+            if self.is_complete() {
+                return Some(version + 1);
+            }
+        }
+        None
+    }
+
+    fn execute(
+        &self,
+        txns: Vec<(Version, SignedTransaction)>,
+    ) -> Vec<(Version, Result<TransactionGasLog>)> {
+        let runtime = Handle::current();
+        let queue = Mutex::new(txns.into_iter());
+        let results = Mutex::new(vec![]);
+
+        tokio::task::block_in_place(|| {
+            thread::scope(|scope| {
+                for _ in 0..self.concurrency_level {
+                    scope.spawn(|| {
+                        // The state views of the debugger are served by tasks of the runtime.
+                        let _guard = runtime.enter();
+                        loop {
+                            let next = queue.lock().unwrap().next();
+                            let Some((version, txn)) = next else {
+                                break;
+                            };
+                            let result = self
+                                .debugger
+                                .execute_transaction_at_version_with_gas_profiler(version, txn)
+                                .map(|(_status, _output, gas_log)| gas_log);
+                            results.lock().unwrap().push((version, result));
+                        }
+                    });
+                }
+            })
+        });
+
+        let mut results = results.into_inner().unwrap();
+        results.sort_by_key(|(version, _)| *version);
+        results
+    }
+}
+
+impl Command {
+    fn is_selected(&self, txn: &SignedTransaction) -> bool {
+        if !self.sender.is_empty() && !self.sender.contains(&txn.sender()) {
+            return false;
+        }
+        match txn.payload().executable_ref() {
+            Ok(TransactionExecutableRef::EntryFunction(entry_func)) => {
+                self.entry_function.is_empty()
+                    || self.entry_function.iter().any(|member| {
+                        &member.module_id == entry_func.module()
+                            && member.member_id.as_ident_str() == entry_func.function()
+                    })
+            },
+            Ok(TransactionExecutableRef::Script(_)) => self.entry_function.is_empty(),
+            // The gas profiler does not support transactions without an executable.
+            Ok(TransactionExecutableRef::Empty) | Err(_) => false,
+        }
+    }
+
+    /// Profiles the selected transactions within the range of versions.
+    ///
+    /// Returns the version the profiling stopped at.
+    async fn profile_versions(&self, profile: &mut Profile<'_>) -> Result<Version> {
+        let mut begin = self.begin_version;
+        while begin < self.end_version {
+            let limit = self.batch_size.min(self.end_version - begin);
+            let (txns, _txn_infos) = profile
+                .debugger
+                .get_committed_transactions(begin, limit)
+                .await?;
+            if txns.is_empty() {
+                break;
+            }
+
+            let num_txns = txns.len() as u64;
+            let selected = (begin..)
+                .zip(txns)
+                .filter_map(|(version, txn)| match txn {
+                    Transaction::UserTransaction(txn) if self.is_selected(&txn) => {
+                        Some((version, txn))
+                    },
+                    _ => None,
+                })
+                .collect();
+            begin += num_txns;
+            if let Some(end) = profile.add(selected) {
+                return Ok(end);
+            }
+
+            println!(
+                "Profiled {} transactions up to version {}",
+                profile.aggregated.num_transactions(),
+                begin
+            );
+        }
+        Ok(begin)
+    }
+
+    /// Profiles the selected transactions of the senders within the range of versions, in the
+    /// order of their versions.
+    ///
+    /// Returns the version the profiling stopped at.
+    async fn profile_senders(&self, profile: &mut Profile<'_>) -> Result<Version> {
+        // For each sender, the sequence number of the next transaction to fetch, and the version
+        // before which all its transactions have been fetched, or `None` once all its transactions
+        // within the range have been fetched.
+        let mut senders = vec![];
+        for sender in &self.sender {
+            let next_seq = profile
+                .debugger
+                .get_account_sequence_number_at_version(*sender, self.begin_version)?;
+            senders.push((*sender, next_seq, Some(self.begin_version)));
+        }
+
+        let mut pending = BTreeMap::new();
+        loop {
+            // All transactions of the senders before this version have been fetched, so they can
+            // be profiled in order.
+            let fetched_until = senders.iter().filter_map(|(_, _, until)| *until).min();
+
+            let later = pending.split_off(&fetched_until.unwrap_or(self.end_version));
+            let ready = std::mem::replace(&mut pending, later);
+            if !ready.is_empty() {
+                if let Some(end) = profile.add(ready.into_iter().collect()) {
+                    return Ok(end);
+                }
+                println!(
+                    "Profiled {} transactions up to version {}",
+                    profile.aggregated.num_transactions(),
+                    fetched_until.unwrap_or(self.end_version)
+                );
+            }
+
+            let Some(fetched_until) = fetched_until else {
+                return Ok(self.end_version);
+            };
+            for (sender, next_seq, until) in &mut senders {
+                if *until != Some(fetched_until) {
+                    continue;
+                }
+                let txns = profile
+                    .debugger
+                    .get_account_ordered_transactions(*sender, *next_seq, self.batch_size)
+                    .await?;
+                *next_seq += txns.len() as u64;
+                *until = match txns.last() {
+                    Some((version, _)) if version + 1 < self.end_version => Some(version + 1),
+                    _ => None,
+                };
+                for (version, txn) in txns {
+                    match txn {
+                        Transaction::UserTransaction(txn)
+                            if version < self.end_version && self.is_selected(&txn) =>
+                        {
+                            pending.insert(version, txn);
+                        },
+                        _ => (),
+                    }
+                }
+            }
+        }
+    }
+
+    pub async fn run(self) -> Result<()> {
+        if self.begin_version >= self.end_version {
+            bail!("--begin-version must be smaller than --end-version");
+        }
+
+        let debugger = if let Some(rest_endpoint) = &self.target.rest_endpoint {
+            AptosDebugger::rest_client(Client::new(Url::parse(rest_endpoint)?))?
+        } else if let Some(db_path) = &self.target.db_path {
+            AptosDebugger::db(db_path.clone())?
+        } else {
+            unreachable!("Must provide one target.");
+        };
+
+        let mut profile = Profile {
+            debugger: &debugger,
+            concurrency_level: self
+                .concurrency_level
+                .or_else(|| thread::available_parallelism().ok())
+                .map_or(1, NonZeroUsize::get),
+            max_transactions: self.max_transactions.map_or(usize::MAX, NonZeroUsize::get),
+            aggregated: AggregatedTransactionGasLogs::new(),
+            num_failed: 0,
+        };
+        let end = if self.sender.is_empty() {
+            self.profile_versions(&mut profile).await?
+        } else {
+            self.profile_senders(&mut profile).await?
+        };
+        let Profile {
+            aggregated,
+            num_failed,
+            ..
+        } = profile;
+
+        if aggregated.num_transactions() == 0 {
+            bail!(
+                "No matching transactions found between versions {} and {}",
+                self.begin_version,
+                end
+            );
+        }
+
+        aggregated.generate_html_report(
+            &self.output_dir,
+            format!(
+                "Gas Report - {} Transactions from Versions {} to {}",
+                aggregated.num_transactions(),
+                self.begin_version,
+                end
+            ),
+        )?;
+
+        if num_failed > 0 {
+            println!("{} transactions could not be profiled.", num_failed);
+        }
+        println!(
+            "Aggregated gas profiling report saved to {}.",
+            self.output_dir.display()
+        );
+
+        Ok(())
+    }
+}
@@ -2,11 +2,24 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    log::{ExecutionAndIOCosts, ExecutionGasEvent},
+    diff::{CostEntry, FrameProfile},
+    flamegraph::{merge_folded_stack_lines, merged_flamegraph},
+    log::{ExecutionAndIOCosts, ExecutionGasEvent, TransactionGasLog},
     render::{Render, TableKey},
+    report::ensure_dirs_exist,
 };
+use anyhow::Result;
 use aptos_gas_algebra::{GasQuantity, GasScalingFactor, InternalGas};
-use std::collections::{btree_map, BTreeMap};
+use handlebars::Handlebars;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::{
+    collections::{btree_map, BTreeMap},
+    fs,
+    path::Path,
+};
+
+const TEMPLATE: &str = include_str!("../templates/aggregate.html");
 
 /// Represents an aggregation of execution gas events, including the count and total gas costs for each type of event.
 ///
@@ -138,3 +151,570 @@ impl ExecutionAndIOCosts {
         }
     }
 }
+
+/// The distribution of a cost over a number of samples, e.g. the gas used by each transaction.
+///
+/// The percentiles are approximated within 1% of the actual costs.
+#[derive(Debug, Clone, Serialize)]
+pub struct CostDistribution {
+    pub samples: usize,
+    pub total: u64,
+    pub min: u64,
+    pub mean: f64,
+    pub p50: u64,
+    pub p90: u64,
+    pub p99: u64,
+    pub max: u64,
+}
+
+/// The relative error of the percentiles reported in a [`CostDistribution`].
+const QUANTILE_RELATIVE_ACCURACY: f64 = 0.01;
+
+/// A streaming sketch of the distribution of a cost.
+///
+/// Non-zero costs are counted in buckets whose bounds grow exponentially, so the memory used only
+/// grows with the logarithm of the range of the costs rather than with the number of samples.
+/// The count, total, minimum and maximum are exact.
+#[derive(Debug, Clone, Default)]
+struct CostSketch {
+    samples: usize,
+    total: u64,
+    min: u64,
+    max: u64,
+    zeros: usize,
+    /// Bucket `i` counts the costs in `(gamma^(i - 1), gamma^i]`.
+    buckets: BTreeMap<i32, usize>,
+}
+
+impl CostSketch {
+    fn gamma() -> f64 {
+        (1.0 + QUANTILE_RELATIVE_ACCURACY) / (1.0 - QUANTILE_RELATIVE_ACCURACY)
+    }
+
+    fn add(&mut self, cost: u64) {
+        if self.samples == 0 || cost < self.min {
+            self.min = cost;
+        }
+        self.max = self.max.max(cost);
+        self.samples += 1;
+        self.total += cost;
+
+        if cost == 0 {
+            self.zeros += 1;
+        } else {
+            let index = ((cost as f64).ln() / Self::gamma().ln()).ceil() as i32;
+            *self.buckets.entry(index).or_default() += 1;
+        }
+    }
+
+    /// Returns the `(samples - 1) * percentile / 100`-th smallest cost (counting from 0),
+    /// approximated by the middle of its bucket.
+    fn percentile(&self, percentile: usize) -> u64 {
+        if self.samples == 0 {
+            return 0;
+        }
+        let rank = (self.samples - 1) * percentile / 100;
+        if rank < self.zeros {
+            return 0;
+        }
+
+        let gamma = Self::gamma();
+        let mut seen = self.zeros;
+        for (index, count) in &self.buckets {
+            seen += count;
+            if rank < seen {
+                let estimate = 2.0 * gamma.powi(*index) / (gamma + 1.0);
+                return (estimate.round() as u64).clamp(self.min, self.max);
+            }
+        }
+        self.max
+    }
+
+    fn distribution(&self) -> CostDistribution {
+        CostDistribution {
+            samples: self.samples,
+            total: self.total,
+            min: self.min,
+            mean: if self.samples == 0 {
+                0.0
+            } else {
+                self.total as f64 / self.samples as f64
+            },
+            p50: self.percentile(50),
+            p90: self.percentile(90),
+            p99: self.percentile(99),
+            max: self.max,
+        }
+    }
+}
+
+/// The gas used by a function in each of the transactions calling it, in internal gas units.
+#[derive(Debug, Clone, Serialize)]
+pub struct FunctionCostDistribution {
+    pub name: String,
+    pub calls: u64,
+    /// Execution and IO gas per transaction, including that of the callees.
+    pub inclusive: CostDistribution,
+    /// Execution and IO gas per transaction, excluding that of the callees.
+    pub exclusive: CostDistribution,
+}
+
+/// A serializable summary of [`AggregatedTransactionGasLogs`].
+///
+/// Execution and IO costs are in internal gas units, storage fees in octas.
+#[derive(Debug, Clone, Serialize)]
+pub struct AggregatedGasSummary {
+    pub gas_scaling_factor: u64,
+    pub transactions: usize,
+    pub execution_and_io: CostDistribution,
+    pub storage_fee: CostDistribution,
+    /// Functions, ordered by the total gas they used, including that of their callees.
+    pub functions: Vec<FunctionCostDistribution>,
+    pub ops: Vec<CostEntry>,
+    pub event_writes: Vec<CostEntry>,
+    pub storage_reads: Vec<CostEntry>,
+    pub storage_writes: Vec<CostEntry>,
+}
+
+#[derive(Default)]
+struct FunctionCosts {
+    calls: u64,
+    inclusive: CostSketch,
+    exclusive: CostSketch,
+}
+
+/// Gas usage aggregated over many transactions, e.g. all calls of an entry function within a
+/// range of versions.
+///
+/// The flamegraphs of the transactions are merged, and the costs of every function in each
+/// transaction are added to sketches of their distributions, so the memory used does not grow
+/// with the number of transactions.
+#[derive(Default)]
+pub struct AggregatedTransactionGasLogs {
+    gas_scaling_factor: u64,
+    execution_and_io: CostSketch,
+    storage_fees: CostSketch,
+    functions: BTreeMap<String, FunctionCosts>,
+    ops: BTreeMap<String, (usize, InternalGas)>,
+    event_writes: BTreeMap<String, (usize, InternalGas)>,
+    storage_reads: BTreeMap<String, (usize, InternalGas)>,
+    storage_writes: BTreeMap<String, (usize, InternalGas)>,
+    exec_io_stacks: BTreeMap<String, u64>,
+    storage_stacks: BTreeMap<String, u64>,
+}
+
+fn merge_aggregated<K, U>(
+    map: &mut BTreeMap<K, (usize, GasQuantity<U>)>,
+    entries: Vec<(K, usize, GasQuantity<U>)>,
+) where
+    K: Ord,
+{
+    for (key, count, amount) in entries {
+        let r = map.entry(key).or_insert((0, 0.into()));
+        r.0 += count;
+        r.1 += amount;
+    }
+}
+
+fn into_cost_entries<U>(map: &BTreeMap<String, (usize, GasQuantity<U>)>) -> Vec<CostEntry> {
+    into_sorted_vec(
+        map.iter()
+            .map(|(name, (count, amount))| (name, (*count, *amount))),
+    )
+    .into_iter()
+    .map(|(name, count, amount)| CostEntry {
+        name: name.clone(),
+        hits: count as u64,
+        cost: u64::from(amount),
+    })
+    .collect()
+}
+
+/// Collects the number of calls and the inclusive and exclusive costs of every function in the
+/// call graph. Recursive calls are only included once in the inclusive costs.
+fn collect_function_costs<'a>(
+    frame: &'a FrameProfile,
+    active: &mut Vec<&'a str>,
+    costs: &mut BTreeMap<&'a str, (u64, u64, u64)>,
+) -> u64 {
+    let exclusive = frame.execution + frame.io;
+
+    active.push(&frame.name);
+    let inclusive = frame
+        .children
+        .iter()
+        .map(|child| collect_function_costs(child, active, costs))
+        .sum::<u64>()
+        + exclusive;
+    active.pop();
+
+    let entry = costs.entry(frame.name.as_str()).or_default();
+    entry.0 += 1;
+    if !active.contains(&frame.name.as_str()) {
+        entry.1 += inclusive;
+    }
+    entry.2 += exclusive;
+    inclusive
+}
+
+impl AggregatedTransactionGasLogs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of transactions aggregated so far.
+    pub fn num_transactions(&self) -> usize {
+        self.execution_and_io.samples
+    }
+
+    /// Adds the gas log of a transaction to the aggregation.
+    pub fn add(&mut self, log: &TransactionGasLog) {
+        self.gas_scaling_factor = u64::from(log.exec_io.gas_scaling_factor);
+        self.execution_and_io.add(u64::from(log.exec_io.total));
+        self.storage_fees.add(u64::from(log.storage.total));
+
+        let call_graph = log.exec_io.call_graph.to_profile();
+        let mut costs = BTreeMap::new();
+        collect_function_costs(&call_graph, &mut vec![], &mut costs);
+        for (name, (calls, inclusive, exclusive)) in costs {
+            let function = self.functions.entry(name.to_string()).or_default();
+            function.calls += calls;
+            function.inclusive.add(inclusive);
+            function.exclusive.add(exclusive);
+        }
+
+        let aggregated = log.exec_io.aggregate_gas_events();
+        merge_aggregated(&mut self.ops, aggregated.ops);
+        merge_aggregated(&mut self.event_writes, aggregated.event_writes);
+        merge_aggregated(&mut self.storage_reads, aggregated.storage_reads);
+        merge_aggregated(&mut self.storage_writes, aggregated.storage_writes);
+
+        merge_folded_stack_lines(
+            &mut self.exec_io_stacks,
+            log.exec_io.to_folded_stack_lines(),
+        );
+        merge_folded_stack_lines(
+            &mut self.storage_stacks,
+            log.storage.to_folded_stack_lines(),
+        );
+    }
+
+    /// Summarizes the aggregated gas usage into cost distributions.
+    pub fn summary(&self) -> AggregatedGasSummary {
+        let mut functions = self
+            .functions
+            .iter()
+            .map(|(name, costs)| FunctionCostDistribution {
+                name: name.clone(),
+                calls: costs.calls,
+                inclusive: costs.inclusive.distribution(),
+                exclusive: costs.exclusive.distribution(),
+            })
+            .collect::<Vec<_>>();
+        functions.sort_by(|lhs, rhs| rhs.inclusive.total.cmp(&lhs.inclusive.total));
+
+        AggregatedGasSummary {
+            gas_scaling_factor: self.gas_scaling_factor,
+            transactions: self.num_transactions(),
+            execution_and_io: self.execution_and_io.distribution(),
+            storage_fee: self.storage_fees.distribution(),
+            functions,
+            ops: into_cost_entries(&self.ops),
+            event_writes: into_cost_entries(&self.event_writes),
+            storage_reads: into_cost_entries(&self.storage_reads),
+            storage_writes: into_cost_entries(&self.storage_writes),
+        }
+    }
+
+    /// Generates an HTML report with merged flamegraphs and the cost distributions of all
+    /// functions, and saves the summary as `summary.json` next to it.
+    pub fn generate_html_report(&self, path: impl AsRef<Path>, header: String) -> Result<()> {
+        let summary = self.summary();
+
+        let scaling_factor = self.gas_scaling_factor.max(1) as f64;
+        let fmt_gas = |amount: f64| -> String {
+            let scaled = format!("{:.8}", amount / scaling_factor);
+            crate::misc::strip_trailing_zeros_and_decimal_point(&scaled).to_string()
+        };
+        let fmt_apt = |amount: f64| -> String {
+            let scaled = format!("{:.8}", amount / 1_0000_0000f64);
+            crate::misc::strip_trailing_zeros_and_decimal_point(&scaled).to_string()
+        };
+        let convert_distribution = |dist: &CostDistribution, fmt: &dyn Fn(f64) -> String| {
+            json!({
+                "samples": dist.samples,
+                "total": fmt(dist.total as f64),
+                "min": fmt(dist.min as f64),
+                "mean": fmt(dist.mean),
+                "p50": fmt(dist.p50 as f64),
+                "p90": fmt(dist.p90 as f64),
+                "p99": fmt(dist.p99 as f64),
+                "max": fmt(dist.max as f64),
+            })
+        };
+        let convert_entries = |entries: &[CostEntry]| -> Value {
+            Value::Array(
+                entries
+                    .iter()
+                    .map(|entry| {
+                        json!({
+                            "name": entry.name,
+                            "hits": entry.hits,
+                            "cost": fmt_gas(entry.cost as f64),
+                        })
+                    })
+                    .collect(),
+            )
+        };
+
+        let graph_exec_io = merged_flamegraph(
+            &self.exec_io_stacks,
+            "Execution & IO".to_string(),
+            |count| format!("{} gas units", fmt_gas(count as f64)),
+        )?;
+        let graph_storage =
+            merged_flamegraph(&self.storage_stacks, "Storage".to_string(), |count| {
+                format!("{} Octa", count)
+            })?;
+
+        let data = json!({
+            "title": header,
+            "transactions": summary.transactions,
+            "execution-and-io": convert_distribution(&summary.execution_and_io, &fmt_gas),
+            "storage": convert_distribution(&summary.storage_fee, &fmt_apt),
+            "graph-exec-io": graph_exec_io.is_some(),
+            "graph-storage": graph_storage.is_some(),
+            "functions": Value::Array(
+                summary
+                    .functions
+                    .iter()
+                    .map(|function| {
+                        json!({
+                            "name": function.name,
+                            "calls": function.calls,
+                            "inclusive": convert_distribution(&function.inclusive, &fmt_gas),
+                            "exclusive": convert_distribution(&function.exclusive, &fmt_gas),
+                        })
+                    })
+                    .collect(),
+            ),
+            "ops": convert_entries(&summary.ops),
+            "event-writes": convert_entries(&summary.event_writes),
+            "storage-reads": convert_entries(&summary.storage_reads),
+            "storage-writes": convert_entries(&summary.storage_writes),
+        });
+
+        let mut handlebars = Handlebars::new();
+        handlebars.register_template_string("index", TEMPLATE)?;
+        let html = handlebars.render("index", &data)?;
+
+        let path_root = path.as_ref();
+
+        ensure_dirs_exist(path_root)?;
+        let path_assets = path_root.join("assets");
+        ensure_dirs_exist(&path_assets)?;
+
+        if let Some(graph_bytes) = graph_exec_io {
+            fs::write(path_assets.join("exec_io.svg"), graph_bytes)?;
+        }
+        if let Some(graph_bytes) = graph_storage {
+            fs::write(path_assets.join("storage.svg"), graph_bytes)?;
+        }
+        fs::write(path_root.join("index.html"), html)?;
+        fs::write(
+            path_root.join("summary.json"),
+            serde_json::to_string_pretty(&summary)?,
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::log::{CallFrame, StorageFees};
+    use move_binary_format::file_format_common::Opcodes;
+    use move_core_types::{
+        account_address::AccountAddress, identifier::Identifier, language_storage::ModuleId,
+    };
+
+    fn sketch(costs: impl IntoIterator<Item = u64>) -> CostSketch {
+        let mut sketch = CostSketch::default();
+        for cost in costs {
+            sketch.add(cost);
+        }
+        sketch
+    }
+
+    fn assert_approx(actual: u64, expected: u64) {
+        let error = (actual as f64 - expected as f64).abs();
+        assert!(
+            error <= expected as f64 * QUANTILE_RELATIVE_ACCURACY + 0.5,
+            "expected {} to be close to {}",
+            actual,
+            expected
+        );
+    }
+
+    /// A call of `0x1::test::<name>` executing a single instruction of the given cost.
+    fn function(name: &str, cost: u64, callees: Vec<CallFrame>) -> CallFrame {
+        let mut frame = CallFrame::new_function(
+            ModuleId::new(AccountAddress::ONE, Identifier::new("test").unwrap()),
+            Identifier::new(name).unwrap(),
+            vec![],
+        );
+        frame.events.push(ExecutionGasEvent::Bytecode {
+            op: Opcodes::ADD,
+            cost: cost.into(),
+        });
+        frame
+            .events
+            .extend(callees.into_iter().map(ExecutionGasEvent::Call));
+        frame
+    }
+
+    fn function_name(name: &str) -> String {
+        function(name, 0, vec![]).name.to_string()
+    }
+
+    fn log(call_graph: CallFrame, execution_and_io: u64, storage_fee: u64) -> TransactionGasLog {
+        TransactionGasLog {
+            exec_io: ExecutionAndIOCosts {
+                gas_scaling_factor: 1_000_000.into(),
+                total: execution_and_io.into(),
+                intrinsic_cost: 0.into(),
+                keyless_cost: 0.into(),
+                dependencies: vec![],
+                call_graph,
+                transaction_transient: None,
+                events_transient: vec![],
+                write_set_transient: vec![],
+            },
+            storage: StorageFees {
+                total: storage_fee.into(),
+                total_refund: 0.into(),
+                write_set_storage: vec![],
+                events: vec![],
+                event_discount: 0.into(),
+                txn_storage: storage_fee.into(),
+            },
+        }
+    }
+
+    #[test]
+    fn distribution_of_no_samples() {
+        let dist = CostSketch::default().distribution();
+        assert_eq!(dist.samples, 0);
+        assert_eq!(dist.total, 0);
+        assert_eq!(dist.min, 0);
+        assert_eq!(dist.mean, 0.0);
+        assert_eq!(dist.p50, 0);
+        assert_eq!(dist.p99, 0);
+        assert_eq!(dist.max, 0);
+    }
+
+    #[test]
+    fn distribution_approximates_percentiles() {
+        let dist = sketch((1..=1000).rev()).distribution();
+        assert_eq!(dist.samples, 1000);
+        assert_eq!(dist.total, 500_500);
+        assert_eq!(dist.min, 1);
+        assert_eq!(dist.mean, 500.5);
+        assert_eq!(dist.max, 1000);
+        assert_approx(dist.p50, 500);
+        assert_approx(dist.p90, 900);
+        assert_approx(dist.p99, 990);
+    }
+
+    #[test]
+    fn distribution_of_a_single_sample_is_exact() {
+        let dist = sketch([12_345]).distribution();
+        assert_eq!(
+            (dist.min, dist.p50, dist.p90, dist.p99, dist.max),
+            (12_345, 12_345, 12_345, 12_345, 12_345)
+        );
+    }
+
+    #[test]
+    fn distribution_counts_zeros() {
+        let dist = sketch([0, 0, 0, 1_000_000]).distribution();
+        assert_eq!(dist.min, 0);
+        assert_eq!(dist.p50, 0);
+        assert_eq!(dist.p90, 0);
+        assert_eq!(dist.max, 1_000_000);
+        assert_eq!(dist.mean, 250_000.0);
+
+        let dist = sketch([0, 7_000_000, 7_000_000, 7_000_000]).distribution();
+        assert_approx(dist.p50, 7_000_000);
+    }
+
+    #[test]
+    fn sketch_memory_does_not_grow_with_samples() {
+        let sketch = sketch((0..100_000).map(|i| 1_000 + i % 100));
+        assert_eq!(sketch.samples, 100_000);
+        assert!(sketch.buckets.len() <= 6);
+    }
+
+    #[test]
+    fn add_aggregates_costs_per_transaction() {
+        let mut aggregated = AggregatedTransactionGasLogs::new();
+        aggregated.add(&log(
+            function("main", 10, vec![
+                function("f", 5, vec![]),
+                function("f", 5, vec![]),
+            ]),
+            20,
+            100,
+        ));
+        // The recursive call is only counted once in the inclusive cost of `f`.
+        let recursive = function("f", 1, vec![function("f", 2, vec![])]);
+        aggregated.add(&log(function("main", 20, vec![recursive]), 23, 300));
+        assert_eq!(aggregated.num_transactions(), 2);
+
+        let summary = aggregated.summary();
+        assert_eq!(summary.gas_scaling_factor, 1_000_000);
+        assert_eq!(summary.transactions, 2);
+        assert_eq!(
+            (
+                summary.execution_and_io.total,
+                summary.execution_and_io.min,
+                summary.execution_and_io.max
+            ),
+            (43, 20, 23)
+        );
+        assert_eq!(
+            (
+                summary.storage_fee.total,
+                summary.storage_fee.min,
+                summary.storage_fee.max
+            ),
+            (400, 100, 300)
+        );
+
+        let functions = summary
+            .functions
+            .iter()
+            .map(|function| {
+                (
+                    function.name.clone(),
+                    function.calls,
+                    function.inclusive.samples,
+                    function.inclusive.total,
+                    function.exclusive.total,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(functions, vec![
+            (function_name("main"), 2, 2, 43, 30),
+            (function_name("f"), 4, 2, 13, 13),
+        ]);
+
+        let f = &summary.functions[1];
+        assert_eq!((f.inclusive.min, f.inclusive.max), (3, 10));
+
+        assert_eq!(summary.ops.len(), 1);
+        assert_eq!(summary.ops[0].name, "add");
+        assert_eq!((summary.ops[0].hits, summary.ops[0].cost), (6, 43));
+    }
+}
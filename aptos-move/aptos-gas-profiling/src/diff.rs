@@ -0,0 +1,877 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Comparison of the gas usage of two executions, e.g. of a transaction before and after an
+//! optimization.
+//!
+//! A gas log is first summarized into a serializable [`GasProfile`], which is saved next to the
+//! HTML report of every profiled transaction, so that profiles of separate runs can be compared
+//! later on. The call frames of the two executions are aligned by function name: the callees of
+//! two aligned frames are matched up using their longest common subsequence.
+
+use crate::{
+    log::{CallFrame, ExecutionGasEvent, TransactionGasLog},
+    render::Render,
+    report::{ensure_dirs_exist, render_table},
+};
+use anyhow::{bail, Context, Result};
+use handlebars::Handlebars;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{collections::BTreeMap, fs, path::Path};
+
+const TEMPLATE: &str = include_str!("../templates/diff.html");
+
+/// Name of the file a [`GasProfile`] is saved to, next to the HTML report of a transaction.
+pub const GAS_PROFILE_FILE_NAME: &str = "gas_profile.json";
+
+/// Up to this many cells, callees are aligned by their longest common subsequence. Beyond that
+/// (e.g. for loops with many calls), they are only matched up by position.
+const MAX_ALIGNMENT_CELLS: usize = 4_000_000;
+
+/// The accumulated cost and number of hits of an item, e.g. an instruction or a state key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostEntry {
+    pub name: String,
+    pub hits: u64,
+    pub cost: u64,
+}
+
+/// The gas used by a call frame, excluding that of its callees.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FrameProfile {
+    pub name: String,
+    /// Execution gas for instructions, native functions and type creation, in internal units.
+    pub execution: u64,
+    /// IO gas for loading resources, in internal units.
+    pub io: u64,
+    /// Instructions and native functions executed by the frame.
+    pub instructions: Vec<CostEntry>,
+    pub children: Vec<FrameProfile>,
+}
+
+/// A serializable summary of a [`TransactionGasLog`], which two executions are compared on.
+///
+/// Execution and IO costs are in internal gas units, storage fees in octas.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GasProfile {
+    pub gas_scaling_factor: u64,
+    pub execution_and_io: u64,
+    pub intrinsic: u64,
+    pub keyless: u64,
+    pub dependencies: Vec<CostEntry>,
+    pub call_graph: FrameProfile,
+    pub transaction_write: u64,
+    pub event_writes: Vec<CostEntry>,
+    pub state_writes: Vec<CostEntry>,
+    pub storage_fee: u64,
+    pub storage_refund: u64,
+    pub storage_transaction: u64,
+    pub storage_writes: Vec<CostEntry>,
+    pub storage_refunds: Vec<CostEntry>,
+    pub storage_events: Vec<CostEntry>,
+}
+
+/// A cost before and after.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Delta {
+    pub base: u64,
+    pub new: u64,
+    pub delta: i128,
+}
+
+/// The change in cost and hits of an item.
+#[derive(Debug, Clone, Serialize)]
+pub struct EntryDelta {
+    pub name: String,
+    pub base_hits: u64,
+    pub new_hits: u64,
+    pub cost: Delta,
+}
+
+/// Whether a call frame occurs in both executions, or only in one of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FrameChange {
+    Matched,
+    Added,
+    Removed,
+}
+
+/// A pair of aligned call frames, with costs including those of their callees.
+#[derive(Debug, Clone, Serialize)]
+pub struct FrameDiff {
+    pub name: String,
+    pub change: FrameChange,
+    pub execution: Delta,
+    pub io: Delta,
+    pub children: Vec<FrameDiff>,
+}
+
+/// The change in the gas used by all calls of a function, excluding that of its callees.
+#[derive(Debug, Clone, Serialize)]
+pub struct FunctionDiff {
+    pub name: String,
+    pub base_calls: u64,
+    pub new_calls: u64,
+    pub execution: Delta,
+    pub io: Delta,
+    pub instructions: Vec<EntryDelta>,
+}
+
+/// The differences between the gas usage of two executions.
+#[derive(Debug, Clone, Serialize)]
+pub struct GasProfileDiff {
+    /// The gas scaling factor of both executions.
+    pub gas_scaling_factor: u64,
+    pub execution_and_io: Delta,
+    pub intrinsic: Delta,
+    pub keyless: Delta,
+    pub dependencies: Vec<EntryDelta>,
+    pub call_graph: FrameDiff,
+    /// Functions, ordered by the absolute change of their execution and IO gas.
+    pub functions: Vec<FunctionDiff>,
+    /// Instructions and native functions over all frames.
+    pub instructions: Vec<EntryDelta>,
+    pub transaction_write: Delta,
+    pub event_writes: Vec<EntryDelta>,
+    pub state_writes: Vec<EntryDelta>,
+    pub storage_fee: Delta,
+    pub storage_refund: Delta,
+    pub storage_transaction: Delta,
+    pub storage_writes: Vec<EntryDelta>,
+    pub storage_refunds: Vec<EntryDelta>,
+    pub storage_events: Vec<EntryDelta>,
+}
+
+impl Delta {
+    pub fn new(base: u64, new: u64) -> Self {
+        Self {
+            base,
+            new,
+            delta: new as i128 - base as i128,
+        }
+    }
+}
+
+/// Accumulates costs by name, keeping the entries sorted by name.
+fn collect_entries(items: impl IntoIterator<Item = (String, u64, u64)>) -> Vec<CostEntry> {
+    let mut map: BTreeMap<String, (u64, u64)> = BTreeMap::new();
+    for (name, hits, cost) in items {
+        let entry = map.entry(name).or_default();
+        entry.0 += hits;
+        entry.1 += cost;
+    }
+    map.into_iter()
+        .map(|(name, (hits, cost))| CostEntry { name, hits, cost })
+        .collect()
+}
+
+impl CallFrame {
+    pub(crate) fn to_profile(&self) -> FrameProfile {
+        use ExecutionGasEvent::*;
+
+        let mut execution = 0;
+        let mut io = 0;
+        let mut instructions = vec![];
+        let mut children = vec![];
+        for event in &self.events {
+            match event {
+                Loc(..) => (),
+                Bytecode { op, cost } => {
+                    execution += u64::from(*cost);
+                    instructions.push((
+                        format!("{:?}", op).to_ascii_lowercase(),
+                        1,
+                        u64::from(*cost),
+                    ));
+                },
+                Call(frame) => children.push(frame.to_profile()),
+                CallNative {
+                    module_id,
+                    fn_name,
+                    ty_args,
+                    cost,
+                } => {
+                    execution += u64::from(*cost);
+                    instructions.push((
+                        format!(
+                            "{}",
+                            Render(&(module_id, fn_name.as_ident_str(), ty_args.as_slice()))
+                        ),
+                        1,
+                        u64::from(*cost),
+                    ));
+                },
+                LoadResource { cost, .. } => io += u64::from(*cost),
+                CreateTy { cost } => {
+                    execution += u64::from(*cost);
+                    instructions.push(("create_ty".to_string(), 1, u64::from(*cost)));
+                },
+            }
+        }
+
+        FrameProfile {
+            name: format!("{}", self.name),
+            execution,
+            io,
+            instructions: collect_entries(instructions),
+            children,
+        }
+    }
+}
+
+impl FrameProfile {
+    /// Returns the execution and IO gas of the frame, including that of its callees.
+    fn inclusive_costs(&self) -> (u64, u64) {
+        self.children
+            .iter()
+            .map(|child| child.inclusive_costs())
+            .fold((self.execution, self.io), |(e, i), (ce, ci)| {
+                (e + ce, i + ci)
+            })
+    }
+
+    /// Accumulates the calls, costs and instructions of each function in the call graph.
+    fn collect_functions<'a>(&'a self, functions: &mut BTreeMap<&'a str, FunctionTotals>) {
+        let totals = functions.entry(self.name.as_str()).or_default();
+        totals.calls += 1;
+        totals.execution += self.execution;
+        totals.io += self.io;
+        for instr in &self.instructions {
+            let entry = totals.instructions.entry(instr.name.clone()).or_default();
+            entry.0 += instr.hits;
+            entry.1 += instr.cost;
+        }
+        for child in &self.children {
+            child.collect_functions(functions);
+        }
+    }
+}
+
+#[derive(Default)]
+struct FunctionTotals {
+    calls: u64,
+    execution: u64,
+    io: u64,
+    instructions: BTreeMap<String, (u64, u64)>,
+}
+
+impl TransactionGasLog {
+    /// Summarizes the gas log into a profile which can be saved and compared with another one.
+    pub fn to_profile(&self) -> GasProfile {
+        let exec_io = &self.exec_io;
+        let storage = &self.storage;
+        GasProfile {
+            gas_scaling_factor: u64::from(exec_io.gas_scaling_factor),
+            execution_and_io: u64::from(exec_io.total),
+            intrinsic: u64::from(exec_io.intrinsic_cost),
+            keyless: u64::from(exec_io.keyless_cost),
+            dependencies: collect_entries(
+                exec_io
+                    .dependencies
+                    .iter()
+                    .map(|dep| (format!("{}", Render(&dep.id)), 1, u64::from(dep.cost))),
+            ),
+            call_graph: exec_io.call_graph.to_profile(),
+            transaction_write: exec_io
+                .transaction_transient
+                .map(u64::from)
+                .unwrap_or_default(),
+            event_writes: collect_entries(
+                exec_io
+                    .events_transient
+                    .iter()
+                    .map(|event| (format!("{}", Render(&event.ty)), 1, u64::from(event.cost))),
+            ),
+            state_writes: collect_entries(
+                exec_io
+                    .write_set_transient
+                    .iter()
+                    .map(|write| (format!("{}", Render(&write.key)), 1, u64::from(write.cost))),
+            ),
+            storage_fee: u64::from(storage.total),
+            storage_refund: u64::from(storage.total_refund),
+            storage_transaction: u64::from(storage.txn_storage),
+            storage_writes: collect_entries(
+                storage
+                    .write_set_storage
+                    .iter()
+                    .map(|write| (format!("{}", Render(&write.key)), 1, u64::from(write.cost))),
+            ),
+            storage_refunds: collect_entries(
+                storage
+                    .write_set_storage
+                    .iter()
+                    .filter(|write| !write.refund.is_zero())
+                    .map(|write| {
+                        (
+                            format!("{}", Render(&write.key)),
+                            1,
+                            u64::from(write.refund),
+                        )
+                    }),
+            ),
+            storage_events: collect_entries(
+                storage
+                    .events
+                    .iter()
+                    .map(|event| (event.ty.to_canonical_string(), 1, u64::from(event.cost))),
+            ),
+        }
+    }
+}
+
+/// Pairs up the entries of two lists by name, ordered by the absolute change of their cost.
+fn diff_entries(base: &[CostEntry], new: &[CostEntry]) -> Vec<EntryDelta> {
+    let mut map: BTreeMap<&str, ((u64, u64), (u64, u64))> = BTreeMap::new();
+    for entry in base {
+        map.entry(entry.name.as_str()).or_default().0 = (entry.hits, entry.cost);
+    }
+    for entry in new {
+        map.entry(entry.name.as_str()).or_default().1 = (entry.hits, entry.cost);
+    }
+    let mut diffs = map
+        .into_iter()
+        .map(|(name, ((base_hits, base), (new_hits, new)))| EntryDelta {
+            name: name.to_string(),
+            base_hits,
+            new_hits,
+            cost: Delta::new(base, new),
+        })
+        .collect::<Vec<_>>();
+    diffs.sort_by(|lhs, rhs| rhs.cost.delta.abs().cmp(&lhs.cost.delta.abs()));
+    diffs
+}
+
+/// Returns the pairs of indices of the longest common subsequence of two lists of names.
+fn align_names(base: &[&str], new: &[&str]) -> Vec<(usize, usize)> {
+    let (n, m) = (base.len(), new.len());
+    if n.saturating_mul(m) > MAX_ALIGNMENT_CELLS {
+        return (0..n.min(m))
+            .filter(|i| base[*i] == new[*i])
+            .map(|i| (i, i))
+            .collect();
+    }
+    // lcs[i][j] is the length of the LCS of base[i..] and new[j..].
+    let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if base[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    let mut pairs = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if base[i] == new[j] {
+            pairs.push((i, j));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs
+}
+
+impl FrameDiff {
+    fn matched(base: &FrameProfile, new: &FrameProfile) -> Self {
+        let (base_execution, base_io) = base.inclusive_costs();
+        let (new_execution, new_io) = new.inclusive_costs();
+        Self {
+            name: new.name.clone(),
+            change: FrameChange::Matched,
+            execution: Delta::new(base_execution, new_execution),
+            io: Delta::new(base_io, new_io),
+            children: Self::align_children(&base.children, &new.children),
+        }
+    }
+
+    fn unmatched(frame: &FrameProfile, change: FrameChange) -> Self {
+        let (execution, io) = frame.inclusive_costs();
+        let (execution, io) = if change == FrameChange::Added {
+            (Delta::new(0, execution), Delta::new(0, io))
+        } else {
+            (Delta::new(execution, 0), Delta::new(io, 0))
+        };
+        Self {
+            name: frame.name.clone(),
+            change,
+            execution,
+            io,
+            children: frame
+                .children
+                .iter()
+                .map(|child| Self::unmatched(child, change))
+                .collect(),
+        }
+    }
+
+    fn align_children(base: &[FrameProfile], new: &[FrameProfile]) -> Vec<Self> {
+        let base_names = base.iter().map(|f| f.name.as_str()).collect::<Vec<_>>();
+        let new_names = new.iter().map(|f| f.name.as_str()).collect::<Vec<_>>();
+        let mut children = vec![];
+        let (mut i, mut j) = (0, 0);
+        for (bi, nj) in align_names(&base_names, &new_names)
+            .into_iter()
+            .chain(std::iter::once((base.len(), new.len())))
+        {
+            children.extend(
+                base[i..bi]
+                    .iter()
+                    .map(|f| Self::unmatched(f, FrameChange::Removed)),
+            );
+            children.extend(
+                new[j..nj]
+                    .iter()
+                    .map(|f| Self::unmatched(f, FrameChange::Added)),
+            );
+            if bi < base.len() && nj < new.len() {
+                children.push(Self::matched(&base[bi], &new[nj]));
+            }
+            i = bi + 1;
+            j = nj + 1;
+        }
+        children
+    }
+
+    /// Visits the frames in pre-order, along with their depth.
+    fn preorder_traversal(&self, depth: usize, f: &mut impl FnMut(usize, &FrameDiff)) {
+        f(depth, self);
+        for child in &self.children {
+            child.preorder_traversal(depth + 1, f);
+        }
+    }
+}
+
+impl GasProfile {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("failed to read gas profile {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("failed to parse gas profile {}", path.display()))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Compares this profile, the base, against the profile of a new execution.
+    ///
+    /// Costs are compared in internal gas units, so both executions must use the same gas
+    /// scaling factor.
+    pub fn diff(&self, new: &GasProfile) -> Result<GasProfileDiff> {
+        if self.gas_scaling_factor != new.gas_scaling_factor {
+            bail!(
+                "cannot compare gas profiles with different gas scaling factors ({} and {})",
+                self.gas_scaling_factor,
+                new.gas_scaling_factor
+            );
+        }
+
+        let mut base_functions = BTreeMap::new();
+        self.call_graph.collect_functions(&mut base_functions);
+        let mut new_functions = BTreeMap::new();
+        new.call_graph.collect_functions(&mut new_functions);
+
+        let to_entries = |instructions: &BTreeMap<String, (u64, u64)>| {
+            instructions
+                .iter()
+                .map(|(name, (hits, cost))| CostEntry {
+                    name: name.clone(),
+                    hits: *hits,
+                    cost: *cost,
+                })
+                .collect::<Vec<_>>()
+        };
+        let empty = FunctionTotals::default();
+        let mut names = base_functions.keys().copied().collect::<Vec<_>>();
+        names.extend(new_functions.keys().copied());
+        names.sort();
+        names.dedup();
+        let mut functions = names
+            .into_iter()
+            .map(|name| {
+                let base = base_functions.get(name).unwrap_or(&empty);
+                let new = new_functions.get(name).unwrap_or(&empty);
+                FunctionDiff {
+                    name: name.to_string(),
+                    base_calls: base.calls,
+                    new_calls: new.calls,
+                    execution: Delta::new(base.execution, new.execution),
+                    io: Delta::new(base.io, new.io),
+                    instructions: diff_entries(
+                        &to_entries(&base.instructions),
+                        &to_entries(&new.instructions),
+                    ),
+                }
+            })
+            .collect::<Vec<_>>();
+        functions.sort_by_key(|f| std::cmp::Reverse((f.execution.delta + f.io.delta).abs()));
+
+        let all_instructions = |functions: &BTreeMap<&str, FunctionTotals>| {
+            collect_entries(functions.values().flat_map(|totals| {
+                totals
+                    .instructions
+                    .iter()
+                    .map(|(name, (hits, cost))| (name.clone(), *hits, *cost))
+            }))
+        };
+
+        Ok(GasProfileDiff {
+            gas_scaling_factor: self.gas_scaling_factor,
+            execution_and_io: Delta::new(self.execution_and_io, new.execution_and_io),
+            intrinsic: Delta::new(self.intrinsic, new.intrinsic),
+            keyless: Delta::new(self.keyless, new.keyless),
+            dependencies: diff_entries(&self.dependencies, &new.dependencies),
+            call_graph: if self.call_graph.name == new.call_graph.name {
+                FrameDiff::matched(&self.call_graph, &new.call_graph)
+            } else {
+                // Different entry points are still compared, under the new name.
+                FrameDiff {
+                    name: format!("{} -> {}", self.call_graph.name, new.call_graph.name),
+                    ..FrameDiff::matched(&self.call_graph, &new.call_graph)
+                }
+            },
+            functions,
+            instructions: diff_entries(
+                &all_instructions(&base_functions),
+                &all_instructions(&new_functions),
+            ),
+            transaction_write: Delta::new(self.transaction_write, new.transaction_write),
+            event_writes: diff_entries(&self.event_writes, &new.event_writes),
+            state_writes: diff_entries(&self.state_writes, &new.state_writes),
+            storage_fee: Delta::new(self.storage_fee, new.storage_fee),
+            storage_refund: Delta::new(self.storage_refund, new.storage_refund),
+            storage_transaction: Delta::new(self.storage_transaction, new.storage_transaction),
+            storage_writes: diff_entries(&self.storage_writes, &new.storage_writes),
+            storage_refunds: diff_entries(&self.storage_refunds, &new.storage_refunds),
+            storage_events: diff_entries(&self.storage_events, &new.storage_events),
+        })
+    }
+}
+
+impl GasProfileDiff {
+    /// Writes the diff as an HTML report (`index.html`) and as JSON (`diff.json`) into the
+    /// directory at `path`.
+    pub fn generate_html_report(&self, path: impl AsRef<Path>, header: String) -> Result<()> {
+        let scaling_factor = self.gas_scaling_factor as f64;
+        let fmt_gas = |amount: f64| -> String {
+            let scaled = format!("{:.8}", amount / scaling_factor);
+            crate::misc::strip_trailing_zeros_and_decimal_point(&scaled).to_string()
+        };
+        let fmt_apt = |amount: f64| -> String {
+            let scaled = format!("{:.8}", amount / 1_0000_0000f64);
+            crate::misc::strip_trailing_zeros_and_decimal_point(&scaled).to_string()
+        };
+        let convert_delta = |delta: &Delta, fmt: &dyn Fn(f64) -> String| -> Value {
+            let percentage = if delta.base == 0 {
+                if delta.new == 0 {
+                    "".to_string()
+                } else {
+                    "new".to_string()
+                }
+            } else {
+                format!("{:+.2}%", delta.delta as f64 / delta.base as f64 * 100.0)
+            };
+            let (sign, class) = match delta.delta.signum() {
+                1 => ("+", "increase"),
+                -1 => ("", "decrease"),
+                _ => ("", ""),
+            };
+            json!({
+                "base": fmt(delta.base as f64),
+                "new": fmt(delta.new as f64),
+                "delta": format!("{}{}", sign, fmt(delta.delta as f64)),
+                "percentage": percentage,
+                "class": class,
+            })
+        };
+        let convert_entries = |entries: &[EntryDelta], fmt: &dyn Fn(f64) -> String| -> Value {
+            Value::Array(
+                entries
+                    .iter()
+                    .map(|entry| {
+                        json!({
+                            "name": entry.name,
+                            "base_hits": entry.base_hits,
+                            "new_hits": entry.new_hits,
+                            "cost": convert_delta(&entry.cost, fmt),
+                        })
+                    })
+                    .collect(),
+            )
+        };
+
+        // The aligned call graph, with costs including those of callees
+        let mut table = vec![[
+            "".to_string(),
+            "Base".to_string(),
+            "New".to_string(),
+            "Delta".to_string(),
+        ]];
+        self.call_graph.preorder_traversal(0, &mut |depth, frame| {
+            let marker = match frame.change {
+                FrameChange::Matched => " ",
+                FrameChange::Added => "+",
+                FrameChange::Removed => "-",
+            };
+            let cost = Delta::new(
+                frame.execution.base + frame.io.base,
+                frame.execution.new + frame.io.new,
+            );
+            let cost = convert_delta(&cost, &fmt_gas);
+            table.push([
+                format!("{} {}{}", marker, " ".repeat(depth * 4), frame.name),
+                cost["base"].as_str().unwrap_or_default().to_string(),
+                cost["new"].as_str().unwrap_or_default().to_string(),
+                cost["delta"].as_str().unwrap_or_default().to_string(),
+            ]);
+        });
+        let mut trace = String::new();
+        render_table(&mut trace, &table, 4)?;
+
+        let data = json!({
+            "title": header,
+            "execution-and-io": convert_delta(&self.execution_and_io, &fmt_gas),
+            "intrinsic": convert_delta(&self.intrinsic, &fmt_gas),
+            "keyless": convert_delta(&self.keyless, &fmt_gas),
+            "deps": convert_entries(&self.dependencies, &fmt_gas),
+            "functions": self.functions.iter().map(|function| {
+                json!({
+                    "name": function.name,
+                    "base_calls": function.base_calls,
+                    "new_calls": function.new_calls,
+                    "execution": convert_delta(&function.execution, &fmt_gas),
+                    "io": convert_delta(&function.io, &fmt_gas),
+                    "instructions": convert_entries(&function.instructions, &fmt_gas),
+                })
+            }).collect::<Vec<_>>(),
+            "ops": convert_entries(&self.instructions, &fmt_gas),
+            "transaction-write": convert_delta(&self.transaction_write, &fmt_gas),
+            "event-writes": convert_entries(&self.event_writes, &fmt_gas),
+            "writes": convert_entries(&self.state_writes, &fmt_gas),
+            "storage": convert_delta(&self.storage_fee, &fmt_apt),
+            "storage-refund": convert_delta(&self.storage_refund, &fmt_apt),
+            "storage-txn": convert_delta(&self.storage_transaction, &fmt_apt),
+            "storage-writes": convert_entries(&self.storage_writes, &fmt_apt),
+            "storage-refunds": convert_entries(&self.storage_refunds, &fmt_apt),
+            "storage-events": convert_entries(&self.storage_events, &fmt_apt),
+            "trace": trace,
+        });
+
+        // Rendering the html doc
+        let mut handlebars = Handlebars::new();
+        handlebars.register_template_string("diff", TEMPLATE)?;
+        let html = handlebars.render("diff", &data)?;
+
+        // Writing to disk
+        let path_root = path.as_ref();
+        ensure_dirs_exist(path_root)?;
+        fs::write(path_root.join("index.html"), html)?;
+        fs::write(
+            path_root.join("diff.json"),
+            serde_json::to_string_pretty(self)?,
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(name: &str, execution: u64, children: Vec<FrameProfile>) -> FrameProfile {
+        FrameProfile {
+            name: name.to_string(),
+            execution,
+            io: 0,
+            instructions: vec![CostEntry {
+                name: "call".to_string(),
+                hits: 1,
+                cost: execution,
+            }],
+            children,
+        }
+    }
+
+    fn profile(call_graph: FrameProfile) -> GasProfile {
+        let (execution, io) = call_graph.inclusive_costs();
+        GasProfile {
+            gas_scaling_factor: 1_000_000,
+            execution_and_io: execution + io,
+            intrinsic: 0,
+            keyless: 0,
+            dependencies: vec![],
+            call_graph,
+            transaction_write: 0,
+            event_writes: vec![],
+            state_writes: vec![],
+            storage_fee: 0,
+            storage_refund: 0,
+            storage_transaction: 0,
+            storage_writes: vec![],
+            storage_refunds: vec![],
+            storage_events: vec![],
+        }
+    }
+
+    /// The name, change and inclusive execution costs of the frames, in pre-order.
+    fn flatten(diff: &FrameDiff) -> Vec<(String, FrameChange, u64, u64)> {
+        let mut frames = vec![];
+        diff.preorder_traversal(0, &mut |depth, frame| {
+            frames.push((
+                format!("{}{}", " ".repeat(depth), frame.name),
+                frame.change,
+                frame.execution.base,
+                frame.execution.new,
+            ))
+        });
+        frames
+    }
+
+    #[test]
+    fn align_names_finds_longest_common_subsequence() {
+        assert_eq!(align_names(&["a", "b", "c"], &["a", "b", "c"]), vec![
+            (0, 0),
+            (1, 1),
+            (2, 2)
+        ]);
+        assert_eq!(align_names(&["a", "c"], &["a", "b", "c"]), vec![
+            (0, 0),
+            (1, 2)
+        ]);
+        assert_eq!(align_names(&["a", "b", "c"], &["a", "c"]), vec![
+            (0, 0),
+            (2, 1)
+        ]);
+        assert_eq!(align_names(&["a", "b", "c"], &["d", "b", "e"]), vec![(
+            1, 1
+        )]);
+        assert!(align_names(&["a", "b"], &[]).is_empty());
+        assert_eq!(
+            align_names(&["x", "a", "b", "c"], &["a", "b", "c", "x"]),
+            vec![(1, 0), (2, 1), (3, 2)]
+        );
+    }
+
+    #[test]
+    fn align_names_by_position_beyond_limit() {
+        let base = vec!["a"; 2_001];
+        let mut new = vec!["a"; 2_001];
+        new[0] = "b";
+        let pairs = align_names(&base, &new);
+        assert_eq!(pairs.len(), 2_000);
+        assert_eq!(pairs[0], (1, 1));
+        assert!(pairs.iter().all(|(i, j)| i == j));
+    }
+
+    #[test]
+    fn align_children_marks_added_and_removed_frames() {
+        let base = [
+            frame("0x1::m::a", 1, vec![]),
+            frame("0x1::m::b", 2, vec![frame("0x1::m::c", 3, vec![])]),
+            frame("0x1::m::d", 4, vec![]),
+        ];
+        let new = [
+            frame("0x1::m::a", 1, vec![]),
+            frame("0x1::m::e", 5, vec![]),
+            frame("0x1::m::d", 6, vec![]),
+        ];
+        let children = FrameDiff::align_children(&base, &new);
+        let changes = children
+            .iter()
+            .map(|child| (child.name.as_str(), child.change))
+            .collect::<Vec<_>>();
+        assert_eq!(changes, vec![
+            ("0x1::m::a", FrameChange::Matched),
+            ("0x1::m::b", FrameChange::Removed),
+            ("0x1::m::e", FrameChange::Added),
+            ("0x1::m::d", FrameChange::Matched),
+        ]);
+        // Removed frames keep their callees, with costs including them.
+        assert_eq!(flatten(&children[1]), vec![
+            ("0x1::m::b".to_string(), FrameChange::Removed, 5, 0),
+            (" 0x1::m::c".to_string(), FrameChange::Removed, 3, 0),
+        ]);
+        assert_eq!(children[2].execution.base, 0);
+        assert_eq!(children[2].execution.new, 5);
+        assert_eq!(children[3].execution.delta, 2);
+    }
+
+    #[test]
+    fn diff_compares_call_graphs_and_functions() {
+        let base = profile(frame("0x1::m::main", 10, vec![
+            frame("0x1::m::f", 5, vec![]),
+            frame("0x1::m::f", 5, vec![]),
+            frame("0x1::m::g", 1, vec![]),
+        ]));
+        let new = profile(frame("0x1::m::main", 10, vec![
+            frame("0x1::m::f", 2, vec![]),
+            frame("0x1::m::h", 7, vec![]),
+        ]));
+        let diff = base.diff(&new).unwrap();
+
+        assert_eq!(diff.execution_and_io.base, 21);
+        assert_eq!(diff.execution_and_io.new, 19);
+        assert_eq!(diff.execution_and_io.delta, -2);
+        assert_eq!(flatten(&diff.call_graph), vec![
+            ("0x1::m::main".to_string(), FrameChange::Matched, 21, 19),
+            (" 0x1::m::f".to_string(), FrameChange::Matched, 5, 2),
+            (" 0x1::m::f".to_string(), FrameChange::Removed, 5, 0),
+            (" 0x1::m::g".to_string(), FrameChange::Removed, 1, 0),
+            (" 0x1::m::h".to_string(), FrameChange::Added, 0, 7),
+        ]);
+
+        // Functions are ordered by the absolute change of their own costs.
+        let functions = diff
+            .functions
+            .iter()
+            .map(|f| {
+                (
+                    f.name.as_str(),
+                    f.base_calls,
+                    f.new_calls,
+                    f.execution.delta,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(functions, vec![
+            ("0x1::m::f", 2, 1, -8),
+            ("0x1::m::h", 0, 1, 7),
+            ("0x1::m::g", 1, 0, -1),
+            ("0x1::m::main", 1, 1, 0),
+        ]);
+        assert_eq!(diff.instructions.len(), 1);
+        assert_eq!(diff.instructions[0].base_hits, 4);
+        assert_eq!(diff.instructions[0].new_hits, 3);
+        assert_eq!(diff.instructions[0].cost.delta, -2);
+    }
+
+    #[test]
+    fn diff_renames_different_entry_points() {
+        let base = profile(frame("0x1::m::old", 1, vec![]));
+        let new = profile(frame("0x1::m::new", 2, vec![]));
+        let diff = base.diff(&new).unwrap();
+        assert_eq!(diff.call_graph.name, "0x1::m::old -> 0x1::m::new");
+        assert_eq!(diff.call_graph.change, FrameChange::Matched);
+        assert_eq!(diff.call_graph.execution.delta, 1);
+    }
+
+    #[test]
+    fn diff_rejects_different_scaling_factors() {
+        let base = profile(frame("0x1::m::main", 1, vec![]));
+        let mut new = base.clone();
+        new.gas_scaling_factor = 1;
+        assert!(base.diff(&new).is_err());
+    }
+}
@@ -8,6 +8,7 @@ use crate::{
 use inferno::flamegraph::TextTruncateDirection;
 use move_core_types::gas_algebra::InternalGas;
 use regex::Captures;
+use std::collections::BTreeMap;
 
 #[derive(Debug)]
 struct LineBuffer(Vec<String>);
@@ -33,7 +34,7 @@ impl LineBuffer {
 impl StorageFees {
     /// Convert the storage fee log into folded stack lines, which can
     /// then be used to generate a flamegraph.
-    fn to_folded_stack_lines(&self) -> Vec<String> {
+    pub(crate) fn to_folded_stack_lines(&self) -> Vec<String> {
         let mut lines = LineBuffer::new();
 
         lines.push("transaction", self.txn_storage);
@@ -99,7 +100,7 @@ impl StorageFees {
 impl ExecutionAndIOCosts {
     /// Convert the execution gas log into folded stack lines, which can
     /// then be used to generate a flamegraph.
-    fn to_folded_stack_lines(&self) -> Vec<String> {
+    pub(crate) fn to_folded_stack_lines(&self) -> Vec<String> {
         let mut lines = LineBuffer::new();
 
         lines.push("intrinsic", self.intrinsic_cost);
@@ -247,3 +248,61 @@ impl ExecutionAndIOCosts {
         Ok(Some(graph_content.as_bytes().to_vec()))
     }
 }
+
+/// Adds folded stack lines (`<stack> <count>`) to the counts of the stacks, so that the lines of
+/// multiple transactions can be merged into a single flamegraph.
+pub(crate) fn merge_folded_stack_lines(stacks: &mut BTreeMap<String, u64>, lines: Vec<String>) {
+    for line in lines {
+        if let Some((stack, count)) = line.rsplit_once(' ') {
+            let count: u64 = count.parse().expect("should be able parse count as u64");
+            *stacks.entry(stack.to_string()).or_insert(0) += count;
+        }
+    }
+}
+
+/// Tries to generate a flamegraph from merged folded stacks, with the counts displayed by
+/// `fmt_count`.
+/// Unlike the flame charts of single transactions, identical stacks are merged and ordered
+/// alphabetically, rather than by time.
+/// None will be returned if there are no stacks.
+pub(crate) fn merged_flamegraph(
+    stacks: &BTreeMap<String, u64>,
+    title: String,
+    fmt_count: impl Fn(u64) -> String,
+) -> anyhow::Result<Option<Vec<u8>>> {
+    let lines = stacks
+        .iter()
+        .filter(|(_stack, count)| **count > 0)
+        .map(|(stack, count)| format!("{} {}", stack, count))
+        .collect::<Vec<_>>();
+
+    if lines.is_empty() {
+        return Ok(None);
+    }
+
+    let mut options = inferno::flamegraph::Options::default();
+    options.text_truncate_direction = TextTruncateDirection::Right;
+    options.color_diffusion = true;
+    options.title = title;
+
+    let mut graph_content = vec![];
+    inferno::flamegraph::from_lines(
+        &mut options,
+        lines.iter().map(|s| s.as_str()),
+        &mut graph_content,
+    )?;
+    let graph_content = String::from_utf8_lossy(&graph_content);
+
+    let re = regex::Regex::new("([1-9][0-9]*(,[0-9]+)*) samples")
+        .expect("should be able to build regex successfully");
+    let graph_content = re.replace_all(&graph_content, |caps: &Captures| {
+        let count: u64 = caps[1]
+            .replace(',', "")
+            .parse()
+            .expect("should be able parse count as u64");
+
+        fmt_count(count)
+    });
+
+    Ok(Some(graph_content.as_bytes().to_vec()))
+}
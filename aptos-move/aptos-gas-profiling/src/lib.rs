@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod aggregate;
+mod diff;
 mod erased;
 mod flamegraph;
 mod log;
@@ -10,5 +11,9 @@ mod profiler;
 mod render;
 mod report;
 
+pub use aggregate::{
+    AggregatedGasSummary, AggregatedTransactionGasLogs, CostDistribution, FunctionCostDistribution,
+};
+pub use diff::{GasProfile, GasProfileDiff, GAS_PROFILE_FILE_NAME};
 pub use log::{FrameName, TransactionGasLog};
 pub use profiler::GasProfiler;
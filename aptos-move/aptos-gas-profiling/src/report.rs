@@ -1,7 +1,7 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{log::TransactionGasLog, render::Render};
+use crate::{diff::GAS_PROFILE_FILE_NAME, log::TransactionGasLog, render::Render};
 use anyhow::Result;
 use aptos_gas_algebra::{Fee, InternalGas};
 use handlebars::Handlebars;
@@ -14,7 +14,7 @@ use std::{
 
 const TEMPLATE: &str = include_str!("../templates/index.html");
 
-fn ensure_dirs_exist(path: impl AsRef<Path>) -> Result<()> {
+pub(crate) fn ensure_dirs_exist(path: impl AsRef<Path>) -> Result<()> {
     if let Err(err) = fs::create_dir_all(&path) {
         match err.kind() {
             std::io::ErrorKind::AlreadyExists => (),
@@ -32,7 +32,11 @@ fn indent(output: &mut impl Write, count: usize) -> fmt::Result {
     write!(output, "{}", " ".repeat(count))
 }
 
-fn render_table<R, S>(output: &mut impl Write, table: &[R], spacing: usize) -> fmt::Result
+pub(crate) fn render_table<R, S>(
+    output: &mut impl Write,
+    table: &[R],
+    spacing: usize,
+) -> fmt::Result
 where
     R: AsRef<[S]>,
     S: AsRef<str>,
@@ -348,6 +352,10 @@ impl TransactionGasLog {
         }
         fs::write(path_root.join("index.html"), html)?;
 
+        // Saving the profile, so that it can be compared with other executions
+        self.to_profile()
+            .save(path_root.join(GAS_PROFILE_FILE_NAME))?;
+
         Ok(())
     }
 }
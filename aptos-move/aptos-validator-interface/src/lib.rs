@@ -81,6 +81,15 @@ pub trait AptosValidatorInterface: Sync {
         account: AccountAddress,
         seq: u64,
     ) -> Result<Option<Version>>;
+
+    /// Returns up to `limit` transactions sent by `account`, with their versions, in the order of
+    /// their sequence numbers starting at `start_seq`.
+    async fn get_account_ordered_transactions(
+        &self,
+        account: AccountAddress,
+        start_seq: u64,
+        limit: u64,
+    ) -> Result<Vec<(Version, Transaction)>>;
 }
 
 pub struct DebuggerStateView {
@@ -353,4 +353,24 @@ impl AptosValidatorInterface for RestDebuggerInterface {
                 .version,
         ))
     }
+
+    async fn get_account_ordered_transactions(
+        &self,
+        account: AccountAddress,
+        start_seq: u64,
+        limit: u64,
+    ) -> Result<Vec<(Version, Transaction)>> {
+        Ok(self
+            .0
+            .get_account_ordered_transactions_bcs(
+                account,
+                Some(start_seq),
+                Some(limit.min(u16::MAX as u64) as u16),
+            )
+            .await?
+            .into_inner()
+            .into_iter()
+            .map(|txn| (txn.version, txn.transaction))
+            .collect())
+    }
 }
@@ -112,4 +112,20 @@ impl AptosValidatorInterface for DBDebuggerInterface {
                 |tp| Ok(tp.map(|e| e.version)),
             )
     }
+
+    async fn get_account_ordered_transactions(
+        &self,
+        account: AccountAddress,
+        start_seq: u64,
+        limit: u64,
+    ) -> Result<Vec<(Version, Transaction)>> {
+        let ledger_version = self.get_latest_ledger_info_version().await?;
+        Ok(self
+            .0
+            .get_account_ordered_transactions(account, start_seq, limit, false, ledger_version)?
+            .into_inner()
+            .into_iter()
+            .map(|txn| (txn.version, txn.transaction))
+            .collect())
+    }
 }
@@ -19,6 +19,7 @@ pub mod chunked_publish;
 pub mod docgen;
 pub mod extended_checks;
 pub mod prover;
+pub mod prover_report;
 mod release_bundle;
 mod released_framework;
 
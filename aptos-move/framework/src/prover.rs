@@ -1,7 +1,10 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::build_model;
+use crate::{
+    build_model,
+    prover_report::{verification_target_hashes, ProverCache, ProverReport, ProverReportFormat},
+};
 use anyhow::bail;
 use codespan_reporting::{
     diagnostic::Severity,
@@ -15,10 +18,11 @@ use move_model::{
     model::{GlobalEnv, VerificationScope},
 };
 use move_prover::cli::Options;
+use sha2::{Digest, Sha256};
 use std::{
     collections::{BTreeMap, BTreeSet},
     fs,
-    path::Path,
+    path::{Path, PathBuf},
     time::Instant,
 };
 use tempfile::TempDir;
@@ -125,6 +129,26 @@ pub struct ProverOptions {
     #[clap(long = "skip-instance-check")]
     pub skip_instance_check: bool,
 
+    /// Writes a machine-readable report listing each verification condition with its status,
+    /// source location, duration and counterexample trace. Diagnostics are still printed.
+    #[clap(long, value_enum, conflicts_with = "benchmark")]
+    pub report_format: Option<ProverReportFormat>,
+
+    /// Where to write the report. Defaults to `prover_report.json` or `prover_report.sarif`
+    /// in the package directory.
+    #[clap(long, requires = "report_format")]
+    pub report_file: Option<PathBuf>,
+
+    /// Whether to skip functions which verified successfully before and whose code, specs,
+    /// callees and module specs are unchanged since. Results are cached per function hash.
+    #[clap(long, conflicts_with = "benchmark")]
+    pub incremental: bool,
+
+    /// The cache file for `--incremental`. Defaults to `build/prover_cache.json` in the
+    /// package directory.
+    #[clap(long, requires = "incremental")]
+    pub cache_file: Option<PathBuf>,
+
     #[clap(skip)]
     pub for_test: bool,
 }
@@ -149,6 +173,26 @@ impl ProverOptions {
         let now = Instant::now();
         let for_test = self.for_test;
         let benchmark = self.benchmark;
+        let report_format = self.report_format;
+        let report_file = self.report_file.clone();
+        let incremental = self.incremental;
+        let cache_file = self
+            .cache_file
+            .clone()
+            .unwrap_or_else(|| package_path.join("build").join("prover_cache.json"));
+        let track_targets = !benchmark && (report_format.is_some() || incremental);
+        let mut options = self.clone().convert_options(package_path)?;
+        let fingerprint = if track_targets {
+            self.fingerprint(
+                package_path,
+                &named_addresses,
+                compiler_version,
+                language_version,
+                &options,
+            )?
+        } else {
+            String::new()
+        };
         let mut experiments_vec = experiments.to_vec();
         // If `filter` is `some` then only the files filtered for are primary targets.
         // This interferes with the package visibility check in the function checker.
@@ -167,7 +211,6 @@ impl ProverOptions {
             known_attributes.clone(),
             experiments_vec,
         )?;
-        let mut options = self.convert_options(package_path)?;
         options.language_version = language_version;
         options.model_builder.language_version = language_version.unwrap_or_default();
         // Need to ensure a distinct output.bpl file for concurrent execution. In non-test
@@ -196,6 +239,43 @@ impl ProverOptions {
         if benchmark {
             // Special mode of benchmarking
             run_prover_benchmark(package_path, &mut model, options)?;
+        } else if track_targets {
+            let hashes =
+                verification_target_hashes(&model, &options.prover.verify_scope, &fingerprint);
+            let mut cache = incremental.then(|| ProverCache::load(&cache_file));
+            let cached = match &cache {
+                Some(cache) => cache.exclude_unchanged(&model, &hashes),
+                None => BTreeMap::new(),
+            };
+            options.backend.xml_report = true;
+            let xml_files = boogie_xml_files(&options);
+            let keep_artifacts = options.backend.keep_artifacts;
+            let mut writer = StandardStream::stderr(ColorChoice::Auto);
+            let result =
+                move_prover::run_move_prover_with_model_v2(&mut model, &mut writer, options, now);
+            let report = ProverReport::new(
+                &model,
+                &hashes,
+                &cached,
+                &xml_files,
+                result.is_ok(),
+                now.elapsed(),
+            );
+            if !keep_artifacts {
+                for file in &xml_files {
+                    fs::remove_file(file).unwrap_or_default();
+                }
+            }
+            if let Some(cache) = &mut cache {
+                cache.update(&report);
+                cache.save(&cache_file)?;
+            }
+            if let Some(format) = report_format {
+                let report_file =
+                    report_file.unwrap_or_else(|| package_path.join(format.default_file_name()));
+                report.write(format, &report_file)?;
+            }
+            result?;
         } else {
             let mut writer = StandardStream::stderr(ColorChoice::Auto);
             move_prover::run_move_prover_with_model_v2(&mut model, &mut writer, options, now)?;
@@ -277,6 +357,42 @@ impl ProverOptions {
         Ok(opts)
     }
 
+    /// Returns a hash of everything besides the package sources which affects verification
+    /// results, so that cached results are discarded when the configuration, the prover or
+    /// the tools it runs change.
+    fn fingerprint(
+        &self,
+        package_path: &Path,
+        named_addresses: &BTreeMap<String, AccountAddress>,
+        compiler_version: Option<CompilerVersion>,
+        language_version: Option<LanguageVersion>,
+        options: &Options,
+    ) -> anyhow::Result<String> {
+        let relevant_options = Self {
+            verbosity: None,
+            report_format: None,
+            report_file: None,
+            incremental: false,
+            cache_file: None,
+            for_test: false,
+            ..self.clone()
+        };
+        let prover_toml = fs::read_to_string(package_path.join("Prover.toml")).unwrap_or_default();
+        let mut hasher = Sha256::new();
+        hasher.update(serde_json::to_vec(&relevant_options)?);
+        hasher.update(prover_toml.as_bytes());
+        hasher.update(format!(
+            "{:?}{:?}{:?}",
+            named_addresses, compiler_version, language_version
+        ));
+        hasher.update(options.backend.tool_versions()?);
+        // The prover is built into this crate, so results are only reused by builds of the
+        // same version and commit.
+        hasher.update(env!("CARGO_PKG_VERSION"));
+        hasher.update(aptos_build_info::get_git_hash());
+        Ok(hex::encode(hasher.finalize()))
+    }
+
     pub fn default_for_test() -> Self {
         Self {
             for_test: true,
@@ -285,6 +401,26 @@ impl ProverOptions {
     }
 }
 
+/// Returns the XML report files Boogie writes for each shard.
+fn boogie_xml_files(options: &Options) -> Vec<PathBuf> {
+    let boogie_files = if options.backend.shards > 1 {
+        (0..options.backend.shards)
+            .map(|shard| {
+                Path::new(&options.output_path)
+                    .with_extension(format!("shard_{}.bpl", shard + 1))
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect()
+    } else {
+        vec![options.output_path.clone()]
+    };
+    boogie_files
+        .iter()
+        .map(|file| PathBuf::from(options.backend.get_boogie_xml_file(file)))
+        .collect()
+}
+
 fn run_prover_benchmark(
     package_path: &Path,
     env: &mut GlobalEnv,
@@ -0,0 +1,839 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Machine-readable prover reports and incremental caching of verification results.
+//!
+//! A verification condition (VC) is a function of a target module which the prover verifies.
+//! Outcomes and durations per VC are taken from the XML report Boogie writes for each
+//! procedure, counterexamples from the diagnostics the prover adds to the model.
+
+use anyhow::Context;
+use codespan_reporting::diagnostic::Severity;
+use log::{info, warn};
+use move_model::{
+    ast::{ConditionKind, PropertyValue, SpecBlockTarget, Value},
+    model::{
+        FunId, FunctionEnv, GlobalEnv, Loc, ModuleEnv, ModuleId, QualifiedId, VerificationScope,
+    },
+    pragmas::VERIFY_PRAGMA,
+};
+use move_prover_boogie_backend::boogie_helpers::boogie_function_name;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// Version of the cache file format. Caches written with a different version are discarded.
+const CACHE_VERSION: u32 = 2;
+
+/// The format of a machine-readable prover report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum, Serialize, Deserialize)]
+pub enum ProverReportFormat {
+    /// A JSON document listing each verification condition.
+    Json,
+    /// A SARIF 2.1.0 log, as consumed by code scanning tools.
+    Sarif,
+}
+
+impl ProverReportFormat {
+    /// The file name used for the report if no explicit path is given.
+    pub fn default_file_name(self) -> &'static str {
+        match self {
+            ProverReportFormat::Json => "prover_report.json",
+            ProverReportFormat::Sarif => "prover_report.sarif",
+        }
+    }
+}
+
+/// The outcome of a verification condition, ordered by severity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VcStatus {
+    Verified,
+    Inconclusive,
+    Timeout,
+    Failed,
+}
+
+impl VcStatus {
+    /// Maps the outcome of a procedure in Boogie's XML report to a status.
+    fn from_boogie_outcome(outcome: &str) -> Self {
+        let outcome = outcome.to_lowercase();
+        if outcome == "correct" {
+            VcStatus::Verified
+        } else if outcome.contains("error") {
+            VcStatus::Failed
+        } else if outcome.contains("time")
+            || outcome.contains("resource")
+            || outcome.contains("memory")
+        {
+            VcStatus::Timeout
+        } else {
+            VcStatus::Inconclusive
+        }
+    }
+}
+
+/// A source range, with 1-based lines and columns. The end column is exclusive.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceLocation {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+impl SourceLocation {
+    fn from_loc(env: &GlobalEnv, loc: &Loc) -> Option<Self> {
+        let (file, start) = env.get_file_and_location(loc)?;
+        if file.starts_with('<') {
+            // `<internal>` or `<unknown>`
+            return None;
+        }
+        let end = env.get_location(&loc.at_end())?;
+        Some(Self {
+            file,
+            line: start.line.0 as usize + 1,
+            column: start.column.0 as usize + 1,
+            end_line: end.line.0 as usize + 1,
+            end_column: end.column.0 as usize + 2,
+        })
+    }
+}
+
+/// An error reported by the prover, with its counterexample trace if there is one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VcError {
+    pub message: String,
+    pub location: Option<SourceLocation>,
+    pub trace: Vec<String>,
+}
+
+/// The result for a single verification condition.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VcResult {
+    /// The fully qualified name of the verified function, e.g. `0x1::coin::transfer`.
+    pub function: String,
+    pub status: VcStatus,
+    pub location: Option<SourceLocation>,
+    /// Solver time, summed over all Boogie procedures of the function.
+    pub duration_secs: Option<f64>,
+    /// Whether the result was taken from the incremental cache instead of being verified.
+    pub cached: bool,
+    /// The hash of everything the verification of this function depends on.
+    pub hash: String,
+    pub errors: Vec<VcError>,
+}
+
+/// A machine-readable report of a prover run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProverReport {
+    pub success: bool,
+    pub duration_secs: f64,
+    pub verification_conditions: Vec<VcResult>,
+    /// Errors which could not be attributed to a verification condition, e.g. failures of
+    /// global invariants outside of functions.
+    pub other_errors: Vec<VcError>,
+}
+
+impl ProverReport {
+    /// Builds a report from the state of the model after the prover has run.
+    pub fn new(
+        env: &GlobalEnv,
+        hashes: &BTreeMap<QualifiedId<FunId>, String>,
+        cached: &BTreeMap<QualifiedId<FunId>, Option<f64>>,
+        boogie_xml_files: &[PathBuf],
+        success: bool,
+        duration: Duration,
+    ) -> Self {
+        let boogie_names: BTreeMap<String, QualifiedId<FunId>> = hashes
+            .keys()
+            .map(|id| (boogie_function_name(&env.get_function(*id), &[]), *id))
+            .collect();
+        let mut outcomes: BTreeMap<QualifiedId<FunId>, (VcStatus, f64)> = BTreeMap::new();
+        let mut have_outcomes = false;
+        for file in boogie_xml_files {
+            let Ok(content) = fs::read_to_string(file) else {
+                continue;
+            };
+            have_outcomes = true;
+            for (procedure, status, duration) in parse_boogie_xml_report(&content) {
+                if let Some(id) = function_of_procedure(&boogie_names, &procedure) {
+                    let entry = outcomes.entry(id).or_insert((VcStatus::Verified, 0.0));
+                    entry.0 = entry.0.max(status);
+                    entry.1 += duration;
+                }
+            }
+        }
+
+        let mut errors: BTreeMap<QualifiedId<FunId>, Vec<VcError>> = BTreeMap::new();
+        let mut other_errors = vec![];
+        for (diag, loc) in env.get_diags_with_loc(Severity::Error) {
+            let error = VcError {
+                message: diag.message.clone(),
+                location: loc.as_ref().and_then(|l| SourceLocation::from_loc(env, l)),
+                trace: diag.notes.iter().map(|n| n.trim().to_string()).collect(),
+            };
+            match loc
+                .and_then(|l| env.get_enclosing_function(&l))
+                .map(|f| f.get_qualified_id())
+                .filter(|id| hashes.contains_key(id))
+            {
+                Some(id) => errors.entry(id).or_default().push(error),
+                None => other_errors.push(error),
+            }
+        }
+
+        let mut verification_conditions = vec![];
+        for (id, hash) in hashes {
+            let fun_env = env.get_function(*id);
+            let errors = errors.remove(id).unwrap_or_default();
+            let (status, duration_secs, is_cached) = if let Some(duration) = cached.get(id) {
+                (VcStatus::Verified, *duration, true)
+            } else if let Some((status, duration)) = outcomes.get(id) {
+                (*status, Some(*duration), false)
+            } else if !errors.is_empty() {
+                (VcStatus::Failed, None, false)
+            } else if have_outcomes {
+                // Not part of the verified shard, or not verified at all after the
+                // pipeline's own analysis.
+                continue;
+            } else {
+                // The prover stopped before running the solver.
+                (VcStatus::Inconclusive, None, false)
+            };
+            let status = if !errors.is_empty() && status == VcStatus::Verified {
+                VcStatus::Failed
+            } else {
+                status
+            };
+            verification_conditions.push(VcResult {
+                function: fun_env.get_full_name_with_address(),
+                status,
+                location: SourceLocation::from_loc(env, &fun_env.get_id_loc()),
+                duration_secs,
+                cached: is_cached,
+                hash: hash.clone(),
+                errors,
+            });
+        }
+
+        Self {
+            success,
+            duration_secs: duration.as_secs_f64(),
+            verification_conditions,
+            other_errors,
+        }
+    }
+
+    /// Writes the report in the given format.
+    pub fn write(&self, format: ProverReportFormat, path: &Path) -> anyhow::Result<()> {
+        let content = match format {
+            ProverReportFormat::Json => serde_json::to_string_pretty(self)?,
+            ProverReportFormat::Sarif => serde_json::to_string_pretty(&self.to_sarif())?,
+        };
+        fs::write(path, content)
+            .with_context(|| format!("failed to write prover report `{}`", path.display()))?;
+        info!("prover report written to `{}`", path.display());
+        Ok(())
+    }
+
+    /// Converts the report into a SARIF 2.1.0 log. Each verification condition becomes a result
+    /// of the `verification` rule, with one result per error for failed conditions.
+    pub fn to_sarif(&self) -> serde_json::Value {
+        let mut results = vec![];
+        for vc in &self.verification_conditions {
+            let (kind, level) = match vc.status {
+                VcStatus::Verified => ("pass", "none"),
+                VcStatus::Inconclusive => ("open", "warning"),
+                VcStatus::Timeout | VcStatus::Failed => ("fail", "error"),
+            };
+            let properties = json!({
+                "function": vc.function,
+                "status": vc.status,
+                "durationSecs": vc.duration_secs,
+                "cached": vc.cached,
+            });
+            if vc.errors.is_empty() {
+                let message = match vc.status {
+                    VcStatus::Verified => format!("`{}` verified", vc.function),
+                    VcStatus::Inconclusive => {
+                        format!("verification of `{}` inconclusive", vc.function)
+                    },
+                    VcStatus::Timeout => format!("verification of `{}` timed out", vc.function),
+                    VcStatus::Failed => format!("verification of `{}` failed", vc.function),
+                };
+                results.push(sarif_result(
+                    "verification",
+                    kind,
+                    level,
+                    &message,
+                    vc.location.as_ref(),
+                    &[],
+                    properties,
+                ));
+            } else {
+                for error in &vc.errors {
+                    results.push(sarif_result(
+                        "verification",
+                        kind,
+                        level,
+                        &error.message,
+                        error.location.as_ref().or(vc.location.as_ref()),
+                        &error.trace,
+                        properties.clone(),
+                    ));
+                }
+            }
+        }
+        for error in &self.other_errors {
+            results.push(sarif_result(
+                "prover-error",
+                "fail",
+                "error",
+                &error.message,
+                error.location.as_ref(),
+                &error.trace,
+                json!({}),
+            ));
+        }
+        json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": "move-prover",
+                        "rules": [
+                            {
+                                "id": "verification",
+                                "shortDescription": {
+                                    "text": "A function must satisfy its specification",
+                                },
+                            },
+                            {
+                                "id": "prover-error",
+                                "shortDescription": {
+                                    "text": "An error reported by the prover outside of a function",
+                                },
+                            },
+                        ],
+                    },
+                },
+                "results": results,
+            }],
+        })
+    }
+}
+
+fn sarif_result(
+    rule_id: &str,
+    kind: &str,
+    level: &str,
+    message: &str,
+    location: Option<&SourceLocation>,
+    trace: &[String],
+    properties: serde_json::Value,
+) -> serde_json::Value {
+    let mut result = json!({
+        "ruleId": rule_id,
+        "kind": kind,
+        "level": level,
+        "message": { "text": message },
+        "properties": properties,
+    });
+    if let Some(location) = location {
+        result["locations"] = json!([{
+            "physicalLocation": {
+                "artifactLocation": { "uri": location.file },
+                "region": {
+                    "startLine": location.line,
+                    "startColumn": location.column,
+                    "endLine": location.end_line,
+                    "endColumn": location.end_column,
+                },
+            },
+        }]);
+    }
+    if !trace.is_empty() {
+        let locations: Vec<_> = trace
+            .iter()
+            .map(|line| json!({ "location": { "message": { "text": line } } }))
+            .collect();
+        result["codeFlows"] = json!([{ "threadFlows": [{ "locations": locations }] }]);
+    }
+    result
+}
+
+/// Returns the functions which the prover verifies for the given scope.
+fn verification_targets<'env>(
+    env: &'env GlobalEnv,
+    scope: &VerificationScope,
+) -> Vec<FunctionEnv<'env>> {
+    env.get_primary_target_modules()
+        .into_iter()
+        .flat_map(|m| m.into_functions())
+        .filter(|f| {
+            !f.is_test_only()
+                && !f.is_intrinsic()
+                && !f.is_native()
+                && !f.is_inline()
+                && f.should_verify(scope)
+        })
+        .collect()
+}
+
+/// Computes a hash for each verification target. The hash covers the `fingerprint` of the
+/// prover configuration and tools, and the sources of everything the verification of the
+/// function depends on, as collected by `dependencies`.
+pub fn verification_target_hashes(
+    env: &GlobalEnv,
+    scope: &VerificationScope,
+    fingerprint: &str,
+) -> BTreeMap<QualifiedId<FunId>, String> {
+    verification_targets(env, scope)
+        .into_iter()
+        .map(|f| (f.get_qualified_id(), function_hash(env, &f, fingerprint)))
+        .collect()
+}
+
+fn function_hash(env: &GlobalEnv, fun_env: &FunctionEnv, fingerprint: &str) -> String {
+    let (functions, modules) = dependencies(env, fun_env);
+    let mut hasher = Sha256::new();
+    hasher.update(fingerprint.as_bytes());
+    for id in &functions {
+        let fun_env = env.get_function(*id);
+        hasher.update(fun_env.get_full_name_with_address().as_bytes());
+        hash_source(&mut hasher, env, &fun_env.get_loc());
+    }
+    for module_id in &modules {
+        let module_env = env.get_module(*module_id);
+        hasher.update(module_env.get_full_name_str().as_bytes());
+        for struct_env in module_env.get_structs() {
+            hash_source(&mut hasher, env, &struct_env.get_loc());
+        }
+        for constant_env in module_env.get_named_constants() {
+            hash_source(&mut hasher, env, &constant_env.get_loc());
+        }
+        for info in module_env.get_spec_block_infos() {
+            let relevant = match &info.target {
+                SpecBlockTarget::Function(mid, fid)
+                | SpecBlockTarget::FunctionCode(mid, fid, _) => {
+                    functions.contains(&mid.qualified(*fid))
+                },
+                // Inline specs are part of the function source.
+                SpecBlockTarget::Inline => false,
+                SpecBlockTarget::Module(_)
+                | SpecBlockTarget::Struct(..)
+                | SpecBlockTarget::Schema(..)
+                | SpecBlockTarget::SpecFunction(..) => true,
+            };
+            if relevant {
+                hash_source(&mut hasher, env, &info.loc);
+            }
+        }
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Returns the functions and modules the verification of `fun_env` depends on.
+///
+/// The functions are the ones it transitively calls or uses, in code or in specs, since their
+/// code or specs are part of the verification condition. The modules are the ones declaring
+/// these functions, the ones they use for types, constants and spec functions, and the ones
+/// declaring global invariants over their memory, since these invariants are checked wherever
+/// the memory is modified. Modules declaring axioms are always included, since axioms hold
+/// everywhere.
+fn dependencies(
+    env: &GlobalEnv,
+    fun_env: &FunctionEnv,
+) -> (BTreeSet<QualifiedId<FunId>>, BTreeSet<ModuleId>) {
+    let mut functions = BTreeSet::new();
+    let mut modules = BTreeSet::new();
+    let mut function_todo = VecDeque::from([fun_env.get_qualified_id()]);
+    let mut module_todo = VecDeque::new();
+    for module_env in env.get_modules() {
+        if has_condition(&module_env, |kind| matches!(kind, ConditionKind::Axiom(..))) {
+            module_todo.push_back(module_env.get_id());
+        }
+    }
+    loop {
+        if let Some(id) = function_todo.pop_front() {
+            if !functions.insert(id) {
+                continue;
+            }
+            module_todo.push_back(id.module_id);
+            let fun_env = env.get_function(id);
+            function_todo.extend(fun_env.get_called_functions().into_iter().flatten());
+            function_todo.extend(fun_env.get_used_functions().into_iter().flatten());
+            function_todo.extend(fun_env.get_spec().used_funs_with_uses().into_keys());
+        } else if let Some(id) = module_todo.pop_front() {
+            if !modules.insert(id) {
+                continue;
+            }
+            let module_env = env.get_module(id);
+            module_todo.extend(module_env.get_used_modules(true));
+            function_todo.extend(module_env.get_spec().used_funs_with_uses().into_keys());
+        } else {
+            // Only look for invariants once all other dependencies are known.
+            let invariant_modules: Vec<_> = env
+                .get_modules()
+                .filter(|module_env| {
+                    !modules.contains(&module_env.get_id())
+                        && has_condition(module_env, ConditionKind::allowed_on_module)
+                        && !module_env.get_used_modules(true).is_disjoint(&modules)
+                })
+                .map(|module_env| module_env.get_id())
+                .collect();
+            if invariant_modules.is_empty() {
+                break;
+            }
+            module_todo.extend(invariant_modules);
+        }
+    }
+    (functions, modules)
+}
+
+/// Returns whether the module-level spec of `module_env` has a condition of a matching kind.
+fn has_condition(module_env: &ModuleEnv, pred: impl Fn(&ConditionKind) -> bool) -> bool {
+    module_env
+        .get_spec()
+        .conditions
+        .iter()
+        .any(|cond| pred(&cond.kind))
+}
+
+fn hash_source(hasher: &mut Sha256, env: &GlobalEnv, loc: &Loc) {
+    hasher.update(env.get_source(loc).unwrap_or_default().as_bytes());
+    hasher.update([0u8]);
+}
+
+/// Parses the XML report written by Boogie's `-xml` flag into the name, status and duration
+/// in seconds of each procedure.
+fn parse_boogie_xml_report(content: &str) -> Vec<(String, VcStatus, f64)> {
+    content
+        .split("<method ")
+        .skip(1)
+        .filter_map(|method| {
+            let open_tag = &method[..method.find('>')?];
+            let name = xml_attribute(open_tag, "name")?;
+            let conclusion = &method[method.find("<conclusion")?..];
+            let conclusion = &conclusion[..conclusion.find('>')?];
+            let outcome = xml_attribute(conclusion, "outcome")?;
+            let duration = xml_attribute(conclusion, "duration")
+                .and_then(|d| d.parse::<f64>().ok())
+                .unwrap_or_default();
+            Some((name, VcStatus::from_boogie_outcome(&outcome), duration))
+        })
+        .collect()
+}
+
+fn xml_attribute(tag: &str, name: &str) -> Option<String> {
+    let start = tag.find(&format!(" {}=\"", name))? + name.len() + 3;
+    let end = start + tag[start..].find('"')?;
+    Some(
+        tag[start..end]
+            .replace("&apos;", "'")
+            .replace("&quot;", "\"")
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&amp;", "&"),
+    )
+}
+
+/// Maps a Boogie procedure such as `$1_coin_transfer'u64'$verify` back to the function it
+/// verifies. Procedures other than verification procedures are ignored.
+fn function_of_procedure(
+    boogie_names: &BTreeMap<String, QualifiedId<FunId>>,
+    procedure: &str,
+) -> Option<QualifiedId<FunId>> {
+    let base = &procedure[..procedure.find("$verify")?];
+    if let Some(id) = boogie_names.get(base) {
+        return Some(*id);
+    }
+    // Instantiations of generic functions carry a type suffix.
+    boogie_names
+        .iter()
+        .filter(|(name, _)| base.starts_with(&format!("{}'", name)))
+        .max_by_key(|(name, _)| name.len())
+        .map(|(_, id)| *id)
+}
+
+/// A cache of functions which were verified successfully, keyed by their hash.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProverCache {
+    version: u32,
+    /// Maps fully qualified function names to the hash they were verified with.
+    entries: BTreeMap<String, CacheEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    hash: String,
+    duration_secs: Option<f64>,
+}
+
+impl ProverCache {
+    /// Loads the cache from `path`. A missing, unreadable or outdated cache yields an empty one.
+    pub fn load(path: &Path) -> Self {
+        let empty = Self {
+            version: CACHE_VERSION,
+            entries: BTreeMap::new(),
+        };
+        let Ok(content) = fs::read_to_string(path) else {
+            return empty;
+        };
+        match serde_json::from_str::<Self>(&content) {
+            Ok(cache) if cache.version == CACHE_VERSION => cache,
+            Ok(_) => {
+                info!(
+                    "discarding prover cache `{}` of older version",
+                    path.display()
+                );
+                empty
+            },
+            Err(e) => {
+                warn!(
+                    "discarding invalid prover cache `{}`: {}",
+                    path.display(),
+                    e
+                );
+                empty
+            },
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("failed to write prover cache `{}`", path.display()))
+    }
+
+    /// Excludes all functions whose hash matches a cached successful verification from
+    /// verification, by setting `pragma verify = false` on them. Returns the excluded functions
+    /// with their cached durations.
+    pub fn exclude_unchanged(
+        &self,
+        env: &GlobalEnv,
+        hashes: &BTreeMap<QualifiedId<FunId>, String>,
+    ) -> BTreeMap<QualifiedId<FunId>, Option<f64>> {
+        let verify = env.symbol_pool().make(VERIFY_PRAGMA);
+        let mut excluded = BTreeMap::new();
+        for (id, hash) in hashes {
+            let fun_env = env.get_function(*id);
+            match self.entries.get(&fun_env.get_full_name_with_address()) {
+                Some(entry) if &entry.hash == hash => {
+                    fun_env
+                        .get_mut_spec()
+                        .properties
+                        .insert(verify, PropertyValue::Value(Value::Bool(false)));
+                    excluded.insert(*id, entry.duration_secs);
+                },
+                _ => {},
+            }
+        }
+        info!(
+            "skipping {} of {} functions unchanged since their last verification",
+            excluded.len(),
+            hashes.len()
+        );
+        excluded
+    }
+
+    /// Records the functions `report` verified, and forgets the ones it failed to verify.
+    /// Entries of functions which are not in the report, e.g. because they were filtered out
+    /// or verified by another shard, are kept.
+    pub fn update(&mut self, report: &ProverReport) {
+        for vc in &report.verification_conditions {
+            if vc.status == VcStatus::Verified {
+                self.entries.insert(vc.function.clone(), CacheEntry {
+                    hash: vc.hash.clone(),
+                    duration_secs: vc.duration_secs,
+                });
+            } else {
+                self.entries.remove(&vc.function);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::build_model;
+    use tempfile::tempdir;
+
+    const MOVE_TOML: &str = r#"
+[package]
+name = "Cached"
+version = "0.0.0"
+
+[addresses]
+cached = "0x42"
+"#;
+
+    const COUNTER: &str = r#"module cached::counter {
+    struct Counter has key { value: u64 }
+
+    public fun increment(addr: address) acquires Counter {
+        let counter = borrow_global_mut<Counter>(addr);
+        counter.value = counter.value + 1;
+    }
+    spec increment {
+        aborts_if !exists<Counter>(addr);
+        aborts_if global<Counter>(addr).value + 1 > MAX_U64;
+    }
+}
+"#;
+
+    const OTHER: &str = r#"module cached::other {
+    public fun double(x: u64): u64 {
+        x * 2
+    }
+}
+"#;
+
+    /// Builds a package of the given modules, and returns the hashes of its functions by name.
+    fn hashes(modules: &[&str], fingerprint: &str) -> BTreeMap<String, String> {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("Move.toml"), MOVE_TOML).unwrap();
+        fs::create_dir(dir.path().join("sources")).unwrap();
+        for (i, module) in modules.iter().enumerate() {
+            fs::write(dir.path().join(format!("sources/m{}.move", i)), module).unwrap();
+        }
+        let env = build_model(
+            true,
+            dir.path(),
+            BTreeMap::new(),
+            None,
+            None,
+            None,
+            None,
+            false,
+            BTreeSet::new(),
+            vec![],
+        )
+        .unwrap();
+        verification_target_hashes(&env, &VerificationScope::All, fingerprint)
+            .into_iter()
+            .map(|(id, hash)| (env.get_function(id).get_full_name_with_address(), hash))
+            .collect()
+    }
+
+    const INCREMENT: &str = "0x42::counter::increment";
+    const DOUBLE: &str = "0x42::other::double";
+
+    #[test]
+    fn hash_is_stable() {
+        assert_eq!(
+            hashes(&[COUNTER, OTHER], "f"),
+            hashes(&[COUNTER, OTHER], "f")
+        );
+    }
+
+    #[test]
+    fn fingerprint_invalidates_all_functions() {
+        let before = hashes(&[COUNTER, OTHER], "f");
+        let after = hashes(&[COUNTER, OTHER], "g");
+        assert_ne!(before[INCREMENT], after[INCREMENT]);
+        assert_ne!(before[DOUBLE], after[DOUBLE]);
+    }
+
+    #[test]
+    fn unrelated_change_keeps_hash() {
+        let before = hashes(&[COUNTER, OTHER], "f");
+        let after = hashes(&[COUNTER, &OTHER.replace("x * 2", "x + x")], "f");
+        assert_eq!(before[INCREMENT], after[INCREMENT]);
+        assert_ne!(before[DOUBLE], after[DOUBLE]);
+    }
+
+    #[test]
+    fn struct_change_invalidates_users() {
+        let before = hashes(&[COUNTER, OTHER], "f");
+        let changed = COUNTER.replace("{ value: u64 }", "{ value: u64, limit: u64 }");
+        let after = hashes(&[&changed, OTHER], "f");
+        assert_ne!(before[INCREMENT], after[INCREMENT]);
+        assert_eq!(before[DOUBLE], after[DOUBLE]);
+    }
+
+    #[test]
+    fn invariant_in_other_module_invalidates_users() {
+        // A global invariant over the counter, declared outside of the counter module, is
+        // checked by the verification of `increment`.
+        let invariant = r#"module cached::bounded {
+    use cached::counter;
+
+    spec module {
+        invariant forall addr: address where exists<counter::Counter>(addr):
+            global<counter::Counter>(addr).value < 10;
+    }
+}
+"#;
+        let before = hashes(&[COUNTER, OTHER], "f");
+        let after = hashes(&[COUNTER, OTHER, invariant], "f");
+        assert_ne!(before[INCREMENT], after[INCREMENT]);
+        assert_eq!(before[DOUBLE], after[DOUBLE]);
+
+        let weaker = invariant.replace("< 10", "< 20");
+        let changed = hashes(&[COUNTER, OTHER, &weaker], "f");
+        assert_ne!(after[INCREMENT], changed[INCREMENT]);
+    }
+
+    #[test]
+    fn callee_change_invalidates_callers() {
+        let caller = r#"module cached::caller {
+    use cached::other;
+
+    public fun quadruple(x: u64): u64 {
+        other::double(other::double(x))
+    }
+}
+"#;
+        let before = hashes(&[COUNTER, OTHER, caller], "f");
+        let after = hashes(&[COUNTER, &OTHER.replace("x * 2", "x + x"), caller], "f");
+        let quadruple = "0x42::caller::quadruple";
+        assert_ne!(before[quadruple], after[quadruple]);
+        assert_eq!(before[INCREMENT], after[INCREMENT]);
+    }
+
+    #[test]
+    fn parses_boogie_xml_report() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<boogie version="3.0.1" commandLine="boogie output.bpl">
+  <fileFragment file="output.bpl">
+    <method name="$1_coin_transfer$verify" startTime="2024-01-01T00:00:00">
+      <conclusion endTime="2024-01-01T00:00:01" duration="1.25" outcome="correct" />
+    </method>
+    <method name="$1_coin_burn&apos;u64&apos;$verify" startTime="2024-01-01T00:00:00">
+      <error message="assert_failed" />
+      <conclusion endTime="2024-01-01T00:00:02" duration="2" outcome="errors" />
+    </method>
+    <method name="$1_coin_mint$verify" startTime="2024-01-01T00:00:00">
+      <conclusion endTime="2024-01-01T00:00:40" duration="40" outcome="timed out" />
+    </method>
+  </fileFragment>
+</boogie>"#;
+        let result = parse_boogie_xml_report(xml);
+        assert_eq!(result, vec![
+            (
+                "$1_coin_transfer$verify".to_string(),
+                VcStatus::Verified,
+                1.25
+            ),
+            (
+                "$1_coin_burn'u64'$verify".to_string(),
+                VcStatus::Failed,
+                2.0
+            ),
+            ("$1_coin_mint$verify".to_string(), VcStatus::Timeout, 40.0),
+        ]);
+    }
+}
@@ -0,0 +1,167 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{anyhow, bail, Result};
+use aptos_backup_cli::{
+    coordinators::restore::{RestoreCoordinator, RestoreCoordinatorOpt},
+    metadata::cache::MetadataCacheOpt,
+    storage::{
+        command_adapter::{config::CommandAdapterConfig, CommandAdapter},
+        local_fs::LocalFs,
+        BackupStorage,
+    },
+    utils::{GlobalRestoreOptions, RestoreRunMode},
+};
+use aptos_config::config::{BackupStorageConfig, NodeConfig, NO_OP_STORAGE_PRUNER_CONFIG};
+use aptos_db::{db::AptosDB, get_restore_handler::GetRestoreHandler};
+use aptos_logger::info;
+use aptos_types::{transaction::Version, waypoint::Waypoint};
+use std::{collections::HashMap, sync::Arc, time::Instant};
+
+/// Restores the storage from the configured backups if the node is bootstrapping
+/// from backups (i.e., `BootstrappingMode::RestoreFromBackup`). The epoch ending
+/// ledger infos are verified against the waypoints (and the signatures of the
+/// previous epochs), and the state snapshot and transactions are verified against
+/// the ledger infos. Once the restore is complete, the state sync bootstrapper
+/// syncs the remaining data from peers (starting at the restored version).
+///
+/// Note: this must be called before the storage is opened by the node. The
+/// restore is skipped if the storage already contains data (unless a previous
+/// restore of a state snapshot was interrupted, in which case it is resumed).
+pub(crate) fn restore_from_backup_if_required(node_config: &NodeConfig) -> Result<()> {
+    // Check if the node is bootstrapping from backups
+    let bootstrapping_mode = node_config.state_sync.state_sync_driver.bootstrapping_mode;
+    if !bootstrapping_mode.is_restore_from_backup() {
+        return Ok(());
+    }
+
+    // Get the backup storage config
+    let backup_restore_config = &node_config.storage.backup_restore;
+    let backup_storage_config = backup_restore_config
+        .backup_storage
+        .clone()
+        .ok_or_else(|| anyhow!("No backup storage was specified to restore from!"))?;
+
+    // Get the trusted waypoints (before touching the storage)
+    let trusted_waypoints = get_trusted_waypoints(node_config)?;
+
+    // Open the storage for restoring
+    let storage_config = &node_config.storage;
+    let aptos_db = AptosDB::open_kv_only(
+        storage_config.get_dir_paths(),
+        false,                       /* read_only */
+        NO_OP_STORAGE_PRUNER_CONFIG, /* pruner config */
+        storage_config.rocksdb_configs,
+        false, /* indexer */
+        storage_config.buffered_state_target_items,
+        storage_config.max_num_nodes_per_lru_cache_shard,
+        None, /* internal indexer db */
+    )
+    .map_err(|error| anyhow!("Failed to open the storage for restoring: {:?}", error))?;
+    let restore_handler = Arc::new(aptos_db).get_restore_handler();
+
+    // Only restore if the storage is empty (or a previous restore was interrupted)
+    let next_expected_version = restore_handler.get_next_expected_transaction_version()?;
+    let in_progress_snapshot_version =
+        restore_handler.get_in_progress_state_kv_snapshot_version()?;
+    if !is_restore_required(next_expected_version, in_progress_snapshot_version) {
+        info!(
+            "The storage is not empty (next expected version: {}). Skipping the restore from backups!",
+            next_expected_version
+        );
+        return Ok(());
+    }
+
+    // Create the restore options
+    let global_restore_options = GlobalRestoreOptions {
+        target_version: Version::MAX, // Restore everything in the backups
+        trusted_waypoints: Arc::new(trusted_waypoints),
+        run_mode: Arc::new(RestoreRunMode::Restore { restore_handler }),
+        concurrent_downloads: backup_restore_config.concurrent_downloads,
+        replay_concurrency_level: backup_restore_config.replay_concurrency_level,
+    };
+    let restore_coordinator_opt = RestoreCoordinatorOpt {
+        metadata_cache_opt: MetadataCacheOpt::new(backup_restore_config.metadata_cache_dir.clone()),
+        replay_all: false,
+        ledger_history_start_version: None,
+        skip_epoch_endings: false,
+    };
+
+    // Restore the storage (on a dedicated runtime)
+    info!(
+        "Restoring the storage from backups: {:?}",
+        backup_storage_config
+    );
+    let start_time = Instant::now();
+    let runtime = aptos_runtimes::spawn_named_runtime("backup-restore".into(), None);
+    runtime
+        .block_on(async move {
+            let backup_storage = create_backup_storage(backup_storage_config).await?;
+            RestoreCoordinator::new(
+                restore_coordinator_opt,
+                global_restore_options,
+                backup_storage,
+            )
+            .run()
+            .await
+        })
+        .map_err(|error| anyhow!("Failed to restore the storage from backups: {:?}", error))?;
+
+    info!(
+        "Restored the storage from backups in {:?}!",
+        start_time.elapsed()
+    );
+    Ok(())
+}
+
+/// Returns true iff the storage should be restored from backups, i.e., the
+/// storage is empty, or a previous restore of a state snapshot was interrupted.
+pub(crate) fn is_restore_required(
+    next_expected_version: Version,
+    in_progress_snapshot_version: Option<Version>,
+) -> bool {
+    next_expected_version == 0 || in_progress_snapshot_version.is_some()
+}
+
+/// Creates the backup storage to restore from
+async fn create_backup_storage(
+    backup_storage_config: BackupStorageConfig,
+) -> Result<Arc<dyn BackupStorage>> {
+    let backup_storage: Arc<dyn BackupStorage> = match backup_storage_config {
+        BackupStorageConfig::LocalFs(dir) => Arc::new(LocalFs::new(dir)),
+        BackupStorageConfig::CommandAdapter(config_path) => {
+            let config = CommandAdapterConfig::load_from_file(&config_path).await?;
+            Arc::new(CommandAdapter::new(config))
+        },
+    };
+    Ok(backup_storage)
+}
+
+/// Returns the waypoints against which to verify the epoch ending ledger
+/// infos in the backups (i.e., the genesis waypoint and the node waypoint).
+pub(crate) fn get_trusted_waypoints(
+    node_config: &NodeConfig,
+) -> Result<HashMap<Version, Waypoint>> {
+    let node_waypoint = node_config.base.waypoint.waypoint();
+    let genesis_waypoint = node_config
+        .execution
+        .genesis_waypoint
+        .as_ref()
+        .unwrap_or(&node_config.base.waypoint)
+        .genesis_waypoint();
+
+    let mut trusted_waypoints = HashMap::new();
+    for waypoint in [genesis_waypoint, node_waypoint] {
+        if let Some(existing_waypoint) = trusted_waypoints.insert(waypoint.version(), waypoint) {
+            if existing_waypoint != waypoint {
+                bail!(
+                    "Found conflicting waypoints at version {}: {:?} and {:?}",
+                    waypoint.version(),
+                    existing_waypoint,
+                    waypoint
+                );
+            }
+        }
+    }
+    Ok(trusted_waypoints)
+}
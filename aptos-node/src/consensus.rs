@@ -29,7 +29,7 @@ use aptos_event_notifications::{
 use aptos_jwk_consensus::{start_jwk_consensus_runtime, types::JWKConsensusMsg};
 use aptos_mempool::QuorumStoreRequest;
 use aptos_network::application::interface::{NetworkClient, NetworkServiceEvents};
-use aptos_storage_interface::DbReaderWriter;
+use aptos_storage_interface::{DbReader, DbReaderWriter};
 use aptos_validator_transaction_pool::VTxnPoolState;
 use futures::channel::mpsc::Sender;
 use std::sync::Arc;
@@ -48,17 +48,19 @@ pub fn create_consensus_runtime(
     admin_service: &mut AdminService,
 ) -> Option<Runtime> {
     consensus_network_interfaces.map(|consensus_network_interfaces| {
-        let (consensus_runtime, consensus_db, quorum_store_db) = services::start_consensus_runtime(
-            node_config,
-            db_rw.clone(),
-            consensus_reconfig_subscription,
-            consensus_network_interfaces,
-            consensus_notifier.clone(),
-            consensus_to_mempool_sender.clone(),
-            vtxn_pool,
-            consensus_publisher.clone(),
-        );
+        let (consensus_runtime, consensus_db, quorum_store_db, consensus_state_inspector) =
+            services::start_consensus_runtime(
+                node_config,
+                db_rw.clone(),
+                consensus_reconfig_subscription,
+                consensus_network_interfaces,
+                consensus_notifier.clone(),
+                consensus_to_mempool_sender.clone(),
+                vtxn_pool,
+                consensus_publisher.clone(),
+            );
         admin_service.set_consensus_dbs(consensus_db, quorum_store_db);
+        admin_service.set_consensus_state_inspector(consensus_state_inspector);
 
         consensus_runtime
     })
@@ -182,6 +184,7 @@ pub fn create_consensus_observer_and_publisher(
         node_config,
         consensus_observer_client.clone(),
         consensus_publisher_message_receiver,
+        db_rw.reader.clone(),
     );
 
     // Create the consensus observer (if enabled)
@@ -244,6 +247,7 @@ fn create_consensus_publisher(
         ConsensusObserverClient<NetworkClient<ConsensusObserverMessage>>,
     >,
     publisher_message_receiver: Receiver<(), ConsensusPublisherNetworkMessage>,
+    db_reader: Arc<dyn DbReader>,
 ) -> (Option<Runtime>, Option<Arc<ConsensusPublisher>>) {
     // If the publisher is not enabled, return early
     if !node_config.consensus_observer.publisher_enabled {
@@ -253,9 +257,10 @@ fn create_consensus_publisher(
     // Create the publisher runtime
     let runtime = aptos_runtimes::spawn_named_runtime("publisher".into(), None);
 
-    // Create the consensus publisher
+    // Create the consensus publisher (with a db reader to serve epoch change proofs)
     let (consensus_publisher, outbound_message_receiver) =
         ConsensusPublisher::new(node_config.consensus_observer, consensus_observer_client);
+    let consensus_publisher = consensus_publisher.with_db_reader(db_reader);
 
     // Start the consensus publisher
     runtime.spawn(
@@ -5,6 +5,7 @@
 #![allow(unexpected_cfgs)]
 #![forbid(unsafe_code)]
 
+mod backup_restorer;
 mod consensus;
 mod indexer;
 mod logger;
@@ -728,6 +729,9 @@ pub fn setup_environment_and_start_node(
         jwk_consensus_subscriptions,
     ) = state_sync::create_event_subscription_service(&node_config, &db_rw);
 
+    // Register the consensus compression dictionaries (before any messages are sent)
+    network::register_consensus_compression_dictionaries(&node_config)?;
+
     // Set up the networks and gather the application network handles
     let peers_and_metadata = network::create_peers_and_metadata(&node_config);
     let (
@@ -746,6 +750,9 @@ pub fn setup_environment_and_start_node(
         &mut event_subscription_service,
     );
 
+    // Set the peer ban list in order to enable the network ban API in the admin service
+    admin_service.set_peer_ban_list(peers_and_metadata.get_peer_ban_list());
+
     // Start the peer monitoring service
     let peer_monitoring_service_runtime = services::start_peer_monitoring_service(
         &node_config,
@@ -3,6 +3,11 @@
 
 use crate::services::start_netbench_service;
 use aptos_channels::{self, aptos_channel, message_queues::QueueStyle};
+use aptos_compression::{
+    client::CompressionClient,
+    dictionary::{register_decompression_dictionary, register_dictionary, ZstdDictionary},
+    DEFAULT_ZSTD_COMPRESSION_LEVEL,
+};
 use aptos_config::{
     config::{NetworkConfig, NodeConfig},
     network_id::NetworkId,
@@ -18,6 +23,7 @@ use aptos_logger::debug;
 use aptos_mempool::network::MempoolSyncMsg;
 use aptos_network::{
     application::{
+        ban_list::{PeerBanList, PEER_BAN_LIST_FILE_NAME},
         interface::{NetworkClient, NetworkServiceEvents},
         storage::PeersAndMetadata,
     },
@@ -238,7 +244,34 @@ fn extract_network_ids(node_config: &NodeConfig) -> Vec<NetworkId> {
 /// Creates the global peers and metadata struct
 pub fn create_peers_and_metadata(node_config: &NodeConfig) -> Arc<PeersAndMetadata> {
     let network_ids = extract_network_ids(node_config);
-    PeersAndMetadata::new(&network_ids)
+
+    // Create the peer ban list (persisted in the node's data directory)
+    let peer_ban_list_path = node_config.storage.dir().join(PEER_BAN_LIST_FILE_NAME);
+    let peer_ban_list = PeerBanList::new_with_persistence(peer_ban_list_path, TimeService::real());
+
+    PeersAndMetadata::new_with_peer_ban_list(&network_ids, Arc::new(peer_ban_list))
+}
+
+/// Registers the zstd dictionaries used to compress and decompress consensus messages.
+/// This must happen before the networks are started, as the dictionaries are advertised
+/// to peers in the handshake (peers without our compression dictionary fall back to lz4).
+/// Decompression-only dictionaries allow all peers to be upgraded to understand a new
+/// dictionary before any of them start using it.
+pub fn register_consensus_compression_dictionaries(node_config: &NodeConfig) -> anyhow::Result<()> {
+    let consensus_config = &node_config.consensus;
+    for dictionary_path in &consensus_config.zstd_decompression_dictionaries {
+        let dictionary =
+            ZstdDictionary::load_from_file(dictionary_path, DEFAULT_ZSTD_COMPRESSION_LEVEL)?;
+        register_decompression_dictionary(dictionary);
+    }
+
+    if let Some(dictionary_path) = &consensus_config.zstd_compression_dictionary {
+        let dictionary =
+            ZstdDictionary::load_from_file(dictionary_path, DEFAULT_ZSTD_COMPRESSION_LEVEL)?;
+        register_dictionary(CompressionClient::Consensus, dictionary);
+    }
+
+    Ok(())
 }
 
 /// Sets up all networks and returns the appropriate application network interfaces
@@ -141,19 +141,25 @@ pub fn start_state_sync_and_get_notification_handles(
     let network_client = storage_network_interfaces.network_client;
     let network_service_events = storage_network_interfaces.network_service_events;
 
+    // Create the persistent metadata storage (this also stores the data client's peer reputations)
+    let metadata_storage = PersistentMetadataStorage::new(&node_config.storage.dir());
+
     // Start the data client
     let peers_and_metadata = network_client.get_peers_and_metadata();
-    let (aptos_data_client, aptos_data_client_runtime) =
-        setup_aptos_data_client(node_config, network_client, db_rw.reader.clone())?;
+    let (aptos_data_client, aptos_data_client_runtime) = setup_aptos_data_client(
+        node_config,
+        network_client,
+        db_rw.reader.clone(),
+        metadata_storage.clone(),
+    )?;
 
     // Start the data streaming service
     let state_sync_config = node_config.state_sync;
     let (streaming_service_client, streaming_service_runtime) =
         setup_data_streaming_service(state_sync_config, aptos_data_client.clone())?;
 
-    // Create the chunk executor and persistent storage
+    // Create the chunk executor
     let chunk_executor = Arc::new(ChunkExecutor::<AptosVMBlockExecutor>::new(db_rw.clone()));
-    let metadata_storage = PersistentMetadataStorage::new(&node_config.storage.dir());
 
     // Create notification senders and listeners for mempool, consensus and the storage service
     let (mempool_notifier, mempool_listener) =
@@ -241,6 +247,7 @@ fn setup_aptos_data_client(
     node_config: &NodeConfig,
     network_client: NetworkClient<StorageServiceMessage>,
     storage: Arc<dyn DbReader>,
+    metadata_storage: PersistentMetadataStorage,
 ) -> anyhow::Result<(AptosDataClient, Runtime)> {
     // Create the storage service client
     let storage_service_client = StorageServiceClient::new(network_client);
@@ -255,6 +262,7 @@ fn setup_aptos_data_client(
         TimeService::real(),
         storage,
         storage_service_client,
+        Some(Arc::new(metadata_storage)),
         Some(aptos_data_client_runtime.handle().clone()),
     );
     aptos_data_client_runtime.spawn(poller::start_poller(data_summary_poller));
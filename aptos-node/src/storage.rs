@@ -184,6 +184,10 @@ pub fn initialize_database_and_checkpoints(
         create_rocksdb_checkpoint_and_change_working_dir(node_config, working_dir);
     }
 
+    // If required, restore the database from backups (before it is opened)
+    crate::backup_restorer::restore_from_backup_if_required(node_config)
+        .map_err(|error| anyhow!("Failed to restore the database from backups: {:?}", error))?;
+
     // Open the database
     let instant = Instant::now();
     let (_aptos_db, db_rw, backup_service, indexer_db_opt, update_receiver) =
@@ -1,15 +1,15 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{create_single_node_test_config, network};
-use aptos_config::config::{NodeConfig, WaypointConfig};
+use crate::{backup_restorer, create_single_node_test_config, network};
+use aptos_config::config::{BootstrappingMode, NodeConfig, WaypointConfig};
 use aptos_event_notifications::EventSubscriptionService;
 use aptos_infallible::RwLock;
 use aptos_storage_interface::{DbReader, DbReaderWriter, DbWriter};
 use aptos_temppath::TempPath;
 use aptos_types::{chain_id::ChainId, waypoint::Waypoint};
 use rand::SeedableRng;
-use std::{fs, sync::Arc};
+use std::{fs, str::FromStr, sync::Arc};
 
 /// A mock database implementing DbReader and DbWriter
 pub struct MockDatabase;
@@ -114,3 +114,80 @@ fn test_create_single_node_test_config() {
             .bootstrapping_mode
     );
 }
+
+#[test]
+fn test_restore_from_backup_not_required() {
+    // Create a node config that doesn't restore from backups (but has no backup storage)
+    let node_config = NodeConfig::default();
+    assert!(node_config.storage.backup_restore.backup_storage.is_none());
+
+    // Verify that the restore is a no-op
+    backup_restorer::restore_from_backup_if_required(&node_config).unwrap();
+}
+
+#[test]
+fn test_restore_from_backup_missing_storage() {
+    // Create a node config that restores from backups without a backup storage
+    let mut node_config = NodeConfig::default();
+    node_config.state_sync.state_sync_driver.bootstrapping_mode =
+        BootstrappingMode::RestoreFromBackup;
+
+    // Verify that the restore fails
+    let error = backup_restorer::restore_from_backup_if_required(&node_config).unwrap_err();
+    assert!(error.to_string().contains("No backup storage"));
+}
+
+#[test]
+fn test_restore_from_backup_is_required() {
+    // Verify that empty storage is restored
+    assert!(backup_restorer::is_restore_required(0, None));
+
+    // Verify that an interrupted state snapshot restore is resumed
+    assert!(backup_restorer::is_restore_required(0, Some(100)));
+    assert!(backup_restorer::is_restore_required(101, Some(100)));
+
+    // Verify that non-empty storage is not restored
+    assert!(!backup_restorer::is_restore_required(1, None));
+    assert!(!backup_restorer::is_restore_required(101, None));
+}
+
+#[test]
+fn test_restore_from_backup_trusted_waypoints() {
+    // Create a node config with only a node waypoint
+    let genesis_waypoint = create_waypoint(0);
+    let mut node_config = NodeConfig::default();
+    node_config.base.waypoint = WaypointConfig::FromConfig(genesis_waypoint);
+
+    // Verify that the node waypoint is trusted (as the genesis waypoint)
+    let trusted_waypoints = backup_restorer::get_trusted_waypoints(&node_config).unwrap();
+    assert_eq!(trusted_waypoints.len(), 1);
+    assert_eq!(trusted_waypoints.get(&0), Some(&genesis_waypoint));
+
+    // Add a genesis waypoint and a later node waypoint
+    let node_waypoint = create_waypoint(100);
+    node_config.execution.genesis_waypoint = Some(WaypointConfig::FromConfig(genesis_waypoint));
+    node_config.base.waypoint = WaypointConfig::FromConfig(node_waypoint);
+
+    // Verify that both waypoints are trusted
+    let trusted_waypoints = backup_restorer::get_trusted_waypoints(&node_config).unwrap();
+    assert_eq!(trusted_waypoints.len(), 2);
+    assert_eq!(trusted_waypoints.get(&0), Some(&genesis_waypoint));
+    assert_eq!(trusted_waypoints.get(&100), Some(&node_waypoint));
+
+    // Use a conflicting genesis waypoint and verify that an error is returned
+    let conflicting_waypoint =
+        Waypoint::from_str("0:0000000000000000000000000000000000000000000000000000000000000001")
+            .unwrap();
+    node_config.base.waypoint = WaypointConfig::FromConfig(conflicting_waypoint);
+    let error = backup_restorer::get_trusted_waypoints(&node_config).unwrap_err();
+    assert!(error.to_string().contains("conflicting waypoints"));
+}
+
+/// Creates a waypoint at the specified version
+fn create_waypoint(version: u64) -> Waypoint {
+    Waypoint::from_str(&format!(
+        "{}:6072b68a942aace147e0655c5704beaa255c84a7829baa4e72a500f1516584c4",
+        version
+    ))
+    .unwrap()
+}
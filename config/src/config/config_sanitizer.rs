@@ -7,9 +7,10 @@ use crate::config::{
     utils::{are_failpoints_enabled, get_config_name},
     AdminServiceConfig, ApiConfig, BaseConfig, ConsensusConfig, DagConsensusConfig, Error,
     ExecutionConfig, IndexerGrpcConfig, InspectionServiceConfig, LoggerConfig, MempoolConfig,
-    NetbenchConfig, NodeConfig, StateSyncConfig, StorageConfig,
+    NetbenchConfig, NetworkConfig, NodeConfig, OutboundSchedulingPolicy, StateSyncConfig,
+    StorageConfig, PROTOCOL_NAMES,
 };
-use aptos_types::chain_id::ChainId;
+use aptos_types::{chain_id::ChainId, network_address::Protocol};
 use std::collections::HashSet;
 
 // Useful sanitizer constants
@@ -148,6 +149,15 @@ fn sanitize_fullnode_network_configs(
                 ),
             ));
         }
+
+        // Verify that the proxy protocol is supported by the transport
+        sanitize_network_proxy_protocol(&sanitizer_name, fullnode_network_config)?;
+
+        // Verify that the outbound scheduler config is valid
+        sanitize_outbound_scheduler_config(&sanitizer_name, fullnode_network_config)?;
+
+        // Verify that the message capture config is valid
+        sanitize_message_capture_config(&sanitizer_name, fullnode_network_config)?;
     }
 
     Ok(())
@@ -195,6 +205,119 @@ fn sanitize_validator_network_config(
                 "Mutual authentication must be enabled for the validator network!".into(),
             ));
         }
+
+        // Verify that the proxy protocol is supported by the transport
+        sanitize_network_proxy_protocol(&sanitizer_name, validator_network_config)?;
+
+        // Verify that the outbound scheduler config is valid
+        sanitize_outbound_scheduler_config(&sanitizer_name, validator_network_config)?;
+
+        // Verify that the message capture config is valid
+        sanitize_message_capture_config(&sanitizer_name, validator_network_config)?;
+    }
+
+    Ok(())
+}
+
+/// Sanitize the proxy protocol of the given network config. The proxy protocol
+/// is only supported by TCP, so it cannot be enabled when listening on QUIC.
+fn sanitize_network_proxy_protocol(
+    sanitizer_name: &str,
+    network_config: &NetworkConfig,
+) -> Result<(), Error> {
+    let listens_on_quic = network_config
+        .listen_address
+        .as_slice()
+        .iter()
+        .any(|protocol| matches!(protocol, Protocol::Quic(_)));
+    if listens_on_quic && network_config.enable_proxy_protocol {
+        return Err(Error::ConfigSanitizerFailed(
+            sanitizer_name.to_string(),
+            format!(
+                "The proxy protocol cannot be enabled when listening on QUIC ({})! Network: {}",
+                network_config.listen_address, network_config.network_id
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Sanitize the outbound scheduler config of the given network config
+fn sanitize_outbound_scheduler_config(
+    sanitizer_name: &str,
+    network_config: &NetworkConfig,
+) -> Result<(), Error> {
+    let scheduler_config = &network_config.outbound_scheduler_config;
+
+    // Verify that the priority queues can hold messages
+    if scheduler_config.max_queued_messages_per_priority == 0 {
+        return Err(Error::ConfigSanitizerFailed(
+            sanitizer_name.to_string(),
+            format!(
+                "The max queued outbound messages per priority must be non-zero! Network: {}",
+                network_config.network_id
+            ),
+        ));
+    }
+
+    // Verify that all prioritized protocols exist (otherwise, a typo silently uses the default class)
+    if let Some(protocol_name) = scheduler_config
+        .protocol_priorities
+        .keys()
+        .find(|protocol_name| !PROTOCOL_NAMES.contains(&protocol_name.as_str()))
+    {
+        return Err(Error::ConfigSanitizerFailed(
+            sanitizer_name.to_string(),
+            format!(
+                "The outbound scheduler priority is set for an unknown protocol: {}! Network: {}",
+                protocol_name, network_config.network_id
+            ),
+        ));
+    }
+
+    // Verify that all priority weights are non-zero (otherwise, classes may never be scheduled)
+    if scheduler_config.scheduling_policy == OutboundSchedulingPolicy::Weighted {
+        if let Some((priority, _)) = scheduler_config
+            .priority_weights
+            .iter()
+            .find(|(_, weight)| **weight == 0)
+        {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name.to_string(),
+                format!(
+                    "The outbound scheduler weight for priority {} must be non-zero! Network: {}",
+                    priority, network_config.network_id
+                ),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Sanitize the message capture config of the given network config (if any)
+fn sanitize_message_capture_config(
+    sanitizer_name: &str,
+    network_config: &NetworkConfig,
+) -> Result<(), Error> {
+    let capture_config = match &network_config.message_capture_config {
+        Some(capture_config) => capture_config,
+        None => return Ok(()), // Message capture is disabled
+    };
+
+    // Verify that the capture files and queue can hold messages
+    if capture_config.max_file_size_bytes == 0
+        || capture_config.max_num_files == 0
+        || capture_config.max_pending_messages == 0
+    {
+        return Err(Error::ConfigSanitizerFailed(
+            sanitizer_name.to_string(),
+            format!(
+                "The message capture file size, number of files and pending messages must be non-zero! Network: {}",
+                network_config.network_id
+            ),
+        ));
     }
 
     Ok(())
@@ -204,7 +327,10 @@ fn sanitize_validator_network_config(
 mod tests {
     use super::*;
     use crate::{
-        config::{node_startup_config::NodeStartupConfig, NetworkConfig},
+        config::{
+            node_startup_config::NodeStartupConfig, MessageCaptureConfig, NetworkConfig,
+            OutboundSchedulerConfig,
+        },
         network_id::NetworkId,
     };
 
@@ -384,6 +510,155 @@ mod tests {
         assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
     }
 
+    #[test]
+    fn test_sanitize_network_proxy_protocol() {
+        // Create a fullnode network config that listens on QUIC
+        let mut node_config = NodeConfig {
+            full_node_networks: vec![NetworkConfig {
+                network_id: NetworkId::Public,
+                listen_address: "/ip4/0.0.0.0/quic/6182".parse().unwrap(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        // Sanitize the config and verify that it succeeds
+        sanitize_fullnode_network_configs(
+            &node_config,
+            NodeType::PublicFullnode,
+            Some(ChainId::testnet()),
+        )
+        .unwrap();
+
+        // Enable the proxy protocol and verify that it fails
+        node_config.full_node_networks[0].enable_proxy_protocol = true;
+        let error = sanitize_fullnode_network_configs(
+            &node_config,
+            NodeType::PublicFullnode,
+            Some(ChainId::testnet()),
+        )
+        .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+
+        // Listen on TCP and verify the proxy protocol is now allowed
+        node_config.full_node_networks[0].listen_address = "/ip4/0.0.0.0/tcp/6182".parse().unwrap();
+        sanitize_fullnode_network_configs(
+            &node_config,
+            NodeType::PublicFullnode,
+            Some(ChainId::testnet()),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_sanitize_outbound_scheduler_config() {
+        // Create a fullnode network config that uses weighted scheduling with a zero weight
+        let mut node_config = NodeConfig {
+            full_node_networks: vec![NetworkConfig {
+                network_id: NetworkId::Public,
+                outbound_scheduler_config: OutboundSchedulerConfig {
+                    scheduling_policy: OutboundSchedulingPolicy::Weighted,
+                    priority_weights: [(0, 4), (1, 0)].into_iter().collect(),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        // Sanitize the config and verify that it fails
+        let error = sanitize_fullnode_network_configs(
+            &node_config,
+            NodeType::PublicFullnode,
+            Some(ChainId::testnet()),
+        )
+        .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+
+        // Use strict priority scheduling and verify the config is now valid
+        node_config.full_node_networks[0]
+            .outbound_scheduler_config
+            .scheduling_policy = OutboundSchedulingPolicy::StrictPriority;
+        sanitize_fullnode_network_configs(
+            &node_config,
+            NodeType::PublicFullnode,
+            Some(ChainId::testnet()),
+        )
+        .unwrap();
+
+        // Set the priority of an unknown protocol and verify that it fails
+        node_config.full_node_networks[0]
+            .outbound_scheduler_config
+            .protocol_priorities
+            .insert("ConsensusRpcBsc".into(), 0);
+        let error = sanitize_fullnode_network_configs(
+            &node_config,
+            NodeType::PublicFullnode,
+            Some(ChainId::testnet()),
+        )
+        .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+
+        // Remove the unknown protocol and verify the config is valid again
+        node_config.full_node_networks[0]
+            .outbound_scheduler_config
+            .protocol_priorities
+            .remove("ConsensusRpcBsc");
+        sanitize_fullnode_network_configs(
+            &node_config,
+            NodeType::PublicFullnode,
+            Some(ChainId::testnet()),
+        )
+        .unwrap();
+
+        // Set the max queued messages to zero and verify that it fails
+        node_config.full_node_networks[0]
+            .outbound_scheduler_config
+            .max_queued_messages_per_priority = 0;
+        let error = sanitize_fullnode_network_configs(
+            &node_config,
+            NodeType::PublicFullnode,
+            Some(ChainId::testnet()),
+        )
+        .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+    }
+
+    #[test]
+    fn test_sanitize_message_capture_config() {
+        // Create a fullnode network config that captures messages
+        let mut node_config = NodeConfig {
+            full_node_networks: vec![NetworkConfig {
+                network_id: NetworkId::Public,
+                message_capture_config: Some(MessageCaptureConfig::default()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        // Sanitize the config and verify that it succeeds
+        sanitize_fullnode_network_configs(
+            &node_config,
+            NodeType::PublicFullnode,
+            Some(ChainId::testnet()),
+        )
+        .unwrap();
+
+        // Set the max number of capture files to zero and verify that it fails
+        node_config.full_node_networks[0]
+            .message_capture_config
+            .as_mut()
+            .unwrap()
+            .max_num_files = 0;
+        let error = sanitize_fullnode_network_configs(
+            &node_config,
+            NodeType::PublicFullnode,
+            Some(ChainId::testnet()),
+        )
+        .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+    }
+
     #[test]
     fn test_sanitize_validator_incorrect_network_id() {
         // Create a validator config with the wrong network ID
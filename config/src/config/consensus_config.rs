@@ -101,6 +101,11 @@ pub struct ConsensusConfig {
     pub enable_round_timeout_msg: bool,
     pub enable_optimistic_proposal_rx: bool,
     pub enable_optimistic_proposal_tx: bool,
+    // The zstd dictionary used to compress consensus messages (if any). Peers that
+    // don't advertise the dictionary in the handshake are sent lz4 messages instead.
+    pub zstd_compression_dictionary: Option<PathBuf>,
+    // Additional zstd dictionaries used only to decompress consensus messages
+    pub zstd_decompression_dictionaries: Vec<PathBuf>,
 }
 
 /// Deprecated
@@ -383,6 +388,8 @@ impl Default for ConsensusConfig {
             enable_round_timeout_msg: true,
             enable_optimistic_proposal_rx: true,
             enable_optimistic_proposal_tx: false,
+            zstd_compression_dictionary: None,
+            zstd_decompression_dictionaries: vec![],
         }
     }
 }
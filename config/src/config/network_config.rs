@@ -23,9 +23,10 @@ use rand::{
 };
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     convert::TryFrom,
     fmt,
+    net::SocketAddr,
     path::PathBuf,
     string::ToString,
 };
@@ -52,6 +53,12 @@ pub const MAX_MESSAGE_SIZE: usize = 64 * 1024 * 1024; /* 64 MiB */
 pub const CONNECTION_BACKOFF_BASE: u64 = 2;
 pub const IP_BYTE_BUCKET_RATE: usize = 102400 /* 100 KiB */;
 pub const IP_BYTE_BUCKET_SIZE: usize = IP_BYTE_BUCKET_RATE;
+pub const QUIC_MAX_IDLE_TIMEOUT_MS: u64 = 30_000;
+pub const QUIC_KEEP_ALIVE_INTERVAL_MS: u64 = 5_000;
+pub const MAX_QUEUED_OUTBOUND_MESSAGES_PER_PRIORITY: usize = 1024;
+pub const MAX_MESSAGE_CAPTURE_FILE_SIZE_BYTES: u64 = 64 * 1024 * 1024; /* 64 MiB */
+pub const MAX_MESSAGE_CAPTURE_FILES: usize = 16;
+pub const MAX_PENDING_CAPTURED_MESSAGES: usize = 10_000;
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(default, deny_unknown_fields)]
@@ -124,6 +131,14 @@ pub struct NetworkConfig {
     pub max_parallel_deserialization_tasks: Option<usize>,
     /// Whether or not to enable latency aware peer dialing
     pub enable_latency_aware_dialing: bool,
+    /// The time after which an idle QUIC connection is closed (only used by QUIC)
+    pub quic_max_idle_timeout_ms: u64,
+    /// The interval at which QUIC keep-alives are sent on idle connections (only used by QUIC)
+    pub quic_keep_alive_interval_ms: u64,
+    /// The configuration for scheduling outbound messages on each peer connection
+    pub outbound_scheduler_config: OutboundSchedulerConfig,
+    /// The configuration for capturing network messages (disabled if not set)
+    pub message_capture_config: Option<MessageCaptureConfig>,
 }
 
 impl Default for NetworkConfig {
@@ -165,6 +180,10 @@ impl NetworkConfig {
             outbound_tx_buffer_size_bytes: None,
             max_parallel_deserialization_tasks: None,
             enable_latency_aware_dialing: true,
+            quic_max_idle_timeout_ms: QUIC_MAX_IDLE_TIMEOUT_MS,
+            quic_keep_alive_interval_ms: QUIC_KEEP_ALIVE_INTERVAL_MS,
+            outbound_scheduler_config: OutboundSchedulerConfig::default(),
+            message_capture_config: None,
         };
 
         // Configure the number of parallel deserialization tasks
@@ -341,12 +360,186 @@ impl NetworkConfig {
     }
 }
 
+/// The names of all network protocols (i.e., `ProtocolId::as_str()` in the network crate).
+/// These are used to validate the protocol names in the outbound scheduler config.
+pub const PROTOCOL_NAMES: &[&str] = &[
+    "ConsensusRpcBcs",
+    "ConsensusDirectSendBcs",
+    "MempoolDirectSend",
+    "StateSyncDirectSend",
+    "DiscoveryDirectSend",
+    "HealthCheckerRpc",
+    "ConsensusDirectSendJson",
+    "ConsensusRpcJson",
+    "StorageServiceRpc",
+    "MempoolRpc",
+    "PeerMonitoringServiceRpc",
+    "ConsensusRpcCompressed",
+    "ConsensusDirectSendCompressed",
+    "NetbenchDirectSend",
+    "NetbenchRpc",
+    "DKGDirectSendCompressed",
+    "DKGDirectSendBcs",
+    "DKGDirectSendJson",
+    "DKGRpcCompressed",
+    "DKGRpcBcs",
+    "DKGRpcJson",
+    "JWKConsensusDirectSendCompressed",
+    "JWKConsensusDirectSendBcs",
+    "JWKConsensusDirectSendJson",
+    "JWKConsensusRpcCompressed",
+    "JWKConsensusRpcBcs",
+    "JWKConsensusRpcJson",
+    "ConsensusObserver",
+    "ConsensusObserverRpc",
+    "ConsensusRpcZstd",
+    "ConsensusDirectSendZstd",
+];
+
+/// The policy used to order outbound messages on each peer connection
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutboundSchedulingPolicy {
+    /// Messages are written in the order they were sent (regardless of protocol)
+    #[default]
+    Fifo,
+    /// Messages in a higher priority class are always written before lower classes
+    StrictPriority,
+    /// Each priority class is written in a round-robin, proportional to its weight
+    Weighted,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutboundSchedulerConfig {
+    /// The policy used to order outbound messages. On connections that support additional
+    /// streams (i.e., QUIC), each priority class is written to its own stream. Note: the FIFO
+    /// policy has a single priority class, so it writes all messages to a single stream.
+    pub scheduling_policy: OutboundSchedulingPolicy,
+    /// The priority class of each protocol, keyed by protocol name (e.g., "ConsensusRpcBcs").
+    /// Lower classes have higher priority. Protocols not listed use the default class.
+    pub protocol_priorities: BTreeMap<String, u8>,
+    /// The priority class for protocols not listed in `protocol_priorities`
+    pub default_priority: u8,
+    /// The weight of each priority class (only used by the weighted policy).
+    /// Classes without a weight are given a weight of 1.
+    pub priority_weights: BTreeMap<u8, u64>,
+    /// The maximum number of outbound messages queued per priority class (per peer).
+    /// When a queue is full, the oldest message in the queue is dropped.
+    pub max_queued_messages_per_priority: usize,
+}
+
+impl Default for OutboundSchedulerConfig {
+    fn default() -> Self {
+        let protocol_priorities = [
+            // Consensus-critical protocols
+            ("ConsensusRpcBcs", 0),
+            ("ConsensusRpcCompressed", 0),
+            ("ConsensusRpcJson", 0),
+            ("ConsensusRpcZstd", 0),
+            ("ConsensusDirectSendBcs", 0),
+            ("ConsensusDirectSendCompressed", 0),
+            ("ConsensusDirectSendJson", 0),
+            ("ConsensusDirectSendZstd", 0),
+            ("DKGDirectSendBcs", 0),
+            ("DKGDirectSendCompressed", 0),
+            ("DKGDirectSendJson", 0),
+            ("DKGRpcBcs", 0),
+            ("DKGRpcCompressed", 0),
+            ("DKGRpcJson", 0),
+            ("JWKConsensusDirectSendBcs", 0),
+            ("JWKConsensusDirectSendCompressed", 0),
+            ("JWKConsensusDirectSendJson", 0),
+            ("JWKConsensusRpcBcs", 0),
+            ("JWKConsensusRpcCompressed", 0),
+            ("JWKConsensusRpcJson", 0),
+            ("HealthCheckerRpc", 0),
+            // Consensus observer protocols
+            ("ConsensusObserver", 1),
+            ("ConsensusObserverRpc", 1),
+            // Transaction dissemination protocols
+            ("MempoolDirectSend", 2),
+            ("MempoolRpc", 2),
+            // Data synchronization and monitoring protocols
+            ("StateSyncDirectSend", 3),
+            ("StorageServiceRpc", 3),
+            ("PeerMonitoringServiceRpc", 3),
+        ]
+        .into_iter()
+        .map(|(protocol, priority)| (protocol.to_string(), priority))
+        .collect();
+        let priority_weights = [(0, 8), (1, 4), (2, 2), (3, 1)].into_iter().collect();
+
+        Self {
+            scheduling_policy: OutboundSchedulingPolicy::Fifo,
+            protocol_priorities,
+            default_priority: 2,
+            priority_weights,
+            max_queued_messages_per_priority: MAX_QUEUED_OUTBOUND_MESSAGES_PER_PRIORITY,
+        }
+    }
+}
+
+impl OutboundSchedulerConfig {
+    /// Returns the priority class of the given protocol (by name)
+    pub fn get_priority(&self, protocol_name: &str) -> u8 {
+        self.protocol_priorities
+            .get(protocol_name)
+            .copied()
+            .unwrap_or(self.default_priority)
+    }
+
+    /// Returns the weight of the given priority class
+    pub fn get_weight(&self, priority: u8) -> u64 {
+        self.priority_weights.get(&priority).copied().unwrap_or(1)
+    }
+}
+
+/// The configuration for capturing the messages sent and received on each peer
+/// connection. Captured messages are written to a set of rotating files, and can
+/// be replayed against a node (e.g., to reproduce protocol issues).
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MessageCaptureConfig {
+    /// The directory in which the capture files are written
+    pub capture_dir: PathBuf,
+    /// The maximum size of each capture file (before rotating to a new file)
+    pub max_file_size_bytes: u64,
+    /// The maximum number of capture files to keep (the oldest files are deleted first)
+    pub max_num_files: usize,
+    /// The protocols to capture (by name). If empty, all protocols are captured.
+    pub protocols: Vec<String>,
+    /// The maximum number of messages waiting to be written. If the writer
+    /// falls behind, new messages are dropped (instead of blocking the network).
+    pub max_pending_messages: usize,
+}
+
+impl Default for MessageCaptureConfig {
+    fn default() -> Self {
+        Self {
+            capture_dir: PathBuf::from("/tmp/aptos-network-capture"),
+            max_file_size_bytes: MAX_MESSAGE_CAPTURE_FILE_SIZE_BYTES,
+            max_num_files: MAX_MESSAGE_CAPTURE_FILES,
+            protocols: vec![],
+            max_pending_messages: MAX_PENDING_CAPTURED_MESSAGES,
+        }
+    }
+}
+
+impl MessageCaptureConfig {
+    /// Returns true iff messages for the given protocol (by name) should be captured
+    pub fn should_capture(&self, protocol_name: &str) -> bool {
+        self.protocols.is_empty() || self.protocols.iter().any(|name| name == protocol_name)
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum DiscoveryMethod {
     Onchain,
     File(FileDiscovery),
     Rest(RestDiscovery),
+    Dns(DnsDiscovery),
     None,
 }
 
@@ -364,6 +557,24 @@ pub struct RestDiscovery {
     pub interval_secs: u64,
 }
 
+/// Discovers (upstream) peers using DNS records. Peers are published as TXT
+/// records (e.g., `addr=<network address> peer_id=<peer id>`) and/or SRV records
+/// (where each target holds a TXT record with its `key=<public key>`).
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct DnsDiscovery {
+    /// The DNS name to resolve (e.g., `_aptosnet._tcp.seeds.example.com`).
+    /// SRV targets are dialed over QUIC if the protocol label is `_quic`
+    /// (e.g., `_aptosnet._quic.seeds.example.com`), and over TCP otherwise.
+    pub name: String,
+    /// The DNS server to query. If not specified, the system resolver configuration is used.
+    pub nameserver: Option<SocketAddr>,
+    /// The minimum interval between lookups (used when records have a smaller TTL)
+    pub min_interval_secs: u64,
+    /// The maximum interval between lookups (used when records have a larger TTL)
+    pub max_interval_secs: u64,
+}
+
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct RateLimitConfig {
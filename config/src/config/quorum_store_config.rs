@@ -46,6 +46,37 @@ impl Default for QuorumStoreBackPressureConfig {
     }
 }
 
+/// Limits the share of a single sender or use case (see `UseCaseKey`) in the quorum store,
+/// so that a spammy account or contract can't fill a validator's batches and blocks.
+/// All quotas are disabled (set to `u64::MAX`) by default.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct QuorumStoreFairnessConfig {
+    /// The maximum number of transactions from a single sender that the batch generator
+    /// puts in the batches created from one mempool pull.
+    pub batch_max_txns_per_sender: u64,
+    /// The maximum number of transactions of a single use case that the batch generator
+    /// puts in the batches created from one mempool pull.
+    pub batch_max_txns_per_use_case: u64,
+    /// The maximum number of transactions from a single sender in the batches (and proofs)
+    /// pulled for a block.
+    pub block_max_txns_per_sender: u64,
+    /// The maximum number of transactions of a single use case in the batches (and proofs)
+    /// pulled for a block.
+    pub block_max_txns_per_use_case: u64,
+}
+
+impl Default for QuorumStoreFairnessConfig {
+    fn default() -> QuorumStoreFairnessConfig {
+        QuorumStoreFairnessConfig {
+            batch_max_txns_per_sender: u64::MAX,
+            batch_max_txns_per_use_case: u64::MAX,
+            block_max_txns_per_sender: u64::MAX,
+            block_max_txns_per_use_case: u64::MAX,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 #[serde(default, deny_unknown_fields)]
 pub struct QuorumStoreConfig {
@@ -93,6 +124,7 @@ pub struct QuorumStoreConfig {
     pub db_quota: usize,
     pub batch_quota: usize,
     pub back_pressure: QuorumStoreBackPressureConfig,
+    pub fairness: QuorumStoreFairnessConfig,
     pub num_workers_for_remote_batches: usize,
     pub batch_buckets: Vec<u64>,
     pub allow_batches_without_pos_in_proposal: bool,
@@ -133,6 +165,7 @@ impl Default for QuorumStoreConfig {
             db_quota: 300_000_000,
             batch_quota: 300_000,
             back_pressure: QuorumStoreBackPressureConfig::default(),
+            fairness: QuorumStoreFairnessConfig::default(),
             // number of batch coordinators to handle QS batch messages, should be >= 1
             num_workers_for_remote_batches: 10,
             batch_buckets: DEFAULT_BUCKETS.to_vec(),
@@ -246,6 +279,40 @@ impl QuorumStoreConfig {
         }
         Ok(())
     }
+
+    fn sanitize_fairness_quotas(
+        sanitizer_name: &str,
+        config: &QuorumStoreConfig,
+    ) -> Result<(), Error> {
+        let fairness = &config.fairness;
+        let quotas = [
+            (
+                fairness.batch_max_txns_per_sender,
+                "batch_max_txns_per_sender",
+            ),
+            (
+                fairness.batch_max_txns_per_use_case,
+                "batch_max_txns_per_use_case",
+            ),
+            (
+                fairness.block_max_txns_per_sender,
+                "block_max_txns_per_sender",
+            ),
+            (
+                fairness.block_max_txns_per_use_case,
+                "block_max_txns_per_use_case",
+            ),
+        ];
+        for (quota, label) in &quotas {
+            if *quota == 0 {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name.to_owned(),
+                    format!("Failed {}: the fairness quota must be non-zero", label),
+                ));
+            }
+        }
+        Ok(())
+    }
 }
 
 impl ConfigSanitizer for QuorumStoreConfig {
@@ -265,6 +332,9 @@ impl ConfigSanitizer for QuorumStoreConfig {
         // Sanitize the batch total limits
         Self::sanitize_batch_total_limits(&sanitizer_name, &node_config.consensus.quorum_store)?;
 
+        // Sanitize the fairness quotas
+        Self::sanitize_fairness_quotas(&sanitizer_name, &node_config.consensus.quorum_store)?;
+
         Ok(())
     }
 }
@@ -462,4 +532,27 @@ mod test {
             QuorumStoreConfig::sanitize(&node_config, NodeType::Validator, None).unwrap_err();
         assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
     }
+
+    #[test]
+    fn test_fairness_quotas() {
+        // Create a node config with a zero sender quota
+        let node_config = NodeConfig {
+            consensus: ConsensusConfig {
+                quorum_store: QuorumStoreConfig {
+                    fairness: QuorumStoreFairnessConfig {
+                        block_max_txns_per_sender: 0,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // Sanitize the config and verify that it fails
+        let error =
+            QuorumStoreConfig::sanitize(&node_config, NodeType::Validator, None).unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+    }
 }
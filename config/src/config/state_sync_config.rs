@@ -44,6 +44,9 @@ pub enum BootstrappingMode {
     ExecuteTransactionsFromGenesis,
     /// Executes transactions or applies outputs from genesis (whichever is faster)
     ExecuteOrApplyFromGenesis,
+    /// Restores the storage from the backups (see `StorageConfig::backup_restore`),
+    /// and then applies transaction outputs (starting at the restored version)
+    RestoreFromBackup,
 }
 
 impl BootstrappingMode {
@@ -57,6 +60,7 @@ impl BootstrappingMode {
                 "execute_transactions_from_genesis"
             },
             BootstrappingMode::ExecuteOrApplyFromGenesis => "execute_or_apply_from_genesis",
+            BootstrappingMode::RestoreFromBackup => "restore_from_backup",
         }
     }
 
@@ -64,6 +68,20 @@ impl BootstrappingMode {
     pub fn is_fast_sync(&self) -> bool {
         *self == BootstrappingMode::DownloadLatestStates
     }
+
+    /// Returns true iff the bootstrapping mode restores from backups
+    pub fn is_restore_from_backup(&self) -> bool {
+        *self == BootstrappingMode::RestoreFromBackup
+    }
+
+    /// Returns true iff the bootstrapping mode may execute transactions
+    pub fn is_execution_mode(&self) -> bool {
+        matches!(
+            self,
+            BootstrappingMode::ExecuteTransactionsFromGenesis
+                | BootstrappingMode::ExecuteOrApplyFromGenesis
+        )
+    }
 }
 
 /// The continuous syncing mode determines how the node will stay up-to-date
@@ -89,6 +107,15 @@ impl ContinuousSyncingMode {
             },
         }
     }
+
+    /// Returns true iff the continuous syncing mode may execute transactions
+    pub fn is_execution_mode(&self) -> bool {
+        matches!(
+            self,
+            ContinuousSyncingMode::ExecuteTransactions
+                | ContinuousSyncingMode::ExecuteTransactionsOrApplyOutputs
+        )
+    }
 }
 
 #[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
@@ -104,12 +131,23 @@ pub struct StateSyncDriverConfig {
     pub enable_auto_bootstrapping: bool,
     /// The interval (ms) to refresh the storage summary
     pub fallback_to_output_syncing_secs: u64,
+    /// The version to fast sync to (instead of the latest epoch ending version).
+    /// This must be a state checkpoint version that peers have not yet pruned. It
+    /// may be in the current epoch. Once the states are synced, the node applies
+    /// the transaction outputs up to the ledger info that proves the version.
+    pub fast_sync_target_version: Option<u64>,
     /// The interval (ms) at which to check state sync progress
     pub progress_check_interval_ms: u64,
     /// The maximum time (secs) to wait for connections from peers before auto-bootstrapping
     pub max_connection_deadline_secs: u64,
     /// The maximum number of notifications to process per driver loop
     pub max_consecutive_stream_notifications: u64,
+    /// The maximum number of state keys to request from a peer in a single
+    /// partial state verification request
+    pub max_partial_state_keys_per_request: u64,
+    /// The maximum number of versions whose touched keys are verified in a single
+    /// partial state verification round. Any remaining versions are carried over.
+    pub max_partial_state_versions_per_round: u64,
     /// The maximum number of stream timeouts allowed before termination
     pub max_num_stream_timeouts: u64,
     /// The maximum number of data chunks pending execution or commit
@@ -120,6 +158,8 @@ pub struct StateSyncDriverConfig {
     pub max_stream_wait_time_ms: u64,
     /// The version lag we'll tolerate before snapshot syncing
     pub num_versions_to_skip_snapshot_sync: u64,
+    /// The interval (ms) at which to verify the stored state values (in partial state mode)
+    pub partial_state_verification_interval_ms: u64,
 }
 
 /// The default state sync driver config will be the one that gets (and keeps)
@@ -132,14 +172,18 @@ impl Default for StateSyncDriverConfig {
             continuous_syncing_mode: ContinuousSyncingMode::ExecuteTransactionsOrApplyOutputs,
             enable_auto_bootstrapping: false,
             fallback_to_output_syncing_secs: 180, // 3 minutes
+            fast_sync_target_version: None,
             progress_check_interval_ms: 100,
             max_connection_deadline_secs: 10,
             max_consecutive_stream_notifications: 10,
+            max_partial_state_keys_per_request: 1_000,
+            max_partial_state_versions_per_round: 10_000,
             max_num_stream_timeouts: 12,
             max_pending_data_chunks: 50,
             max_pending_mempool_notifications: 100,
             max_stream_wait_time_ms: 5000,
             num_versions_to_skip_snapshot_sync: 400_000_000, // At 5k TPS, this allows a node to fail for about 24 hours.
+            partial_state_verification_interval_ms: 60_000,
         }
     }
 }
@@ -392,6 +436,34 @@ impl Default for AptosLatencyFilteringConfig {
     }
 }
 
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AptosPeerReputationConfig {
+    /// Whether or not to persist peer reputations across restarts
+    pub enable_persistence: bool,
+    /// The half-life (secs) of a persisted peer score (i.e., the time it
+    /// takes for the score to decay halfway back to the starting score).
+    pub decay_half_life_secs: u64,
+    /// The maximum age (secs) of persisted reputations and misbehaviour records
+    pub max_reputation_age_secs: u64,
+    /// The maximum number of misbehaviour records to keep per peer
+    pub max_misbehaviour_records_per_peer: u64,
+    /// The interval (secs) at which to persist the peer reputations
+    pub persistence_interval_secs: u64,
+}
+
+impl Default for AptosPeerReputationConfig {
+    fn default() -> Self {
+        Self {
+            enable_persistence: false,
+            decay_half_life_secs: 21_600,     // 6 hours
+            max_reputation_age_secs: 604_800, // 7 days
+            max_misbehaviour_records_per_peer: 20,
+            persistence_interval_secs: 60, // 1 minute
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
 #[serde(default, deny_unknown_fields)]
 pub struct AptosDataClientConfig {
@@ -431,6 +503,8 @@ pub struct AptosDataClientConfig {
     pub max_transaction_output_chunk_size: u64,
     /// Timeout (in ms) when waiting for an optimistic fetch response
     pub optimistic_fetch_timeout_ms: u64,
+    /// The peer reputation config for the data client
+    pub peer_reputation_config: AptosPeerReputationConfig,
     /// First timeout (in ms) when waiting for a response
     pub response_timeout_ms: u64,
     /// Timeout (in ms) when waiting for a subscription response
@@ -457,6 +531,7 @@ impl Default for AptosDataClientConfig {
             max_subscription_lag_secs: 20, // 20 seconds
             max_transaction_chunk_size: MAX_TRANSACTION_CHUNK_SIZE,
             max_transaction_output_chunk_size: MAX_TRANSACTION_OUTPUT_CHUNK_SIZE,
+            peer_reputation_config: AptosPeerReputationConfig::default(),
             optimistic_fetch_timeout_ms: 5000,        // 5 seconds
             response_timeout_ms: 10_000,              // 10 seconds
             subscription_response_timeout_ms: 15_000, // 15 seconds (longer than a regular timeout because of prefetching)
@@ -496,6 +571,38 @@ impl ConfigSanitizer for StateSyncDriverConfig {
             ));
         }
 
+        // Verify that a fast sync target version is only
+        // specified for nodes that are fast syncing.
+        if let Some(fast_sync_target_version) = state_sync_driver_config.fast_sync_target_version {
+            if !fast_sync_enabled {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name,
+                    "A fast sync target version should only be specified for nodes that are fast syncing!"
+                        .to_string(),
+                ));
+            }
+            if fast_sync_target_version == 0 {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name,
+                    "The fast sync target version must be greater than genesis!".to_string(),
+                ));
+            }
+        }
+
+        // Verify that a backup storage is specified for
+        // nodes that are restoring from backups.
+        if state_sync_driver_config
+            .bootstrapping_mode
+            .is_restore_from_backup()
+            && node_config.storage.backup_restore.backup_storage.is_none()
+        {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                "A backup storage must be specified for nodes that are restoring from backups!"
+                    .to_string(),
+            ));
+        }
+
         Ok(())
     }
 }
@@ -583,6 +690,7 @@ impl ConfigOptimizer for DataStreamingServiceConfig {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::BackupStorageConfig;
 
     #[test]
     fn test_optimize_bootstrapping_mode_devnet_vfn() {
@@ -801,6 +909,71 @@ mod tests {
         assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
     }
 
+    #[test]
+    fn test_sanitize_fast_sync_target_version() {
+        // Create a node config with a fast sync target version but no fast sync
+        let mut node_config = NodeConfig {
+            state_sync: StateSyncConfig {
+                state_sync_driver: StateSyncDriverConfig {
+                    bootstrapping_mode: BootstrappingMode::ExecuteOrApplyFromGenesis,
+                    fast_sync_target_version: Some(1000),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // Verify that sanitization fails
+        let error =
+            StateSyncConfig::sanitize(&node_config, NodeType::Validator, Some(ChainId::testnet()))
+                .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+
+        // Enable fast sync and verify that sanitization passes
+        node_config.state_sync.state_sync_driver.bootstrapping_mode =
+            BootstrappingMode::DownloadLatestStates;
+        StateSyncConfig::sanitize(&node_config, NodeType::Validator, Some(ChainId::testnet()))
+            .unwrap();
+
+        // Target genesis and verify that sanitization fails
+        node_config
+            .state_sync
+            .state_sync_driver
+            .fast_sync_target_version = Some(0);
+        let error =
+            StateSyncConfig::sanitize(&node_config, NodeType::Validator, Some(ChainId::testnet()))
+                .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+    }
+
+    #[test]
+    fn test_sanitize_restore_from_backup_storage() {
+        // Create a node config that restores from backups without a backup storage
+        let mut node_config = NodeConfig {
+            state_sync: StateSyncConfig {
+                state_sync_driver: StateSyncDriverConfig {
+                    bootstrapping_mode: BootstrappingMode::RestoreFromBackup,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // Verify that sanitization fails
+        let error =
+            StateSyncConfig::sanitize(&node_config, NodeType::Validator, Some(ChainId::testnet()))
+                .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+
+        // Specify a backup storage and verify that sanitization passes
+        node_config.storage.backup_restore.backup_storage =
+            Some(BackupStorageConfig::LocalFs("/opt/aptos/backups".into()));
+        StateSyncConfig::sanitize(&node_config, NodeType::Validator, Some(ChainId::testnet()))
+            .unwrap();
+    }
+
     /// Creates and returns a node config with the syncing modes set to execution
     fn create_execution_mode_config() -> NodeConfig {
         NodeConfig {
@@ -11,12 +11,14 @@ use crate::{
 };
 use anyhow::{bail, ensure, Result};
 use aptos_logger::warn;
-use aptos_types::chain_id::ChainId;
+use aptos_types::{
+    account_address::AccountAddress, account_config::primary_apt_store, chain_id::ChainId,
+};
 use arr_macro::arr;
 use serde::{Deserialize, Serialize};
 use serde_yaml::Value;
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     net::{IpAddr, Ipv4Addr, SocketAddr},
     path::{Path, PathBuf},
     str::FromStr,
@@ -210,6 +212,95 @@ pub struct StorageConfig {
     pub ensure_rlimit_nofile: u64,
     /// panic if failed to ensure `ulimit -n`
     pub assert_rlimit_nofile: bool,
+    /// The backups to restore from when bootstrapping (only used if the
+    /// bootstrapping mode is `RestoreFromBackup`)
+    pub backup_restore: BackupRestoreConfig,
+    /// Partial state mode (i.e., only storing the state values of selected accounts)
+    pub partial_state: PartialStateConfig,
+}
+
+/// The config for partial state mode. In this mode, the node keeps the full
+/// state Merkle tree (i.e., all state value hashes), so every transaction output
+/// and state root is still verified. However, the state values themselves are
+/// only stored for the tracked accounts and resource groups. All other values
+/// are dropped on commit (and during state snapshot sync).
+///
+/// Note: because untracked values are unavailable, the node cannot execute
+/// transactions (it must apply transaction outputs). Likewise, the state storage
+/// usage reported by the node is inaccurate: new untracked values are counted
+/// when written, but the old values they replace (or that are deleted) are never
+/// subtracted, as they were not stored. The reported usage thus only grows.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PartialStateConfig {
+    /// Whether partial state mode is enabled
+    pub enabled: bool,
+    /// The accounts whose state values (resources, resource groups and
+    /// modules) are stored, along with their primary APT fungible stores.
+    /// Special addresses (e.g., the framework at 0x1) are always tracked, as
+    /// the node requires the on-chain configs. Table items are never stored.
+    pub tracked_accounts: Vec<AccountAddress>,
+    /// The resource groups (e.g., "0x1::object::ObjectGroup") that are stored
+    /// for all accounts, specified as struct tags
+    pub tracked_resource_groups: Vec<String>,
+}
+
+impl PartialStateConfig {
+    /// Returns true iff the state values of the given account (or object) are stored
+    pub fn is_account_tracked(&self, address: &AccountAddress) -> bool {
+        !self.enabled
+            || address.is_special()
+            || self.tracked_accounts.contains(address)
+            || self
+                .tracked_accounts
+                .iter()
+                .any(|account| primary_apt_store(*account) == *address)
+    }
+
+    /// Returns the addresses whose state values are stored, i.e., the tracked
+    /// accounts and their primary APT fungible stores (special addresses are
+    /// always tracked, so they are not included).
+    pub fn get_tracked_addresses(&self) -> HashSet<AccountAddress> {
+        self.tracked_accounts
+            .iter()
+            .flat_map(|account| [*account, primary_apt_store(*account)])
+            .collect()
+    }
+}
+
+/// The backup storage from which the node restores its database
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupStorageConfig {
+    /// A local directory holding the backups (used mainly for tests)
+    LocalFs(PathBuf),
+    /// A command adapter config file, which specifies the commands used to
+    /// read the backups from a (possibly remote) storage.
+    CommandAdapter(PathBuf),
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BackupRestoreConfig {
+    /// The backup storage to restore from
+    pub backup_storage: Option<BackupStorageConfig>,
+    /// The number of concurrent downloads from the backup storage
+    pub concurrent_downloads: usize,
+    /// The directory in which to cache the backup metadata (defaults to a temporary dir)
+    pub metadata_cache_dir: Option<PathBuf>,
+    /// The concurrency level used to replay the transactions after the state snapshot
+    pub replay_concurrency_level: usize,
+}
+
+impl Default for BackupRestoreConfig {
+    fn default() -> Self {
+        Self {
+            backup_storage: None,
+            concurrent_downloads: 16,
+            metadata_cache_dir: None,
+            replay_concurrency_level: 16,
+        }
+    }
 }
 
 pub const NO_OP_STORAGE_PRUNER_CONFIG: PrunerConfig = PrunerConfig {
@@ -359,6 +450,8 @@ impl Default for StorageConfig {
             max_num_nodes_per_lru_cache_shard: DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
             ensure_rlimit_nofile: 0,
             assert_rlimit_nofile: false,
+            backup_restore: BackupRestoreConfig::default(),
+            partial_state: PartialStateConfig::default(),
         }
     }
 }
@@ -554,12 +647,31 @@ impl ConfigOptimizer for StorageConfig {
 impl ConfigSanitizer for StorageConfig {
     fn sanitize(
         node_config: &NodeConfig,
-        _node_type: NodeType,
+        node_type: NodeType,
         _chain_id: Option<ChainId>,
     ) -> Result<(), Error> {
         let sanitizer_name = Self::get_sanitizer_name();
         let config = &node_config.storage;
 
+        // Verify that partial state mode is only used by fullnodes that apply outputs
+        if config.partial_state.enabled {
+            if node_type.is_validator() {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name,
+                    "Partial state mode is not supported for validators!".to_string(),
+                ));
+            }
+            let driver_config = &node_config.state_sync.state_sync_driver;
+            if driver_config.bootstrapping_mode.is_execution_mode()
+                || driver_config.continuous_syncing_mode.is_execution_mode()
+            {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name,
+                    "Partial state mode requires state sync to apply transaction outputs (not execute transactions)!".to_string(),
+                ));
+            }
+        }
+
         let ledger_prune_window = config
             .storage_pruner_config
             .ledger_pruner_config
@@ -657,10 +769,14 @@ impl ConfigSanitizer for StorageConfig {
 #[cfg(test)]
 mod test {
     use crate::config::{
-        config_optimizer::ConfigOptimizer, NodeConfig, NodeType, PrunerConfig, ShardPathConfig,
-        ShardedDbPathConfig, StorageConfig,
+        config_optimizer::ConfigOptimizer, config_sanitizer::ConfigSanitizer, BootstrappingMode,
+        ContinuousSyncingMode, Error, NodeConfig, NodeType, PartialStateConfig, PrunerConfig,
+        ShardPathConfig, ShardedDbPathConfig, StorageConfig,
+    };
+    use aptos_types::{
+        account_address::AccountAddress, account_config::primary_apt_store, chain_id::ChainId,
     };
-    use aptos_types::chain_id::ChainId;
+    use std::collections::HashSet;
 
     #[test]
     pub fn test_default_prune_window() {
@@ -767,4 +883,65 @@ mod test {
         assert_eq!(node_config.storage.ensure_rlimit_nofile, 999_999);
         assert!(node_config.storage.assert_rlimit_nofile);
     }
+
+    #[test]
+    fn test_sanitize_partial_state_config() {
+        // Create a node config with partial state mode enabled (and output syncing)
+        let mut node_config = NodeConfig {
+            storage: StorageConfig {
+                partial_state: PartialStateConfig {
+                    enabled: true,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let driver_config = &mut node_config.state_sync.state_sync_driver;
+        driver_config.bootstrapping_mode = BootstrappingMode::DownloadLatestStates;
+        driver_config.continuous_syncing_mode = ContinuousSyncingMode::ApplyTransactionOutputs;
+
+        // Verify that the config is valid for fullnodes (but not validators)
+        StorageConfig::sanitize(&node_config, NodeType::PublicFullnode, None).unwrap();
+        let error = StorageConfig::sanitize(&node_config, NodeType::Validator, None).unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+
+        // Verify that the config is invalid if state sync executes transactions
+        node_config
+            .state_sync
+            .state_sync_driver
+            .continuous_syncing_mode = ContinuousSyncingMode::ExecuteTransactions;
+        let error =
+            StorageConfig::sanitize(&node_config, NodeType::PublicFullnode, None).unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+    }
+
+    #[test]
+    fn test_partial_state_tracked_accounts() {
+        // Verify that all accounts are tracked if partial state mode is disabled
+        let tracked_account = AccountAddress::random();
+        let mut partial_state_config = PartialStateConfig {
+            enabled: false,
+            tracked_accounts: vec![tracked_account],
+            tracked_resource_groups: vec![],
+        };
+        assert!(partial_state_config.is_account_tracked(&AccountAddress::random()));
+
+        // Verify that only the tracked and special accounts are tracked once enabled
+        partial_state_config.enabled = true;
+        assert!(partial_state_config.is_account_tracked(&tracked_account));
+        assert!(partial_state_config.is_account_tracked(&AccountAddress::ONE));
+        assert!(!partial_state_config.is_account_tracked(&AccountAddress::random()));
+
+        // Verify that the primary APT stores of the tracked accounts are tracked
+        let primary_store = primary_apt_store(tracked_account);
+        assert!(partial_state_config.is_account_tracked(&primary_store));
+        assert!(
+            !partial_state_config.is_account_tracked(&primary_apt_store(AccountAddress::random()))
+        );
+        assert_eq!(
+            partial_state_config.get_tracked_addresses(),
+            HashSet::from([tracked_account, primary_store])
+        );
+    }
 }
@@ -0,0 +1,603 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_consensus::consensus_observer::{
+    common::error::Error,
+    network::observer_message::{BlockHeader, CommitDecision, OrderedBlockHeaders},
+};
+use aptos_types::{
+    block_info::BlockInfo, epoch_change::EpochChangeProof, epoch_state::EpochState,
+    ledger_info::LedgerInfoWithSignatures,
+};
+use std::sync::Arc;
+
+/// The events emitted by the header chain verifier as the chain progresses
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum HeaderClientEvent {
+    /// New blocks were ordered (in chain order), together with their ordered proof
+    OrderedBlocks {
+        block_headers: Vec<BlockHeader>,
+        ordered_proof: LedgerInfoWithSignatures,
+    },
+    /// New blocks were committed, up to (and including) the block in the commit proof
+    Committed(LedgerInfoWithSignatures),
+    /// The epoch changed. All following events are verified against the new epoch state.
+    NewEpoch(Arc<EpochState>),
+}
+
+/// Follows the chain by verifying header-only messages against the current epoch
+/// state. The verifier starts from a trusted ledger info, and moves to the next
+/// epoch when it receives an epoch-ending commit proof (or an epoch change proof).
+pub struct HeaderChainVerifier {
+    // The epoch state that messages are verified against
+    epoch_state: Arc<EpochState>,
+
+    // The highest ordered block seen in the current epoch (if any). New ordered
+    // blocks must extend this block.
+    highest_ordered_block: Option<BlockInfo>,
+
+    // The highest verified commit proof
+    highest_committed: LedgerInfoWithSignatures,
+}
+
+impl HeaderChainVerifier {
+    /// Creates a new verifier from a trusted ledger info and the epoch state of the
+    /// following messages. If the trusted ledger info ends an epoch, the epoch state
+    /// is taken from the ledger info.
+    pub fn new(
+        epoch_state: Arc<EpochState>,
+        trusted_ledger_info: LedgerInfoWithSignatures,
+    ) -> Self {
+        let epoch_state = trusted_ledger_info
+            .ledger_info()
+            .next_epoch_state()
+            .map(|epoch_state| Arc::new(epoch_state.clone()))
+            .unwrap_or(epoch_state);
+
+        Self {
+            epoch_state,
+            highest_ordered_block: None,
+            highest_committed: trusted_ledger_info,
+        }
+    }
+
+    /// Returns the current epoch state
+    pub fn epoch_state(&self) -> Arc<EpochState> {
+        self.epoch_state.clone()
+    }
+
+    /// Returns a reference to the highest verified commit proof
+    pub fn highest_committed(&self) -> &LedgerInfoWithSignatures {
+        &self.highest_committed
+    }
+
+    /// Returns the block that new ordered blocks must extend (i.e., the highest
+    /// ordered block, or the highest committed block if no block was ordered since).
+    fn highest_block(&self) -> &BlockInfo {
+        self.highest_ordered_block
+            .as_ref()
+            .unwrap_or_else(|| self.highest_committed.commit_info())
+    }
+
+    /// Verifies the given ordered block headers and returns the events to emit.
+    /// Headers that were already ordered are dropped, and the remaining headers
+    /// must extend the highest ordered block.
+    pub fn process_ordered_block_headers(
+        &mut self,
+        ordered_block_headers: OrderedBlockHeaders,
+    ) -> Result<Vec<HeaderClientEvent>, Error> {
+        // Ignore the message if it's not newer than what we already have
+        let proof_block_info = ordered_block_headers.proof_block_info();
+        let highest_block = self.highest_block().clone();
+        if (proof_block_info.epoch(), proof_block_info.round())
+            <= (highest_block.epoch(), highest_block.round())
+        {
+            return Ok(vec![]);
+        }
+
+        // Verify the message contents, the ordered proof and the quorum certs
+        self.verify_epoch(proof_block_info)?;
+        ordered_block_headers.verify_ordered_block_headers()?;
+        ordered_block_headers.verify_ordered_proof(&self.epoch_state)?;
+
+        // Drop any headers that were already ordered (e.g., if the previous
+        // proof was for a block in the middle of this chain).
+        let (mut block_headers, ordered_proof) = ordered_block_headers.into_parts();
+        let num_known_headers = block_headers
+            .iter()
+            .take_while(|block_header| {
+                (block_header.epoch(), block_header.round())
+                    <= (highest_block.epoch(), highest_block.round())
+            })
+            .count();
+        let block_headers = block_headers.split_off(num_known_headers);
+
+        // Verify the first new header extends the highest block. This can only be
+        // checked against a committed block if the message includes it (e.g., when
+        // following the chain from a trusted ledger info, the first message may
+        // start after the committed block).
+        let first_block_header = block_headers.first().ok_or_else(|| {
+            Error::InvalidMessageError(format!(
+                "The block headers are not ordered by round! Ordered proof block: {}",
+                ordered_proof.commit_info()
+            ))
+        })?;
+        let must_extend_highest_block =
+            self.highest_ordered_block.is_some() || num_known_headers > 0;
+        if must_extend_highest_block && first_block_header.parent_id() != highest_block.id() {
+            return Err(Error::InvalidMessageError(format!(
+                "The block headers do not extend the highest block! Highest block: {}, First block header parent: {}",
+                highest_block,
+                first_block_header.parent_block_info()
+            )));
+        }
+
+        // Update the highest ordered block
+        self.highest_ordered_block = Some(ordered_proof.commit_info().clone());
+
+        Ok(vec![HeaderClientEvent::OrderedBlocks {
+            block_headers,
+            ordered_proof,
+        }])
+    }
+
+    /// Verifies the given commit decision and returns the events to emit.
+    /// If the commit proof ends the epoch, the verifier moves to the next epoch.
+    pub fn process_commit_decision(
+        &mut self,
+        commit_decision: CommitDecision,
+    ) -> Result<Vec<HeaderClientEvent>, Error> {
+        // Ignore the commit decision if it's not newer than the highest commit
+        let proof_block_info = commit_decision.proof_block_info();
+        let highest_committed_block = self.highest_committed.commit_info();
+        if (proof_block_info.epoch(), proof_block_info.round())
+            <= (
+                highest_committed_block.epoch(),
+                highest_committed_block.round(),
+            )
+        {
+            return Ok(vec![]);
+        }
+
+        // Verify the commit proof
+        self.verify_epoch(proof_block_info)?;
+        commit_decision.verify_commit_proof(&self.epoch_state)?;
+
+        // Update the highest commit
+        Ok(self.update_highest_committed(commit_decision.commit_proof().clone()))
+    }
+
+    /// Verifies the given epoch change proof and returns the events to emit.
+    /// The verifier moves to the last epoch in the proof (any ledger infos
+    /// for epochs that already ended are skipped).
+    pub fn process_epoch_change_proof(
+        &mut self,
+        epoch_change_proof: EpochChangeProof,
+    ) -> Result<Vec<HeaderClientEvent>, Error> {
+        // Verify the epoch change proof against the current epoch state
+        epoch_change_proof
+            .verify(self.epoch_state.as_ref())
+            .map_err(|error| {
+                Error::InvalidMessageError(format!(
+                    "Failed to verify the epoch change proof! Current epoch: {}, Error: {:?}",
+                    self.epoch_state.epoch, error
+                ))
+            })?;
+
+        // Commit each epoch-ending ledger info, and move to the next epoch
+        let mut events = vec![];
+        for ledger_info in epoch_change_proof.ledger_info_with_sigs {
+            if ledger_info.ledger_info().epoch() >= self.epoch_state.epoch {
+                events.extend(self.update_highest_committed(ledger_info));
+            }
+        }
+
+        Ok(events)
+    }
+
+    /// Updates the highest commit with the given (verified) commit proof, and
+    /// returns the events to emit. If the commit proof ends the epoch, the
+    /// verifier moves to the next epoch.
+    fn update_highest_committed(
+        &mut self,
+        commit_proof: LedgerInfoWithSignatures,
+    ) -> Vec<HeaderClientEvent> {
+        // If the commit is beyond the highest ordered block, some ordered blocks
+        // were missed. New ordered blocks will be checked against the commit instead.
+        let committed_block = commit_proof.commit_info();
+        if let Some(ordered_block) = &self.highest_ordered_block {
+            if (committed_block.epoch(), committed_block.round())
+                > (ordered_block.epoch(), ordered_block.round())
+            {
+                self.highest_ordered_block = None;
+            }
+        }
+
+        // Update the highest commit
+        self.highest_committed = commit_proof.clone();
+        let mut events = vec![HeaderClientEvent::Committed(commit_proof.clone())];
+
+        // If the commit proof ends the epoch, move to the next epoch
+        if let Some(next_epoch_state) = commit_proof.ledger_info().next_epoch_state() {
+            let next_epoch_state = Arc::new(next_epoch_state.clone());
+            self.epoch_state = next_epoch_state.clone();
+            self.highest_ordered_block = None;
+            events.push(HeaderClientEvent::NewEpoch(next_epoch_state));
+        }
+
+        events
+    }
+
+    /// Verifies the given block is in the current epoch. Blocks in future epochs
+    /// can't be verified until the epoch change is verified, so the caller should
+    /// fetch an epoch change proof when the verifier is falling behind.
+    fn verify_epoch(&self, block_info: &BlockInfo) -> Result<(), Error> {
+        if block_info.epoch() > self.epoch_state.epoch {
+            return Err(Error::ObserverFallingBehind(format!(
+                "Received message for future epoch: {}, but the current epoch is: {}! Block: {}",
+                block_info.epoch(),
+                self.epoch_state.epoch,
+                block_info
+            )));
+        }
+        if block_info.epoch() != self.epoch_state.epoch {
+            return Err(Error::InvalidMessageError(format!(
+                "Received message for epoch: {}, but the current epoch is: {}! Block: {}",
+                block_info.epoch(),
+                self.epoch_state.epoch,
+                block_info
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use aptos_consensus_types::{quorum_cert::QuorumCert, vote_data::VoteData};
+    use aptos_crypto::{hash::CryptoHash, HashValue};
+    use aptos_types::{
+        block_info::Round,
+        ledger_info::{generate_ledger_info_with_sig, LedgerInfo},
+        validator_signer::ValidatorSigner,
+        validator_verifier::random_validator_verifier,
+    };
+    use claims::assert_matches;
+
+    #[test]
+    fn test_process_ordered_block_headers() {
+        // Create the verifier for the current epoch
+        let current_epoch = 10;
+        let (signers, epoch_state) = create_epoch_state(current_epoch);
+        let trusted_block_info = create_block_info(current_epoch, 1, None);
+        let trusted_ledger_info = create_ledger_info(&signers, trusted_block_info.clone());
+        let mut verifier = HeaderChainVerifier::new(epoch_state, trusted_ledger_info);
+
+        // Process ordered block headers for rounds 2 to 4 and verify the event
+        let block_headers =
+            create_block_headers(&signers, current_epoch, 2..5, trusted_block_info.clone());
+        let ordered_block_headers = create_ordered_block_headers(&signers, block_headers.clone());
+        let events = verifier
+            .process_ordered_block_headers(ordered_block_headers.clone())
+            .unwrap();
+        assert_eq!(events, vec![HeaderClientEvent::OrderedBlocks {
+            block_headers: block_headers.clone(),
+            ordered_proof: ordered_block_headers.ordered_proof().clone(),
+        }]);
+
+        // Process the same headers again and verify they are ignored
+        let events = verifier
+            .process_ordered_block_headers(ordered_block_headers)
+            .unwrap();
+        assert!(events.is_empty());
+
+        // Process overlapping headers (rounds 3 to 6) and verify the old headers are dropped
+        let last_block_info = block_headers.last().unwrap().block_info().clone();
+        let new_block_headers =
+            create_block_headers(&signers, current_epoch, 5..7, last_block_info);
+        let overlapping_block_headers = [&block_headers[1..], &new_block_headers[..]].concat();
+        let ordered_block_headers =
+            create_ordered_block_headers(&signers, overlapping_block_headers);
+        let events = verifier
+            .process_ordered_block_headers(ordered_block_headers.clone())
+            .unwrap();
+        assert_eq!(events, vec![HeaderClientEvent::OrderedBlocks {
+            block_headers: new_block_headers.clone(),
+            ordered_proof: ordered_block_headers.ordered_proof().clone(),
+        }]);
+
+        // Process headers that don't extend the highest ordered block and verify they are rejected
+        let unknown_block_info = create_block_info(current_epoch, 6, None);
+        let block_headers = create_block_headers(&signers, current_epoch, 7..9, unknown_block_info);
+        let ordered_block_headers = create_ordered_block_headers(&signers, block_headers);
+        let error = verifier
+            .process_ordered_block_headers(ordered_block_headers)
+            .unwrap_err();
+        assert_matches!(error, Error::InvalidMessageError(_));
+
+        // Process headers with quorum certs signed by an unknown validator set
+        // (but a valid ordered proof) and verify they are rejected.
+        let (other_signers, _) = create_epoch_state(current_epoch);
+        let last_block_info = new_block_headers.last().unwrap().block_info().clone();
+        let block_headers =
+            create_block_headers(&other_signers, current_epoch, 7..8, last_block_info);
+        let ordered_block_headers = create_ordered_block_headers(&signers, block_headers);
+        let error = verifier
+            .process_ordered_block_headers(ordered_block_headers)
+            .unwrap_err();
+        assert_matches!(error, Error::InvalidMessageError(_));
+
+        // Process headers signed by an unknown validator set and verify they are rejected
+        let last_block_info = new_block_headers.last().unwrap().block_info().clone();
+        let block_headers =
+            create_block_headers(&other_signers, current_epoch, 7..8, last_block_info);
+        let ordered_block_headers = create_ordered_block_headers(&other_signers, block_headers);
+        let error = verifier
+            .process_ordered_block_headers(ordered_block_headers)
+            .unwrap_err();
+        assert_matches!(error, Error::InvalidMessageError(_));
+
+        // Process headers for a future epoch and verify the verifier is falling behind
+        let (next_signers, _) = create_epoch_state(current_epoch + 1);
+        let parent_block_info = create_block_info(current_epoch + 1, 1, None);
+        let block_headers =
+            create_block_headers(&next_signers, current_epoch + 1, 2..3, parent_block_info);
+        let ordered_block_headers = create_ordered_block_headers(&next_signers, block_headers);
+        let error = verifier
+            .process_ordered_block_headers(ordered_block_headers)
+            .unwrap_err();
+        assert_matches!(error, Error::ObserverFallingBehind(_));
+    }
+
+    #[test]
+    fn test_process_ordered_block_headers_after_missed_blocks() {
+        // Create the verifier for the current epoch
+        let current_epoch = 10;
+        let (signers, epoch_state) = create_epoch_state(current_epoch);
+        let trusted_block_info = create_block_info(current_epoch, 1, None);
+        let trusted_ledger_info = create_ledger_info(&signers, trusted_block_info.clone());
+        let mut verifier = HeaderChainVerifier::new(epoch_state, trusted_ledger_info);
+
+        // Process ordered block headers for rounds 2 to 3
+        let block_headers = create_block_headers(&signers, current_epoch, 2..4, trusted_block_info);
+        let ordered_block_headers = create_ordered_block_headers(&signers, block_headers);
+        let events = verifier
+            .process_ordered_block_headers(ordered_block_headers)
+            .unwrap();
+        assert_eq!(events.len(), 1);
+
+        // Miss the ordered blocks for rounds 4 to 5, and verify that the
+        // ordered blocks for rounds 6 to 7 are rejected (they can't be linked).
+        let missed_block_info = create_block_info(current_epoch, 5, None);
+        let block_headers =
+            create_block_headers(&signers, current_epoch, 6..8, missed_block_info.clone());
+        let ordered_block_headers = create_ordered_block_headers(&signers, block_headers.clone());
+        let error = verifier
+            .process_ordered_block_headers(ordered_block_headers.clone())
+            .unwrap_err();
+        assert_matches!(error, Error::InvalidMessageError(_));
+
+        // Process a commit decision for the missed blocks
+        let commit_proof = create_ledger_info(&signers, missed_block_info);
+        let events = verifier
+            .process_commit_decision(CommitDecision::new(commit_proof.clone()))
+            .unwrap();
+        assert_eq!(events, vec![HeaderClientEvent::Committed(commit_proof)]);
+
+        // Verify that the ordered blocks now extend the committed block
+        let events = verifier
+            .process_ordered_block_headers(ordered_block_headers.clone())
+            .unwrap();
+        assert_eq!(events, vec![HeaderClientEvent::OrderedBlocks {
+            block_headers,
+            ordered_proof: ordered_block_headers.ordered_proof().clone(),
+        }]);
+    }
+
+    #[test]
+    fn test_process_commit_decision() {
+        // Create the verifier for the current epoch
+        let current_epoch = 10;
+        let (signers, epoch_state) = create_epoch_state(current_epoch);
+        let trusted_ledger_info =
+            create_ledger_info(&signers, create_block_info(current_epoch, 5, None));
+        let mut verifier = HeaderChainVerifier::new(epoch_state, trusted_ledger_info);
+
+        // Process a stale commit decision and verify it is ignored
+        let commit_proof = create_ledger_info(&signers, create_block_info(current_epoch, 3, None));
+        let events = verifier
+            .process_commit_decision(CommitDecision::new(commit_proof))
+            .unwrap();
+        assert!(events.is_empty());
+
+        // Process a new commit decision and verify the event
+        let commit_proof = create_ledger_info(&signers, create_block_info(current_epoch, 8, None));
+        let events = verifier
+            .process_commit_decision(CommitDecision::new(commit_proof.clone()))
+            .unwrap();
+        assert_eq!(events, vec![HeaderClientEvent::Committed(
+            commit_proof.clone()
+        )]);
+        assert_eq!(verifier.highest_committed(), &commit_proof);
+
+        // Process an epoch-ending commit decision and verify the epoch changes
+        let (next_signers, next_epoch_state) = create_epoch_state(current_epoch + 1);
+        let commit_proof = create_ledger_info(
+            &signers,
+            create_block_info(current_epoch, 9, Some(next_epoch_state.as_ref().clone())),
+        );
+        let events = verifier
+            .process_commit_decision(CommitDecision::new(commit_proof.clone()))
+            .unwrap();
+        assert_eq!(events, vec![
+            HeaderClientEvent::Committed(commit_proof),
+            HeaderClientEvent::NewEpoch(next_epoch_state.clone()),
+        ]);
+        assert_eq!(verifier.epoch_state(), next_epoch_state);
+
+        // Verify that messages signed by the old validator set are now rejected
+        let commit_proof =
+            create_ledger_info(&signers, create_block_info(current_epoch + 1, 1, None));
+        let error = verifier
+            .process_commit_decision(CommitDecision::new(commit_proof))
+            .unwrap_err();
+        assert_matches!(error, Error::InvalidMessageError(_));
+
+        // Verify that messages signed by the new validator set are accepted
+        let parent_block_info = create_block_info(current_epoch + 1, 1, None);
+        let block_headers =
+            create_block_headers(&next_signers, current_epoch + 1, 2..4, parent_block_info);
+        let ordered_block_headers = create_ordered_block_headers(&next_signers, block_headers);
+        let events = verifier
+            .process_ordered_block_headers(ordered_block_headers)
+            .unwrap();
+        assert_eq!(events.len(), 1);
+
+        // Verify that commit decisions for future epochs can't be verified yet
+        let commit_proof =
+            create_ledger_info(&next_signers, create_block_info(current_epoch + 2, 1, None));
+        let error = verifier
+            .process_commit_decision(CommitDecision::new(commit_proof))
+            .unwrap_err();
+        assert_matches!(error, Error::ObserverFallingBehind(_));
+    }
+
+    #[test]
+    fn test_process_epoch_change_proof() {
+        // Create the verifier for the current epoch
+        let current_epoch = 10;
+        let (signers, epoch_state) = create_epoch_state(current_epoch);
+        let trusted_ledger_info =
+            create_ledger_info(&signers, create_block_info(current_epoch, 5, None));
+        let mut verifier = HeaderChainVerifier::new(epoch_state, trusted_ledger_info);
+
+        // Create the epoch-ending ledger infos for the next two epochs
+        let (next_signers, next_epoch_state) = create_epoch_state(current_epoch + 1);
+        let (_, final_epoch_state) = create_epoch_state(current_epoch + 2);
+        let epoch_ending_ledger_info = create_ledger_info(
+            &signers,
+            create_block_info(current_epoch, 9, Some(next_epoch_state.as_ref().clone())),
+        );
+        let next_epoch_ending_ledger_info = create_ledger_info(
+            &next_signers,
+            create_block_info(
+                current_epoch + 1,
+                3,
+                Some(final_epoch_state.as_ref().clone()),
+            ),
+        );
+
+        // Process an epoch change proof signed by an unknown validator set and verify it is rejected
+        let (other_signers, _) = create_epoch_state(current_epoch);
+        let invalid_ledger_info = create_ledger_info(
+            &other_signers,
+            create_block_info(current_epoch, 9, Some(next_epoch_state.as_ref().clone())),
+        );
+        let error = verifier
+            .process_epoch_change_proof(EpochChangeProof::new(vec![invalid_ledger_info], false))
+            .unwrap_err();
+        assert_matches!(error, Error::InvalidMessageError(_));
+
+        // Process the epoch change proof and verify the verifier moves to the final epoch
+        let epoch_change_proof = EpochChangeProof::new(
+            vec![
+                epoch_ending_ledger_info.clone(),
+                next_epoch_ending_ledger_info.clone(),
+            ],
+            false,
+        );
+        let events = verifier
+            .process_epoch_change_proof(epoch_change_proof.clone())
+            .unwrap();
+        assert_eq!(events, vec![
+            HeaderClientEvent::Committed(epoch_ending_ledger_info),
+            HeaderClientEvent::NewEpoch(next_epoch_state),
+            HeaderClientEvent::Committed(next_epoch_ending_ledger_info.clone()),
+            HeaderClientEvent::NewEpoch(final_epoch_state.clone()),
+        ]);
+        assert_eq!(verifier.epoch_state(), final_epoch_state);
+        assert_eq!(verifier.highest_committed(), &next_epoch_ending_ledger_info);
+
+        // Process the same epoch change proof again and verify it is rejected (it's stale)
+        let error = verifier
+            .process_epoch_change_proof(epoch_change_proof)
+            .unwrap_err();
+        assert_matches!(error, Error::InvalidMessageError(_));
+    }
+
+    /// Creates a block info for the given epoch and round
+    fn create_block_info(
+        epoch: u64,
+        round: Round,
+        next_epoch_state: Option<EpochState>,
+    ) -> BlockInfo {
+        BlockInfo::new(
+            epoch,
+            round,
+            HashValue::random(),
+            HashValue::random(),
+            round,
+            round,
+            next_epoch_state,
+        )
+    }
+
+    /// Creates a random validator set and the epoch state for the given epoch
+    fn create_epoch_state(epoch: u64) -> (Vec<ValidatorSigner>, Arc<EpochState>) {
+        let (signers, validator_verifier) = random_validator_verifier(4, None, false);
+        let epoch_state = EpochState::new(epoch, validator_verifier);
+        (signers, Arc::new(epoch_state))
+    }
+
+    /// Creates a ledger info for the given block, signed by all the given signers
+    fn create_ledger_info(
+        signers: &[ValidatorSigner],
+        block_info: BlockInfo,
+    ) -> LedgerInfoWithSignatures {
+        generate_ledger_info_with_sig(signers, LedgerInfo::new(block_info, HashValue::zero()))
+    }
+
+    /// Creates a quorum cert for the given block, signed by all the given signers
+    fn create_quorum_cert(signers: &[ValidatorSigner], certified_block: BlockInfo) -> QuorumCert {
+        let parent_block =
+            create_block_info(certified_block.epoch(), certified_block.round() - 1, None);
+        let vote_data = VoteData::new(certified_block, parent_block);
+        let ledger_info = generate_ledger_info_with_sig(
+            signers,
+            LedgerInfo::new(BlockInfo::empty(), vote_data.hash()),
+        );
+        QuorumCert::new(vote_data, ledger_info)
+    }
+
+    /// Creates chained block headers for the given rounds (the first header
+    /// extends the given parent block). The quorum certs are signed by all
+    /// the given signers.
+    fn create_block_headers(
+        signers: &[ValidatorSigner],
+        epoch: u64,
+        rounds: std::ops::Range<Round>,
+        parent_block_info: BlockInfo,
+    ) -> Vec<BlockHeader> {
+        let mut block_headers = vec![];
+        let mut parent_block_info = parent_block_info;
+        for round in rounds {
+            let block_info = create_block_info(epoch, round, None);
+            let quorum_cert = create_quorum_cert(signers, parent_block_info);
+            block_headers.push(BlockHeader::new(block_info.clone(), quorum_cert));
+            parent_block_info = block_info;
+        }
+        block_headers
+    }
+
+    /// Creates the ordered block headers message for the given block headers
+    /// (with an ordered proof for the last block, signed by all the given signers).
+    fn create_ordered_block_headers(
+        signers: &[ValidatorSigner],
+        block_headers: Vec<BlockHeader>,
+    ) -> OrderedBlockHeaders {
+        let last_block_info = block_headers.last().unwrap().block_info().clone();
+        let ordered_proof = create_ledger_info(signers, last_block_info);
+        OrderedBlockHeaders::new(block_headers, ordered_proof)
+    }
+}
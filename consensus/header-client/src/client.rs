@@ -0,0 +1,367 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::chain_verifier::{HeaderChainVerifier, HeaderClientEvent};
+use aptos_channels::aptos_channel::Receiver;
+use aptos_config::{config::ConsensusObserverConfig, network_id::PeerNetworkId};
+use aptos_consensus::consensus_observer::{
+    common::{
+        error::Error,
+        logging::{LogEntry, LogEvent, LogSchema},
+    },
+    network::{
+        network_handler::ConsensusObserverNetworkMessage,
+        observer_client::ConsensusObserverClient,
+        observer_message::{
+            ConsensusObserverDirectSend, ConsensusObserverMessage, ConsensusObserverRequest,
+            ConsensusObserverResponse,
+        },
+    },
+};
+use aptos_logger::{error, info, warn};
+use aptos_network::application::interface::NetworkClient;
+use aptos_time_service::{TimeService, TimeServiceTrait};
+use aptos_types::{
+    epoch_change::EpochChangeProof, epoch_state::EpochState, ledger_info::LedgerInfoWithSignatures,
+};
+use futures::{SinkExt, StreamExt};
+use futures_channel::mpsc;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::time::interval;
+use tokio_stream::wrappers::IntervalStream;
+
+/// A lightweight consensus client that holds a header-only subscription to a
+/// single publisher, verifies the received messages against the epoch state,
+/// and forwards the verified chain updates to the embedder. Blocks are never
+/// executed, so the client can follow the chain cheaply. If the client misses
+/// an epoch change, it fetches an epoch change proof from the publisher.
+///
+/// The embedder is responsible for setting up the network, and for creating
+/// the network handler with the observer enabled (so that the direct sends
+/// from the publisher are forwarded to the client).
+pub struct ConsensusHeaderClient {
+    // The configuration for the consensus observer
+    consensus_observer_config: ConsensusObserverConfig,
+
+    // The consensus observer client to send network messages
+    consensus_observer_client:
+        Arc<ConsensusObserverClient<NetworkClient<ConsensusObserverMessage>>>,
+
+    // The publisher to subscribe to
+    publisher_peer: PeerNetworkId,
+
+    // The verifier that follows the chain
+    chain_verifier: HeaderChainVerifier,
+
+    // The sender for the verified chain updates
+    event_sender: mpsc::Sender<HeaderClientEvent>,
+
+    // The time at which the last verified update was received
+    last_progress_time: Instant,
+
+    // The time service (used to check subscription progress)
+    time_service: TimeService,
+}
+
+impl ConsensusHeaderClient {
+    /// Creates a new header client that follows the chain from the given trusted
+    /// ledger info. Returns the client and the receiver for the chain updates.
+    pub fn new(
+        consensus_observer_config: ConsensusObserverConfig,
+        consensus_observer_client: Arc<
+            ConsensusObserverClient<NetworkClient<ConsensusObserverMessage>>,
+        >,
+        publisher_peer: PeerNetworkId,
+        epoch_state: Arc<EpochState>,
+        trusted_ledger_info: LedgerInfoWithSignatures,
+        time_service: TimeService,
+    ) -> (Self, mpsc::Receiver<HeaderClientEvent>) {
+        // Create the event sender and receiver
+        let max_network_channel_size = consensus_observer_config.max_network_channel_size as usize;
+        let (event_sender, event_receiver) = mpsc::channel(max_network_channel_size);
+
+        // Create the header client
+        let header_client = Self {
+            consensus_observer_config,
+            consensus_observer_client,
+            publisher_peer,
+            chain_verifier: HeaderChainVerifier::new(epoch_state, trusted_ledger_info),
+            event_sender,
+            last_progress_time: time_service.now(),
+            time_service,
+        };
+
+        (header_client, event_receiver)
+    }
+
+    /// Sends a header-only subscription request to the publisher
+    async fn subscribe(&mut self) -> Result<(), Error> {
+        // Reset the progress time (to give the new subscription time to make progress)
+        self.last_progress_time = self.time_service.now();
+
+        // Send the subscription request and wait for the response
+        let response = self
+            .consensus_observer_client
+            .send_rpc_request_to_peer(
+                &self.publisher_peer,
+                ConsensusObserverRequest::SubscribeHeaders,
+                self.consensus_observer_config.network_request_timeout_ms,
+            )
+            .await?;
+
+        // Verify the response type
+        match response {
+            ConsensusObserverResponse::SubscribeAck => {
+                info!(LogSchema::new(LogEntry::ConsensusHeaderClient)
+                    .event(LogEvent::Subscription)
+                    .message(&format!(
+                        "Subscribed to consensus header updates from peer: {}!",
+                        self.publisher_peer
+                    )));
+                Ok(())
+            },
+            response => Err(Error::InvalidMessageError(format!(
+                "Got unexpected response type for header subscription request: {:?}",
+                response.get_label()
+            ))),
+        }
+    }
+
+    /// Subscribes to the publisher (logging any failures)
+    async fn subscribe_and_log_errors(&mut self) {
+        if let Err(error) = self.subscribe().await {
+            warn!(LogSchema::new(LogEntry::ConsensusHeaderClient)
+                .event(LogEvent::Subscription)
+                .message(&format!(
+                    "Failed to subscribe to consensus header updates from peer: {}! Error: {:?}",
+                    self.publisher_peer, error
+                )));
+        }
+    }
+
+    /// Sends an epoch change proof request (for the current epoch) to the publisher
+    async fn request_epoch_change_proof(
+        &self,
+        known_epoch: u64,
+    ) -> Result<EpochChangeProof, Error> {
+        // Send the epoch change proof request and wait for the response
+        let response = self
+            .consensus_observer_client
+            .send_rpc_request_to_peer(
+                &self.publisher_peer,
+                ConsensusObserverRequest::GetEpochChangeProof(known_epoch),
+                self.consensus_observer_config.network_request_timeout_ms,
+            )
+            .await?;
+
+        // Verify the response type
+        match response {
+            ConsensusObserverResponse::EpochChangeProof(epoch_change_proof) => {
+                Ok(epoch_change_proof)
+            },
+            response => Err(Error::InvalidMessageError(format!(
+                "Got unexpected response type for epoch change proof request: {:?}",
+                response.get_label()
+            ))),
+        }
+    }
+
+    /// Fetches and verifies the epoch changes since the current epoch (e.g., if the
+    /// client missed the epoch-ending commit decision), and returns the chain updates
+    /// to forward. Any failures are logged.
+    async fn catch_up_on_epoch_changes(&mut self) -> Vec<HeaderClientEvent> {
+        let mut events = vec![];
+        loop {
+            // Fetch and verify the epoch change proof for the current epoch
+            let known_epoch = self.chain_verifier.epoch_state().epoch;
+            let result = match self.request_epoch_change_proof(known_epoch).await {
+                Ok(epoch_change_proof) => {
+                    let more = epoch_change_proof.more;
+                    self.chain_verifier
+                        .process_epoch_change_proof(epoch_change_proof)
+                        .map(|new_events| (new_events, more))
+                },
+                Err(error) => Err(error),
+            };
+
+            // Process the result (storage may truncate the proof, so fetch again if needed)
+            match result {
+                Ok((new_events, more)) => {
+                    info!(
+                        LogSchema::new(LogEntry::ConsensusHeaderClient).message(&format!(
+                            "Caught up on epoch changes from peer: {}! New epoch: {}",
+                            self.publisher_peer,
+                            self.chain_verifier.epoch_state().epoch
+                        ))
+                    );
+                    events.extend(new_events);
+                    if !more {
+                        break;
+                    }
+                },
+                Err(error) => {
+                    warn!(LogSchema::new(LogEntry::ConsensusHeaderClient)
+                        .error(&error)
+                        .message(&format!(
+                            "Failed to catch up on epoch changes from peer: {}! Known epoch: {}",
+                            self.publisher_peer, known_epoch
+                        )));
+                    break;
+                },
+            }
+        }
+
+        if !events.is_empty() {
+            self.last_progress_time = self.time_service.now();
+        }
+        events
+    }
+
+    /// Checks for missed epoch changes and resubscribes to the publisher if no
+    /// verified updates were received recently. Returns the chain updates to forward.
+    async fn check_progress(&mut self) -> Vec<HeaderClientEvent> {
+        let max_subscription_timeout =
+            Duration::from_millis(self.consensus_observer_config.max_subscription_timeout_ms);
+        let duration_since_progress = self
+            .time_service
+            .now()
+            .duration_since(self.last_progress_time);
+        if duration_since_progress <= max_subscription_timeout {
+            return vec![];
+        }
+
+        // The client may have missed an epoch change (and the publisher may only
+        // be sending messages for the old epoch, e.g., if it's also behind).
+        warn!(LogSchema::new(LogEntry::ConsensusHeaderClient)
+            .event(LogEvent::Subscription)
+            .message(&format!(
+                "No progress from peer: {} for {:?}. Checking for epoch changes and resubscribing!",
+                self.publisher_peer, duration_since_progress
+            )));
+        let events = self.catch_up_on_epoch_changes().await;
+        self.subscribe_and_log_errors().await;
+        events
+    }
+
+    /// Verifies the given network message and returns the chain updates to forward.
+    /// If the message is for a future epoch, the client catches up on the epoch changes.
+    async fn process_network_message(
+        &mut self,
+        network_message: ConsensusObserverNetworkMessage,
+    ) -> Vec<HeaderClientEvent> {
+        // Ignore any messages that are not from the publisher
+        let (peer_network_id, message) = network_message.into_parts();
+        if peer_network_id != self.publisher_peer {
+            warn!(LogSchema::new(LogEntry::ConsensusHeaderClient)
+                .peer(&peer_network_id)
+                .message(&format!(
+                    "Received message that was not from the publisher! Ignoring: {}",
+                    message.get_label()
+                )));
+            return vec![];
+        }
+
+        // Verify the message and update the chain
+        let message_label = message.get_label();
+        let result = match message {
+            ConsensusObserverDirectSend::OrderedBlockHeaders(ordered_block_headers) => self
+                .chain_verifier
+                .process_ordered_block_headers(ordered_block_headers),
+            ConsensusObserverDirectSend::CommitDecision(commit_decision) => {
+                self.chain_verifier.process_commit_decision(commit_decision)
+            },
+            message => Err(Error::InvalidMessageError(format!(
+                "Received unexpected message type on a header subscription: {}",
+                message.get_label()
+            ))),
+        };
+
+        match result {
+            Ok(events) => {
+                if !events.is_empty() {
+                    self.last_progress_time = self.time_service.now();
+                }
+                events
+            },
+            Err(Error::ObserverFallingBehind(message)) => {
+                // The message is for a future epoch. Catch up on the epoch changes
+                // (the message itself is dropped, later messages will be verified).
+                info!(LogSchema::new(LogEntry::ConsensusHeaderClient)
+                    .peer(&peer_network_id)
+                    .message_type(message_label)
+                    .message(&format!(
+                        "Received a message for a future epoch: {}. Fetching epoch changes!",
+                        message
+                    )));
+                self.catch_up_on_epoch_changes().await
+            },
+            Err(error) => {
+                warn!(LogSchema::new(LogEntry::ConsensusHeaderClient)
+                    .error(&error)
+                    .peer(&peer_network_id)
+                    .message_type(message_label)
+                    .message("Failed to verify the received message!"));
+                vec![]
+            },
+        }
+    }
+
+    /// Forwards the given chain updates to the embedder. Returns false
+    /// if the event receiver was dropped.
+    async fn forward_events(&mut self, events: Vec<HeaderClientEvent>) -> bool {
+        for event in events {
+            if self.event_sender.send(event).await.is_err() {
+                info!(LogSchema::new(LogEntry::ConsensusHeaderClient)
+                    .message("The event receiver was dropped. Stopping the header client!"));
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Starts the header client. The client runs until the network
+    /// message receiver is closed, or the event receiver is dropped.
+    pub async fn start(
+        mut self,
+        mut observer_message_receiver: Receiver<(), ConsensusObserverNetworkMessage>,
+    ) {
+        // Create a progress check ticker
+        let mut progress_check_interval = IntervalStream::new(interval(Duration::from_millis(
+            self.consensus_observer_config.progress_check_interval_ms,
+        )))
+        .fuse();
+
+        // Subscribe to the publisher
+        self.subscribe_and_log_errors().await;
+
+        // Start the header client loop
+        info!(LogSchema::new(LogEntry::ConsensusHeaderClient)
+            .message("Starting the consensus header client loop!"));
+        loop {
+            tokio::select! {
+                Some(network_message) = observer_message_receiver.next() => {
+                    let events = self.process_network_message(network_message).await;
+                    if !self.forward_events(events).await {
+                        return;
+                    }
+                },
+                _ = progress_check_interval.select_next_some() => {
+                    let events = self.check_progress().await;
+                    if !self.forward_events(events).await {
+                        return;
+                    }
+                },
+                else => {
+                    break; // Exit the header client loop
+                }
+            }
+        }
+
+        // Log the exit of the header client loop
+        error!(LogSchema::new(LogEntry::ConsensusHeaderClient)
+            .message("The consensus header client loop exited unexpectedly!"));
+    }
+}
@@ -0,0 +1,12 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+//! A lightweight client that follows the chain using header-only subscriptions
+//! (i.e., block headers, ordered proofs and commit decisions are verified against
+//! the epoch state, but the blocks are never executed). This is useful for bridges
+//! and monitoring services that only need to track the ordered and committed blocks.
+
+pub mod chain_verifier;
+pub mod client;
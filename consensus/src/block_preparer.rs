@@ -5,12 +5,12 @@ use crate::{
     counters::{self, MAX_TXNS_FROM_BLOCK_TO_EXECUTE, TXN_SHUFFLE_SECONDS},
     payload_manager::TPayloadManager,
     transaction_deduper::TransactionDeduper,
-    transaction_shuffler::TransactionShuffler,
 };
 use aptos_config::config::BlockTransactionFilterConfig;
 use aptos_consensus_types::{block::Block, quorum_cert::QuorumCert};
 use aptos_crypto::HashValue;
 use aptos_executor_types::ExecutorResult;
+use aptos_transaction_shuffler::TransactionShuffler;
 use aptos_types::transaction::SignedTransaction;
 use fail::fail_point;
 use futures::future::Shared;
@@ -74,26 +74,17 @@ impl BlockPreparer {
 
         // Transaction filtering, deduplication and shuffling are CPU intensive tasks, so we run them in a blocking task.
         let result = tokio::task::spawn_blocking(move || {
-            let filtered_txns = filter_block_transactions(
-                txn_filter_config,
+            Ok(prepare_block_transactions(
+                &txn_filter_config,
+                txn_deduper.as_ref(),
+                txn_shuffler.as_ref(),
                 block_id,
                 block_author,
                 block_epoch,
                 block_timestamp_usecs,
                 txns,
-            );
-            let deduped_txns = txn_deduper.dedup(filtered_txns);
-            let mut shuffled_txns = {
-                let _timer = TXN_SHUFFLE_SECONDS.start_timer();
-
-                txn_shuffler.shuffle(deduped_txns)
-            };
-
-            if let Some(max_txns_from_block_to_execute) = max_txns_from_block_to_execute {
-                shuffled_txns.truncate(max_txns_from_block_to_execute as usize);
-            }
-            MAX_TXNS_FROM_BLOCK_TO_EXECUTE.observe(shuffled_txns.len() as f64);
-            Ok(shuffled_txns)
+                max_txns_from_block_to_execute,
+            ))
         })
         .await
         .expect("Failed to spawn blocking task for transaction generation");
@@ -102,9 +93,46 @@ impl BlockPreparer {
     }
 }
 
+/// Filters, deduplicates and shuffles the transactions of a block, and truncates them to the max
+/// number of transactions to execute. Shared with the consensus replay tool, so the replayed
+/// blocks go through the exact same steps.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn prepare_block_transactions(
+    txn_filter_config: &BlockTransactionFilterConfig,
+    txn_deduper: &dyn TransactionDeduper,
+    txn_shuffler: &dyn TransactionShuffler,
+    block_id: HashValue,
+    block_author: Option<AccountAddress>,
+    block_epoch: u64,
+    block_timestamp_usecs: u64,
+    txns: Vec<SignedTransaction>,
+    max_txns_from_block_to_execute: Option<u64>,
+) -> Vec<SignedTransaction> {
+    let filtered_txns = filter_block_transactions(
+        txn_filter_config,
+        block_id,
+        block_author,
+        block_epoch,
+        block_timestamp_usecs,
+        txns,
+    );
+    let deduped_txns = txn_deduper.dedup(filtered_txns);
+    let mut shuffled_txns = {
+        let _timer = TXN_SHUFFLE_SECONDS.start_timer();
+
+        txn_shuffler.shuffle(deduped_txns)
+    };
+
+    if let Some(max_txns_from_block_to_execute) = max_txns_from_block_to_execute {
+        shuffled_txns.truncate(max_txns_from_block_to_execute as usize);
+    }
+    MAX_TXNS_FROM_BLOCK_TO_EXECUTE.observe(shuffled_txns.len() as f64);
+    shuffled_txns
+}
+
 /// Filters transactions in a block based on the filter configuration
 fn filter_block_transactions(
-    txn_filter_config: Arc<BlockTransactionFilterConfig>,
+    txn_filter_config: &BlockTransactionFilterConfig,
     block_id: HashValue,
     block_author: Option<AccountAddress>,
     block_epoch: u64,
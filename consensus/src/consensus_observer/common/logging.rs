@@ -38,6 +38,7 @@ impl LogSchema<'_> {
 #[derive(Clone, Copy, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum LogEntry {
+    ConsensusHeaderClient,
     ConsensusObserver,
     ConsensusPublisher,
     GetDownstreamPeers,
@@ -11,7 +11,7 @@ use aptos_consensus_types::{
 use aptos_crypto::{hash::CryptoHash, HashValue};
 use aptos_types::{
     block_info::{BlockInfo, Round},
-    epoch_change::Verifier,
+    epoch_change::{EpochChangeProof, Verifier},
     epoch_state::EpochState,
     ledger_info::LedgerInfoWithSignatures,
     transaction::SignedTransaction,
@@ -84,6 +84,8 @@ impl Display for ConsensusObserverMessage {
 pub enum ConsensusObserverRequest {
     Subscribe,
     Unsubscribe,
+    SubscribeHeaders,
+    GetEpochChangeProof(u64), // The highest epoch known to the requester
 }
 
 impl ConsensusObserverRequest {
@@ -92,6 +94,8 @@ impl ConsensusObserverRequest {
         match self {
             ConsensusObserverRequest::Subscribe => "subscribe",
             ConsensusObserverRequest::Unsubscribe => "unsubscribe",
+            ConsensusObserverRequest::SubscribeHeaders => "subscribe_headers",
+            ConsensusObserverRequest::GetEpochChangeProof(_) => "get_epoch_change_proof",
         }
     }
 }
@@ -107,6 +111,7 @@ impl Display for ConsensusObserverRequest {
 pub enum ConsensusObserverResponse {
     SubscribeAck,
     UnsubscribeAck,
+    EpochChangeProof(EpochChangeProof),
 }
 
 impl ConsensusObserverResponse {
@@ -115,6 +120,7 @@ impl ConsensusObserverResponse {
         match self {
             ConsensusObserverResponse::SubscribeAck => "subscribe_ack",
             ConsensusObserverResponse::UnsubscribeAck => "unsubscribe_ack",
+            ConsensusObserverResponse::EpochChangeProof(_) => "epoch_change_proof",
         }
     }
 }
@@ -132,6 +138,7 @@ pub enum ConsensusObserverDirectSend {
     CommitDecision(CommitDecision),
     BlockPayload(BlockPayload),
     OrderedBlockWithWindow(OrderedBlockWithWindow),
+    OrderedBlockHeaders(OrderedBlockHeaders),
 }
 
 impl ConsensusObserverDirectSend {
@@ -142,6 +149,25 @@ impl ConsensusObserverDirectSend {
             ConsensusObserverDirectSend::CommitDecision(_) => "commit_decision",
             ConsensusObserverDirectSend::BlockPayload(_) => "block_payload",
             ConsensusObserverDirectSend::OrderedBlockWithWindow(_) => "ordered_block_with_window",
+            ConsensusObserverDirectSend::OrderedBlockHeaders(_) => "ordered_block_headers",
+        }
+    }
+
+    /// Returns the message to send to header-only subscribers in place of this
+    /// message, or None if header-only subscribers should not receive it.
+    pub fn to_header_only(&self) -> Option<ConsensusObserverDirectSend> {
+        match self {
+            ConsensusObserverDirectSend::OrderedBlock(ordered_block) => Some(
+                ConsensusObserverDirectSend::OrderedBlockHeaders(ordered_block.to_headers()),
+            ),
+            ConsensusObserverDirectSend::OrderedBlockWithWindow(ordered_block_with_window) => {
+                Some(ConsensusObserverDirectSend::OrderedBlockHeaders(
+                    ordered_block_with_window.ordered_block().to_headers(),
+                ))
+            },
+            ConsensusObserverDirectSend::CommitDecision(_)
+            | ConsensusObserverDirectSend::OrderedBlockHeaders(_) => Some(self.clone()),
+            ConsensusObserverDirectSend::BlockPayload(_) => None,
         }
     }
 }
@@ -172,6 +198,13 @@ impl Display for ConsensusObserverDirectSend {
                     ordered_block_with_window.ordered_block.proof_block_info(),
                 )
             },
+            ConsensusObserverDirectSend::OrderedBlockHeaders(ordered_block_headers) => {
+                write!(
+                    f,
+                    "OrderedBlockHeaders: {}",
+                    ordered_block_headers.proof_block_info()
+                )
+            },
         }
     }
 }
@@ -275,6 +308,200 @@ impl OrderedBlock {
             ))
         })
     }
+
+    /// Returns the headers of the ordered blocks (without the block payloads).
+    /// The block info of each header is the one certified by the quorum cert of
+    /// the next block (or by the ordered proof, for the last block).
+    pub fn to_headers(&self) -> OrderedBlockHeaders {
+        let certified_block_infos = self
+            .blocks
+            .iter()
+            .skip(1)
+            .map(|block| block.quorum_cert().certified_block().clone())
+            .chain(std::iter::once(self.proof_block_info().clone()));
+        let block_headers = self
+            .blocks
+            .iter()
+            .zip(certified_block_infos)
+            .map(|(block, block_info)| BlockHeader::new(block_info, block.quorum_cert().clone()))
+            .collect();
+        OrderedBlockHeaders::new(block_headers, self.ordered_proof.clone())
+    }
+}
+
+/// The metadata of a block, without the payload. The block info is certified by
+/// the quorum cert of the child block (or the ordered proof), and the quorum cert
+/// of the block certifies the parent block info. The block author is not included,
+/// as it can't be authenticated without the block payload.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct BlockHeader {
+    block_info: BlockInfo,
+    quorum_cert: QuorumCert,
+}
+
+impl BlockHeader {
+    pub fn new(block_info: BlockInfo, quorum_cert: QuorumCert) -> Self {
+        Self {
+            block_info,
+            quorum_cert,
+        }
+    }
+
+    /// Returns a reference to the block info
+    pub fn block_info(&self) -> &BlockInfo {
+        &self.block_info
+    }
+
+    /// Returns a reference to the quorum cert of the block (certifying the parent)
+    pub fn quorum_cert(&self) -> &QuorumCert {
+        &self.quorum_cert
+    }
+
+    /// Returns a reference to the parent block info
+    pub fn parent_block_info(&self) -> &BlockInfo {
+        self.quorum_cert.certified_block()
+    }
+
+    /// Returns the block ID
+    pub fn id(&self) -> HashValue {
+        self.block_info.id()
+    }
+
+    /// Returns the parent block ID
+    pub fn parent_id(&self) -> HashValue {
+        self.parent_block_info().id()
+    }
+
+    /// Returns the block epoch
+    pub fn epoch(&self) -> u64 {
+        self.block_info.epoch()
+    }
+
+    /// Returns the block round
+    pub fn round(&self) -> Round {
+        self.block_info.round()
+    }
+
+    /// Returns the block timestamp
+    pub fn timestamp_usecs(&self) -> u64 {
+        self.block_info.timestamp_usecs()
+    }
+}
+
+/// OrderedBlockHeaders message contains the headers of the ordered blocks and the
+/// proof of the ordering. This is sent to header-only subscribers instead of the
+/// ordered blocks (and the block payloads are never sent to them).
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct OrderedBlockHeaders {
+    block_headers: Vec<BlockHeader>,
+    ordered_proof: LedgerInfoWithSignatures,
+}
+
+impl OrderedBlockHeaders {
+    pub fn new(block_headers: Vec<BlockHeader>, ordered_proof: LedgerInfoWithSignatures) -> Self {
+        Self {
+            block_headers,
+            ordered_proof,
+        }
+    }
+
+    /// Returns a reference to the ordered block headers
+    pub fn block_headers(&self) -> &Vec<BlockHeader> {
+        &self.block_headers
+    }
+
+    /// Returns a reference to the ordered proof
+    pub fn ordered_proof(&self) -> &LedgerInfoWithSignatures {
+        &self.ordered_proof
+    }
+
+    /// Returns a reference to the ordered proof block info
+    pub fn proof_block_info(&self) -> &BlockInfo {
+        self.ordered_proof.commit_info()
+    }
+
+    /// Consumes the ordered block headers and returns the inner parts
+    pub fn into_parts(self) -> (Vec<BlockHeader>, LedgerInfoWithSignatures) {
+        (self.block_headers, self.ordered_proof)
+    }
+
+    /// Verifies the ordered block headers and returns an error if the data is invalid.
+    /// The last header must match the ordered proof, and every other header must match
+    /// the block info certified by the quorum cert of the next header.
+    /// Note: this does not check the ordered proof or the quorum certs.
+    pub fn verify_ordered_block_headers(&self) -> Result<(), Error> {
+        // Verify that we have at least one block header
+        let last_block_header = match self.block_headers.last() {
+            Some(block_header) => block_header,
+            None => {
+                return Err(Error::InvalidMessageError(
+                    "Received empty ordered block headers!".to_string(),
+                ));
+            },
+        };
+
+        // Verify the last block info matches the ordered proof block info
+        if last_block_header.block_info() != self.proof_block_info() {
+            return Err(Error::InvalidMessageError(format!(
+                "Last block header does not match the ordered proof! Number of headers: {:?}, Last block header: {}, Ordered proof block: {}",
+                self.block_headers.len(),
+                last_block_header.block_info(),
+                self.proof_block_info()
+            )));
+        }
+
+        // Verify the headers are correctly chained together, i.e., each block info
+        // is the one certified by the quorum cert of the next header.
+        for (block_header, next_block_header) in self
+            .block_headers
+            .iter()
+            .zip(self.block_headers.iter().skip(1))
+        {
+            if block_header.block_info() != next_block_header.parent_block_info() {
+                return Err(Error::InvalidMessageError(format!(
+                    "Block header does not match the parent certified by the next header! Block header: {}, Certified parent: {}",
+                    block_header.block_info(),
+                    next_block_header.parent_block_info()
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Verifies the ordered proof and the quorum certs of the block headers,
+    /// and returns an error if any of them is invalid.
+    pub fn verify_ordered_proof(&self, epoch_state: &EpochState) -> Result<(), Error> {
+        // Verify the ordered proof
+        epoch_state.verify(&self.ordered_proof).map_err(|error| {
+            Error::InvalidMessageError(format!(
+                "Failed to verify ordered proof ledger info: {:?}, Error: {:?}",
+                self.proof_block_info(),
+                error
+            ))
+        })?;
+
+        // Verify the quorum certs of the block headers
+        for block_header in &self.block_headers {
+            let quorum_cert = block_header.quorum_cert();
+            if quorum_cert.certified_block().epoch() != epoch_state.epoch {
+                return Err(Error::InvalidMessageError(format!(
+                    "Block header quorum cert is not for the current epoch: {}! Certified block: {}",
+                    epoch_state.epoch,
+                    quorum_cert.certified_block()
+                )));
+            }
+            quorum_cert.verify(&epoch_state.verifier).map_err(|error| {
+                Error::InvalidMessageError(format!(
+                    "Failed to verify block header quorum cert: {}, Error: {:?}",
+                    quorum_cert.certified_block(),
+                    error
+                ))
+            })?;
+        }
+
+        Ok(())
+    }
 }
 
 /// OrderedBlockWithWindow message contains the ordered blocks, and
@@ -1048,6 +1275,7 @@ mod test {
         },
         pipelined_block::OrderedBlockWindow,
         quorum_cert::QuorumCert,
+        vote_data::VoteData,
     };
     use aptos_crypto::{ed25519::Ed25519PrivateKey, HashValue, PrivateKey, SigningKey, Uniform};
     use aptos_types::{
@@ -1484,6 +1712,133 @@ mod test {
         ordered_block.verify_ordered_blocks().unwrap();
     }
 
+    #[test]
+    fn test_ordered_block_headers() {
+        // Create multiple pipelined blocks that are chained together
+        // (i.e., the quorum cert of each block certifies the previous block).
+        let current_epoch = 0;
+        let mut pipelined_blocks = vec![];
+        let mut parent_block_info = create_block_info_for_round(current_epoch, 0);
+        for round in 1..6 {
+            let block_info = create_block_info_for_round(current_epoch, round);
+            let quorum_cert = create_quorum_cert(parent_block_info);
+            let pipelined_block =
+                create_pipelined_block_with_quorum_cert(block_info.clone(), quorum_cert);
+            pipelined_blocks.push(pipelined_block);
+            parent_block_info = block_info;
+        }
+
+        // Create an ordered block with the pipelined blocks and proof
+        let ordered_proof = LedgerInfoWithSignatures::new(
+            LedgerInfo::new(parent_block_info, HashValue::random()),
+            AggregateSignature::empty(),
+        );
+        let ordered_block = OrderedBlock::new(pipelined_blocks.clone(), ordered_proof.clone());
+
+        // Convert the ordered block to headers and verify the headers match the blocks
+        let ordered_block_headers = ordered_block.to_headers();
+        assert_eq!(ordered_block_headers.ordered_proof(), &ordered_proof);
+        for (block_header, pipelined_block) in ordered_block_headers
+            .block_headers()
+            .iter()
+            .zip(pipelined_blocks.iter())
+        {
+            assert_eq!(block_header.id(), pipelined_block.id());
+            assert_eq!(block_header.parent_id(), pipelined_block.parent_id());
+            assert_eq!(block_header.round(), pipelined_block.round());
+            assert_eq!(block_header.quorum_cert(), pipelined_block.quorum_cert());
+        }
+
+        // Verify the ordered block headers and ensure they pass
+        ordered_block_headers
+            .verify_ordered_block_headers()
+            .unwrap();
+
+        // Modify the timestamp of a header in the middle of the chain and ensure verification fails
+        let (block_headers, ordered_proof) = ordered_block_headers.into_parts();
+        let mut modified_block_headers = block_headers.clone();
+        let block_info = modified_block_headers[2].block_info().clone();
+        modified_block_headers[2] = BlockHeader::new(
+            create_block_info_with_timestamp(&block_info, block_info.timestamp_usecs() + 1),
+            modified_block_headers[2].quorum_cert().clone(),
+        );
+        let ordered_block_headers =
+            OrderedBlockHeaders::new(modified_block_headers, ordered_proof.clone());
+        let error = ordered_block_headers
+            .verify_ordered_block_headers()
+            .unwrap_err();
+        assert_matches!(error, Error::InvalidMessageError(_));
+
+        // Modify the timestamp of the last header and ensure verification fails
+        // (the last header no longer matches the ordered proof).
+        let mut modified_block_headers = block_headers.clone();
+        let last_block_header = modified_block_headers.pop().unwrap();
+        let block_info = last_block_header.block_info();
+        modified_block_headers.push(BlockHeader::new(
+            create_block_info_with_timestamp(block_info, block_info.timestamp_usecs() + 1),
+            last_block_header.quorum_cert().clone(),
+        ));
+        let ordered_block_headers =
+            OrderedBlockHeaders::new(modified_block_headers, ordered_proof.clone());
+        let error = ordered_block_headers
+            .verify_ordered_block_headers()
+            .unwrap_err();
+        assert_matches!(error, Error::InvalidMessageError(_));
+
+        // Remove a header from the middle of the chain and ensure verification fails
+        let mut modified_block_headers = block_headers;
+        modified_block_headers.remove(2);
+        let ordered_block_headers =
+            OrderedBlockHeaders::new(modified_block_headers, ordered_proof.clone());
+        let error = ordered_block_headers
+            .verify_ordered_block_headers()
+            .unwrap_err();
+        assert_matches!(error, Error::InvalidMessageError(_));
+
+        // Verify that empty headers fail verification
+        let ordered_block_headers = OrderedBlockHeaders::new(vec![], ordered_proof);
+        let error = ordered_block_headers
+            .verify_ordered_block_headers()
+            .unwrap_err();
+        assert_matches!(error, Error::InvalidMessageError(_));
+    }
+
+    #[test]
+    fn test_to_header_only() {
+        // Verify that ordered blocks are converted to block headers
+        let current_epoch = 10;
+        let block_info = create_block_info(current_epoch, HashValue::random());
+        let pipelined_block = create_pipelined_block(block_info.clone());
+        let ordered_proof = LedgerInfoWithSignatures::new(
+            LedgerInfo::new(block_info.clone(), HashValue::random()),
+            AggregateSignature::empty(),
+        );
+        let ordered_block_message = ConsensusObserverMessage::new_ordered_block_message(
+            vec![pipelined_block],
+            ordered_proof.clone(),
+        );
+        let header_only_message = ordered_block_message.to_header_only().unwrap();
+        assert_matches!(
+            header_only_message,
+            ConsensusObserverDirectSend::OrderedBlockHeaders(ref headers) if headers.ordered_proof() == &ordered_proof
+        );
+
+        // Verify that commit decisions are sent unchanged
+        let commit_decision_message =
+            ConsensusObserverMessage::new_commit_decision_message(ordered_proof);
+        assert_eq!(
+            commit_decision_message.to_header_only(),
+            Some(commit_decision_message.clone())
+        );
+
+        // Verify that block payloads are never sent to header-only subscribers
+        let block_payload_message = ConsensusObserverMessage::new_block_payload_message(
+            block_info,
+            BlockTransactionPayload::empty(),
+        );
+        assert!(block_payload_message.to_header_only().is_none());
+    }
+
     #[test]
     fn test_verify_ordered_proof() {
         // Create a ledger info with an empty signature set
@@ -1847,6 +2202,42 @@ mod test {
         BlockInfo::new(epoch, 0, block_id, HashValue::random(), 0, 0, None)
     }
 
+    /// Creates and returns a new block info for the given epoch and round
+    fn create_block_info_for_round(epoch: u64, round: Round) -> BlockInfo {
+        BlockInfo::new(
+            epoch,
+            round,
+            HashValue::random(),
+            HashValue::random(),
+            round,
+            round * 1000,
+            None,
+        )
+    }
+
+    /// Creates and returns a copy of the given block info with a different timestamp
+    fn create_block_info_with_timestamp(block_info: &BlockInfo, timestamp_usecs: u64) -> BlockInfo {
+        BlockInfo::new(
+            block_info.epoch(),
+            block_info.round(),
+            block_info.id(),
+            block_info.executed_state_id(),
+            block_info.version(),
+            timestamp_usecs,
+            block_info.next_epoch_state().cloned(),
+        )
+    }
+
+    /// Creates and returns an (unsigned) quorum cert that certifies the given block
+    fn create_quorum_cert(certified_block: BlockInfo) -> QuorumCert {
+        let vote_data = VoteData::new(certified_block, BlockInfo::empty());
+        let ledger_info = LedgerInfoWithSignatures::new(
+            LedgerInfo::new(BlockInfo::empty(), vote_data.hash()),
+            AggregateSignature::empty(),
+        );
+        QuorumCert::new(vote_data, ledger_info)
+    }
+
     /// Creates and returns a hybrid quorum store payload using the given data
     fn create_block_payload(
         block_info: Option<BlockInfo>,
@@ -1987,6 +2378,27 @@ mod test {
         ))
     }
 
+    /// Creates and returns a new pipelined block with the given block info and quorum cert
+    fn create_pipelined_block_with_quorum_cert(
+        block_info: BlockInfo,
+        quorum_cert: QuorumCert,
+    ) -> Arc<PipelinedBlock> {
+        let block_data = BlockData::new_for_testing(
+            block_info.epoch(),
+            block_info.round(),
+            block_info.timestamp_usecs(),
+            quorum_cert,
+            BlockType::NilBlock {
+                failed_authors: vec![],
+            },
+        );
+        let block = Block::new_for_testing(block_info.id(), block_data, None);
+        Arc::new(PipelinedBlock::new_ordered(
+            block,
+            OrderedBlockWindow::empty(),
+        ))
+    }
+
     /// Creates a returns multiple signed transactions
     fn create_signed_transactions(num_transactions: usize) -> Vec<SignedTransaction> {
         // Create a random sender and keypair
@@ -630,6 +630,21 @@ impl ConsensusObserver {
                 )
                 .await;
             },
+            ConsensusObserverDirectSend::OrderedBlockHeaders(ordered_block_headers) => {
+                // Header-only messages are only sent to header subscriptions (which
+                // the consensus observer never creates), so the message is ignored.
+                warn!(
+                    LogSchema::new(LogEntry::ConsensusObserver).message(&format!(
+                        "Received unexpected ordered block headers from peer: {:?}! Ignoring: {:?}",
+                        peer_network_id,
+                        ordered_block_headers.proof_block_info()
+                    ))
+                );
+                increment_invalid_message_counter(
+                    &peer_network_id,
+                    metrics::ORDERED_BLOCK_HEADERS_LABEL,
+                );
+            },
         }
 
         // Update the metrics for the processed blocks
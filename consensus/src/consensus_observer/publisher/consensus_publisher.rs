@@ -3,6 +3,7 @@
 
 use crate::consensus_observer::{
     common::{
+        error::Error,
         logging::{LogEntry, LogEvent, LogSchema},
         metrics,
     },
@@ -20,6 +21,8 @@ use aptos_config::{config::ConsensusObserverConfig, network_id::PeerNetworkId};
 use aptos_infallible::RwLock;
 use aptos_logger::{error, info, warn};
 use aptos_network::application::interface::NetworkClient;
+use aptos_storage_interface::DbReader;
+use aptos_types::epoch_change::EpochChangeProof;
 use futures::StreamExt;
 use futures_channel::mpsc;
 use std::{collections::HashSet, sync::Arc, time::Duration};
@@ -39,8 +42,15 @@ pub struct ConsensusPublisher {
     // The set of active subscribers that have subscribed to consensus updates
     active_subscribers: Arc<RwLock<HashSet<PeerNetworkId>>>,
 
+    // The set of active subscribers that have subscribed to header-only updates
+    // (i.e., block headers, ordered proofs and commit decisions, but no payloads)
+    active_header_subscribers: Arc<RwLock<HashSet<PeerNetworkId>>>,
+
     // The sender for outbound network messages
     outbound_message_sender: mpsc::Sender<(PeerNetworkId, ConsensusObserverDirectSend)>,
+
+    // The storage reader used to serve epoch change proofs (if any)
+    db_reader: Option<Arc<dyn DbReader>>,
 }
 
 impl ConsensusPublisher {
@@ -63,13 +73,22 @@ impl ConsensusPublisher {
             consensus_observer_client,
             consensus_observer_config,
             active_subscribers: Arc::new(RwLock::new(HashSet::new())),
+            active_header_subscribers: Arc::new(RwLock::new(HashSet::new())),
             outbound_message_sender,
+            db_reader: None,
         };
 
         // Return the publisher and the outbound message receiver
         (consensus_publisher, outbound_message_receiver)
     }
 
+    /// Sets the storage reader used to serve epoch change proofs to subscribers
+    /// (e.g., header clients that missed an epoch-ending commit decision).
+    pub fn with_db_reader(mut self, db_reader: Arc<dyn DbReader>) -> Self {
+        self.db_reader = Some(db_reader);
+        self
+    }
+
     #[cfg(test)]
     /// Creates a new consensus publisher with the given active subscribers
     pub fn new_with_active_subscribers(
@@ -90,15 +109,28 @@ impl ConsensusPublisher {
         consensus_publisher
     }
 
-    /// Adds the given subscriber to the set of active subscribers
+    /// Adds the given subscriber to the set of active subscribers.
+    /// A peer holds a single subscription, so any header-only subscription is replaced.
     fn add_active_subscriber(&self, peer_network_id: PeerNetworkId) {
+        self.active_header_subscribers
+            .write()
+            .remove(&peer_network_id);
         self.active_subscribers.write().insert(peer_network_id);
     }
 
+    /// Adds the given subscriber to the set of active header-only subscribers.
+    /// A peer holds a single subscription, so any full subscription is replaced.
+    fn add_active_header_subscriber(&self, peer_network_id: PeerNetworkId) {
+        self.active_subscribers.write().remove(&peer_network_id);
+        self.active_header_subscribers
+            .write()
+            .insert(peer_network_id);
+    }
+
     /// Garbage collect inactive subscriptions by removing peers that are no longer connected
     fn garbage_collect_subscriptions(&self) {
-        // Get the set of active subscribers
-        let active_subscribers = self.get_active_subscribers();
+        // Get the set of active subscribers (of both subscription types)
+        let active_subscribers = self.get_all_active_subscribers();
 
         // Get the connected peers and metadata
         let peers_and_metadata = self.consensus_observer_client.get_peers_and_metadata();
@@ -137,7 +169,7 @@ impl ConsensusPublisher {
         }
 
         // Update the number of active subscribers for each network
-        let active_subscribers = self.get_active_subscribers();
+        let active_subscribers = self.get_all_active_subscribers();
         for network_id in peers_and_metadata.get_registered_networks() {
             // Calculate the number of active subscribers for the network
             let num_active_subscribers = active_subscribers
@@ -159,9 +191,24 @@ impl ConsensusPublisher {
         self.active_subscribers.read().clone()
     }
 
-    /// Removes the given subscriber from the set of active subscribers
+    /// Returns a clone of the currently active header-only subscribers
+    pub fn get_active_header_subscribers(&self) -> HashSet<PeerNetworkId> {
+        self.active_header_subscribers.read().clone()
+    }
+
+    /// Returns the currently active subscribers of both subscription types
+    fn get_all_active_subscribers(&self) -> HashSet<PeerNetworkId> {
+        let mut active_subscribers = self.get_active_subscribers();
+        active_subscribers.extend(self.get_active_header_subscribers());
+        active_subscribers
+    }
+
+    /// Removes the given subscriber from the sets of active subscribers
     fn remove_active_subscriber(&self, peer_network_id: &PeerNetworkId) {
         self.active_subscribers.write().remove(peer_network_id);
+        self.active_header_subscribers
+            .write()
+            .remove(peer_network_id);
     }
 
     /// Processes a network message received by the consensus publisher
@@ -191,6 +238,19 @@ impl ConsensusPublisher {
                 // Send a simple subscription ACK
                 response_sender.send(ConsensusObserverResponse::SubscribeAck);
             },
+            ConsensusObserverRequest::SubscribeHeaders => {
+                // Add the peer to the set of active header-only subscribers
+                self.add_active_header_subscriber(peer_network_id);
+                info!(LogSchema::new(LogEntry::ConsensusPublisher)
+                    .event(LogEvent::Subscription)
+                    .message(&format!(
+                        "New peer subscribed to consensus header updates! Peer: {:?}",
+                        peer_network_id
+                    )));
+
+                // Send a simple subscription ACK
+                response_sender.send(ConsensusObserverResponse::SubscribeAck);
+            },
             ConsensusObserverRequest::Unsubscribe => {
                 // Remove the peer from the set of active subscribers
                 self.remove_active_subscriber(&peer_network_id);
@@ -204,17 +264,84 @@ impl ConsensusPublisher {
                 // Send a simple unsubscription ACK
                 response_sender.send(ConsensusObserverResponse::UnsubscribeAck);
             },
+            ConsensusObserverRequest::GetEpochChangeProof(known_epoch) => {
+                match self.get_epoch_change_proof(known_epoch) {
+                    Ok(epoch_change_proof) => {
+                        response_sender.send(ConsensusObserverResponse::EpochChangeProof(
+                            epoch_change_proof,
+                        ));
+                    },
+                    Err(error) => {
+                        // Drop the response sender (the peer will receive an RPC error)
+                        warn!(LogSchema::new(LogEntry::ConsensusPublisher)
+                            .error(&error)
+                            .message(&format!(
+                                "Failed to serve epoch change proof request! Peer: {:?}, Known epoch: {}",
+                                peer_network_id, known_epoch
+                            )));
+                    },
+                }
+            },
         }
     }
 
-    /// Publishes a direct send message to all active subscribers. Note: this method
-    /// is non-blocking (to avoid blocking callers during publishing, e.g., consensus).
+    /// Returns the epoch change proof from the given (known) epoch to the latest epoch
+    fn get_epoch_change_proof(&self, known_epoch: u64) -> Result<EpochChangeProof, Error> {
+        // Verify that the publisher can serve epoch change proofs
+        let db_reader = self.db_reader.as_ref().ok_or_else(|| {
+            Error::UnexpectedError("The publisher has no storage reader!".to_string())
+        })?;
+
+        // Verify that the known epoch has ended
+        let latest_ledger_info = db_reader.get_latest_ledger_info().map_err(|error| {
+            Error::UnexpectedError(format!(
+                "Failed to get the latest ledger info! Error: {:?}",
+                error
+            ))
+        })?;
+        let latest_epoch = latest_ledger_info.ledger_info().next_block_epoch();
+        if known_epoch >= latest_epoch {
+            return Err(Error::InvalidMessageError(format!(
+                "The known epoch: {} has not ended! Latest epoch: {}",
+                known_epoch, latest_epoch
+            )));
+        }
+
+        // Fetch the epoch ending ledger infos (the proof may be truncated by storage)
+        db_reader
+            .get_epoch_ending_ledger_infos(known_epoch, latest_epoch)
+            .map_err(|error| {
+                Error::UnexpectedError(format!(
+                    "Failed to get the epoch ending ledger infos! Error: {:?}",
+                    error
+                ))
+            })
+    }
+
+    /// Publishes a direct send message to all active subscribers. Header-only subscribers
+    /// receive the header-only version of the message (if any). Note: this method is
+    /// non-blocking (to avoid blocking callers during publishing, e.g., consensus).
     pub fn publish_message(&self, message: ConsensusObserverDirectSend) {
-        // Get the active subscribers
-        let active_subscribers = self.get_active_subscribers();
+        // Send the header-only message to all active header subscribers
+        let active_header_subscribers = self.get_active_header_subscribers();
+        if !active_header_subscribers.is_empty() {
+            if let Some(header_only_message) = message.to_header_only() {
+                self.send_message_to_subscribers(&active_header_subscribers, header_only_message);
+            }
+        }
 
         // Send the message to all active subscribers
-        for peer_network_id in &active_subscribers {
+        let active_subscribers = self.get_active_subscribers();
+        self.send_message_to_subscribers(&active_subscribers, message);
+    }
+
+    /// Sends the given message to the outbound receiver for each of the given subscribers
+    fn send_message_to_subscribers(
+        &self,
+        subscribers: &HashSet<PeerNetworkId>,
+        message: ConsensusObserverDirectSend,
+    ) {
+        for peer_network_id in subscribers {
             // Send the message to the outbound receiver for publishing
             let mut outbound_message_sender = self.outbound_message_sender.clone();
             if let Err(error) =
@@ -353,7 +480,8 @@ fn spawn_message_serializer_and_sender(
 mod test {
     use super::*;
     use crate::consensus_observer::network::{
-        network_events::ResponseSender, observer_message::BlockTransactionPayload,
+        network_events::ResponseSender,
+        observer_message::{BlockTransactionPayload, OrderedBlockHeaders},
     };
     use aptos_config::network_id::NetworkId;
     use aptos_crypto::HashValue;
@@ -368,9 +496,27 @@ mod test {
         PeerId,
     };
     use futures::FutureExt;
+    use futures_channel::oneshot;
     use maplit::hashmap;
+    use mockall::mock;
     use tokio_stream::StreamExt;
 
+    // This is a simple mock of the DbReader (it generates a MockDatabaseReader)
+    mock! {
+        pub DatabaseReader {}
+        impl DbReader for DatabaseReader {
+            fn get_latest_ledger_info_option(
+                &self,
+            ) -> aptos_storage_interface::Result<Option<LedgerInfoWithSignatures>>;
+
+            fn get_epoch_ending_ledger_infos(
+                &self,
+                start_epoch: u64,
+                end_epoch: u64,
+            ) -> aptos_storage_interface::Result<EpochChangeProof>;
+        }
+    }
+
     #[test]
     pub fn test_garbage_collect_subscriptions() {
         // Create a network client
@@ -496,6 +642,196 @@ mod test {
         ]);
     }
 
+    #[test]
+    fn test_handle_header_subscription_request() {
+        // Create a network client
+        let network_id = NetworkId::Public;
+        let peers_and_metadata = PeersAndMetadata::new(&[network_id]);
+        let network_client =
+            NetworkClient::new(vec![], vec![], hashmap![], peers_and_metadata.clone());
+        let consensus_observer_client = Arc::new(ConsensusObserverClient::new(network_client));
+
+        // Create a consensus publisher
+        let (consensus_publisher, _) = ConsensusPublisher::new(
+            ConsensusObserverConfig::default(),
+            consensus_observer_client,
+        );
+
+        // Subscribe a new peer to header updates and verify the subscription
+        let peer_network_id_1 = PeerNetworkId::new(network_id, PeerId::random());
+        process_header_subscription_for_peer(&consensus_publisher, &peer_network_id_1);
+        verify_active_header_subscribers(&consensus_publisher, 1, vec![&peer_network_id_1], vec![]);
+        verify_active_subscribers(&consensus_publisher, 0, vec![], vec![&peer_network_id_1]);
+
+        // Subscribe the same peer to full updates and verify the header subscription is replaced
+        process_subscription_for_peer(&consensus_publisher, &peer_network_id_1);
+        verify_active_subscribers(&consensus_publisher, 1, vec![&peer_network_id_1], vec![]);
+        verify_active_header_subscribers(&consensus_publisher, 0, vec![], vec![&peer_network_id_1]);
+
+        // Subscribe the peer to header updates again and verify the full subscription is replaced
+        process_header_subscription_for_peer(&consensus_publisher, &peer_network_id_1);
+        verify_active_header_subscribers(&consensus_publisher, 1, vec![&peer_network_id_1], vec![]);
+        verify_active_subscribers(&consensus_publisher, 0, vec![], vec![&peer_network_id_1]);
+
+        // Subscribe another peer to header updates and verify the subscription
+        let peer_network_id_2 = PeerNetworkId::new(network_id, PeerId::random());
+        process_header_subscription_for_peer(&consensus_publisher, &peer_network_id_2);
+        verify_active_header_subscribers(
+            &consensus_publisher,
+            2,
+            vec![&peer_network_id_1, &peer_network_id_2],
+            vec![],
+        );
+
+        // Unsubscribe the first peer and verify the header subscription is removed
+        process_unsubscription_for_peer(&consensus_publisher, &peer_network_id_1);
+        verify_active_header_subscribers(&consensus_publisher, 1, vec![&peer_network_id_2], vec![
+            &peer_network_id_1,
+        ]);
+
+        // Garbage collect the subscriptions and verify the second peer is removed
+        // (we have no metadata for the peer, so it is considered disconnected).
+        consensus_publisher.garbage_collect_subscriptions();
+        verify_active_header_subscribers(&consensus_publisher, 0, vec![], vec![&peer_network_id_2]);
+    }
+
+    #[tokio::test]
+    async fn test_handle_epoch_change_proof_request() {
+        // Create a network client
+        let network_id = NetworkId::Public;
+        let peers_and_metadata = PeersAndMetadata::new(&[network_id]);
+        let network_client =
+            NetworkClient::new(vec![], vec![], hashmap![], peers_and_metadata.clone());
+        let consensus_observer_client = Arc::new(ConsensusObserverClient::new(network_client));
+
+        // Create a mock db reader where the latest ledger info is in epoch 10
+        let latest_epoch = 10;
+        let latest_ledger_info = create_ledger_info(latest_epoch, 5);
+        let epoch_ending_ledger_info = create_ledger_info(latest_epoch - 2, 20);
+        let epoch_change_proof = EpochChangeProof::new(vec![epoch_ending_ledger_info], false);
+        let mut mock_db_reader = MockDatabaseReader::new();
+        mock_db_reader
+            .expect_get_latest_ledger_info_option()
+            .returning(move || Ok(Some(latest_ledger_info.clone())));
+        let expected_proof = epoch_change_proof.clone();
+        mock_db_reader
+            .expect_get_epoch_ending_ledger_infos()
+            .withf(move |start_epoch, end_epoch| {
+                *start_epoch == latest_epoch - 2 && *end_epoch == latest_epoch
+            })
+            .returning(move |_, _| Ok(expected_proof.clone()));
+
+        // Create a consensus publisher without a db reader
+        let (consensus_publisher, _) = ConsensusPublisher::new(
+            ConsensusObserverConfig::default(),
+            consensus_observer_client,
+        );
+
+        // Verify that epoch change proof requests are not served
+        let peer_network_id = PeerNetworkId::new(network_id, PeerId::random());
+        let response = process_epoch_change_proof_request(
+            &consensus_publisher,
+            &peer_network_id,
+            latest_epoch - 2,
+        )
+        .await;
+        assert!(response.is_none());
+
+        // Add the db reader to the publisher
+        let consensus_publisher = consensus_publisher.with_db_reader(Arc::new(mock_db_reader));
+
+        // Verify that the epoch change proof is served for an old epoch
+        let response = process_epoch_change_proof_request(
+            &consensus_publisher,
+            &peer_network_id,
+            latest_epoch - 2,
+        )
+        .await;
+        assert_eq!(
+            response,
+            Some(ConsensusObserverResponse::EpochChangeProof(
+                epoch_change_proof
+            ))
+        );
+
+        // Verify that requests for the latest epoch are not served
+        let response = process_epoch_change_proof_request(
+            &consensus_publisher,
+            &peer_network_id,
+            latest_epoch,
+        )
+        .await;
+        assert!(response.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_publish_message_to_header_subscribers() {
+        // Create a network client
+        let network_id = NetworkId::Public;
+        let peers_and_metadata = PeersAndMetadata::new(&[network_id]);
+        let network_client =
+            NetworkClient::new(vec![], vec![], hashmap![], peers_and_metadata.clone());
+        let consensus_observer_client = Arc::new(ConsensusObserverClient::new(network_client));
+
+        // Create a consensus publisher
+        let (consensus_publisher, mut outbound_message_receiver) = ConsensusPublisher::new(
+            ConsensusObserverConfig::default(),
+            consensus_observer_client,
+        );
+
+        // Subscribe one peer to header updates and another to full updates
+        let header_peer_network_id = PeerNetworkId::new(network_id, PeerId::random());
+        process_header_subscription_for_peer(&consensus_publisher, &header_peer_network_id);
+        let full_peer_network_id = PeerNetworkId::new(network_id, PeerId::random());
+        process_subscription_for_peer(&consensus_publisher, &full_peer_network_id);
+
+        // Publish an ordered block message
+        let ordered_proof = LedgerInfoWithSignatures::new(
+            LedgerInfo::new(BlockInfo::empty(), HashValue::zero()),
+            AggregateSignature::empty(),
+        );
+        let ordered_block_message =
+            ConsensusObserverMessage::new_ordered_block_message(vec![], ordered_proof.clone());
+        consensus_publisher.publish_message(ordered_block_message.clone());
+
+        // Verify the header peer received the headers and the full peer received the blocks
+        for _ in 0..2 {
+            let (peer_network_id, message) = outbound_message_receiver.next().await.unwrap();
+            if peer_network_id == header_peer_network_id {
+                assert_eq!(
+                    message,
+                    ConsensusObserverDirectSend::OrderedBlockHeaders(OrderedBlockHeaders::new(
+                        vec![],
+                        ordered_proof.clone()
+                    ))
+                );
+            } else {
+                assert_eq!(peer_network_id, full_peer_network_id);
+                assert_eq!(message, ordered_block_message);
+            }
+        }
+
+        // Publish a block payload message and verify only the full peer received it
+        let block_payload_message = ConsensusObserverMessage::new_block_payload_message(
+            BlockInfo::empty(),
+            BlockTransactionPayload::empty(),
+        );
+        consensus_publisher.publish_message(block_payload_message.clone());
+        let (peer_network_id, message) = outbound_message_receiver.next().await.unwrap();
+        assert_eq!(peer_network_id, full_peer_network_id);
+        assert_eq!(message, block_payload_message);
+        assert!(outbound_message_receiver.next().now_or_never().is_none());
+
+        // Publish a commit decision and verify both peers received it
+        let commit_decision_message =
+            ConsensusObserverMessage::new_commit_decision_message(ordered_proof);
+        consensus_publisher.publish_message(commit_decision_message.clone());
+        for _ in 0..2 {
+            let (_, message) = outbound_message_receiver.next().await.unwrap();
+            assert_eq!(message, commit_decision_message);
+        }
+    }
+
     #[tokio::test]
     async fn test_publish_message() {
         // Create a network client
@@ -614,6 +950,65 @@ mod test {
         consensus_publisher.process_network_message(network_message);
     }
 
+    /// Processes a header-only subscription request for the given peer
+    fn process_header_subscription_for_peer(
+        consensus_publisher: &ConsensusPublisher,
+        peer_network_id: &PeerNetworkId,
+    ) {
+        // Create the subscribe headers message
+        let network_message = ConsensusPublisherNetworkMessage::new(
+            *peer_network_id,
+            ConsensusObserverRequest::SubscribeHeaders,
+            ResponseSender::new_for_test(),
+        );
+
+        // Process the subscription request
+        consensus_publisher.process_network_message(network_message);
+    }
+
+    /// Creates a ledger info for the given epoch and round (with no signatures)
+    fn create_ledger_info(epoch: u64, round: u64) -> LedgerInfoWithSignatures {
+        LedgerInfoWithSignatures::new(
+            LedgerInfo::new(
+                BlockInfo::new(
+                    epoch,
+                    round,
+                    HashValue::random(),
+                    HashValue::random(),
+                    round,
+                    0,
+                    None,
+                ),
+                HashValue::random(),
+            ),
+            AggregateSignature::empty(),
+        )
+    }
+
+    /// Processes an epoch change proof request for the given peer, and returns
+    /// the response (or None, if the request was not served).
+    async fn process_epoch_change_proof_request(
+        consensus_publisher: &ConsensusPublisher,
+        peer_network_id: &PeerNetworkId,
+        known_epoch: u64,
+    ) -> Option<ConsensusObserverResponse> {
+        // Create the epoch change proof request
+        let (response_tx, response_rx) = oneshot::channel();
+        let network_message = ConsensusPublisherNetworkMessage::new(
+            *peer_network_id,
+            ConsensusObserverRequest::GetEpochChangeProof(known_epoch),
+            ResponseSender::new(response_tx),
+        );
+
+        // Process the request and deserialize the response (if any)
+        consensus_publisher.process_network_message(network_message);
+        let response_bytes = response_rx.await.ok()?.unwrap();
+        match bcs::from_bytes(&response_bytes).unwrap() {
+            ConsensusObserverMessage::Response(response) => Some(response),
+            message => panic!("Unexpected message: {:?}", message),
+        }
+    }
+
     /// Processes an unsubscription request for the given peer
     fn process_unsubscription_for_peer(
         consensus_publisher: &ConsensusPublisher,
@@ -651,4 +1046,26 @@ mod test {
             assert!(!active_subscribers.contains(peer_network_id));
         }
     }
+
+    /// Verifies the active header subscribers has the expected size and contains the expected peers
+    fn verify_active_header_subscribers(
+        consensus_publisher: &ConsensusPublisher,
+        expected_size: usize,
+        expected_peers: Vec<&PeerNetworkId>,
+        unexpected_peers: Vec<&PeerNetworkId>,
+    ) {
+        // Verify the number of active header subscribers
+        let active_header_subscribers = consensus_publisher.get_active_header_subscribers();
+        assert_eq!(active_header_subscribers.len(), expected_size);
+
+        // Verify that the active header subscribers contains the expected peers
+        for peer_network_id in expected_peers {
+            assert!(active_header_subscribers.contains(peer_network_id));
+        }
+
+        // Verify that the active header subscribers does not contain the unexpected peers
+        for peer_network_id in unexpected_peers {
+            assert!(!active_header_subscribers.contains(peer_network_id));
+        }
+    }
 }
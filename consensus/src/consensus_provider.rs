@@ -20,6 +20,7 @@ use crate::{
     quorum_store::quorum_store_db::QuorumStoreDB,
     rand::rand_gen::storage::db::RandDb,
     state_computer::ExecutionProxy,
+    state_inspector::ConsensusStateInspector,
     txn_notifier::MempoolNotifier,
     util::time_service::ClockTimeService,
 };
@@ -53,10 +54,16 @@ pub fn start_consensus(
     reconfig_events: ReconfigNotificationListener<DbBackedOnChainConfig>,
     vtxn_pool: VTxnPoolState,
     consensus_publisher: Option<Arc<ConsensusPublisher>>,
-) -> (Runtime, Arc<StorageWriteProxy>, Arc<QuorumStoreDB>) {
+) -> (
+    Runtime,
+    Arc<StorageWriteProxy>,
+    Arc<QuorumStoreDB>,
+    Arc<ConsensusStateInspector>,
+) {
     let runtime = aptos_runtimes::spawn_named_runtime("consensus".into(), None);
     let storage = Arc::new(StorageWriteProxy::new(node_config, aptos_db.reader.clone()));
     let quorum_store_db = Arc::new(QuorumStoreDB::new(node_config.storage.dir()));
+    let state_inspector = Arc::new(ConsensusStateInspector::default());
 
     let txn_notifier = Arc::new(MempoolNotifier::new(
         consensus_to_mempool_sender.clone(),
@@ -94,6 +101,7 @@ pub fn start_consensus(
         rand_storage.clone(),
         node_config.consensus_observer,
         consensus_publisher.clone(),
+        state_inspector.clone(),
     ));
 
     let epoch_mgr = EpochManager::new(
@@ -112,6 +120,7 @@ pub fn start_consensus(
         vtxn_pool,
         rand_storage,
         consensus_publisher,
+        state_inspector.clone(),
     );
 
     let (network_task, network_receiver) = NetworkTask::new(network_service_events, self_receiver);
@@ -120,7 +129,7 @@ pub fn start_consensus(
     runtime.spawn(epoch_mgr.start(timeout_receiver, network_receiver));
 
     debug!("Consensus started.");
-    (runtime, storage, quorum_store_db)
+    (runtime, storage, quorum_store_db, state_inspector)
 }
 
 /// A helper function to start the consensus observer
@@ -177,6 +186,7 @@ pub fn start_consensus_observer(
             rand_storage.clone(),
             node_config.consensus_observer,
             consensus_publisher.clone(),
+            Arc::new(ConsensusStateInspector::default()),
         ));
         execution_proxy_client as Arc<dyn TExecutionClient>
     } else {
@@ -53,6 +53,7 @@ use crate::{
     },
     recovery_manager::RecoveryManager,
     round_manager::{RoundManager, UnverifiedEvent, VerifiedEvent},
+    state_inspector::ConsensusStateInspector,
     util::time_service::TimeService,
 };
 use anyhow::{anyhow, bail, ensure, Context};
@@ -176,6 +177,7 @@ pub struct EpochManager<P: OnChainConfigProvider> {
     consensus_publisher: Option<Arc<ConsensusPublisher>>,
     pending_blocks: Arc<Mutex<PendingBlocks>>,
     key_storage: PersistentSafetyStorage,
+    state_inspector: Arc<ConsensusStateInspector>,
 
     consensus_txn_filter_config: BlockTransactionFilterConfig,
     quorum_store_txn_filter_config: BatchTransactionFilterConfig,
@@ -199,6 +201,7 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
         vtxn_pool: VTxnPoolState,
         rand_storage: Arc<dyn RandStorage<AugmentedData>>,
         consensus_publisher: Option<Arc<ConsensusPublisher>>,
+        state_inspector: Arc<ConsensusStateInspector>,
     ) -> Self {
         let author = node_config.validator_network.as_ref().unwrap().peer_id();
         let config = node_config.consensus.clone();
@@ -252,6 +255,7 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
             consensus_publisher,
             pending_blocks: Arc::new(Mutex::new(PendingBlocks::new())),
             key_storage,
+            state_inspector,
             consensus_txn_filter_config,
             quorum_store_txn_filter_config,
         }
@@ -746,6 +750,7 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
                 self.quorum_store_storage.clone(),
                 !consensus_config.is_dag_enabled(),
                 consensus_key,
+                self.state_inspector.clone(),
             ))
         } else {
             info!("Building DirectMempool");
@@ -977,6 +982,7 @@ impl<P: OnChainConfigProvider> EpochManager<P> {
             fast_rand_config,
             failures_tracker,
             opt_proposal_loopback_tx,
+            self.state_inspector.clone(),
         );
 
         round_manager.init(last_vote).await;
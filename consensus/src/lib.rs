@@ -40,6 +40,8 @@ mod rand;
 mod recovery_manager;
 mod round_manager;
 mod state_computer;
+/// Required by the admin service
+pub mod state_inspector;
 mod state_replication;
 #[cfg(any(test, feature = "fuzzing"))]
 mod test_utils;
@@ -58,9 +60,6 @@ pub mod counters;
 pub mod network_interface;
 mod payload_manager;
 mod transaction_deduper;
-mod transaction_shuffler;
-#[cfg(feature = "fuzzing")]
-pub use transaction_shuffler::transaction_shuffler_fuzzing;
 mod txn_hash_and_authenticator_deduper;
 
 use aptos_metrics_core::IntGauge;
@@ -5,6 +5,7 @@
 use crate::{
     counters,
     pending_votes::{PendingVotes, VoteReceptionResult, VoteStatus},
+    state_inspector::{TimeoutState, VoteTally},
     util::time_service::{SendTask, TimeService},
 };
 use aptos_consensus_types::{
@@ -336,6 +337,33 @@ impl RoundState {
         self.timeout_sent.clone()
     }
 
+    /// Returns the highest ordered round known to the RoundState.
+    pub fn highest_ordered_round(&self) -> Round {
+        self.highest_ordered_round
+    }
+
+    /// Returns the votes received for the current round, grouped by the block they vote for.
+    pub fn vote_tallies(&self, verifier: &ValidatorVerifier) -> Vec<VoteTally> {
+        self.pending_votes.vote_tallies(verifier)
+    }
+
+    /// Returns the local and aggregated timeout state of the current round.
+    pub fn timeout_state(&self, verifier: &ValidatorVerifier) -> TimeoutState {
+        let timeout_voters = self.pending_votes.timeout_voters();
+        TimeoutState {
+            current_round_deadline_usecs: self.current_round_deadline.as_micros() as u64,
+            vote_sent: self
+                .vote_sent
+                .as_ref()
+                .map(|vote| vote.vote_data().proposed().id()),
+            timeout_sent: self.is_timeout_sent(),
+            timeout_voting_power: verifier
+                .sum_voting_power(timeout_voters.iter())
+                .unwrap_or(0),
+            timeout_voters,
+        }
+    }
+
     /// Setup the timeout task and return the duration of the current timeout
     fn setup_timeout(&mut self, multiplier: u32) -> Duration {
         let timeout_sender = self.timeout_sender.clone();
@@ -139,14 +139,18 @@ pub struct ConsensusNetworkClient<NetworkClient> {
 }
 
 /// Supported protocols in preferred order (from highest priority to lowest).
+/// Zstd is preferred, but peers that only support lz4 fall back to it.
 pub const RPC: &[ProtocolId] = &[
+    ProtocolId::ConsensusRpcZstd,
     ProtocolId::ConsensusRpcCompressed,
     ProtocolId::ConsensusRpcBcs,
     ProtocolId::ConsensusRpcJson,
 ];
 
 /// Supported protocols in preferred order (from highest priority to lowest).
+/// Zstd is preferred, but peers that only support lz4 fall back to it.
 pub const DIRECT_SEND: &[ProtocolId] = &[
+    ProtocolId::ConsensusDirectSendZstd,
     ProtocolId::ConsensusDirectSendCompressed,
     ProtocolId::ConsensusDirectSendBcs,
     ProtocolId::ConsensusDirectSendJson,
@@ -8,7 +8,7 @@
 //! when enough votes (or timeout votes) have been observed.
 //! Votes are automatically dropped when the structure goes out of scope.
 
-use crate::counters;
+use crate::{counters, state_inspector::VoteTally};
 use aptos_bitvec::BitVec;
 use aptos_consensus_types::{
     common::Author,
@@ -25,7 +25,11 @@ use aptos_types::{
     ledger_info::{LedgerInfo, LedgerInfoWithSignatures, SignatureAggregator},
     validator_verifier::{ValidatorVerifier, VerifyError},
 };
-use std::{collections::HashMap, fmt, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    sync::Arc,
+};
 
 /// Result of the vote processing. The failure case (Verification error) is returned
 /// as the Error part of the result.
@@ -506,6 +510,43 @@ impl PendingVotes {
             self.maybe_2chain_timeout_votes.take(),
         )
     }
+
+    /// Returns the votes received so far, grouped by the LedgerInfo they vote for.
+    pub fn vote_tallies(&self, verifier: &ValidatorVerifier) -> Vec<VoteTally> {
+        let mut voters_by_digest: BTreeMap<HashValue, (HashValue, Vec<Author>)> = BTreeMap::new();
+        for (author, (vote, li_digest)) in self.author_to_vote.iter() {
+            voters_by_digest
+                .entry(*li_digest)
+                .or_insert_with(|| (vote.vote_data().proposed().id(), vec![]))
+                .1
+                .push(*author);
+        }
+
+        voters_by_digest
+            .into_iter()
+            .map(|(ledger_info_digest, (block_id, mut voters))| {
+                voters.sort();
+                VoteTally {
+                    block_id,
+                    ledger_info_digest,
+                    voting_power: verifier.sum_voting_power(voters.iter()).unwrap_or(0),
+                    quorum_reached: matches!(
+                        self.li_digest_to_votes.get(&ledger_info_digest),
+                        Some((_, VoteStatus::EnoughVotes(_)))
+                    ),
+                    voters,
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the authors of the timeout votes received so far.
+    pub fn timeout_voters(&self) -> Vec<Author> {
+        self.maybe_2chain_timeout_votes
+            .as_ref()
+            .map(|votes| votes.partial_2chain_tc.signers().cloned().collect())
+            .unwrap_or_default()
+    }
 }
 
 fn hash_index_to_str(hash_index: usize) -> String {
@@ -657,6 +698,63 @@ mod tests {
         };
     }
 
+    #[test]
+    fn test_vote_tallies() {
+        ::aptos_logger::Logger::init_for_testing();
+
+        // set up 4 validators
+        let (signers, validator_verifier) = random_validator_verifier(4, Some(2), false);
+        let mut pending_votes = PendingVotes::new();
+
+        let li1 = random_ledger_info();
+        let vote_data_1 = random_vote_data();
+        let li2 = random_ledger_info();
+        let vote_data_2 = random_vote_data();
+        let block_1 = vote_data_1.proposed().id();
+        let block_2 = vote_data_2.proposed().id();
+
+        let vote_data_1_author_0 =
+            Vote::new(vote_data_1, signers[0].author(), li1.clone(), &signers[0]).unwrap();
+        let vote_data_2_author_1 = Vote::new(
+            vote_data_2.clone(),
+            signers[1].author(),
+            li2.clone(),
+            &signers[1],
+        )
+        .unwrap();
+        pending_votes.insert_vote(&vote_data_1_author_0, &validator_verifier);
+        pending_votes.insert_vote(&vote_data_2_author_1, &validator_verifier);
+
+        let tallies = pending_votes.vote_tallies(&validator_verifier);
+        assert_eq!(tallies.len(), 2);
+        assert!(tallies.iter().all(|tally| tally.voters.len() == 1
+            && tally.voting_power == 1
+            && !tally.quorum_reached));
+
+        // a second vote for the second ledger info forms a QC
+        let vote_data_2_author_2 =
+            Vote::new(vote_data_2, signers[2].author(), li2.clone(), &signers[2]).unwrap();
+        pending_votes.insert_vote(&vote_data_2_author_2, &validator_verifier);
+
+        let tallies = pending_votes.vote_tallies(&validator_verifier);
+        let tally_1 = tallies
+            .iter()
+            .find(|tally| tally.ledger_info_digest == li1.hash())
+            .unwrap();
+        assert_eq!(tally_1.block_id, block_1);
+        assert_eq!(tally_1.voters, vec![signers[0].author()]);
+        assert!(!tally_1.quorum_reached);
+
+        let tally_2 = tallies
+            .iter()
+            .find(|tally| tally.ledger_info_digest == li2.hash())
+            .unwrap();
+        assert_eq!(tally_2.block_id, block_2);
+        assert_eq!(tally_2.voting_power, 2);
+        assert!(tally_2.quorum_reached);
+        assert!(pending_votes.timeout_voters().is_empty());
+    }
+
     #[test]
     fn test_qc_aggregation_with_unverified_votes() {
         ::aptos_logger::Logger::init_for_testing();
@@ -22,6 +22,9 @@ use crate::{
         pipeline_phase::CountedRequest,
         signing_phase::{SigningRequest, SigningResponse},
     },
+    state_inspector::{
+        BufferItemPhase, BufferManagerState, ConsensusStateInspector, PipelineBlockState,
+    },
 };
 use aptos_bounded_executor::BoundedExecutor;
 use aptos_config::config::ConsensusObserverConfig;
@@ -32,6 +35,7 @@ use aptos_consensus_types::{
 };
 use aptos_crypto::HashValue;
 use aptos_executor_types::ExecutorResult;
+use aptos_infallible::duration_since_epoch;
 use aptos_logger::prelude::*;
 use aptos_network::protocols::{rpc::error::RpcError, wire::handshake::v1::ProtocolId};
 use aptos_reliable_broadcast::{DropGuard, ReliableBroadcast};
@@ -171,6 +175,8 @@ pub struct BufferManager {
     // Items are popped from the buffer when sending to the persisting phase since callback is not clonable.
     // but we need to keep the pending blocks for reset.
     pending_commit_blocks: BTreeMap<Round, Arc<PipelinedBlock>>,
+
+    state_inspector: Arc<ConsensusStateInspector>,
 }
 
 impl BufferManager {
@@ -202,6 +208,7 @@ impl BufferManager {
         consensus_observer_config: ConsensusObserverConfig,
         consensus_publisher: Option<Arc<ConsensusPublisher>>,
         max_pending_rounds_in_commit_vote_cache: u64,
+        state_inspector: Arc<ConsensusStateInspector>,
     ) -> Self {
         let buffer = Buffer::<BufferItem>::new();
 
@@ -263,6 +270,8 @@ impl BufferManager {
             max_pending_rounds_in_commit_vote_cache,
             pending_commit_votes: BTreeMap::new(),
             pending_commit_blocks: BTreeMap::new(),
+
+            state_inspector,
         }
     }
 
@@ -898,6 +907,45 @@ impl BufferManager {
             .set(pending_aggregated as i64);
     }
 
+    /// Publishes the phase of every block in the buffer to the state inspector.
+    fn publish_state(&self) {
+        let mut blocks = vec![];
+        let mut cursor = *self.buffer.head_cursor();
+        while cursor.is_some() {
+            let item = self.buffer.get(&cursor);
+            let phase = match item {
+                BufferItem::Ordered(_) => BufferItemPhase::Ordered,
+                BufferItem::Executed(_) => BufferItemPhase::Executed,
+                BufferItem::Signed(_) => BufferItemPhase::Signed,
+                BufferItem::Aggregated(_) => BufferItemPhase::Aggregated,
+            };
+            blocks.extend(item.get_blocks().iter().map(|block| PipelineBlockState {
+                round: block.round(),
+                block_id: block.id(),
+                phase,
+            }));
+            cursor = self.buffer.get_next(&cursor);
+        }
+
+        self.state_inspector
+            .update_buffer_manager_state(BufferManagerState {
+                updated_at_usecs: duration_since_epoch().as_micros() as u64,
+                epoch: self.epoch_state.epoch,
+                latest_round: self.latest_round,
+                highest_committed_round: self.highest_committed_round,
+                back_pressured: self.need_back_pressure(),
+                blocks,
+                execution_root: self.execution_root,
+                signing_root: self.signing_root,
+                num_pending_commit_proofs: self.pending_commit_proofs.len(),
+                num_pending_commit_votes: self
+                    .pending_commit_votes
+                    .values()
+                    .map(HashMap::len)
+                    .sum(),
+            });
+    }
+
     fn need_back_pressure(&self) -> bool {
         const MAX_BACKLOG: Round = 20;
 
@@ -981,6 +1029,7 @@ impl BufferManager {
                 _ = interval.tick().fuse() => {
                     monitor!("buffer_manager_process_interval_tick", {
                     self.update_buffer_manager_metrics();
+                    self.publish_state();
                     self.rebroadcast_commit_votes_if_needed().await
                     });
                 },
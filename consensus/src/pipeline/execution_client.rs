@@ -22,9 +22,9 @@ use crate::{
         types::{AugmentedData, RandConfig, Share},
     },
     state_computer::ExecutionProxy,
+    state_inspector::ConsensusStateInspector,
     state_replication::StateComputer,
     transaction_deduper::create_transaction_deduper,
-    transaction_shuffler::create_transaction_shuffler,
 };
 use anyhow::{anyhow, Result};
 use aptos_bounded_executor::BoundedExecutor;
@@ -40,6 +40,7 @@ use aptos_executor_types::ExecutorResult;
 use aptos_infallible::RwLock;
 use aptos_logger::prelude::*;
 use aptos_network::{application::interface::NetworkClient, protocols::network::Event};
+use aptos_transaction_shuffler::create_transaction_shuffler;
 use aptos_types::{
     epoch_state::EpochState,
     ledger_info::LedgerInfoWithSignatures,
@@ -167,6 +168,7 @@ pub struct ExecutionProxyClient {
     rand_storage: Arc<dyn RandStorage<AugmentedData>>,
     consensus_observer_config: ConsensusObserverConfig,
     consensus_publisher: Option<Arc<ConsensusPublisher>>,
+    state_inspector: Arc<ConsensusStateInspector>,
 }
 
 impl ExecutionProxyClient {
@@ -180,6 +182,7 @@ impl ExecutionProxyClient {
         rand_storage: Arc<dyn RandStorage<AugmentedData>>,
         consensus_observer_config: ConsensusObserverConfig,
         consensus_publisher: Option<Arc<ConsensusPublisher>>,
+        state_inspector: Arc<ConsensusStateInspector>,
     ) -> Self {
         Self {
             consensus_config,
@@ -192,6 +195,7 @@ impl ExecutionProxyClient {
             rand_storage,
             consensus_observer_config,
             consensus_publisher,
+            state_inspector,
         }
     }
 
@@ -293,6 +297,7 @@ impl ExecutionProxyClient {
             consensus_publisher,
             self.consensus_config
                 .max_pending_rounds_in_commit_vote_cache,
+            self.state_inspector.clone(),
         );
 
         tokio::spawn(execution_schedule_phase.start());
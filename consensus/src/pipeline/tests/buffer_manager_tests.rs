@@ -19,6 +19,7 @@ use crate::{
         signing_phase::SigningPhase,
         tests::test_utils::prepare_executed_blocks_with_ledger_info,
     },
+    state_inspector::ConsensusStateInspector,
     test_utils::{
         consensus_runtime, timed_block_on, MockStorage, RandomComputeResultStateComputer,
     },
@@ -158,6 +159,7 @@ pub fn prepare_buffer_manager(
         ConsensusObserverConfig::default(),
         None,
         100,
+        Arc::new(ConsensusStateInspector::default()),
     );
 
     (
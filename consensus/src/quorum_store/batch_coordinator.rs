@@ -8,6 +8,7 @@ use crate::{
         batch_generator::BatchGeneratorCommand,
         batch_store::{BatchStore, BatchWriter},
         counters,
+        fairness_quotas::TxnCounts,
         proof_manager::ProofManagerCommand,
         tracing::{observe_batch, BatchStage},
         types::{Batch, PersistedValue},
@@ -95,6 +96,7 @@ impl BatchCoordinator {
                     (
                         persisted_value.batch_info().clone(),
                         persisted_value.summary(),
+                        TxnCounts::from_txns(persisted_value.payload().iter().flatten()),
                     )
                 })
                 .collect();
@@ -6,6 +6,7 @@ use crate::{
     quorum_store::{
         batch_store::BatchWriter,
         counters,
+        fairness_quotas::{filter_txns_by_quotas, FairnessQuotas},
         quorum_store_db::QuorumStoreStorage,
         types::Batch,
         utils::{MempoolProxy, TimeExpirations},
@@ -342,6 +343,13 @@ impl BatchGenerator {
             .await
             .unwrap_or_default();
 
+        // Apply the fairness quotas before the transactions are reordered by gas price
+        let mut fairness_quotas = FairnessQuotas::new(
+            self.config.fairness.batch_max_txns_per_sender,
+            self.config.fairness.batch_max_txns_per_use_case,
+        );
+        filter_txns_by_quotas(&mut fairness_quotas, &mut pulled_txns);
+
         trace!("QS: pulled_txns len: {:?}", pulled_txns.len());
 
         if pulled_txns.is_empty() {
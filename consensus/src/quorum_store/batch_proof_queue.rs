@@ -5,7 +5,11 @@ use super::{
     batch_store::BatchStore,
     utils::{BatchKey, BatchSortKey, TimeExpirations},
 };
-use crate::quorum_store::counters;
+use crate::quorum_store::{
+    counters,
+    fairness_quotas::{FairnessQuotas, TxnCounts, BLOCK_INCLUSION_STAGE},
+};
+use aptos_config::config::QuorumStoreFairnessConfig;
 use aptos_consensus_types::{
     common::{Author, TxnSummaryWithExpiration},
     payload::TDataInfo,
@@ -34,6 +38,9 @@ struct QueueItem {
     /// Contains the summary of transactions in the batch.
     /// It is optional as the summary can be updated after the proof.
     txn_summaries: Option<Vec<TxnSummaryWithExpiration>>,
+    /// Contains the number of transactions per sender and use case in the batch.
+    /// It is set together with the transaction summaries.
+    txn_counts: Option<TxnCounts>,
 
     /// Contains the proof associated with the batch.
     /// It is optional as the proof can be updated after the summary.
@@ -51,6 +58,7 @@ impl QueueItem {
         self.proof = None;
         self.proof_insertion_time = None;
         self.txn_summaries = None;
+        self.txn_counts = None;
     }
 }
 
@@ -74,6 +82,7 @@ pub struct BatchProofQueue {
     remaining_local_proofs: u64,
 
     batch_expiry_gap_when_init_usecs: u64,
+    fairness_config: QuorumStoreFairnessConfig,
 }
 
 impl BatchProofQueue {
@@ -81,6 +90,7 @@ impl BatchProofQueue {
         my_peer_id: PeerId,
         batch_store: Arc<BatchStore>,
         batch_expiry_gap_when_init_usecs: u64,
+        fairness_config: QuorumStoreFairnessConfig,
     ) -> Self {
         Self {
             my_peer_id,
@@ -95,9 +105,19 @@ impl BatchProofQueue {
             remaining_local_txns: 0,
             remaining_local_proofs: 0,
             batch_expiry_gap_when_init_usecs,
+            fairness_config,
         }
     }
 
+    /// Returns the fairness quotas for a block. The same quotas should be passed to
+    /// all the pulls for the block (i.e., proofs, opt batches and inline batches).
+    pub(crate) fn new_block_fairness_quotas(&self) -> FairnessQuotas {
+        FairnessQuotas::new(
+            self.fairness_config.block_max_txns_per_sender,
+            self.fairness_config.block_max_txns_per_use_case,
+        )
+    }
+
     #[inline]
     fn inc_remaining_proofs(&mut self, author: &PeerId, num_txns: u64) {
         self.remaining_txns_with_duplicates += num_txns;
@@ -239,6 +259,7 @@ impl BatchProofQueue {
                     proof: Some(proof),
                     proof_insertion_time: Some(Instant::now()),
                     txn_summaries: None,
+                    txn_counts: None,
                 });
             },
         }
@@ -258,11 +279,11 @@ impl BatchProofQueue {
 
     pub fn insert_batches(
         &mut self,
-        batches_with_txn_summaries: Vec<(BatchInfo, Vec<TxnSummaryWithExpiration>)>,
+        batches_with_txn_summaries: Vec<(BatchInfo, Vec<TxnSummaryWithExpiration>, TxnCounts)>,
     ) {
         let start = Instant::now();
 
-        for (batch_info, txn_summaries) in batches_with_txn_summaries.into_iter() {
+        for (batch_info, txn_summaries, txn_counts) in batches_with_txn_summaries.into_iter() {
             let batch_sort_key = BatchSortKey::from_info(&batch_info);
             let batch_key = BatchKey::from_info(&batch_info);
 
@@ -300,7 +321,9 @@ impl BatchProofQueue {
 
             match self.items.entry(batch_key) {
                 Entry::Occupied(mut entry) => {
-                    entry.get_mut().txn_summaries = Some(txn_summaries);
+                    let item = entry.get_mut();
+                    item.txn_summaries = Some(txn_summaries);
+                    item.txn_counts = Some(txn_counts);
                 },
                 Entry::Vacant(entry) => {
                     entry.insert(QueueItem {
@@ -308,6 +331,7 @@ impl BatchProofQueue {
                         proof: None,
                         proof_insertion_time: None,
                         txn_summaries: Some(txn_summaries),
+                        txn_counts: Some(txn_counts),
                     });
                 },
             }
@@ -406,6 +430,7 @@ impl BatchProofQueue {
         soft_max_txns_after_filtering: u64,
         return_non_full: bool,
         block_timestamp: Duration,
+        fairness_quotas: &mut FairnessQuotas,
     ) -> (Vec<ProofOfStore>, PayloadTxnsSize, u64, bool) {
         let (result, all_txns, unique_txns, is_full) = self.pull_internal(
             false,
@@ -417,6 +442,7 @@ impl BatchProofQueue {
             return_non_full,
             block_timestamp,
             None,
+            fairness_quotas,
         );
         let proof_of_stores: Vec<_> = result
             .into_iter()
@@ -464,6 +490,7 @@ impl BatchProofQueue {
         return_non_full: bool,
         block_timestamp: Duration,
         minimum_batch_age_usecs: Option<u64>,
+        fairness_quotas: &mut FairnessQuotas,
     ) -> (Vec<BatchInfo>, PayloadTxnsSize, u64) {
         let (result, pulled_txns, unique_txns, is_full) = self.pull_batches_internal(
             excluded_batches,
@@ -474,6 +501,7 @@ impl BatchProofQueue {
             return_non_full,
             block_timestamp,
             minimum_batch_age_usecs,
+            fairness_quotas,
         );
 
         if is_full || return_non_full {
@@ -497,6 +525,7 @@ impl BatchProofQueue {
         return_non_full: bool,
         block_timestamp: Duration,
         minimum_batch_age_usecs: Option<u64>,
+        fairness_quotas: &mut FairnessQuotas,
     ) -> (Vec<BatchInfo>, PayloadTxnsSize, u64, bool) {
         let (result, all_txns, unique_txns, is_full) = self.pull_internal(
             true,
@@ -508,6 +537,7 @@ impl BatchProofQueue {
             return_non_full,
             block_timestamp,
             minimum_batch_age_usecs,
+            fairness_quotas,
         );
         let batches = result.into_iter().map(|item| item.info.clone()).collect();
         (batches, all_txns, unique_txns, is_full)
@@ -521,6 +551,7 @@ impl BatchProofQueue {
         soft_max_txns_after_filtering: u64,
         return_non_full: bool,
         block_timestamp: Duration,
+        fairness_quotas: &mut FairnessQuotas,
     ) -> (
         Vec<(BatchInfo, Vec<SignedTransaction>)>,
         PayloadTxnsSize,
@@ -535,6 +566,7 @@ impl BatchProofQueue {
             return_non_full,
             block_timestamp,
             None,
+            fairness_quotas,
         );
         let mut result = Vec::new();
         for batch in batches.into_iter() {
@@ -570,12 +602,16 @@ impl BatchProofQueue {
         return_non_full: bool,
         block_timestamp: Duration,
         min_batch_age_usecs: Option<u64>,
+        fairness_quotas: &mut FairnessQuotas,
     ) -> (Vec<&QueueItem>, PayloadTxnsSize, u64, bool) {
         let mut result = Vec::new();
         let mut cur_unique_txns = 0;
         let mut cur_all_txns = PayloadTxnsSize::zero();
         let mut excluded_txns = 0;
         let mut full = false;
+        // The quotas are shared by all the pulls for a block, so only update
+        // them if the pulled batches are returned.
+        let mut pull_fairness_quotas = fairness_quotas.clone();
         // Set of all the excluded transactions and all the transactions included in the result
         let mut filtered_txns = HashSet::new();
         for batch_info in excluded_batches {
@@ -634,6 +670,19 @@ impl BatchProofQueue {
                 if let Some((batch, item)) = iter.next() {
                     if excluded_batches.contains(batch) {
                         excluded_txns += batch.num_txns();
+                    } else if let Err(quota_kind) = match &item.txn_counts {
+                        Some(txn_counts) => pull_fairness_quotas.check(txn_counts),
+                        // The senders and use cases of the batch are unknown (the batch
+                        // was never received), so count the batch conservatively.
+                        None => pull_fairness_quotas.check_unknown(batch.num_txns()),
+                    } {
+                        // Including this batch would exceed a fairness quota, skip it
+                        // (but keep pulling the next batches of the author).
+                        counters::fairness_quota_hit(
+                            BLOCK_INCLUSION_STAGE,
+                            quota_kind,
+                            batch.num_txns(),
+                        );
                     } else {
                         // Calculate the number of unique transactions if this batch is included in the result
                         let unique_txns = if let Some(ref txn_summaries) = item.txn_summaries {
@@ -672,6 +721,10 @@ impl BatchProofQueue {
                                         })
                                         .count() as u64
                                 });
+                        match &item.txn_counts {
+                            Some(txn_counts) => pull_fairness_quotas.add(txn_counts),
+                            None => pull_fairness_quotas.add_unknown(batch.num_txns()),
+                        }
                         assert!(item.proof.is_none() == batches_without_proofs);
                         result.push(item);
                         if cur_all_txns == max_txns
@@ -706,6 +759,7 @@ impl BatchProofQueue {
         counters::EXCLUDED_TXNS_WHEN_PULL.observe(excluded_txns as f64);
 
         if full || return_non_full {
+            *fairness_quotas = pull_fairness_quotas;
             // Stable sort, so the order of proofs within an author will not change.
             result.sort_by_key(|item| Reverse(item.info.gas_bucket_start()));
             (result, cur_all_txns, cur_unique_txns, full)
@@ -899,6 +953,7 @@ impl BatchProofQueue {
                 self.items.insert(batch_key, QueueItem {
                     info: batch,
                     txn_summaries: None,
+                    txn_counts: None,
                     proof: None,
                     proof_insertion_time: None,
                 });
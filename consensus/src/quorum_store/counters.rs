@@ -3,6 +3,7 @@
 
 #![allow(clippy::unwrap_used)]
 
+use crate::quorum_store::fairness_quotas::QuotaKind;
 use aptos_consensus_types::block::Block;
 use aptos_metrics_core::{
     exponential_buckets, op_counters::DurationHistogram, register_avg_counter, register_histogram,
@@ -1065,3 +1066,19 @@ pub static PROOF_MANAGER_OUT_OF_ORDER_PROOF_INSERTION: Lazy<IntCounterVec> = Laz
     )
     .unwrap()
 });
+
+/// Number of transactions excluded from batches or blocks because of a fairness quota
+static FAIRNESS_QUOTA_HITS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "quorum_store_fairness_quota_hits",
+        "Number of transactions excluded because of a per-sender or per-use-case quota",
+        &["stage", "quota"]
+    )
+    .unwrap()
+});
+
+pub(crate) fn fairness_quota_hit(stage: &str, quota_kind: QuotaKind, num_txns: u64) {
+    FAIRNESS_QUOTA_HITS
+        .with_label_values(&[stage, quota_kind.get_label()])
+        .inc_by(num_txns);
+}
@@ -0,0 +1,183 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::quorum_store::counters;
+use aptos_types::transaction::use_case::{UseCaseAwareTransaction, UseCaseKey};
+use move_core_types::account_address::AccountAddress;
+use std::collections::HashMap;
+
+pub(crate) const BATCH_CREATION_STAGE: &str = "batch_creation";
+pub(crate) const BLOCK_INCLUSION_STAGE: &str = "block_inclusion";
+
+/// The quota that prevented transactions from being included
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum QuotaKind {
+    Sender,
+    UseCase,
+}
+
+impl QuotaKind {
+    pub(crate) fn get_label(&self) -> &'static str {
+        match self {
+            QuotaKind::Sender => "sender",
+            QuotaKind::UseCase => "use_case",
+        }
+    }
+}
+
+/// The number of transactions per sender and per use case in a batch
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TxnCounts {
+    per_sender: HashMap<AccountAddress, u64>,
+    per_use_case: HashMap<UseCaseKey, u64>,
+}
+
+impl TxnCounts {
+    pub fn from_txns<'a, T: UseCaseAwareTransaction + 'a>(
+        txns: impl IntoIterator<Item = &'a T>,
+    ) -> Self {
+        let mut txn_counts = Self::default();
+        for txn in txns {
+            *txn_counts.per_sender.entry(txn.parse_sender()).or_insert(0) += 1;
+            *txn_counts
+                .per_use_case
+                .entry(txn.parse_use_case())
+                .or_insert(0) += 1;
+        }
+        txn_counts
+    }
+}
+
+/// Tracks the number of transactions per sender and per use case included so far
+/// (e.g., in the batches created from a mempool pull, or in a block), and checks
+/// them against the configured quotas.
+#[derive(Clone)]
+pub(crate) struct FairnessQuotas {
+    max_txns_per_sender: u64,
+    max_txns_per_use_case: u64,
+    txn_counts: TxnCounts,
+    // The highest number of transactions of a single sender and of a single use case
+    max_sender_txns: u64,
+    max_use_case_txns: u64,
+    // The number of included transactions with unknown senders and use cases (e.g.,
+    // the transactions of proofs whose batches were never received). These are
+    // conservatively counted towards every sender and every use case.
+    num_unknown_txns: u64,
+}
+
+impl FairnessQuotas {
+    pub(crate) fn new(max_txns_per_sender: u64, max_txns_per_use_case: u64) -> Self {
+        Self {
+            max_txns_per_sender,
+            max_txns_per_use_case,
+            txn_counts: TxnCounts::default(),
+            max_sender_txns: 0,
+            max_use_case_txns: 0,
+            num_unknown_txns: 0,
+        }
+    }
+
+    /// Returns true iff the quotas can't be exceeded (i.e., they are disabled)
+    pub(crate) fn is_unlimited(&self) -> bool {
+        self.max_txns_per_sender == u64::MAX && self.max_txns_per_use_case == u64::MAX
+    }
+
+    /// Returns the quota that would be exceeded by adding the given transactions (if any)
+    pub(crate) fn check(&self, txn_counts: &TxnCounts) -> Result<(), QuotaKind> {
+        for (sender, num_txns) in &txn_counts.per_sender {
+            let current = self.txn_counts.per_sender.get(sender).unwrap_or(&0);
+            if current
+                .saturating_add(self.num_unknown_txns)
+                .saturating_add(*num_txns)
+                > self.max_txns_per_sender
+            {
+                return Err(QuotaKind::Sender);
+            }
+        }
+        for (use_case, num_txns) in &txn_counts.per_use_case {
+            let current = self.txn_counts.per_use_case.get(use_case).unwrap_or(&0);
+            if current
+                .saturating_add(self.num_unknown_txns)
+                .saturating_add(*num_txns)
+                > self.max_txns_per_use_case
+            {
+                return Err(QuotaKind::UseCase);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the quota that could be exceeded by adding the given number of transactions
+    /// with unknown senders and use cases (if any). All of them are assumed to be from the
+    /// sender and of the use case with the most transactions so far.
+    pub(crate) fn check_unknown(&self, num_txns: u64) -> Result<(), QuotaKind> {
+        let num_txns = self.num_unknown_txns.saturating_add(num_txns);
+        if self.max_sender_txns.saturating_add(num_txns) > self.max_txns_per_sender {
+            return Err(QuotaKind::Sender);
+        }
+        if self.max_use_case_txns.saturating_add(num_txns) > self.max_txns_per_use_case {
+            return Err(QuotaKind::UseCase);
+        }
+        Ok(())
+    }
+
+    /// Adds the given transactions to the counts, without checking the quotas
+    pub(crate) fn add(&mut self, txn_counts: &TxnCounts) {
+        for (sender, num_txns) in &txn_counts.per_sender {
+            let count = self.txn_counts.per_sender.entry(*sender).or_insert(0);
+            *count += num_txns;
+            self.max_sender_txns = self.max_sender_txns.max(*count);
+        }
+        for (use_case, num_txns) in &txn_counts.per_use_case {
+            let count = self
+                .txn_counts
+                .per_use_case
+                .entry(use_case.clone())
+                .or_insert(0);
+            *count += num_txns;
+            self.max_use_case_txns = self.max_use_case_txns.max(*count);
+        }
+    }
+
+    /// Adds the given number of transactions with unknown senders and use cases
+    /// to the counts, without checking the quotas
+    pub(crate) fn add_unknown(&mut self, num_txns: u64) {
+        self.num_unknown_txns = self.num_unknown_txns.saturating_add(num_txns);
+    }
+
+    /// Adds the given transactions to the counts if no quota is exceeded
+    pub(crate) fn try_add(&mut self, txn_counts: &TxnCounts) -> Result<(), QuotaKind> {
+        self.check(txn_counts)?;
+        self.add(txn_counts);
+        Ok(())
+    }
+}
+
+/// Filters the given transactions (pulled from mempool) so that no quota is exceeded.
+/// Once a transaction of a sender is excluded, all the following transactions of the
+/// same sender are excluded as well, to avoid gaps in the sequence numbers.
+pub(crate) fn filter_txns_by_quotas<T: UseCaseAwareTransaction>(
+    quotas: &mut FairnessQuotas,
+    txns: &mut Vec<T>,
+) {
+    if quotas.is_unlimited() {
+        return;
+    }
+
+    let mut excluded_senders: HashMap<AccountAddress, QuotaKind> = HashMap::new();
+    txns.retain(|txn| {
+        let sender = txn.parse_sender();
+        let result = match excluded_senders.get(&sender) {
+            Some(quota_kind) => Err(*quota_kind),
+            None => quotas.try_add(&TxnCounts::from_txns([txn])),
+        };
+        match result {
+            Ok(()) => true,
+            Err(quota_kind) => {
+                excluded_senders.insert(sender, quota_kind);
+                counters::fairness_quota_hit(BATCH_CREATION_STAGE, quota_kind, 1);
+                false
+            },
+        }
+    });
+}
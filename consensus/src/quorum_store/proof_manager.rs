@@ -4,8 +4,13 @@
 use super::batch_store::BatchStore;
 use crate::{
     monitor,
-    quorum_store::{batch_generator::BackPressure, batch_proof_queue::BatchProofQueue, counters},
+    quorum_store::{
+        batch_generator::BackPressure, batch_proof_queue::BatchProofQueue, counters,
+        fairness_quotas::TxnCounts,
+    },
+    state_inspector::{ConsensusStateInspector, ProofQueueState},
 };
+use aptos_config::config::QuorumStoreFairnessConfig;
 use aptos_consensus_types::{
     common::{Payload, PayloadFilter, ProofWithData, TxnSummaryWithExpiration},
     payload::{OptQuorumStorePayload, PayloadExecutionLimit},
@@ -13,6 +18,7 @@ use aptos_consensus_types::{
     request_response::{GetPayloadCommand, GetPayloadResponse},
     utils::PayloadTxnsSize,
 };
+use aptos_infallible::duration_since_epoch;
 use aptos_logger::prelude::*;
 use aptos_types::PeerId;
 use futures::StreamExt;
@@ -22,7 +28,7 @@ use std::{cmp::min, collections::HashSet, sync::Arc, time::Duration};
 #[derive(Debug)]
 pub enum ProofManagerCommand {
     ReceiveProofs(ProofOfStoreMsg),
-    ReceiveBatches(Vec<(BatchInfo, Vec<TxnSummaryWithExpiration>)>),
+    ReceiveBatches(Vec<(BatchInfo, Vec<TxnSummaryWithExpiration>, TxnCounts)>),
     CommitNotification(u64, Vec<BatchInfo>),
     Shutdown(tokio::sync::oneshot::Sender<()>),
 }
@@ -35,6 +41,7 @@ pub struct ProofManager {
     remaining_total_proof_num: u64,
     allow_batches_without_pos_in_proposal: bool,
     enable_payload_v2: bool,
+    state_inspector: Arc<ConsensusStateInspector>,
 }
 
 impl ProofManager {
@@ -46,12 +53,15 @@ impl ProofManager {
         allow_batches_without_pos_in_proposal: bool,
         enable_payload_v2: bool,
         batch_expiry_gap_when_init_usecs: u64,
+        fairness_config: QuorumStoreFairnessConfig,
+        state_inspector: Arc<ConsensusStateInspector>,
     ) -> Self {
         Self {
             batch_proof_queue: BatchProofQueue::new(
                 my_peer_id,
                 batch_store,
                 batch_expiry_gap_when_init_usecs,
+                fairness_config,
             ),
             back_pressure_total_txn_limit,
             remaining_total_txn_num: 0,
@@ -59,6 +69,7 @@ impl ProofManager {
             remaining_total_proof_num: 0,
             allow_batches_without_pos_in_proposal,
             enable_payload_v2,
+            state_inspector,
         }
     }
 
@@ -70,16 +81,28 @@ impl ProofManager {
     }
 
     fn update_remaining_txns_and_proofs(&mut self) {
-        sample!(
-            SampleRate::Duration(Duration::from_millis(200)),
+        sample!(SampleRate::Duration(Duration::from_millis(200)), {
             (self.remaining_total_txn_num, self.remaining_total_proof_num) =
                 self.batch_proof_queue.remaining_txns_and_proofs();
-        );
+            self.publish_state();
+        });
+    }
+
+    fn publish_state(&self) {
+        self.state_inspector
+            .update_proof_queue_state(ProofQueueState {
+                updated_at_usecs: duration_since_epoch().as_micros() as u64,
+                remaining_txns: self.remaining_total_txn_num,
+                remaining_proofs: self.remaining_total_proof_num,
+                batches_without_proof: self.batch_proof_queue.num_batches_without_proof(),
+                back_pressure_txn_limit: self.back_pressure_total_txn_limit,
+                back_pressure_proof_limit: self.back_pressure_total_proof_limit,
+            });
     }
 
     pub(crate) fn receive_batches(
         &mut self,
-        batch_summaries: Vec<(BatchInfo, Vec<TxnSummaryWithExpiration>)>,
+        batch_summaries: Vec<(BatchInfo, Vec<TxnSummaryWithExpiration>, TxnCounts)>,
     ) {
         self.batch_proof_queue.insert_batches(batch_summaries);
         self.update_remaining_txns_and_proofs();
@@ -111,6 +134,8 @@ impl ProofManager {
             PayloadFilter::InQuorumStore(batches) => batches,
         };
 
+        // The fairness quotas are shared by all the pulls for the block
+        let mut fairness_quotas = self.batch_proof_queue.new_block_fairness_quotas();
         let (proof_block, txns_with_proof_size, cur_unique_txns, proof_queue_fully_utilized) =
             self.batch_proof_queue.pull_proofs(
                 &excluded_batches,
@@ -119,6 +144,7 @@ impl ProofManager {
                 request.soft_max_txns_after_filtering,
                 request.return_non_full,
                 request.block_timestamp,
+                &mut fairness_quotas,
             );
 
         counters::NUM_BATCHES_WITHOUT_PROOF_OF_STORE
@@ -145,6 +171,7 @@ impl ProofManager {
                         request.return_non_full,
                         request.block_timestamp,
                         Some(params.minimum_batch_age_usecs),
+                        &mut fairness_quotas,
                     );
                 (opt_batches, opt_payload_size)
             } else {
@@ -177,6 +204,7 @@ impl ProofManager {
                         request.soft_max_txns_after_filtering,
                         request.return_non_full,
                         request.block_timestamp,
+                        &mut fairness_quotas,
                     );
                 (inline_batches, inline_payload_size)
             } else {
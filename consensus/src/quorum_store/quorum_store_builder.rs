@@ -26,6 +26,7 @@ use crate::{
         types::{Batch, BatchResponse},
     },
     round_manager::VerifiedEvent,
+    state_inspector::ConsensusStateInspector,
 };
 use aptos_channels::{aptos_channel, message_queues::QueueStyle};
 use aptos_config::config::{BatchTransactionFilterConfig, QuorumStoreConfig};
@@ -152,6 +153,7 @@ pub struct InnerBuilder {
     batch_reader: Option<Arc<dyn BatchReader>>,
     broadcast_proofs: bool,
     consensus_key: Arc<PrivateKey>,
+    state_inspector: Arc<ConsensusStateInspector>,
 }
 
 impl InnerBuilder {
@@ -172,6 +174,7 @@ impl InnerBuilder {
         quorum_store_storage: Arc<dyn QuorumStoreStorage>,
         broadcast_proofs: bool,
         consensus_key: Arc<PrivateKey>,
+        state_inspector: Arc<ConsensusStateInspector>,
     ) -> Self {
         let (coordinator_tx, coordinator_rx) = futures_channel::mpsc::channel(config.channel_size);
         let (batch_generator_cmd_tx, batch_generator_cmd_rx) =
@@ -228,6 +231,7 @@ impl InnerBuilder {
             batch_reader: None,
             broadcast_proofs,
             consensus_key,
+            state_inspector,
         }
     }
 
@@ -371,6 +375,8 @@ impl InnerBuilder {
             self.config.allow_batches_without_pos_in_proposal,
             self.config.enable_payload_v2,
             self.config.batch_expiry_gap_when_init_usecs,
+            self.config.fairness,
+            self.state_inspector.clone(),
         );
         spawn_named!(
             "proof_manager",
@@ -2,8 +2,11 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::quorum_store::{
-    batch_proof_queue::BatchProofQueue, tests::batch_store_test::batch_store_for_test,
+    batch_proof_queue::BatchProofQueue,
+    fairness_quotas::TxnCounts,
+    tests::{batch_store_test::batch_store_for_test, fairness_quotas_test::create_txns},
 };
+use aptos_config::config::QuorumStoreFairnessConfig;
 use aptos_consensus_types::{
     common::TxnSummaryWithExpiration,
     proof_of_store::{BatchInfo, ProofOfStore},
@@ -65,7 +68,12 @@ fn proof_of_store_with_size(
 async fn test_proof_queue_sorting() {
     let my_peer_id = PeerId::random();
     let batch_store = batch_store_for_test(5 * 1024 * 1024);
-    let mut proof_queue = BatchProofQueue::new(my_peer_id, batch_store, 1);
+    let mut proof_queue = BatchProofQueue::new(
+        my_peer_id,
+        batch_store,
+        1,
+        QuorumStoreFairnessConfig::default(),
+    );
 
     let author_0 = PeerId::random();
     let author_1 = PeerId::random();
@@ -97,6 +105,7 @@ async fn test_proof_queue_sorting() {
         2,
         true,
         aptos_infallible::duration_since_epoch(),
+        &mut proof_queue.new_block_fairness_quotas(),
     );
     let mut count_author_0 = 0;
     let mut count_author_1 = 0;
@@ -126,6 +135,7 @@ async fn test_proof_queue_sorting() {
         4,
         true,
         aptos_infallible::duration_since_epoch(),
+        &mut proof_queue.new_block_fairness_quotas(),
     );
     let mut count_author_0 = 0;
     let mut count_author_1 = 0;
@@ -152,7 +162,12 @@ async fn test_proof_queue_sorting() {
 async fn test_proof_calculate_remaining_txns_and_proofs() {
     let my_peer_id = PeerId::random();
     let batch_store = batch_store_for_test(5 * 1024 * 1024);
-    let mut proof_queue = BatchProofQueue::new(my_peer_id, batch_store, 1);
+    let mut proof_queue = BatchProofQueue::new(
+        my_peer_id,
+        batch_store,
+        1,
+        QuorumStoreFairnessConfig::default(),
+    );
     let now_in_secs = aptos_infallible::duration_since_epoch().as_secs() as u64;
     let now_in_usecs = aptos_infallible::duration_since_epoch().as_micros() as u64;
     let author_0 = PeerId::random();
@@ -237,7 +252,7 @@ async fn test_proof_calculate_remaining_txns_and_proofs() {
     let info_7 = author_1_batches[2].info().clone();
     let info_8 = author_1_batches[3].info().clone();
 
-    proof_queue.insert_batches(vec![(info_1.clone(), vec![txns[0]])]);
+    proof_queue.insert_batches(vec![(info_1.clone(), vec![txns[0]], TxnCounts::default())]);
     // batch_summaries: [1 -> txn_0]
     assert_eq!(proof_queue.remaining_txns_and_proofs(), (0, 0));
     assert_eq!(proof_queue.batch_summaries_len(), 1);
@@ -256,14 +271,14 @@ async fn test_proof_calculate_remaining_txns_and_proofs() {
     assert_eq!(proof_queue.remaining_txns_and_proofs(), (2, 2));
     assert_eq!(proof_queue.batch_summaries_len(), 1);
 
-    proof_queue.insert_batches(vec![(info_2, vec![txns[1]])]);
+    proof_queue.insert_batches(vec![(info_2, vec![txns[1]], TxnCounts::default())]);
     // txns: [txn_0, txn_1]
     // proofs: [1, 2]
     // batch_summaries: [1 -> txn_0, 2 -> txn_1]
     assert_eq!(proof_queue.remaining_txns_and_proofs(), (2, 2));
     assert_eq!(proof_queue.batch_summaries_len(), 2);
 
-    proof_queue.insert_batches(vec![(info_3.clone(), vec![txns[0]])]);
+    proof_queue.insert_batches(vec![(info_3.clone(), vec![txns[0]], TxnCounts::default())]);
     // txns: [txn_0, txn_1]
     // proofs: [1, 2]
     // batch_summaries: [1 -> txn_0, 2 -> txn_1, 3 -> txn_0]
@@ -271,7 +286,7 @@ async fn test_proof_calculate_remaining_txns_and_proofs() {
     assert_eq!(proof_queue.batch_summaries_len(), 3);
 
     // Adding the batch again shouldn't have an effect
-    proof_queue.insert_batches(vec![(info_3.clone(), vec![txns[0]])]);
+    proof_queue.insert_batches(vec![(info_3.clone(), vec![txns[0]], TxnCounts::default())]);
     // txns: [txn_0, txn_1]
     // proofs: [1, 2]
     // batch_summaries: [1 -> txn_0, 2 -> txn_1, 3 -> txn_0]
@@ -286,7 +301,7 @@ async fn test_proof_calculate_remaining_txns_and_proofs() {
     assert_eq!(proof_queue.batch_summaries_len(), 3);
 
     // Adding the batch again shouldn't have an effect
-    proof_queue.insert_batches(vec![(info_3.clone(), vec![txns[0]])]);
+    proof_queue.insert_batches(vec![(info_3.clone(), vec![txns[0]], TxnCounts::default())]);
     // txns: [txn_0, txn_1]
     // proofs: [1, 2, 3]
     // batch_summaries: [1 -> txn_0, 2 -> txn_1, 3 -> txn_0]
@@ -300,14 +315,14 @@ async fn test_proof_calculate_remaining_txns_and_proofs() {
     assert_eq!(proof_queue.remaining_txns_and_proofs(), (3, 4));
     assert_eq!(proof_queue.batch_summaries_len(), 3);
 
-    proof_queue.insert_batches(vec![(info_5, vec![txns[1]])]);
+    proof_queue.insert_batches(vec![(info_5, vec![txns[1]], TxnCounts::default())]);
     // txns: [txn_0, txn_1]
     // proofs: [1, 2, 3, 5]
     // batch_summaries: [1 -> txn_0, 2 -> txn_1, 3 -> txn_0, 5 -> txn_1]
     assert_eq!(proof_queue.remaining_txns_and_proofs(), (2, 4));
     assert_eq!(proof_queue.batch_summaries_len(), 4);
 
-    proof_queue.insert_batches(vec![(info_4, vec![txns[2]])]);
+    proof_queue.insert_batches(vec![(info_4, vec![txns[2]], TxnCounts::default())]);
     // txns: [txn_0, txn_1]
     // proofs: [1, 2, 3, 5]
     // batch_summaries: [1 -> txn_0, 2 -> txn_1, 3 -> txn_0, 4 -> txn_2, 5 -> txn_1]
@@ -344,7 +359,7 @@ async fn test_proof_calculate_remaining_txns_and_proofs() {
     assert_eq!(proof_queue.batch_summaries_len(), 1);
 
     // Adding an expired batch again
-    proof_queue.insert_batches(vec![(info_3, vec![txns[0]])]);
+    proof_queue.insert_batches(vec![(info_3, vec![txns[0]], TxnCounts::default())]);
     // txns: [txn_1] + txns(proof_6)
     // proofs: [2, 6]
     // batch_summaries: [2 -> txn_1, 3 -> txn_0]
@@ -359,7 +374,7 @@ async fn test_proof_calculate_remaining_txns_and_proofs() {
     assert_eq!(proof_queue.remaining_txns_and_proofs(), (2, 2));
     assert_eq!(proof_queue.batch_summaries_len(), 2);
 
-    proof_queue.insert_batches(vec![(info_7, vec![txns[3]])]);
+    proof_queue.insert_batches(vec![(info_7, vec![txns[3]], TxnCounts::default())]);
     // txns: [txn_1] + txns(proof_6)
     // proofs: [2, 6]
     // batch_summaries: [2 -> txn_1, 7 -> txn_3, 3 -> txn_0]
@@ -374,7 +389,7 @@ async fn test_proof_calculate_remaining_txns_and_proofs() {
     assert_eq!(proof_queue.remaining_txns_and_proofs(), (1, 1));
     assert_eq!(proof_queue.batch_summaries_len(), 2);
 
-    proof_queue.insert_batches(vec![(info_6, vec![txns[0]])]);
+    proof_queue.insert_batches(vec![(info_6, vec![txns[0]], TxnCounts::default())]);
     // Expired batch not added to batch summaries
     // txns: [txn_1]
     // proofs: [2]
@@ -403,7 +418,7 @@ async fn test_proof_calculate_remaining_txns_and_proofs() {
     assert_eq!(proof_queue.remaining_txns_and_proofs(), (2, 2));
     assert_eq!(proof_queue.batch_summaries_len(), 3);
 
-    proof_queue.insert_batches(vec![(info_8, vec![txns[0]])]);
+    proof_queue.insert_batches(vec![(info_8, vec![txns[0]], TxnCounts::default())]);
     // Committed batch not added to batch summaries
     // txns: [txn_1, txn_3]
     // proofs: [2, 7]
@@ -432,7 +447,12 @@ async fn test_proof_calculate_remaining_txns_and_proofs() {
 async fn test_proof_pull_proofs_with_duplicates() {
     let my_peer_id = PeerId::random();
     let batch_store = batch_store_for_test(5 * 1024 * 1024);
-    let mut proof_queue = BatchProofQueue::new(my_peer_id, batch_store, 1);
+    let mut proof_queue = BatchProofQueue::new(
+        my_peer_id,
+        batch_store,
+        1,
+        QuorumStoreFairnessConfig::default(),
+    );
     let now_in_secs = aptos_infallible::duration_since_epoch().as_secs() as u64;
     let now_in_usecs = now_in_secs * 1_000_000;
     let txns = vec![
@@ -522,19 +542,51 @@ async fn test_proof_pull_proofs_with_duplicates() {
     let info_0 = author_0_batches[0].info().clone();
     let info_7 = author_1_batches[2].info().clone();
 
-    proof_queue.insert_batches(vec![(author_0_batches[0].info().clone(), vec![txns[0]])]);
-    proof_queue.insert_batches(vec![(author_0_batches[1].info().clone(), vec![txns[1]])]);
-    proof_queue.insert_batches(vec![(author_0_batches[2].info().clone(), vec![txns[2]])]);
-    proof_queue.insert_batches(vec![(author_0_batches[3].info().clone(), vec![txns[0]])]);
+    proof_queue.insert_batches(vec![(
+        author_0_batches[0].info().clone(),
+        vec![txns[0]],
+        TxnCounts::default(),
+    )]);
+    proof_queue.insert_batches(vec![(
+        author_0_batches[1].info().clone(),
+        vec![txns[1]],
+        TxnCounts::default(),
+    )]);
+    proof_queue.insert_batches(vec![(
+        author_0_batches[2].info().clone(),
+        vec![txns[2]],
+        TxnCounts::default(),
+    )]);
+    proof_queue.insert_batches(vec![(
+        author_0_batches[3].info().clone(),
+        vec![txns[0]],
+        TxnCounts::default(),
+    )]);
 
     for batch in author_0_batches {
         proof_queue.insert_proof(batch);
     }
 
-    proof_queue.insert_batches(vec![(author_1_batches[0].info().clone(), vec![txns[1]])]);
-    proof_queue.insert_batches(vec![(author_1_batches[1].info().clone(), vec![txns[2]])]);
-    proof_queue.insert_batches(vec![(author_1_batches[2].info().clone(), vec![txns[3]])]);
-    proof_queue.insert_batches(vec![(author_1_batches[3].info().clone(), vec![txns[0]])]);
+    proof_queue.insert_batches(vec![(
+        author_1_batches[0].info().clone(),
+        vec![txns[1]],
+        TxnCounts::default(),
+    )]);
+    proof_queue.insert_batches(vec![(
+        author_1_batches[1].info().clone(),
+        vec![txns[2]],
+        TxnCounts::default(),
+    )]);
+    proof_queue.insert_batches(vec![(
+        author_1_batches[2].info().clone(),
+        vec![txns[3]],
+        TxnCounts::default(),
+    )]);
+    proof_queue.insert_batches(vec![(
+        author_1_batches[3].info().clone(),
+        vec![txns[0]],
+        TxnCounts::default(),
+    )]);
 
     for batch in author_1_batches {
         proof_queue.insert_proof(batch);
@@ -548,6 +600,7 @@ async fn test_proof_pull_proofs_with_duplicates() {
         4,
         true,
         Duration::from_micros(now_in_usecs),
+        &mut proof_queue.new_block_fairness_quotas(),
     );
     assert_eq!(result.2, 4);
 
@@ -574,6 +627,7 @@ async fn test_proof_pull_proofs_with_duplicates() {
         4,
         true,
         Duration::from_micros(now_in_usecs),
+        &mut proof_queue.new_block_fairness_quotas(),
     );
     assert_eq!(result.0.len(), 7);
     // filtered_txns: txn_0 (included in excluded batches)
@@ -588,6 +642,7 @@ async fn test_proof_pull_proofs_with_duplicates() {
         5,
         true,
         Duration::from_micros(now_in_usecs + 500_100),
+        &mut proof_queue.new_block_fairness_quotas(),
     );
     assert_eq!(result.2, 4);
 
@@ -600,6 +655,7 @@ async fn test_proof_pull_proofs_with_duplicates() {
         5,
         true,
         Duration::from_micros(now_in_usecs + 1_000_100),
+        &mut proof_queue.new_block_fairness_quotas(),
     );
     assert_eq!(result.0.len(), 8);
     assert_eq!(result.2, 3);
@@ -613,6 +669,7 @@ async fn test_proof_pull_proofs_with_duplicates() {
         4,
         true,
         Duration::from_micros(now_in_usecs + 1_200_100),
+        &mut proof_queue.new_block_fairness_quotas(),
     );
     assert_eq!(result.0.len(), 7);
     assert_eq!(result.2, 3);
@@ -626,6 +683,7 @@ async fn test_proof_pull_proofs_with_duplicates() {
         4,
         true,
         Duration::from_micros(now_in_usecs + 2_000_100),
+        &mut proof_queue.new_block_fairness_quotas(),
     );
     assert_eq!(result.0.len(), 7);
     assert_eq!(result.2, 2);
@@ -639,6 +697,7 @@ async fn test_proof_pull_proofs_with_duplicates() {
         4,
         true,
         Duration::from_micros(now_in_usecs + 2_500_100),
+        &mut proof_queue.new_block_fairness_quotas(),
     );
     assert_eq!(result.0.len(), 6);
     assert_eq!(result.2, 2);
@@ -650,6 +709,7 @@ async fn test_proof_pull_proofs_with_duplicates() {
         4,
         true,
         Duration::from_micros(now_in_usecs + 2_500_100),
+        &mut proof_queue.new_block_fairness_quotas(),
     );
     // author_0_batches[0], author_1_batches[1] is removed. author_1_batches[2] is excluded. txn_0, txn_1 are expired.
     assert_eq!(result.0.len(), 5);
@@ -663,6 +723,7 @@ async fn test_proof_pull_proofs_with_duplicates() {
         8,
         true,
         Duration::from_micros(now_in_usecs + 3_000_100),
+        &mut proof_queue.new_block_fairness_quotas(),
     );
     // author_0_batches[0], author_0_batches[1], author_1_batches[1] are removed. txn_0, txn_1, txn_2 are expired.
     assert_eq!(result.0.len(), 5);
@@ -676,6 +737,7 @@ async fn test_proof_pull_proofs_with_duplicates() {
         4,
         true,
         Duration::from_micros(now_in_usecs + 3_500_100),
+        &mut proof_queue.new_block_fairness_quotas(),
     );
     // author_0_batches[0], author_0_batches[1], author_1_batches[1], author_1_batches[2] are removed. txn_0, txn_1, txn_0 are expired.
     assert_eq!(result.0.len(), 4);
@@ -689,6 +751,7 @@ async fn test_proof_pull_proofs_with_duplicates() {
         4,
         true,
         Duration::from_micros(now_in_usecs + 4_000_100),
+        &mut proof_queue.new_block_fairness_quotas(),
     );
     // author_0_batches[0], author_0_batches[1], author_0_batches[3], author_1_batches[0], author_1_batches[1], author_1_batches[2] are removed.
     // txn_0, txn_1, txn_2 are expired.
@@ -703,7 +766,12 @@ async fn test_proof_pull_proofs_with_duplicates() {
 async fn test_proof_queue_soft_limit() {
     let my_peer_id = PeerId::random();
     let batch_store = batch_store_for_test(5 * 1024 * 1024);
-    let mut proof_queue = BatchProofQueue::new(my_peer_id, batch_store, 1);
+    let mut proof_queue = BatchProofQueue::new(
+        my_peer_id,
+        batch_store,
+        1,
+        QuorumStoreFairnessConfig::default(),
+    );
 
     let author = PeerId::random();
 
@@ -723,6 +791,7 @@ async fn test_proof_queue_soft_limit() {
         12,
         true,
         aptos_infallible::duration_since_epoch(),
+        &mut proof_queue.new_block_fairness_quotas(),
     );
 
     assert_eq!(pulled.len(), 1);
@@ -735,6 +804,7 @@ async fn test_proof_queue_soft_limit() {
         12,
         true,
         aptos_infallible::duration_since_epoch(),
+        &mut proof_queue.new_block_fairness_quotas(),
     );
 
     assert_eq!(pulled.len(), 2);
@@ -745,7 +815,12 @@ async fn test_proof_queue_soft_limit() {
 async fn test_proof_queue_insert_after_commit() {
     let my_peer_id = PeerId::random();
     let batch_store = batch_store_for_test(5 * 1024);
-    let mut proof_queue = BatchProofQueue::new(my_peer_id, batch_store, 1);
+    let mut proof_queue = BatchProofQueue::new(
+        my_peer_id,
+        batch_store,
+        1,
+        QuorumStoreFairnessConfig::default(),
+    );
 
     let author = PeerId::random();
     let author_batches = vec![
@@ -777,7 +852,12 @@ async fn test_proof_queue_insert_after_commit() {
 async fn test_proof_queue_pull_full_utilization() {
     let my_peer_id = PeerId::random();
     let batch_store = batch_store_for_test(5 * 1024);
-    let mut proof_queue = BatchProofQueue::new(my_peer_id, batch_store, 1);
+    let mut proof_queue = BatchProofQueue::new(
+        my_peer_id,
+        batch_store,
+        1,
+        QuorumStoreFairnessConfig::default(),
+    );
 
     let author = PeerId::random();
     let author_batches = vec![
@@ -803,6 +883,7 @@ async fn test_proof_queue_pull_full_utilization() {
             10,
             true,
             now_in_secs,
+            &mut proof_queue.new_block_fairness_quotas(),
         );
 
     assert_eq!(proof_block.len(), 1);
@@ -819,6 +900,7 @@ async fn test_proof_queue_pull_full_utilization() {
             50,
             true,
             now_in_secs,
+            &mut proof_queue.new_block_fairness_quotas(),
         );
 
     assert_eq!(proof_block.len(), 3);
@@ -829,3 +911,191 @@ async fn test_proof_queue_pull_full_utilization() {
     proof_queue.handle_updated_block_timestamp(10);
     assert!(proof_queue.is_empty());
 }
+
+#[test]
+fn test_proof_pull_with_sender_quota() {
+    let my_peer_id = PeerId::random();
+    let batch_store = batch_store_for_test(5 * 1024 * 1024);
+    let fairness_config = QuorumStoreFairnessConfig {
+        block_max_txns_per_sender: 4,
+        ..QuorumStoreFairnessConfig::default()
+    };
+    let mut proof_queue = BatchProofQueue::new(my_peer_id, batch_store, 1, fairness_config);
+
+    // Insert 3 batches (from different authors), each with 2 txns from the same sender
+    let now_in_secs = aptos_infallible::duration_since_epoch().as_secs() as u64;
+    let now_in_usecs = now_in_secs * 1_000_000;
+    for sequence_number in 0..3 {
+        let proof = proof_of_store(
+            PeerId::random(),
+            BatchId::new_for_test(sequence_number),
+            0,
+            now_in_usecs + 1_000_000,
+        );
+        let txn_summary = TxnSummaryWithExpiration::new(
+            PeerId::ONE,
+            ReplayProtector::SequenceNumber(sequence_number),
+            now_in_secs + 1,
+            HashValue::zero(),
+        );
+        let txn_counts = TxnCounts::from_txns(&create_txns(&[(1, 2), (1, 2)]));
+        proof_queue.insert_batches(vec![(proof.info().clone(), vec![txn_summary], txn_counts)]);
+        proof_queue.insert_proof(proof);
+    }
+
+    // Only 2 of the batches fit within the sender quota
+    let (proof_block, _, _, _) = proof_queue.pull_proofs(
+        &HashSet::new(),
+        PayloadTxnsSize::new(10, 10),
+        10,
+        10,
+        true,
+        Duration::from_micros(now_in_usecs),
+        &mut proof_queue.new_block_fairness_quotas(),
+    );
+    assert_eq!(proof_block.len(), 2);
+}
+
+#[test]
+fn test_pulls_share_sender_quota() {
+    let my_peer_id = PeerId::random();
+    let batch_store = batch_store_for_test(5 * 1024 * 1024);
+    let fairness_config = QuorumStoreFairnessConfig {
+        block_max_txns_per_sender: 4,
+        ..QuorumStoreFairnessConfig::default()
+    };
+    let mut proof_queue = BatchProofQueue::new(my_peer_id, batch_store, 1, fairness_config);
+
+    // Insert 3 batches (from different authors), each with 2 txns from the same
+    // sender. Only the first batch has a proof.
+    let now_in_secs = aptos_infallible::duration_since_epoch().as_secs() as u64;
+    let now_in_usecs = now_in_secs * 1_000_000;
+    let mut proofs = vec![];
+    for batch_index in 0..3 {
+        let proof = proof_of_store_with_size(
+            PeerId::random(),
+            BatchId::new_for_test(batch_index),
+            0,
+            now_in_usecs + 1_000_000,
+            2,
+        );
+        let txn_summaries = (0..2)
+            .map(|txn_index| {
+                TxnSummaryWithExpiration::new(
+                    PeerId::ONE,
+                    ReplayProtector::SequenceNumber(batch_index * 2 + txn_index),
+                    now_in_secs + 1,
+                    HashValue::zero(),
+                )
+            })
+            .collect();
+        let txn_counts = TxnCounts::from_txns(&create_txns(&[(1, 2), (1, 2)]));
+        proof_queue.insert_batches(vec![(proof.info().clone(), txn_summaries, txn_counts)]);
+        proofs.push(proof);
+    }
+    proof_queue.insert_proof(proofs[0].clone());
+
+    // Pull the proofs and the batches for a block (with the same quotas)
+    let mut fairness_quotas = proof_queue.new_block_fairness_quotas();
+    let (proof_block, _, _, _) = proof_queue.pull_proofs(
+        &HashSet::new(),
+        PayloadTxnsSize::new(10, 100),
+        10,
+        10,
+        true,
+        Duration::from_micros(now_in_usecs),
+        &mut fairness_quotas,
+    );
+    assert_eq!(proof_block.len(), 1);
+    let excluded_batches: HashSet<_> = proof_block
+        .iter()
+        .map(|proof| proof.info().clone())
+        .collect();
+    let (batches, _, _) = proof_queue.pull_batches(
+        &excluded_batches,
+        &HashSet::new(),
+        PayloadTxnsSize::new(10, 100),
+        10,
+        10,
+        true,
+        Duration::from_micros(now_in_usecs),
+        None,
+        &mut fairness_quotas,
+    );
+
+    // Only one of the batches fits within the remaining sender quota
+    assert_eq!(batches.len(), 1);
+
+    // Both batches fit if the quotas are not shared with the proof pull
+    let (batches, _, _) = proof_queue.pull_batches(
+        &excluded_batches,
+        &HashSet::new(),
+        PayloadTxnsSize::new(10, 100),
+        10,
+        10,
+        true,
+        Duration::from_micros(now_in_usecs),
+        None,
+        &mut proof_queue.new_block_fairness_quotas(),
+    );
+    assert_eq!(batches.len(), 2);
+}
+
+#[test]
+fn test_proof_pull_with_unknown_txn_counts() {
+    let my_peer_id = PeerId::random();
+    let batch_store = batch_store_for_test(5 * 1024 * 1024);
+    let fairness_config = QuorumStoreFairnessConfig {
+        block_max_txns_per_sender: 4,
+        ..QuorumStoreFairnessConfig::default()
+    };
+    let mut proof_queue = BatchProofQueue::new(my_peer_id, batch_store, 1, fairness_config);
+
+    // Insert a batch with 2 txns from the same sender (and its proof)
+    let now_in_secs = aptos_infallible::duration_since_epoch().as_secs() as u64;
+    let now_in_usecs = now_in_secs * 1_000_000;
+    let proof = proof_of_store_with_size(
+        PeerId::random(),
+        BatchId::new_for_test(0),
+        0,
+        now_in_usecs + 1_000_000,
+        2,
+    );
+    let txn_summaries = (0..2)
+        .map(|sequence_number| {
+            TxnSummaryWithExpiration::new(
+                PeerId::ONE,
+                ReplayProtector::SequenceNumber(sequence_number),
+                now_in_secs + 1,
+                HashValue::zero(),
+            )
+        })
+        .collect();
+    let txn_counts = TxnCounts::from_txns(&create_txns(&[(1, 2), (1, 2)]));
+    proof_queue.insert_batches(vec![(proof.info().clone(), txn_summaries, txn_counts)]);
+    proof_queue.insert_proof(proof);
+
+    // Insert 3 proofs (with 2 txns each) whose batches were never received
+    for batch_index in 1..4 {
+        proof_queue.insert_proof(proof_of_store_with_size(
+            PeerId::random(),
+            BatchId::new_for_test(batch_index),
+            0,
+            now_in_usecs + 1_000_000,
+            2,
+        ));
+    }
+
+    // The txns with unknown senders are counted towards every sender, so
+    // only 2 of the proofs fit within the sender quota.
+    let (proof_block, _, _, _) = proof_queue.pull_proofs(
+        &HashSet::new(),
+        PayloadTxnsSize::new(10, 100),
+        10,
+        10,
+        true,
+        Duration::from_micros(now_in_usecs),
+        &mut proof_queue.new_block_fairness_quotas(),
+    );
+    assert_eq!(proof_block.len(), 2);
+}
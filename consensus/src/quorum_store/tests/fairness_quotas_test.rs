@@ -0,0 +1,104 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::quorum_store::fairness_quotas::{
+    filter_txns_by_quotas, FairnessQuotas, QuotaKind, TxnCounts,
+};
+use aptos_types::transaction::use_case::{UseCaseAwareTransaction, UseCaseKey};
+use move_core_types::account_address::AccountAddress;
+
+pub(crate) struct Transaction {
+    sender: AccountAddress,
+    use_case: UseCaseKey,
+}
+
+impl UseCaseAwareTransaction for Transaction {
+    fn parse_sender(&self) -> AccountAddress {
+        self.sender
+    }
+
+    fn parse_use_case(&self) -> UseCaseKey {
+        self.use_case.clone()
+    }
+}
+
+pub(crate) fn address(byte: u8) -> AccountAddress {
+    AccountAddress::new([byte; AccountAddress::LENGTH])
+}
+
+pub(crate) fn create_txns(txns: &[(u8, u8)]) -> Vec<Transaction> {
+    txns.iter()
+        .map(|(sender, contract)| Transaction {
+            sender: address(*sender),
+            use_case: UseCaseKey::ContractAddress(address(*contract)),
+        })
+        .collect()
+}
+
+fn senders(txns: &[Transaction]) -> Vec<AccountAddress> {
+    txns.iter().map(|txn| txn.sender).collect()
+}
+
+#[test]
+fn test_filter_txns_by_sender_quota() {
+    let mut quotas = FairnessQuotas::new(2, u64::MAX);
+    let mut txns = create_txns(&[(1, 1), (1, 1), (2, 1), (1, 1), (2, 1), (3, 1)]);
+    filter_txns_by_quotas(&mut quotas, &mut txns);
+
+    // The third transaction of sender 1 is excluded
+    assert_eq!(senders(&txns), vec![
+        address(1),
+        address(1),
+        address(2),
+        address(2),
+        address(3)
+    ]);
+}
+
+#[test]
+fn test_filter_txns_by_use_case_quota() {
+    let mut quotas = FairnessQuotas::new(u64::MAX, 2);
+    let mut txns = create_txns(&[(1, 1), (2, 1), (3, 1), (3, 2), (4, 2), (1, 2)]);
+    filter_txns_by_quotas(&mut quotas, &mut txns);
+
+    // The transaction of sender 3 on contract 1 exceeds the use case quota, so the
+    // following transaction of the same sender (on contract 2) is excluded as well.
+    assert_eq!(senders(&txns), vec![
+        address(1),
+        address(2),
+        address(4),
+        address(1)
+    ]);
+}
+
+#[test]
+fn test_batch_quotas() {
+    let mut quotas = FairnessQuotas::new(3, u64::MAX);
+    let batch = TxnCounts::from_txns(&create_txns(&[(1, 1), (1, 1), (2, 1)]));
+
+    // The first batch fits, but the second one exceeds the quota of sender 1
+    quotas.try_add(&batch).unwrap();
+    assert_eq!(quotas.try_add(&batch), Err(QuotaKind::Sender));
+
+    // A batch from another sender still fits
+    let batch = TxnCounts::from_txns(&create_txns(&[(3, 1), (3, 1), (3, 1)]));
+    quotas.try_add(&batch).unwrap();
+}
+
+#[test]
+fn test_unknown_txns_quotas() {
+    let mut quotas = FairnessQuotas::new(4, u64::MAX);
+    let batch = TxnCounts::from_txns(&create_txns(&[(1, 1), (1, 1)]));
+    quotas.try_add(&batch).unwrap();
+
+    // Unknown transactions are counted towards the sender with the most transactions
+    assert_eq!(quotas.check_unknown(3), Err(QuotaKind::Sender));
+    quotas.check_unknown(2).unwrap();
+    quotas.add_unknown(2);
+
+    // Unknown transactions are also counted towards every other sender
+    let batch = TxnCounts::from_txns(&create_txns(&[(2, 1), (2, 1), (2, 1)]));
+    assert_eq!(quotas.check(&batch), Err(QuotaKind::Sender));
+    let batch = TxnCounts::from_txns(&create_txns(&[(2, 1), (2, 1)]));
+    quotas.try_add(&batch).unwrap();
+}
@@ -1,9 +1,11 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::quorum_store::{
-    proof_manager::ProofManager, tests::batch_store_test::batch_store_for_test,
+use crate::{
+    quorum_store::{proof_manager::ProofManager, tests::batch_store_test::batch_store_for_test},
+    state_inspector::ConsensusStateInspector,
 };
+use aptos_config::config::QuorumStoreFairnessConfig;
 use aptos_consensus_types::{
     common::{Payload, PayloadFilter},
     proof_of_store::{BatchInfo, ProofOfStore},
@@ -13,11 +15,21 @@ use aptos_consensus_types::{
 use aptos_crypto::HashValue;
 use aptos_types::{aggregate_signature::AggregateSignature, quorum_store::BatchId, PeerId};
 use futures::channel::oneshot;
-use std::{cmp::max, collections::HashSet};
+use std::{cmp::max, collections::HashSet, sync::Arc};
 
 fn create_proof_manager() -> ProofManager {
     let batch_store = batch_store_for_test(5 * 1024 * 1024);
-    ProofManager::new(PeerId::random(), 10, 10, batch_store, true, true, 1)
+    ProofManager::new(
+        PeerId::random(),
+        10,
+        10,
+        batch_store,
+        true,
+        true,
+        1,
+        QuorumStoreFairnessConfig::default(),
+        Arc::new(ConsensusStateInspector::default()),
+    )
 }
 
 fn create_proof(author: PeerId, expiration: u64, batch_sequence: u64) -> ProofOfStore {
@@ -31,6 +31,7 @@ use crate::{
     persistent_liveness_storage::PersistentLivenessStorage,
     quorum_store::types::BatchMsg,
     rand::rand_gen::types::{FastShare, RandConfig, Share, TShare},
+    state_inspector::{ConsensusStateInspector, RoundManagerState, STATE_PUBLISH_INTERVAL_MS},
     util::is_vtxn_expected,
 };
 use anyhow::{bail, ensure, Context};
@@ -57,7 +58,7 @@ use aptos_consensus_types::{
     wrapped_ledger_info::WrappedLedgerInfo,
 };
 use aptos_crypto::{hash::CryptoHash, HashValue};
-use aptos_infallible::{checked, Mutex};
+use aptos_infallible::{checked, duration_since_epoch, Mutex};
 use aptos_logger::prelude::*;
 #[cfg(test)]
 use aptos_safety_rules::ConsensusState;
@@ -291,6 +292,7 @@ pub struct RoundManager {
     proposal_status_tracker: Arc<dyn TPastProposalStatusTracker>,
     pending_opt_proposals: BTreeMap<Round, OptBlockData>,
     opt_proposal_loopback_tx: aptos_channels::UnboundedSender<OptBlockData>,
+    state_inspector: Arc<ConsensusStateInspector>,
 }
 
 impl RoundManager {
@@ -313,6 +315,7 @@ impl RoundManager {
         fast_rand_config: Option<RandConfig>,
         proposal_status_tracker: Arc<dyn TPastProposalStatusTracker>,
         opt_proposal_loopback_tx: aptos_channels::UnboundedSender<OptBlockData>,
+        state_inspector: Arc<ConsensusStateInspector>,
     ) -> Self {
         // when decoupled execution is false,
         // the counter is still static.
@@ -349,6 +352,7 @@ impl RoundManager {
             proposal_status_tracker,
             pending_opt_proposals: BTreeMap::new(),
             opt_proposal_loopback_tx,
+            state_inspector,
         }
     }
 
@@ -1993,6 +1997,28 @@ impl RoundManager {
         &self.round_state
     }
 
+    /// Publishes the current round state, certificates and votes to the state inspector.
+    fn publish_state(&self) {
+        let verifier = &self.epoch_state.verifier;
+        self.state_inspector
+            .update_round_manager_state(RoundManagerState {
+                updated_at_usecs: duration_since_epoch().as_micros() as u64,
+                epoch: self.epoch_state.epoch,
+                current_round: self.round_state.current_round(),
+                highest_ordered_round: self.round_state.highest_ordered_round(),
+                quorum_voting_power: verifier.quorum_voting_power(),
+                highest_quorum_cert: self.block_store.highest_quorum_cert().as_ref().into(),
+                highest_timeout_cert: self
+                    .block_store
+                    .highest_2chain_timeout_cert()
+                    .map(|tc| tc.as_ref().into()),
+                highest_ordered_cert: self.block_store.highest_ordered_cert().as_ref().into(),
+                highest_commit_cert: self.block_store.highest_commit_cert().as_ref().into(),
+                pending_votes: self.round_state.vote_tallies(verifier),
+                timeout: self.round_state.timeout_state(verifier),
+            });
+    }
+
     fn new_log(&self, event: LogEvent) -> LogSchema {
         Self::new_log_with_round_epoch(
             event,
@@ -2018,6 +2044,12 @@ impl RoundManager {
         close_rx: oneshot::Receiver<oneshot::Sender<()>>,
     ) {
         info!(epoch = self.epoch_state.epoch, "RoundManager started");
+        // Building the state isn't free, so it's published periodically rather than after every
+        // event. The interval fires immediately, so the initial state is published right away.
+        let mut publish_state_interval =
+            tokio::time::interval(Duration::from_millis(STATE_PUBLISH_INTERVAL_MS));
+        // Missed ticks are skipped rather than burst, so a slow event doesn't queue up publishes.
+        publish_state_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
         let mut close_rx = close_rx.into_stream();
         loop {
             tokio::select! {
@@ -2028,6 +2060,11 @@ impl RoundManager {
                     }
                     break;
                 }
+                // The tick is polled ahead of the event arms, so a steady stream of events can't
+                // starve it. It's ready at most once per interval, so it doesn't delay them either.
+                _ = publish_state_interval.tick() => {
+                    self.publish_state();
+                }
                 opt_proposal = opt_proposal_loopback_rx.select_next_some() => {
                     self.pending_opt_proposals = self.pending_opt_proposals.split_off(&opt_proposal.round().add(1));
                     let result = monitor!("process_opt_proposal_loopback", self.process_opt_proposal(opt_proposal).await);
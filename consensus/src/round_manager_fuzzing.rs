@@ -21,6 +21,7 @@ use crate::{
     persistent_liveness_storage::{PersistentLivenessStorage, RecoveryData},
     pipeline::execution_client::DummyExecutionClient,
     round_manager::RoundManager,
+    state_inspector::ConsensusStateInspector,
     test_utils::{
         MockOptQSPayloadProvider, MockPastProposalStatusTracker, MockPayloadManager, MockStorage,
     },
@@ -225,6 +226,7 @@ fn create_node_for_fuzzing() -> RoundManager {
         None,
         Arc::new(MockPastProposalStatusTracker {}),
         opt_proposal_loopback_tx,
+        Arc::new(ConsensusStateInspector::default()),
     )
 }
 
@@ -25,6 +25,7 @@ use crate::{
     pipeline::buffer_manager::OrderedBlocks,
     quorum_store::batch_store::BatchReader,
     round_manager::RoundManager,
+    state_inspector::ConsensusStateInspector,
     test_utils::{
         mock_execution_client::MockExecutionClient, MockOptQSPayloadProvider,
         MockPastProposalStatusTracker, MockPayloadManager, MockStorage,
@@ -480,6 +481,7 @@ impl NodeSetup {
             None,
             Arc::new(MockPastProposalStatusTracker {}),
             opt_proposal_loopback_tx,
+            Arc::new(ConsensusStateInspector::default()),
         );
         block_on(round_manager.init(last_vote_sent));
         Self {
@@ -6,7 +6,7 @@ use crate::{
     block_preparer::BlockPreparer, error::StateSyncError, monitor,
     payload_manager::TPayloadManager, pipeline::pipeline_builder::PipelineBuilder,
     state_replication::StateComputer, transaction_deduper::TransactionDeduper,
-    transaction_shuffler::TransactionShuffler, txn_notifier::TxnNotifier,
+    txn_notifier::TxnNotifier,
 };
 use anyhow::Result;
 use aptos_config::config::BlockTransactionFilterConfig;
@@ -15,6 +15,7 @@ use aptos_consensus_types::common::Round;
 use aptos_executor_types::BlockExecutorTrait;
 use aptos_infallible::RwLock;
 use aptos_logger::prelude::*;
+use aptos_transaction_shuffler::TransactionShuffler;
 use aptos_types::{
     account_address::AccountAddress, block_executor::config::BlockExecutorConfigFromOnchain,
     epoch_state::EpochState, ledger_info::LedgerInfoWithSignatures,
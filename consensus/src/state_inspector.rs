@@ -0,0 +1,181 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Structured view of the live consensus state, for debugging stuck or lagging validators.
+//!
+//! The components running the consensus event loops (RoundManager, BufferManager and the quorum
+//! store's ProofManager) publish a summary of their state into a shared [`ConsensusStateInspector`],
+//! which the admin service serves as JSON. Each component overwrites its own section, so the
+//! sections of a snapshot may have been taken at slightly different times; each one carries its
+//! own timestamp.
+
+use aptos_consensus_types::{
+    common::{Author, Round},
+    quorum_cert::QuorumCert,
+    timeout_2chain::TwoChainTimeoutCertificate,
+    wrapped_ledger_info::WrappedLedgerInfo,
+};
+use aptos_crypto::HashValue;
+use aptos_infallible::RwLock;
+use aptos_types::block_info::BlockInfo;
+use serde::Serialize;
+
+/// Interval at which the RoundManager publishes its state. The other components publish on their
+/// own periodic ticks.
+pub(crate) const STATE_PUBLISH_INTERVAL_MS: u64 = 200;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct CertifiedBlockSummary {
+    pub epoch: u64,
+    pub round: Round,
+    pub block_id: HashValue,
+    pub version: u64,
+}
+
+impl From<&BlockInfo> for CertifiedBlockSummary {
+    fn from(block_info: &BlockInfo) -> Self {
+        Self {
+            epoch: block_info.epoch(),
+            round: block_info.round(),
+            block_id: block_info.id(),
+            version: block_info.version(),
+        }
+    }
+}
+
+impl From<&QuorumCert> for CertifiedBlockSummary {
+    fn from(qc: &QuorumCert) -> Self {
+        qc.certified_block().into()
+    }
+}
+
+impl From<&WrappedLedgerInfo> for CertifiedBlockSummary {
+    fn from(ledger_info: &WrappedLedgerInfo) -> Self {
+        ledger_info.commit_info().into()
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct TimeoutCertSummary {
+    pub epoch: u64,
+    pub round: Round,
+    pub highest_hqc_round: Round,
+}
+
+impl From<&TwoChainTimeoutCertificate> for TimeoutCertSummary {
+    fn from(tc: &TwoChainTimeoutCertificate) -> Self {
+        Self {
+            epoch: tc.epoch(),
+            round: tc.round(),
+            highest_hqc_round: tc.highest_hqc_round(),
+        }
+    }
+}
+
+/// Votes received for one of the blocks proposed in the current round.
+#[derive(Clone, Debug, Serialize)]
+pub struct VoteTally {
+    pub block_id: HashValue,
+    pub ledger_info_digest: HashValue,
+    pub voters: Vec<Author>,
+    pub voting_power: u128,
+    pub quorum_reached: bool,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct TimeoutState {
+    /// Deadline of the next local timeout, in microseconds since the unix epoch.
+    pub current_round_deadline_usecs: u64,
+    pub vote_sent: Option<HashValue>,
+    pub timeout_sent: bool,
+    pub timeout_voters: Vec<Author>,
+    pub timeout_voting_power: u128,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct RoundManagerState {
+    pub updated_at_usecs: u64,
+    pub epoch: u64,
+    pub current_round: Round,
+    pub highest_ordered_round: Round,
+    pub quorum_voting_power: u128,
+    pub highest_quorum_cert: CertifiedBlockSummary,
+    pub highest_timeout_cert: Option<TimeoutCertSummary>,
+    pub highest_ordered_cert: CertifiedBlockSummary,
+    pub highest_commit_cert: CertifiedBlockSummary,
+    pub pending_votes: Vec<VoteTally>,
+    pub timeout: TimeoutState,
+}
+
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BufferItemPhase {
+    Ordered,
+    Executed,
+    Signed,
+    Aggregated,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct PipelineBlockState {
+    pub round: Round,
+    pub block_id: HashValue,
+    pub phase: BufferItemPhase,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct BufferManagerState {
+    pub updated_at_usecs: u64,
+    pub epoch: u64,
+    pub latest_round: Round,
+    pub highest_committed_round: Round,
+    pub back_pressured: bool,
+    /// Blocks in the buffer, from the oldest to the newest.
+    pub blocks: Vec<PipelineBlockState>,
+    pub execution_root: Option<HashValue>,
+    pub signing_root: Option<HashValue>,
+    pub num_pending_commit_proofs: usize,
+    pub num_pending_commit_votes: usize,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ProofQueueState {
+    pub updated_at_usecs: u64,
+    pub remaining_txns: u64,
+    pub remaining_proofs: u64,
+    pub batches_without_proof: usize,
+    pub back_pressure_txn_limit: u64,
+    pub back_pressure_proof_limit: u64,
+}
+
+/// The latest published state of every consensus component. A section is `None` until the
+/// component published it for the first time.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ConsensusStateSnapshot {
+    pub round_manager: Option<RoundManagerState>,
+    pub buffer_manager: Option<BufferManagerState>,
+    pub proof_queue: Option<ProofQueueState>,
+}
+
+#[derive(Default)]
+pub struct ConsensusStateInspector {
+    snapshot: RwLock<ConsensusStateSnapshot>,
+}
+
+impl ConsensusStateInspector {
+    pub fn snapshot(&self) -> ConsensusStateSnapshot {
+        self.snapshot.read().clone()
+    }
+
+    pub(crate) fn update_round_manager_state(&self, state: RoundManagerState) {
+        self.snapshot.write().round_manager = Some(state);
+    }
+
+    pub(crate) fn update_buffer_manager_state(&self, state: BufferManagerState) {
+        self.snapshot.write().buffer_manager = Some(state);
+    }
+
+    pub(crate) fn update_proof_queue_state(&self, state: ProofQueueState) {
+        self.snapshot.write().proof_queue = Some(state);
+    }
+}
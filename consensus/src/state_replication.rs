@@ -4,10 +4,11 @@
 
 use crate::{
     error::StateSyncError, payload_manager::TPayloadManager,
-    transaction_deduper::TransactionDeduper, transaction_shuffler::TransactionShuffler,
+    transaction_deduper::TransactionDeduper,
 };
 use anyhow::Result;
 use aptos_consensus_types::pipelined_block::PipelinedBlock;
+use aptos_transaction_shuffler::TransactionShuffler;
 use aptos_types::{
     block_executor::config::BlockExecutorConfigFromOnchain, epoch_state::EpochState,
     ledger_info::LedgerInfoWithSignatures,
@@ -4,10 +4,11 @@
 
 use crate::{
     error::StateSyncError, payload_manager::TPayloadManager, state_replication::StateComputer,
-    transaction_deduper::TransactionDeduper, transaction_shuffler::TransactionShuffler,
+    transaction_deduper::TransactionDeduper,
 };
 use anyhow::{anyhow, Result};
 use aptos_crypto::HashValue;
+use aptos_transaction_shuffler::TransactionShuffler;
 use aptos_types::{
     block_executor::config::BlockExecutorConfigFromOnchain, epoch_state::EpochState,
     ledger_info::LedgerInfoWithSignatures,
@@ -12,6 +12,7 @@ use crate::{
     pipeline::buffer_manager::OrderedBlocks,
     quorum_store::quorum_store_db::MockQuorumStoreDB,
     rand::rand_gen::storage::in_memory::InMemRandDb,
+    state_inspector::ConsensusStateInspector,
     test_utils::{mock_execution_client::MockExecutionClient, MockStorage},
     util::time_service::ClockTimeService,
 };
@@ -164,6 +165,7 @@ impl SMRNode {
             vtxn_pool,
             Arc::new(InMemRandDb::new()),
             None,
+            Arc::new(ConsensusStateInspector::default()),
         );
         let (network_task, network_receiver) =
             NetworkTask::new(network_service_events, self_receiver);
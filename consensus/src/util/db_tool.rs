@@ -132,6 +132,9 @@ pub fn extract_txns_from_block<'a>(
                     )
                     .unwrap(),
                 );
+                for inline_batch in opt_qs_payload.inline_batches().iter() {
+                    all_txns.extend(inline_batch.transactions());
+                }
                 Ok(all_txns)
             },
         },
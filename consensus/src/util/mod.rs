@@ -11,6 +11,7 @@ use aptos_types::{
 pub mod db_tool;
 #[cfg(any(test, feature = "fuzzing"))]
 pub mod mock_time_service;
+pub mod replay_tool;
 pub mod time_service;
 
 pub fn is_vtxn_expected(
@@ -0,0 +1,571 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    block_preparer::prepare_block_transactions,
+    consensusdb::ConsensusDB,
+    quorum_store::{
+        quorum_store_db::{QuorumStoreDB, QuorumStoreStorage},
+        types::PersistedValue,
+    },
+    transaction_deduper::create_transaction_deduper,
+    util::db_tool::extract_txns_from_block,
+};
+use anyhow::Result;
+use aptos_config::config::BlockTransactionFilterConfig;
+use aptos_consensus_types::{block::Block, common::Payload, quorum_cert::QuorumCert};
+use aptos_crypto::HashValue;
+use aptos_executor::block_executor::BlockExecutor;
+use aptos_executor_types::BlockExecutorTrait;
+use aptos_logger::prelude::*;
+use aptos_storage_interface::{
+    state_store::state_view::db_state_view::DbStateViewAtVersion, DbReader, DbReaderWriter,
+};
+use aptos_transaction_shuffler::create_transaction_shuffler;
+use aptos_types::{
+    account_address::AccountAddress,
+    block_metadata_ext::BlockMetadataExt,
+    on_chain_config::{
+        OnChainConfig, OnChainExecutionConfig, OnChainRandomnessConfig, RandomnessConfigMoveStruct,
+        RandomnessConfigSeqNum, ValidatorSet,
+    },
+    transaction::{
+        signature_verified_transaction::SignatureVerifiedTransaction, AuxiliaryInfo,
+        EphemeralAuxiliaryInfo, PersistedAuxiliaryInfo, Transaction, TransactionInfo,
+        TransactionOutput, Version,
+    },
+    validator_verifier::ValidatorVerifier,
+};
+use aptos_vm::aptos_vm::AptosVMBlockExecutor;
+use std::{collections::HashMap, fmt, path::Path, sync::Arc};
+
+/// Re-executes the blocks persisted in a validator's consensus db (with the payloads from its
+/// quorum store db) on top of a copy of its AptosDB, and compares the results against what was
+/// certified or committed.
+///
+/// The AptosDB must be at the version of the last committed block, i.e. the root of the blocks
+/// in the consensus db. Nothing is ever committed to it, but the executor needs write access, so
+/// never point this at the db of a running node.
+///
+/// The transactions of each block go through the same filtering, deduplication and shuffling as
+/// in the block preparer, so the filter config must be the execution filter of the validator.
+pub struct ConsensusReplayer {
+    blocks_by_parent: HashMap<HashValue, Vec<Block>>,
+    qcs: HashMap<HashValue, QuorumCert>,
+    batches: HashMap<HashValue, PersistedValue>,
+    db: DbReaderWriter,
+    expected_db: Option<Arc<dyn DbReader>>,
+    txn_filter_config: BlockTransactionFilterConfig,
+}
+
+impl ConsensusReplayer {
+    pub fn new(
+        consensus_db_dir: &Path,
+        db: DbReaderWriter,
+        expected_db: Option<Arc<dyn DbReader>>,
+        txn_filter_config: BlockTransactionFilterConfig,
+    ) -> Result<Self> {
+        let batches = QuorumStoreDB::new(consensus_db_dir).get_all_batches()?;
+        let (_, _, blocks, qcs) = ConsensusDB::new(consensus_db_dir).get_data()?;
+
+        let mut blocks_by_parent: HashMap<HashValue, Vec<Block>> = HashMap::new();
+        for block in blocks {
+            blocks_by_parent
+                .entry(block.parent_id())
+                .or_default()
+                .push(block);
+        }
+        let qcs = qcs
+            .into_iter()
+            .map(|qc| (qc.certified_block().id(), qc))
+            .collect();
+
+        Ok(Self {
+            blocks_by_parent,
+            qcs,
+            batches,
+            db,
+            expected_db,
+            txn_filter_config,
+        })
+    }
+
+    /// Picks the child to follow. Certified children are preferred over uncertified ones, and
+    /// the lowest round wins among equals, as later rounds are the result of timeouts.
+    fn next_block(&self, parent_id: HashValue) -> Option<&Block> {
+        let children = self.blocks_by_parent.get(&parent_id)?;
+        if children.len() > 1 {
+            warn!(
+                parent_id = parent_id,
+                num_children = children.len(),
+                "Fork in the consensus db, following the certified child with the lowest round."
+            );
+        }
+        children
+            .iter()
+            .min_by_key(|block| (!self.qcs.contains_key(&block.id()), block.round()))
+    }
+
+    /// Replays the chain of blocks descending from the committed root, until `until_block` (if
+    /// given, inclusive), the end of the chain or the end of the epoch, and returns a report per
+    /// replayed block.
+    pub fn replay(&self, until_block: Option<HashValue>) -> Result<Vec<BlockReplayReport>> {
+        let executor = BlockExecutor::<AptosVMBlockExecutor>::new(self.db.clone());
+        let committed_version = self.db.reader.get_latest_ledger_info_version()?;
+        let state_view = self
+            .db
+            .reader
+            .state_view_at_version(Some(committed_version))?;
+
+        let execution_config = OnChainExecutionConfig::fetch_config(&state_view)
+            .unwrap_or_else(OnChainExecutionConfig::default_if_missing);
+        let validator_set = ValidatorSet::fetch_config(&state_view)
+            .ok_or_else(|| anyhow::anyhow!("ValidatorSet not found in state."))?;
+        let validators = ValidatorVerifier::from(&validator_set).get_ordered_account_addresses();
+        let is_randomness_enabled = OnChainRandomnessConfig::from_configs(
+            0,
+            RandomnessConfigSeqNum::fetch_config(&state_view)
+                .unwrap_or_else(RandomnessConfigSeqNum::default_if_missing)
+                .seq_num,
+            RandomnessConfigMoveStruct::fetch_config(&state_view),
+        )
+        .randomness_enabled();
+        let deduper = create_transaction_deduper(execution_config.transaction_deduper_type());
+        let shuffler = create_transaction_shuffler(execution_config.transaction_shuffler_type());
+
+        let mut reports = vec![];
+        let mut parent_id = executor.committed_block_id();
+        let mut first_version = committed_version + 1;
+        while let Some(block) = self.next_block(parent_id) {
+            let (max_txns_to_execute, block_gas_limit) = execution_limits(block.payload());
+            let user_txns = prepare_block_transactions(
+                &self.txn_filter_config,
+                deduper.as_ref(),
+                shuffler.as_ref(),
+                block.id(),
+                block.author(),
+                block.epoch(),
+                block.timestamp_usecs(),
+                extract_txns_from_block(block, &self.batches)?
+                    .into_iter()
+                    .cloned()
+                    .collect(),
+                max_txns_to_execute,
+            );
+
+            let metadata_txn = match self.committed_metadata_txn(block, first_version)? {
+                Some(metadata_txn) => metadata_txn,
+                None if is_randomness_enabled => {
+                    warn!(
+                        block_id = block.id(),
+                        "Randomness is not available offline, replaying without it."
+                    );
+                    block.new_metadata_with_randomness(&validators, None)
+                },
+                None => block.new_block_metadata(&validators).into(),
+            };
+            let txns: Vec<SignatureVerifiedTransaction> =
+                std::iter::once(Transaction::from(metadata_txn))
+                    .chain(
+                        block
+                            .validator_txns()
+                            .cloned()
+                            .unwrap_or_default()
+                            .into_iter()
+                            .map(Transaction::ValidatorTransaction),
+                    )
+                    .chain(user_txns.into_iter().map(Transaction::UserTransaction))
+                    .map(SignatureVerifiedTransaction::from)
+                    .collect();
+            let auxiliary_info = auxiliary_info(block, &validators, &txns);
+
+            info!(
+                block_id = block.id(),
+                epoch = block.epoch(),
+                round = block.round(),
+                num_txns = txns.len(),
+                "Replaying block."
+            );
+            executor.execute_and_update_state(
+                (block.id(), txns, auxiliary_info).into(),
+                parent_id,
+                execution_config
+                    .block_executor_onchain_config()
+                    .with_block_gas_limit_override(block_gas_limit),
+            )?;
+            let result = executor.ledger_update(block.id(), parent_id)?;
+
+            let txn_infos = &result.ledger_update_output.transaction_infos;
+            let txn_outputs = &result.execution_output.to_commit.transaction_outputs;
+            let expected_txn_infos = self.expected_txn_infos(first_version, txn_infos.len())?;
+            let txn_diffs = match &expected_txn_infos {
+                Some(expected) => {
+                    diff_transaction_infos(first_version, txn_infos, txn_outputs, expected)
+                },
+                None => vec![],
+            };
+            let report = BlockReplayReport {
+                block_id: block.id(),
+                epoch: block.epoch(),
+                round: block.round(),
+                first_version,
+                num_txns: txn_infos.len(),
+                computed_root_hash: result.root_hash(),
+                certified_root_hash: self
+                    .qcs
+                    .get(&block.id())
+                    .map(|qc| qc.certified_block().executed_state_id()),
+                compared_txn_infos: expected_txn_infos.is_some(),
+                txn_diffs,
+            };
+            reports.push(report);
+
+            first_version += txn_infos.len() as Version;
+            parent_id = block.id();
+            if result.has_reconfiguration() {
+                info!(block_id = block.id(), "Reached the end of the epoch.");
+                break;
+            }
+            if until_block == Some(block.id()) {
+                break;
+            }
+        }
+
+        Ok(reports)
+    }
+
+    /// Takes the block metadata transaction from the expected db, if it was committed there, so
+    /// the randomness matches the original execution.
+    fn committed_metadata_txn(
+        &self,
+        block: &Block,
+        version: Version,
+    ) -> Result<Option<BlockMetadataExt>> {
+        let Some(expected_db) = &self.expected_db else {
+            return Ok(None);
+        };
+        let ledger_version = expected_db.get_latest_ledger_info_version()?;
+        if version > ledger_version {
+            return Ok(None);
+        }
+
+        let txn = expected_db
+            .get_transaction_by_version(version, ledger_version, false)?
+            .transaction;
+        Ok(match txn {
+            Transaction::BlockMetadataExt(metadata) if metadata.id() == block.id() => {
+                Some(metadata)
+            },
+            Transaction::BlockMetadata(metadata) if metadata.id() == block.id() => {
+                Some(metadata.into())
+            },
+            _ => None,
+        })
+    }
+
+    fn expected_txn_infos(
+        &self,
+        first_version: Version,
+        num_txns: usize,
+    ) -> Result<Option<Vec<TransactionInfo>>> {
+        let Some(expected_db) = &self.expected_db else {
+            return Ok(None);
+        };
+        let ledger_version = expected_db.get_latest_ledger_info_version()?;
+        if first_version > ledger_version {
+            return Ok(None);
+        }
+
+        let limit = std::cmp::min(num_txns as u64, ledger_version - first_version + 1);
+        expected_db
+            .get_transaction_info_iterator(first_version, limit)?
+            .collect::<Result<Vec<_>, _>>()
+            .map(Some)
+            .map_err(Into::into)
+    }
+}
+
+/// Returns the max number of transactions to execute and the block gas limit override carried by
+/// the payload, same as the payload manager does.
+fn execution_limits(payload: Option<&Payload>) -> (Option<u64>, Option<u64>) {
+    match payload {
+        Some(Payload::InQuorumStoreWithLimit(proof_with_data)) => {
+            (proof_with_data.max_txns_to_execute, None)
+        },
+        Some(Payload::QuorumStoreInlineHybrid(_, _, max_txns_to_execute)) => {
+            (*max_txns_to_execute, None)
+        },
+        Some(Payload::QuorumStoreInlineHybridV2(_, _, execution_limits)) => (
+            execution_limits.max_txns_to_execute(),
+            execution_limits.block_gas_limit(),
+        ),
+        Some(Payload::OptQuorumStore(opt_qs_payload)) => {
+            (opt_qs_payload.max_txns_to_execute(), None)
+        },
+        _ => (None, None),
+    }
+}
+
+fn auxiliary_info(
+    block: &Block,
+    validators: &[AccountAddress],
+    txns: &[SignatureVerifiedTransaction],
+) -> Vec<AuxiliaryInfo> {
+    let proposer_index = block
+        .author()
+        .and_then(|proposer| validators.iter().position(|&v| v == proposer));
+    txns.iter()
+        .map(|txn| {
+            txn.borrow_into_inner().try_as_signed_user_txn().map_or(
+                AuxiliaryInfo::new_empty(),
+                |_| {
+                    AuxiliaryInfo::new(
+                        PersistedAuxiliaryInfo::None,
+                        proposer_index.map(|index| EphemeralAuxiliaryInfo {
+                            proposer_index: index as u64,
+                        }),
+                    )
+                },
+            )
+        })
+        .collect()
+}
+
+fn diff_transaction_infos(
+    first_version: Version,
+    txn_infos: &[TransactionInfo],
+    txn_outputs: &[TransactionOutput],
+    expected_txn_infos: &[TransactionInfo],
+) -> Vec<TransactionDiff> {
+    let mut diffs: Vec<TransactionDiff> = txn_infos
+        .iter()
+        .zip(txn_outputs)
+        .zip(expected_txn_infos)
+        .enumerate()
+        .filter_map(|(idx, ((txn_info, txn_output), expected))| {
+            let version = first_version + idx as Version;
+            txn_output
+                .ensure_match_transaction_info(version, expected, None, None)
+                .err()
+                .map(|err| err.to_string())
+                .or_else(|| {
+                    (txn_info != expected)
+                        .then(|| format!("computed: {:?}, expected: {:?}", txn_info, expected))
+                })
+                .map(|details| TransactionDiff { version, details })
+        })
+        .collect();
+
+    if txn_infos.len() != expected_txn_infos.len() {
+        diffs.push(TransactionDiff {
+            version: first_version + txn_infos.len().min(expected_txn_infos.len()) as Version,
+            details: format!(
+                "Computed {} transactions, while {} are expected.",
+                txn_infos.len(),
+                expected_txn_infos.len()
+            ),
+        });
+    }
+    diffs
+}
+
+#[derive(Debug)]
+pub struct TransactionDiff {
+    pub version: Version,
+    pub details: String,
+}
+
+#[derive(Debug)]
+pub struct BlockReplayReport {
+    pub block_id: HashValue,
+    pub epoch: u64,
+    pub round: u64,
+    pub first_version: Version,
+    pub num_txns: usize,
+    pub computed_root_hash: HashValue,
+    /// The executed state id certified by the quorum cert of the block, if any.
+    pub certified_root_hash: Option<HashValue>,
+    /// Whether the computed transaction infos were compared against the expected db. They can't
+    /// be if the expected db doesn't have the block committed.
+    pub compared_txn_infos: bool,
+    pub txn_diffs: Vec<TransactionDiff>,
+}
+
+impl BlockReplayReport {
+    pub fn is_divergent(&self) -> bool {
+        !self.txn_diffs.is_empty()
+            || self
+                .certified_root_hash
+                .is_some_and(|root_hash| root_hash != self.computed_root_hash)
+    }
+}
+
+impl fmt::Display for BlockReplayReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Block {} (epoch {}, round {}), versions [{}, {}): computed root hash {}, certified root hash {}",
+            self.block_id,
+            self.epoch,
+            self.round,
+            self.first_version,
+            self.first_version + self.num_txns as Version,
+            self.computed_root_hash,
+            self.certified_root_hash
+                .map_or_else(|| "<not certified>".to_string(), |hash| hash.to_string()),
+        )?;
+        if !self.compared_txn_infos {
+            writeln!(
+                f,
+                "  Transaction infos not compared, block not committed in the expected db."
+            )?;
+        }
+        for diff in &self.txn_diffs {
+            writeln!(f, "  version {}: {}", diff.version, diff.details)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_consensus_types::{common::ProofWithData, proof_of_store::BatchInfo};
+    use aptos_crypto::hash::{CryptoHash, ACCUMULATOR_PLACEHOLDER_HASH};
+    use aptos_executor_test_helpers::integration_test_impl::create_db_and_executor;
+    use aptos_temppath::TempPath;
+    use aptos_transaction_filters::block_transaction_filter::BlockTransactionFilter;
+    use aptos_types::{
+        account_config::aptos_test_root_address,
+        quorum_store::BatchId,
+        test_helpers::transaction_test_helpers::get_test_signed_txn,
+        transaction::{ExecutionStatus, TransactionStatus, WriteSetPayload},
+        validator_signer::ValidatorSigner,
+        write_set::WriteSet,
+    };
+
+    fn txn_info(gas_used: u64) -> TransactionInfo {
+        TransactionInfo::new(
+            HashValue::zero(),
+            CryptoHash::hash(&WriteSet::default()),
+            *ACCUMULATOR_PLACEHOLDER_HASH,
+            None,
+            gas_used,
+            ExecutionStatus::Success,
+            None,
+        )
+    }
+
+    fn txn_output(gas_used: u64) -> TransactionOutput {
+        TransactionOutput::new(
+            WriteSet::default(),
+            vec![],
+            gas_used,
+            TransactionStatus::Keep(ExecutionStatus::Success),
+            Default::default(),
+        )
+    }
+
+    #[test]
+    fn test_diff_transaction_infos() {
+        let computed = vec![txn_info(1), txn_info(2)];
+        let outputs = vec![txn_output(1), txn_output(2)];
+
+        assert!(diff_transaction_infos(10, &computed, &outputs, &computed).is_empty());
+
+        let diffs = diff_transaction_infos(10, &computed, &outputs, &[txn_info(1), txn_info(3)]);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].version, 11);
+
+        let diffs = diff_transaction_infos(10, &computed, &outputs, &[txn_info(1)]);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].version, 11);
+    }
+
+    /// Persists a block on top of genesis in a consensus db, and replays it with the filter.
+    fn replay_block_on_genesis(
+        txn_filter_config: BlockTransactionFilterConfig,
+    ) -> BlockReplayReport {
+        let db_dir = TempPath::new();
+        db_dir.create_as_dir().unwrap();
+        let consensus_db_dir = TempPath::new();
+        consensus_db_dir.create_as_dir().unwrap();
+
+        let (genesis, validators) =
+            aptos_vm_genesis::test_genesis_change_set_and_validators(Some(1));
+        let genesis_txn = Transaction::GenesisTransaction(WriteSetPayload::Direct(genesis));
+        let (_, db, executor, _) = create_db_and_executor(db_dir.path(), &genesis_txn, false);
+        let signer = ValidatorSigner::new(
+            validators[0].data.owner_address,
+            Arc::new(validators[0].consensus_key.clone()),
+        );
+
+        let genesis_ledger_info = db.reader.get_latest_ledger_info().unwrap();
+        let genesis_block =
+            Block::make_genesis_block_from_ledger_info(genesis_ledger_info.ledger_info());
+        assert_eq!(genesis_block.id(), executor.committed_block_id());
+        let genesis_qc = QuorumCert::certificate_for_genesis_from_ledger_info(
+            genesis_ledger_info.ledger_info(),
+            genesis_block.id(),
+        );
+
+        let txn = get_test_signed_txn(
+            aptos_test_root_address(),
+            0,
+            &aptos_vm_genesis::GENESIS_KEYPAIR.0,
+            aptos_vm_genesis::GENESIS_KEYPAIR.1.clone(),
+            Some(aptos_cached_packages::aptos_stdlib::aptos_coin_mint(
+                signer.author(),
+                1_000,
+            )),
+        );
+        let batch_info = BatchInfo::new(
+            signer.author(),
+            BatchId::new_for_test(1),
+            genesis_block.epoch(),
+            u64::MAX,
+            HashValue::random(),
+            1,
+            txn.raw_txn_bytes_len() as u64,
+            0,
+        );
+        let payload = Payload::QuorumStoreInlineHybrid(
+            vec![(batch_info, vec![txn])],
+            ProofWithData::new(vec![]),
+            None,
+        );
+        let block =
+            Block::new_proposal(payload, 1, 1_000_000, genesis_qc, &signer, vec![]).unwrap();
+        ConsensusDB::new(consensus_db_dir.path())
+            .save_blocks_and_quorum_certificates(vec![block.clone()], vec![])
+            .unwrap();
+
+        let replayer =
+            ConsensusReplayer::new(consensus_db_dir.path(), db, None, txn_filter_config).unwrap();
+        let mut reports = replayer.replay(None).unwrap();
+        assert_eq!(reports.len(), 1);
+        let report = reports.pop().unwrap();
+        assert_eq!(report.block_id, block.id());
+        assert_eq!(
+            report.first_version,
+            genesis_ledger_info.ledger_info().version() + 1
+        );
+        assert!(!report.is_divergent());
+        report
+    }
+
+    #[test]
+    fn test_replay_block() {
+        let report = replay_block_on_genesis(BlockTransactionFilterConfig::default());
+        // Block metadata, user transaction and state checkpoint.
+        assert_eq!(report.num_txns, 3);
+    }
+
+    #[test]
+    fn test_replay_block_with_filter() {
+        let filter = BlockTransactionFilter::empty().add_all_filter(false);
+        let report = replay_block_on_genesis(BlockTransactionFilterConfig::new(true, filter));
+        // The user transaction is filtered out, as by the block preparer.
+        assert_eq!(report.num_txns, 2);
+    }
+}
@@ -0,0 +1,130 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::conflict_aware::Config;
+use aptos_types::transaction::conflict_hint::{ConflictHintedTransaction, ConflictKey};
+use move_core_types::account_address::AccountAddress;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+type OutputIdx = usize;
+
+#[derive(Debug)]
+struct PendingTxn<Txn> {
+    sender: AccountAddress,
+    conflict_keys: Vec<ConflictKey>,
+    txn: Txn,
+}
+
+/// Greedily picks, among the first `max_lookahead` pending transactions, the earliest one whose
+/// sender and conflict keys were not used by any of the last few selected transactions. If none
+/// qualifies, the earliest pending transaction is selected, so the output is never starved.
+///
+/// Transactions from the same sender are always output in their input order: once a transaction
+/// is passed over, later transactions from its sender are passed over as well.
+#[derive(Debug)]
+pub(super) struct ConflictAwareTransactionIterator<Txn> {
+    config: Config,
+    pending: VecDeque<PendingTxn<Txn>>,
+    last_output_by_sender: HashMap<AccountAddress, OutputIdx>,
+    last_output_by_key: HashMap<ConflictKey, OutputIdx>,
+    output_idx: OutputIdx,
+}
+
+impl<Txn> ConflictAwareTransactionIterator<Txn>
+where
+    Txn: ConflictHintedTransaction,
+{
+    pub(super) fn new(config: Config) -> Self {
+        Self {
+            config,
+            pending: VecDeque::new(),
+            last_output_by_sender: HashMap::new(),
+            last_output_by_key: HashMap::new(),
+            output_idx: 0,
+        }
+    }
+
+    pub(super) fn extended_with(mut self, txns: impl IntoIterator<Item = Txn>) -> Self {
+        for txn in txns {
+            let pending = PendingTxn {
+                sender: txn.parse_sender(),
+                conflict_keys: self.config.conflict_keys(&txn),
+                txn,
+            };
+            self.pending.push_back(pending);
+        }
+        self
+    }
+
+    fn is_spread(&self, last_output_idx: Option<&OutputIdx>, spread_factor: usize) -> bool {
+        last_output_idx.is_none_or(|idx| self.output_idx - idx > spread_factor)
+    }
+
+    fn is_ready(&self, pending: &PendingTxn<Txn>) -> bool {
+        self.is_spread(
+            self.last_output_by_sender.get(&pending.sender),
+            self.config.sender_spread_factor,
+        ) && pending.conflict_keys.iter().all(|key| {
+            self.is_spread(
+                self.last_output_by_key.get(key),
+                self.config.conflict_spread_factor,
+            )
+        })
+    }
+
+    fn select_next_idx(&self) -> Option<usize> {
+        if self.pending.is_empty() {
+            return None;
+        }
+
+        let mut passed_over_senders = HashSet::new();
+        for (idx, pending) in self
+            .pending
+            .iter()
+            .take(self.config.max_lookahead.max(1))
+            .enumerate()
+        {
+            if passed_over_senders.contains(&pending.sender) {
+                continue;
+            }
+            if self.is_ready(pending) {
+                return Some(idx);
+            }
+            passed_over_senders.insert(pending.sender);
+        }
+
+        // Nothing within the lookahead is free of conflicts, output the earliest transaction.
+        Some(0)
+    }
+
+    fn select_next_txn(&mut self) -> Option<Txn> {
+        let idx = self.select_next_idx()?;
+        let PendingTxn {
+            sender,
+            conflict_keys,
+            txn,
+        } = self
+            .pending
+            .remove(idx)
+            .expect("Index is known to be in range.");
+
+        self.last_output_by_sender.insert(sender, self.output_idx);
+        for key in conflict_keys {
+            self.last_output_by_key.insert(key, self.output_idx);
+        }
+        self.output_idx += 1;
+
+        Some(txn)
+    }
+}
+
+impl<Txn> Iterator for ConflictAwareTransactionIterator<Txn>
+where
+    Txn: ConflictHintedTransaction,
+{
+    type Item = Txn;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.select_next_txn()
+    }
+}
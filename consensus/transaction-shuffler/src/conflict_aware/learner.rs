@@ -0,0 +1,109 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_infallible::Mutex;
+use aptos_types::{
+    transaction::{
+        conflict_hint::{ConflictHintedTransaction, ConflictKey, EntryFunctionConflictHints},
+        Transaction, TransactionOutput,
+    },
+    write_set::WriteSet,
+};
+use move_core_types::{identifier::Identifier, language_storage::ModuleId};
+use std::collections::HashMap;
+
+/// Bound on the number of distinct keys tracked per entry function. Keys written under per-user
+/// addresses (e.g. primary fungible stores) would otherwise grow the map without bound.
+const MAX_TRACKED_KEYS_PER_FUNCTION: usize = 1024;
+
+#[derive(Debug, Default)]
+struct FunctionStats {
+    num_calls: u64,
+    key_counts: HashMap<ConflictKey, u64>,
+}
+
+/// Learns conflict hints from the write sets of executed transactions: a key becomes a hint of an
+/// entry function once it has been written by at least `min_frequency` of its calls.
+///
+/// Shuffling must be deterministic across validators, and a block is shuffled before the
+/// previous one is committed, so hints learned locally must not be fed into consensus directly.
+/// The learned hints are meant to be published through the on-chain execution config instead,
+/// e.g. after learning them from a replay or the executor benchmark.
+#[derive(Debug)]
+pub struct ConflictHintLearner {
+    min_samples: u64,
+    min_frequency: f64,
+    stats: Mutex<HashMap<(ModuleId, Identifier), FunctionStats>>,
+}
+
+impl ConflictHintLearner {
+    pub fn new(min_samples: u64, min_frequency: f64) -> Self {
+        assert!(
+            min_frequency > 0.0 && min_frequency <= 1.0,
+            "min_frequency must be in (0, 1]"
+        );
+        Self {
+            min_samples,
+            min_frequency,
+            stats: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Records the keys written by a call to the entry function.
+    pub fn observe(&self, module: &ModuleId, function: &Identifier, write_set: &WriteSet) {
+        let mut keys: Vec<_> = write_set
+            .write_op_iter()
+            .filter_map(|(state_key, _)| ConflictKey::from_state_key(state_key))
+            .collect();
+        keys.sort();
+        keys.dedup();
+
+        let mut stats = self.stats.lock();
+        let function_stats = stats.entry((module.clone(), function.clone())).or_default();
+        function_stats.num_calls += 1;
+        for key in keys {
+            *function_stats.key_counts.entry(key).or_insert(0) += 1;
+        }
+
+        if function_stats.key_counts.len() > MAX_TRACKED_KEYS_PER_FUNCTION {
+            // Keys touched by a single call so far are the least likely to become hints.
+            function_stats.key_counts.retain(|_, count| *count > 1);
+        }
+    }
+
+    /// Records the keys written by the user transactions of an executed block.
+    pub fn observe_outputs(&self, txns: &[Transaction], outputs: &[TransactionOutput]) {
+        for (txn, output) in txns.iter().zip(outputs) {
+            if let Transaction::UserTransaction(signed_txn) = txn {
+                if let Some((module, function)) = signed_txn.parse_entry_function() {
+                    self.observe(module, &function.to_owned(), output.write_set());
+                }
+            }
+        }
+    }
+
+    /// Returns the hints learned so far, for the entry functions called at least `min_samples`
+    /// times.
+    pub fn hints(&self) -> Vec<EntryFunctionConflictHints> {
+        let stats = self.stats.lock();
+        let mut hints: Vec<_> = stats
+            .iter()
+            .filter(|(_, function_stats)| function_stats.num_calls >= self.min_samples.max(1))
+            .filter_map(|((module, function), function_stats)| {
+                let threshold = function_stats.num_calls as f64 * self.min_frequency;
+                let mut keys: Vec<_> = function_stats
+                    .key_counts
+                    .iter()
+                    .filter(|(_, count)| **count as f64 >= threshold)
+                    .map(|(key, _)| key.clone())
+                    .collect();
+                keys.sort();
+                (!keys.is_empty()).then(|| {
+                    EntryFunctionConflictHints::new(module.clone(), function.clone(), keys)
+                })
+            })
+            .collect();
+        hints.sort_by(|a, b| (&a.module, &a.function).cmp(&(&b.module, &b.function)));
+        hints
+    }
+}
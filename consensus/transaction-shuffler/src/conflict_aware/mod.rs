@@ -0,0 +1,134 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::TransactionShuffler;
+use aptos_types::transaction::{
+    conflict_hint::{ConflictHintedTransaction, ConflictKey, EntryFunctionConflictHints},
+    signature_verified_transaction::SignatureVerifiedTransaction,
+    SignedTransaction,
+};
+use iterator::ConflictAwareTransactionIterator;
+use learner::ConflictHintLearner;
+use move_core_types::{identifier::Identifier, language_storage::ModuleId};
+use std::{collections::HashMap, sync::Arc};
+
+pub(crate) mod iterator;
+pub mod learner;
+#[cfg(test)]
+mod tests;
+
+#[derive(Clone, Debug, Default)]
+pub struct Config {
+    pub sender_spread_factor: usize,
+    pub conflict_spread_factor: usize,
+    pub max_lookahead: usize,
+    learned_hints: Arc<HashMap<ModuleId, HashMap<Identifier, Vec<ConflictKey>>>>,
+}
+
+impl Config {
+    pub fn new(
+        sender_spread_factor: usize,
+        conflict_spread_factor: usize,
+        max_lookahead: usize,
+        learned_hints: Vec<EntryFunctionConflictHints>,
+    ) -> Self {
+        let mut hints_by_module: HashMap<ModuleId, HashMap<Identifier, Vec<ConflictKey>>> =
+            HashMap::new();
+        for hints in learned_hints {
+            hints_by_module
+                .entry(hints.module)
+                .or_default()
+                .entry(hints.function)
+                .or_default()
+                .extend(hints.keys);
+        }
+
+        Self {
+            sender_spread_factor,
+            conflict_spread_factor,
+            max_lookahead,
+            learned_hints: Arc::new(hints_by_module),
+        }
+    }
+
+    /// Returns a copy of the config with the hints added to the learned ones.
+    pub(crate) fn with_additional_hints(&self, hints: Vec<EntryFunctionConflictHints>) -> Self {
+        let mut hints_by_module = (*self.learned_hints).clone();
+        for hints in hints {
+            hints_by_module
+                .entry(hints.module)
+                .or_default()
+                .entry(hints.function)
+                .or_default()
+                .extend(hints.keys);
+        }
+
+        Self {
+            learned_hints: Arc::new(hints_by_module),
+            ..self.clone()
+        }
+    }
+
+    pub(crate) fn num_learned_hints(&self) -> usize {
+        self.learned_hints.values().map(HashMap::len).sum()
+    }
+
+    /// Returns the deduplicated set of conflict keys of a transaction, i.e. the ones derivable
+    /// from its payload plus the ones learned for the entry function it calls.
+    pub(crate) fn conflict_keys<Txn: ConflictHintedTransaction>(
+        &self,
+        txn: &Txn,
+    ) -> Vec<ConflictKey> {
+        let mut keys = txn.static_conflict_keys();
+        if let Some(learned) = txn.parse_entry_function().and_then(|(module, function)| {
+            self.learned_hints
+                .get(module)
+                .and_then(|functions| functions.get(function))
+        }) {
+            keys.extend(learned.iter().cloned());
+        }
+        keys.sort();
+        keys.dedup();
+        keys
+    }
+}
+
+pub struct ConflictAwareShuffler {
+    pub config: Config,
+    /// When set, the hints learned so far are used in addition to the configured ones. Only for
+    /// benchmarks and offline tools, see [`ConflictHintLearner`].
+    pub learner: Option<Arc<ConflictHintLearner>>,
+}
+
+impl ConflictAwareShuffler {
+    fn current_config(&self) -> Config {
+        match &self.learner {
+            Some(learner) => self.config.with_additional_hints(learner.hints()),
+            None => self.config.clone(),
+        }
+    }
+}
+
+impl TransactionShuffler for ConflictAwareShuffler {
+    fn shuffle(&self, txns: Vec<SignedTransaction>) -> Vec<SignedTransaction> {
+        self.signed_transaction_iterator(txns).collect()
+    }
+
+    fn signed_transaction_iterator(
+        &self,
+        txns: Vec<SignedTransaction>,
+    ) -> Box<dyn Iterator<Item = SignedTransaction> + 'static> {
+        let iterator =
+            ConflictAwareTransactionIterator::new(self.current_config()).extended_with(txns);
+        Box::new(iterator)
+    }
+
+    fn signature_verified_transaction_iterator(
+        &self,
+        txns: Vec<SignatureVerifiedTransaction>,
+    ) -> Box<dyn Iterator<Item = SignatureVerifiedTransaction> + 'static> {
+        let iterator =
+            ConflictAwareTransactionIterator::new(self.current_config()).extended_with(txns);
+        Box::new(iterator)
+    }
+}
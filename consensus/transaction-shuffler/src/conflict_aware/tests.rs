@@ -0,0 +1,231 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::conflict_aware::{
+    iterator::ConflictAwareTransactionIterator, learner::ConflictHintLearner, Config,
+};
+use aptos_types::{
+    state_store::{state_key::StateKey, table::TableHandle},
+    transaction::{
+        conflict_hint::{ConflictHintedTransaction, ConflictKey, EntryFunctionConflictHints},
+        use_case::{UseCaseAwareTransaction, UseCaseKey},
+    },
+    write_set::WriteSet,
+};
+use itertools::Itertools;
+use move_core_types::{
+    account_address::AccountAddress,
+    ident_str,
+    identifier::{IdentStr, Identifier},
+    language_storage::ModuleId,
+};
+use proptest::{collection::vec, prelude::*};
+use std::collections::HashMap;
+
+struct Transaction {
+    sender: u8,
+    /// Index of the user module called, `None` for framework calls.
+    module: Option<u8>,
+    module_id: ModuleId,
+    function: Identifier,
+    original_idx: usize,
+}
+
+fn address(byte: u8) -> AccountAddress {
+    let mut addr = [0u8; 32];
+    addr[0] = 0xFF;
+    addr[31] = byte;
+    AccountAddress::new(addr)
+}
+
+fn module_id(module: Option<u8>) -> ModuleId {
+    match module {
+        Some(module) => ModuleId::new(address(module), ident_str!("dex").to_owned()),
+        None => ModuleId::new(AccountAddress::ONE, ident_str!("aptos_account").to_owned()),
+    }
+}
+
+impl UseCaseAwareTransaction for Transaction {
+    fn parse_sender(&self) -> AccountAddress {
+        address(self.sender)
+    }
+
+    fn parse_use_case(&self) -> UseCaseKey {
+        match self.module {
+            Some(_) => UseCaseKey::ContractAddress(*self.module_id.address()),
+            None => UseCaseKey::Platform,
+        }
+    }
+}
+
+impl ConflictHintedTransaction for Transaction {
+    fn parse_entry_function(&self) -> Option<(&ModuleId, &IdentStr)> {
+        Some((&self.module_id, &self.function))
+    }
+}
+
+fn into_txns(txns: impl IntoIterator<Item = (u8, Option<u8>)>) -> Vec<Transaction> {
+    txns.into_iter()
+        .enumerate()
+        .map(|(original_idx, (sender, module))| Transaction {
+            sender,
+            module,
+            module_id: module_id(module),
+            function: ident_str!("swap").to_owned(),
+            original_idx,
+        })
+        .collect()
+}
+
+fn shuffled_order(config: Config, txns: Vec<Transaction>) -> Vec<usize> {
+    ConflictAwareTransactionIterator::new(config)
+        .extended_with(txns)
+        .map(|txn| txn.original_idx)
+        .collect_vec()
+}
+
+#[test]
+fn test_no_spreading() {
+    let config = Config::new(0, 0, 16, vec![]);
+    let txns = into_txns([(1, Some(1)), (2, Some(1)), (3, Some(2)), (4, None)]);
+
+    assert_eq!(shuffled_order(config, txns), vec![0, 1, 2, 3]);
+}
+
+#[test]
+fn test_spread_by_module() {
+    let config = Config::new(0, 1, 16, vec![]);
+    let txns = into_txns([
+        (1, Some(1)),
+        (2, Some(1)),
+        (3, Some(1)),
+        (4, Some(2)),
+        (5, None),
+        (6, None),
+    ]);
+
+    // Framework calls never conflict with each other.
+    assert_eq!(shuffled_order(config, txns), vec![0, 3, 1, 4, 2, 5]);
+}
+
+#[test]
+fn test_sender_order_is_preserved() {
+    let config = Config::new(0, 1, 16, vec![]);
+    let txns = into_txns([(1, Some(1)), (2, Some(1)), (2, Some(2)), (3, Some(2))]);
+
+    // The second transaction of sender 2 doesn't conflict with the first transaction, but it
+    // can't be output before the first transaction of the same sender.
+    assert_eq!(shuffled_order(config, txns), vec![0, 3, 1, 2]);
+}
+
+#[test]
+fn test_lookahead_limit() {
+    let config = Config::new(0, 1, 2, vec![]);
+    let txns = into_txns([(1, Some(1)), (2, Some(1)), (3, Some(1)), (4, Some(2))]);
+
+    // The transaction on module 2 is beyond the lookahead window when the second transaction is
+    // selected, so the earliest pending transaction is output despite the conflict.
+    assert_eq!(shuffled_order(config, txns), vec![0, 1, 3, 2]);
+}
+
+#[test]
+fn test_learned_hints() {
+    let table = ConflictKey::Table(TableHandle(address(0xAA)));
+    let learned_hints = vec![
+        EntryFunctionConflictHints::new(module_id(Some(1)), ident_str!("swap").to_owned(), vec![
+            table.clone(),
+        ]),
+        EntryFunctionConflictHints::new(module_id(Some(2)), ident_str!("swap").to_owned(), vec![
+            table,
+        ]),
+    ];
+    let config = Config::new(0, 1, 16, learned_hints);
+    let txns = into_txns([(1, Some(1)), (2, Some(2)), (3, Some(3))]);
+
+    // Modules 1 and 2 are known to share a table, so they are spread apart.
+    assert_eq!(shuffled_order(config, txns), vec![0, 2, 1]);
+}
+
+fn write_set(keys: impl IntoIterator<Item = StateKey>) -> WriteSet {
+    WriteSet::new_for_test(keys.into_iter().map(|key| (key, None)))
+}
+
+#[test]
+fn test_learner() {
+    let learner = ConflictHintLearner::new(4, 0.5);
+    let module = module_id(Some(1));
+    let function = ident_str!("swap").to_owned();
+    let pool = TableHandle(address(0xAA));
+    let pool_item = StateKey::table_item(&pool, b"pool");
+
+    for i in 0..3u8 {
+        let user_item = StateKey::table_item(&TableHandle(address(i)), b"balance");
+        learner.observe(
+            &module,
+            &function,
+            &write_set([pool_item.clone(), user_item]),
+        );
+    }
+    // Not enough samples yet.
+    assert!(learner.hints().is_empty());
+
+    learner.observe(&module, &function, &write_set([]));
+    // The pool is written by 3 out of 4 calls, each user table by a single one.
+    assert_eq!(learner.hints(), vec![EntryFunctionConflictHints::new(
+        module.clone(),
+        function.clone(),
+        vec![ConflictKey::Table(pool)],
+    )]);
+
+    for _ in 0..3 {
+        learner.observe(&module, &function, &write_set([]));
+    }
+    // The pool is now written by less than half of the calls.
+    assert!(learner.hints().is_empty());
+}
+
+#[test]
+fn test_learned_hints_spread_transactions() {
+    let learner = ConflictHintLearner::new(1, 1.0);
+    let table_item = StateKey::table_item(&TableHandle(address(0xAA)), b"pool");
+    for module in [1, 2] {
+        learner.observe(
+            &module_id(Some(module)),
+            &ident_str!("swap").to_owned(),
+            &write_set([table_item.clone()]),
+        );
+    }
+
+    let config = Config::new(0, 1, 16, learner.hints());
+    let txns = into_txns([(1, Some(1)), (2, Some(2)), (3, Some(3))]);
+    assert_eq!(shuffled_order(config, txns), vec![0, 2, 1]);
+}
+
+fn txn_indices_by_sender(txns: &[Transaction]) -> HashMap<u8, Vec<usize>> {
+    txns.iter()
+        .map(|txn| (txn.sender, txn.original_idx))
+        .into_group_map()
+}
+
+proptest! {
+    #[test]
+    fn test_no_panic(
+        txns in vec(any::<(u8, Option<u8>)>(), 0..100).prop_map(into_txns),
+        sender_factor in 0..100usize,
+        conflict_factor in 0..100usize,
+        max_lookahead in 0..100usize,
+    ) {
+        let num_txns = txns.len();
+        let txns_by_sender = txn_indices_by_sender(&txns);
+
+        let config = Config::new(sender_factor, conflict_factor, max_lookahead, vec![]);
+        let shuffled_txns = ConflictAwareTransactionIterator::new(config)
+            .extended_with(txns)
+            .collect_vec();
+
+        prop_assert_eq!(txn_indices_by_sender(&shuffled_txns), txns_by_sender);
+
+        let txn_indices = shuffled_txns.into_iter().map(|txn| txn.original_idx).sorted().collect_vec();
+        prop_assert_eq!(txn_indices, (0..num_txns).collect_vec());
+    }
+}
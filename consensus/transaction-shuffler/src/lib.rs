@@ -10,12 +10,15 @@ use aptos_types::{
 };
 use std::sync::Arc;
 
+mod conflict_aware;
 mod use_case_aware;
+
+pub use conflict_aware::learner::ConflictHintLearner;
 // re-export use case aware shuffler for fuzzer.
 #[cfg(feature = "fuzzing")]
 pub mod transaction_shuffler_fuzzing {
     pub mod use_case_aware {
-        pub use crate::transaction_shuffler::use_case_aware::{Config, UseCaseAwareShuffler};
+        pub use crate::use_case_aware::{Config, UseCaseAwareShuffler};
     }
 }
 
@@ -63,6 +66,16 @@ impl TransactionShuffler for NoOpShuffler {
 
 pub fn create_transaction_shuffler(
     shuffler_type: TransactionShufflerType,
+) -> Arc<dyn TransactionShuffler> {
+    create_transaction_shuffler_with_learner(shuffler_type, None)
+}
+
+/// Same as [`create_transaction_shuffler`], but a conflict aware shuffler additionally uses the
+/// hints learned so far by the learner. Must not be used by validators, as the learned hints
+/// differ between nodes.
+pub fn create_transaction_shuffler_with_learner(
+    shuffler_type: TransactionShufflerType,
+    learner: Option<Arc<ConflictHintLearner>>,
 ) -> Arc<dyn TransactionShuffler> {
     use TransactionShufflerType::*;
 
@@ -97,5 +110,27 @@ pub fn create_transaction_shuffler(
             );
             Arc::new(use_case_aware::UseCaseAwareShuffler { config })
         },
+        ConflictAware {
+            sender_spread_factor,
+            conflict_spread_factor,
+            max_lookahead,
+            learned_hints,
+        } => {
+            let config = conflict_aware::Config::new(
+                sender_spread_factor,
+                conflict_spread_factor,
+                max_lookahead,
+                learned_hints,
+            );
+            info!(
+                sender_spread_factor = sender_spread_factor,
+                conflict_spread_factor = conflict_spread_factor,
+                max_lookahead = max_lookahead,
+                num_learned_hints = config.num_learned_hints(),
+                online_learning = learner.is_some(),
+                "Using conflict aware transaction shuffling."
+            );
+            Arc::new(conflict_aware::ConflictAwareShuffler { config, learner })
+        },
     }
 }
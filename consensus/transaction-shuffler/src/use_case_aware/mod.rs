@@ -1,7 +1,7 @@
 // Copyright (c) Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::transaction_shuffler::TransactionShuffler;
+use crate::TransactionShuffler;
 use aptos_types::transaction::{
     signature_verified_transaction::SignatureVerifiedTransaction, use_case::UseCaseKey,
     SignedTransaction,
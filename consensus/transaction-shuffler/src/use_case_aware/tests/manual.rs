@@ -1,7 +1,7 @@
 // Copyright (c) Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::transaction_shuffler::use_case_aware::{
+use crate::use_case_aware::{
     iterator::ShuffledTransactionIterator,
     tests,
     tests::{Account, Contract},
@@ -4,13 +4,14 @@
 use anyhow::{bail, Error};
 use aptos_consensus::{
     persistent_liveness_storage::PersistentLivenessStorage,
-    quorum_store::quorum_store_db::QuorumStoreStorage, util::db_tool::extract_txns_from_block,
+    quorum_store::quorum_store_db::QuorumStoreStorage, state_inspector::ConsensusStateInspector,
+    util::db_tool::extract_txns_from_block,
 };
 use aptos_crypto::HashValue;
 use aptos_logger::info;
 use aptos_system_utils::utils::{reply_with, reply_with_status, spawn_blocking};
 use aptos_types::transaction::Transaction;
-use http::header::{HeaderValue, CONTENT_LENGTH};
+use http::header::{HeaderValue, CONTENT_LENGTH, CONTENT_TYPE};
 use hyper::{Body, Request, Response, StatusCode};
 use std::{collections::HashMap, sync::Arc};
 
@@ -71,6 +72,31 @@ pub async fn handle_dump_quorum_store_db_request(
     }
 }
 
+pub async fn handle_consensus_state_request(
+    _req: Request<Body>,
+    consensus_state_inspector: Arc<ConsensusStateInspector>,
+) -> hyper::Result<Response<Body>> {
+    info!("Dumping consensus state.");
+
+    match serde_json::to_vec_pretty(&consensus_state_inspector.snapshot()) {
+        Ok(result) => {
+            info!("Finished dumping consensus state.");
+            let headers: Vec<(_, HeaderValue)> = vec![
+                (CONTENT_LENGTH, HeaderValue::from(result.len())),
+                (CONTENT_TYPE, HeaderValue::from_static("application/json")),
+            ];
+            Ok(reply_with(headers, result))
+        },
+        Err(e) => {
+            info!("Failed to serialize consensus state: {e:?}");
+            Ok(reply_with_status(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                e.to_string(),
+            ))
+        },
+    }
+}
+
 pub async fn handle_dump_block_request(
     req: Request<Body>,
     consensus_db: Arc<dyn PersistentLivenessStorage>,
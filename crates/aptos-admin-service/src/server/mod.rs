@@ -4,10 +4,12 @@
 use aptos_config::config::{AuthenticationConfig, NodeConfig};
 use aptos_consensus::{
     persistent_liveness_storage::StorageWriteProxy, quorum_store::quorum_store_db::QuorumStoreDB,
+    state_inspector::ConsensusStateInspector,
 };
 use aptos_infallible::RwLock;
 use aptos_logger::info;
 use aptos_mempool::MempoolClientSender;
+use aptos_network::application::ban_list::PeerBanList;
 use aptos_storage_interface::DbReaderWriter;
 use aptos_system_utils::utils::reply_with_status;
 #[cfg(target_os = "linux")]
@@ -28,6 +30,7 @@ use tokio::runtime::Runtime;
 
 mod consensus;
 mod mempool;
+mod network;
 
 #[derive(Default)]
 pub struct Context {
@@ -36,7 +39,9 @@ pub struct Context {
     aptos_db: RwLock<Option<Arc<DbReaderWriter>>>,
     consensus_db: RwLock<Option<Arc<StorageWriteProxy>>>,
     quorum_store_db: RwLock<Option<Arc<QuorumStoreDB>>>,
+    consensus_state_inspector: RwLock<Option<Arc<ConsensusStateInspector>>>,
     mempool_client_sender: RwLock<Option<MempoolClientSender>>,
+    peer_ban_list: RwLock<Option<Arc<PeerBanList>>>,
 }
 
 impl Context {
@@ -53,9 +58,20 @@ impl Context {
         *self.quorum_store_db.write() = Some(quorum_store_db);
     }
 
+    fn set_consensus_state_inspector(
+        &self,
+        consensus_state_inspector: Arc<ConsensusStateInspector>,
+    ) {
+        *self.consensus_state_inspector.write() = Some(consensus_state_inspector);
+    }
+
     fn set_mempool_client_sender(&self, mempool_client_sender: MempoolClientSender) {
         *self.mempool_client_sender.write() = Some(mempool_client_sender);
     }
+
+    fn set_peer_ban_list(&self, peer_ban_list: Arc<PeerBanList>) {
+        *self.peer_ban_list.write() = Some(peer_ban_list);
+    }
 }
 
 pub struct AdminService {
@@ -114,11 +130,23 @@ impl AdminService {
             .set_consensus_dbs(consensus_db, quorum_store_db)
     }
 
+    pub fn set_consensus_state_inspector(
+        &self,
+        consensus_state_inspector: Arc<ConsensusStateInspector>,
+    ) {
+        self.context
+            .set_consensus_state_inspector(consensus_state_inspector)
+    }
+
     pub fn set_mempool_client_sender(&self, mempool_client_sender: MempoolClientSender) {
         self.context
             .set_mempool_client_sender(mempool_client_sender)
     }
 
+    pub fn set_peer_ban_list(&self, peer_ban_list: Arc<PeerBanList>) {
+        self.context.set_peer_ban_list(peer_ban_list)
+    }
+
     fn start(&self, address: SocketAddr, enabled: bool) {
         let context = self.context.clone();
         self.runtime.spawn(async move {
@@ -222,6 +250,17 @@ impl AdminService {
                     ))
                 }
             },
+            (hyper::Method::GET, "/debug/consensus/state") => {
+                let consensus_state_inspector = context.consensus_state_inspector.read().clone();
+                if let Some(consensus_state_inspector) = consensus_state_inspector {
+                    consensus::handle_consensus_state_request(req, consensus_state_inspector).await
+                } else {
+                    Ok(reply_with_status(
+                        StatusCode::NOT_FOUND,
+                        "Consensus state is not available.",
+                    ))
+                }
+            },
             (hyper::Method::GET, "/debug/mempool/parking-lot/addresses") => {
                 let mempool_client_sender = context.mempool_client_sender.read().clone();
                 if mempool_client_sender.is_some() {
@@ -237,6 +276,39 @@ impl AdminService {
                     ))
                 }
             },
+            (hyper::Method::GET, "/debug/network/bans") => {
+                let peer_ban_list = context.peer_ban_list.read().clone();
+                if let Some(peer_ban_list) = peer_ban_list {
+                    network::handle_get_peer_bans_request(req, peer_ban_list).await
+                } else {
+                    Ok(reply_with_status(
+                        StatusCode::NOT_FOUND,
+                        "Peer ban list is not available.",
+                    ))
+                }
+            },
+            (hyper::Method::POST, "/debug/network/ban") => {
+                let peer_ban_list = context.peer_ban_list.read().clone();
+                if let Some(peer_ban_list) = peer_ban_list {
+                    network::handle_ban_peer_request(req, peer_ban_list).await
+                } else {
+                    Ok(reply_with_status(
+                        StatusCode::NOT_FOUND,
+                        "Peer ban list is not available.",
+                    ))
+                }
+            },
+            (hyper::Method::POST, "/debug/network/unban") => {
+                let peer_ban_list = context.peer_ban_list.read().clone();
+                if let Some(peer_ban_list) = peer_ban_list {
+                    network::handle_unban_peer_request(req, peer_ban_list).await
+                } else {
+                    Ok(reply_with_status(
+                        StatusCode::NOT_FOUND,
+                        "Peer ban list is not available.",
+                    ))
+                }
+            },
             _ => Ok(reply_with_status(StatusCode::NOT_FOUND, "Not found.")),
         }
     }
@@ -0,0 +1,119 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_logger::info;
+use aptos_network::application::{
+    ban_list::{BanTarget, PeerBanList},
+    error::Error,
+};
+use aptos_system_utils::utils::{reply_with, reply_with_status};
+use http::header::{HeaderValue, CONTENT_TYPE};
+use hyper::{Body, Request, Response, StatusCode};
+use serde::Serialize;
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+/// Returns all active peer bans (as JSON)
+pub async fn handle_get_peer_bans_request(
+    _req: Request<Body>,
+    peer_ban_list: Arc<PeerBanList>,
+) -> hyper::Result<Response<Body>> {
+    Ok(reply_with_json(&peer_ban_list.get_active_bans()))
+}
+
+/// Bans a peer ID or IP range. The request must specify exactly one of
+/// `peer_id` or `ip_range`, and may specify `duration_secs` and `reason`.
+pub async fn handle_ban_peer_request(
+    req: Request<Body>,
+    peer_ban_list: Arc<PeerBanList>,
+) -> hyper::Result<Response<Body>> {
+    let query_pairs = get_query_pairs(&req);
+    let target = match parse_ban_target(&query_pairs) {
+        Ok(target) => target,
+        Err(error) => return Ok(reply_with_status(StatusCode::BAD_REQUEST, error)),
+    };
+    let duration = match query_pairs.get("duration_secs") {
+        Some(duration_secs) => match duration_secs.parse() {
+            Ok(duration_secs) => Some(Duration::from_secs(duration_secs)),
+            Err(error) => {
+                return Ok(reply_with_status(
+                    StatusCode::BAD_REQUEST,
+                    format!("Invalid duration_secs: {}", error),
+                ))
+            },
+        },
+        None => None,
+    };
+    let reason = query_pairs.get("reason").cloned();
+
+    info!("Banning {} for duration: {:?}", target, duration);
+    match peer_ban_list.ban(target, duration, reason) {
+        Ok(peer_ban) => Ok(reply_with_json(&peer_ban)),
+        Err(error) => {
+            info!("Failed to ban {}: {:?}", target, error);
+            Ok(reply_with_status(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                error.to_string(),
+            ))
+        },
+    }
+}
+
+/// Removes the ban for a peer ID or IP range. The request must
+/// specify exactly one of `peer_id` or `ip_range`.
+pub async fn handle_unban_peer_request(
+    req: Request<Body>,
+    peer_ban_list: Arc<PeerBanList>,
+) -> hyper::Result<Response<Body>> {
+    let query_pairs = get_query_pairs(&req);
+    let target = match parse_ban_target(&query_pairs) {
+        Ok(target) => target,
+        Err(error) => return Ok(reply_with_status(StatusCode::BAD_REQUEST, error)),
+    };
+
+    info!("Unbanning {}", target);
+    match peer_ban_list.unban(&target) {
+        Ok(Some(peer_ban)) => Ok(reply_with_json(&peer_ban)),
+        Ok(None) => Ok(reply_with_status(
+            StatusCode::NOT_FOUND,
+            format!("No ban found for {}", target),
+        )),
+        Err(error) => {
+            info!("Failed to unban {}: {:?}", target, error);
+            Ok(reply_with_status(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                error.to_string(),
+            ))
+        },
+    }
+}
+
+/// Returns the query pairs of the given request
+fn get_query_pairs(req: &Request<Body>) -> HashMap<String, String> {
+    let query = req.uri().query().unwrap_or("");
+    url::form_urlencoded::parse(query.as_bytes())
+        .into_owned()
+        .collect()
+}
+
+/// Parses the ban target from the given query pairs
+fn parse_ban_target(query_pairs: &HashMap<String, String>) -> Result<BanTarget, String> {
+    let target = match (query_pairs.get("peer_id"), query_pairs.get("ip_range")) {
+        (Some(peer_id), None) => BanTarget::parse_peer_id(peer_id),
+        (None, Some(ip_range)) => BanTarget::parse_ip_range(ip_range),
+        _ => {
+            return Err("Exactly one of peer_id or ip_range must be specified!".into());
+        },
+    };
+    target.map_err(|error: Error| error.to_string())
+}
+
+/// Serializes the given value to JSON and returns it as the response
+fn reply_with_json<T: Serialize>(value: &T) -> Response<Body> {
+    match serde_json::to_string_pretty(value) {
+        Ok(json) => reply_with(
+            vec![(CONTENT_TYPE, HeaderValue::from_static("application/json"))],
+            json,
+        ),
+        Err(error) => reply_with_status(StatusCode::INTERNAL_SERVER_ERROR, error.to_string()),
+    }
+}
@@ -4,7 +4,7 @@
 /// A simple enum for identifying clients of the compression crate. This
 /// allows us to provide a runtime breakdown of compression metrics for
 /// each client.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum CompressionClient {
     Consensus,
     ConsensusObserver,
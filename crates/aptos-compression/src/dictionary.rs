@@ -0,0 +1,168 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{client::CompressionClient, Error, Error::DictionaryError};
+use aptos_infallible::RwLock;
+use aptos_logger::prelude::*;
+use once_cell::sync::Lazy;
+use std::{collections::HashMap, fmt, fs, path::Path, sync::Arc};
+use zstd::dict::{DecoderDictionary, EncoderDictionary};
+
+/// The default maximum dictionary size (in bytes) when training new
+/// dictionaries. This is the default used by the zstd CLI.
+pub const DEFAULT_MAX_DICTIONARY_SIZE: usize = 112_640; // 110 KiB
+
+/// The dictionaries used to compress data for each client
+static COMPRESSION_DICTIONARIES: Lazy<RwLock<HashMap<CompressionClient, Arc<ZstdDictionary>>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// The dictionaries available to decompress data (indexed by dictionary ID)
+static DECOMPRESSION_DICTIONARIES: Lazy<RwLock<HashMap<u32, Arc<ZstdDictionary>>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// A trained zstd dictionary, prepared for both compression and
+/// decompression. Compressed frames record the ID of the dictionary
+/// used to compress them, so the receiver can identify the dictionary
+/// to use for decompression.
+pub struct ZstdDictionary {
+    dictionary_id: u32,
+    encoder_dictionary: EncoderDictionary<'static>,
+    decoder_dictionary: DecoderDictionary<'static>,
+}
+
+impl ZstdDictionary {
+    /// Creates a new dictionary from the given (trained) dictionary bytes
+    pub fn new(dictionary_bytes: &[u8], compression_level: i32) -> Result<Self, Error> {
+        // Trained dictionaries always contain a (non-zero) dictionary ID
+        let dictionary_id = zstd::zstd_safe::get_dict_id_from_dict(dictionary_bytes)
+            .ok_or_else(|| {
+                DictionaryError(
+                    "The dictionary does not contain an ID! Is it a trained dictionary?".into(),
+                )
+            })?
+            .get();
+
+        Ok(Self {
+            dictionary_id,
+            encoder_dictionary: EncoderDictionary::copy(dictionary_bytes, compression_level),
+            decoder_dictionary: DecoderDictionary::copy(dictionary_bytes),
+        })
+    }
+
+    /// Loads a dictionary from the given file path
+    pub fn load_from_file(path: &Path, compression_level: i32) -> Result<Self, Error> {
+        let dictionary_bytes = fs::read(path).map_err(|error| {
+            DictionaryError(format!(
+                "Failed to read the dictionary file: {:?}, error: {}",
+                path, error
+            ))
+        })?;
+        Self::new(&dictionary_bytes, compression_level)
+    }
+
+    /// Returns the ID of the dictionary
+    pub fn get_dictionary_id(&self) -> u32 {
+        self.dictionary_id
+    }
+
+    /// Returns the dictionary prepared for compression
+    pub(crate) fn get_encoder_dictionary(&self) -> &EncoderDictionary<'static> {
+        &self.encoder_dictionary
+    }
+
+    /// Returns the dictionary prepared for decompression
+    pub(crate) fn get_decoder_dictionary(&self) -> &DecoderDictionary<'static> {
+        &self.decoder_dictionary
+    }
+}
+
+impl fmt::Debug for ZstdDictionary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "ZstdDictionary {{ dictionary_id: {} }}",
+            self.dictionary_id
+        )
+    }
+}
+
+/// Registers the dictionary to compress all zstd data for the given client.
+/// The dictionary is also made available for decompression.
+///
+/// Note: receivers must be able to decompress the data, so the dictionary
+/// should be registered for decompression (on all peers) before it is
+/// used for compression.
+pub fn register_dictionary(client: CompressionClient, dictionary: ZstdDictionary) {
+    let dictionary = Arc::new(dictionary);
+    info!(
+        "Registering zstd dictionary {} for client: {}",
+        dictionary.get_dictionary_id(),
+        client.get_label()
+    );
+
+    DECOMPRESSION_DICTIONARIES
+        .write()
+        .insert(dictionary.get_dictionary_id(), dictionary.clone());
+    COMPRESSION_DICTIONARIES.write().insert(client, dictionary);
+}
+
+/// Registers the dictionary for decompression only (e.g., to prepare
+/// for peers that have started compressing data using the dictionary).
+pub fn register_decompression_dictionary(dictionary: ZstdDictionary) {
+    info!(
+        "Registering zstd dictionary {} for decompression",
+        dictionary.get_dictionary_id()
+    );
+
+    DECOMPRESSION_DICTIONARIES
+        .write()
+        .insert(dictionary.get_dictionary_id(), Arc::new(dictionary));
+}
+
+/// Returns the dictionary to use when compressing data for the given client
+pub(crate) fn get_compression_dictionary(
+    client: &CompressionClient,
+) -> Option<Arc<ZstdDictionary>> {
+    COMPRESSION_DICTIONARIES.read().get(client).cloned()
+}
+
+/// Returns the ID of the dictionary used to compress data for the given
+/// client (if any). Peers must advertise this ID before data is sent to
+/// them using the dictionary.
+pub fn get_compression_dictionary_id(client: &CompressionClient) -> Option<u32> {
+    get_compression_dictionary(client).map(|dictionary| dictionary.get_dictionary_id())
+}
+
+/// Returns the (sorted) IDs of all dictionaries available for decompression
+pub fn get_decompression_dictionary_ids() -> Vec<u32> {
+    let mut dictionary_ids: Vec<_> = DECOMPRESSION_DICTIONARIES.read().keys().copied().collect();
+    dictionary_ids.sort_unstable();
+    dictionary_ids
+}
+
+/// Returns the dictionary with the given ID (for decompression)
+pub(crate) fn get_decompression_dictionary(dictionary_id: u32) -> Option<Arc<ZstdDictionary>> {
+    DECOMPRESSION_DICTIONARIES
+        .read()
+        .get(&dictionary_id)
+        .cloned()
+}
+
+/// Trains a new zstd dictionary from the given data samples. The samples
+/// should be representative of the data that will be compressed (e.g.,
+/// BCS encoded messages captured from the network).
+pub fn train_dictionary(samples: &[Vec<u8>], max_dictionary_size: usize) -> Result<Vec<u8>, Error> {
+    if samples.is_empty() {
+        return Err(DictionaryError(
+            "At least one sample is required to train a dictionary!".into(),
+        ));
+    }
+
+    zstd::dict::from_samples(samples, max_dictionary_size).map_err(|error| {
+        DictionaryError(format!(
+            "Failed to train the dictionary using {} samples! Error: {}",
+            samples.len(),
+            error
+        ))
+    })
+}
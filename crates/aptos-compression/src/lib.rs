@@ -9,6 +9,7 @@ use aptos_logger::prelude::*;
 use lz4::block::CompressionMode;
 use std::time::Instant;
 use thiserror::Error;
+use zstd::bulk::{Compressor, Decompressor};
 
 /// This crate provides a simple library interface for data compression.
 /// It is useful for compressing large data chunks that are
@@ -16,10 +17,16 @@ use thiserror::Error;
 /// Internally, it uses LZ4 in fast mode to compress the data.
 /// See <https://github.com/10xGenomics/lz4-rs> for more information.
 ///
+/// The crate also offers zstd compression (with optional trained
+/// dictionaries), which trades a little speed for a much better
+/// compression ratio on highly repetitive data (e.g., BCS messages).
+/// See <https://github.com/gyscos/zstd-rs> for more information.
+///
 /// Note: the crate also exposes some basic compression metrics
 /// that can be used to track the cumulative compression ratio
 /// and compression/decompression durations during the runtime.
 pub mod client;
+pub mod dictionary;
 mod metrics;
 #[cfg(test)]
 mod tests;
@@ -28,6 +35,9 @@ mod tests;
 /// This was determined anecdotally.
 const ACCELERATION_PARAMETER: i32 = 1;
 
+/// The zstd compression level to use (when no dictionary is registered)
+pub const DEFAULT_ZSTD_COMPRESSION_LEVEL: i32 = 3;
+
 /// A useful wrapper for representing compressed data
 pub type CompressedData = Vec<u8>;
 
@@ -38,6 +48,8 @@ pub enum Error {
     CompressionError(String),
     #[error("Encountered a decompression error! Error: {0}")]
     DecompressionError(String),
+    #[error("Encountered a dictionary error! Error: {0}")]
+    DictionaryError(String),
 }
 
 /// Compresses the raw data stream
@@ -120,6 +132,122 @@ pub fn decompress(
     Ok(raw_data)
 }
 
+/// Compresses the raw data stream using zstd. If a dictionary has been
+/// registered for the client, the dictionary is used to compress the data.
+pub fn compress_with_zstd(
+    raw_data: Vec<u8>,
+    client: CompressionClient,
+    max_bytes: usize,
+) -> Result<CompressedData, Error> {
+    // Start the compression timer
+    let start_time = Instant::now();
+
+    // Ensure that the raw data size is not greater than the max bytes limit
+    if raw_data.len() > max_bytes {
+        let error_string = format!(
+            "Raw data size greater than max bytes limit: {}, max: {}",
+            raw_data.len(),
+            max_bytes
+        );
+        return create_compression_error(&client, error_string);
+    }
+
+    // Create the compressor (using the registered dictionary, if any)
+    let dictionary = dictionary::get_compression_dictionary(&client);
+    let compressor = match &dictionary {
+        Some(dictionary) => {
+            Compressor::with_prepared_dictionary(dictionary.get_encoder_dictionary())
+        },
+        None => Compressor::new(DEFAULT_ZSTD_COMPRESSION_LEVEL),
+    };
+
+    // Compress the data
+    let compressed_data = match compressor.and_then(|mut compressor| compressor.compress(&raw_data))
+    {
+        Ok(compressed_data) => compressed_data,
+        Err(error) => {
+            let error_string = format!("Failed to compress the data using zstd: {}", error);
+            return create_compression_error(&client, error_string);
+        },
+    };
+
+    // Ensure that the compressed data size is not greater than the max byte limit
+    if compressed_data.len() > max_bytes {
+        let error_string = format!(
+            "Compressed size greater than max bytes limit: {}, max: {}",
+            compressed_data.len(),
+            max_bytes
+        );
+        return create_compression_error(&client, error_string);
+    }
+
+    // Stop the timer and update the metrics
+    metrics::observe_compression_operation_time(&client, start_time);
+    metrics::update_compression_metrics(&client, &raw_data, &compressed_data);
+
+    Ok(compressed_data)
+}
+
+/// Decompresses the zstd compressed data stream. If the data was compressed
+/// using a dictionary, the dictionary must have been registered locally.
+pub fn decompress_with_zstd(
+    compressed_data: &CompressedData,
+    client: CompressionClient,
+    max_size: usize,
+) -> Result<Vec<u8>, Error> {
+    // Start the decompression timer
+    let start_time = Instant::now();
+
+    // Check size of the data
+    let decompressed_size = match get_zstd_decompressed_size(compressed_data, max_size) {
+        Ok(size) => size,
+        Err(error) => {
+            let error_string = format!("Failed to get decompressed size: {}", error);
+            return create_decompression_error(&client, error_string);
+        },
+    };
+
+    // Identify the dictionary used to compress the data (if any)
+    let dictionary = match zstd::zstd_safe::get_dict_id_from_frame(compressed_data) {
+        Some(dictionary_id) => {
+            match dictionary::get_decompression_dictionary(dictionary_id.get()) {
+                Some(dictionary) => Some(dictionary),
+                None => {
+                    let error_string = format!(
+                        "The data was compressed using an unknown dictionary: {}",
+                        dictionary_id
+                    );
+                    return create_decompression_error(&client, error_string);
+                },
+            }
+        },
+        None => None,
+    };
+
+    // Create the decompressor and decompress the data
+    let decompressor = match &dictionary {
+        Some(dictionary) => {
+            Decompressor::with_prepared_dictionary(dictionary.get_decoder_dictionary())
+        },
+        None => Decompressor::new(),
+    };
+    let raw_data = match decompressor
+        .and_then(|mut decompressor| decompressor.decompress(compressed_data, decompressed_size))
+    {
+        Ok(raw_data) => raw_data,
+        Err(error) => {
+            let error_string = format!("Failed to decompress the data using zstd: {}", error);
+            return create_decompression_error(&client, error_string);
+        },
+    };
+
+    // Stop the timer and update the metrics
+    metrics::observe_decompression_operation_time(&client, start_time);
+    metrics::update_decompression_metrics(&client, compressed_data, &raw_data);
+
+    Ok(raw_data)
+}
+
 /// A simple utility function that wraps the given error string in a compression error
 fn create_compression_error(
     client: &CompressionClient,
@@ -183,6 +311,39 @@ fn get_decompressed_size(
     Ok(size)
 }
 
+/// Returns the decompressed size of the zstd frame. The compressor
+/// always records the content size in the frame header.
+fn get_zstd_decompressed_size(
+    compressed_data: &CompressedData,
+    max_size: usize,
+) -> Result<usize, Error> {
+    // Parse the content size from the frame header
+    let size = match zstd::zstd_safe::get_frame_content_size(compressed_data) {
+        Ok(Some(size)) => size,
+        Ok(None) => {
+            return Err(DecompressionError(
+                "The zstd frame does not contain the content size!".into(),
+            ))
+        },
+        Err(error) => {
+            return Err(DecompressionError(format!(
+                "Failed to parse the zstd frame header! Error: {:?}",
+                error
+            )))
+        },
+    };
+
+    // Ensure that the size is not greater than the max size limit
+    if size > max_size as u64 {
+        return Err(DecompressionError(format!(
+            "Parsed frame content size is too big: {} > {}",
+            size, max_size
+        )));
+    }
+
+    Ok(size as usize)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -1,7 +1,13 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::CompressionClient;
+use crate::{
+    dictionary::{
+        get_compression_dictionary_id, get_decompression_dictionary_ids, register_dictionary,
+        train_dictionary, ZstdDictionary,
+    },
+    CompressionClient, DEFAULT_ZSTD_COMPRESSION_LEVEL,
+};
 use aptos_crypto::{ed25519::Ed25519PrivateKey, hash::HashValue, PrivateKey, SigningKey, Uniform};
 use aptos_types::{
     account_address::AccountAddress,
@@ -74,6 +80,138 @@ fn test_compression_limits() {
     assert!(maybe_decompressed_bytes.is_err());
 }
 
+#[test]
+fn test_basic_zstd_compression() {
+    // Test compress random bytes
+    let raw_bytes: Vec<_> = (0..MIB).map(|_| rand::thread_rng().gen::<u8>()).collect();
+    test_zstd_compress_and_decompress(raw_bytes, CompressionClient::StateSync);
+
+    // Test epoch ending ledger infos
+    let epoch_ending_ledger_infos = create_epoch_ending_ledger_infos(0, 999);
+    test_zstd_compress_and_decompress(epoch_ending_ledger_infos, CompressionClient::StateSync);
+
+    // Test transaction outputs with proof
+    let outputs_with_proof = create_output_list_with_proof(13434, 17000, 19000);
+    test_zstd_compress_and_decompress(outputs_with_proof, CompressionClient::StateSync);
+
+    // Test transactions with proof
+    let transactions_with_proof = create_transaction_list_with_proof(1000, 1999, 1999, true);
+    test_zstd_compress_and_decompress(transactions_with_proof, CompressionClient::StateSync);
+}
+
+#[test]
+fn test_zstd_compression_limits() {
+    // Create test data
+    let too_small_bytes = 1;
+    let transactions_with_proof = create_transaction_list_with_proof(1000, 1999, 1999, true);
+
+    // Test compression limit
+    let bcs_encoded_bytes = bcs::to_bytes(&transactions_with_proof).unwrap();
+    let maybe_compressed_bytes = crate::compress_with_zstd(
+        bcs_encoded_bytes,
+        CompressionClient::StateSync,
+        too_small_bytes,
+    );
+    assert!(maybe_compressed_bytes.is_err());
+
+    // Test decompression limit
+    let bcs_encoded_bytes = bcs::to_bytes(&transactions_with_proof).unwrap();
+    let compressed_bytes = crate::compress_with_zstd(
+        bcs_encoded_bytes,
+        CompressionClient::StateSync,
+        MAX_COMPRESSION_SIZE,
+    )
+    .unwrap();
+    let maybe_decompressed_bytes = crate::decompress_with_zstd(
+        &compressed_bytes,
+        CompressionClient::StateSync,
+        too_small_bytes,
+    );
+    assert!(maybe_decompressed_bytes.is_err());
+
+    // Verify that lz4 compressed data can't be decompressed using zstd
+    let bcs_encoded_bytes = bcs::to_bytes(&transactions_with_proof).unwrap();
+    let lz4_compressed_bytes = crate::compress(
+        bcs_encoded_bytes,
+        CompressionClient::StateSync,
+        MAX_COMPRESSION_SIZE,
+    )
+    .unwrap();
+    let maybe_decompressed_bytes = crate::decompress_with_zstd(
+        &lz4_compressed_bytes,
+        CompressionClient::StateSync,
+        MAX_COMPRESSION_SIZE,
+    );
+    assert!(maybe_decompressed_bytes.is_err());
+}
+
+#[test]
+fn test_zstd_dictionary_compression() {
+    // Train a dictionary using BCS encoded transactions
+    let samples = create_transaction_samples(0, 2000);
+    let dictionary_bytes = train_dictionary(&samples, 4 * 1024).unwrap();
+    let dictionary =
+        ZstdDictionary::new(&dictionary_bytes, DEFAULT_ZSTD_COMPRESSION_LEVEL).unwrap();
+    let dictionary_id = dictionary.get_dictionary_id();
+
+    // Register the dictionary for the client
+    let client = CompressionClient::Mempool;
+    register_dictionary(client, dictionary);
+
+    // Verify that the dictionary is advertised for compression and decompression
+    assert_eq!(get_compression_dictionary_id(&client), Some(dictionary_id));
+    assert!(get_decompression_dictionary_ids().contains(&dictionary_id));
+
+    // Compress and decompress new transactions using the dictionary
+    for sample in create_transaction_samples(5000, 5100) {
+        let compressed_bytes =
+            crate::compress_with_zstd(sample.clone(), client, MAX_COMPRESSION_SIZE).unwrap();
+        assert_eq!(
+            zstd::zstd_safe::get_dict_id_from_frame(&compressed_bytes)
+                .unwrap()
+                .get(),
+            dictionary_id
+        );
+
+        // Verify that the data can be decompressed (the dictionary is identified by the frame)
+        let decompressed_bytes = crate::decompress_with_zstd(
+            &compressed_bytes,
+            CompressionClient::StateSync,
+            MAX_COMPRESSION_SIZE,
+        )
+        .unwrap();
+        assert_eq!(sample, decompressed_bytes);
+    }
+
+    // Verify that data compressed using an unknown dictionary can't be decompressed
+    let unknown_dictionary_bytes =
+        train_dictionary(&create_transaction_samples(10000, 12000), 2 * 1024).unwrap();
+    let unknown_dictionary =
+        ZstdDictionary::new(&unknown_dictionary_bytes, DEFAULT_ZSTD_COMPRESSION_LEVEL).unwrap();
+    let compressed_bytes = zstd::bulk::Compressor::with_prepared_dictionary(
+        unknown_dictionary.get_encoder_dictionary(),
+    )
+    .unwrap()
+    .compress(&samples[0])
+    .unwrap();
+    let maybe_decompressed_bytes = crate::decompress_with_zstd(
+        &compressed_bytes,
+        CompressionClient::StateSync,
+        MAX_COMPRESSION_SIZE,
+    );
+    assert!(maybe_decompressed_bytes.is_err());
+}
+
+#[test]
+fn test_zstd_dictionary_errors() {
+    // Verify that training fails without samples
+    assert!(train_dictionary(&[], 1024).is_err());
+
+    // Verify that raw content (i.e., an untrained dictionary) is rejected
+    let raw_content = vec![0u8; 1024];
+    assert!(ZstdDictionary::new(&raw_content, DEFAULT_ZSTD_COMPRESSION_LEVEL).is_err());
+}
+
 /// Ensures that the given object can be compressed and decompressed successfully
 /// when BCS encoded.
 fn test_compress_and_decompress<T: Debug + DeserializeOwned + PartialEq + Serialize>(object: T) {
@@ -95,6 +233,32 @@ fn test_compress_and_decompress<T: Debug + DeserializeOwned + PartialEq + Serial
     assert_eq!(object, decoded_object);
 }
 
+/// Ensures that the given object can be compressed and decompressed successfully
+/// using zstd when BCS encoded.
+fn test_zstd_compress_and_decompress<T: Debug + DeserializeOwned + PartialEq + Serialize>(
+    object: T,
+    client: CompressionClient,
+) {
+    let bcs_encoded_bytes = bcs::to_bytes(&object).unwrap();
+    let compressed_bytes =
+        crate::compress_with_zstd(bcs_encoded_bytes, client, MAX_COMPRESSION_SIZE).unwrap();
+    let decompressed_bytes =
+        crate::decompress_with_zstd(&compressed_bytes, client, MAX_COMPRESSION_SIZE).unwrap();
+    let decoded_object = bcs::from_bytes::<T>(&decompressed_bytes).unwrap();
+
+    assert_eq!(object, decoded_object);
+}
+
+/// Creates BCS encoded transactions (for dictionary training)
+fn create_transaction_samples(
+    start_sequence_number: u64,
+    end_sequence_number: u64,
+) -> Vec<Vec<u8>> {
+    (start_sequence_number..end_sequence_number)
+        .map(|sequence_number| bcs::to_bytes(&create_test_transaction(sequence_number)).unwrap())
+        .collect()
+}
+
 /// Creates a test epoch change proof
 fn create_epoch_ending_ledger_infos(
     start_epoch: u64,
@@ -0,0 +1,110 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{bail, Result};
+use aptos_config::config::{
+    BlockTransactionFilterConfig, NodeConfig, RocksdbConfigs, StorageDirPaths,
+    BUFFERED_STATE_TARGET_ITEMS, DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+    NO_OP_STORAGE_PRUNER_CONFIG,
+};
+use aptos_consensus::util::replay_tool::ConsensusReplayer;
+use aptos_crypto::HashValue;
+use aptos_db::AptosDB;
+use aptos_storage_interface::{DbReader, DbReaderWriter};
+use clap::Parser;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+#[derive(Parser)]
+#[clap(
+    about = "Re-execute the blocks in a validator's consensus db on top of a copy of its AptosDB, \
+    and report the transactions whose outputs differ from the committed ones."
+)]
+pub struct Command {
+    /// Directory holding the consensus db and the quorum store db of the validator.
+    #[clap(long, value_parser)]
+    pub consensus_db_dir: PathBuf,
+
+    /// A copy of the validator's AptosDB, at the version of its last committed block.
+    /// It's opened in write mode, never point this at the db of a running node.
+    #[clap(long, value_parser)]
+    pub target_db_dir: PathBuf,
+
+    /// AptosDB holding the committed (or expected, e.g. taken from a healthy node) transactions
+    /// to compare the replayed outputs against.
+    #[clap(long, value_parser)]
+    pub expected_db_dir: Option<PathBuf>,
+
+    /// Stop after replaying this block. If not set, replays all blocks descending from the last
+    /// committed one, until the end of the epoch.
+    #[clap(long)]
+    pub block_id: Option<HashValue>,
+
+    /// Config of the validator, to apply the same execution transaction filter to the replayed
+    /// blocks. If not set, no transaction is filtered.
+    #[clap(long, value_parser)]
+    pub node_config: Option<PathBuf>,
+
+    #[clap(long)]
+    pub enable_storage_sharding: bool,
+}
+
+impl Command {
+    pub async fn run(self) -> Result<()> {
+        let target_db = self.open_db(&self.target_db_dir, false)?;
+        let expected_db = self
+            .expected_db_dir
+            .as_ref()
+            .map(|dir| self.open_db(dir, true))
+            .transpose()?
+            .map(|db| Arc::new(db) as Arc<dyn DbReader>);
+
+        let txn_filter_config = match &self.node_config {
+            Some(path) => {
+                NodeConfig::load_from_path(path)?
+                    .transaction_filters
+                    .execution_filter
+            },
+            None => BlockTransactionFilterConfig::default(),
+        };
+
+        let replayer = ConsensusReplayer::new(
+            &self.consensus_db_dir,
+            DbReaderWriter::new(target_db),
+            expected_db,
+            txn_filter_config,
+        )?;
+        let reports = replayer.replay(self.block_id)?;
+        for report in &reports {
+            println!("{}", report);
+        }
+
+        let num_divergent = reports.iter().filter(|r| r.is_divergent()).count();
+        if num_divergent > 0 {
+            bail!(
+                "{} out of {} replayed blocks diverged.",
+                num_divergent,
+                reports.len()
+            );
+        }
+        println!("Replayed {} blocks, no divergence found.", reports.len());
+        Ok(())
+    }
+
+    fn open_db(&self, db_dir: &Path, readonly: bool) -> Result<AptosDB> {
+        let mut rocksdb_configs = RocksdbConfigs::default();
+        rocksdb_configs.enable_storage_sharding = self.enable_storage_sharding;
+        Ok(AptosDB::open(
+            StorageDirPaths::from_path(db_dir),
+            readonly,
+            NO_OP_STORAGE_PRUNER_CONFIG,
+            rocksdb_configs,
+            false, /* enable_indexer */
+            BUFFERED_STATE_TARGET_ITEMS,
+            DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+            None, /* internal_indexer_db */
+        )?)
+    }
+}
@@ -4,6 +4,8 @@
 use anyhow::Result;
 use clap::Parser;
 
+mod consensus_replay;
+
 #[derive(Parser)]
 pub enum Cmd {
     #[clap(subcommand)]
@@ -13,6 +15,8 @@ pub enum Cmd {
 
     DumpPendingTxns(aptos_consensus::util::db_tool::Command),
 
+    ReplayConsensusDb(consensus_replay::Command),
+
     #[clap(subcommand)]
     Move(aptos_move_debugger::common::Command),
 }
@@ -24,6 +28,7 @@ impl Cmd {
             Cmd::Decode(cmd) => cmd.run().await,
             Cmd::DumpPendingTxns(cmd) => cmd.run().await,
             Cmd::Move(cmd) => cmd.run().await,
+            Cmd::ReplayConsensusDb(cmd) => cmd.run().await,
         }
     }
 }
@@ -7,7 +7,8 @@ use aptos_config::{
     network_id::{NetworkId, PeerNetworkId},
 };
 use aptos_data_client::{
-    client::AptosDataClient, interface::AptosDataClientInterface, peer_states,
+    client::AptosDataClient, interface::AptosDataClientInterface, peer_reputation::PeerReputation,
+    peer_states,
 };
 use aptos_network::application::storage::PeersAndMetadata;
 use hyper::{Body, StatusCode};
@@ -75,6 +76,10 @@ fn get_peer_information(
     );
     peer_information_output.push("\n".into());
 
+    // Display the active peer bans
+    display_peer_bans(&mut peer_information_output, peers_and_metadata.deref());
+    peer_information_output.push("\n".into());
+
     // Display basic peer metadata for each peer
     display_peer_monitoring_metadata(
         &mut peer_information_output,
@@ -84,7 +89,15 @@ fn get_peer_information(
     peer_information_output.push("\n".into());
 
     // Display state sync metadata for each peer
-    display_state_sync_metadata(&mut peer_information_output, &all_peers, aptos_data_client);
+    display_state_sync_metadata(
+        &mut peer_information_output,
+        &all_peers,
+        aptos_data_client.clone(),
+    );
+    peer_information_output.push("\n".into());
+
+    // Display the state sync reputations for disconnected peers
+    display_disconnected_peer_reputations(&mut peer_information_output, aptos_data_client);
     peer_information_output.push("\n".into());
 
     // Display detailed peer metadata for each peer
@@ -241,13 +254,15 @@ fn display_state_sync_metadata(
             let peer = *peer_state_entry.key();
             let peer_bucket_id = peer_states::get_bucket_id_for_peer(peer);
             let peer_score = peer_state_entry.get_score();
+            let peer_ignored = peer_state_entry.is_ignored();
             let peer_storage_summary = peer_state_entry.get_storage_summary();
 
             // Display the peer states
             peer_information_output.push(format!(
-                "\t- Peer: {}, score: {}, bucket ID: {}",
-                peer, peer_score, peer_bucket_id
+                "\t- Peer: {}, score: {}, ignored: {}, bucket ID: {}",
+                peer, peer_score, peer_ignored, peer_bucket_id
             ));
+            display_peer_reputation(peer_information_output, peer_state_entry.get_reputation());
             peer_information_output.push(format!(
                 "\t\t- Advertised storage summary: {:?}",
                 peer_storage_summary
@@ -270,6 +285,64 @@ fn display_state_sync_metadata(
     }
 }
 
+/// Displays the persisted reputations of the peers that are not currently connected
+fn display_disconnected_peer_reputations(
+    peer_information_output: &mut Vec<String>,
+    aptos_data_client: AptosDataClient,
+) {
+    peer_information_output
+        .push("State sync reputations for disconnected peers (persisted across restarts):".into());
+
+    // Sort the peer reputations before displaying them
+    let disconnected_peer_reputations = aptos_data_client
+        .get_peer_states()
+        .get_disconnected_peer_reputations();
+    let mut sorted_peer_reputations = BTreeMap::new();
+    for peer_reputation in disconnected_peer_reputations.iter() {
+        sorted_peer_reputations.insert(*peer_reputation.key(), peer_reputation.value().clone());
+    }
+
+    // Display the peer reputations
+    for (peer, peer_reputation) in sorted_peer_reputations {
+        peer_information_output.push(format!(
+            "\t- Peer: {}, score: {}",
+            peer,
+            peer_reputation.get_score()
+        ));
+        display_peer_reputation(peer_information_output, &peer_reputation);
+    }
+}
+
+/// Displays the reputation details of a single peer (i.e., why it may be ignored)
+fn display_peer_reputation(
+    peer_information_output: &mut Vec<String>,
+    peer_reputation: &PeerReputation,
+) {
+    peer_information_output.push(format!(
+        "\t\t- Average response latency: {:?}, last reputation update (usecs): {}",
+        peer_reputation.get_average_response_latency(),
+        peer_reputation.get_last_updated_usecs()
+    ));
+    peer_information_output.push(format!(
+        "\t\t- Misbehaviour counts: {:?}, recent misbehaviours: {:?}",
+        peer_reputation.get_misbehaviour_counts(),
+        peer_reputation.get_misbehaviour_records()
+    ));
+}
+
+/// Displays the active peer bans (i.e., banned peer IDs and IP ranges)
+fn display_peer_bans(
+    peer_information_output: &mut Vec<String>,
+    peers_and_metadata: &PeersAndMetadata,
+) {
+    peer_information_output.push("Active peer bans (persisted across restarts):".into());
+
+    // Fetch and display the active bans (these are already sorted by target)
+    for peer_ban in peers_and_metadata.get_peer_ban_list().get_active_bans() {
+        peer_information_output.push(format!("\t- Ban: {}", peer_ban));
+    }
+}
+
 /// Displays the entire set of trusted peers
 fn display_trusted_peers(
     peer_information_output: &mut Vec<String>,
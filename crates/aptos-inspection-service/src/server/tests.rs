@@ -178,6 +178,7 @@ async fn test_inspect_peer_information() {
     assert!(response_body_string.contains("Registered networks"));
     assert!(response_body_string.contains("Peers and network IDs"));
     assert!(response_body_string.contains("State sync metadata"));
+    assert!(response_body_string.contains("Active peer bans"));
 }
 
 rusty_fork_test! {
@@ -264,6 +265,7 @@ async fn send_get_request_to_path(config: &NodeConfig, endpoint: &str) -> Respon
         Arc::new(MockDatabaseReader {}),
         StorageServiceClient::new(network_client),
         None,
+        None,
     );
 
     // Serve the request
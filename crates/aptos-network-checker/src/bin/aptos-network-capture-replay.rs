@@ -0,0 +1,36 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{Context, Result};
+use aptos_crypto::{x25519, ValidCryptoMaterialStringExt};
+use aptos_network_checker::replay_capture::{replay_capture, ReplayCaptureArgs};
+use clap::Parser;
+
+/// Replays the inbound messages captured by a node (see the message capture
+/// config) against a live node, and compares the RPC responses of the node
+/// with the captured responses.
+#[derive(Parser)]
+pub struct Args {
+    #[clap(flatten)]
+    replay_capture_args: ReplayCaptureArgs,
+
+    /// The (hex encoded) x25519 private key used to connect to the node.
+    /// If not provided, a dummy key is used.
+    #[clap(long)]
+    private_key: Option<String>,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+    let private_key = args
+        .private_key
+        .as_deref()
+        .map(x25519::PrivateKey::from_encoded_string)
+        .transpose()
+        .context("Invalid x25519 private key")?;
+
+    let summary = replay_capture(&args.replay_capture_args, private_key).await?;
+    println!("{}", summary);
+    Ok(())
+}
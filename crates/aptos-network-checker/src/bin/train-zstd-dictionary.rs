@@ -0,0 +1,135 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{bail, Context, Result};
+use aptos_compression::{
+    dictionary::{train_dictionary, ZstdDictionary, DEFAULT_MAX_DICTIONARY_SIZE},
+    DEFAULT_ZSTD_COMPRESSION_LEVEL,
+};
+use aptos_config::network_id::NetworkId;
+use aptos_network::{
+    capture::{get_network_capture_dir, read_captured_messages},
+    ProtocolId,
+};
+use clap::Parser;
+use std::{fs, path::PathBuf};
+use zstd::bulk::Compressor;
+
+/// Trains a zstd dictionary from the messages captured by a node (see the
+/// message capture config). Each captured message is decompressed (if its
+/// protocol is compressed) and used as a single sample, e.g., a BCS encoded
+/// consensus message.
+#[derive(Parser)]
+pub struct Args {
+    /// The capture directories of the nodes that captured the messages
+    /// (i.e., the `capture_dir` in the message capture config)
+    #[clap(long, required = true)]
+    capture_dir: Vec<PathBuf>,
+
+    /// The network on which the messages were captured
+    #[clap(long, default_value_t = NetworkId::Validator)]
+    network_id: NetworkId,
+
+    /// The protocols of the messages to train on (e.g., ConsensusDirectSendCompressed).
+    /// If not provided, the messages of all protocols are used.
+    #[clap(long)]
+    protocol: Vec<String>,
+
+    /// The file to write the trained dictionary to
+    #[clap(long)]
+    output_file: PathBuf,
+
+    /// The maximum size (in bytes) of the trained dictionary
+    #[clap(long, default_value_t = DEFAULT_MAX_DICTIONARY_SIZE)]
+    max_dictionary_size: usize,
+
+    /// The compression level used to evaluate the trained dictionary
+    #[clap(long, default_value_t = DEFAULT_ZSTD_COMPRESSION_LEVEL)]
+    compression_level: i32,
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    let protocol_ids = args
+        .protocol
+        .iter()
+        .map(|protocol_name| parse_protocol_id(protocol_name))
+        .collect::<Result<Vec<_>>>()?;
+
+    // Read the captured messages and decompress them into samples
+    let mut samples = vec![];
+    let mut num_skipped_messages = 0;
+    for capture_dir in &args.capture_dir {
+        let network_capture_dir = get_network_capture_dir(capture_dir, args.network_id);
+        let captured_messages =
+            read_captured_messages(&network_capture_dir).with_context(|| {
+                format!(
+                    "Failed to read the captured messages in {:?}",
+                    network_capture_dir
+                )
+            })?;
+        for captured_message in captured_messages {
+            if !protocol_ids.is_empty() && !protocol_ids.contains(&captured_message.protocol_id) {
+                continue;
+            }
+
+            // Messages compressed with an unknown dictionary can't be decompressed
+            match captured_message.decompress() {
+                Ok(sample) => samples.push(sample),
+                Err(_) => num_skipped_messages += 1,
+            }
+        }
+    }
+    if samples.is_empty() {
+        bail!(
+            "No captured messages found in: {:?} (network: {}, skipped: {})",
+            args.capture_dir,
+            args.network_id,
+            num_skipped_messages
+        );
+    }
+
+    // Train the dictionary and write it to the output file
+    let dictionary_bytes = train_dictionary(&samples, args.max_dictionary_size)?;
+    fs::write(&args.output_file, &dictionary_bytes)?;
+    let dictionary = ZstdDictionary::new(&dictionary_bytes, args.compression_level)?;
+    println!(
+        "Trained dictionary {} ({} bytes) using {} samples ({} messages skipped). Written to: {:?}",
+        dictionary.get_dictionary_id(),
+        dictionary_bytes.len(),
+        samples.len(),
+        num_skipped_messages,
+        args.output_file
+    );
+
+    // Evaluate the compression ratio with and without the dictionary
+    let raw_bytes: usize = samples.iter().map(|sample| sample.len()).sum();
+    let mut compressed_bytes = 0;
+    let mut dictionary_compressed_bytes = 0;
+    let mut compressor = Compressor::new(args.compression_level)?;
+    let mut dictionary_compressor =
+        Compressor::with_dictionary(args.compression_level, &dictionary_bytes)?;
+    for sample in &samples {
+        compressed_bytes += compressor.compress(sample)?.len();
+        dictionary_compressed_bytes += dictionary_compressor.compress(sample)?.len();
+    }
+    println!(
+        "Compression ratio without dictionary: {:.2}, with dictionary: {:.2} (raw bytes: {})",
+        raw_bytes as f64 / compressed_bytes as f64,
+        raw_bytes as f64 / dictionary_compressed_bytes as f64,
+        raw_bytes
+    );
+
+    Ok(())
+}
+
+/// Parses the given protocol name (e.g., "ConsensusDirectSendCompressed")
+fn parse_protocol_id(protocol_name: &str) -> Result<ProtocolId> {
+    match ProtocolId::all()
+        .iter()
+        .find(|protocol_id| protocol_id.as_str() == protocol_name)
+    {
+        Some(protocol_id) => Ok(*protocol_id),
+        None => bail!("Unknown protocol: {}", protocol_name),
+    }
+}
@@ -157,7 +157,7 @@ async fn check_endpoint_no_handshake(address: NetworkAddress) -> Result<String>
 }
 
 /// Builds a listener free noise connector
-fn build_upgrade_context(
+pub(crate) fn build_upgrade_context(
     chain_id: ChainId,
     network_id: NetworkId,
     peer_id: PeerId,
@@ -189,7 +189,9 @@ fn build_upgrade_context(
 
 /// Derive the peer id that we're using. This is a convenience to only have to
 /// provide a private key.
-fn private_key_to_public_info(private_key: &x25519::PrivateKey) -> (PeerId, x25519::PublicKey) {
+pub(crate) fn private_key_to_public_info(
+    private_key: &x25519::PrivateKey,
+) -> (PeerId, x25519::PublicKey) {
     let public_key = private_key.public_key();
     let peer_id = account_address::from_identity_public_key(public_key);
     (peer_id, public_key)
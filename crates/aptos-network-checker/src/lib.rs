@@ -3,5 +3,6 @@
 
 pub mod args;
 pub mod check_endpoint;
+pub mod replay_capture;
 
 pub use check_endpoint::check_endpoint;
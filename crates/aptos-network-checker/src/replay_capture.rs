@@ -0,0 +1,112 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    args::{HandshakeArgs, NodeAddressArgs},
+    check_endpoint::{build_upgrade_context, private_key_to_public_info},
+};
+use anyhow::{Context, Result};
+use aptos_config::config::{MAX_FRAME_SIZE, MAX_MESSAGE_SIZE};
+use aptos_crypto::x25519::{self, PRIVATE_KEY_SIZE};
+use aptos_network::{
+    capture::{
+        get_network_capture_dir, read_captured_messages,
+        replay::{replay_captured_messages, ReplayConfig, ReplaySummary},
+    },
+    transport::{resolve_and_connect, upgrade_outbound, TCPBufferCfg, TcpSocket},
+};
+use aptos_types::account_address;
+use clap::Parser;
+use std::path::PathBuf;
+use tokio::time::Duration;
+
+#[derive(Clone, Debug, Parser)]
+pub struct ReplayCaptureArgs {
+    #[clap(flatten)]
+    pub node_address_args: NodeAddressArgs,
+
+    #[clap(flatten)]
+    pub handshake_args: HandshakeArgs,
+
+    /// The capture directory of the node that captured the messages
+    /// (i.e., the `capture_dir` in the message capture config)
+    #[clap(long)]
+    pub capture_dir: PathBuf,
+
+    /// Reproduce the captured delays between messages (instead of sending
+    /// the messages back-to-back)
+    #[clap(long)]
+    pub preserve_timing: bool,
+
+    /// The number of seconds to wait for the response to each RPC request
+    #[clap(long, default_value_t = 10)]
+    pub rpc_timeout_seconds: u64,
+}
+
+/// Connects to the node and replays the inbound messages captured on the
+/// network (see `HandshakeArgs::network_id`), in capture order.
+pub async fn replay_capture(
+    args: &ReplayCaptureArgs,
+    private_key: Option<x25519::PrivateKey>,
+) -> Result<ReplaySummary> {
+    // Read the captured messages
+    let network_id = args.handshake_args.network_id;
+    let network_capture_dir = get_network_capture_dir(&args.capture_dir, network_id);
+    let captured_messages = read_captured_messages(&network_capture_dir).with_context(|| {
+        format!(
+            "Failed to read the captured messages in {:?}",
+            network_capture_dir
+        )
+    })?;
+
+    // Connect to the node
+    let private_key = private_key.unwrap_or_else(|| {
+        let dummy = [0; PRIVATE_KEY_SIZE];
+        x25519::PrivateKey::from(dummy)
+    });
+    let (peer_id, _) = private_key_to_public_info(&private_key);
+    let address = args.node_address_args.address.clone();
+    let remote_pubkey = address.find_noise_proto().with_context(|| {
+        format!(
+            "Failed to find noise protocol in {}, /noise-ik/<pubkey> missing",
+            address
+        )
+    })?;
+    let upgrade_context = build_upgrade_context(
+        args.node_address_args.chain_id,
+        network_id,
+        peer_id,
+        private_key,
+    );
+    let fut_socket = async {
+        resolve_and_connect(address.clone(), TCPBufferCfg::new())
+            .await
+            .map(TcpSocket::new)
+    };
+    let remote_peer_id = account_address::from_identity_public_key(remote_pubkey);
+    let connection = tokio::time::timeout(
+        Duration::from_secs(args.handshake_args.timeout_seconds),
+        upgrade_outbound(
+            upgrade_context,
+            fut_socket,
+            address.clone(),
+            remote_peer_id,
+            remote_pubkey,
+        ),
+    )
+    .await
+    .with_context(|| format!("Timed out while connecting to {}", address))?
+    .with_context(|| format!("Failed to connect to {}", address))?;
+
+    // Replay the captured messages
+    let replay_config = ReplayConfig {
+        max_frame_size: MAX_FRAME_SIZE,
+        max_message_size: MAX_MESSAGE_SIZE,
+        preserve_timing: args.preserve_timing,
+        rpc_timeout: Duration::from_secs(args.rpc_timeout_seconds),
+    };
+    let summary = replay_captured_messages(connection.socket, &captured_messages, &replay_config)
+        .await
+        .with_context(|| format!("Failed to replay the captured messages to {}", address))?;
+    Ok(summary)
+}
@@ -1296,6 +1296,19 @@ impl Client {
         .await
     }
 
+    pub async fn get_account_modules_at_version_bcs(
+        &self,
+        address: AccountAddress,
+        version: u64,
+    ) -> AptosResult<Response<BTreeMap<MoveModuleId, Vec<u8>>>> {
+        self.paginate_with_cursor_bcs(
+            &format!("accounts/{}/modules", address.to_hex()),
+            MODULES_PER_CALL_PAGINATION,
+            Some(version),
+        )
+        .await
+    }
+
     pub async fn get_account_module(
         &self,
         address: AccountAddress,
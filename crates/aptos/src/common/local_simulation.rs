@@ -1,7 +1,7 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::common::types::{CliError, CliTypedResult};
+use crate::common::types::{CliError, CliTypedResult, MoveDebugOptions};
 use aptos_crypto::HashValue;
 use aptos_gas_profiling::FrameName;
 use aptos_move_debugger::aptos_debugger::AptosDebugger;
@@ -146,3 +146,115 @@ pub fn profile_transaction_using_debugger(
 
     Ok((vm_status, vm_output))
 }
+
+#[cfg(feature = "move-debugger")]
+pub use self::debugging::debug_transaction_using_debugger;
+
+#[cfg(feature = "move-debugger")]
+mod debugging {
+    use super::*;
+    use aptos_framework::{BuildOptions, BuiltPackage};
+    use aptos_resource_viewer::AptosValueAnnotator;
+    use aptos_types::{
+        account_address::AccountAddress,
+        state_store::{state_key::StateKey, StateView},
+        transaction::TransactionStatus,
+    };
+    use move_core_types::language_storage::StructTag;
+    use move_dap::{DebugSession, SourceIndex};
+    use std::collections::BTreeMap;
+
+    pub fn debug_transaction_using_debugger(
+        debugger: &AptosDebugger,
+        version: u64,
+        transaction: SignedTransaction,
+        hash: HashValue,
+        debug_options: &MoveDebugOptions,
+    ) -> CliTypedResult<(VMStatus, VMOutput)> {
+        let port = debug_options
+            .debug_port
+            .ok_or_else(|| CliError::CommandArgumentError("Missing `--debug-port`".to_string()))?;
+        let index = match &debug_options.debug_package_dir {
+            Some(package_dir) => {
+                let package = BuiltPackage::build(package_dir.clone(), BuildOptions::default())
+                    .map_err(|err| CliError::MoveCompilationError(format!("{:#}", err)))?;
+                SourceIndex::from_package(&package.package)?
+            },
+            None => SourceIndex::default(),
+        };
+
+        // Resources which the transaction has not loaded yet are read from the state it executes on.
+        let state_view = debugger.state_view_at_version(version);
+        let session = DebugSession::start(
+            port,
+            index,
+            Some(Box::new(
+                move |addr: &AccountAddress, struct_tag: &StructTag| {
+                    read_resource(&state_view, addr, struct_tag)
+                },
+            )),
+        )
+        .map_err(|err| {
+            CliError::UnexpectedError(format!("Failed to start the debug session: {:#}", err))
+        })?;
+
+        let (vm_status, vm_output) =
+            match run_transaction_using_debugger(debugger, version, transaction, hash) {
+                Ok(result) => result,
+                Err(err) => {
+                    session.finish(1);
+                    return Err(err);
+                },
+            };
+
+        let success = matches!(
+            vm_output.status(),
+            TransactionStatus::Keep(exec_status) if exec_status.is_success()
+        );
+        session.finish(if success { 0 } else { 1 });
+
+        Ok((vm_status, vm_output))
+    }
+
+    /// Reads the resource at `addr` from storage, as a human-readable string.
+    fn read_resource<S: StateView>(
+        state_view: &S,
+        addr: &AccountAddress,
+        struct_tag: &StructTag,
+    ) -> anyhow::Result<Option<String>> {
+        let annotator = AptosValueAnnotator::new(state_view);
+        let bytes = match annotator.view_resource_group_member(struct_tag) {
+            Some(group_tag) => {
+                let group_key = StateKey::resource_group(addr, &group_tag);
+                match state_view.get_state_value_bytes(&group_key)? {
+                    Some(group) => {
+                        bcs::from_bytes::<BTreeMap<StructTag, Vec<u8>>>(&group)?.remove(struct_tag)
+                    },
+                    None => None,
+                }
+            },
+            None => {
+                let key = StateKey::resource(addr, struct_tag)?;
+                state_view
+                    .get_state_value_bytes(&key)?
+                    .map(|bytes| bytes.to_vec())
+            },
+        };
+        bytes
+            .map(|bytes| Ok(annotator.view_resource(struct_tag, &bytes)?.to_string()))
+            .transpose()
+    }
+}
+
+#[cfg(not(feature = "move-debugger"))]
+pub fn debug_transaction_using_debugger(
+    _debugger: &AptosDebugger,
+    _version: u64,
+    _transaction: SignedTransaction,
+    _hash: HashValue,
+    _debug_options: &MoveDebugOptions,
+) -> CliTypedResult<(VMStatus, VMOutput)> {
+    Err(CliError::UnexpectedError(
+        "The CLI was built without the `move-debugger` feature".to_string(),
+    ))
+}
@@ -1763,6 +1763,40 @@ pub enum AccountType {
     HardwareWallet,
 }
 
+/// Options for stepping through the Move code executed by a transaction with a debugger.
+/// The options are only available if the CLI is built with the `move-debugger` feature.
+#[derive(Debug, Default, Parser)]
+pub struct MoveDebugOptions {
+    /// If this option is set, execute the transaction locally, and wait for a debugger to attach
+    /// on this port over the Debug Adapter Protocol before the execution starts.
+    #[cfg(feature = "move-debugger")]
+    #[clap(long)]
+    pub(crate) debug_port: Option<u16>,
+
+    /// Path to a package with the sources of the modules to debug, for setting breakpoints
+    /// and mapping the execution to source lines.
+    ///
+    /// The package is compiled with the named addresses of its `Move.toml`, and must compile to
+    /// the same bytecode as the modules on chain.
+    #[cfg(feature = "move-debugger")]
+    #[clap(long, value_parser, requires = "debug_port")]
+    pub(crate) debug_package_dir: Option<PathBuf>,
+}
+
+impl MoveDebugOptions {
+    /// Returns true if the execution should be stepped through with a debugger
+    #[cfg(feature = "move-debugger")]
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.debug_port.is_some()
+    }
+
+    /// Returns true if the execution should be stepped through with a debugger
+    #[cfg(not(feature = "move-debugger"))]
+    pub(crate) fn is_enabled(&self) -> bool {
+        false
+    }
+}
+
 /// Common options for interacting with an account for a validator
 #[derive(Debug, Default, Parser)]
 pub struct TransactionOptions {
@@ -1799,6 +1833,9 @@ pub struct TransactionOptions {
     #[clap(long)]
     pub(crate) profile_gas: bool,
 
+    #[clap(flatten)]
+    pub(crate) debug_options: MoveDebugOptions,
+
     /// Replay protection mechanism to use when generating the transaction.
     ///
     /// When "nonce" is chosen, the transaction will be an orderless transaction and contains a replay protection nonce.
@@ -2161,6 +2198,26 @@ impl TransactionOptions {
             .await
     }
 
+    /// Simulates the transaction locally with a debugger attached.
+    pub async fn debug_locally(
+        &self,
+        payload: TransactionPayload,
+    ) -> CliTypedResult<TransactionSummary> {
+        println!();
+        println!("Simulating transaction locally using the debugger...");
+
+        self.simulate_using_debugger(payload, |debugger, version, transaction, hash| {
+            local_simulation::debug_transaction_using_debugger(
+                debugger,
+                version,
+                transaction,
+                hash,
+                &self.debug_options,
+            )
+        })
+        .await
+    }
+
     /// Benchmarks the transaction payload locally.
     /// The transaction is executed multiple times, and the median value is calculated to improve
     /// the accuracy of the measurement results.
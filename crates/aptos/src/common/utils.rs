@@ -514,6 +514,8 @@ pub async fn profile_or_submit(
         txn_options_ref.profile_gas(payload).await
     } else if txn_options_ref.benchmark {
         txn_options_ref.benchmark_locally(payload).await
+    } else if txn_options_ref.debug_options.is_enabled() {
+        txn_options_ref.debug_locally(payload).await
     } else if txn_options_ref.local {
         txn_options_ref.simulate_locally(payload).await
     } else {
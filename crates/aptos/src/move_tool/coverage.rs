@@ -7,10 +7,11 @@ use crate::{
 };
 use aptos_framework::extended_checks;
 use async_trait::async_trait;
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use legacy_move_compiler::compiled_unit::{CompiledUnit, NamedCompiledModule};
 use move_coverage::{
     coverage_map::CoverageMap,
+    export::{write_cobertura, write_lcov, ModuleLineCoverage},
     format_csv_summary, format_human_summary,
     source_coverage::{ColorChoice, SourceCoverageBuilder, TextIndicator},
     summary::summarize_inst_cov,
@@ -18,6 +19,12 @@ use move_coverage::{
 use move_disassembler::disassembler::Disassembler;
 use move_model::metadata::{CompilerVersion, LanguageVersion};
 use move_package::{compilation::compiled_package::CompiledPackage, BuildConfig, CompilerConfig};
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 /// Display a coverage summary for all modules in a package
 ///
@@ -166,6 +173,92 @@ impl CliCommand<()> for BytecodeCoverage {
     }
 }
 
+/// Format of exported coverage
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum CoverageFormat {
+    /// LCOV tracefile, as consumed by e.g. `genhtml`
+    Lcov,
+    /// Cobertura XML
+    Cobertura,
+}
+
+/// Export line, function and branch coverage of a package
+///
+/// The coverage is mapped back to the source files, and written in the LCOV or Cobertura XML
+/// format, so that it can be shown by standard coverage tools and CI dashboards.
+#[derive(Debug, Parser)]
+pub struct ExportCoverage {
+    /// Format of the exported coverage
+    #[clap(long, value_enum, default_value_t = CoverageFormat::Lcov)]
+    pub format: CoverageFormat,
+    /// File to write the coverage to
+    #[clap(long, value_parser)]
+    pub output_file: PathBuf,
+    /// A filter string to determine which modules to export coverage for
+    #[clap(long, short)]
+    pub filter: Option<String>,
+    #[clap(flatten)]
+    pub move_options: MovePackageOptions,
+}
+
+#[async_trait]
+impl CliCommand<()> for ExportCoverage {
+    fn command_name(&self) -> &'static str {
+        "ExportCoverage"
+    }
+
+    async fn execute(self) -> CliTypedResult<()> {
+        let package_path = self.move_options.get_package_path()?;
+        let (coverage_map, package) = compile_coverage(self.move_options)?;
+        let coverage_map = coverage_map.to_unified_exec_map();
+        let modules = package
+            .root_modules()
+            .filter(|unit| match &self.filter {
+                Some(filter_str) => unit.unit.name().as_str().contains(filter_str.as_str()),
+                None => true,
+            })
+            .filter_map(|unit| match &unit.unit {
+                CompiledUnit::Module(NamedCompiledModule {
+                    module, source_map, ..
+                }) => Some(ModuleLineCoverage::new(
+                    module,
+                    source_map,
+                    &unit.source_path,
+                    &coverage_map,
+                )),
+                _ => None,
+            })
+            .collect::<anyhow::Result<Vec<_>>>()
+            .map_err(|err| {
+                CliError::UnexpectedError(format!("Failed to compute coverage: {:#}", err))
+            })?;
+
+        let file = File::create(&self.output_file)
+            .map_err(|err| CliError::IO(self.output_file.display().to_string(), err))?;
+        let mut writer = BufWriter::new(file);
+        let result = match self.format {
+            CoverageFormat::Lcov => write_lcov(&modules, &mut writer),
+            CoverageFormat::Cobertura => {
+                let timestamp = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|duration| duration.as_secs())
+                    .unwrap_or_default();
+                write_cobertura(
+                    &modules,
+                    package.compiled_package_info.package_name.as_str(),
+                    &package_path,
+                    timestamp,
+                    &mut writer,
+                )
+            },
+        };
+        // Flush explicitly, as errors are ignored when the writer is dropped
+        result
+            .and_then(|_| writer.flush())
+            .map_err(|err| CliError::IO(self.output_file.display().to_string(), err))
+    }
+}
+
 fn compile_coverage(
     move_options: MovePackageOptions,
 ) -> CliTypedResult<(CoverageMap, CompiledPackage)> {
@@ -215,6 +308,7 @@ pub enum CoveragePackage {
     Summary(SummaryCoverage),
     Source(SourceCoverage),
     Bytecode(BytecodeCoverage),
+    Export(ExportCoverage),
 }
 
 impl CoveragePackage {
@@ -223,6 +317,159 @@ impl CoveragePackage {
             Self::Summary(tool) => tool.execute_serialized_success().await,
             Self::Source(tool) => tool.execute_serialized_success().await,
             Self::Bytecode(tool) => tool.execute_serialized_success().await,
+            Self::Export(tool) => tool.execute_serialized_success().await,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use move_binary_format::{
+        access::ModuleAccess,
+        file_format::{Bytecode, CodeOffset},
+    };
+    use move_core_types::account_address::AccountAddress;
+    use move_coverage::{coverage_map::ExecCoverageMap, export::BranchHits};
+    use std::{collections::BTreeMap, fs};
+
+    const SOURCE: &str = "module 0x42::m {
+    fun f(x: u64): u64 {
+        if (x > 10) {
+            x - 10
+        } else {
+            x + 1
+        }
+    }
+
+    fun g(x: u64, y: u64): u64 {
+        if (x > 10 && y > 10) 1 else 2
+    }
+}
+";
+
+    /// Runs `code` once for each of the given runs, where a run is the list of outcomes taken by
+    /// the conditional branches it reaches (in order). Returns the execution count of each
+    /// offset, and the number of times each branch outcome was taken.
+    fn simulate(
+        code: &[Bytecode],
+        runs: &[&[usize]],
+    ) -> (
+        BTreeMap<CodeOffset, u64>,
+        BTreeMap<(CodeOffset, usize), u64>,
+    ) {
+        let mut counts = BTreeMap::new();
+        let mut taken = BTreeMap::new();
+        for run in runs {
+            let mut outcomes = run.iter();
+            let mut offset = 0;
+            loop {
+                *counts.entry(offset).or_insert(0) += 1;
+                let successors = Bytecode::get_successors(offset, code);
+                offset = match successors.len() {
+                    0 => break,
+                    1 => successors[0],
+                    _ => {
+                        let outcome = outcomes.next().copied().unwrap_or(0);
+                        *taken.entry((offset, outcome)).or_insert(0) += 1;
+                        successors[outcome]
+                    },
+                };
+            }
+        }
+        (counts, taken)
+    }
+
+    #[test]
+    fn test_module_line_coverage() {
+        // Compile a package with a single module
+        let package_dir = tempfile::tempdir().unwrap();
+        fs::write(
+            package_dir.path().join("Move.toml"),
+            "[package]\nname = \"CoverageTest\"\nversion = \"0.0.0\"\n",
+        )
+        .unwrap();
+        fs::create_dir(package_dir.path().join("sources")).unwrap();
+        fs::write(package_dir.path().join("sources/m.move"), SOURCE).unwrap();
+        let config = BuildConfig {
+            install_dir: Some(package_dir.path().join("build")),
+            compiler_config: CompilerConfig {
+                compiler_version: Some(CompilerVersion::latest_stable()),
+                language_version: Some(LanguageVersion::latest_stable()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let package = config
+            .compile_package(package_dir.path(), &mut Vec::new())
+            .unwrap();
+        let unit = package.root_modules().next().unwrap();
+        let CompiledUnit::Module(NamedCompiledModule {
+            module, source_map, ..
+        }) = &unit.unit
+        else {
+            panic!("expected a module");
+        };
+
+        // Simulate runs of both functions, taking each combination of branch outcomes
+        let runs: &[&[usize]] = &[&[0, 0], &[0, 1], &[1, 0], &[1, 1], &[0, 0]];
+        let mut coverage_map = ExecCoverageMap::new(String::new());
+        let mut taken_by_function = BTreeMap::new();
+        for function_def in module.function_defs() {
+            let name = module.identifier_at(module.function_handle_at(function_def.function).name);
+            let (counts, taken) = simulate(&function_def.code.as_ref().unwrap().code, runs);
+            for (offset, count) in counts {
+                coverage_map.insert_multi(
+                    AccountAddress::from_hex_literal("0x42").unwrap(),
+                    module.self_id().name().to_owned(),
+                    name.to_owned(),
+                    offset as u64,
+                    count,
+                );
+            }
+            taken_by_function.insert(name.to_string(), taken);
+        }
+
+        let coverage =
+            ModuleLineCoverage::new(module, source_map, &unit.source_path, &coverage_map).unwrap();
+        for function in &coverage.functions {
+            assert_eq!(function.hits, runs.len() as u64);
+        }
+
+        // Known outcomes are exact (and never overstate coverage)
+        let function_lines: Vec<_> = coverage
+            .functions
+            .iter()
+            .map(|function| (function.name.clone(), function.lines.clone()))
+            .collect();
+        for branch in &coverage.branches {
+            let (name, _) = function_lines
+                .iter()
+                .find(|(_, lines)| lines.contains_key(&branch.line))
+                .unwrap();
+            let expected = taken_by_function[name]
+                .get(&(branch.offset, branch.outcome))
+                .copied()
+                .unwrap_or(0);
+            match branch.hits {
+                BranchHits::Taken(hits) => assert_eq!(hits, expected),
+                BranchHits::Unknown => {},
+                BranchHits::NotReached => panic!("all branches are reached"),
+            }
+        }
+
+        // The outcomes of the if-else in `f` are known, and match the lines of its arms
+        let f = coverage.functions.iter().find(|f| f.name == "f").unwrap();
+        let mut f_outcomes: Vec<_> = coverage
+            .branches
+            .iter()
+            .filter(|branch| f.lines.contains_key(&branch.line))
+            .map(|branch| branch.hits)
+            .collect();
+        f_outcomes.sort_by_key(|hits| format!("{:?}", hits));
+        assert_eq!(f_outcomes, vec![BranchHits::Taken(2), BranchHits::Taken(3)]);
+        let mut arm_hits = vec![f.lines[&4], f.lines[&6]];
+        arm_hits.sort();
+        assert_eq!(arm_hits, vec![2, 3]);
+    }
+}
@@ -0,0 +1,88 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::types::{CliCommand, CliError, CliTypedResult};
+use aptos_gas_profiling::{GasProfile, GAS_PROFILE_FILE_NAME};
+use async_trait::async_trait;
+use clap::Parser;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Compares the gas usage of two profiled executions
+///
+/// Every gas report generated with `--profile-gas` (e.g. by `aptos move simulate` or
+/// `aptos move replay`) contains a `gas_profile.json` file. This command aligns the call frames
+/// of two such profiles, and writes an HTML report and a JSON file (`diff.json`) with the
+/// changes in execution, IO and storage costs per function and per instruction.
+#[derive(Parser)]
+pub struct DiffGasProfiles {
+    /// The gas report (or its `gas_profile.json` file) of the base execution
+    #[clap(long, value_parser)]
+    pub(crate) base: PathBuf,
+
+    /// The gas report (or its `gas_profile.json` file) of the new execution
+    #[clap(long, value_parser)]
+    pub(crate) new: PathBuf,
+
+    /// Directory to write the report to
+    #[clap(long, value_parser, default_value = "gas-profiling/diff")]
+    pub(crate) output_dir: PathBuf,
+}
+
+/// The change in the total gas usage between two executions.
+#[derive(Debug, Serialize)]
+pub struct GasProfileDiffSummary {
+    /// Execution and IO gas, in gas units
+    pub execution_and_io_base: f64,
+    pub execution_and_io_new: f64,
+    /// Storage fees, in octas
+    pub storage_fee_base: u64,
+    pub storage_fee_new: u64,
+    pub report: PathBuf,
+}
+
+fn load_profile(path: &Path) -> CliTypedResult<GasProfile> {
+    let path = if path.is_dir() {
+        path.join(GAS_PROFILE_FILE_NAME)
+    } else {
+        path.to_path_buf()
+    };
+    GasProfile::load(&path)
+        .map_err(|err| CliError::UnableToReadFile(path.display().to_string(), format!("{:#}", err)))
+}
+
+#[async_trait]
+impl CliCommand<GasProfileDiffSummary> for DiffGasProfiles {
+    fn command_name(&self) -> &'static str {
+        "DiffGasProfiles"
+    }
+
+    async fn execute(self) -> CliTypedResult<GasProfileDiffSummary> {
+        let base = load_profile(&self.base)?;
+        let new = load_profile(&self.new)?;
+        let diff = base
+            .diff(&new)
+            .map_err(|err| CliError::CommandArgumentError(format!("{:#}", err)))?;
+
+        diff.generate_html_report(
+            &self.output_dir,
+            format!(
+                "Gas Report Diff - {} vs. {}",
+                self.base.display(),
+                self.new.display()
+            ),
+        )
+        .map_err(|err| CliError::UnexpectedError(format!("{:#}", err)))?;
+        println!("Gas report diff saved to {}.", self.output_dir.display());
+
+        // Both profiles have the same scaling factor, otherwise they cannot be compared.
+        let to_gas_units = |amount: u64| amount as f64 / diff.gas_scaling_factor as f64;
+        Ok(GasProfileDiffSummary {
+            execution_and_io_base: to_gas_units(diff.execution_and_io.base),
+            execution_and_io_new: to_gas_units(diff.execution_and_io.new),
+            storage_fee_base: diff.storage_fee.base,
+            storage_fee_new: diff.storage_fee.new,
+            report: self.output_dir.join("index.html"),
+        })
+    }
+}
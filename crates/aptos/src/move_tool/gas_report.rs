@@ -0,0 +1,215 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Metering of Move unit tests with the latest gas schedule in the code (i.e.,
+//! `AptosGasParameters::initial()`), which may differ from the schedule currently on chain.
+//!
+//! Tests are executed as plain function calls, so there is no transaction prologue or epilogue.
+//! After a test finishes, the IO gas and storage fees for the resources it wrote are charged the
+//! same way the epilogue would. This is an approximation: table items and events are not charged,
+//! and modified resources are charged as if their size did not change (the size before the test is
+//! not known).
+
+use aptos_gas_algebra::{Fee, Gas, InternalGas};
+use aptos_gas_meter::{AptosGasMeter, GasAlgebra, StandardGasAlgebra, StandardGasMeter};
+use aptos_gas_profiling::GasProfiler;
+use aptos_gas_schedule::{AptosGasParameters, LATEST_GAS_FEATURE_VERSION};
+use aptos_types::{
+    on_chain_config::CurrentTimeMicroseconds,
+    state_store::{state_key::StateKey, state_value::StateValueMetadata},
+    write_set::WriteOpSize,
+};
+use aptos_vm_types::{
+    change_set::WriteOpInfo, resolver::NoopBlockSynchronizationKillSwitch,
+    storage::StorageGasParameters,
+};
+use move_binary_format::errors::{Location, PartialVMError, VMResult};
+use move_core_types::{
+    effects::{ChangeSet, Op},
+    vm_status::StatusCode,
+};
+use move_unit_test::test_reporter::{GasBreakdown, TestRunInfo, UnitTestFactory};
+use move_vm_runtime::native_extensions::NativeContextExtensions;
+use std::path::PathBuf;
+
+type TestGasMeter =
+    GasProfiler<StandardGasMeter<StandardGasAlgebra<'static, NoopBlockSynchronizationKillSwitch>>>;
+
+/// Creates gas meters for unit tests which use the latest gas schedule in the code, and reports
+/// the execution, IO and storage gas used by each test.
+pub struct AptosGasUnitTestFactory {
+    gas_params: AptosGasParameters,
+    storage_gas_params: StorageGasParameters,
+    /// If set, an HTML gas profile of each test is written to this directory.
+    profile_dir: Option<PathBuf>,
+}
+
+impl AptosGasUnitTestFactory {
+    pub fn new(profile_dir: Option<PathBuf>) -> Self {
+        Self {
+            gas_params: AptosGasParameters::initial(),
+            storage_gas_params: StorageGasParameters::latest(),
+            profile_dir,
+        }
+    }
+
+    /// Charges the IO gas and storage fees for the resources written by a test.
+    fn charge_writes(&self, gas_meter: &mut TestGasMeter, change_set: &ChangeSet) -> VMResult<()> {
+        let txn_params = &self.gas_params.vm.txn;
+        let mut storage_fee = Fee::new(0);
+        for (addr, account_changes) in change_set.accounts() {
+            for (struct_tag, op) in account_changes.resources() {
+                let key = StateKey::resource(addr, struct_tag).map_err(|err| {
+                    PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR)
+                        .with_message(err.to_string())
+                        .finish(Location::Undefined)
+                })?;
+                let op_size = match op {
+                    Op::New(bytes) => WriteOpSize::Creation {
+                        write_len: bytes.len() as u64,
+                    },
+                    Op::Modify(bytes) => WriteOpSize::Modification {
+                        write_len: bytes.len() as u64,
+                    },
+                    Op::Delete => WriteOpSize::Deletion,
+                };
+                gas_meter.charge_io_gas_for_write(&key, &op_size)?;
+
+                let mut metadata =
+                    StateValueMetadata::placeholder(&CurrentTimeMicroseconds { microseconds: 0 });
+                let prev_size = op_size.write_len().unwrap_or(0);
+                storage_fee += self
+                    .storage_gas_params
+                    .space_pricing
+                    .charge_refund_write_op(txn_params, WriteOpInfo {
+                        key: &key,
+                        op_size,
+                        prev_size,
+                        metadata_mut: &mut metadata,
+                    })
+                    .charge;
+            }
+        }
+        gas_meter
+            .charge_storage_fee(storage_fee, txn_params.min_price_per_gas_unit)
+            .map_err(|err| err.finish(Location::Undefined))
+    }
+}
+
+impl UnitTestFactory for AptosGasUnitTestFactory {
+    type GasMeter = TestGasMeter;
+
+    fn new_gas_meter(&self) -> Self::GasMeter {
+        GasProfiler::new_script(StandardGasMeter::new(StandardGasAlgebra::new(
+            LATEST_GAS_FEATURE_VERSION,
+            self.gas_params.vm.clone(),
+            self.storage_gas_params.clone(),
+            false,
+            self.gas_params.vm.txn.maximum_number_of_gas_units,
+            &NoopBlockSynchronizationKillSwitch {},
+        )))
+    }
+
+    fn finalize_test_run_info(
+        &self,
+        change_set: &ChangeSet,
+        _: &mut NativeContextExtensions,
+        mut gas_meter: Self::GasMeter,
+        mut test_run_info: TestRunInfo,
+    ) -> TestRunInfo {
+        // If the test runs out of gas here, the gas used so far is still reported.
+        let _ = self.charge_writes(&mut gas_meter, change_set);
+
+        let algebra = gas_meter.algebra();
+        let txn_params = &algebra.vm_gas_params().txn;
+        let to_gas_units = |internal_gas: InternalGas| {
+            let gas: Gas = internal_gas.to_unit_round_up_with_params(txn_params);
+            u64::from(gas)
+        };
+        let gas_breakdown = GasBreakdown {
+            execution: to_gas_units(algebra.execution_gas_used()),
+            io: to_gas_units(algebra.io_gas_used()),
+            storage: to_gas_units(algebra.storage_fee_used_in_gas_units()),
+        };
+        test_run_info.gas_used = gas_breakdown.total();
+        test_run_info.gas_breakdown = Some(gas_breakdown);
+
+        if let Some(profile_dir) = &self.profile_dir {
+            let name = format!(
+                "{}::{}",
+                test_run_info.module_id.name(),
+                test_run_info.function_ident
+            );
+            let path = profile_dir.join(name.replace("::", "-"));
+            if let Err(err) = gas_meter
+                .finish()
+                .generate_html_report(&path, format!("Gas Report - {}", name))
+            {
+                eprintln!("Failed to write the gas profile of {}: {:#}", name, err);
+            }
+        }
+        test_run_info
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use move_core_types::{
+        account_address::AccountAddress,
+        identifier::Identifier,
+        language_storage::{ModuleId, StructTag},
+    };
+    use std::time::Duration;
+
+    fn finalize(change_set: &ChangeSet) -> TestRunInfo {
+        let factory = AptosGasUnitTestFactory::new(None);
+        let module_id = ModuleId::new(AccountAddress::ONE, Identifier::new("m").unwrap());
+        let test_run_info = TestRunInfo::new(module_id, "f".to_string(), Duration::ZERO);
+        factory.finalize_test_run_info(
+            change_set,
+            &mut NativeContextExtensions::default(),
+            factory.new_gas_meter(),
+            test_run_info,
+        )
+    }
+
+    fn resource_change_set(op: Op<Vec<u8>>) -> ChangeSet {
+        let struct_tag = StructTag {
+            address: AccountAddress::ONE,
+            module: Identifier::new("m").unwrap(),
+            name: Identifier::new("R").unwrap(),
+            type_args: vec![],
+        };
+        let mut change_set = ChangeSet::new();
+        change_set
+            .add_resource_op(AccountAddress::TWO, struct_tag, op.map(Into::into))
+            .unwrap();
+        change_set
+    }
+
+    #[test]
+    fn no_writes_are_free() {
+        let test_run_info = finalize(&ChangeSet::new());
+        assert_eq!(test_run_info.gas_breakdown, Some(GasBreakdown::default()));
+        assert_eq!(test_run_info.gas_used, 0);
+    }
+
+    #[test]
+    fn created_resources_are_charged_io_and_storage() {
+        let test_run_info = finalize(&resource_change_set(Op::New(vec![0; 100])));
+        let gas_breakdown = test_run_info.gas_breakdown.unwrap();
+        assert!(gas_breakdown.io > 0);
+        assert!(gas_breakdown.storage > 0);
+        assert_eq!(test_run_info.gas_used, gas_breakdown.total());
+    }
+
+    #[test]
+    fn modified_resources_are_only_charged_io() {
+        let test_run_info = finalize(&resource_change_set(Op::Modify(vec![0; 100])));
+        let gas_breakdown = test_run_info.gas_breakdown.unwrap();
+        assert!(gas_breakdown.io > 0);
+        assert_eq!(gas_breakdown.storage, 0);
+        assert_eq!(test_run_info.gas_used, gas_breakdown.total());
+    }
+}
@@ -9,7 +9,7 @@ use aptos_framework::{BuildOptions, BuiltPackage};
 use async_trait::async_trait;
 use clap::Parser;
 use move_compiler_v2::Experiment;
-use move_linter::MoveLintChecks;
+use move_linter::{LintSet, MoveLintChecks};
 use move_model::metadata::{CompilerVersion, LanguageVersion, LATEST_STABLE_LANGUAGE_VERSION};
 use move_package::source_package::std_lib::StdVersion;
 use std::{collections::BTreeMap, path::PathBuf};
@@ -73,6 +73,15 @@ pub struct LintPackage {
     /// Experiments
     #[clap(long, hide(true))]
     pub experiments: Vec<String>,
+
+    /// Additional lint sets to enable, as a comma separated list
+    ///
+    /// The `default` lint set is always enabled. The `security` lint set flags common
+    /// security issues in Aptos contracts, e.g., unchecked signers, unchecked narrowing
+    /// casts, unbounded loops over caller-controlled vectors, and object references
+    /// leaking out of public functions.
+    #[clap(long, value_delimiter = ',', default_value = "default")]
+    pub lint_sets: Vec<LintSet>,
 }
 
 impl LintPackage {
@@ -87,6 +96,7 @@ impl LintPackage {
             language_version,
             skip_attribute_checks,
             experiments,
+            lint_sets: _,
         } = self.clone();
         MovePackageOptions {
             dev,
@@ -138,7 +148,7 @@ impl CliCommand<&'static str> for LintPackage {
             resolved_graph,
             build_options,
             build_config,
-            vec![MoveLintChecks::make()],
+            vec![MoveLintChecks::make_with_lint_sets(&self.lint_sets)],
         )?;
 
         Ok("succeeded")
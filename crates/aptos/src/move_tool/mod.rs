@@ -9,9 +9,10 @@ use crate::{
         types::{
             load_account_arg, ArgWithTypeJSON, ChunkedPublishOption, CliConfig, CliError,
             CliTypedResult, ConfigSearchMode, EntryFunctionArguments, EntryFunctionArgumentsJSON,
-            LargePackagesModuleOption, MoveManifestAccountWrapper, MovePackageOptions,
-            OverrideSizeCheckOption, ProfileOptions, PromptOptions, RestOptions, SaveFile,
-            ScriptFunctionArguments, TransactionOptions, TransactionSummary, GIT_IGNORE,
+            LargePackagesModuleOption, MoveDebugOptions, MoveManifestAccountWrapper,
+            MovePackageOptions, OverrideSizeCheckOption, ProfileOptions, PromptOptions,
+            RestOptions, SaveFile, ScriptFunctionArguments, TransactionOptions, TransactionSummary,
+            GIT_IGNORE,
         },
         utils::{
             check_if_file_exists, create_dir_if_not_exist, dir_default_to_current,
@@ -23,6 +24,7 @@ use crate::{
         bytecode::{Decompile, Disassemble},
         coverage::SummaryCoverage,
         fmt::Fmt,
+        gas_report::AptosGasUnitTestFactory,
         lint::LintPackage,
         manifest::{Dependency, ManifestNamedAddress, MovePackageManifest, PackageInfo},
     },
@@ -40,7 +42,7 @@ use aptos_framework::{
     prover::ProverOptions,
     BuildOptions, BuiltPackage,
 };
-use aptos_gas_schedule::{MiscGasParameters, NativeGasParameters};
+use aptos_gas_schedule::{AptosGasParameters, MiscGasParameters, NativeGasParameters};
 use aptos_move_debugger::aptos_debugger::AptosDebugger;
 use aptos_rest_client::{
     aptos_api_types::{EntryFunctionId, HexEncodedBytes, IdentifierWrapper, MoveModuleId},
@@ -63,9 +65,13 @@ use itertools::Itertools;
 use move_cli::{self, base::test::UnitTestResult};
 use move_command_line_common::{address::NumericalAddress, env::MOVE_HOME};
 use move_core_types::{identifier::Identifier, language_storage::ModuleId, u256::U256};
+#[cfg(feature = "move-debugger")]
+use move_dap::{DebugSession, SourceIndex};
 use move_model::metadata::{CompilerVersion, LanguageVersion};
 use move_package::{source_package::layout::SourcePackageLayout, BuildConfig, CompilerConfig};
-use move_unit_test::UnitTestingConfig;
+use move_unit_test::{
+    fuzzer::DEFAULT_FUZZ_RUNS, UnitTestingConfig, DEFAULT_GAS_REGRESSION_THRESHOLD,
+};
 pub use package_hooks::*;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
@@ -83,11 +89,14 @@ pub mod aptos_debug_natives;
 mod bytecode;
 pub mod coverage;
 mod fmt;
+mod gas_profile_diff;
+mod gas_report;
 mod lint;
 mod manifest;
 pub mod package_hooks;
 mod show;
 pub mod stored_package;
+mod upgrade;
 
 const HELLO_BLOCKCHAIN_EXAMPLE: &str = include_str!(
     "../../../../aptos-move/move-examples/hello_blockchain/sources/hello_blockchain.move"
@@ -101,6 +110,7 @@ const HELLO_BLOCKCHAIN_EXAMPLE: &str = include_str!(
 #[derive(Subcommand)]
 pub enum MoveTool {
     BuildPublishPayload(BuildPublishPayload),
+    CheckUpgrade(upgrade::CheckUpgrade),
     Clean(CleanPackage),
     ClearStagingArea(ClearStagingArea),
     #[clap(alias = "build")]
@@ -116,6 +126,7 @@ pub enum MoveTool {
     CreateResourceAccountAndPublishPackage(CreateResourceAccountAndPublishPackage),
     Disassemble(Disassemble),
     Decompile(Decompile),
+    DiffGasProfiles(gas_profile_diff::DiffGasProfiles),
     #[clap(alias = "doc")]
     Document(DocumentPackage),
     Download(DownloadPackage),
@@ -141,6 +152,7 @@ impl MoveTool {
     pub async fn execute(self) -> CliResult {
         match self {
             MoveTool::BuildPublishPayload(tool) => tool.execute_serialized().await,
+            MoveTool::CheckUpgrade(tool) => tool.execute_serialized().await,
             MoveTool::Clean(tool) => tool.execute_serialized().await,
             MoveTool::ClearStagingArea(tool) => tool.execute_serialized().await,
             MoveTool::Compile(tool) => tool.execute_serialized().await,
@@ -157,6 +169,7 @@ impl MoveTool {
             },
             MoveTool::Disassemble(tool) => tool.execute_serialized().await,
             MoveTool::Decompile(tool) => tool.execute_serialized().await,
+            MoveTool::DiffGasProfiles(tool) => tool.execute_serialized().await,
             MoveTool::Document(tool) => tool.execute_serialized().await,
             MoveTool::Download(tool) => tool.execute_serialized().await,
             MoveTool::Init(tool) => tool.execute_serialized_success().await,
@@ -538,6 +551,56 @@ pub struct TestPackage {
     /// Dump storage state on failure.
     #[clap(long = "dump")]
     pub dump_state: bool,
+
+    /// The number of runs (with different generated arguments) of each `#[fuzz]` test
+    #[clap(long, default_value_t = DEFAULT_FUZZ_RUNS)]
+    pub fuzz_runs: u64,
+
+    /// The seed for generating the arguments of `#[fuzz]` tests
+    ///
+    /// If not set, a random seed is used. The seed is reported when a fuzz test fails, so that
+    /// the failure can be reproduced.
+    #[clap(long)]
+    pub fuzz_seed: Option<u64>,
+
+    /// Meter tests with the latest gas schedule, and report the execution, IO and storage gas
+    /// used by each passing test
+    ///
+    /// The gas schedule is the one built into this CLI (`AptosGasParameters::initial()`), which
+    /// may differ from the schedule currently on chain. The IO and storage gas for resources
+    /// written by a test are charged as by a transaction, but table items and events are not
+    /// charged.
+    #[clap(long)]
+    pub gas_report: bool,
+
+    /// A gas baseline file to compare the gas used by each passing test against
+    ///
+    /// Testing fails if a test uses more gas than in the baseline, plus the threshold set via
+    /// `--gas-regression-threshold`. Implies `--gas-report` metering.
+    #[clap(long, value_parser)]
+    pub gas_baseline: Option<PathBuf>,
+
+    /// Write the gas used by each passing test to the `--gas-baseline` file instead of comparing
+    ///
+    /// Entries already in the file for tests which did not pass in this run (e.g., tests
+    /// excluded by `--filter`) are kept.
+    #[clap(long, requires = "gas_baseline")]
+    pub update_gas_baseline: bool,
+
+    /// The percentage by which the gas used by a test may exceed its baseline
+    #[clap(long, default_value_t = DEFAULT_GAS_REGRESSION_THRESHOLD)]
+    pub gas_regression_threshold: u64,
+
+    /// Write an HTML gas profile of each test to `gas-profiling/` in the package directory,
+    /// for drilling down into the gas usage. Implies `--gas-report` metering.
+    #[clap(long)]
+    pub gas_profile: bool,
+
+    /// Wait for a debugger to attach on this port over the Debug Adapter Protocol before running
+    /// the tests, and run them one at a time so that they can be stepped through.
+    #[cfg(feature = "move-debugger")]
+    #[clap(long)]
+    pub debug_port: Option<u16>,
 }
 
 pub(crate) fn fix_bytecode_version(
@@ -555,6 +618,40 @@ pub(crate) fn fix_bytecode_version(
     }
 }
 
+#[cfg(feature = "move-debugger")]
+impl TestPackage {
+    /// Starts a debug session for the tests if `--debug-port` is set, and returns the test config
+    /// adjusted for debugging.
+    fn start_debug_session(
+        &self,
+        config: &BuildConfig,
+        path: &Path,
+        unit_test_config: UnitTestingConfig,
+    ) -> CliTypedResult<(UnitTestingConfig, Option<DebugSession>)> {
+        let Some(port) = self.debug_port else {
+            return Ok((unit_test_config, None));
+        };
+
+        // Tests are compiled in dev mode, which needs to be matched for the source maps
+        let mut debug_config = config.clone();
+        debug_config.dev_mode = true;
+        let package = debug_config
+            .compile_package(path, &mut Vec::new())
+            .map_err(|err| CliError::MoveCompilationError(format!("{:#}", err)))?;
+        let index = SourceIndex::from_package(&package)?;
+        let debug_session = DebugSession::start(port, index, None).map_err(|err| {
+            CliError::UnexpectedError(format!("Failed to start the debug session: {:#}", err))
+        })?;
+
+        // The debugger is shared by all threads, so tests must not run concurrently
+        let unit_test_config = UnitTestingConfig {
+            num_threads: 1,
+            ..unit_test_config
+        };
+        Ok((unit_test_config, Some(debug_session)))
+    }
+}
+
 #[async_trait]
 impl CliCommand<&'static str> for TestPackage {
     fn command_name(&self) -> &'static str {
@@ -591,39 +688,76 @@ impl CliCommand<&'static str> for TestPackage {
         };
 
         let path = self.move_options.get_package_path()?;
-        let result = move_cli::base::test::run_move_unit_tests(
-            path.as_path(),
-            config.clone(),
-            UnitTestingConfig {
-                filter: self.filter.clone(),
-                report_stacktrace_on_abort: true,
-                report_storage_on_error: self.dump_state,
-                ignore_compile_warnings: self.ignore_compile_warnings,
-                named_address_values: self
-                    .move_options
-                    .named_addresses
-                    .iter()
-                    .map(|(name, addr_wrap)| {
-                        (
-                            name.clone(),
-                            NumericalAddress::from_account_address(addr_wrap.account_address),
-                        )
-                    })
-                    .collect(),
-                ..UnitTestingConfig::default()
-            },
-            // TODO(Gas): we may want to switch to non-zero costs in the future
-            aptos_debug_natives::aptos_debug_natives(
-                NativeGasParameters::zeros(),
-                MiscGasParameters::zeros(),
-            ),
-            aptos_test_feature_flags_genesis(),
-            None,
-            None,
-            self.compute_coverage,
-            &mut std::io::stdout(),
-        )
-        .map_err(|err| CliError::UnexpectedError(format!("Failed to run tests: {:#}", err)))?;
+        let unit_test_config = UnitTestingConfig {
+            filter: self.filter.clone(),
+            report_stacktrace_on_abort: true,
+            report_storage_on_error: self.dump_state,
+            ignore_compile_warnings: self.ignore_compile_warnings,
+            fuzz_runs: self.fuzz_runs,
+            fuzz_seed: self.fuzz_seed,
+            gas_report: self.gas_report,
+            gas_baseline: self.gas_baseline.clone(),
+            update_gas_baseline: self.update_gas_baseline,
+            gas_regression_threshold: self.gas_regression_threshold,
+            named_address_values: self
+                .move_options
+                .named_addresses
+                .iter()
+                .map(|(name, addr_wrap)| {
+                    (
+                        name.clone(),
+                        NumericalAddress::from_account_address(addr_wrap.account_address),
+                    )
+                })
+                .collect(),
+            ..UnitTestingConfig::default()
+        };
+
+        #[cfg(feature = "move-debugger")]
+        let (unit_test_config, debug_session) =
+            self.start_debug_session(&config, &path, unit_test_config)?;
+
+        // Any of the gas options require metering with the latest (in-code) gas schedule
+        let result = if self.gas_report || self.gas_baseline.is_some() || self.gas_profile {
+            let gas_params = AptosGasParameters::initial();
+            move_cli::base::test::run_move_unit_tests_with_factory(
+                path.as_path(),
+                config.clone(),
+                unit_test_config,
+                aptos_debug_natives::aptos_debug_natives(gas_params.natives, gas_params.vm.misc),
+                aptos_test_feature_flags_genesis(),
+                self.compute_coverage,
+                &mut std::io::stdout(),
+                AptosGasUnitTestFactory::new(self.gas_profile.then(|| path.join("gas-profiling"))),
+            )
+        } else {
+            move_cli::base::test::run_move_unit_tests(
+                path.as_path(),
+                config.clone(),
+                unit_test_config,
+                // TODO(Gas): we may want to switch to non-zero costs in the future
+                aptos_debug_natives::aptos_debug_natives(
+                    NativeGasParameters::zeros(),
+                    MiscGasParameters::zeros(),
+                ),
+                aptos_test_feature_flags_genesis(),
+                None,
+                None,
+                self.compute_coverage,
+                &mut std::io::stdout(),
+            )
+        }
+        .map_err(|err| CliError::UnexpectedError(format!("Failed to run tests: {:#}", err)));
+
+        // The debugger is notified that the tests exited, even if they failed to run
+        #[cfg(feature = "move-debugger")]
+        if let Some(debug_session) = debug_session {
+            debug_session.finish(match &result {
+                Ok(UnitTestResult::Success) => 0,
+                _ => 1,
+            });
+        }
+        let result = result?;
 
         // Print coverage summary if --coverage is set
         if self.compute_coverage {
@@ -2268,6 +2402,9 @@ pub struct Replay {
     #[clap(long)]
     pub(crate) skip_comparison: bool,
 
+    #[clap(flatten)]
+    pub(crate) debug_options: MoveDebugOptions,
+
     /// Key to use for ratelimiting purposes with the node API. This value will be used
     /// as `Authorization: Bearer <key>`
     #[clap(long)]
@@ -2358,6 +2495,15 @@ impl CliCommand<TransactionSummary> for Replay {
                 txn.clone(),
                 hash,
             )?
+        } else if self.debug_options.is_enabled() {
+            println!("Replaying transaction using the debugger...");
+            local_simulation::debug_transaction_using_debugger(
+                &debugger,
+                self.txn_id,
+                txn.clone(),
+                hash,
+                &self.debug_options,
+            )?
         } else {
             println!("Replaying transaction...");
             local_simulation::run_transaction_using_debugger(
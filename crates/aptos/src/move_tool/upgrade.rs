@@ -0,0 +1,689 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    common::{
+        types::{
+            load_account_arg, CliCommand, CliError, CliResult, CliTypedResult, MovePackageOptions,
+            ProfileOptions, RestOptions,
+        },
+        utils::{start_logger, to_common_result},
+    },
+    move_tool::{stored_package::CachedPackageRegistry, IncludedArtifacts},
+};
+use aptos_framework::{natives::code::UpgradePolicy, BuiltPackage};
+use aptos_logger::Level;
+use aptos_rest_client::Client;
+use aptos_types::{
+    account_address::AccountAddress,
+    chain_id::ChainId,
+    on_chain_config::{
+        ConfigurationResource, FeatureFlag, Features, TimedFeatureFlag, TimedFeatures,
+        TimedFeaturesBuilder,
+    },
+};
+use async_trait::async_trait;
+use clap::Parser;
+use move_binary_format::{
+    access::ModuleAccess,
+    compatibility::{Compatibility, Incompatibility},
+    file_format::{VariantIndex, Visibility},
+    views::{ModuleView, StructDefinitionView},
+    CompiledModule,
+};
+use move_core_types::{ability::AbilitySet, language_storage::CORE_CODE_ADDRESS};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+    time::Instant,
+};
+
+/// Checks whether a package can be upgraded to the local version
+///
+/// The package built from the local sources is compared against the version published on chain
+/// (or against another local version of the package). Every change to the structs, functions
+/// and friend declarations of its modules is listed, and classified as compatible or not with
+/// the upgrade policy of the package, following the compatibility rules of the VM. The rules
+/// depend on the feature flags of the network, so the network is queried even when comparing
+/// against `--old-package-dir`.
+///
+/// The report is printed even if the package cannot be upgraded, in which case the command fails.
+#[derive(Parser)]
+pub struct CheckUpgrade {
+    /// Address of the account the package is published at
+    #[clap(long, value_parser = load_account_arg, required_unless_present = "old_package_dir")]
+    pub(crate) account: Option<AccountAddress>,
+
+    /// Path to a local version of the package to compare against, instead of the version
+    /// published on chain. It is built with the same options as the new version.
+    #[clap(long, value_parser, conflicts_with = "account")]
+    pub(crate) old_package_dir: Option<PathBuf>,
+
+    #[clap(flatten)]
+    pub(crate) move_options: MovePackageOptions,
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+}
+
+/// The changes between two versions of a package.
+#[derive(Debug, Serialize)]
+pub struct UpgradeReport {
+    pub package: String,
+    pub old_upgrade_policy: String,
+    pub new_upgrade_policy: String,
+    /// Whether the new version can be published as an upgrade of the old version.
+    pub compatible: bool,
+    /// Reasons why the package as a whole cannot be upgraded.
+    pub errors: Vec<String>,
+    pub changes: Vec<UpgradeChange>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UpgradeChange {
+    pub module: String,
+    pub change: String,
+    /// Whether the change is allowed by the upgrade policy of the old version.
+    pub compatible: bool,
+}
+
+#[async_trait]
+impl CliCommand<UpgradeReport> for CheckUpgrade {
+    fn command_name(&self) -> &'static str {
+        "CheckUpgrade"
+    }
+
+    async fn execute(self) -> CliTypedResult<UpgradeReport> {
+        let client = self.rest_options.client(&self.profile_options)?;
+        let compatibility = network_compatibility(&client).await?;
+
+        let build_options = IncludedArtifacts::Sparse.build_options(&self.move_options)?;
+        let new_package =
+            BuiltPackage::build(self.move_options.get_package_path()?, build_options.clone())
+                .map_err(|err| CliError::MoveCompilationError(format!("{:#}", err)))?;
+        let new_policy = new_package.extract_metadata()?.upgrade_policy;
+        let new_modules = package_modules(&new_package);
+
+        let (old_policy, old_modules) = match &self.old_package_dir {
+            Some(old_package_dir) => {
+                let old_package = BuiltPackage::build(old_package_dir.clone(), build_options)
+                    .map_err(|err| CliError::MoveCompilationError(format!("{:#}", err)))?;
+                if old_package.name() != new_package.name() {
+                    return Err(CliError::CommandArgumentError(format!(
+                        "Expected package `{}` in `{}`, found `{}`",
+                        new_package.name(),
+                        old_package_dir.display(),
+                        old_package.name()
+                    )));
+                }
+                (
+                    old_package.extract_metadata()?.upgrade_policy,
+                    package_modules(&old_package),
+                )
+            },
+            None => {
+                let account = self.account.ok_or_else(|| {
+                    CliError::CommandArgumentError(
+                        "Either `--account` or `--old-package-dir` must be provided".to_string(),
+                    )
+                })?;
+                let url = self.rest_options.url(&self.profile_options)?;
+                let registry = CachedPackageRegistry::create(url, account, true).await?;
+                let package = registry
+                    .get_package(new_package.name())
+                    .await
+                    .map_err(|err| CliError::CommandArgumentError(err.to_string()))?;
+                let mut modules = BTreeMap::new();
+                for name in package.module_names() {
+                    let bytecode = registry.get_bytecode(name).await?.ok_or_else(|| {
+                        CliError::UnexpectedError(format!("Missing bytecode of module `{}`", name))
+                    })?;
+                    let module = CompiledModule::deserialize(bytecode).map_err(|err| {
+                        CliError::UnexpectedError(format!(
+                            "Failed to deserialize module `{}`: {}",
+                            name, err
+                        ))
+                    })?;
+                    modules.insert(name.to_string(), module);
+                }
+                (package.upgrade_policy(), modules)
+            },
+        };
+
+        let report = check_upgrade(
+            new_package.name(),
+            old_policy,
+            &old_modules,
+            new_policy,
+            &new_modules,
+            compatibility,
+        );
+        for error in &report.errors {
+            eprintln!("error: {}", error);
+        }
+        for change in &report.changes {
+            eprintln!(
+                "[{}] {}: {}",
+                if change.compatible {
+                    "compatible"
+                } else {
+                    "incompatible"
+                },
+                change.module,
+                change.change
+            );
+        }
+        Ok(report)
+    }
+
+    /// Prints the report as the result, but fails if the package cannot be upgraded
+    async fn execute_serialized(self) -> CliResult {
+        start_logger(Level::Warn);
+        let command_name = self.command_name();
+        let start_time = Instant::now();
+        let jsonify_error_output = self.jsonify_error_output();
+        let result = self.execute().await;
+        let compatible = matches!(&result, Ok(report) if report.compatible);
+        let output =
+            to_common_result(command_name, start_time, result, jsonify_error_output).await?;
+        if compatible {
+            Ok(output)
+        } else {
+            Err(output)
+        }
+    }
+}
+
+/// Returns the compatibility checks which the VM of the network applies to module upgrades
+async fn network_compatibility(client: &Client) -> CliTypedResult<Compatibility> {
+    let features = client
+        .get_account_resource_bcs::<Features>(CORE_CODE_ADDRESS, "0x1::features::Features")
+        .await?
+        .into_inner();
+    let response = client
+        .get_account_resource_bcs::<ConfigurationResource>(
+            CORE_CODE_ADDRESS,
+            "0x1::reconfiguration::Configuration",
+        )
+        .await?;
+    let chain_id = ChainId::new(response.state().chain_id);
+    // The VM enables timed features as of the last reconfiguration
+    let timed_features = TimedFeaturesBuilder::new(
+        chain_id,
+        response.inner().last_reconfiguration_time_micros(),
+    )
+    .build();
+    Ok(compatibility_checks(&features, &timed_features))
+}
+
+/// Mirrors the compatibility checks of the VM when publishing modules (see
+/// `AptosVM::resolve_pending_code_publish_and_finish_user_session`)
+pub fn compatibility_checks(features: &Features, timed_features: &TimedFeatures) -> Compatibility {
+    let check_struct_layout = true;
+    let check_friend_linking = !features.is_enabled(FeatureFlag::TREAT_FRIEND_AS_PRIVATE);
+    Compatibility::new(
+        check_struct_layout,
+        check_friend_linking,
+        timed_features.is_enabled(TimedFeatureFlag::EntryCompatibility),
+    )
+}
+
+fn package_modules(package: &BuiltPackage) -> BTreeMap<String, CompiledModule> {
+    package
+        .modules()
+        .map(|module| (module.self_id().name().to_string(), module.clone()))
+        .collect()
+}
+
+/// Compares two versions of a package, mirroring the checks of `code::publish_package` and of the
+/// VM when the new version is published as an upgrade of the old version. `compatibility` holds
+/// the checks of the VM for packages with the `compatible` upgrade policy.
+pub fn check_upgrade(
+    package: &str,
+    old_policy: UpgradePolicy,
+    old_modules: &BTreeMap<String, CompiledModule>,
+    new_policy: UpgradePolicy,
+    new_modules: &BTreeMap<String, CompiledModule>,
+    compatibility: Compatibility,
+) -> UpgradeReport {
+    let mut errors = vec![];
+    if old_policy == UpgradePolicy::immutable() {
+        errors.push("the published package is immutable".to_string());
+    }
+    if new_policy.policy < old_policy.policy {
+        errors.push(format!(
+            "the upgrade policy is weakened from `{}` to `{}`",
+            old_policy, new_policy
+        ));
+    }
+    for name in old_modules.keys() {
+        if !new_modules.contains_key(name) {
+            errors.push(format!("removed module `{}`", name));
+        }
+    }
+
+    // An immutable package cannot be upgraded at all, so none of the changes are compatible.
+    let compatibility = if old_policy == UpgradePolicy::immutable() {
+        None
+    } else if old_policy == UpgradePolicy::arbitrary() {
+        Some(Compatibility::no_check())
+    } else {
+        Some(compatibility)
+    };
+
+    let mut changes = vec![];
+    for (name, new_module) in new_modules {
+        let module = new_module.self_id().short_str_lossless();
+        match old_modules.get(name) {
+            Some(old_module) => {
+                changes.extend(
+                    module_changes(old_module, new_module, compatibility.as_ref())
+                        .into_iter()
+                        .map(|(change, compatible)| UpgradeChange {
+                            module: module.clone(),
+                            change,
+                            compatible,
+                        }),
+                );
+            },
+            None => changes.push(UpgradeChange {
+                module,
+                change: "added module".to_string(),
+                compatible: compatibility.is_some(),
+            }),
+        }
+    }
+
+    UpgradeReport {
+        package: package.to_string(),
+        old_upgrade_policy: old_policy.to_string(),
+        new_upgrade_policy: new_policy.to_string(),
+        compatible: errors.is_empty() && changes.iter().all(|change| change.compatible),
+        errors,
+        changes,
+    }
+}
+
+/// Lists the changes between two versions of a module, and whether each of them is compatible
+/// with `compatibility`, or with nothing if `None`.
+fn module_changes(
+    old_module: &CompiledModule,
+    new_module: &CompiledModule,
+    compatibility: Option<&Compatibility>,
+) -> Vec<(String, bool)> {
+    let incompatibilities = compatibility
+        .map(|compatibility| compatibility.incompatibilities(old_module, new_module))
+        .unwrap_or_default();
+    let is_compatible = |incompatibility: &Incompatibility| {
+        compatibility.is_some() && !incompatibilities.contains(incompatibility)
+    };
+    let old_view = ModuleView::new(old_module);
+    let new_view = ModuleView::new(new_module);
+    let mut changes = vec![];
+
+    // Changes which are not compatible with the strictest checks, and hence may break users of
+    // the module.
+    let all_incompatibilities =
+        Compatibility::full_check().incompatibilities(old_module, new_module);
+    for incompatibility in &all_incompatibilities {
+        let change = match incompatibility {
+            Incompatibility::ChangedStructLayout(name) => match (
+                old_view.struct_definition(name),
+                new_view.struct_definition(name),
+            ) {
+                (Some(old_struct), Some(new_struct)) => format!(
+                    "changed layout of struct `{}` from `{}` to `{}`",
+                    name,
+                    struct_layout(old_struct),
+                    struct_layout(new_struct)
+                ),
+                _ => incompatibility.to_string(),
+            },
+            _ => incompatibility.to_string(),
+        };
+        changes.push((change, is_compatible(incompatibility)));
+    }
+
+    // Changes which are always compatible, since they only extend the module.
+    let compatible = compatibility.is_some();
+    for new_struct in new_view.structs() {
+        let name = new_struct.name();
+        match old_view.struct_definition(name) {
+            Some(old_struct) => {
+                let added_abilities = new_struct.abilities().setminus(old_struct.abilities());
+                if added_abilities != AbilitySet::EMPTY {
+                    changes.push((
+                        format!("added abilities `{}` to struct `{}`", added_abilities, name),
+                        compatible,
+                    ));
+                }
+                if old_struct.variant_count() > 0
+                    && new_struct.variant_count() > old_struct.variant_count()
+                {
+                    changes.push((
+                        format!(
+                            "added variants to enum `{}`: `{}` to `{}`",
+                            name,
+                            struct_layout(old_struct),
+                            struct_layout(new_struct)
+                        ),
+                        compatible,
+                    ));
+                }
+            },
+            None => changes.push((format!("added struct `{}`", name), compatible)),
+        }
+    }
+    for new_func in new_view.functions() {
+        let name = new_func.name();
+        match old_view.function_definition(name) {
+            Some(old_func) => {
+                if old_func.visibility() != new_func.visibility() {
+                    // Changes which restrict the visibility are reported above.
+                    let widened = old_func.visibility() == Visibility::Private
+                        || new_func.visibility() == Visibility::Public;
+                    if widened {
+                        changes.push((
+                            format!(
+                                "changed visibility of function `{}` from `{}` to `{}`",
+                                name,
+                                visibility_str(old_func.visibility()),
+                                visibility_str(new_func.visibility())
+                            ),
+                            compatible,
+                        ));
+                    }
+                }
+                if !old_func.is_entry() && new_func.is_entry() {
+                    changes.push((
+                        format!("added `entry` modifier to function `{}`", name),
+                        compatible,
+                    ));
+                }
+            },
+            None => changes.push((
+                format!(
+                    "added {} function `{}`",
+                    visibility_str(new_func.visibility()),
+                    name
+                ),
+                compatible,
+            )),
+        }
+    }
+    for old_func in old_view.functions() {
+        let name = old_func.name();
+        let reported = all_incompatibilities.iter().any(|incompatibility| {
+            matches!(incompatibility, Incompatibility::RemovedFunction(removed) if removed.as_ident_str() == name)
+        });
+        if new_view.function_definition(name).is_none() && !reported {
+            changes.push((format!("removed private function `{}`", name), compatible));
+        }
+    }
+    let old_friends: BTreeSet<_> = old_module.immediate_friends().into_iter().collect();
+    for friend in new_module.immediate_friends() {
+        if !old_friends.contains(&friend) {
+            changes.push((
+                format!("added friend declaration `{}`", friend.short_str_lossless()),
+                compatible,
+            ));
+        }
+    }
+    changes
+}
+
+fn visibility_str(visibility: Visibility) -> &'static str {
+    match visibility {
+        Visibility::Private => "private",
+        Visibility::Friend => "public(friend)",
+        Visibility::Public => "public",
+    }
+}
+
+/// Describes the fields of a struct, or the variants of an enum, by name.
+fn struct_layout(view: &StructDefinitionView<CompiledModule>) -> String {
+    let fields = |variant: Option<VariantIndex>| {
+        view.fields_optional_variant(variant)
+            .map(|field| field.name().to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    if view.variant_count() == 0 {
+        format!("{{ {} }}", fields(None))
+    } else {
+        (0..view.variant_count())
+            .map(|idx| {
+                let idx = idx as VariantIndex;
+                format!("{} {{ {} }}", view.variant_name(idx), fields(Some(idx)))
+            })
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use move_binary_format::file_format::empty_module;
+    use move_model::metadata::{CompilerVersion, LanguageVersion};
+    use move_package::{BuildConfig, CompilerConfig};
+    use std::fs;
+
+    /// Compiles the modules of `source` (at address `0x42`), and returns module `m`.
+    fn compile_module(source: &str) -> CompiledModule {
+        let package_dir = tempfile::tempdir().unwrap();
+        fs::write(
+            package_dir.path().join("Move.toml"),
+            "[package]\nname = \"UpgradeTest\"\nversion = \"0.0.0\"\n",
+        )
+        .unwrap();
+        fs::create_dir(package_dir.path().join("sources")).unwrap();
+        fs::write(package_dir.path().join("sources/m.move"), source).unwrap();
+        let config = BuildConfig {
+            install_dir: Some(package_dir.path().join("build")),
+            compiler_config: CompilerConfig {
+                compiler_version: Some(CompilerVersion::latest_stable()),
+                language_version: Some(LanguageVersion::latest_stable()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let package = config
+            .compile_package(package_dir.path(), &mut Vec::new())
+            .unwrap();
+        package
+            .root_modules_map()
+            .iter_modules_owned()
+            .into_iter()
+            .find(|module| module.self_id().name().as_str() == "m")
+            .unwrap()
+    }
+
+    fn changes(
+        old_source: &str,
+        new_source: &str,
+        compatibility: Option<&Compatibility>,
+    ) -> BTreeSet<(String, bool)> {
+        module_changes(
+            &compile_module(old_source),
+            &compile_module(new_source),
+            compatibility,
+        )
+        .into_iter()
+        .collect()
+    }
+
+    fn change(description: &str, compatible: bool) -> (String, bool) {
+        (description.to_string(), compatible)
+    }
+
+    #[test]
+    fn test_check_upgrade_policies() {
+        let modules = BTreeMap::from([("empty".to_string(), empty_module())]);
+        let report = check_upgrade(
+            "package",
+            UpgradePolicy::compat(),
+            &modules,
+            UpgradePolicy::compat(),
+            &modules,
+            Compatibility::full_check(),
+        );
+        assert!(report.compatible);
+        assert!(report.changes.is_empty());
+
+        let report = check_upgrade(
+            "package",
+            UpgradePolicy::compat(),
+            &modules,
+            UpgradePolicy::arbitrary(),
+            &BTreeMap::new(),
+            Compatibility::full_check(),
+        );
+        assert!(!report.compatible);
+        assert_eq!(report.errors, vec![
+            "the upgrade policy is weakened from `compatible` to `arbitrary`".to_string(),
+            "removed module `empty`".to_string(),
+        ]);
+
+        let report = check_upgrade(
+            "package",
+            UpgradePolicy::immutable(),
+            &BTreeMap::new(),
+            UpgradePolicy::immutable(),
+            &modules,
+            Compatibility::full_check(),
+        );
+        assert!(!report.compatible);
+        assert!(!report.changes[0].compatible);
+    }
+
+    #[test]
+    fn test_struct_layout_changes() {
+        let old_source = "module 0x42::m { struct S has drop { a: u64 } }";
+        let new_source = "module 0x42::m { struct S has drop { a: u64, b: u64 } }";
+        let layout_change = "changed layout of struct `S` from `{ a }` to `{ a, b }`";
+
+        let compat = Compatibility::full_check();
+        assert_eq!(
+            changes(old_source, new_source, Some(&compat)),
+            BTreeSet::from([change(layout_change, false)])
+        );
+        let arbitrary = Compatibility::no_check();
+        assert_eq!(
+            changes(old_source, new_source, Some(&arbitrary)),
+            BTreeSet::from([change(layout_change, true)])
+        );
+        assert_eq!(
+            changes(old_source, new_source, None),
+            BTreeSet::from([change(layout_change, false)])
+        );
+    }
+
+    #[test]
+    fn test_struct_ability_changes() {
+        let old_source = "module 0x42::m {
+            struct S has drop { a: u64 }
+            struct T has copy, drop { a: u64 }
+        }";
+        let new_source = "module 0x42::m {
+            struct S has copy, drop { a: u64 }
+            struct T has drop { a: u64 }
+        }";
+        assert_eq!(
+            changes(old_source, new_source, Some(&Compatibility::full_check())),
+            BTreeSet::from([
+                change("removed abilities `copy` from struct `T`", false),
+                change("added abilities `copy` to struct `S`", true),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_function_visibility_changes() {
+        let old_source = "module 0x42::m {
+            public fun f(): u64 { 0 }
+            fun g(): u64 { 0 }
+            entry fun e() {}
+        }";
+        let new_source = "module 0x42::m {
+            public(friend) fun f(): u64 { 0 }
+            public fun g(): u64 { 0 }
+            fun e() {}
+        }";
+        assert_eq!(
+            changes(old_source, new_source, Some(&Compatibility::full_check())),
+            BTreeSet::from([
+                change("changed visibility of function `f`", false),
+                change("removed `entry` modifier of function `e`", false),
+                change(
+                    "changed visibility of function `g` from `private` to `public`",
+                    true
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_friend_changes() {
+        let old_source = "module 0x42::m {
+            friend 0x42::a;
+            public(friend) fun h(): u64 { 0 }
+            public(friend) entry fun e() {}
+        }
+        module 0x42::a {}
+        module 0x42::b {}";
+        let new_source = "module 0x42::m {
+            friend 0x42::a;
+            friend 0x42::b;
+        }
+        module 0x42::a {}
+        module 0x42::b {}";
+
+        let mut features = Features::default();
+        features.disable(FeatureFlag::TREAT_FRIEND_AS_PRIVATE);
+        let timed_features = TimedFeaturesBuilder::enable_all().build();
+        assert_eq!(
+            changes(
+                old_source,
+                new_source,
+                Some(&compatibility_checks(&features, &timed_features))
+            ),
+            BTreeSet::from([
+                change("removed function `h`", false),
+                change("removed function `e`", false),
+                change("added friend declaration `0x42::b`", true),
+            ])
+        );
+
+        // With `TREAT_FRIEND_AS_PRIVATE`, friend functions can be removed, unless they are entry
+        // functions and `EntryCompatibility` is enabled
+        features.enable(FeatureFlag::TREAT_FRIEND_AS_PRIVATE);
+        assert_eq!(
+            changes(
+                old_source,
+                new_source,
+                Some(&compatibility_checks(&features, &timed_features))
+            ),
+            BTreeSet::from([
+                change("removed function `h`", true),
+                change("removed function `e`", false),
+                change("added friend declaration `0x42::b`", true),
+            ])
+        );
+        let timed_features = TimedFeaturesBuilder::new(ChainId::mainnet(), 0).build();
+        assert_eq!(
+            changes(
+                old_source,
+                new_source,
+                Some(&compatibility_checks(&features, &timed_features))
+            ),
+            BTreeSet::from([
+                change("removed function `h`", true),
+                change("removed function `e`", true),
+                change("added friend declaration `0x42::b`", true),
+            ])
+        );
+    }
+}
@@ -0,0 +1,1961 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Support for running the localnet as a fork of an existing network.
+//!
+//! Instead of running a node from genesis, we serve the subset of the node API used by
+//! the CLI and the SDKs ourselves. State is read from a remote fullnode at the fork
+//! version the first time it is needed and cached, and the effects of transactions
+//! submitted to the fork are stacked on top of it in memory. Nothing is ever sent to
+//! the remote network.
+
+use super::{health_checker::HealthChecker, traits::ServiceManager, RunLocalnet};
+use crate::{common::types::load_account_arg, node::local_testnet::utils::socket_addr_to_url};
+use anyhow::{anyhow, Context, Result};
+use aptos_api_types::{
+    mime_types::{BCS, BCS_SIGNED_TRANSACTION, BCS_VIEW_FUNCTION, JSON},
+    Address, AptosError, AptosErrorCode, AsConverter, GasEstimation, GasEstimationBcs,
+    IndexResponse, IndexResponseBcs, LedgerInfo, MoveModuleBytecode, MoveModuleId,
+    RawTableItemRequest, TableItemRequest, Transaction, TransactionData, TransactionOnChainData,
+    ViewFunction, ViewRequest, MAX_RECURSIVE_TYPES_ALLOWED, X_APTOS_BLOCK_HEIGHT, X_APTOS_CHAIN_ID,
+    X_APTOS_EPOCH, X_APTOS_LEDGER_OLDEST_VERSION, X_APTOS_LEDGER_TIMESTAMP, X_APTOS_LEDGER_VERSION,
+    X_APTOS_OLDEST_BLOCK_HEIGHT,
+};
+use aptos_bcs_utils::serialize_uleb128;
+use aptos_config::config::{ApiConfig, RoleType};
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_gas_schedule::{AptosGasParameters, FromOnChainGasSchedule};
+use aptos_move_debugger::aptos_debugger::AptosDebugger;
+use aptos_resource_viewer::AptosValueAnnotator;
+use aptos_rest_client::{error::RestError, AptosBaseUrl, Client};
+use aptos_storage_interface::DbReader;
+use aptos_transaction_simulation::{DeltaStateStore, SimulationStateStore};
+use aptos_types::{
+    access_path::Path as StatePath,
+    account_address::AccountAddress,
+    account_config::{AccountResource, CORE_CODE_ADDRESS},
+    block_metadata::BlockMetadata,
+    on_chain_config::{ConfigurationResource, GasScheduleV2, OnChainConfig, ValidatorSet},
+    state_store::{
+        state_key::{inner::StateKeyInner, StateKey},
+        table::TableHandle,
+        StateView, TStateView,
+    },
+    transaction::{
+        authenticator::{AccountAuthenticator, TransactionAuthenticator},
+        signature_verified_transaction::SignatureVerifiedTransaction,
+        ExecutionStatus, SignedTransaction, TransactionInfo, TransactionOutput, TransactionStatus,
+    },
+    write_set::WriteSet,
+};
+use aptos_validator_interface::DebuggerStateView;
+use aptos_vm::{
+    data_cache::AsMoveResolver,
+    move_vm_ext::SessionId,
+    system_module_names::{
+        ACCOUNT_MODULE, CREATE_ACCOUNT_IF_DOES_NOT_EXIST, TRANSACTION_FEE_MODULE,
+    },
+    AptosSimulationVM, AptosVM,
+};
+use aptos_vm_environment::environment::AptosEnvironment;
+use aptos_vm_logging::log_schema::AdapterLogSchema;
+use aptos_vm_types::{
+    module_and_script_storage::AsAptosCodeStorage, module_write_set::ModuleWriteSet,
+    storage::change_set_configs::ChangeSetConfigs,
+};
+use async_trait::async_trait;
+use clap::Parser;
+use maplit::hashset;
+use move_core_types::{
+    ident_str,
+    identifier::Identifier,
+    language_storage::{StructTag, TypeTag},
+    value::{serialize_values, MoveValue},
+};
+use move_vm_runtime::module_traversal::{TraversalContext, TraversalStorage};
+use move_vm_types::gas::UnmeteredGasMeter;
+use poem::{
+    get, handler,
+    http::{header::ACCEPT, StatusCode},
+    listener::TcpListener,
+    middleware::Tracing,
+    post,
+    web::{Data, Json, Path},
+    Endpoint, EndpointExt, Request, Response, Route, Server,
+};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    fmt::Display,
+    net::{Ipv4Addr, SocketAddr, SocketAddrV4},
+    str::FromStr,
+    sync::{Arc, Mutex, RwLock},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Args for running the localnet as a fork of an existing network.
+#[derive(Debug, Parser)]
+pub struct ForkArgs {
+    /// Fork an existing network instead of running a node from genesis
+    ///
+    /// This can be the name of a network (mainnet, testnet or devnet) or the URL of a
+    /// fullnode API. Instead of running a node, the localnet serves the node API itself:
+    /// state is fetched from the remote node the first time it is read and cached
+    /// locally, and transactions submitted to the localnet are executed locally on top
+    /// of it. Nothing is ever sent to the remote network.
+    ///
+    /// The faucet is replaced by the `/v1/fork/fund` endpoint, and the transaction
+    /// stream and indexer API are not available for forks. Events and blocks cannot be
+    /// queried, and only the transactions executed on the fork can be fetched.
+    #[clap(long, conflicts_with_all = [
+        "with_indexer_api", "config_path", "test_config_override", "no_node",
+    ])]
+    pub fork_url: Option<String>,
+
+    /// The version of the remote ledger to fork from. Defaults to the latest version
+    #[clap(long, requires = "fork_url")]
+    pub fork_version: Option<u64>,
+
+    /// API key to use for requests to the remote node
+    #[clap(long, requires = "fork_url")]
+    pub fork_api_key: Option<String>,
+
+    /// The port at which to expose the node API of the fork
+    #[clap(long, default_value_t = 8080)]
+    pub fork_api_port: u16,
+
+    /// Accounts to impersonate
+    ///
+    /// Transactions sent by these accounts are executed without checking that they were
+    /// signed with the account's key, so they can be signed with any key. More accounts
+    /// can be impersonated while the fork is running with the `/v1/fork/impersonate`
+    /// endpoint.
+    #[clap(long, num_args = 1.., value_parser = load_account_arg, requires = "fork_url")]
+    pub impersonate: Vec<AccountAddress>,
+}
+
+impl ForkArgs {
+    pub fn is_fork(&self) -> bool {
+        self.fork_url.is_some()
+    }
+}
+
+/// Parses the value of `--fork-url`, which is either a network name or a URL.
+fn parse_fork_url(fork_url: &str) -> Result<AptosBaseUrl> {
+    Ok(match fork_url.to_lowercase().trim() {
+        "mainnet" => AptosBaseUrl::Mainnet,
+        "testnet" => AptosBaseUrl::Testnet,
+        "devnet" => AptosBaseUrl::Devnet,
+        _ => AptosBaseUrl::Custom(
+            Url::parse(fork_url).with_context(|| format!("Invalid fork URL {}", fork_url))?,
+        ),
+    })
+}
+
+#[derive(Debug)]
+pub struct ForkManager {
+    remote_url: Url,
+    api_key: Option<String>,
+    fork_version: Option<u64>,
+    impersonate: Vec<AccountAddress>,
+    listen_address: SocketAddrV4,
+}
+
+impl ForkManager {
+    pub fn new(args: &RunLocalnet, bind_to: Ipv4Addr) -> Result<Self> {
+        let fork_args = &args.fork_args;
+        let fork_url = fork_args
+            .fork_url
+            .as_deref()
+            .ok_or_else(|| anyhow!("--fork-url must be set to run a fork"))?;
+        Ok(ForkManager {
+            remote_url: parse_fork_url(fork_url)?.to_url(),
+            api_key: fork_args.fork_api_key.clone(),
+            fork_version: fork_args.fork_version,
+            impersonate: fork_args.impersonate.clone(),
+            listen_address: SocketAddrV4::new(bind_to, fork_args.fork_api_port),
+        })
+    }
+
+    pub fn get_node_api_url(&self) -> Url {
+        socket_addr_to_url(&SocketAddr::V4(self.listen_address), "http").unwrap()
+    }
+}
+
+#[async_trait]
+impl ServiceManager for ForkManager {
+    fn get_name(&self) -> String {
+        "Forked Node API".to_string()
+    }
+
+    fn get_health_checkers(&self) -> HashSet<HealthChecker> {
+        hashset! {HealthChecker::NodeApi(self.get_node_api_url())}
+    }
+
+    fn get_prerequisite_health_checkers(&self) -> HashSet<&HealthChecker> {
+        // Like the node, the fork doesn't depend on anything, we start it first.
+        hashset! {}
+    }
+
+    async fn run_service(self: Box<Self>) -> Result<()> {
+        let mut client_builder = Client::builder(AptosBaseUrl::Custom(self.remote_url.clone()));
+        if let Some(api_key) = &self.api_key {
+            client_builder = client_builder.api_key(api_key)?;
+        }
+        let client = client_builder.build();
+
+        let chain = ForkedChain::new(client, self.fork_version, self.impersonate)
+            .await
+            .with_context(|| format!("Failed to fork {}", self.remote_url))?;
+        eprintln!(
+            "Forked {} at version {}",
+            self.remote_url,
+            chain.ledger.read().unwrap().fork_version
+        );
+
+        run_fork_server(Arc::new(chain), self.listen_address).await
+    }
+}
+
+/// The ledger of the fork. Transactions executed locally are numbered after the fork
+/// version, each of them in its own block. The block prologue advances the chain clock
+/// before each transaction, but is not recorded as a version of its own.
+struct ForkLedger {
+    chain_id: u8,
+    epoch: u64,
+    fork_version: u64,
+    fork_block_height: u64,
+    timestamp_usecs: u64,
+    /// The transactions executed on the fork, along with the timestamp of their block.
+    transactions: Vec<(TransactionOnChainData, u64)>,
+    transactions_by_hash: HashMap<HashValue, usize>,
+}
+
+impl ForkLedger {
+    fn version(&self) -> u64 {
+        self.fork_version + self.transactions.len() as u64
+    }
+
+    fn ledger_info(&self) -> LedgerInfo {
+        LedgerInfo {
+            chain_id: self.chain_id,
+            epoch: self.epoch.into(),
+            ledger_version: self.version().into(),
+            oldest_ledger_version: 0.into(),
+            block_height: (self.fork_block_height + self.transactions.len() as u64).into(),
+            oldest_block_height: 0.into(),
+            ledger_timestamp: self.timestamp_usecs.into(),
+        }
+    }
+}
+
+/// The converter only needs a database to look up auxiliary data of committed
+/// transactions, which we never ask it to do, since we render transactions ourselves.
+struct NoDbReader;
+
+impl DbReader for NoDbReader {}
+
+/// Lists the accounts of the remote network. The state view reads the state one key at a
+/// time, so it cannot tell which resources and modules an account has.
+#[async_trait]
+trait AccountLister: Send + Sync {
+    /// Returns the types of the resources (with resource groups flattened into their
+    /// members) and the names of the modules stored under `address` at `version`.
+    async fn list_account(
+        &self,
+        address: AccountAddress,
+        version: u64,
+    ) -> Result<(Vec<StructTag>, Vec<Identifier>)>;
+}
+
+#[async_trait]
+impl AccountLister for Client {
+    async fn list_account(
+        &self,
+        address: AccountAddress,
+        version: u64,
+    ) -> Result<(Vec<StructTag>, Vec<Identifier>)> {
+        let resources = self
+            .get_account_resources_at_version_bcs(address, version)
+            .await;
+        let modules = self
+            .get_account_modules_at_version_bcs(address, version)
+            .await;
+        match (resources, modules) {
+            (Ok(resources), Ok(modules)) => Ok((
+                resources.into_inner().into_keys().collect(),
+                modules
+                    .into_inner()
+                    .into_keys()
+                    .map(|module_id| module_id.name.0)
+                    .collect(),
+            )),
+            // The account does not exist on the remote network
+            (Err(RestError::Api(err)), _) | (_, Err(RestError::Api(err)))
+                if err.status_code == reqwest::StatusCode::NOT_FOUND =>
+            {
+                Ok((vec![], vec![]))
+            },
+            (Err(err), _) | (_, Err(err)) => Err(err.into()),
+        }
+    }
+}
+
+struct ForkedChain {
+    state_view: DeltaStateStore<DebuggerStateView>,
+    remote: Arc<dyn AccountLister>,
+    ledger: RwLock<ForkLedger>,
+    impersonated: RwLock<HashSet<AccountAddress>>,
+    /// The state keys written on the fork, by account, to list the resources and modules
+    /// that were added to an account on the fork.
+    written_keys: RwLock<HashMap<AccountAddress, HashSet<StateKey>>>,
+    /// Held while executing a transaction, so transactions are executed one at a time.
+    execution_lock: Mutex<()>,
+    db: Arc<dyn DbReader>,
+}
+
+impl ForkedChain {
+    async fn new(
+        client: Client,
+        fork_version: Option<u64>,
+        impersonated: Vec<AccountAddress>,
+    ) -> Result<Self> {
+        let state = client
+            .get_ledger_information()
+            .await
+            .context("Failed to get ledger information from the remote node")?
+            .into_inner();
+        let fork_version = fork_version.unwrap_or(state.version);
+        let block = client
+            .get_block_by_version_bcs(fork_version, false)
+            .await
+            .with_context(|| format!("Failed to get the block at version {}", fork_version))?
+            .into_inner();
+        let configuration: ConfigurationResource = client
+            .get_account_resource_at_version_bcs(
+                CORE_CODE_ADDRESS,
+                "0x1::reconfiguration::Configuration",
+                fork_version,
+            )
+            .await
+            .context("Failed to get the epoch at the fork version")?
+            .into_inner();
+
+        // The debugger reads the state as of the version before the one it is given.
+        let debugger = AptosDebugger::rest_client(client.clone())?;
+        Ok(Self::from_state_view(
+            debugger.state_view_at_version(fork_version + 1),
+            Arc::new(client),
+            ForkLedger {
+                chain_id: state.chain_id,
+                epoch: configuration.epoch(),
+                fork_version,
+                fork_block_height: block.block_height,
+                timestamp_usecs: block.block_timestamp,
+                transactions: vec![],
+                transactions_by_hash: HashMap::new(),
+            },
+            impersonated,
+        ))
+    }
+
+    fn from_state_view(
+        state_view: DebuggerStateView,
+        remote: Arc<dyn AccountLister>,
+        ledger: ForkLedger,
+        impersonated: Vec<AccountAddress>,
+    ) -> Self {
+        Self {
+            state_view: DeltaStateStore::new_with_base(state_view),
+            remote,
+            ledger: RwLock::new(ledger),
+            impersonated: RwLock::new(impersonated.into_iter().collect()),
+            written_keys: RwLock::new(HashMap::new()),
+            execution_lock: Mutex::new(()),
+            db: Arc::new(NoDbReader),
+        }
+    }
+
+    fn ledger_info(&self) -> LedgerInfo {
+        self.ledger.read().unwrap().ledger_info()
+    }
+
+    /// Runs `f` on a blocking thread, since reading state that is not cached yet blocks
+    /// on a request to the remote node, and turns the result into a response carrying
+    /// the usual ledger headers.
+    async fn respond<F>(self: Arc<Self>, f: F) -> Response
+    where
+        F: FnOnce(&ForkedChain) -> Result<Output, ForkError> + Send + 'static,
+    {
+        let chain = self.clone();
+        let result = tokio::task::spawn_blocking(move || f(&chain))
+            .await
+            .unwrap_or_else(|err| Err(ForkError::internal(err)));
+        let output = result.unwrap_or_else(|err| Output {
+            status: err.status,
+            content_type: JSON,
+            body: serde_json::to_vec(&err.error).unwrap(),
+        });
+
+        let ledger_info = self.ledger_info();
+        Response::builder()
+            .status(output.status)
+            .content_type(output.content_type)
+            .header(X_APTOS_CHAIN_ID, ledger_info.chain_id.to_string())
+            .header(
+                X_APTOS_LEDGER_VERSION,
+                ledger_info.ledger_version.to_string(),
+            )
+            .header(
+                X_APTOS_LEDGER_OLDEST_VERSION,
+                ledger_info.oldest_ledger_version.to_string(),
+            )
+            .header(
+                X_APTOS_LEDGER_TIMESTAMP,
+                ledger_info.ledger_timestamp.to_string(),
+            )
+            .header(X_APTOS_EPOCH, ledger_info.epoch.to_string())
+            .header(X_APTOS_BLOCK_HEIGHT, ledger_info.block_height.to_string())
+            .header(
+                X_APTOS_OLDEST_BLOCK_HEIGHT,
+                ledger_info.oldest_block_height.to_string(),
+            )
+            .body(output.body)
+    }
+
+    fn index(&self, bcs: bool) -> Result<Output, ForkError> {
+        let ledger_info = self.ledger_info();
+        if bcs {
+            Output::bcs(&IndexResponseBcs::new(ledger_info, RoleType::FullNode))
+        } else {
+            Output::json(&IndexResponse::new(ledger_info, RoleType::FullNode, None))
+        }
+    }
+
+    fn estimate_gas_price(&self, bcs: bool) -> Result<Output, ForkError> {
+        let gas_schedule = GasScheduleV2::fetch_config(&self.state_view)
+            .ok_or_else(|| anyhow!("Failed to fetch the gas schedule"))?;
+        let gas_feature_version = gas_schedule.feature_version;
+        let gas_params = AptosGasParameters::from_on_chain_gas_schedule(
+            &gas_schedule.into_btree_map(),
+            gas_feature_version,
+        )
+        .map_err(|err| anyhow!("Failed to construct gas parameters: {}", err))?;
+        // Transactions are executed as soon as they are submitted, so the minimum gas
+        // price is always enough.
+        let gas_estimate: u64 = gas_params.vm.txn.min_price_per_gas_unit.into();
+        if bcs {
+            Output::bcs(&GasEstimationBcs { gas_estimate })
+        } else {
+            Output::json(&GasEstimation {
+                deprioritized_gas_estimate: Some(gas_estimate),
+                gas_estimate,
+                prioritized_gas_estimate: Some(gas_estimate),
+            })
+        }
+    }
+
+    fn account(&self, address: &str, bcs: bool) -> Result<Output, ForkError> {
+        let address = parse_address(address)?;
+        let state_key =
+            StateKey::resource_typed::<AccountResource>(&address).map_err(ForkError::internal)?;
+        let bytes = self
+            .state_view
+            .get_state_value_bytes(&state_key)
+            .context("Failed to read the account")?;
+        let account_resource = match &bytes {
+            Some(bytes) => bcs::from_bytes(bytes).context("Failed to deserialize the account")?,
+            None => {
+                let features = self
+                    .state_view
+                    .get_features()
+                    .context("Failed to read the feature flags")?;
+                if !features.is_default_account_resource_enabled() {
+                    return Err(ForkError::not_found(
+                        format!("Account {} not found", address),
+                        AptosErrorCode::AccountNotFound,
+                    ));
+                }
+                AccountResource::new_stateless(address)
+            },
+        };
+        if bcs {
+            Output::bcs(&account_resource)
+        } else {
+            Output::json(&aptos_api_types::AccountData::from(account_resource))
+        }
+    }
+
+    fn resource(&self, address: &str, resource_type: &str, bcs: bool) -> Result<Output, ForkError> {
+        let address = parse_address(address)?;
+        let struct_tag = StructTag::from_str(resource_type)
+            .map_err(|err| ForkError::bad_request(err, AptosErrorCode::InvalidInput))?;
+        let bytes = self.read_resource(&address, &struct_tag)?.ok_or_else(|| {
+            ForkError::not_found(
+                format!(
+                    "Resource {} not found at {}",
+                    struct_tag.to_canonical_string(),
+                    address
+                ),
+                AptosErrorCode::ResourceNotFound,
+            )
+        })?;
+        if bcs {
+            Ok(Output::encoded(bytes))
+        } else {
+            let move_resource = self
+                .state_view
+                .as_converter(self.db.clone(), None)
+                .try_into_resource(&struct_tag, &bytes)?;
+            Output::json(&move_resource)
+        }
+    }
+
+    fn read_resource(
+        &self,
+        address: &AccountAddress,
+        struct_tag: &StructTag,
+    ) -> Result<Option<Vec<u8>>, ForkError> {
+        let annotator = AptosValueAnnotator::new(&self.state_view);
+        Ok(match annotator.view_resource_group_member(struct_tag) {
+            Some(group_tag) => {
+                let group_key = StateKey::resource_group(address, &group_tag);
+                match self
+                    .state_view
+                    .get_state_value_bytes(&group_key)
+                    .context("Failed to read the resource group")?
+                {
+                    Some(group) => bcs::from_bytes::<BTreeMap<StructTag, Vec<u8>>>(&group)
+                        .context("Failed to deserialize the resource group")?
+                        .remove(struct_tag),
+                    None => None,
+                }
+            },
+            None => {
+                let state_key =
+                    StateKey::resource(address, struct_tag).map_err(ForkError::internal)?;
+                self.state_view
+                    .get_state_value_bytes(&state_key)
+                    .context("Failed to read the resource")?
+                    .map(|bytes| bytes.to_vec())
+            },
+        })
+    }
+
+    fn module(&self, address: &str, module_name: &str, bcs: bool) -> Result<Output, ForkError> {
+        let address = parse_address(address)?;
+        let module_name = Identifier::new(module_name)
+            .map_err(|err| ForkError::bad_request(err, AptosErrorCode::InvalidInput))?;
+        let bytes = self
+            .state_view
+            .get_state_value_bytes(&StateKey::module(&address, &module_name))
+            .context("Failed to read the module")?
+            .ok_or_else(|| {
+                ForkError::not_found(
+                    format!("Module {}::{} not found", address, module_name),
+                    AptosErrorCode::ModuleNotFound,
+                )
+            })?;
+        if bcs {
+            Ok(Output::encoded(bytes.to_vec()))
+        } else {
+            Output::json(&MoveModuleBytecode::new(bytes.to_vec()).try_parse_abi()?)
+        }
+    }
+
+    fn resources(&self, address: &str, bcs: bool) -> Result<Output, ForkError> {
+        let address = parse_address(address)?;
+        let mut resources = BTreeMap::new();
+        for (path, bytes) in self.read_account(address)? {
+            match path {
+                StatePath::Resource(struct_tag) => {
+                    resources.insert(struct_tag, bytes);
+                },
+                StatePath::ResourceGroup(_) => resources.extend(
+                    bcs::from_bytes::<BTreeMap<StructTag, Vec<u8>>>(&bytes)
+                        .context("Failed to deserialize the resource group")?,
+                ),
+                StatePath::Code(_) => (),
+            }
+        }
+        if bcs {
+            Output::bcs(&resources)
+        } else {
+            let move_resources = self
+                .state_view
+                .as_converter(self.db.clone(), None)
+                .try_into_resources(
+                    resources
+                        .iter()
+                        .map(|(struct_tag, bytes)| (struct_tag.clone(), bytes.as_slice())),
+                )?;
+            Output::json(&move_resources)
+        }
+    }
+
+    fn modules(&self, address: &str, bcs: bool) -> Result<Output, ForkError> {
+        let address = parse_address(address)?;
+        let modules: BTreeMap<_, _> = self
+            .read_account(address)?
+            .into_iter()
+            .filter_map(|(path, bytes)| match path {
+                StatePath::Code(module_id) => Some((MoveModuleId::from(module_id), bytes)),
+                StatePath::Resource(_) | StatePath::ResourceGroup(_) => None,
+            })
+            .collect();
+        if bcs {
+            Output::bcs(&modules)
+        } else {
+            let modules = modules
+                .into_values()
+                .map(|bytes| MoveModuleBytecode::new(bytes).try_parse_abi())
+                .collect::<Result<Vec<_>>>()?;
+            Output::json(&modules)
+        }
+    }
+
+    /// Returns the paths and values of the resources, resource groups and modules stored
+    /// under `address`: the ones of the remote account at the fork version, along with
+    /// the ones written on the fork, as of the latest version of the fork.
+    fn read_account(
+        &self,
+        address: AccountAddress,
+    ) -> Result<Vec<(StatePath, Vec<u8>)>, ForkError> {
+        let fork_version = self.ledger.read().unwrap().fork_version;
+        let (resources, modules) = tokio::runtime::Handle::current()
+            .block_on(self.remote.list_account(address, fork_version))
+            .with_context(|| format!("Failed to list account {} on the remote node", address))?;
+
+        let mut state_keys: BTreeSet<StateKey> = self
+            .written_keys
+            .read()
+            .unwrap()
+            .get(&address)
+            .into_iter()
+            .flatten()
+            .cloned()
+            .collect();
+        let annotator = AptosValueAnnotator::new(&self.state_view);
+        for struct_tag in resources {
+            state_keys.insert(match annotator.view_resource_group_member(&struct_tag) {
+                Some(group_tag) => StateKey::resource_group(&address, &group_tag),
+                None => StateKey::resource(&address, &struct_tag).map_err(ForkError::internal)?,
+            });
+        }
+        for module_name in modules {
+            state_keys.insert(StateKey::module(&address, &module_name));
+        }
+
+        let mut values = vec![];
+        for state_key in state_keys {
+            let StateKeyInner::AccessPath(access_path) = state_key.inner() else {
+                continue;
+            };
+            // Resources may have been deleted on the fork
+            if let Some(bytes) = self
+                .state_view
+                .get_state_value_bytes(&state_key)
+                .context("Failed to read the account")?
+            {
+                values.push((access_path.get_path(), bytes.to_vec()));
+            }
+        }
+        Ok(values)
+    }
+
+    fn table_item(
+        &self,
+        table_handle: &str,
+        request: TableItemRequest,
+        bcs: bool,
+    ) -> Result<Output, ForkError> {
+        let table_handle = parse_address(table_handle)?;
+        let key_type: TypeTag = (&request.key_type)
+            .try_into()
+            .map_err(|err| ForkError::bad_request(err, AptosErrorCode::InvalidInput))?;
+        let value_type: TypeTag = (&request.value_type)
+            .try_into()
+            .map_err(|err| ForkError::bad_request(err, AptosErrorCode::InvalidInput))?;
+        let converter = self.state_view.as_converter(self.db.clone(), None);
+        let raw_key = converter
+            .try_into_vm_value(&key_type, request.key.clone())
+            .map_err(|err| ForkError::bad_request(err, AptosErrorCode::InvalidInput))?
+            .undecorate()
+            .simple_serialize()
+            .ok_or_else(|| {
+                ForkError::bad_request(
+                    "Failed to serialize the table key",
+                    AptosErrorCode::InvalidInput,
+                )
+            })?;
+        let bytes = self.read_table_item(table_handle, &raw_key)?;
+        if bcs {
+            Ok(Output::encoded(bytes))
+        } else {
+            Output::json(&converter.try_into_move_value(&value_type, &bytes)?)
+        }
+    }
+
+    fn raw_table_item(
+        &self,
+        table_handle: &str,
+        request: RawTableItemRequest,
+    ) -> Result<Output, ForkError> {
+        let table_handle = parse_address(table_handle)?;
+        Ok(Output::encoded(
+            self.read_table_item(table_handle, &request.key.0)?,
+        ))
+    }
+
+    fn read_table_item(
+        &self,
+        table_handle: AccountAddress,
+        raw_key: &[u8],
+    ) -> Result<Vec<u8>, ForkError> {
+        self.state_view
+            .get_state_value_bytes(&StateKey::table_item(&TableHandle(table_handle), raw_key))
+            .context("Failed to read the table item")?
+            .map(|bytes| bytes.to_vec())
+            .ok_or_else(|| {
+                ForkError::not_found(
+                    format!(
+                        "Table item {} not found in table {}",
+                        hex::encode(raw_key),
+                        table_handle
+                    ),
+                    AptosErrorCode::TableItemNotFound,
+                )
+            })
+    }
+
+    fn submit(&self, content_type: Option<&str>, body: &[u8]) -> Result<Output, ForkError> {
+        let txn = parse_signed_transaction(content_type, body)?;
+        self.execute(txn.clone())?;
+        let pending = self
+            .state_view
+            .as_converter(self.db.clone(), None)
+            .try_into_pending_transaction_poem(txn)?;
+        Ok(Output::json(&pending)?.with_status(StatusCode::ACCEPTED))
+    }
+
+    /// Executes a transaction on top of the fork in a block of its own, and commits its
+    /// output along with the one of the block prologue.
+    fn execute(&self, txn: SignedTransaction) -> Result<(), ForkError> {
+        let _guard = self.execution_lock.lock().unwrap();
+
+        // The block prologue is staged, so it is dropped if the transaction is discarded.
+        let block_state = DeltaStateStore::new_with_base(&self.state_view);
+        let (timestamp, prologue_write_set) = self.run_block_prologue(&block_state)?;
+        block_state
+            .apply_write_set(&prologue_write_set)
+            .context("Failed to apply the block prologue")?;
+
+        let (vm_status, output) = if self.impersonated.read().unwrap().contains(&txn.sender()) {
+            let authenticator = txn.authenticator_ref();
+            if !authenticator.secondary_signer_addresses().is_empty()
+                || authenticator.fee_payer_address().is_some()
+            {
+                return Err(ForkError::bad_request(
+                    "Multi-agent and fee payer transactions cannot be sent by impersonated accounts",
+                    AptosErrorCode::InvalidInput,
+                ));
+            }
+            // Without an authenticator, the VM does not check the authentication key of
+            // the sender in simulation mode.
+            let unsigned_txn = SignedTransaction::new_signed_transaction(
+                txn.raw_transaction_ref().clone(),
+                TransactionAuthenticator::SingleSender {
+                    sender: AccountAuthenticator::NoAccountAuthenticator,
+                },
+            );
+            AptosSimulationVM::create_vm_and_simulate_signed_transaction(
+                &unsigned_txn,
+                &block_state,
+            )
+        } else {
+            txn.verify_signature().map_err(|err| {
+                ForkError::bad_request(
+                    format!("Invalid signature: {:#}", err),
+                    AptosErrorCode::InvalidInput,
+                )
+            })?;
+            let env = AptosEnvironment::new(&block_state);
+            let vm = AptosVM::new(&env, &block_state);
+            let log_context = AdapterLogSchema::new(block_state.id(), 0);
+            let resolver = block_state.as_move_resolver();
+            let code_storage = block_state.as_aptos_code_storage(&env);
+            let (vm_status, vm_output) =
+                vm.execute_user_transaction(&resolver, &code_storage, &txn, &log_context);
+            let output = vm_output
+                .try_materialize_into_transaction_output(&resolver)
+                .context("Failed to materialize the transaction output")?;
+            (vm_status, output)
+        };
+
+        let status = match output.status() {
+            TransactionStatus::Keep(status) => status.clone(),
+            TransactionStatus::Discard(status_code) => {
+                return Err(ForkError {
+                    status: StatusCode::BAD_REQUEST,
+                    error: AptosError::new_with_vm_status(
+                        format!("Invalid transaction: {}", vm_status),
+                        AptosErrorCode::VmError,
+                        *status_code,
+                    ),
+                })
+            },
+            TransactionStatus::Retry => {
+                return Err(ForkError::internal("The transaction was not executed"))
+            },
+        };
+        // The changes of the block prologue and of the transaction are committed in a
+        // single write set, while holding the ledger lock, so readers never observe a
+        // half-applied block.
+        let mut block_write_set = prologue_write_set.into_mut();
+        block_write_set.extend(
+            output
+                .write_set()
+                .write_op_iter()
+                .map(|(state_key, write_op)| (state_key.clone(), write_op.clone())),
+        );
+        let block_write_set = block_write_set
+            .freeze()
+            .context("Failed to build the block write set")?;
+        let mut ledger = self.ledger.write().unwrap();
+        self.commit(&block_write_set)?;
+        // The block prologue starts a new epoch once the epoch interval has passed.
+        let epoch = ConfigurationResource::fetch_config(&self.state_view)
+            .ok_or_else(|| anyhow!("Failed to fetch the epoch"))?
+            .epoch();
+
+        let data = to_on_chain_data(ledger.version() + 1, txn, &output, status);
+        let hash = data.info.transaction_hash();
+        let position = ledger.transactions.len();
+        ledger.transactions.push((data, timestamp));
+        ledger.transactions_by_hash.insert(hash, position);
+        ledger.timestamp_usecs = timestamp;
+        ledger.epoch = epoch;
+        Ok(())
+    }
+
+    /// Applies `write_set` to the state of the fork, and records the keys it writes.
+    fn commit(&self, write_set: &WriteSet) -> Result<(), ForkError> {
+        self.state_view
+            .apply_write_set(write_set)
+            .context("Failed to apply the write set")?;
+        let mut written_keys = self.written_keys.write().unwrap();
+        for (state_key, _) in write_set.write_op_iter() {
+            if let StateKeyInner::AccessPath(access_path) = state_key.inner() {
+                written_keys
+                    .entry(access_path.address)
+                    .or_default()
+                    .insert(state_key.clone());
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs the block prologue of the block of the next transaction on `state_view`, and
+    /// returns the timestamp of the block along with the changes of the prologue.
+    ///
+    /// The block is proposed by the first active validator, since only blocks proposed by
+    /// validators can advance the clock. Its timestamp is the current time, unless the
+    /// clock of the forked network is ahead of it.
+    fn run_block_prologue(
+        &self,
+        state_view: &impl StateView,
+    ) -> Result<(u64, WriteSet), ForkError> {
+        let (version, epoch, last_timestamp) = {
+            let ledger = self.ledger.read().unwrap();
+            (ledger.version() + 1, ledger.epoch, ledger.timestamp_usecs)
+        };
+        let proposer = ValidatorSet::fetch_config(state_view)
+            .ok_or_else(|| anyhow!("Failed to fetch the validator set"))?
+            .active_validators()
+            .first()
+            .copied()
+            .ok_or_else(|| anyhow!("The validator set has no active validators"))?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .context("The system clock is before the UNIX epoch")?
+            .as_micros() as u64;
+        let timestamp = now.max(last_timestamp + 1);
+
+        let block_metadata = BlockMetadata::new(
+            HashValue::sha3_256_of(&version.to_le_bytes()),
+            epoch,
+            version,
+            proposer,
+            vec![],
+            vec![],
+            timestamp,
+        );
+        let env = AptosEnvironment::new(state_view);
+        let vm = AptosVM::new(&env, state_view);
+        let log_context = AdapterLogSchema::new(state_view.id(), 0);
+        let resolver = state_view.as_move_resolver();
+        let code_storage = state_view.as_aptos_code_storage(&env);
+        let (vm_status, vm_output) = vm
+            .execute_single_transaction(
+                &SignatureVerifiedTransaction::Valid(
+                    aptos_types::transaction::Transaction::BlockMetadata(block_metadata),
+                ),
+                &resolver,
+                &code_storage,
+                &log_context,
+            )
+            .map_err(|err| anyhow!("Failed to run the block prologue: {}", err))?;
+        let output = vm_output
+            .try_materialize_into_transaction_output(&resolver)
+            .context("Failed to materialize the block prologue output")?;
+        if !matches!(
+            output.status(),
+            TransactionStatus::Keep(ExecutionStatus::Success)
+        ) {
+            return Err(ForkError::internal(format!(
+                "Failed to run the block prologue: {}",
+                vm_status
+            )));
+        }
+        Ok((timestamp, output.write_set().clone()))
+    }
+
+    fn simulate(
+        &self,
+        content_type: Option<&str>,
+        body: &[u8],
+        bcs: bool,
+    ) -> Result<Output, ForkError> {
+        let txn = parse_signed_transaction(content_type, body)?;
+        if txn.verify_signature().is_ok() {
+            return Err(ForkError::bad_request(
+                "Simulated transactions must not have a valid signature",
+                AptosErrorCode::InvalidInput,
+            ));
+        }
+
+        let (vm_status, output) =
+            AptosSimulationVM::create_vm_and_simulate_signed_transaction(&txn, &self.state_view);
+        let status = ExecutionStatus::conmbine_vm_status_for_simulation(
+            output.auxiliary_data(),
+            output.status().clone(),
+        );
+        let (version, timestamp) = {
+            let ledger = self.ledger.read().unwrap();
+            (ledger.version(), ledger.timestamp_usecs)
+        };
+        let data = to_on_chain_data(version, txn, &output, status);
+        if bcs {
+            return Output::bcs(&data);
+        }
+
+        let mut user_txn = match self.render_transaction(&data, timestamp)? {
+            Transaction::UserTransaction(user_txn) => user_txn,
+            _ => unreachable!("Only user transactions can be simulated"),
+        };
+        user_txn.append_simulation_error_message(&vm_status);
+        Output::json(&vec![user_txn])
+    }
+
+    fn transaction_by_hash(&self, hash: &str, bcs: bool) -> Result<Output, ForkError> {
+        let hash: HashValue = aptos_api_types::HashValue::from_str(hash)
+            .map_err(|err| ForkError::bad_request(err, AptosErrorCode::InvalidInput))?
+            .into();
+        let ledger = self.ledger.read().unwrap();
+        let data = ledger
+            .transactions_by_hash
+            .get(&hash)
+            .map(|position| ledger.transactions[*position].clone())
+            .ok_or_else(|| {
+                ForkError::not_found(
+                    format!("Transaction {} not found", hash),
+                    AptosErrorCode::TransactionNotFound,
+                )
+            })?;
+        self.transaction_output(data, bcs)
+    }
+
+    fn transaction_by_version(&self, version: &str, bcs: bool) -> Result<Output, ForkError> {
+        let version = u64::from_str(version)
+            .map_err(|err| ForkError::bad_request(err, AptosErrorCode::InvalidInput))?;
+        let ledger = self.ledger.read().unwrap();
+        let data = version
+            .checked_sub(ledger.fork_version + 1)
+            .and_then(|offset| ledger.transactions.get(offset as usize))
+            .cloned()
+            .ok_or_else(|| {
+                ForkError::not_found(
+                    format!(
+                        "Transaction {} not found, only transactions after the fork version {} \
+                        are available",
+                        version, ledger.fork_version
+                    ),
+                    AptosErrorCode::TransactionNotFound,
+                )
+            })?;
+        self.transaction_output(data, bcs)
+    }
+
+    fn transaction_output(
+        &self,
+        (data, timestamp): (TransactionOnChainData, u64),
+        bcs: bool,
+    ) -> Result<Output, ForkError> {
+        if bcs {
+            Output::bcs(&TransactionData::OnChain(data))
+        } else {
+            Output::json(&self.render_transaction(&data, timestamp)?)
+        }
+    }
+
+    fn render_transaction(
+        &self,
+        data: &TransactionOnChainData,
+        timestamp: u64,
+    ) -> Result<Transaction, ForkError> {
+        let txn = match &data.transaction {
+            aptos_types::transaction::Transaction::UserTransaction(txn) => txn,
+            _ => return Err(ForkError::internal("Only user transactions are executed")),
+        };
+        let converter = self.state_view.as_converter(self.db.clone(), None);
+        let info = converter.into_transaction_info(
+            data.version,
+            &data.info,
+            data.accumulator_root_hash,
+            data.changes.clone(),
+            None,
+        );
+        let payload = converter.try_into_transaction_payload(txn.payload().clone())?;
+        let events = converter.try_into_events(&data.events)?;
+        Ok((txn, info, payload, events, timestamp).into())
+    }
+
+    fn view(
+        &self,
+        content_type: Option<&str>,
+        body: &[u8],
+        bcs: bool,
+    ) -> Result<Output, ForkError> {
+        let converter = self.state_view.as_converter(self.db.clone(), None);
+        let view_function: ViewFunction = if content_type == Some(BCS_VIEW_FUNCTION) {
+            bcs::from_bytes_with_limit(body, MAX_RECURSIVE_TYPES_ALLOWED as usize)
+                .map_err(|err| ForkError::bad_request(err, AptosErrorCode::InvalidInput))?
+        } else {
+            let request: ViewRequest = serde_json::from_slice(body)
+                .map_err(|err| ForkError::bad_request(err, AptosErrorCode::InvalidInput))?;
+            converter
+                .convert_view_function(request)
+                .map_err(|err| ForkError::bad_request(err, AptosErrorCode::InvalidInput))?
+        };
+
+        let output = AptosVM::execute_view_function(
+            &self.state_view,
+            view_function.module.clone(),
+            view_function.function.clone(),
+            view_function.ty_args.clone(),
+            view_function.args.clone(),
+            ApiConfig::default().max_gas_view_function,
+        );
+        let values = output.values.map_err(|err| {
+            let (message, vm_error_code) = converter.explain_view_function_error(&err);
+            let mut error = ForkError::bad_request(message, AptosErrorCode::InvalidInput);
+            error.error.vm_error_code = vm_error_code.map(|status_code| status_code as u64);
+            error
+        })?;
+
+        if bcs {
+            // The values are already BCS encoded, only the outer vector needs encoding.
+            let mut bytes = vec![];
+            serialize_uleb128(&mut bytes, values.len() as u64)?;
+            bytes.extend(values.into_iter().flatten());
+            return Ok(Output::encoded(bytes));
+        }
+        Output::json(&converter.try_into_view_function_values(&view_function, &values)?)
+    }
+
+    /// Mints `amount` octas of APT to `address`, creating the account if needed.
+    fn fund(&self, request: &FundRequest) -> Result<Output, ForkError> {
+        let _guard = self.execution_lock.lock().unwrap();
+
+        let env = AptosEnvironment::new(&self.state_view);
+        let vm = AptosVM::new(&env, &self.state_view);
+        let resolver = self.state_view.as_move_resolver();
+        let module_storage = self.state_view.as_aptos_code_storage(&env);
+        let gas_schedule = GasScheduleV2::fetch_config(&self.state_view)
+            .ok_or_else(|| anyhow!("Failed to fetch the gas schedule"))?;
+        let change_set_configs =
+            ChangeSetConfigs::unlimited_at_gas_feature_version(gas_schedule.feature_version);
+
+        let traversal_storage = TraversalStorage::new();
+        let mut session = vm.new_session(&resolver, SessionId::void(), None);
+        let address = MoveValue::Address(request.address);
+        for (module_id, function, args) in [
+            (&*ACCOUNT_MODULE, CREATE_ACCOUNT_IF_DOES_NOT_EXIST, vec![
+                address.clone(),
+            ]),
+            (
+                &*TRANSACTION_FEE_MODULE,
+                ident_str!("mint_and_refund"),
+                vec![address, MoveValue::U64(request.amount)],
+            ),
+        ] {
+            session
+                .execute_function_bypass_visibility(
+                    module_id,
+                    function,
+                    vec![],
+                    serialize_values(&args),
+                    &mut UnmeteredGasMeter,
+                    &mut TraversalContext::new(&traversal_storage),
+                    &module_storage,
+                )
+                .with_context(|| format!("Failed to call {}::{}", module_id, function))?;
+        }
+        let mut change_set = session
+            .finish(&change_set_configs, &module_storage)
+            .context("Failed to finish the session")?;
+        change_set
+            .try_materialize_aggregator_v1_delta_set(&resolver)
+            .context("Failed to materialize aggregator deltas")?;
+        let (write_set, _events) = change_set
+            .try_combine_into_storage_change_set(ModuleWriteSet::empty())
+            .map_err(|err| anyhow!("Failed to convert the change set: {:?}", err))?
+            .into_inner();
+        self.commit(&write_set)?;
+        Output::json(request)
+    }
+
+    fn set_impersonated(&self, address: AccountAddress, enabled: bool) -> Output {
+        let mut impersonated = self.impersonated.write().unwrap();
+        if enabled {
+            impersonated.insert(address);
+        } else {
+            impersonated.remove(&address);
+        }
+        Output::encoded(vec![]).with_status(StatusCode::NO_CONTENT)
+    }
+}
+
+fn to_on_chain_data(
+    version: u64,
+    txn: SignedTransaction,
+    output: &TransactionOutput,
+    status: ExecutionStatus,
+) -> TransactionOnChainData {
+    // There is no accumulator or state tree, so all state hashes are zero.
+    let txn = aptos_types::transaction::Transaction::UserTransaction(txn);
+    let info = TransactionInfo::new(
+        txn.hash(),
+        HashValue::zero(),
+        HashValue::zero(),
+        None,
+        output.gas_used(),
+        status,
+        None,
+    );
+    TransactionOnChainData {
+        version,
+        transaction: txn,
+        info,
+        events: output.events().to_vec(),
+        accumulator_root_hash: HashValue::zero(),
+        changes: output.write_set().clone(),
+    }
+}
+
+fn parse_address(address: &str) -> Result<AccountAddress, ForkError> {
+    Address::from_str(address)
+        .map(|address| *address.inner())
+        .map_err(|err| ForkError::bad_request(err, AptosErrorCode::InvalidInput))
+}
+
+fn parse_signed_transaction(
+    content_type: Option<&str>,
+    body: &[u8],
+) -> Result<SignedTransaction, ForkError> {
+    if content_type != Some(BCS_SIGNED_TRANSACTION) {
+        return Err(ForkError {
+            status: StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            error: AptosError::new_with_error_code(
+                format!(
+                    "Forks only accept transactions as {}",
+                    BCS_SIGNED_TRANSACTION
+                ),
+                AptosErrorCode::InvalidInput,
+            ),
+        });
+    }
+    bcs::from_bytes_with_limit(body, MAX_RECURSIVE_TYPES_ALLOWED as usize)
+        .map_err(|err| ForkError::bad_request(err, AptosErrorCode::InvalidInput))
+}
+
+/// A response body along with its status and content type.
+#[derive(Debug)]
+struct Output {
+    status: StatusCode,
+    content_type: &'static str,
+    body: Vec<u8>,
+}
+
+impl Output {
+    fn json<T: Serialize>(value: &T) -> Result<Self, ForkError> {
+        Ok(Self {
+            status: StatusCode::OK,
+            content_type: JSON,
+            body: serde_json::to_vec(value).context("Failed to serialize the response")?,
+        })
+    }
+
+    fn bcs<T: Serialize>(value: &T) -> Result<Self, ForkError> {
+        Ok(Self::encoded(
+            bcs::to_bytes(value).context("Failed to serialize the response")?,
+        ))
+    }
+
+    fn encoded(body: Vec<u8>) -> Self {
+        Self {
+            status: StatusCode::OK,
+            content_type: BCS,
+            body,
+        }
+    }
+
+    fn with_status(mut self, status: StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+}
+
+/// An error returned in the same format as the node API.
+#[derive(Debug)]
+struct ForkError {
+    status: StatusCode,
+    error: AptosError,
+}
+
+impl ForkError {
+    fn bad_request(err: impl Display, error_code: AptosErrorCode) -> Self {
+        Self {
+            status: StatusCode::BAD_REQUEST,
+            error: AptosError::new_with_error_code(err, error_code),
+        }
+    }
+
+    fn not_found(err: impl Display, error_code: AptosErrorCode) -> Self {
+        Self {
+            status: StatusCode::NOT_FOUND,
+            error: AptosError::new_with_error_code(err, error_code),
+        }
+    }
+
+    fn internal(err: impl Display) -> Self {
+        Self {
+            status: StatusCode::INTERNAL_SERVER_ERROR,
+            error: AptosError::new_with_error_code(err, AptosErrorCode::InternalError),
+        }
+    }
+}
+
+impl From<anyhow::Error> for ForkError {
+    fn from(err: anyhow::Error) -> Self {
+        Self::internal(err)
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct FundRequest {
+    address: AccountAddress,
+    /// The amount to mint, in octas.
+    amount: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct ImpersonateRequest {
+    address: AccountAddress,
+}
+
+fn accepts_bcs(req: &Request) -> bool {
+    req.headers()
+        .get(ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .is_some_and(|accept| accept.contains(BCS))
+}
+
+/// Runs a web server that serves the node API of the fork.
+async fn run_fork_server(chain: Arc<ForkedChain>, listen_address: SocketAddrV4) -> Result<()> {
+    let app = fork_api(chain).with(Tracing);
+    Server::new(TcpListener::bind(listen_address))
+        .name("fork")
+        .run(app)
+        .await?;
+    Err(anyhow!("Fork server exited unexpectedly"))
+}
+
+/// The node API of the fork, along with endpoints under `/v1/fork` to fund and impersonate
+/// accounts.
+fn fork_api(chain: Arc<ForkedChain>) -> impl Endpoint {
+    let api = Route::new()
+        .at("/", get(index))
+        .at("/-/healthy", get(healthy))
+        .at("/estimate_gas_price", get(estimate_gas_price))
+        .at("/accounts/:address", get(account))
+        .at("/accounts/:address/resources", get(resources))
+        .at("/accounts/:address/resource/:resource_type", get(resource))
+        .at("/accounts/:address/modules", get(modules))
+        .at("/accounts/:address/module/:module_name", get(module))
+        .at("/tables/:table_handle/item", post(table_item))
+        .at("/tables/:table_handle/raw_item", post(raw_table_item))
+        .at("/transactions", post(submit))
+        .at("/transactions/simulate", post(simulate))
+        .at("/transactions/by_hash/:hash", get(transaction_by_hash))
+        // Transactions are executed as soon as they are submitted, so there is nothing
+        // to wait for.
+        .at("/transactions/wait_by_hash/:hash", get(transaction_by_hash))
+        .at(
+            "/transactions/by_version/:version",
+            get(transaction_by_version),
+        )
+        .at("/view", post(view))
+        .at("/fork/fund", post(fund))
+        .at("/fork/impersonate", post(impersonate))
+        .at("/fork/stop_impersonating", post(stop_impersonating));
+    Route::new().nest("/v1", api).data(chain)
+}
+
+#[handler]
+async fn index(req: &Request, Data(chain): Data<&Arc<ForkedChain>>) -> Response {
+    let bcs = accepts_bcs(req);
+    chain.clone().respond(move |chain| chain.index(bcs)).await
+}
+
+#[handler]
+async fn healthy(Data(chain): Data<&Arc<ForkedChain>>) -> Response {
+    chain
+        .clone()
+        .respond(|_| Output::json(&serde_json::json!({ "message": "aptos-node:ok" })))
+        .await
+}
+
+#[handler]
+async fn estimate_gas_price(req: &Request, Data(chain): Data<&Arc<ForkedChain>>) -> Response {
+    let bcs = accepts_bcs(req);
+    chain
+        .clone()
+        .respond(move |chain| chain.estimate_gas_price(bcs))
+        .await
+}
+
+#[handler]
+async fn account(
+    req: &Request,
+    Path(address): Path<String>,
+    Data(chain): Data<&Arc<ForkedChain>>,
+) -> Response {
+    let bcs = accepts_bcs(req);
+    chain
+        .clone()
+        .respond(move |chain| chain.account(&address, bcs))
+        .await
+}
+
+#[handler]
+async fn resources(
+    req: &Request,
+    Path(address): Path<String>,
+    Data(chain): Data<&Arc<ForkedChain>>,
+) -> Response {
+    let bcs = accepts_bcs(req);
+    chain
+        .clone()
+        .respond(move |chain| chain.resources(&address, bcs))
+        .await
+}
+
+#[handler]
+async fn resource(
+    req: &Request,
+    Path((address, resource_type)): Path<(String, String)>,
+    Data(chain): Data<&Arc<ForkedChain>>,
+) -> Response {
+    let bcs = accepts_bcs(req);
+    chain
+        .clone()
+        .respond(move |chain| chain.resource(&address, &resource_type, bcs))
+        .await
+}
+
+#[handler]
+async fn module(
+    req: &Request,
+    Path((address, module_name)): Path<(String, String)>,
+    Data(chain): Data<&Arc<ForkedChain>>,
+) -> Response {
+    let bcs = accepts_bcs(req);
+    chain
+        .clone()
+        .respond(move |chain| chain.module(&address, &module_name, bcs))
+        .await
+}
+
+#[handler]
+async fn modules(
+    req: &Request,
+    Path(address): Path<String>,
+    Data(chain): Data<&Arc<ForkedChain>>,
+) -> Response {
+    let bcs = accepts_bcs(req);
+    chain
+        .clone()
+        .respond(move |chain| chain.modules(&address, bcs))
+        .await
+}
+
+#[handler]
+async fn table_item(
+    req: &Request,
+    Path(table_handle): Path<String>,
+    Json(request): Json<TableItemRequest>,
+    Data(chain): Data<&Arc<ForkedChain>>,
+) -> Response {
+    let bcs = accepts_bcs(req);
+    chain
+        .clone()
+        .respond(move |chain| chain.table_item(&table_handle, request, bcs))
+        .await
+}
+
+#[handler]
+async fn raw_table_item(
+    Path(table_handle): Path<String>,
+    Json(request): Json<RawTableItemRequest>,
+    Data(chain): Data<&Arc<ForkedChain>>,
+) -> Response {
+    chain
+        .clone()
+        .respond(move |chain| chain.raw_table_item(&table_handle, request))
+        .await
+}
+
+#[handler]
+async fn submit(req: &Request, body: Vec<u8>, Data(chain): Data<&Arc<ForkedChain>>) -> Response {
+    let content_type = req.content_type().map(str::to_string);
+    chain
+        .clone()
+        .respond(move |chain| chain.submit(content_type.as_deref(), &body))
+        .await
+}
+
+#[handler]
+async fn simulate(req: &Request, body: Vec<u8>, Data(chain): Data<&Arc<ForkedChain>>) -> Response {
+    let content_type = req.content_type().map(str::to_string);
+    let bcs = accepts_bcs(req);
+    chain
+        .clone()
+        .respond(move |chain| chain.simulate(content_type.as_deref(), &body, bcs))
+        .await
+}
+
+#[handler]
+async fn transaction_by_hash(
+    req: &Request,
+    Path(hash): Path<String>,
+    Data(chain): Data<&Arc<ForkedChain>>,
+) -> Response {
+    let bcs = accepts_bcs(req);
+    chain
+        .clone()
+        .respond(move |chain| chain.transaction_by_hash(&hash, bcs))
+        .await
+}
+
+#[handler]
+async fn transaction_by_version(
+    req: &Request,
+    Path(version): Path<String>,
+    Data(chain): Data<&Arc<ForkedChain>>,
+) -> Response {
+    let bcs = accepts_bcs(req);
+    chain
+        .clone()
+        .respond(move |chain| chain.transaction_by_version(&version, bcs))
+        .await
+}
+
+#[handler]
+async fn view(req: &Request, body: Vec<u8>, Data(chain): Data<&Arc<ForkedChain>>) -> Response {
+    let content_type = req.content_type().map(str::to_string);
+    let bcs = accepts_bcs(req);
+    chain
+        .clone()
+        .respond(move |chain| chain.view(content_type.as_deref(), &body, bcs))
+        .await
+}
+
+#[handler]
+async fn fund(Json(request): Json<FundRequest>, Data(chain): Data<&Arc<ForkedChain>>) -> Response {
+    chain
+        .clone()
+        .respond(move |chain| chain.fund(&request))
+        .await
+}
+
+#[handler]
+async fn impersonate(
+    Json(request): Json<ImpersonateRequest>,
+    Data(chain): Data<&Arc<ForkedChain>>,
+) -> Response {
+    chain
+        .clone()
+        .respond(move |chain| Ok(chain.set_impersonated(request.address, true)))
+        .await
+}
+
+#[handler]
+async fn stop_impersonating(
+    Json(request): Json<ImpersonateRequest>,
+    Data(chain): Data<&Arc<ForkedChain>>,
+) -> Response {
+    chain
+        .clone()
+        .respond(move |chain| Ok(chain.set_impersonated(request.address, false)))
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_cached_packages::aptos_stdlib;
+    use aptos_framework::natives::code::PackageMetadata;
+    use aptos_keygen::KeyGen;
+    use aptos_transaction_simulation::{Account, GENESIS_CHANGE_SET_HEAD};
+    use aptos_types::{
+        chain_id::ChainId,
+        state_store::state_value::StateValue,
+        transaction::{Transaction as CoreTransaction, Version},
+    };
+    use aptos_validator_interface::{AptosValidatorInterface, FilterCondition};
+    use move_core_types::language_storage::ModuleId;
+    use poem::{http::Method, IntoResponse};
+    use serde_json::{json, Value};
+
+    /// A remote network whose state is the one of a test genesis, at every version.
+    struct GenesisInterface(HashMap<StateKey, StateValue>);
+
+    #[async_trait]
+    impl AptosValidatorInterface for GenesisInterface {
+        async fn get_state_value_by_version(
+            &self,
+            state_key: &StateKey,
+            _version: Version,
+        ) -> Result<Option<StateValue>> {
+            Ok(self.0.get(state_key).cloned())
+        }
+
+        async fn get_committed_transactions(
+            &self,
+            _start: Version,
+            _limit: u64,
+        ) -> Result<(Vec<CoreTransaction>, Vec<TransactionInfo>)> {
+            unimplemented!("Forks never read remote transactions")
+        }
+
+        async fn get_and_filter_committed_transactions(
+            &self,
+            _start: Version,
+            _limit: u64,
+            _filter_condition: FilterCondition,
+            _package_cache: &mut HashMap<
+                ModuleId,
+                (
+                    AccountAddress,
+                    String,
+                    HashMap<(AccountAddress, String), PackageMetadata>,
+                ),
+            >,
+        ) -> Result<
+            Vec<(
+                u64,
+                CoreTransaction,
+                Option<(
+                    AccountAddress,
+                    String,
+                    HashMap<(AccountAddress, String), PackageMetadata>,
+                )>,
+            )>,
+        > {
+            unimplemented!("Forks never read remote transactions")
+        }
+
+        async fn get_latest_ledger_info_version(&self) -> Result<Version> {
+            Ok(0)
+        }
+
+        async fn get_version_by_account_sequence(
+            &self,
+            _account: AccountAddress,
+            _seq: u64,
+        ) -> Result<Option<Version>> {
+            unimplemented!("Forks never read remote transactions")
+        }
+
+        async fn get_account_ordered_transactions(
+            &self,
+            _account: AccountAddress,
+            _start_seq: u64,
+            _limit: u64,
+        ) -> Result<Vec<(Version, CoreTransaction)>> {
+            unimplemented!("Forks never read remote transactions")
+        }
+    }
+
+    #[async_trait]
+    impl AccountLister for GenesisInterface {
+        async fn list_account(
+            &self,
+            address: AccountAddress,
+            _version: u64,
+        ) -> Result<(Vec<StructTag>, Vec<Identifier>)> {
+            let mut resources = vec![];
+            let mut modules = vec![];
+            for (state_key, state_value) in &self.0 {
+                let StateKeyInner::AccessPath(access_path) = state_key.inner() else {
+                    continue;
+                };
+                if access_path.address != address {
+                    continue;
+                }
+                match access_path.get_path() {
+                    StatePath::Resource(struct_tag) => resources.push(struct_tag),
+                    StatePath::ResourceGroup(_) => resources.extend(
+                        bcs::from_bytes::<BTreeMap<StructTag, Vec<u8>>>(state_value.bytes())?
+                            .into_keys(),
+                    ),
+                    StatePath::Code(module_id) => modules.push(module_id.name().to_owned()),
+                }
+            }
+            Ok((resources, modules))
+        }
+    }
+
+    /// Forks a network right after its genesis.
+    fn fork_genesis() -> Arc<ForkedChain> {
+        let state = GENESIS_CHANGE_SET_HEAD
+            .write_set()
+            .write_op_iter()
+            .filter_map(|(state_key, write_op)| {
+                write_op
+                    .as_state_value()
+                    .map(|state_value| (state_key.clone(), state_value))
+            })
+            .collect();
+        let remote = Arc::new(GenesisInterface(state));
+        let state_view = DebuggerStateView::new(remote.clone(), 1);
+        Arc::new(ForkedChain::from_state_view(
+            state_view,
+            remote,
+            ForkLedger {
+                chain_id: ChainId::test().id(),
+                epoch: 1,
+                fork_version: 0,
+                fork_block_height: 0,
+                timestamp_usecs: 0,
+                transactions: vec![],
+                transactions_by_hash: HashMap::new(),
+            },
+            vec![],
+        ))
+    }
+
+    /// Calls `f` on a blocking thread, since reading the remote state blocks, and returns the
+    /// JSON body of the response.
+    async fn call<F>(chain: &Arc<ForkedChain>, f: F) -> Result<Value, ForkError>
+    where
+        F: FnOnce(&ForkedChain) -> Result<Output, ForkError> + Send + 'static,
+    {
+        let chain = chain.clone();
+        let output = tokio::task::spawn_blocking(move || f(&chain))
+            .await
+            .unwrap()?;
+        assert_eq!(output.content_type, JSON);
+        Ok(serde_json::from_slice(&output.body).unwrap())
+    }
+
+    /// Sends a request to the node API of the fork, and returns the status and the body of
+    /// the response.
+    async fn request(chain: &Arc<ForkedChain>, request: Request) -> (StatusCode, Vec<u8>) {
+        let response = fork_api(chain.clone())
+            .call(request)
+            .await
+            .unwrap()
+            .into_response();
+        let status = response.status();
+        (status, response.into_body().into_vec().await.unwrap())
+    }
+
+    async fn get_json(chain: &Arc<ForkedChain>, path: &str) -> (StatusCode, Value) {
+        let (status, body) = request(
+            chain,
+            Request::builder().uri(path.parse().unwrap()).finish(),
+        )
+        .await;
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    async fn post_json(
+        chain: &Arc<ForkedChain>,
+        path: &str,
+        body: &Value,
+    ) -> (StatusCode, Vec<u8>) {
+        request(
+            chain,
+            Request::builder()
+                .method(Method::POST)
+                .uri(path.parse().unwrap())
+                .content_type(JSON)
+                .body(serde_json::to_vec(body).unwrap()),
+        )
+        .await
+    }
+
+    async fn fund(chain: &Arc<ForkedChain>, address: AccountAddress, amount: u64) {
+        call(chain, move |chain| {
+            chain.fund(&FundRequest { address, amount })
+        })
+        .await
+        .unwrap();
+    }
+
+    async fn balance(chain: &Arc<ForkedChain>, address: AccountAddress) -> u64 {
+        let request = json!({
+            "function": "0x1::coin::balance",
+            "type_arguments": ["0x1::aptos_coin::AptosCoin"],
+            "arguments": [address.to_hex_literal()],
+        });
+        let values = call(chain, move |chain| {
+            chain.view(Some(JSON), &serde_json::to_vec(&request).unwrap(), false)
+        })
+        .await
+        .unwrap();
+        values[0].as_str().unwrap().parse().unwrap()
+    }
+
+    async fn submit(chain: &Arc<ForkedChain>, txn: SignedTransaction) -> Result<Value, ForkError> {
+        let body = bcs::to_bytes(&txn).unwrap();
+        call(chain, move |chain| {
+            chain.submit(Some(BCS_SIGNED_TRANSACTION), &body)
+        })
+        .await
+    }
+
+    async fn transaction_by_hash(chain: &Arc<ForkedChain>, hash: &Value) -> Value {
+        let hash = hash.as_str().unwrap().to_string();
+        call(chain, move |chain| chain.transaction_by_hash(&hash, false))
+            .await
+            .unwrap()
+    }
+
+    fn transfer(sender: &Account, sequence_number: u64, to: AccountAddress) -> SignedTransaction {
+        sender
+            .transaction()
+            .sequence_number(sequence_number)
+            .payload(aptos_stdlib::aptos_account_transfer(to, 100))
+            .gas_unit_price(100)
+            .ttl(u64::MAX)
+            .sign()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_fund_and_view() {
+        let chain = fork_genesis();
+        let account = Account::new();
+        fund(&chain, *account.address(), 1_000).await;
+        assert_eq!(balance(&chain, *account.address()).await, 1_000);
+        fund(&chain, *account.address(), 500).await;
+        assert_eq!(balance(&chain, *account.address()).await, 1_500);
+        // Funding does not execute a transaction
+        assert_eq!(chain.ledger_info().ledger_version.0, 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_view_abort() {
+        let chain = fork_genesis();
+        // There is no stake pool at this address
+        let request = json!({
+            "function": "0x1::stake::get_stake",
+            "type_arguments": [],
+            "arguments": ["0x1234"],
+        });
+        let err = call(&chain, move |chain| {
+            chain.view(Some(JSON), &serde_json::to_vec(&request).unwrap(), false)
+        })
+        .await
+        .unwrap_err();
+        assert_eq!(err.status, StatusCode::BAD_REQUEST);
+        assert_eq!(err.error.error_code, AptosErrorCode::InvalidInput);
+        assert!(
+            err.error.message.starts_with("Move abort in 0x1::stake"),
+            "{}",
+            err.error.message
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_submit_and_get_by_hash() {
+        let chain = fork_genesis();
+        let sender = Account::new();
+        let receiver = Account::new();
+        fund(&chain, *sender.address(), 100_000_000).await;
+
+        let first = submit(&chain, transfer(&sender, 0, *receiver.address()))
+            .await
+            .unwrap();
+        let second = submit(&chain, transfer(&sender, 1, *receiver.address()))
+            .await
+            .unwrap();
+
+        let first = transaction_by_hash(&chain, &first["hash"]).await;
+        let second = transaction_by_hash(&chain, &second["hash"]).await;
+        assert_eq!(first["success"], true, "{}", first);
+        assert_eq!(second["success"], true, "{}", second);
+        assert_eq!(first["version"], "1");
+        assert_eq!(second["version"], "2");
+        assert_eq!(balance(&chain, *receiver.address()).await, 200);
+
+        // Each transaction is executed in a block of its own, which advances the clock.
+        let first_timestamp: u64 = first["timestamp"].as_str().unwrap().parse().unwrap();
+        let second_timestamp: u64 = second["timestamp"].as_str().unwrap().parse().unwrap();
+        assert!(first_timestamp > 0);
+        assert!(second_timestamp > first_timestamp);
+        let ledger_info = chain.ledger_info();
+        assert_eq!(ledger_info.ledger_version.0, 2);
+        assert_eq!(ledger_info.block_height.0, 2);
+        assert_eq!(ledger_info.ledger_timestamp.0, second_timestamp);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_impersonation() {
+        let chain = fork_genesis();
+        let owner = Account::new();
+        fund(&chain, *owner.address(), 100_000_000).await;
+        let impostor = Account::new_from_addr_with_new_keypair_from_seed(
+            *owner.address(),
+            &mut KeyGen::from_os_rng(),
+        );
+        let receiver = *Account::new().address();
+
+        // The transaction is signed with a key that does not match the authentication key
+        let err = submit(&chain, transfer(&impostor, 0, receiver))
+            .await
+            .unwrap_err();
+        assert_eq!(err.status, StatusCode::BAD_REQUEST);
+        assert_eq!(err.error.error_code, AptosErrorCode::VmError);
+        assert_eq!(chain.ledger_info().ledger_version.0, 0);
+
+        chain.set_impersonated(*owner.address(), true);
+        let pending = submit(&chain, transfer(&impostor, 0, receiver))
+            .await
+            .unwrap();
+        let txn = transaction_by_hash(&chain, &pending["hash"]).await;
+        assert_eq!(txn["success"], true, "{}", txn);
+
+        chain.set_impersonated(*owner.address(), false);
+        let err = submit(&chain, transfer(&impostor, 1, receiver))
+            .await
+            .unwrap_err();
+        assert_eq!(err.error.error_code, AptosErrorCode::VmError);
+        assert_eq!(chain.ledger_info().ledger_version.0, 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_list_resources_and_modules() {
+        let chain = fork_genesis();
+        let account = Account::new();
+        fund(&chain, *account.address(), 1_000).await;
+        let has_resource = |resources: &Value, resource_type: &str| {
+            resources
+                .as_array()
+                .unwrap()
+                .iter()
+                .any(|resource| resource["type"] == resource_type)
+        };
+
+        // The resources and modules of the framework are listed from the remote network
+        let (status, resources) = get_json(&chain, "/v1/accounts/0x1/resources").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(has_resource(&resources, "0x1::chain_id::ChainId"));
+        let (status, modules) = get_json(&chain, "/v1/accounts/0x1/modules").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(modules
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|module| module["abi"]["name"] == "coin"));
+
+        // The account only exists on the fork
+        let path = format!(
+            "/v1/accounts/{}/resources",
+            account.address().to_hex_literal()
+        );
+        let (status, resources) = get_json(&chain, &path).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(has_resource(&resources, "0x1::account::Account"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_table_item() {
+        let chain = fork_genesis();
+        let table_handle = AccountAddress::random();
+        chain
+            .state_view
+            .set_state_value(
+                StateKey::table_item(&TableHandle(table_handle), &bcs::to_bytes(&1u64).unwrap()),
+                StateValue::new_legacy(bcs::to_bytes(&42u64).unwrap().into()),
+            )
+            .unwrap();
+
+        let path = format!("/v1/tables/{}/item", table_handle.to_hex_literal());
+        let (status, body) = post_json(
+            &chain,
+            &path,
+            &json!({ "key_type": "u64", "value_type": "u64", "key": "1" }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(serde_json::from_slice::<Value>(&body).unwrap(), "42");
+
+        let (status, body) = post_json(
+            &chain,
+            &path,
+            &json!({ "key_type": "u64", "value_type": "u64", "key": "2" }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let error: AptosError = serde_json::from_slice(&body).unwrap();
+        assert_eq!(error.error_code, AptosErrorCode::TableItemNotFound);
+
+        let path = format!("/v1/tables/{}/raw_item", table_handle.to_hex_literal());
+        let key = hex::encode(bcs::to_bytes(&1u64).unwrap());
+        let (status, body) =
+            post_json(&chain, &path, &json!({ "key": format!("0x{}", key) })).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(bcs::from_bytes::<u64>(&body).unwrap(), 42);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_wait_by_hash() {
+        let chain = fork_genesis();
+        let sender = Account::new();
+        fund(&chain, *sender.address(), 100_000_000).await;
+        let pending = submit(&chain, transfer(&sender, 0, *Account::new().address()))
+            .await
+            .unwrap();
+
+        let hash = pending["hash"].as_str().unwrap();
+        let path = format!("/v1/transactions/wait_by_hash/{}", hash);
+        let (status, txn) = get_json(&chain, &path).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(txn["hash"], hash);
+        assert_eq!(txn["success"], true, "{}", txn);
+    }
+}
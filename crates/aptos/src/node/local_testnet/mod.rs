@@ -9,6 +9,7 @@ mod utils;
 // This is to allow external crates to use the localnode.
 pub mod docker;
 pub mod faucet;
+pub mod fork;
 pub mod health_checker;
 pub mod indexer_api;
 pub mod node;
@@ -17,6 +18,7 @@ pub mod traits;
 
 use self::{
     faucet::FaucetArgs,
+    fork::{ForkArgs, ForkManager},
     indexer_api::IndexerApiArgs,
     logging::ThreadNameMakeWriter,
     node::NodeArgs,
@@ -59,6 +61,9 @@ const TESTNET_FOLDER: &str = "testnet";
 /// This localnet will run it's own genesis and run as a single node network
 /// locally. A faucet and grpc transaction stream will run alongside the node unless
 /// you specify otherwise with --no-faucet and --no-txn-stream respectively.
+///
+/// With --fork-url, the localnet instead forks an existing network, serving its state
+/// locally and fetching it from a remote node as needed.
 #[derive(Parser)]
 pub struct RunLocalnet {
     /// The directory to save all files for the node
@@ -81,6 +86,9 @@ pub struct RunLocalnet {
     #[clap(flatten)]
     faucet_args: FaucetArgs,
 
+    #[clap(flatten)]
+    fork_args: ForkArgs,
+
     #[clap(flatten)]
     postgres_args: PostgresArgs,
 
@@ -245,67 +253,75 @@ impl CliCommand<()> for RunLocalnet {
 
         let mut managers: Vec<Box<dyn ServiceManager>> = Vec::new();
 
-        // Build the node manager. We do this unconditionally.
-        let node_manager = NodeManager::new(&self, bind_to, test_dir.clone())
-            .context("Failed to build node service manager")?;
-        let node_health_checkers = node_manager.get_health_checkers();
-
-        // If configured to do so, build the faucet manager.
-        if !self.faucet_args.no_faucet {
-            let faucet_manager = FaucetManager::new(
-                &self,
-                node_health_checkers.clone(),
-                bind_to,
-                test_dir.clone(),
-                node_manager.get_node_api_url(),
-            )
-            .context("Failed to build faucet service manager")?;
-            managers.push(Box::new(faucet_manager));
-        }
+        if self.fork_args.is_fork() {
+            // When forking, the fork manager serves the node API in place of a node. The
+            // faucet, txn stream and indexer all need a real node, so we don't run them.
+            let fork_manager =
+                ForkManager::new(&self, bind_to).context("Failed to build fork service manager")?;
+            managers.push(Box::new(fork_manager));
+        } else {
+            // Build the node manager.
+            let node_manager = NodeManager::new(&self, bind_to, test_dir.clone())
+                .context("Failed to build node service manager")?;
+            let node_health_checkers = node_manager.get_health_checkers();
+
+            // If configured to do so, build the faucet manager.
+            if !self.faucet_args.no_faucet {
+                let faucet_manager = FaucetManager::new(
+                    &self,
+                    node_health_checkers.clone(),
+                    bind_to,
+                    test_dir.clone(),
+                    node_manager.get_node_api_url(),
+                )
+                .context("Failed to build faucet service manager")?;
+                managers.push(Box::new(faucet_manager));
+            }
 
-        if self.indexer_api_args.with_indexer_api {
-            let postgres_manager = postgres::PostgresManager::new(&self, test_dir.clone())
-                .context("Failed to build postgres service manager")?;
-            let postgres_health_checkers = postgres_manager.get_health_checkers();
-            managers.push(Box::new(postgres_manager));
+            if self.indexer_api_args.with_indexer_api {
+                let postgres_manager = postgres::PostgresManager::new(&self, test_dir.clone())
+                    .context("Failed to build postgres service manager")?;
+                let postgres_health_checkers = postgres_manager.get_health_checkers();
+                managers.push(Box::new(postgres_manager));
+
+                let processor_preqrequisite_healthcheckers =
+                    [node_health_checkers, postgres_health_checkers]
+                        .into_iter()
+                        .flatten()
+                        .collect();
+                let processor_managers = ProcessorManager::many_new(
+                    &self,
+                    processor_preqrequisite_healthcheckers,
+                    node_manager.get_data_service_url(),
+                    self.postgres_args.get_connection_string(None, true),
+                )
+                .context("Failed to build processor service managers")?;
+
+                let processor_health_checkers = processor_managers
+                    .iter()
+                    .flat_map(|m| m.get_health_checkers())
+                    .collect();
 
-            let processor_preqrequisite_healthcheckers =
-                [node_health_checkers, postgres_health_checkers]
+                let mut processor_managers = processor_managers
                     .into_iter()
-                    .flatten()
+                    .map(|m| Box::new(m) as Box<dyn ServiceManager>)
                     .collect();
-            let processor_managers = ProcessorManager::many_new(
-                &self,
-                processor_preqrequisite_healthcheckers,
-                node_manager.get_data_service_url(),
-                self.postgres_args.get_connection_string(None, true),
-            )
-            .context("Failed to build processor service managers")?;
-
-            let processor_health_checkers = processor_managers
-                .iter()
-                .flat_map(|m| m.get_health_checkers())
-                .collect();
-
-            let mut processor_managers = processor_managers
-                .into_iter()
-                .map(|m| Box::new(m) as Box<dyn ServiceManager>)
-                .collect();
-            managers.append(&mut processor_managers);
-
-            let indexer_api_manager = IndexerApiManager::new(
-                &self,
-                processor_health_checkers,
-                test_dir.clone(),
-                self.postgres_args.get_connection_string(None, false),
-            )
-            .context("Failed to build indexer API service manager")?;
-            managers.push(Box::new(indexer_api_manager));
-        }
+                managers.append(&mut processor_managers);
+
+                let indexer_api_manager = IndexerApiManager::new(
+                    &self,
+                    processor_health_checkers,
+                    test_dir.clone(),
+                    self.postgres_args.get_connection_string(None, false),
+                )
+                .context("Failed to build indexer API service manager")?;
+                managers.push(Box::new(indexer_api_manager));
+            }
 
-        // We put the node manager into managers at the end just so we have access to
-        // it before this so we can call things like `node_manager.get_node_api_url()`.
-        managers.push(Box::new(node_manager));
+            // We put the node manager into managers at the end just so we have access to
+            // it before this so we can call things like `node_manager.get_node_api_url()`.
+            managers.push(Box::new(node_manager));
+        }
 
         // Get the healthcheckers from all the managers. We'll pass to this
         // `wait_for_startup`.
@@ -65,6 +65,7 @@ use aptos_sdk::move_types::{account_address::AccountAddress, language_storage::M
 use aptos_temppath::TempPath;
 use aptos_types::on_chain_config::ValidatorSet;
 use move_core_types::ident_str;
+use move_unit_test::{fuzzer::DEFAULT_FUZZ_RUNS, DEFAULT_GAS_REGRESSION_THRESHOLD};
 use reqwest::Url;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -858,6 +859,15 @@ impl CliTestFramework {
             ignore_compile_warnings: false,
             compute_coverage: false,
             dump_state: false,
+            fuzz_runs: DEFAULT_FUZZ_RUNS,
+            fuzz_seed: None,
+            gas_report: false,
+            gas_baseline: None,
+            update_gas_baseline: false,
+            gas_regression_threshold: DEFAULT_GAS_REGRESSION_THRESHOLD,
+            gas_profile: false,
+            #[cfg(feature = "move-debugger")]
+            debug_port: None,
         }
         .execute()
         .await
@@ -9,12 +9,13 @@ use aptos_block_partitioner::{BlockPartitioner, PartitionerConfig};
 use aptos_crypto::HashValue;
 use aptos_experimental_runtimes::thread_manager::optimal_min_len;
 use aptos_logger::info;
+use aptos_transaction_shuffler::TransactionShuffler;
 use aptos_types::{
     block_executor::partitioner::{ExecutableBlock, ExecutableTransactions},
     transaction::{signature_verified_transaction::SignatureVerifiedTransaction, Transaction},
 };
 use rayon::iter::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
-use std::time::Instant;
+use std::{sync::Arc, time::Instant};
 
 /// Smallest number of transactions Rayon should put into a single worker task.
 /// Same as in consensus/src/execution_pipeline.rs
@@ -32,6 +33,8 @@ pub(crate) struct BlockPreparationStage {
     num_executor_shards: usize,
     /// When execution sharding is enabled, partitioner that splits block into shards
     maybe_partitioner: Option<Box<dyn BlockPartitioner>>,
+    /// When set, shuffler that reorders transactions of a block before execution
+    maybe_shuffler: Option<Arc<dyn TransactionShuffler>>,
 }
 
 impl BlockPreparationStage {
@@ -39,6 +42,7 @@ impl BlockPreparationStage {
         num_sig_verify_threads: usize,
         num_shards: usize,
         partitioner_config: &dyn PartitionerConfig,
+        maybe_shuffler: Option<Arc<dyn TransactionShuffler>>,
     ) -> Self {
         let maybe_partitioner = if num_shards == 0 {
             None
@@ -56,6 +60,7 @@ impl BlockPreparationStage {
             num_executor_shards: num_shards,
             num_blocks_processed: 0,
             maybe_partitioner,
+            maybe_shuffler,
             sig_verify_pool,
         }
     }
@@ -82,6 +87,15 @@ impl BlockPreparationStage {
                     .map(|t| t.into())
                     .collect::<Vec<_>>()
             });
+        let sig_verified_txns = match &self.maybe_shuffler {
+            None => sig_verified_txns,
+            Some(shuffler) => {
+                let _timer = TIMER.with_label_values(&["shuffle"]).start_timer();
+                shuffler
+                    .signature_verified_transaction_iterator(sig_verified_txns)
+                    .collect()
+            },
+        };
         let block: ExecutableBlock = match &self.maybe_partitioner {
             None => (block_id, sig_verified_txns).into(),
             Some(partitioner) => {
@@ -6,6 +6,7 @@ use crate::pipeline::{CommitBlockMessage, LedgerUpdateMessage};
 use aptos_executor::block_executor::BlockExecutor;
 use aptos_executor_types::BlockExecutorTrait;
 use aptos_infallible::Mutex;
+use aptos_transaction_shuffler::ConflictHintLearner;
 use aptos_vm::VMBlockExecutor;
 use move_core_types::language_storage::StructTag;
 use std::{
@@ -27,6 +28,7 @@ pub struct LedgerUpdateStage<V> {
     allow_discards: bool,
     allow_retries: bool,
     event_summary: Arc<Mutex<BTreeMap<(usize, StructTag), usize>>>,
+    conflict_hint_learner: Option<Arc<ConflictHintLearner>>,
 }
 
 impl<V> LedgerUpdateStage<V>
@@ -40,6 +42,7 @@ where
         allow_discards: bool,
         allow_retries: bool,
         event_summary: Arc<Mutex<BTreeMap<(usize, StructTag), usize>>>,
+        conflict_hint_learner: Option<Arc<ConflictHintLearner>>,
     ) -> Self {
         Self {
             executor,
@@ -48,6 +51,7 @@ where
             allow_discards,
             allow_retries,
             event_summary,
+            conflict_hint_learner,
         }
     }
 
@@ -87,6 +91,11 @@ where
             }
         }
 
+        if let Some(learner) = &self.conflict_hint_learner {
+            let to_commit = &output.execution_output.to_commit;
+            learner.observe_outputs(&to_commit.transactions, &to_commit.transaction_outputs);
+        }
+
         match &self.commit_processing {
             CommitProcessing::SendToQueue(commit_sender) => {
                 let msg = CommitBlockMessage {
@@ -643,7 +643,7 @@ mod tests {
         pipeline::PipelineConfig,
         transaction_executor::BENCHMARKS_BLOCK_EXECUTOR_ONCHAIN_CONFIG,
         transaction_generator::TransactionGenerator,
-        BenchmarkWorkload,
+        BenchmarkWorkload, SingleRunResults,
     };
     use aptos_config::config::NO_OP_STORAGE_PRUNER_CONFIG;
     use aptos_crypto::HashValue;
@@ -652,11 +652,12 @@ mod tests {
     use aptos_sdk::{transaction_builder::aptos_stdlib, types::LocalAccount};
     use aptos_temppath::TempPath;
     use aptos_transaction_generator_lib::WorkflowProgress;
+    use aptos_transaction_shuffler::ConflictHintLearner;
     use aptos_transaction_workloads_lib::args::TransactionTypeArg;
     use aptos_types::{
         access_path::Path,
         account_address::AccountAddress,
-        on_chain_config::{FeatureFlag, Features},
+        on_chain_config::{FeatureFlag, Features, TransactionShufflerType},
         state_store::state_key::inner::StateKeyInner,
         transaction::{
             signature_verified_transaction::into_signature_verified_block, Transaction,
@@ -670,6 +671,7 @@ mod tests {
     use std::{
         collections::{BTreeMap, HashMap},
         fs,
+        sync::Arc,
     };
 
     #[test]
@@ -1003,4 +1005,104 @@ mod tests {
             NativeParallelUncoordinatedBlockExecutor<NativeNoStorageRawTransactionExecutor>,
         >(Some(TransactionTypeArg::NoOp), false);
     }
+
+    fn run_mixed_benchmark(
+        mix: &[(TransactionTypeArg, usize)],
+        pipeline_config: PipelineConfig,
+    ) -> SingleRunResults {
+        let storage_dir = TempPath::new();
+        let checkpoint_dir = TempPath::new();
+
+        let mut features = default_benchmark_features();
+        features.enable(FeatureFlag::NEW_ACCOUNTS_DEFAULT_TO_FA_APT_STORE);
+        features.enable(FeatureFlag::OPERATIONS_DEFAULT_TO_FA_APT_STORE);
+
+        crate::db_generator::create_db_with_accounts::<AptosVMBlockExecutor>(
+            500,             /* num_accounts */
+            100_000_000_000, /* init_account_balance */
+            100,             /* block_size */
+            storage_dir.as_ref(),
+            NO_OP_STORAGE_PRUNER_CONFIG, /* prune_window */
+            true,
+            false,
+            PipelineConfig::default(),
+            features.clone(),
+            false,
+        );
+
+        super::run_benchmark::<AptosVMBlockExecutor>(
+            200, /* block_size */
+            20,  /* num_blocks */
+            BenchmarkWorkload::TransactionMix(
+                mix.iter()
+                    .map(|(t, weight)| {
+                        (
+                            t.materialize(1, true, WorkflowProgress::MoveByPhases),
+                            *weight,
+                        )
+                    })
+                    .collect(),
+            ),
+            1,   /* transactions per sender */
+            200, /* num_main_signer_accounts */
+            200, /* num_dst_pool_accounts */
+            storage_dir.as_ref(),
+            checkpoint_dir,
+            true,
+            NO_OP_STORAGE_PRUNER_CONFIG,
+            false,
+            pipeline_config,
+            features,
+            false,
+        )
+    }
+
+    /// Compares the speculative aborts per transaction with and without conflict aware
+    /// shuffling, on a mix of transactions writing to a few hot locations and independent
+    /// transfers. Slow, run with `--ignored --nocapture` to see the numbers.
+    #[test]
+    #[ignore]
+    fn test_conflict_aware_shuffler_aborts() {
+        aptos_logger::Logger::new().init();
+        AptosVM::set_concurrency_level_once(8);
+
+        let mix = [
+            (TransactionTypeArg::ModifyGlobalResource, 1),
+            (TransactionTypeArg::ResourceGroupsGlobalWriteTag1KB, 1),
+            (TransactionTypeArg::AptFaTransfer, 8),
+        ];
+        let shuffler_type = TransactionShufflerType::ConflictAware {
+            sender_spread_factor: 32,
+            conflict_spread_factor: 8,
+            max_lookahead: 64,
+            learned_hints: vec![],
+        };
+
+        let unshuffled = run_mixed_benchmark(&mix, PipelineConfig {
+            allow_aborts: true,
+            ..Default::default()
+        });
+        let shuffled = run_mixed_benchmark(&mix, PipelineConfig {
+            allow_aborts: true,
+            transaction_shuffler_type: Some(shuffler_type.clone()),
+            ..Default::default()
+        });
+        let learner = Arc::new(ConflictHintLearner::new(10, 0.5));
+        let learning = run_mixed_benchmark(&mix, PipelineConfig {
+            allow_aborts: true,
+            transaction_shuffler_type: Some(shuffler_type),
+            conflict_hint_learner: Some(learner.clone()),
+            ..Default::default()
+        });
+
+        let aborts = [&unshuffled, &shuffled, &learning]
+            .map(|results| results.measurements.speculative_aborts_per_txn());
+        println!(
+            "Speculative aborts per txn: no shuffling {:.3}, conflict aware {:.3}, conflict aware with learned hints {:.3}",
+            aborts[0], aborts[1], aborts[2]
+        );
+        assert!(!learner.hints().is_empty());
+        assert!(aborts[1] <= aborts[0]);
+        assert!(aborts[2] <= aborts[0]);
+    }
 }
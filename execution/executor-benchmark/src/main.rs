@@ -35,15 +35,18 @@ use aptos_metrics_core::{register_int_gauge, IntGauge};
 use aptos_profiler::{ProfilerConfig, ProfilerHandler};
 use aptos_push_metrics::MetricsPusher;
 use aptos_transaction_generator_lib::WorkflowProgress;
+use aptos_transaction_shuffler::ConflictHintLearner;
 use aptos_transaction_workloads_lib::args::TransactionTypeArg;
-use aptos_types::on_chain_config::{FeatureFlag, Features};
+use aptos_types::on_chain_config::{FeatureFlag, Features, TransactionShufflerType};
 use aptos_vm::{aptos_vm::AptosVMBlockExecutor, AptosVM, VMBlockExecutor};
 use aptos_vm_environment::prod_configs::set_paranoid_type_checks;
 use clap::{Parser, Subcommand, ValueEnum};
 use once_cell::sync::Lazy;
 use std::{
+    fs,
     net::SocketAddr,
     path::PathBuf,
+    sync::Arc,
     time::{SystemTime, UNIX_EPOCH},
 };
 
@@ -150,6 +153,9 @@ pub struct PipelineOpt {
     /// Sharding configuration.
     #[clap(flatten)]
     sharding_opt: ShardingOpt,
+    /// Transaction shuffling configuration.
+    #[clap(flatten)]
+    shuffler_opt: ShufflerOpt,
 }
 
 impl PipelineOpt {
@@ -165,11 +171,84 @@ impl PipelineOpt {
             num_generator_workers: self.num_generator_workers,
             partitioner_config: self.sharding_opt.partitioner_config(),
             num_sig_verify_threads: self.num_sig_verify_threads,
+            transaction_shuffler_type: self.shuffler_opt.transaction_shuffler_type(),
+            conflict_hint_learner: self.shuffler_opt.conflict_hint_learner(),
             print_transactions: false,
         }
     }
 }
 
+#[derive(Parser, Debug, ValueEnum, Clone)]
+enum TransactionShufflerTypeOpt {
+    /// Spread transactions by sender and by use case (contract address)
+    UseCaseAware,
+    /// Spread transactions by sender and by the storage locations they are expected to conflict on
+    ConflictAware,
+}
+
+#[derive(Debug, Parser)]
+struct ShufflerOpt {
+    /// Shuffle transactions within each block before execution, the same way consensus does.
+    /// Compare the reported speculative aborts per transaction against a run without shuffling,
+    /// on a workload with hot contracts, to see the effect of the shuffler.
+    #[clap(long, value_enum, ignore_case = true)]
+    transaction_shuffler_type: Option<TransactionShufflerTypeOpt>,
+    #[clap(long, default_value = "32")]
+    shuffler_sender_spread_factor: usize,
+    /// User use case spread factor for the use case aware shuffler,
+    /// and conflict spread factor for the conflict aware shuffler.
+    #[clap(long, default_value = "4")]
+    shuffler_conflict_spread_factor: usize,
+    #[clap(long, default_value = "64")]
+    shuffler_max_lookahead: usize,
+    /// Learn conflict hints from the write sets of the executed blocks, and use them when
+    /// shuffling the following blocks with the conflict aware shuffler.
+    #[clap(long)]
+    learn_conflict_hints: bool,
+    /// Fraction of the calls to an entry function that must write a location for it to become
+    /// a hint of the function.
+    #[clap(long, default_value = "0.5", requires = "learn_conflict_hints")]
+    conflict_hint_min_frequency: f64,
+    /// JSON file to write the learned conflict hints to, in the format of the `learned_hints`
+    /// of the on-chain execution config.
+    #[clap(long, requires = "learn_conflict_hints")]
+    learned_conflict_hints_output: Option<PathBuf>,
+}
+
+/// Number of calls to an entry function needed before learning its conflict hints.
+const CONFLICT_HINT_MIN_SAMPLES: u64 = 10;
+
+impl ShufflerOpt {
+    fn conflict_hint_learner(&self) -> Option<Arc<ConflictHintLearner>> {
+        self.learn_conflict_hints.then(|| {
+            Arc::new(ConflictHintLearner::new(
+                CONFLICT_HINT_MIN_SAMPLES,
+                self.conflict_hint_min_frequency,
+            ))
+        })
+    }
+
+    fn transaction_shuffler_type(&self) -> Option<TransactionShufflerType> {
+        self.transaction_shuffler_type
+            .as_ref()
+            .map(|shuffler_type| match shuffler_type {
+                TransactionShufflerTypeOpt::UseCaseAware => TransactionShufflerType::UseCaseAware {
+                    sender_spread_factor: self.shuffler_sender_spread_factor,
+                    platform_use_case_spread_factor: 0,
+                    user_use_case_spread_factor: self.shuffler_conflict_spread_factor,
+                },
+                TransactionShufflerTypeOpt::ConflictAware => {
+                    TransactionShufflerType::ConflictAware {
+                        sender_spread_factor: self.shuffler_sender_spread_factor,
+                        conflict_spread_factor: self.shuffler_conflict_spread_factor,
+                        max_lookahead: self.shuffler_max_lookahead,
+                        learned_hints: vec![],
+                    }
+                },
+            })
+    }
+}
+
 #[derive(Debug, Parser)]
 struct ShardingOpt {
     #[clap(long, default_value = "0")]
@@ -523,6 +602,9 @@ where
                 }
             }
 
+            let pipeline_config = opt.pipeline_opt.pipeline_config();
+            let conflict_hint_learner = pipeline_config.conflict_hint_learner.clone();
+
             aptos_executor_benchmark::run_benchmark::<E>(
                 opt.block_size,
                 blocks,
@@ -535,10 +617,23 @@ where
                 opt.verify_sequence_numbers,
                 opt.pruner_opt.pruner_config(),
                 opt.enable_storage_sharding,
-                opt.pipeline_opt.pipeline_config(),
+                pipeline_config,
                 get_init_features(enable_feature, disable_feature),
                 opt.use_keyless_accounts,
             );
+
+            if let (Some(learner), Some(output)) = (
+                conflict_hint_learner,
+                &opt.pipeline_opt.shuffler_opt.learned_conflict_hints_output,
+            ) {
+                let hints = learner.hints();
+                println!(
+                    "Writing {} learned conflict hints to {}",
+                    hints.len(),
+                    output.display()
+                );
+                fs::write(output, serde_json::to_string_pretty(&hints).unwrap()).unwrap();
+            }
         },
         Command::AddAccounts {
             data_dir,
@@ -236,6 +236,10 @@ pub struct OverallMeasurement {
 }
 
 impl OverallMeasurement {
+    pub fn speculative_aborts_per_txn(&self) -> f64 {
+        self.delta_gas.speculative_abort_count as f64 / self.num_txns.max(1) as f64
+    }
+
     pub fn print_end(&self) {
         let num_txns = self.num_txns as f64;
 
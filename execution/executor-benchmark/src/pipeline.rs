@@ -14,8 +14,10 @@ use aptos_executor::block_executor::BlockExecutor;
 use aptos_executor_types::{state_compute_result::StateComputeResult, BlockExecutorTrait};
 use aptos_infallible::Mutex;
 use aptos_logger::info;
+use aptos_transaction_shuffler::{create_transaction_shuffler_with_learner, ConflictHintLearner};
 use aptos_types::{
     block_executor::partitioner::ExecutableBlock,
+    on_chain_config::TransactionShufflerType,
     transaction::{Transaction, TransactionPayload, Version},
 };
 use aptos_vm::VMBlockExecutor;
@@ -48,6 +50,12 @@ pub struct PipelineConfig {
     pub partitioner_config: PartitionerV2Config,
     #[derivative(Default(value = "8"))]
     pub num_sig_verify_threads: usize,
+    /// If set, transactions of each block are shuffled the same way consensus would,
+    /// before being executed.
+    pub transaction_shuffler_type: Option<TransactionShufflerType>,
+    /// If set, conflict hints are learned from the executed blocks, and used by the conflict
+    /// aware shuffler for the following blocks.
+    pub conflict_hint_learner: Option<Arc<ConflictHintLearner>>,
 
     pub print_transactions: bool,
 }
@@ -125,6 +133,15 @@ where
             // Assume the distributed executor and the distributed partitioner share the same worker set.
             config.num_executor_shards,
             &config.partitioner_config,
+            config
+                .transaction_shuffler_type
+                .clone()
+                .map(|shuffler_type| {
+                    create_transaction_shuffler_with_learner(
+                        shuffler_type,
+                        config.conflict_hint_learner.clone(),
+                    )
+                }),
         );
 
         let mut exe = TransactionExecutor::new(executor_1, parent_block_id, ledger_update_sender);
@@ -145,6 +162,7 @@ where
             config.allow_discards,
             config.allow_retries,
             staged_events_clone,
+            config.conflict_hint_learner.clone(),
         );
 
         let print_transactions = config.print_transactions;
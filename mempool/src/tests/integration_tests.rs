@@ -2,14 +2,25 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::tests::{
-    common::TestTransaction,
-    test_framework::{test_transaction, MempoolNode, MempoolTestFrameworkBuilder},
+use crate::{
+    shared_mempool::types::MempoolMessageId,
+    tests::{
+        common::TestTransaction,
+        test_framework::{
+            sign_transactions, test_transaction, MempoolNode, MempoolTestFrameworkBuilder,
+        },
+    },
+    MempoolSyncMsg,
+};
+use aptos_config::{
+    config::PeerRole,
+    network_id::{NetworkId, PeerNetworkId},
 };
-use aptos_config::network_id::PeerNetworkId;
 use aptos_netcore::transport::ConnectionOrigin;
 use aptos_network::{
+    capture::{CapturedMessage, CapturedMessageType, MessageDirection},
     testutils::{
+        capture_replay::CaptureReplayer,
         test_framework::TestFramework,
         test_node::{
             pfn_pfn_mock_connection, pfn_vfn_mock_connection, validator_mock_connection,
@@ -19,7 +30,7 @@ use aptos_network::{
     transport::ConnectionMetadata,
     ProtocolId,
 };
-use aptos_types::transaction::ReplayProtector;
+use aptos_types::{transaction::ReplayProtector, PeerId};
 use once_cell::sync::Lazy;
 use std::time::Duration;
 
@@ -406,3 +417,63 @@ async fn fn_to_val_test() {
         val.assert_only_txns_in_mempool(&ALL_TXNS);
     }
 }
+
+/// Tests that captured mempool broadcasts can be replayed against a node
+#[tokio::test]
+async fn replay_captured_broadcasts_test() {
+    let node = MempoolTestFrameworkBuilder::single_validator();
+    let network_id = NetworkId::Validator;
+
+    // Create the captured broadcasts (each sent by a different peer)
+    let mut captured_messages: Vec<_> = ALL_TXNS
+        .iter()
+        .enumerate()
+        .map(|(index, txn)| {
+            create_captured_broadcast(network_id, index as u64, MessageDirection::Inbound, txn)
+        })
+        .collect();
+
+    // Add an outbound broadcast (which should not be replayed)
+    captured_messages.push(create_captured_broadcast(
+        network_id,
+        100,
+        MessageDirection::Outbound,
+        &test_transaction(2),
+    ));
+
+    // Replay the captured broadcasts against the node
+    let replayer = CaptureReplayer::new(captured_messages);
+    assert_eq!(replayer.remote_peers(network_id).len(), ALL_TXNS.len());
+    replayer.connect_remote_peers(&node, network_id, PeerRole::Validator);
+    let rpc_responses = replayer.replay(&node, network_id, true).await;
+    assert!(rpc_responses.is_empty());
+
+    // Verify that only the inbound transactions were added to mempool
+    node.wait_on_txns_in_mempool(&ALL_TXNS).await;
+    node.assert_only_txns_in_mempool(&ALL_TXNS);
+}
+
+/// Creates a captured mempool broadcast (direct send) for the given transaction
+fn create_captured_broadcast(
+    network_id: NetworkId,
+    timestamp_usecs: u64,
+    direction: MessageDirection,
+    txn: &TestTransaction,
+) -> CapturedMessage {
+    let message = MempoolSyncMsg::BroadcastTransactionsRequest {
+        message_id: MempoolMessageId::from_timeline_ids(vec![(
+            0,
+            (vec![1].into(), vec![10].into()),
+        )]),
+        transactions: sign_transactions(std::slice::from_ref(txn)),
+    };
+    CapturedMessage {
+        timestamp_usecs,
+        network_id,
+        peer_id: PeerId::random(),
+        direction,
+        message_type: CapturedMessageType::DirectSend,
+        protocol_id: ProtocolId::MempoolDirectSend,
+        data: ProtocolId::MempoolDirectSend.to_bytes(&message).unwrap(),
+    }
+}
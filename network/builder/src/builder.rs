@@ -12,15 +12,16 @@
 //! long as the latter is in its trusted peers set.
 use aptos_config::{
     config::{
-        DiscoveryMethod, NetworkConfig, Peer, PeerRole, PeerSet, RoleType, CONNECTION_BACKOFF_BASE,
-        CONNECTIVITY_CHECK_INTERVAL_MS, MAX_CONNECTION_DELAY_MS, MAX_FRAME_SIZE,
-        MAX_FULLNODE_OUTBOUND_CONNECTIONS, MAX_INBOUND_CONNECTIONS, NETWORK_CHANNEL_SIZE,
+        DiscoveryMethod, MessageCaptureConfig, NetworkConfig, OutboundSchedulerConfig, Peer,
+        PeerRole, PeerSet, RoleType, CONNECTION_BACKOFF_BASE, CONNECTIVITY_CHECK_INTERVAL_MS,
+        MAX_CONNECTION_DELAY_MS, MAX_FRAME_SIZE, MAX_FULLNODE_OUTBOUND_CONNECTIONS,
+        MAX_INBOUND_CONNECTIONS, NETWORK_CHANNEL_SIZE,
     },
     network_id::NetworkContext,
 };
 use aptos_event_notifications::{DbBackedOnChainConfig, EventSubscriptionService};
 use aptos_logger::prelude::*;
-use aptos_netcore::transport::tcp::TCPBufferCfg;
+use aptos_netcore::transport::{quic::QuicTransport, tcp::TCPBufferCfg};
 use aptos_network::{
     application::storage::PeersAndMetadata,
     connectivity_manager::{builder::ConnectivityManagerBuilder, ConnectivityRequest},
@@ -85,6 +86,9 @@ impl NetworkBuilder {
         network_channel_size: usize,
         inbound_connection_limit: usize,
         tcp_buffer_cfg: TCPBufferCfg,
+        quic_transport: QuicTransport,
+        outbound_scheduler_config: OutboundSchedulerConfig,
+        message_capture_config: Option<MessageCaptureConfig>,
     ) -> Self {
         // A network cannot exist without a PeerManager
         // TODO:  construct this in create and pass it to new() as a parameter. The complication is manual construction of NetworkBuilder in various tests.
@@ -101,6 +105,9 @@ impl NetworkBuilder {
             enable_proxy_protocol,
             inbound_connection_limit,
             tcp_buffer_cfg,
+            quic_transport,
+            outbound_scheduler_config,
+            message_capture_config,
         );
 
         NetworkBuilder {
@@ -140,6 +147,9 @@ impl NetworkBuilder {
             NETWORK_CHANNEL_SIZE,
             MAX_INBOUND_CONNECTIONS,
             TCPBufferCfg::default(),
+            QuicTransport::default(),
+            OutboundSchedulerConfig::default(),
+            None, /* Disable message capture */
         );
 
         builder.add_connectivity_manager(
@@ -195,6 +205,12 @@ impl NetworkBuilder {
                 config.outbound_rx_buffer_size_bytes,
                 config.outbound_tx_buffer_size_bytes,
             ),
+            QuicTransport::new(
+                Duration::from_millis(config.quic_max_idle_timeout_ms),
+                Duration::from_millis(config.quic_keep_alive_interval_ms),
+            ),
+            config.outbound_scheduler_config.clone(),
+            config.message_capture_config.clone(),
         );
 
         network_builder.add_connection_monitoring(
@@ -384,6 +400,13 @@ impl NetworkBuilder {
                     Duration::from_secs(rest_discovery.interval_secs),
                     self.time_service.clone(),
                 ),
+                DiscoveryMethod::Dns(dns_discovery) => DiscoveryChangeListener::dns(
+                    self.network_context,
+                    conn_mgr_reqs_tx.clone(),
+                    dns_discovery,
+                    self.time_service.clone(),
+                )
+                .expect("DNS discovery is unable to create a DNS resolver!"),
                 DiscoveryMethod::None => {
                     continue;
                 },
@@ -0,0 +1,683 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::DiscoveryError;
+use aptos_config::{
+    config::{Peer, PeerRole, PeerSet, HANDSHAKE_VERSION},
+    network_id::NetworkContext,
+};
+use aptos_crypto::{x25519, ValidCryptoMaterialStringExt};
+use aptos_logger::prelude::*;
+use aptos_network::logging::NetworkSchema;
+use aptos_time_service::{Sleep, TimeService, TimeServiceTrait};
+use aptos_types::{
+    account_address::from_identity_public_key,
+    network_address::{DnsName, NetworkAddress, Protocol},
+    PeerId,
+};
+use futures::{
+    future::{BoxFuture, FutureExt},
+    Future, Stream,
+};
+use hickory_resolver::{
+    config::{NameServerConfigGroup, ResolverConfig, ResolverOpts},
+    error::{ResolveError, ResolveErrorKind},
+    lookup::Lookup,
+    proto::rr::{RData, RecordType},
+    TokioAsyncResolver,
+};
+use std::{
+    collections::HashSet,
+    net::SocketAddr,
+    pin::Pin,
+    str::FromStr,
+    sync::Arc,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+/// The SRV protocol label (e.g., `_aptosnet._quic.seeds.example.com`) for
+/// which SRV targets are dialed over QUIC. All other SRV targets use TCP.
+const QUIC_SRV_PROTOCOL_LABEL: &str = "_quic";
+
+/// The DNS record types used for discovery
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum DnsRecordType {
+    Txt,
+    Srv,
+}
+
+/// A DNS record used for discovery
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DnsRecord {
+    /// The text of a TXT record (with all character strings concatenated)
+    Txt(String),
+    /// The target and port of a SRV record
+    Srv { target: String, port: u16 },
+}
+
+/// The records returned by a DNS lookup, along with the smallest record TTL
+/// (if any records were returned).
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct DnsLookup {
+    pub records: Vec<DnsRecord>,
+    pub ttl: Option<Duration>,
+}
+
+/// A resolver for DNS records. This allows DNS discovery to be tested with a stub resolver.
+pub trait DnsResolver: Send + Sync {
+    fn lookup(
+        &self,
+        name: &str,
+        record_type: DnsRecordType,
+    ) -> BoxFuture<'static, Result<DnsLookup, DiscoveryError>>;
+}
+
+/// A resolver backed by hickory. This queries all configured nameservers
+/// (falling back to TCP for truncated responses) and caches records
+/// according to their TTLs.
+pub struct HickoryDnsResolver {
+    resolver: TokioAsyncResolver,
+}
+
+impl HickoryDnsResolver {
+    /// Creates a new resolver. If no nameserver is given, the system
+    /// resolver configuration (e.g., `/etc/resolv.conf`) is used.
+    pub fn new(nameserver: Option<SocketAddr>) -> Result<Self, DiscoveryError> {
+        let resolver = match nameserver {
+            Some(nameserver) => {
+                let nameservers = NameServerConfigGroup::from_ips_clear(
+                    &[nameserver.ip()],
+                    nameserver.port(),
+                    true,
+                );
+                let config = ResolverConfig::from_parts(None, vec![], nameservers);
+                TokioAsyncResolver::tokio(config, ResolverOpts::default())
+            },
+            None => TokioAsyncResolver::tokio_from_system_conf().map_err(|error| {
+                DiscoveryError::Dns(format!(
+                    "Failed to read the system resolver configuration: {}",
+                    error
+                ))
+            })?,
+        };
+        Ok(Self { resolver })
+    }
+}
+
+impl DnsResolver for HickoryDnsResolver {
+    fn lookup(
+        &self,
+        name: &str,
+        record_type: DnsRecordType,
+    ) -> BoxFuture<'static, Result<DnsLookup, DiscoveryError>> {
+        let resolver = self.resolver.clone();
+        let name = name.to_string();
+        async move {
+            let query_type = match record_type {
+                DnsRecordType::Txt => RecordType::TXT,
+                DnsRecordType::Srv => RecordType::SRV,
+            };
+            let result = resolver.lookup(name.as_str(), query_type).await;
+            to_dns_lookup(&name, result, Instant::now())
+        }
+        .boxed()
+    }
+}
+
+/// Converts the result of a hickory lookup into the TXT and SRV records (and
+/// their TTL). A name without records is not an error (it results in an empty lookup).
+fn to_dns_lookup(
+    name: &str,
+    result: Result<Lookup, ResolveError>,
+    now: Instant,
+) -> Result<DnsLookup, DiscoveryError> {
+    let lookup = match result {
+        Ok(lookup) => lookup,
+        Err(error) => {
+            return match error.kind() {
+                ResolveErrorKind::NoRecordsFound { negative_ttl, .. } => Ok(DnsLookup {
+                    records: vec![],
+                    ttl: negative_ttl.map(|ttl| Duration::from_secs(ttl as u64)),
+                }),
+                _ => Err(DiscoveryError::Dns(format!(
+                    "DNS query for {} failed: {}",
+                    name, error
+                ))),
+            };
+        },
+    };
+
+    let records: Vec<_> = lookup
+        .iter()
+        .filter_map(|data| match data {
+            RData::TXT(record) => Some(DnsRecord::Txt(
+                record
+                    .txt_data()
+                    .iter()
+                    .map(|data| String::from_utf8_lossy(data))
+                    .collect::<String>(),
+            )),
+            RData::SRV(record) => Some(DnsRecord::Srv {
+                target: record.target().to_utf8(),
+                port: record.port(),
+            }),
+            _ => None, // Ignore other records (e.g., CNAMEs)
+        })
+        .collect();
+    let ttl = (!records.is_empty()).then(|| lookup.valid_until().saturating_duration_since(now));
+    Ok(DnsLookup { records, ttl })
+}
+
+/// Returns the transport protocol for the targets of the SRV records
+/// published under the given name (i.e., QUIC if the protocol label
+/// of the name is `_quic`, and TCP otherwise).
+fn srv_transport_protocol(name: &str, port: u16) -> Protocol {
+    let is_quic = name.split('.').nth(1).map_or(false, |label| {
+        label.eq_ignore_ascii_case(QUIC_SRV_PROTOCOL_LABEL)
+    });
+    if is_quic {
+        Protocol::Quic(port)
+    } else {
+        Protocol::Tcp(port)
+    }
+}
+
+/// The peer fields published in a TXT record, e.g.,
+/// `addr=/dns/node0.example.com/tcp/6182/noise-ik/<key>/handshake/0 peer_id=<peer id>`.
+#[derive(Debug, Default)]
+struct TxtPeerFields {
+    addresses: Vec<NetworkAddress>,
+    keys: HashSet<x25519::PublicKey>,
+    peer_id: Option<PeerId>,
+}
+
+impl TxtPeerFields {
+    /// Parses the peer fields from the text of a TXT record. Returns None if
+    /// the record does not describe a peer (e.g., it is used by another service).
+    fn parse(text: &str) -> Option<Result<Self, DiscoveryError>> {
+        let mut fields = TxtPeerFields::default();
+        let mut is_peer_record = false;
+        for field in text.split_whitespace() {
+            let Some((key, value)) = field.split_once('=') else {
+                continue;
+            };
+            let result = match key {
+                "addr" => NetworkAddress::from_str(value)
+                    .map(|address| fields.addresses.push(address))
+                    .map_err(|error| error.to_string()),
+                "key" => x25519::PublicKey::from_encoded_string(value)
+                    .map(|key| {
+                        fields.keys.insert(key);
+                    })
+                    .map_err(|error| error.to_string()),
+                "peer_id" => PeerId::from_str(value)
+                    .map(|peer_id| fields.peer_id = Some(peer_id))
+                    .map_err(|error| error.to_string()),
+                _ => continue, // Ignore unknown fields (for forward compatibility)
+            };
+            is_peer_record = true;
+            if let Err(error) = result {
+                return Some(Err(DiscoveryError::Parsing(format!(
+                    "Invalid field {} in TXT record: {}. Error: {}",
+                    key, text, error
+                ))));
+            }
+        }
+        is_peer_record.then_some(Ok(fields))
+    }
+
+    /// Converts the fields into a peer. The peer ID is derived from the
+    /// public key if it is not given explicitly.
+    fn into_peer(self, text: &str) -> Result<(PeerId, Peer), DiscoveryError> {
+        let peer = Peer::new(self.addresses, self.keys, PeerRole::Upstream);
+        let peer_id = match self.peer_id {
+            Some(peer_id) => peer_id,
+            None if peer.keys.len() == 1 => {
+                from_identity_public_key(*peer.keys.iter().next().unwrap())
+            },
+            None => {
+                return Err(DiscoveryError::Parsing(format!(
+                    "A peer_id is required for TXT records with {} keys: {}",
+                    peer.keys.len(),
+                    text
+                )))
+            },
+        };
+        if peer.addresses.is_empty() || peer.keys.is_empty() {
+            return Err(DiscoveryError::Parsing(format!(
+                "TXT records must contain an address and a key: {}",
+                text
+            )));
+        }
+        Ok((peer_id, peer))
+    }
+}
+
+/// Adds the peer to the peer set (merging it with any existing entry for the same peer)
+fn insert_peer(peers: &mut PeerSet, peer_id: PeerId, peer: Peer) {
+    let existing_peer = peers
+        .entry(peer_id)
+        .or_insert_with(|| Peer::new(vec![], HashSet::new(), peer.role));
+    for address in peer.addresses {
+        if !existing_peer.addresses.contains(&address) {
+            existing_peer.addresses.push(address);
+        }
+    }
+    existing_peer.keys.extend(peer.keys);
+}
+
+/// Returns the smaller of the two (optional) TTLs
+fn min_ttl(ttl: Option<Duration>, other_ttl: Option<Duration>) -> Option<Duration> {
+    match (ttl, other_ttl) {
+        (Some(ttl), Some(other_ttl)) => Some(ttl.min(other_ttl)),
+        (ttl, other_ttl) => ttl.or(other_ttl),
+    }
+}
+
+/// Resolves the peers published under the given DNS name. Peers are
+/// published as TXT records (see [`TxtPeerFields`]) and/or SRV records,
+/// where each SRV target holds a TXT record with the public key of the peer
+/// (see [`srv_transport_protocol`] for how SRV targets are dialed).
+/// Returns the peers and the smallest TTL of all resolved records.
+async fn resolve_peers(
+    network_context: NetworkContext,
+    resolver: Arc<dyn DnsResolver>,
+    name: String,
+) -> Result<(PeerSet, Option<Duration>), DiscoveryError> {
+    let mut peers = PeerSet::new();
+
+    // Resolve the peers published directly in TXT records
+    let txt_lookup = resolver.lookup(&name, DnsRecordType::Txt).await?;
+    let mut ttl = txt_lookup.ttl;
+    for record in txt_lookup.records {
+        let DnsRecord::Txt(text) = record else {
+            continue;
+        };
+        match TxtPeerFields::parse(&text).map(|fields| fields?.into_peer(&text)) {
+            Some(Ok((peer_id, peer))) => insert_peer(&mut peers, peer_id, peer),
+            Some(Err(error)) => warn!(
+                NetworkSchema::new(&network_context),
+                "{} Ignoring invalid DNS discovery record: {:?}", network_context, error
+            ),
+            None => {}, // The record doesn't describe a peer
+        }
+    }
+
+    // Resolve the peers published in SRV records
+    let srv_lookup = resolver.lookup(&name, DnsRecordType::Srv).await?;
+    ttl = min_ttl(ttl, srv_lookup.ttl);
+    for record in srv_lookup.records {
+        let DnsRecord::Srv { target, port } = record else {
+            continue;
+        };
+        let target = target.trim_end_matches('.').to_string();
+        let transport_protocol = srv_transport_protocol(&name, port);
+        match resolve_srv_target(resolver.as_ref(), &target, transport_protocol).await {
+            Ok((srv_peers, target_ttl)) => {
+                ttl = min_ttl(ttl, target_ttl);
+                for (peer_id, peer) in srv_peers {
+                    insert_peer(&mut peers, peer_id, peer);
+                }
+            },
+            Err(error) => warn!(
+                NetworkSchema::new(&network_context),
+                "{} Failed to resolve DNS discovery target {}: {:?}",
+                network_context,
+                target,
+                error
+            ),
+        }
+    }
+
+    Ok((peers, ttl))
+}
+
+/// Resolves the peers for a single SRV target (using the TXT records of the
+/// target). The target is dialed using the given transport protocol and port.
+async fn resolve_srv_target(
+    resolver: &dyn DnsResolver,
+    target: &str,
+    transport_protocol: Protocol,
+) -> Result<(Vec<(PeerId, Peer)>, Option<Duration>), DiscoveryError> {
+    let dns_name = DnsName::from_str(target)
+        .map_err(|error| DiscoveryError::Parsing(format!("Invalid SRV target: {}", error)))?;
+    let base_address =
+        NetworkAddress::from_protocols(vec![Protocol::Dns(dns_name), transport_protocol])
+            .map_err(|error| DiscoveryError::Parsing(error.to_string()))?;
+
+    let txt_lookup = resolver.lookup(target, DnsRecordType::Txt).await?;
+    let mut peers = vec![];
+    for record in txt_lookup.records {
+        let DnsRecord::Txt(text) = record else {
+            continue;
+        };
+        let Some(fields) = TxtPeerFields::parse(&text) else {
+            continue;
+        };
+        let mut fields = fields?;
+        for key in fields.keys.iter() {
+            fields.addresses.push(
+                base_address
+                    .clone()
+                    .append_prod_protos(*key, HANDSHAKE_VERSION),
+            );
+        }
+        peers.push(fields.into_peer(&text)?);
+    }
+    Ok((peers, txt_lookup.ttl))
+}
+
+/// A discovery stream that periodically resolves peers from DNS records.
+/// Lookups are refreshed according to the TTL of the records (bounded by
+/// the min and max intervals), and updates are only sent when peers change.
+pub struct DnsStream {
+    network_context: NetworkContext,
+    name: String,
+    resolver: Arc<dyn DnsResolver>,
+    time_service: TimeService,
+    min_interval: Duration,
+    max_interval: Duration,
+    next_lookup: Option<Pin<Box<Sleep>>>,
+    pending_lookup: Option<BoxFuture<'static, Result<(PeerSet, Option<Duration>), DiscoveryError>>>,
+    last_peers: Option<PeerSet>,
+}
+
+impl DnsStream {
+    pub(crate) fn new(
+        network_context: NetworkContext,
+        name: String,
+        resolver: Arc<dyn DnsResolver>,
+        min_interval: Duration,
+        max_interval: Duration,
+        time_service: TimeService,
+    ) -> Self {
+        DnsStream {
+            network_context,
+            name,
+            resolver,
+            time_service,
+            min_interval,
+            max_interval: max_interval.max(min_interval),
+            next_lookup: None,
+            pending_lookup: None,
+            last_peers: None,
+        }
+    }
+
+    /// Schedules the next lookup according to the TTL of the records. If
+    /// there is no TTL (e.g., no records were found), the min interval is used.
+    fn schedule_next_lookup(&mut self, ttl: Option<Duration>) {
+        let interval = ttl
+            .unwrap_or(self.min_interval)
+            .clamp(self.min_interval, self.max_interval);
+        self.next_lookup = Some(Box::pin(self.time_service.sleep(interval)));
+    }
+}
+
+impl Stream for DnsStream {
+    type Item = Result<PeerSet, DiscoveryError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            // Wait until the next lookup is due
+            if let Some(next_lookup) = self.next_lookup.as_mut() {
+                futures::ready!(next_lookup.as_mut().poll(cx));
+                self.next_lookup = None;
+            }
+
+            // Start a new lookup (if one isn't already in progress)
+            if self.pending_lookup.is_none() {
+                let lookup = resolve_peers(
+                    self.network_context,
+                    self.resolver.clone(),
+                    self.name.clone(),
+                );
+                self.pending_lookup = Some(lookup.boxed());
+            }
+            let result = futures::ready!(self.pending_lookup.as_mut().unwrap().poll_unpin(cx));
+            self.pending_lookup = None;
+
+            match result {
+                Ok((peers, ttl)) => {
+                    self.schedule_next_lookup(ttl);
+
+                    // Only notify the connectivity manager if the peers have changed
+                    if self.last_peers.as_ref() != Some(&peers) {
+                        self.last_peers = Some(peers.clone());
+                        return Poll::Ready(Some(Ok(peers)));
+                    }
+                },
+                Err(error) => {
+                    self.schedule_next_lookup(None);
+                    return Poll::Ready(Some(Err(error)));
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_crypto::Uniform;
+    use futures::StreamExt;
+    use hickory_resolver::proto::{
+        op::{Query, ResponseCode},
+        rr::{
+            rdata::{SRV, TXT},
+            Name,
+        },
+    };
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::{collections::HashMap, sync::Mutex};
+
+    const SEED_NAME: &str = "_aptosnet._tcp.seeds.example.com";
+
+    /// A stub resolver that serves records from memory
+    #[derive(Default)]
+    struct StubDnsResolver {
+        lookups: Mutex<HashMap<(String, DnsRecordType), DnsLookup>>,
+    }
+
+    impl StubDnsResolver {
+        fn set_records(&self, name: &str, record_type: DnsRecordType, records: Vec<DnsRecord>) {
+            let lookup = DnsLookup {
+                records,
+                ttl: Some(Duration::from_secs(60)),
+            };
+            self.lookups
+                .lock()
+                .unwrap()
+                .insert((name.to_string(), record_type), lookup);
+        }
+    }
+
+    impl DnsResolver for StubDnsResolver {
+        fn lookup(
+            &self,
+            name: &str,
+            record_type: DnsRecordType,
+        ) -> BoxFuture<'static, Result<DnsLookup, DiscoveryError>> {
+            let lookup = self
+                .lookups
+                .lock()
+                .unwrap()
+                .get(&(name.to_string(), record_type))
+                .cloned()
+                .unwrap_or_default();
+            futures::future::ready(Ok(lookup)).boxed()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dns_stream_txt_and_srv_records() {
+        // Create a TXT record peer and a SRV record peer
+        let mut rng = StdRng::from_seed([0u8; 32]);
+        let txt_key = x25519::PrivateKey::generate(&mut rng).public_key();
+        let txt_address = NetworkAddress::from_str("/dns/node0.example.com/tcp/6182")
+            .unwrap()
+            .append_prod_protos(txt_key, HANDSHAKE_VERSION);
+        let srv_key = x25519::PrivateKey::generate(&mut rng).public_key();
+        let srv_peer_id = PeerId::random();
+
+        // Publish the records
+        let resolver = Arc::new(StubDnsResolver::default());
+        resolver.set_records(SEED_NAME, DnsRecordType::Txt, vec![
+            DnsRecord::Txt(format!("addr={}", txt_address)),
+            DnsRecord::Txt("v=spf1 -all".into()),
+        ]);
+        resolver.set_records(SEED_NAME, DnsRecordType::Srv, vec![DnsRecord::Srv {
+            target: "node1.example.com.".into(),
+            port: 6180,
+        }]);
+        resolver.set_records("node1.example.com", DnsRecordType::Txt, vec![
+            DnsRecord::Txt(format!("key={} peer_id={}", srv_key, srv_peer_id)),
+        ]);
+
+        // Verify the stream resolves both peers
+        let time_service = TimeService::mock();
+        let mut dns_stream = create_dns_stream(resolver.clone(), time_service.clone());
+        let peers = dns_stream.next().await.unwrap().unwrap();
+        assert_eq!(peers.len(), 2);
+        let txt_peer = peers.get(&from_identity_public_key(txt_key)).unwrap();
+        assert_eq!(txt_peer.addresses, vec![txt_address]);
+        assert_eq!(txt_peer.role, PeerRole::Upstream);
+        let srv_peer = peers.get(&srv_peer_id).unwrap();
+        assert_eq!(srv_peer.keys, [srv_key].into_iter().collect());
+        assert_eq!(srv_peer.addresses, vec![NetworkAddress::from_str(
+            "/dns/node1.example.com/tcp/6180"
+        )
+        .unwrap()
+        .append_prod_protos(srv_key, HANDSHAKE_VERSION)]);
+    }
+
+    #[tokio::test]
+    async fn test_dns_stream_refresh_on_change() {
+        // Publish a single peer
+        let mut rng = StdRng::from_seed([1u8; 32]);
+        let key = x25519::PrivateKey::generate(&mut rng).public_key();
+        let address = NetworkAddress::from_str("/ip4/10.0.0.1/tcp/6182")
+            .unwrap()
+            .append_prod_protos(key, HANDSHAKE_VERSION);
+        let resolver = Arc::new(StubDnsResolver::default());
+        resolver.set_records(SEED_NAME, DnsRecordType::Txt, vec![DnsRecord::Txt(
+            format!("addr={}", address),
+        )]);
+
+        // Verify the first lookup is sent immediately
+        let time_service = TimeService::mock();
+        let mut dns_stream = create_dns_stream(resolver.clone(), time_service.clone());
+        let peers = dns_stream.next().await.unwrap().unwrap();
+        assert_eq!(peers.len(), 1);
+
+        // Elapse the record TTL and verify no update is sent (the peers didn't change)
+        let mock_time = time_service.into_mock();
+        mock_time.advance_async(Duration::from_secs(60)).await;
+        assert!(dns_stream.next().now_or_never().is_none());
+
+        // Remove the peer, elapse the record TTL and verify an update is sent
+        resolver.set_records(SEED_NAME, DnsRecordType::Txt, vec![]);
+        mock_time.advance_async(Duration::from_secs(60)).await;
+        let peers = dns_stream.next().await.unwrap().unwrap();
+        assert!(peers.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_peers_quic_srv_records() {
+        // Publish a SRV record under a QUIC name
+        let mut rng = StdRng::from_seed([2u8; 32]);
+        let key = x25519::PrivateKey::generate(&mut rng).public_key();
+        let quic_seed_name = "_aptosnet._quic.seeds.example.com";
+        let resolver = Arc::new(StubDnsResolver::default());
+        resolver.set_records(quic_seed_name, DnsRecordType::Srv, vec![DnsRecord::Srv {
+            target: "node2.example.com.".into(),
+            port: 6180,
+        }]);
+        resolver.set_records("node2.example.com", DnsRecordType::Txt, vec![
+            DnsRecord::Txt(format!("key={}", key)),
+        ]);
+
+        // Verify the SRV target is dialed over QUIC
+        let (peers, ttl) =
+            resolve_peers(NetworkContext::mock(), resolver, quic_seed_name.to_string())
+                .await
+                .unwrap();
+        assert_eq!(ttl, Some(Duration::from_secs(60)));
+        let peer = peers.get(&from_identity_public_key(key)).unwrap();
+        assert_eq!(peer.addresses, vec![NetworkAddress::from_str(
+            "/dns/node2.example.com/quic/6180"
+        )
+        .unwrap()
+        .append_prod_protos(key, HANDSHAKE_VERSION)]);
+    }
+
+    #[test]
+    fn test_srv_transport_protocol() {
+        assert_eq!(srv_transport_protocol(SEED_NAME, 6180), Protocol::Tcp(6180));
+        assert_eq!(
+            srv_transport_protocol("_aptosnet._QUIC.seeds.example.com", 6180),
+            Protocol::Quic(6180)
+        );
+        assert_eq!(
+            srv_transport_protocol("seeds.example.com", 6180),
+            Protocol::Tcp(6180)
+        );
+    }
+
+    #[test]
+    fn test_to_dns_lookup() {
+        // Convert a lookup with a TXT and a SRV record
+        let name = Name::from_str(SEED_NAME).unwrap();
+        let now = Instant::now();
+        let txt_lookup = Lookup::from_rdata(
+            Query::query(name.clone(), RecordType::TXT),
+            RData::TXT(TXT::new(vec!["key=".into(), "abc".into()])),
+        );
+        let srv_lookup = Lookup::from_rdata(
+            Query::query(name.clone(), RecordType::SRV),
+            RData::SRV(SRV::new(
+                0,
+                0,
+                6180,
+                Name::from_str("node1.example.com.").unwrap(),
+            )),
+        );
+        let txt_lookup = to_dns_lookup(SEED_NAME, Ok(txt_lookup), now).unwrap();
+        assert_eq!(txt_lookup.records, vec![DnsRecord::Txt("key=abc".into())]);
+        assert!(txt_lookup.ttl.is_some());
+        let srv_lookup = to_dns_lookup(SEED_NAME, Ok(srv_lookup), now).unwrap();
+        assert_eq!(srv_lookup.records, vec![DnsRecord::Srv {
+            target: "node1.example.com.".into(),
+            port: 6180,
+        }]);
+
+        // Verify a missing name results in an empty lookup (with the negative TTL)
+        let no_records = ResolveError::nx_error(
+            Query::query(name, RecordType::TXT),
+            None,
+            Some(30),
+            ResponseCode::NXDomain,
+            true,
+        );
+        let lookup = to_dns_lookup(SEED_NAME, Err(no_records), now).unwrap();
+        assert_eq!(lookup, DnsLookup {
+            records: vec![],
+            ttl: Some(Duration::from_secs(30)),
+        });
+
+        // Verify other errors are returned
+        to_dns_lookup(SEED_NAME, Err(ResolveError::from("timed out")), now).unwrap_err();
+    }
+
+    fn create_dns_stream(resolver: Arc<StubDnsResolver>, time_service: TimeService) -> DnsStream {
+        DnsStream::new(
+            NetworkContext::mock(),
+            SEED_NAME.to_string(),
+            resolver,
+            Duration::from_secs(10),
+            Duration::from_secs(300),
+            time_service,
+        )
+    }
+}
@@ -3,10 +3,16 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    counters::DISCOVERY_COUNTS, file::FileStream, rest::RestStream,
+    counters::DISCOVERY_COUNTS,
+    dns::{DnsResolver, DnsStream, HickoryDnsResolver},
+    file::FileStream,
+    rest::RestStream,
     validator_set::ValidatorSetStream,
 };
-use aptos_config::{config::PeerSet, network_id::NetworkContext};
+use aptos_config::{
+    config::{DnsDiscovery, PeerSet},
+    network_id::NetworkContext,
+};
 use aptos_crypto::x25519;
 use aptos_event_notifications::ReconfigNotificationListener;
 use aptos_logger::prelude::*;
@@ -21,12 +27,14 @@ use futures::{Stream, StreamExt};
 use std::{
     path::Path,
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
     time::Duration,
 };
 use tokio::runtime::Handle;
 
 mod counters;
+pub mod dns;
 mod file;
 mod rest;
 mod validator_set;
@@ -36,6 +44,7 @@ pub enum DiscoveryError {
     IO(std::io::Error),
     Parsing(String),
     Rest(aptos_rest_client::error::RestError),
+    Dns(String),
 }
 
 /// A union type for all implementations of `DiscoveryChangeListenerTrait`
@@ -50,6 +59,7 @@ enum DiscoveryChangeStream<P: OnChainConfigProvider> {
     ValidatorSet(ValidatorSetStream<P>),
     File(FileStream),
     Rest(RestStream),
+    Dns(DnsStream),
 }
 
 impl<P: OnChainConfigProvider> Stream for DiscoveryChangeStream<P> {
@@ -60,6 +70,7 @@ impl<P: OnChainConfigProvider> Stream for DiscoveryChangeStream<P> {
             Self::ValidatorSet(stream) => Pin::new(stream).poll_next(cx),
             Self::File(stream) => Pin::new(stream).poll_next(cx),
             Self::Rest(stream) => Pin::new(stream).poll_next(cx),
+            Self::Dns(stream) => Pin::new(stream).poll_next(cx),
         }
     }
 }
@@ -125,6 +136,46 @@ impl<P: OnChainConfigProvider> DiscoveryChangeListener<P> {
         }
     }
 
+    pub fn dns(
+        network_context: NetworkContext,
+        update_channel: aptos_channels::Sender<ConnectivityRequest>,
+        dns_discovery: &DnsDiscovery,
+        time_service: TimeService,
+    ) -> Result<Self, DiscoveryError> {
+        let resolver = Arc::new(HickoryDnsResolver::new(dns_discovery.nameserver)?);
+        Ok(Self::dns_with_resolver(
+            network_context,
+            update_channel,
+            dns_discovery,
+            resolver,
+            time_service,
+        ))
+    }
+
+    /// Creates a DNS discovery listener that uses the given resolver (e.g., a stub resolver)
+    pub fn dns_with_resolver(
+        network_context: NetworkContext,
+        update_channel: aptos_channels::Sender<ConnectivityRequest>,
+        dns_discovery: &DnsDiscovery,
+        resolver: Arc<dyn DnsResolver>,
+        time_service: TimeService,
+    ) -> Self {
+        let source_stream = DiscoveryChangeStream::Dns(DnsStream::new(
+            network_context,
+            dns_discovery.name.clone(),
+            resolver,
+            Duration::from_secs(dns_discovery.min_interval_secs),
+            Duration::from_secs(dns_discovery.max_interval_secs),
+            time_service,
+        ));
+        DiscoveryChangeListener {
+            discovery_source: DiscoverySource::Dns,
+            network_context,
+            update_channel,
+            source_stream,
+        }
+    }
+
     pub fn start(self, executor: &Handle) {
         spawn_named!("DiscoveryChangeListener", executor, Box::pin(self).run());
     }
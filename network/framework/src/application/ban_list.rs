@@ -0,0 +1,303 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{application::error::Error, transport::ConnectionMetadata};
+use aptos_channels::{aptos_channel, message_queues::QueueStyle};
+use aptos_infallible::{Mutex, RwLock};
+use aptos_logger::{info, warn};
+use aptos_time_service::{TimeService, TimeServiceTrait};
+use aptos_types::PeerId;
+use ipnet::IpNet;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    collections::BTreeMap,
+    fmt, fs,
+    net::IpAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+
+/// The file name used to persist the peer ban list (inside the node's data directory)
+pub const PEER_BAN_LIST_FILE_NAME: &str = "peer_ban_list.json";
+
+/// The target of a peer ban (i.e., a single peer or a range of IP addresses)
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BanTarget {
+    PeerId(PeerId),
+    IpRange(#[serde(with = "ip_range_serde")] IpNet),
+}
+
+impl BanTarget {
+    /// Parses the given peer ID (hex string) into a ban target
+    pub fn parse_peer_id(peer_id: &str) -> Result<Self, Error> {
+        PeerId::from_str(peer_id)
+            .map(BanTarget::PeerId)
+            .map_err(|error| {
+                Error::UnexpectedError(format!("Invalid peer ID: {}, error: {}", peer_id, error))
+            })
+    }
+
+    /// Parses the given IP range (CIDR notation, or a single IP address) into a ban target
+    pub fn parse_ip_range(ip_range: &str) -> Result<Self, Error> {
+        if let Ok(ip_net) = IpNet::from_str(ip_range) {
+            return Ok(BanTarget::IpRange(ip_net.trunc()));
+        }
+        IpAddr::from_str(ip_range)
+            .map(|ip_addr| BanTarget::IpRange(IpNet::from(ip_addr)))
+            .map_err(|error| {
+                Error::UnexpectedError(format!("Invalid IP range: {}, error: {}", ip_range, error))
+            })
+    }
+
+    /// Returns true iff the given connection is covered by this ban target
+    fn matches(&self, connection_metadata: &ConnectionMetadata) -> bool {
+        match self {
+            BanTarget::PeerId(peer_id) => *peer_id == connection_metadata.remote_peer_id,
+            BanTarget::IpRange(ip_range) => connection_metadata
+                .addr
+                .find_ip_addr()
+                .is_some_and(|ip_addr| ip_range.contains(&ip_addr.to_canonical())),
+        }
+    }
+}
+
+impl fmt::Display for BanTarget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BanTarget::PeerId(peer_id) => write!(f, "peer ID: {}", peer_id),
+            BanTarget::IpRange(ip_range) => write!(f, "IP range: {}", ip_range),
+        }
+    }
+}
+
+/// A single ban entry in the peer ban list
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PeerBan {
+    pub target: BanTarget,
+    pub reason: Option<String>,
+    pub created_at_secs: u64, // The unix time (in seconds) at which the ban was created
+    pub expires_at_secs: Option<u64>, // The unix time (in seconds) at which the ban expires
+}
+
+impl PeerBan {
+    /// Returns true iff the ban has expired at the given unix time (in seconds)
+    fn is_expired(&self, now_secs: u64) -> bool {
+        self.expires_at_secs
+            .is_some_and(|expires_at_secs| expires_at_secs <= now_secs)
+    }
+}
+
+impl fmt::Display for PeerBan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}, reason: {}, created at (secs): {}, expires at (secs): {}",
+            self.target,
+            self.reason.as_deref().unwrap_or("none"),
+            self.created_at_secs,
+            self.expires_at_secs
+                .map(|expires_at_secs| expires_at_secs.to_string())
+                .unwrap_or_else(|| "never".into())
+        )
+    }
+}
+
+/// A runtime list of banned peers and IP ranges, shared by all networks on
+/// the node. Bans are enforced by the peer manager (at handshake time, and
+/// by disconnecting existing connections), and are optionally persisted to
+/// disk so that they survive restarts.
+#[derive(Debug)]
+pub struct PeerBanList {
+    bans: RwLock<BTreeMap<BanTarget, PeerBan>>,
+    persistence_path: Option<PathBuf>,
+    subscribers: Mutex<Vec<aptos_channel::Sender<(), ()>>>,
+    time_service: TimeService,
+}
+
+impl PeerBanList {
+    /// Creates a new (in-memory only) peer ban list
+    pub fn new(time_service: TimeService) -> Self {
+        Self {
+            bans: RwLock::new(BTreeMap::new()),
+            persistence_path: None,
+            subscribers: Mutex::new(vec![]),
+            time_service,
+        }
+    }
+
+    /// Creates a new peer ban list that is persisted at the given path.
+    /// Any bans previously persisted at the path are loaded.
+    pub fn new_with_persistence(persistence_path: PathBuf, time_service: TimeService) -> Self {
+        let peer_ban_list = Self {
+            persistence_path: Some(persistence_path.clone()),
+            ..Self::new(time_service)
+        };
+
+        // Load any persisted bans
+        if persistence_path.exists() {
+            match load_persisted_bans(&persistence_path) {
+                Ok(persisted_bans) => {
+                    info!(
+                        "Loaded {} persisted peer bans from: {:?}",
+                        persisted_bans.len(),
+                        persistence_path
+                    );
+                    let mut bans = peer_ban_list.bans.write();
+                    for peer_ban in persisted_bans {
+                        bans.insert(peer_ban.target, peer_ban);
+                    }
+                },
+                Err(error) => {
+                    warn!(
+                        "Failed to load the persisted peer bans from: {:?}. Error: {:?}",
+                        persistence_path, error
+                    );
+                },
+            }
+        }
+
+        peer_ban_list
+    }
+
+    /// Bans the given target for the specified duration (or indefinitely,
+    /// if no duration is given). Any existing ban for the target is replaced.
+    pub fn ban(
+        &self,
+        target: BanTarget,
+        duration: Option<Duration>,
+        reason: Option<String>,
+    ) -> Result<PeerBan, Error> {
+        let now_secs = self.time_service.now_unix_time().as_secs();
+        let peer_ban = PeerBan {
+            target,
+            reason,
+            created_at_secs: now_secs,
+            expires_at_secs: duration.map(|duration| now_secs.saturating_add(duration.as_secs())),
+        };
+
+        // Update the ban list and persist it
+        let persist_result = {
+            let mut bans = self.bans.write();
+            bans.insert(target, peer_ban.clone());
+            self.persist_bans(&bans)
+        };
+        info!("Added a new peer ban: {}", peer_ban);
+
+        // Notify the subscribers so that existing connections are closed (the
+        // ban is enforced at runtime even if it could not be persisted).
+        self.notify_subscribers();
+
+        persist_result.map(|_| peer_ban)
+    }
+
+    /// Removes the ban for the given target. Returns the removed ban (if any).
+    pub fn unban(&self, target: &BanTarget) -> Result<Option<PeerBan>, Error> {
+        let mut bans = self.bans.write();
+        let removed_ban = bans.remove(target);
+        if let Some(removed_ban) = &removed_ban {
+            self.persist_bans(&bans)?;
+            info!("Removed the peer ban: {}", removed_ban);
+        }
+
+        Ok(removed_ban)
+    }
+
+    /// Returns all bans that have not yet expired (sorted by target)
+    pub fn get_active_bans(&self) -> Vec<PeerBan> {
+        let now_secs = self.time_service.now_unix_time().as_secs();
+        self.bans
+            .read()
+            .values()
+            .filter(|peer_ban| !peer_ban.is_expired(now_secs))
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the active ban covering the given connection (if any)
+    pub fn get_ban_for_connection(
+        &self,
+        connection_metadata: &ConnectionMetadata,
+    ) -> Option<PeerBan> {
+        let now_secs = self.time_service.now_unix_time().as_secs();
+        self.bans
+            .read()
+            .values()
+            .find(|peer_ban| {
+                !peer_ban.is_expired(now_secs) && peer_ban.target.matches(connection_metadata)
+            })
+            .cloned()
+    }
+
+    /// Returns a channel that is notified every time a new ban is added.
+    /// Notifications are coalesced, so subscribers should re-check all
+    /// of their connections upon receiving one.
+    pub fn subscribe(&self) -> aptos_channel::Receiver<(), ()> {
+        let (sender, receiver) = aptos_channel::new(QueueStyle::KLAST, 1, None);
+        self.subscribers.lock().push(sender);
+        receiver
+    }
+
+    /// Notifies all subscribers of a ban list update (and
+    /// removes any subscribers that have been dropped).
+    fn notify_subscribers(&self) {
+        self.subscribers
+            .lock()
+            .retain(|subscriber| subscriber.push((), ()).is_ok());
+    }
+
+    /// Persists the given bans to disk (if persistence is enabled).
+    /// Expired bans are not persisted.
+    fn persist_bans(&self, bans: &BTreeMap<BanTarget, PeerBan>) -> Result<(), Error> {
+        let persistence_path = match &self.persistence_path {
+            Some(persistence_path) => persistence_path,
+            None => return Ok(()), // Persistence is disabled
+        };
+
+        // Serialize the active bans
+        let now_secs = self.time_service.now_unix_time().as_secs();
+        let active_bans: Vec<_> = bans
+            .values()
+            .filter(|peer_ban| !peer_ban.is_expired(now_secs))
+            .collect();
+        let serialized_bans = serde_json::to_vec_pretty(&active_bans).map_err(|error| {
+            Error::UnexpectedError(format!("Failed to serialize the peer bans: {}", error))
+        })?;
+
+        // Write the bans to a temporary file and move it into place
+        let temp_path = persistence_path.with_extension("tmp");
+        fs::write(&temp_path, serialized_bans)
+            .and_then(|_| fs::rename(&temp_path, persistence_path))
+            .map_err(|error| {
+                Error::UnexpectedError(format!(
+                    "Failed to persist the peer bans to: {:?}, error: {}",
+                    persistence_path, error
+                ))
+            })
+    }
+}
+
+/// Loads the persisted bans from the given path
+fn load_persisted_bans(persistence_path: &Path) -> Result<Vec<PeerBan>, Error> {
+    let serialized_bans = fs::read(persistence_path).map_err(|error| {
+        Error::UnexpectedError(format!("Failed to read the peer bans file: {}", error))
+    })?;
+    serde_json::from_slice(&serialized_bans).map_err(|error| {
+        Error::UnexpectedError(format!("Failed to deserialize the peer bans: {}", error))
+    })
+}
+
+/// Serializes IP ranges using their string (CIDR) representation
+mod ip_range_serde {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(ip_range: &IpNet, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(ip_range)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<IpNet, D::Error> {
+        let ip_range = String::deserialize(deserializer)?;
+        IpNet::from_str(&ip_range).map_err(serde::de::Error::custom)
+    }
+}
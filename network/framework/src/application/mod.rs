@@ -2,6 +2,7 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod ban_list;
 pub mod error;
 pub mod interface;
 pub mod metadata;
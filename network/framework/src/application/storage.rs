@@ -4,6 +4,7 @@
 
 use crate::{
     application::{
+        ban_list::PeerBanList,
         error::Error,
         metadata::{ConnectionState, PeerMetadata},
     },
@@ -19,6 +20,7 @@ use aptos_config::{
 use aptos_infallible::{Mutex, RwLock};
 use aptos_logger::{sample, sample::SampleRate, warn};
 use aptos_peer_monitoring_service_types::PeerMonitoringMetadata;
+use aptos_time_service::TimeService;
 use aptos_types::{account_address::AccountAddress, PeerId};
 use arc_swap::ArcSwap;
 use std::{
@@ -52,16 +54,29 @@ pub struct PeersAndMetadata {
     cached_peers_and_metadata: Arc<ArcSwap<HashMap<NetworkId, HashMap<PeerId, PeerMetadata>>>>,
 
     subscribers: Mutex<Vec<tokio::sync::mpsc::Sender<ConnectionNotification>>>,
+
+    // The runtime list of banned peers and IP ranges (shared by all networks)
+    peer_ban_list: Arc<PeerBanList>,
 }
 
 impl PeersAndMetadata {
     pub fn new(network_ids: &[NetworkId]) -> Arc<PeersAndMetadata> {
+        let peer_ban_list = Arc::new(PeerBanList::new(TimeService::real()));
+        Self::new_with_peer_ban_list(network_ids, peer_ban_list)
+    }
+
+    /// Creates the container using the given peer ban list (e.g., a persisted one)
+    pub fn new_with_peer_ban_list(
+        network_ids: &[NetworkId],
+        peer_ban_list: Arc<PeerBanList>,
+    ) -> Arc<PeersAndMetadata> {
         // Create the container
         let mut peers_and_metadata = PeersAndMetadata {
             peers_and_metadata: RwLock::new(HashMap::new()),
             trusted_peers: HashMap::new(),
             cached_peers_and_metadata: Arc::new(ArcSwap::from(Arc::new(HashMap::new()))),
             subscribers: Mutex::new(vec![]),
+            peer_ban_list,
         };
 
         // Initialize each network mapping and trusted peer set
@@ -87,6 +102,11 @@ impl PeersAndMetadata {
         Arc::new(peers_and_metadata)
     }
 
+    /// Returns the runtime list of banned peers and IP ranges
+    pub fn get_peer_ban_list(&self) -> Arc<PeerBanList> {
+        self.peer_ban_list.clone()
+    }
+
     /// Returns all peers. Note: this will return disconnected and unhealthy peers, so
     /// it is not recommended for applications to use this interface. Instead,
     /// `get_connected_peers_and_metadata()` should be used.
@@ -4,6 +4,7 @@
 
 use crate::{
     application::{
+        ban_list::{BanTarget, PeerBanList},
         error::Error,
         interface::{NetworkClient, NetworkClientInterface, NetworkServiceEvents},
         metadata::{ConnectionState, PeerMetadata},
@@ -31,7 +32,10 @@ use aptos_config::{
     network_id::{NetworkId, PeerNetworkId},
 };
 use aptos_peer_monitoring_service_types::PeerMonitoringMetadata;
-use aptos_types::{account_address::AccountAddress, PeerId};
+use aptos_temppath::TempPath;
+use aptos_time_service::TimeService;
+use aptos_types::{account_address::AccountAddress, network_address::NetworkAddress, PeerId};
+use futures::FutureExt;
 use futures_util::StreamExt;
 use maplit::hashmap;
 use serde::{Deserialize, Serialize};
@@ -40,6 +44,7 @@ use std::{
     fmt::Debug,
     hash::Hash,
     ops::Deref,
+    str::FromStr,
     sync::Arc,
     time::Duration,
 };
@@ -947,6 +952,158 @@ fn check_available_peers(
     compare_vectors_ignore_order(available_peers, expected_peers);
 }
 
+#[test]
+fn test_peer_ban_list_peer_ids_and_ip_ranges() {
+    // Create a peer ban list
+    let peer_ban_list = PeerBanList::new(TimeService::mock());
+
+    // Create connections for two peers with different IP addresses
+    let connection_1 = create_connection_with_address(PeerId::random(), "/ip4/10.0.0.5/tcp/6180");
+    let connection_2 = create_connection_with_address(PeerId::random(), "/ip4/10.0.1.5/tcp/6180");
+
+    // Verify that neither connection is banned
+    assert!(peer_ban_list
+        .get_ban_for_connection(&connection_1)
+        .is_none());
+    assert!(peer_ban_list
+        .get_ban_for_connection(&connection_2)
+        .is_none());
+
+    // Ban the first peer by peer ID and verify that only the first connection is banned
+    let peer_id_target = BanTarget::PeerId(connection_1.remote_peer_id);
+    peer_ban_list
+        .ban(peer_id_target, None, Some("misbehaving".into()))
+        .unwrap();
+    assert!(peer_ban_list
+        .get_ban_for_connection(&connection_1)
+        .is_some());
+    assert!(peer_ban_list
+        .get_ban_for_connection(&connection_2)
+        .is_none());
+
+    // Ban the IP range of the second peer and verify that both connections are banned
+    let ip_range_target = BanTarget::parse_ip_range("10.0.1.0/24").unwrap();
+    peer_ban_list.ban(ip_range_target, None, None).unwrap();
+    assert!(peer_ban_list
+        .get_ban_for_connection(&connection_1)
+        .is_some());
+    assert!(peer_ban_list
+        .get_ban_for_connection(&connection_2)
+        .is_some());
+    assert_eq!(peer_ban_list.get_active_bans().len(), 2);
+
+    // Unban the first peer and verify that only the second connection is banned
+    let removed_ban = peer_ban_list.unban(&peer_id_target).unwrap().unwrap();
+    assert_eq!(removed_ban.target, peer_id_target);
+    assert!(peer_ban_list
+        .get_ban_for_connection(&connection_1)
+        .is_none());
+    assert!(peer_ban_list
+        .get_ban_for_connection(&connection_2)
+        .is_some());
+
+    // Verify that unbanning a target that isn't banned returns nothing
+    assert!(peer_ban_list.unban(&peer_id_target).unwrap().is_none());
+}
+
+#[test]
+fn test_peer_ban_list_expiry() {
+    // Create a peer ban list
+    let time_service = TimeService::mock();
+    let peer_ban_list = PeerBanList::new(time_service.clone());
+
+    // Ban a peer for 60 seconds
+    let connection = create_connection_with_address(PeerId::random(), "/ip4/10.0.0.5/tcp/6180");
+    let target = BanTarget::parse_peer_id(&connection.remote_peer_id.to_hex()).unwrap();
+    peer_ban_list
+        .ban(target, Some(Duration::from_secs(60)), None)
+        .unwrap();
+
+    // Elapse some time (but not enough for the ban to expire) and verify the peer is banned
+    let mock_time_service = time_service.into_mock();
+    mock_time_service.advance(Duration::from_secs(59));
+    assert!(peer_ban_list.get_ban_for_connection(&connection).is_some());
+
+    // Elapse the remaining time and verify the ban has expired
+    mock_time_service.advance(Duration::from_secs(1));
+    assert!(peer_ban_list.get_ban_for_connection(&connection).is_none());
+    assert!(peer_ban_list.get_active_bans().is_empty());
+}
+
+#[test]
+fn test_peer_ban_list_notifications() {
+    // Create a peer ban list and subscribe to ban notifications
+    let peer_ban_list = PeerBanList::new(TimeService::mock());
+    let mut ban_notifications = peer_ban_list.subscribe();
+
+    // Add several bans and verify that the notifications are coalesced
+    for _ in 0..3 {
+        peer_ban_list
+            .ban(BanTarget::PeerId(PeerId::random()), None, None)
+            .unwrap();
+    }
+    assert!(ban_notifications
+        .select_next_some()
+        .now_or_never()
+        .is_some());
+    assert!(ban_notifications
+        .select_next_some()
+        .now_or_never()
+        .is_none());
+
+    // Unban a peer and verify that no notification is sent
+    let target = peer_ban_list.get_active_bans()[0].target;
+    peer_ban_list.unban(&target).unwrap();
+    assert!(ban_notifications
+        .select_next_some()
+        .now_or_never()
+        .is_none());
+}
+
+#[test]
+fn test_peer_ban_list_persistence() {
+    // Create a persisted peer ban list
+    let persistence_path = TempPath::new();
+    let time_service = TimeService::mock();
+    let peer_ban_list =
+        PeerBanList::new_with_persistence(persistence_path.path().into(), time_service.clone());
+
+    // Add several bans (one of which will expire)
+    let peer_id_target = BanTarget::PeerId(PeerId::random());
+    let ip_range_target = BanTarget::parse_ip_range("2001:db8::/32").unwrap();
+    let expiring_target = BanTarget::parse_ip_range("192.168.0.1").unwrap();
+    peer_ban_list
+        .ban(peer_id_target, None, Some("spamming".into()))
+        .unwrap();
+    peer_ban_list
+        .ban(ip_range_target, Some(Duration::from_secs(1000)), None)
+        .unwrap();
+    peer_ban_list
+        .ban(expiring_target, Some(Duration::from_secs(10)), None)
+        .unwrap();
+
+    // Elapse enough time for the expiring ban to expire
+    time_service
+        .clone()
+        .into_mock()
+        .advance(Duration::from_secs(100));
+    let expected_bans = peer_ban_list.get_active_bans();
+    assert_eq!(expected_bans.len(), 2);
+
+    // Recreate the peer ban list (i.e., emulate a restart) and verify the bans are restored
+    let peer_ban_list =
+        PeerBanList::new_with_persistence(persistence_path.path().into(), time_service.clone());
+    assert_eq!(peer_ban_list.get_active_bans(), expected_bans);
+
+    // Unban a target, recreate the peer ban list and verify the ban was removed
+    peer_ban_list.unban(&peer_id_target).unwrap();
+    let peer_ban_list =
+        PeerBanList::new_with_persistence(persistence_path.path().into(), time_service.clone());
+    let active_bans = peer_ban_list.get_active_bans();
+    assert_eq!(active_bans.len(), 1);
+    assert_eq!(active_bans[0].target, ip_range_target);
+}
+
 /// Verifies that the registered networks are correct
 fn check_registered_networks(
     peers_and_metadata: &Arc<PeersAndMetadata>,
@@ -1000,6 +1157,13 @@ fn compare_vectors_ignore_order<T: Clone + Debug + Ord>(
     assert_eq!(vector_1, vector_2);
 }
 
+/// Creates new connection metadata for the given peer and network address
+fn create_connection_with_address(peer_id: PeerId, address: &str) -> ConnectionMetadata {
+    let mut connection_metadata = ConnectionMetadata::mock(peer_id);
+    connection_metadata.addr = NetworkAddress::from_str(address).unwrap();
+    connection_metadata
+}
+
 /// Returns an aptos channel for testing
 fn create_aptos_channel<K: Eq + Hash + Clone, T>(
 ) -> (aptos_channel::Sender<K, T>, aptos_channel::Receiver<K, T>) {
@@ -0,0 +1,393 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! An opt-in capture layer that records the application messages sent and
+//! received on each peer connection. Captured messages can be replayed
+//! against a node (see `testutils::capture_replay`) to deterministically
+//! reproduce protocol issues (e.g., mempool, state sync or consensus bugs).
+//!
+//! Messages are written by a dedicated thread to a set of rotating capture
+//! files (one directory per network). Each file is a sequence of records,
+//! where each record is a little-endian `u32` length prefix followed by the
+//! BCS encoded [`CapturedMessage`]. Once a file exceeds the configured size,
+//! a new file is started and the oldest files are deleted.
+//!
+//! RPC requests and responses are captured with their request IDs (and the
+//! protocol ID of the request), so that each response can be correlated with
+//! its request (see [`find_rpc_response`]). Captured messages can also be
+//! replayed against a live node (see the [`replay`] module), in which case the
+//! responses of the node are compared with the captured responses.
+
+use crate::{
+    counters::{self, CAPTURED_LABEL, DROPPED_LABEL},
+    protocols::wire::messaging::v1::RequestId,
+    ProtocolId,
+};
+use aptos_config::{
+    config::MessageCaptureConfig,
+    network_id::{NetworkContext, NetworkId},
+};
+use aptos_logger::prelude::*;
+use aptos_time_service::{TimeService, TimeServiceTrait};
+use aptos_types::PeerId;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, SyncSender},
+    thread,
+    time::Duration,
+};
+
+pub mod replay;
+#[cfg(test)]
+mod tests;
+
+/// The prefix and extension of each capture file
+const CAPTURE_FILE_PREFIX: &str = "capture-";
+const CAPTURE_FILE_EXTENSION: &str = "bin";
+
+/// The direction of a captured message (relative to the capturing node)
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum MessageDirection {
+    Inbound,
+    Outbound,
+}
+
+/// The type of a captured message. RPC messages hold their request ID, which
+/// is only unique per connection (and per RPC direction).
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum CapturedMessageType {
+    DirectSend,
+    RpcRequest(RequestId),
+    RpcResponse(RequestId),
+}
+
+/// A single message captured on a peer connection
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CapturedMessage {
+    pub timestamp_usecs: u64, // The unix time (in microseconds) at which the message was captured
+    pub network_id: NetworkId,
+    pub peer_id: PeerId, // The remote peer that sent (or received) the message
+    pub direction: MessageDirection,
+    pub message_type: CapturedMessageType,
+    pub protocol_id: ProtocolId,
+    #[serde(with = "serde_bytes")]
+    pub data: Vec<u8>, // The serialized application message (i.e., as sent on the wire)
+}
+
+impl CapturedMessage {
+    /// Decodes the captured data into an application message (using the protocol ID)
+    pub fn decode<T: DeserializeOwned>(&self) -> anyhow::Result<T> {
+        self.protocol_id.from_bytes(&self.data)
+    }
+
+    /// Returns the uncompressed application message bytes (e.g., to train compression dictionaries)
+    pub fn decompress(&self) -> anyhow::Result<Vec<u8>> {
+        self.protocol_id.decompress(&self.data)
+    }
+
+    /// Returns the size (in bytes) of the serialized application message
+    pub fn size_bytes(&self) -> usize {
+        self.data.len()
+    }
+}
+
+/// The message capture for a single network. Messages are handed off to a
+/// dedicated writer thread, so capturing never blocks the network. If the
+/// writer falls behind, new messages are dropped.
+pub struct MessageCapture {
+    network_context: NetworkContext,
+    capture_config: MessageCaptureConfig,
+    message_sender: SyncSender<CapturedMessage>,
+    time_service: TimeService,
+}
+
+impl MessageCapture {
+    /// Creates a new message capture and starts the writer thread
+    pub fn new(
+        network_context: NetworkContext,
+        capture_config: MessageCaptureConfig,
+        time_service: TimeService,
+    ) -> io::Result<Self> {
+        // Create the capture file writer
+        let capture_dir =
+            get_network_capture_dir(&capture_config.capture_dir, network_context.network_id());
+        let file_writer = CaptureFileWriter::new(
+            capture_dir.clone(),
+            capture_config.max_file_size_bytes,
+            capture_config.max_num_files,
+        )?;
+
+        // Start the writer thread
+        let (message_sender, message_receiver) =
+            mpsc::sync_channel(capture_config.max_pending_messages);
+        thread::Builder::new()
+            .name(format!("net-capture-{}", network_context.network_id()))
+            .spawn(move || file_writer.run(message_receiver, network_context))?;
+
+        info!(
+            "{} Capturing network messages to: {:?}",
+            network_context, capture_dir
+        );
+        Ok(Self {
+            network_context,
+            capture_config,
+            message_sender,
+            time_service,
+        })
+    }
+
+    /// Captures the given message (if the protocol should be captured)
+    pub fn capture_message(
+        &self,
+        peer_id: PeerId,
+        direction: MessageDirection,
+        message_type: CapturedMessageType,
+        protocol_id: ProtocolId,
+        data: &[u8],
+    ) {
+        if !self.capture_config.should_capture(protocol_id.as_str()) {
+            return;
+        }
+
+        let captured_message = CapturedMessage {
+            timestamp_usecs: self.time_service.now_unix_time().as_micros() as u64,
+            network_id: self.network_context.network_id(),
+            peer_id,
+            direction,
+            message_type,
+            protocol_id,
+            data: data.to_vec(),
+        };
+        let state_label = match self.message_sender.try_send(captured_message) {
+            Ok(()) => CAPTURED_LABEL,
+            Err(_) => DROPPED_LABEL, // The writer is falling behind (or has failed)
+        };
+        counters::captured_messages(&self.network_context, protocol_id, state_label).inc();
+    }
+}
+
+/// Writes captured messages to a set of rotating capture files
+struct CaptureFileWriter {
+    capture_dir: PathBuf,
+    max_file_size_bytes: u64,
+    max_num_files: usize,
+    next_file_index: u64,
+    current_file: Option<BufWriter<File>>,
+    current_file_size_bytes: u64,
+}
+
+impl CaptureFileWriter {
+    fn new(
+        capture_dir: PathBuf,
+        max_file_size_bytes: u64,
+        max_num_files: usize,
+    ) -> io::Result<Self> {
+        fs::create_dir_all(&capture_dir)?;
+
+        // Continue after any existing capture files (e.g., from before a restart)
+        let next_file_index = list_capture_files(&capture_dir)?
+            .last()
+            .map(|(file_index, _)| file_index + 1)
+            .unwrap_or(0);
+
+        Ok(Self {
+            capture_dir,
+            max_file_size_bytes,
+            max_num_files,
+            next_file_index,
+            current_file: None,
+            current_file_size_bytes: 0,
+        })
+    }
+
+    /// Writes all captured messages until the message sender is dropped
+    fn run(mut self, message_receiver: Receiver<CapturedMessage>, network_context: NetworkContext) {
+        while let Ok(captured_message) = message_receiver.recv() {
+            // Write the message, and any others that are already pending
+            let mut result = self.write_message(&captured_message);
+            while result.is_ok() {
+                match message_receiver.try_recv() {
+                    Ok(captured_message) => result = self.write_message(&captured_message),
+                    Err(_) => break, // There are no more pending messages
+                }
+            }
+
+            // Flush the file once there are no more pending messages
+            if let Err(error) = result.and_then(|_| self.flush()) {
+                sample!(
+                    SampleRate::Duration(Duration::from_secs(10)),
+                    warn!(
+                        "{} [sampled] Failed to write captured messages to: {:?}. Error: {:?}",
+                        network_context, self.capture_dir, error
+                    )
+                );
+            }
+        }
+    }
+
+    /// Writes the given message to the current capture file (rotating files if required)
+    fn write_message(&mut self, captured_message: &CapturedMessage) -> io::Result<()> {
+        let message_bytes = bcs::to_bytes(captured_message)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        let record_size_bytes = (message_bytes.len() + 4) as u64;
+
+        // Rotate the capture file if it is full (or no file is open yet)
+        if self.current_file.is_none()
+            || self.current_file_size_bytes + record_size_bytes > self.max_file_size_bytes
+        {
+            self.rotate_file()?;
+        }
+
+        // Write the length prefixed record
+        if let Some(current_file) = self.current_file.as_mut() {
+            current_file.write_all(&(message_bytes.len() as u32).to_le_bytes())?;
+            current_file.write_all(&message_bytes)?;
+            self.current_file_size_bytes += record_size_bytes;
+        }
+        Ok(())
+    }
+
+    /// Starts a new capture file and removes the oldest files (if there are too many)
+    fn rotate_file(&mut self) -> io::Result<()> {
+        if let Some(mut current_file) = self.current_file.take() {
+            current_file.flush()?;
+        }
+
+        // Create the new capture file
+        let file_path = get_capture_file_path(&self.capture_dir, self.next_file_index);
+        self.current_file = Some(BufWriter::new(File::create(file_path)?));
+        self.current_file_size_bytes = 0;
+        self.next_file_index += 1;
+
+        // Remove the oldest capture files
+        let capture_files = list_capture_files(&self.capture_dir)?;
+        let num_files_to_remove = capture_files.len().saturating_sub(self.max_num_files);
+        for (_, file_path) in capture_files.iter().take(num_files_to_remove) {
+            fs::remove_file(file_path)?;
+        }
+
+        Ok(())
+    }
+
+    /// Flushes the current capture file (if any)
+    fn flush(&mut self) -> io::Result<()> {
+        match self.current_file.as_mut() {
+            Some(current_file) => current_file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Returns the capture directory for the given network
+pub fn get_network_capture_dir(capture_dir: &Path, network_id: NetworkId) -> PathBuf {
+    capture_dir.join(network_id.as_str().to_lowercase())
+}
+
+/// Returns the path of the capture file with the given index
+fn get_capture_file_path(capture_dir: &Path, file_index: u64) -> PathBuf {
+    capture_dir.join(format!(
+        "{}{:010}.{}",
+        CAPTURE_FILE_PREFIX, file_index, CAPTURE_FILE_EXTENSION
+    ))
+}
+
+/// Returns all capture files in the given directory (sorted by file index)
+fn list_capture_files(capture_dir: &Path) -> io::Result<Vec<(u64, PathBuf)>> {
+    let mut capture_files = vec![];
+    for entry in fs::read_dir(capture_dir)? {
+        let file_path = entry?.path();
+        let file_index = file_path
+            .file_name()
+            .and_then(|file_name| file_name.to_str())
+            .and_then(|file_name| file_name.strip_prefix(CAPTURE_FILE_PREFIX))
+            .and_then(|file_name| file_name.strip_suffix(CAPTURE_FILE_EXTENSION))
+            .and_then(|file_index| file_index.strip_suffix('.'))
+            .and_then(|file_index| file_index.parse::<u64>().ok());
+        if let Some(file_index) = file_index {
+            capture_files.push((file_index, file_path));
+        }
+    }
+    capture_files.sort();
+
+    Ok(capture_files)
+}
+
+/// Returns the captured response to the RPC request at the given index (if
+/// any). The response is the first subsequent RPC response with the same
+/// request ID, sent in the opposite direction on the same connection. As
+/// request IDs are reused across connections, the search stops at the next
+/// RPC request from the same peer with the same ID.
+pub fn find_rpc_response(
+    captured_messages: &[CapturedMessage],
+    request_index: usize,
+) -> Option<&CapturedMessage> {
+    let request = captured_messages.get(request_index)?;
+    let CapturedMessageType::RpcRequest(request_id) = request.message_type else {
+        return None;
+    };
+
+    for captured_message in &captured_messages[request_index + 1..] {
+        if captured_message.network_id != request.network_id
+            || captured_message.peer_id != request.peer_id
+        {
+            continue;
+        }
+        match captured_message.message_type {
+            CapturedMessageType::RpcResponse(response_id)
+                if response_id == request_id && captured_message.direction != request.direction =>
+            {
+                return Some(captured_message);
+            },
+            CapturedMessageType::RpcRequest(next_request_id)
+                if next_request_id == request_id
+                    && captured_message.direction == request.direction =>
+            {
+                return None; // The request ID was reused (e.g., on a new connection)
+            },
+            _ => {},
+        }
+    }
+    None
+}
+
+/// Reads all captured messages in the given (network) capture directory,
+/// in the order in which they were captured.
+pub fn read_captured_messages(capture_dir: &Path) -> io::Result<Vec<CapturedMessage>> {
+    let mut captured_messages = vec![];
+    for (_, file_path) in list_capture_files(capture_dir)? {
+        captured_messages.extend(read_capture_file(&file_path)?);
+    }
+    Ok(captured_messages)
+}
+
+/// Reads all captured messages in the given capture file. A truncated
+/// record at the end of the file (e.g., due to a crash) is ignored.
+pub fn read_capture_file(file_path: &Path) -> io::Result<Vec<CapturedMessage>> {
+    let mut reader = BufReader::new(File::open(file_path)?);
+    let mut captured_messages = vec![];
+    loop {
+        // Read the record length
+        let mut length_bytes = [0u8; 4];
+        match reader.read_exact(&mut length_bytes) {
+            Ok(()) => (),
+            Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => break,
+            Err(error) => return Err(error),
+        }
+
+        // Read and deserialize the record
+        let mut message_bytes = vec![0u8; u32::from_le_bytes(length_bytes) as usize];
+        match reader.read_exact(&mut message_bytes) {
+            Ok(()) => (),
+            Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => break,
+            Err(error) => return Err(error),
+        }
+        let captured_message = bcs::from_bytes(&message_bytes)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        captured_messages.push(captured_message);
+    }
+
+    Ok(captured_messages)
+}
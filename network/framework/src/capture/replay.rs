@@ -0,0 +1,315 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Replays captured messages against a live node, over an authenticated
+//! connection to the node (e.g., see `aptos-network-capture-replay`).
+//!
+//! Inbound direct send messages and RPC requests are resent in the order in
+//! which they were captured. After each RPC request, the replay waits for the
+//! response of the node and compares it with the captured response (if any).
+//! Health checker pings from the node are answered, so that the connection is
+//! kept alive, and all other messages sent by the node are ignored.
+
+use crate::{
+    capture::{find_rpc_response, CapturedMessage, CapturedMessageType, MessageDirection},
+    protocols::{
+        health_checker::HealthCheckerMsg,
+        stream::{InboundStreamBuffer, MessageFragmenter, StreamMessage},
+        wire::messaging::v1::{
+            DirectSendMsg, MultiplexMessage, MultiplexMessageSink, MultiplexMessageStream,
+            NetworkMessage, RequestId, RpcRequest, RpcResponse,
+        },
+    },
+    ProtocolId,
+};
+use futures::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, ReadHalf, WriteHalf},
+    SinkExt, StreamExt,
+};
+use std::{fmt, io, time::Duration};
+
+/// The configuration for replaying captured messages
+#[derive(Clone, Debug)]
+pub struct ReplayConfig {
+    pub max_frame_size: usize,
+    pub max_message_size: usize,
+    pub preserve_timing: bool, // Whether to reproduce the captured delays between messages
+    pub rpc_timeout: Duration, // The time to wait for the response to each RPC request
+}
+
+/// The outcome of a replayed RPC request
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RpcReplayOutcome {
+    Matched,     // The response is identical to the captured response
+    Mismatched,  // The response differs from the captured response
+    NotCaptured, // A response was received, but none was captured for the request
+    TimedOut,    // No response was received before the timeout
+}
+
+/// A summary of the replayed messages and RPC outcomes
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ReplaySummary {
+    pub num_direct_sends: u64,
+    pub num_rpc_requests: u64,
+    pub num_matched_responses: u64,
+    pub num_mismatched_responses: u64,
+    pub num_uncaptured_responses: u64,
+    pub num_timed_out_requests: u64,
+}
+
+impl ReplaySummary {
+    fn record_rpc_outcome(&mut self, outcome: RpcReplayOutcome) {
+        self.num_rpc_requests += 1;
+        match outcome {
+            RpcReplayOutcome::Matched => self.num_matched_responses += 1,
+            RpcReplayOutcome::Mismatched => self.num_mismatched_responses += 1,
+            RpcReplayOutcome::NotCaptured => self.num_uncaptured_responses += 1,
+            RpcReplayOutcome::TimedOut => self.num_timed_out_requests += 1,
+        }
+    }
+}
+
+impl fmt::Display for ReplaySummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Replayed {} direct send messages and {} RPC requests. RPC responses: {} matched, \
+            {} mismatched, {} not captured, {} timed out.",
+            self.num_direct_sends,
+            self.num_rpc_requests,
+            self.num_matched_responses,
+            self.num_mismatched_responses,
+            self.num_uncaptured_responses,
+            self.num_timed_out_requests
+        )
+    }
+}
+
+/// Replays the inbound messages in the given captured messages (in capture
+/// order) over the socket, and returns a summary of the replay.
+pub async fn replay_captured_messages<TSocket>(
+    socket: TSocket,
+    captured_messages: &[CapturedMessage],
+    replay_config: &ReplayConfig,
+) -> io::Result<ReplaySummary>
+where
+    TSocket: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut connection = ReplayConnection::new(socket, replay_config);
+    let mut summary = ReplaySummary::default();
+    let mut next_request_id: RequestId = 0;
+    let mut last_timestamp_usecs = None;
+
+    for (message_index, captured_message) in captured_messages.iter().enumerate() {
+        if captured_message.direction != MessageDirection::Inbound {
+            continue; // Only messages sent to the capturing node are replayed
+        }
+
+        // Create the message to replay
+        let protocol_id = captured_message.protocol_id;
+        let message = match captured_message.message_type {
+            CapturedMessageType::DirectSend => NetworkMessage::DirectSendMsg(DirectSendMsg {
+                protocol_id,
+                priority: 0,
+                raw_msg: captured_message.data.clone(),
+            }),
+            CapturedMessageType::RpcRequest(_) => NetworkMessage::RpcRequest(RpcRequest {
+                protocol_id,
+                request_id: next_request_id,
+                priority: 0,
+                raw_request: captured_message.data.clone(),
+            }),
+            CapturedMessageType::RpcResponse(_) => continue, // Responses to the capturing node
+        };
+
+        // Wait for the captured delay (if required), while handling inbound messages
+        if replay_config.preserve_timing {
+            if let Some(last_timestamp_usecs) = last_timestamp_usecs {
+                let delay_usecs = captured_message
+                    .timestamp_usecs
+                    .saturating_sub(last_timestamp_usecs);
+                connection
+                    .receive_until(Duration::from_micros(delay_usecs), None)
+                    .await?;
+            }
+            last_timestamp_usecs = Some(captured_message.timestamp_usecs);
+        }
+
+        // Send the message to the node
+        connection.send(message).await?;
+
+        // Wait for the RPC response (if required) and compare it with the captured response
+        if let CapturedMessageType::RpcRequest(_) = captured_message.message_type {
+            let request_id = next_request_id;
+            next_request_id = next_request_id.wrapping_add(1);
+
+            let response = connection
+                .receive_until(replay_config.rpc_timeout, Some(request_id))
+                .await?;
+            let outcome = match (
+                response,
+                find_rpc_response(captured_messages, message_index),
+            ) {
+                (None, _) => RpcReplayOutcome::TimedOut,
+                (Some(_), None) => RpcReplayOutcome::NotCaptured,
+                (Some(response), Some(captured_response)) => {
+                    if response.raw_response == captured_response.data {
+                        RpcReplayOutcome::Matched
+                    } else {
+                        RpcReplayOutcome::Mismatched
+                    }
+                },
+            };
+            summary.record_rpc_outcome(outcome);
+        } else {
+            summary.num_direct_sends += 1;
+
+            // Handle any messages that are already pending (e.g., health checks)
+            connection.receive_until(Duration::ZERO, None).await?;
+        }
+    }
+
+    connection.close().await?;
+    Ok(summary)
+}
+
+/// A connection to the node on which messages are replayed
+struct ReplayConnection<TSocket> {
+    message_stream: MultiplexMessageStream<ReadHalf<TSocket>>,
+    message_sink: MultiplexMessageSink<WriteHalf<TSocket>>,
+    message_fragmenter: MessageFragmenter,
+    inbound_stream: InboundStreamBuffer,
+}
+
+impl<TSocket: AsyncRead + AsyncWrite + Unpin> ReplayConnection<TSocket> {
+    fn new(socket: TSocket, replay_config: &ReplayConfig) -> Self {
+        let (reader, writer) = socket.split();
+        let max_frame_size = replay_config.max_frame_size;
+        let max_message_size = replay_config.max_message_size;
+        Self {
+            message_stream: MultiplexMessageStream::new(reader, max_frame_size),
+            message_sink: MultiplexMessageSink::new(writer, max_frame_size),
+            message_fragmenter: MessageFragmenter::new(max_frame_size, max_message_size),
+            inbound_stream: InboundStreamBuffer::new(max_message_size / max_frame_size),
+        }
+    }
+
+    /// Sends the message to the node (streaming it in fragments, if required)
+    async fn send(&mut self, message: NetworkMessage) -> io::Result<()> {
+        if self.message_fragmenter.should_stream(&message) {
+            let stream_messages = self
+                .message_fragmenter
+                .fragment_message(message)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error.to_string()))?;
+            for stream_message in stream_messages {
+                self.send_multiplex_message(MultiplexMessage::Stream(stream_message))
+                    .await?;
+            }
+            Ok(())
+        } else {
+            self.send_multiplex_message(MultiplexMessage::Message(message))
+                .await
+        }
+    }
+
+    async fn send_multiplex_message(&mut self, message: MultiplexMessage) -> io::Result<()> {
+        self.message_sink
+            .send(&message)
+            .await
+            .map_err(|error| io::Error::new(io::ErrorKind::Other, error.to_string()))
+    }
+
+    /// Handles inbound messages until the timeout elapses, or until the response
+    /// to the given RPC request is received (in which case it is returned).
+    async fn receive_until(
+        &mut self,
+        timeout: Duration,
+        request_id: Option<RequestId>,
+    ) -> io::Result<Option<RpcResponse>> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            // Note: the pending message is always polled before the deadline
+            let message = match tokio::time::timeout_at(deadline, self.message_stream.next()).await
+            {
+                Ok(Some(Ok(message))) => message,
+                Ok(Some(Err(error))) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        error.to_string(),
+                    ))
+                },
+                Ok(None) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "The node closed the connection!",
+                    ))
+                },
+                Err(_) => return Ok(None), // The timeout elapsed
+            };
+
+            // Reassemble streamed messages
+            let message = match message {
+                MultiplexMessage::Message(message) => message,
+                MultiplexMessage::Stream(StreamMessage::Header(header)) => {
+                    self.inbound_stream
+                        .new_stream(header)
+                        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+                    continue;
+                },
+                MultiplexMessage::Stream(StreamMessage::Fragment(fragment)) => {
+                    match self
+                        .inbound_stream
+                        .append_fragment(fragment)
+                        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?
+                    {
+                        Some(message) => message,
+                        None => continue, // The stream is not complete yet
+                    }
+                },
+            };
+
+            match message {
+                NetworkMessage::RpcResponse(response)
+                    if Some(response.request_id) == request_id =>
+                {
+                    return Ok(Some(response));
+                },
+                NetworkMessage::RpcRequest(request)
+                    if request.protocol_id == ProtocolId::HealthCheckerRpc =>
+                {
+                    self.respond_to_health_check(request).await?;
+                },
+                _ => {}, // Ignore all other messages (e.g., direct sends from the node)
+            }
+        }
+    }
+
+    /// Responds to the health checker ping of the node
+    async fn respond_to_health_check(&mut self, request: RpcRequest) -> io::Result<()> {
+        let to_io_error = |error: anyhow::Error| io::Error::new(io::ErrorKind::InvalidData, error);
+        let HealthCheckerMsg::Ping(ping) = request
+            .protocol_id
+            .from_bytes(&request.raw_request)
+            .map_err(to_io_error)?
+        else {
+            return Ok(()); // Only pings are answered
+        };
+        let response = RpcResponse {
+            request_id: request.request_id,
+            priority: request.priority,
+            raw_response: request
+                .protocol_id
+                .to_bytes(&HealthCheckerMsg::Pong(ping.to_pong()))
+                .map_err(to_io_error)?,
+        };
+        self.send(NetworkMessage::RpcResponse(response)).await
+    }
+
+    /// Closes the connection to the node
+    async fn close(mut self) -> io::Result<()> {
+        self.message_sink
+            .close()
+            .await
+            .map_err(|error| io::Error::new(io::ErrorKind::Other, error.to_string()))
+    }
+}
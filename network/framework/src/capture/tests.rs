@@ -0,0 +1,303 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    capture::{
+        find_rpc_response, get_network_capture_dir, list_capture_files, read_capture_file,
+        read_captured_messages, CaptureFileWriter, CapturedMessage, CapturedMessageType,
+        MessageCapture, MessageDirection,
+    },
+    ProtocolId,
+};
+use aptos_config::{
+    config::MessageCaptureConfig,
+    network_id::{NetworkContext, NetworkId},
+};
+use aptos_temppath::TempPath;
+use aptos_time_service::TimeService;
+use aptos_types::PeerId;
+use std::{fs::OpenOptions, io::Write, path::Path, thread, time::Duration};
+
+#[test]
+fn test_capture_and_read_messages() {
+    // Create a message capture that only captures mempool messages
+    let capture_dir = TempPath::new();
+    capture_dir.create_as_dir().unwrap();
+    let capture_config = MessageCaptureConfig {
+        capture_dir: capture_dir.path().into(),
+        protocols: vec![
+            ProtocolId::MempoolDirectSend.as_str().into(),
+            ProtocolId::MempoolRpc.as_str().into(),
+        ],
+        ..Default::default()
+    };
+    let network_context = NetworkContext::mock();
+    let message_capture =
+        MessageCapture::new(network_context, capture_config, TimeService::mock()).unwrap();
+
+    // Capture several messages (including some that should be filtered out)
+    let peer_id = PeerId::random();
+    message_capture.capture_message(
+        peer_id,
+        MessageDirection::Inbound,
+        CapturedMessageType::DirectSend,
+        ProtocolId::MempoolDirectSend,
+        &[0, 1, 2],
+    );
+    message_capture.capture_message(
+        peer_id,
+        MessageDirection::Outbound,
+        CapturedMessageType::DirectSend,
+        ProtocolId::ConsensusDirectSendBcs,
+        &[3, 4, 5],
+    );
+    message_capture.capture_message(
+        peer_id,
+        MessageDirection::Outbound,
+        CapturedMessageType::RpcRequest(0),
+        ProtocolId::MempoolRpc,
+        &[6, 7, 8],
+    );
+
+    // Wait for the messages to be written and verify they were captured in order
+    let network_capture_dir =
+        get_network_capture_dir(capture_dir.path(), network_context.network_id());
+    let captured_messages = wait_for_captured_messages(&network_capture_dir, 2);
+    assert_eq!(captured_messages.len(), 2);
+    verify_captured_message(
+        &captured_messages[0],
+        peer_id,
+        MessageDirection::Inbound,
+        CapturedMessageType::DirectSend,
+        ProtocolId::MempoolDirectSend,
+        vec![0, 1, 2],
+    );
+    verify_captured_message(
+        &captured_messages[1],
+        peer_id,
+        MessageDirection::Outbound,
+        CapturedMessageType::RpcRequest(0),
+        ProtocolId::MempoolRpc,
+        vec![6, 7, 8],
+    );
+}
+
+#[test]
+fn test_capture_file_rotation() {
+    // Create a capture file writer that holds roughly two messages per file
+    let capture_dir = TempPath::new();
+    capture_dir.create_as_dir().unwrap();
+    let message_size_bytes = bcs::to_bytes(&create_captured_message(0)).unwrap().len() + 4;
+    let max_file_size_bytes = (2 * message_size_bytes) as u64;
+    let max_num_files = 3;
+    let mut file_writer = CaptureFileWriter::new(
+        capture_dir.path().into(),
+        max_file_size_bytes,
+        max_num_files,
+    )
+    .unwrap();
+
+    // Write several messages
+    let num_messages = 10;
+    for index in 0..num_messages {
+        file_writer
+            .write_message(&create_captured_message(index))
+            .unwrap();
+    }
+    file_writer.flush().unwrap();
+
+    // Verify that only the most recent files (and messages) remain
+    let capture_files = list_capture_files(capture_dir.path()).unwrap();
+    let file_indices: Vec<_> = capture_files.iter().map(|(index, _)| *index).collect();
+    assert_eq!(file_indices, vec![2, 3, 4]);
+    let captured_messages = read_captured_messages(capture_dir.path()).unwrap();
+    let expected_messages: Vec<_> = (4..num_messages).map(create_captured_message).collect();
+    assert_eq!(captured_messages, expected_messages);
+
+    // Create a new writer (e.g., after a restart) and verify the file indices continue
+    let mut file_writer = CaptureFileWriter::new(
+        capture_dir.path().into(),
+        max_file_size_bytes,
+        max_num_files,
+    )
+    .unwrap();
+    file_writer
+        .write_message(&create_captured_message(num_messages))
+        .unwrap();
+    file_writer.flush().unwrap();
+    let capture_files = list_capture_files(capture_dir.path()).unwrap();
+    let file_indices: Vec<_> = capture_files.iter().map(|(index, _)| *index).collect();
+    assert_eq!(file_indices, vec![3, 4, 5]);
+}
+
+#[test]
+fn test_truncated_capture_file() {
+    // Write several messages to a capture file
+    let capture_dir = TempPath::new();
+    capture_dir.create_as_dir().unwrap();
+    let mut file_writer = CaptureFileWriter::new(capture_dir.path().into(), u64::MAX, 1).unwrap();
+    for index in 0..3 {
+        file_writer
+            .write_message(&create_captured_message(index))
+            .unwrap();
+    }
+    file_writer.flush().unwrap();
+
+    // Append a truncated record to the file (e.g., as if the node crashed)
+    let (_, file_path) = list_capture_files(capture_dir.path()).unwrap().remove(0);
+    let mut file = OpenOptions::new().append(true).open(&file_path).unwrap();
+    file.write_all(&100u32.to_le_bytes()).unwrap();
+    file.write_all(&[1, 2, 3]).unwrap();
+
+    // Verify that the complete messages can still be read
+    let captured_messages = read_capture_file(&file_path).unwrap();
+    let expected_messages: Vec<_> = (0..3).map(create_captured_message).collect();
+    assert_eq!(captured_messages, expected_messages);
+}
+
+#[test]
+fn test_decode_captured_message() {
+    // Create a captured message for a BCS protocol
+    let message = (10u64, "hello world".to_string());
+    let captured_message = CapturedMessage {
+        data: ProtocolId::ConsensusRpcBcs.to_bytes(&message).unwrap(),
+        protocol_id: ProtocolId::ConsensusRpcBcs,
+        ..create_captured_message(0)
+    };
+
+    // Verify that the message can be decoded
+    let decoded_message: (u64, String) = captured_message.decode().unwrap();
+    assert_eq!(decoded_message, message);
+    assert_eq!(captured_message.size_bytes(), captured_message.data.len());
+}
+
+#[test]
+fn test_decompress_captured_message() {
+    // Create a captured message for a compressed protocol
+    let message = (10u64, "hello world".repeat(10));
+    let protocol_id = ProtocolId::ConsensusDirectSendCompressed;
+    let captured_message = CapturedMessage {
+        data: protocol_id.to_bytes(&message).unwrap(),
+        protocol_id,
+        ..create_captured_message(0)
+    };
+
+    // Verify that the message is decompressed into its BCS bytes
+    assert_ne!(captured_message.data, bcs::to_bytes(&message).unwrap());
+    assert_eq!(
+        captured_message.decompress().unwrap(),
+        bcs::to_bytes(&message).unwrap()
+    );
+}
+
+#[test]
+fn test_find_rpc_response() {
+    // Create an inbound RPC request, followed by unrelated messages and the response
+    let peer_id = PeerId::random();
+    let create_rpc_message = |direction, message_type, peer_id| CapturedMessage {
+        peer_id,
+        direction,
+        message_type,
+        protocol_id: ProtocolId::StorageServiceRpc,
+        ..create_captured_message(0)
+    };
+    let captured_messages = vec![
+        create_rpc_message(
+            MessageDirection::Inbound,
+            CapturedMessageType::RpcRequest(5),
+            peer_id,
+        ),
+        create_rpc_message(
+            MessageDirection::Outbound,
+            CapturedMessageType::RpcResponse(5),
+            PeerId::random(), // A response to another peer
+        ),
+        create_rpc_message(
+            MessageDirection::Inbound,
+            CapturedMessageType::RpcResponse(5),
+            peer_id, // A response to one of our own requests
+        ),
+        create_rpc_message(
+            MessageDirection::Outbound,
+            CapturedMessageType::RpcResponse(6),
+            peer_id,
+        ),
+        create_rpc_message(
+            MessageDirection::Outbound,
+            CapturedMessageType::RpcResponse(5),
+            peer_id,
+        ),
+    ];
+
+    // Verify that the response is correlated with the request
+    let response = find_rpc_response(&captured_messages, 0).unwrap();
+    assert_eq!(response, &captured_messages[4]);
+
+    // Verify that no responses are found for other message types
+    assert!(find_rpc_response(&captured_messages, 1).is_none());
+    assert!(find_rpc_response(&captured_messages, 10).is_none());
+
+    // Verify that the search stops if the request ID is reused (e.g., on a new connection)
+    let mut captured_messages = captured_messages;
+    captured_messages.insert(
+        1,
+        create_rpc_message(
+            MessageDirection::Inbound,
+            CapturedMessageType::RpcRequest(5),
+            peer_id,
+        ),
+    );
+    assert!(find_rpc_response(&captured_messages, 0).is_none());
+    assert_eq!(
+        find_rpc_response(&captured_messages, 1).unwrap(),
+        &captured_messages[5]
+    );
+}
+
+/// Creates a captured message with the given index
+fn create_captured_message(index: u64) -> CapturedMessage {
+    CapturedMessage {
+        timestamp_usecs: index,
+        network_id: NetworkId::Public,
+        peer_id: PeerId::ZERO,
+        direction: MessageDirection::Inbound,
+        message_type: CapturedMessageType::DirectSend,
+        protocol_id: ProtocolId::MempoolDirectSend,
+        data: index.to_le_bytes().to_vec(),
+    }
+}
+
+/// Verifies that the captured message has the expected fields
+fn verify_captured_message(
+    captured_message: &CapturedMessage,
+    peer_id: PeerId,
+    direction: MessageDirection,
+    message_type: CapturedMessageType,
+    protocol_id: ProtocolId,
+    data: Vec<u8>,
+) {
+    assert_eq!(captured_message.peer_id, peer_id);
+    assert_eq!(captured_message.direction, direction);
+    assert_eq!(captured_message.message_type, message_type);
+    assert_eq!(captured_message.protocol_id, protocol_id);
+    assert_eq!(captured_message.data, data);
+}
+
+/// Waits until the expected number of messages have been written to the capture directory
+fn wait_for_captured_messages(
+    capture_dir: &Path,
+    expected_num_messages: usize,
+) -> Vec<CapturedMessage> {
+    for _ in 0..100 {
+        if let Ok(captured_messages) = read_captured_messages(capture_dir) {
+            if captured_messages.len() >= expected_num_messages {
+                return captured_messages;
+            }
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    panic!(
+        "Timed out waiting for {} captured messages!",
+        expected_num_messages
+    );
+}
@@ -10,11 +10,14 @@
 //! Consensus actor informs the ConnectivityManager of eligible nodes.
 //!
 //! Different discovery sources notify the ConnectivityManager of updates to
-//! peers' addresses. Currently, there are 2 discovery sources (ordered by
+//! peers' addresses. Currently, there are 5 discovery sources (ordered by
 //! decreasing dial priority, i.e., first is highest priority):
 //!
 //! 1. Onchain discovery protocol
-//! 2. Seed peers from config
+//! 2. File discovery (peers read from a local file)
+//! 3. REST discovery (validator set fetched from a REST endpoint)
+//! 4. DNS discovery (peers published in TXT and SRV records)
+//! 5. Seed peers from config
 //!
 //! In other words, if a we have some addresses discovered via onchain discovery
 //! and some seed addresses from our local config, we will try the onchain
@@ -141,6 +144,7 @@ pub enum DiscoverySource {
     OnChainValidatorSet,
     File,
     Rest,
+    Dns,
     Config,
 }
 
@@ -157,6 +161,7 @@ impl fmt::Display for DiscoverySource {
             DiscoverySource::File => "File",
             DiscoverySource::Config => "Config",
             DiscoverySource::Rest => "Rest",
+            DiscoverySource::Dns => "Dns",
         })
     }
 }
@@ -27,6 +27,8 @@ pub const SENT_LABEL: &str = "sent";
 pub const SUCCEEDED_LABEL: &str = "succeeded";
 pub const FAILED_LABEL: &str = "failed";
 pub const UNKNOWN_LABEL: &str = "unknown";
+pub const CAPTURED_LABEL: &str = "captured";
+pub const DROPPED_LABEL: &str = "dropped";
 
 // Connection operation labels
 pub const DIAL_LABEL: &str = "dial";
@@ -451,24 +453,62 @@ pub static PENDING_WIRE_MESSAGES: Lazy<IntCounterVec> = Lazy::new(|| {
     .unwrap()
 });
 
-/// Counter of messages pending in queue to be sent out on the multiplex channel
-pub static PENDING_MULTIPLEX_MESSAGE: Lazy<IntGauge> = Lazy::new(|| {
-    register_int_gauge!(
-        "aptos_network_pending_multiplex_messages",
-        "Number of pending multiplex messages"
+/// Gauge of outbound messages queued (across all peers) in the outbound scheduler
+pub static OUTBOUND_QUEUE_DEPTH: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "aptos_network_outbound_queue_depth",
+        "Number of outbound messages queued in the outbound scheduler",
+        &["network_id", "protocol_id"]
     )
     .unwrap()
 });
 
-/// Counter of stream messages pending in queue to be sent out on the multiplex channel
-pub static PENDING_MULTIPLEX_STREAM: Lazy<IntGauge> = Lazy::new(|| {
-    register_int_gauge!(
-        "aptos_network_pending_multiplex_stream",
-        "Number of pending multiplex stream messages"
+/// Returns the outbound queue depth gauge for the given protocol
+pub fn outbound_queue_depth(network_context: &NetworkContext, protocol_label: &str) -> IntGauge {
+    OUTBOUND_QUEUE_DEPTH.with_label_values(&[network_context.network_id().as_str(), protocol_label])
+}
+
+/// Time an outbound message sits in the outbound scheduler before it is fully written
+pub static OUTBOUND_QUEUE_DELAY: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "aptos_network_outbound_queue_time",
+        "Time an outbound message sits in queue before it is written to the peer socket",
+        &["network_id", "protocol_id"],
+        exponential_buckets(/*start=*/ 1e-6, /*factor=*/ 2.0, /*count=*/ 20).unwrap(),
     )
     .unwrap()
 });
 
+/// Observes the outbound queue delay for the given protocol
+pub fn outbound_queue_delay_observe(
+    network_context: &NetworkContext,
+    protocol_label: &str,
+    seconds: f64,
+) {
+    OUTBOUND_QUEUE_DELAY
+        .with_label_values(&[network_context.network_id().as_str(), protocol_label])
+        .observe(seconds)
+}
+
+/// Counter of outbound messages dropped by the outbound scheduler (because the queue was full)
+pub static OUTBOUND_QUEUE_DROPPED_MESSAGES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_network_outbound_queue_dropped_messages",
+        "Number of outbound messages dropped by the outbound scheduler",
+        &["network_id", "protocol_id"]
+    )
+    .unwrap()
+});
+
+/// Returns the dropped outbound messages counter for the given protocol
+pub fn outbound_queue_dropped_messages(
+    network_context: &NetworkContext,
+    protocol_label: &str,
+) -> IntCounter {
+    OUTBOUND_QUEUE_DROPPED_MESSAGES
+        .with_label_values(&[network_context.network_id().as_str(), protocol_label])
+}
+
 /// Counter of pending requests in Direct Send
 pub static PENDING_DIRECT_SEND_REQUESTS: Lazy<IntGauge> = Lazy::new(|| {
     register_int_gauge!(
@@ -681,3 +721,25 @@ pub fn inbound_queue_delay_observe(protocol_id: ProtocolId, seconds: f64) {
         .with_label_values(&[protocol_id.as_str()])
         .observe(seconds)
 }
+
+pub static APTOS_NETWORK_CAPTURED_MESSAGES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_network_captured_messages",
+        "Number of network messages handled by the message capture",
+        &["role_type", "network_id", "protocol_id", "state"]
+    )
+    .unwrap()
+});
+
+pub fn captured_messages(
+    network_context: &NetworkContext,
+    protocol_id: ProtocolId,
+    state_label: &'static str,
+) -> IntCounter {
+    APTOS_NETWORK_CAPTURED_MESSAGES.with_label_values(&[
+        network_context.role().as_str(),
+        network_context.network_id().as_str(),
+        protocol_id.as_str(),
+        state_label,
+    ])
+}
@@ -11,6 +11,7 @@
 
 use aptos_crypto::{noise, x25519};
 use aptos_logger::prelude::*;
+use aptos_netcore::transport::{quic::QuicConnection, MultiStreamSocket};
 use futures::{
     io::{AsyncRead, AsyncWrite},
     ready,
@@ -63,6 +64,15 @@ impl<TSocket> NoiseStream<TSocket> {
     }
 }
 
+impl<TSocket> MultiStreamSocket for NoiseStream<TSocket>
+where
+    TSocket: MultiStreamSocket,
+{
+    fn quic_connection(&self) -> Option<QuicConnection> {
+        self.socket.quic_connection()
+    }
+}
+
 //
 // Reading a stream
 // ----------------
@@ -16,6 +16,7 @@
 //! [`PeerManager`]: crate::peer_manager::PeerManager
 
 use crate::{
+    capture::{CapturedMessageType, MessageCapture, MessageDirection},
     counters::{
         self, network_application_inbound_traffic, network_application_outbound_traffic,
         DECLINED_LABEL, FAILED_LABEL, RECEIVED_LABEL, SENT_LABEL, UNKNOWN_LABEL,
@@ -26,7 +27,7 @@ use crate::{
         direct_send::Message,
         network::ReceivedMessage,
         rpc::{error::RpcError, InboundRpcs, OutboundRpcRequest, OutboundRpcs},
-        stream::{InboundStreamBuffer, OutboundStream, StreamMessage},
+        stream::{InboundStreamBuffer, StreamMessage},
         wire::messaging::v1::{
             DirectSendMsg, ErrorCode, MultiplexMessage, MultiplexMessageSink,
             MultiplexMessageStream, NetworkMessage, Priority, ReadError, WriteError,
@@ -36,8 +37,12 @@ use crate::{
     ProtocolId,
 };
 use aptos_channels::{aptos_channel, message_queues::QueueStyle};
-use aptos_config::network_id::{NetworkContext, PeerNetworkId};
+use aptos_config::{
+    config::OutboundSchedulerConfig,
+    network_id::{NetworkContext, PeerNetworkId},
+};
 use aptos_logger::prelude::*;
+use aptos_netcore::transport::MultiStreamSocket;
 use aptos_short_hex_str::AsShortHexStr;
 use aptos_time_service::{TimeService, TimeServiceTrait};
 use aptos_types::PeerId;
@@ -45,17 +50,19 @@ use futures::{
     self,
     channel::oneshot,
     io::{AsyncRead, AsyncWrite},
-    stream::StreamExt,
-    SinkExt,
+    stream::{self, BoxStream, SelectAll, StreamExt},
+    Future, FutureExt, SinkExt,
 };
-use futures_util::stream::select;
+use outbound_scheduler::{get_priority, get_priority_classes, OutboundMessage, OutboundScheduler};
 use serde::Serialize;
-use std::{collections::HashMap, fmt, panic, sync::Arc, time::Duration};
+use std::{collections::HashMap, fmt, io, panic, sync::Arc, time::Duration};
 use tokio::{runtime::Handle, time::timeout};
 use tokio_util::compat::{
     FuturesAsyncReadCompatExt, TokioAsyncReadCompatExt, TokioAsyncWriteCompatExt,
 };
 
+pub mod outbound_scheduler;
+
 #[cfg(test)]
 mod test;
 
@@ -136,13 +143,21 @@ pub struct Peer<TSocket> {
     max_frame_size: usize,
     /// The maximum size of an inbound or outbound request message
     max_message_size: usize,
-    /// Inbound stream buffer
-    inbound_stream: InboundStreamBuffer,
+    /// Inbound stream buffers, keyed by the index of the connection stream the
+    /// fragments are read from (the connection socket has index 0).
+    inbound_streams: HashMap<usize, InboundStreamBuffer>,
+    /// The maximum number of fragments in an inbound stream
+    max_fragments: usize,
+    /// The configuration for scheduling outbound messages
+    outbound_scheduler_config: Arc<OutboundSchedulerConfig>,
+    /// The capture for inbound and outbound direct send messages (if enabled).
+    /// RPC messages are captured by the inbound and outbound RPC queues.
+    message_capture: Option<Arc<MessageCapture>>,
 }
 
 impl<TSocket> Peer<TSocket>
 where
-    TSocket: AsyncRead + AsyncWrite + Send + 'static,
+    TSocket: AsyncRead + AsyncWrite + MultiStreamSocket + Send + 'static,
 {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
@@ -160,6 +175,8 @@ where
         max_concurrent_outbound_rpcs: u32,
         max_frame_size: usize,
         max_message_size: usize,
+        outbound_scheduler_config: Arc<OutboundSchedulerConfig>,
+        message_capture: Option<Arc<MessageCapture>>,
     ) -> Self {
         let Connection {
             metadata: connection_metadata,
@@ -182,17 +199,22 @@ where
                 remote_peer_id,
                 inbound_rpc_timeout,
                 max_concurrent_inbound_rpcs,
+                message_capture.clone(),
             ),
             outbound_rpcs: OutboundRpcs::new(
                 network_context,
                 time_service,
                 remote_peer_id,
                 max_concurrent_outbound_rpcs,
+                message_capture.clone(),
             ),
             state: State::Connected,
             max_frame_size,
             max_message_size,
-            inbound_stream: InboundStreamBuffer::new(max_fragments),
+            inbound_streams: HashMap::new(),
+            max_fragments,
+            outbound_scheduler_config,
+            message_capture,
         }
     }
 
@@ -211,8 +233,9 @@ where
         );
 
         // Split the connection into a ReadHalf and a WriteHalf.
-        let (read_socket, write_socket) =
-            tokio::io::split(self.connection.take().unwrap().compat());
+        let socket = self.connection.take().unwrap();
+        let quic_connection = socket.quic_connection();
+        let (read_socket, write_socket) = tokio::io::split(socket.compat());
 
         let mut reader =
             MultiplexMessageStream::new(read_socket.compat(), self.max_frame_size).fuse();
@@ -220,17 +243,59 @@ where
 
         // Start writer "process" as a separate task. We receive two handles to
         // communicate with the task:
-        //   1. `write_reqs_tx`: Queue of pending NetworkMessages to write.
+        //   1. `write_reqs_tx`: Queue of pending OutboundMessages to write.
         //   2. `close_tx`: Handle to close the task and underlying connection.
-        let (mut write_reqs_tx, writer_close_tx) = Self::start_writer_task(
-            &self.executor,
-            self.time_service.clone(),
-            self.connection_metadata.clone(),
-            self.network_context,
-            writer,
-            self.max_frame_size,
-            self.max_message_size,
-        );
+        // If the connection supports additional streams (e.g., QUIC) and there
+        // are multiple priority classes, each class is written by its own task.
+        let priority_classes = get_priority_classes(&self.outbound_scheduler_config);
+        let (mut write_reqs_tx, writer_close_tx) = match &quic_connection {
+            Some(quic_connection) if priority_classes.len() > 1 => Self::start_stream_writer_tasks(
+                &self.executor,
+                self.time_service.clone(),
+                self.connection_metadata.clone(),
+                self.network_context,
+                writer,
+                {
+                    let quic_connection = quic_connection.clone();
+                    move || {
+                        let quic_connection = quic_connection.clone();
+                        async move { quic_connection.open_stream().await }
+                    }
+                },
+                priority_classes,
+                self.max_frame_size,
+                self.max_message_size,
+                self.outbound_scheduler_config.clone(),
+            ),
+            _ => Self::start_writer_task(
+                &self.executor,
+                self.time_service.clone(),
+                self.connection_metadata.clone(),
+                self.network_context,
+                writer,
+                self.max_frame_size,
+                self.max_message_size,
+                self.outbound_scheduler_config.clone(),
+            ),
+        };
+
+        // Accept the additional streams opened by the remote peer (if the
+        // connection supports them), and read from all of them concurrently.
+        let mut accepted_streams = match quic_connection {
+            Some(quic_connection) => {
+                stream::unfold(quic_connection, |quic_connection| async move {
+                    let recv_stream = quic_connection.accept_stream().await.ok()?;
+                    Some((recv_stream, quic_connection))
+                })
+                .boxed()
+            },
+            None => stream::empty().boxed(),
+        }
+        .fuse();
+        let mut stream_readers: SelectAll<
+            BoxStream<'static, (usize, Result<MultiplexMessage, ReadError>)>,
+        > = SelectAll::new();
+        let mut next_stream_index = 1;
 
         // Start main Peer event loop.
         let reason = loop {
@@ -253,7 +318,7 @@ where
                 maybe_message = reader.next() => {
                     match maybe_message {
                         Some(message) =>  {
-                            if let Err(err) = self.handle_inbound_message(message, &mut write_reqs_tx) {
+                            if let Err(err) = self.handle_inbound_message(0, message, &mut write_reqs_tx) {
                                 warn!(
                                     NetworkSchema::new(&self.network_context)
                                         .connection_metadata(&self.connection_metadata),
@@ -269,6 +334,30 @@ where
                         None => self.shutdown(DisconnectReason::ConnectionClosed),
                     }
                 },
+                // Handle a new inbound MultiplexMessage that we've just read off
+                // one of the additional streams of the connection.
+                (stream_index, message) = stream_readers.select_next_some() => {
+                    if let Err(err) = self.handle_inbound_message(stream_index, message, &mut write_reqs_tx) {
+                        warn!(
+                            NetworkSchema::new(&self.network_context)
+                                .connection_metadata(&self.connection_metadata),
+                            error = %err,
+                            "{} Error in handling inbound message from peer: {} (stream: {}), error: {}",
+                            self.network_context,
+                            remote_peer_id.short_str(),
+                            stream_index,
+                            err
+                        );
+                    }
+                },
+                // Start reading from a new stream opened by the remote peer.
+                recv_stream = accepted_streams.select_next_some() => {
+                    let stream_index = next_stream_index;
+                    next_stream_index += 1;
+                    let stream_reader = MultiplexMessageStream::new(recv_stream, self.max_frame_size)
+                        .map(move |message| (stream_index, message));
+                    stream_readers.push(stream_reader.boxed());
+                },
                 // Drive the queue of pending inbound rpcs. When one is fulfilled
                 // by an upstream protocol, send the response to the remote peer.
                 maybe_response = self.inbound_rpcs.next_completed_response() => {
@@ -321,128 +410,332 @@ where
     // Start a new task on the given executor which is responsible for writing outbound messages on
     // the wire. The function returns two channels which can be used to send instructions to the
     // task:
-    // 1. The first channel is used to send outbound NetworkMessages to the task
+    // 1. The first channel is used to send outbound messages to the task
     // 2. The second channel is used to instruct the task to close the connection and terminate.
-    // If outbound messages are queued when the task receives a close instruction, it discards
-    // them and immediately closes the connection.
+    // Outbound messages are written in the order selected by the outbound scheduler (e.g., by
+    // protocol priority). If outbound messages are queued when the task receives a close
+    // instruction, it discards them and immediately closes the connection.
+    #[allow(clippy::too_many_arguments)]
     fn start_writer_task(
         executor: &Handle,
         time_service: TimeService,
         connection_metadata: ConnectionMetadata,
         network_context: NetworkContext,
-        mut writer: MultiplexMessageSink<impl AsyncWrite + Unpin + Send + 'static>,
+        writer: MultiplexMessageSink<impl AsyncWrite + Unpin + Send + 'static>,
         max_frame_size: usize,
         max_message_size: usize,
+        outbound_scheduler_config: Arc<OutboundSchedulerConfig>,
     ) -> (
-        aptos_channel::Sender<(), NetworkMessage>,
+        aptos_channel::Sender<(), OutboundMessage>,
         oneshot::Sender<()>,
     ) {
-        let remote_peer_id = connection_metadata.remote_peer_id;
-        let (write_reqs_tx, mut write_reqs_rx): (aptos_channel::Sender<(), NetworkMessage>, _) =
+        let (write_reqs_tx, write_reqs_rx): (aptos_channel::Sender<(), OutboundMessage>, _) =
             aptos_channel::new(
                 QueueStyle::KLAST,
                 1024,
                 Some(&counters::PENDING_WIRE_MESSAGES),
             );
-        let (close_tx, mut close_rx) = oneshot::channel();
+        let (close_tx, close_rx) = oneshot::channel();
+
+        // this task ends when receiving a close instruction (or the close handle is dropped)
+        let writer_task = Self::write_messages(
+            time_service,
+            connection_metadata,
+            network_context,
+            writer,
+            write_reqs_rx,
+            close_rx,
+            max_frame_size,
+            max_message_size,
+            outbound_scheduler_config,
+        );
+        executor.spawn(writer_task);
+        (write_reqs_tx, close_tx)
+    }
+
+    // Start the writer tasks for a connection that supports additional streams (e.g., QUIC).
+    // Each priority class is written to its own stream, so that the messages of one class are
+    // never blocked behind the messages of another (i.e., there is no head-of-line blocking
+    // between classes). The highest priority class is written to the connection socket, and
+    // each other class is written to a new stream opened by this peer. The function returns
+    // the same channels as `start_writer_task`: outbound messages are routed to the writer
+    // task of their priority class, and a close instruction closes all writer tasks. If a
+    // stream fails to open, the messages of its class fall back to the connection socket.
+    #[allow(clippy::too_many_arguments)]
+    fn start_stream_writer_tasks<OpenStream, StreamFuture, StreamWriter>(
+        executor: &Handle,
+        time_service: TimeService,
+        connection_metadata: ConnectionMetadata,
+        network_context: NetworkContext,
+        writer: MultiplexMessageSink<impl AsyncWrite + Unpin + Send + 'static>,
+        open_stream: OpenStream,
+        priority_classes: Vec<u8>,
+        max_frame_size: usize,
+        max_message_size: usize,
+        outbound_scheduler_config: Arc<OutboundSchedulerConfig>,
+    ) -> (
+        aptos_channel::Sender<(), OutboundMessage>,
+        oneshot::Sender<()>,
+    )
+    where
+        OpenStream: Fn() -> StreamFuture,
+        StreamFuture: Future<Output = io::Result<StreamWriter>> + Send + 'static,
+        StreamWriter: AsyncWrite + Unpin + Send + 'static,
+    {
+        let mut class_write_reqs_txs = HashMap::new();
+        let mut class_close_txs = vec![];
 
-        let (mut msg_tx, msg_rx) = aptos_channels::new(1024, &counters::PENDING_MULTIPLEX_MESSAGE);
-        let (stream_msg_tx, stream_msg_rx) =
-            aptos_channels::new(1024, &counters::PENDING_MULTIPLEX_STREAM);
+        // Start the writer task for the highest priority class (on the connection socket)
+        let (highest_priority, other_priorities) = priority_classes
+            .split_first()
+            .expect("There should always be at least one priority class!");
+        let (write_reqs_tx, close_tx) = Self::start_writer_task(
+            executor,
+            time_service.clone(),
+            connection_metadata.clone(),
+            network_context,
+            writer,
+            max_frame_size,
+            max_message_size,
+            outbound_scheduler_config.clone(),
+        );
+        let socket_write_reqs_tx = write_reqs_tx.clone();
+        class_write_reqs_txs.insert(*highest_priority, write_reqs_tx);
+        class_close_txs.push(close_tx);
 
-        // this task ends when the multiplex task ends (by dropping the senders) or receiving a close instruction
-        let writer_task = async move {
-            let mut stream = select(msg_rx, stream_msg_rx);
-            let log_context =
-                NetworkSchema::new(&network_context).connection_metadata(&connection_metadata);
+        // Start the writer tasks for all other classes (each on a new stream)
+        for priority in other_priorities {
+            let (write_reqs_tx, write_reqs_rx) = aptos_channel::new(
+                QueueStyle::KLAST,
+                1024,
+                Some(&counters::PENDING_WIRE_MESSAGES),
+            );
+            let (close_tx, close_rx) = oneshot::channel();
+            class_write_reqs_txs.insert(*priority, write_reqs_tx);
+            class_close_txs.push(close_tx);
+
+            let time_service = time_service.clone();
+            let connection_metadata = connection_metadata.clone();
+            let stream_future = open_stream();
+            let socket_write_reqs_tx = socket_write_reqs_tx.clone();
+            let outbound_scheduler_config = outbound_scheduler_config.clone();
+            let priority = *priority;
+            let writer_task = async move {
+                match stream_future.await {
+                    Ok(send_stream) => {
+                        let writer = MultiplexMessageSink::new(send_stream, max_frame_size);
+                        Self::write_messages(
+                            time_service,
+                            connection_metadata,
+                            network_context,
+                            writer,
+                            write_reqs_rx,
+                            close_rx,
+                            max_frame_size,
+                            max_message_size,
+                            outbound_scheduler_config,
+                        )
+                        .await
+                    },
+                    Err(error) => {
+                        warn!(
+                            NetworkSchema::new(&network_context)
+                                .connection_metadata(&connection_metadata),
+                            error = %error,
+                            "{} Failed to open the stream for priority class {} to peer: {}, \
+                            falling back to the connection socket. Error: {}",
+                            network_context,
+                            priority,
+                            connection_metadata.remote_peer_id.short_str(),
+                            error
+                        );
+                        Self::forward_messages(socket_write_reqs_tx, write_reqs_rx, close_rx).await
+                    },
+                }
+            };
+            executor.spawn(writer_task);
+        }
+
+        // Start the task that routes outbound messages to the writer task of their class
+        let (write_reqs_tx, mut write_reqs_rx): (aptos_channel::Sender<(), OutboundMessage>, _) =
+            aptos_channel::new(QueueStyle::KLAST, 1024, None);
+        let (close_tx, mut close_rx) = oneshot::channel();
+        let router_task = async move {
             loop {
                 futures::select! {
-                    message = stream.select_next_some() => {
-                        if let Err(err) = timeout(transport::TRANSPORT_TIMEOUT,writer.send(&message)).await {
-                            warn!(
-                                log_context,
-                                error = %err,
-                                "{} Error in sending message to peer: {}",
-                                network_context,
-                                remote_peer_id.short_str(),
-                            );
+                    message = write_reqs_rx.next() => {
+                        match message {
+                            Some(message) => {
+                                // Note: the priority classes include every class a message
+                                // can be assigned to, so the writer always exists.
+                                let priority = get_priority(&outbound_scheduler_config, message.protocol_id());
+                                if let Some(write_reqs_tx) = class_write_reqs_txs.get_mut(&priority) {
+                                    let _ = write_reqs_tx.push((), message);
+                                }
+                            },
+                            None => {
+                                // Stop queueing new messages and wait for the close instruction
+                                class_write_reqs_txs.clear();
+                                let _ = (&mut close_rx).await;
+                                break;
+                            },
                         }
-                    }
+                    },
                     _ = close_rx => {
                         break;
                     }
                 }
             }
-            info!(
-                log_context,
-                "{} Closing connection to peer: {}",
-                network_context,
-                remote_peer_id.short_str()
-            );
-            let flush_and_close = async {
-                writer.flush().await?;
-                writer.close().await?;
-                Ok(()) as Result<(), WriteError>
-            };
-            match time_service
-                .timeout(transport::TRANSPORT_TIMEOUT, flush_and_close)
-                .await
-            {
-                Err(_) => {
-                    info!(
-                        log_context,
-                        "{} Timeout in flush/close of connection to peer: {}",
-                        network_context,
-                        remote_peer_id.short_str()
-                    );
-                },
-                Ok(Err(err)) => {
-                    info!(
-                        log_context,
-                        error = %err,
-                        "{} Failure in flush/close of connection to peer: {}, error: {}",
-                        network_context,
-                        remote_peer_id.short_str(),
-                        err
-                    );
-                },
-                Ok(Ok(())) => {
-                    info!(
-                        log_context,
-                        "{} Closed connection to peer: {}",
-                        network_context,
-                        remote_peer_id.short_str()
-                    );
-                },
+
+            // Send a close instruction to all writer tasks
+            for close_tx in class_close_txs {
+                let _ = close_tx.send(());
             }
         };
-        // the task ends when the write_reqs_tx is dropped
-        let multiplex_task = async move {
-            let mut outbound_stream =
-                OutboundStream::new(max_frame_size, max_message_size, stream_msg_tx);
-            while let Some(message) = write_reqs_rx.next().await {
-                // either channel full would block the other one
-                let result = if outbound_stream.should_stream(&message) {
-                    outbound_stream.stream_message(message).await
-                } else {
-                    msg_tx
-                        .send(MultiplexMessage::Message(message))
-                        .await
-                        .map_err(|_| anyhow::anyhow!("Writer task ended"))
-                };
-                if let Err(err) = result {
-                    warn!(
-                        error = %err,
-                        "{} Error in sending message to peer: {}",
-                        network_context,
-                        remote_peer_id.short_str(),
-                    );
+        executor.spawn(router_task);
+        (write_reqs_tx, close_tx)
+    }
+
+    // Forward outbound messages to the given writer task (e.g., the connection socket's, if
+    // the stream of a priority class failed to open) until a close instruction is received.
+    async fn forward_messages(
+        mut write_reqs_tx: aptos_channel::Sender<(), OutboundMessage>,
+        mut write_reqs_rx: aptos_channel::Receiver<(), OutboundMessage>,
+        mut close_rx: oneshot::Receiver<()>,
+    ) {
+        loop {
+            futures::select! {
+                message = write_reqs_rx.next() => {
+                    match message {
+                        Some(message) => {
+                            let _ = write_reqs_tx.push((), message);
+                        },
+                        None => break,
+                    }
+                },
+                _ = close_rx => {
+                    break;
+                }
+            }
+        }
+    }
+
+    // Write outbound messages to the given writer (in the order selected by the outbound
+    // scheduler) until a close instruction is received, and then close the writer.
+    #[allow(clippy::too_many_arguments)]
+    async fn write_messages(
+        time_service: TimeService,
+        connection_metadata: ConnectionMetadata,
+        network_context: NetworkContext,
+        mut writer: MultiplexMessageSink<impl AsyncWrite + Unpin + Send + 'static>,
+        mut write_reqs_rx: aptos_channel::Receiver<(), OutboundMessage>,
+        mut close_rx: oneshot::Receiver<()>,
+        max_frame_size: usize,
+        max_message_size: usize,
+        outbound_scheduler_config: Arc<OutboundSchedulerConfig>,
+    ) {
+        let remote_peer_id = connection_metadata.remote_peer_id;
+        let mut outbound_scheduler = OutboundScheduler::new(
+            network_context,
+            time_service.clone(),
+            outbound_scheduler_config,
+            max_frame_size,
+            max_message_size,
+        );
+        let mut write_reqs_closed = false;
+        let log_context =
+            NetworkSchema::new(&network_context).connection_metadata(&connection_metadata);
+        loop {
+            // Move all pending write requests into the scheduler (so that
+            // the scheduler can select between all pending messages).
+            while !write_reqs_closed {
+                match write_reqs_rx.next().now_or_never() {
+                    Some(Some(message)) => outbound_scheduler.enqueue(message),
+                    Some(None) => write_reqs_closed = true,
+                    None => break,
                 }
             }
+
+            // Write the next frame (if any), otherwise wait for new
+            // messages or a close instruction.
+            match outbound_scheduler.next_frame() {
+                Some(message) => {
+                    if let Err(err) =
+                        timeout(transport::TRANSPORT_TIMEOUT, writer.send(&message)).await
+                    {
+                        warn!(
+                            log_context,
+                            error = %err,
+                            "{} Error in sending message to peer: {}",
+                            network_context,
+                            remote_peer_id.short_str(),
+                        );
+                    }
+                    if !matches!(close_rx.try_recv(), Ok(None)) {
+                        break;
+                    }
+                },
+                None if write_reqs_closed => {
+                    let _ = (&mut close_rx).await;
+                    break;
+                },
+                None => {
+                    futures::select! {
+                        message = write_reqs_rx.next() => {
+                            match message {
+                                Some(message) => outbound_scheduler.enqueue(message),
+                                None => write_reqs_closed = true,
+                            }
+                        }
+                        _ = close_rx => {
+                            break;
+                        }
+                    }
+                },
+            }
+        }
+        info!(
+            log_context,
+            "{} Closing connection to peer: {}",
+            network_context,
+            remote_peer_id.short_str()
+        );
+        let flush_and_close = async {
+            writer.flush().await?;
+            writer.close().await?;
+            Ok(()) as Result<(), WriteError>
         };
-        executor.spawn(writer_task);
-        executor.spawn(multiplex_task);
-        (write_reqs_tx, close_tx)
+        match time_service
+            .timeout(transport::TRANSPORT_TIMEOUT, flush_and_close)
+            .await
+        {
+            Err(_) => {
+                info!(
+                    log_context,
+                    "{} Timeout in flush/close of connection to peer: {}",
+                    network_context,
+                    remote_peer_id.short_str()
+                );
+            },
+            Ok(Err(err)) => {
+                info!(
+                    log_context,
+                    error = %err,
+                    "{} Failure in flush/close of connection to peer: {}, error: {}",
+                    network_context,
+                    remote_peer_id.short_str(),
+                    err
+                );
+            },
+            Ok(Ok(())) => {
+                info!(
+                    log_context,
+                    "{} Closed connection to peer: {}",
+                    network_context,
+                    remote_peer_id.short_str()
+                );
+            },
+        }
     }
 
     fn handle_inbound_network_message(
@@ -451,6 +744,11 @@ where
     ) -> Result<(), PeerManagerError> {
         match &message {
             NetworkMessage::DirectSendMsg(direct) => {
+                self.capture_direct_send(
+                    MessageDirection::Inbound,
+                    direct.protocol_id,
+                    &direct.raw_msg,
+                );
                 let data_len = direct.raw_msg.len();
                 network_application_inbound_traffic(
                     self.network_context,
@@ -543,14 +841,21 @@ where
 
     fn handle_inbound_stream_message(
         &mut self,
+        stream_index: usize,
         message: StreamMessage,
     ) -> Result<(), PeerManagerError> {
+        // Each connection stream reassembles its own (fragmented) messages
+        let max_fragments = self.max_fragments;
+        let inbound_stream = self
+            .inbound_streams
+            .entry(stream_index)
+            .or_insert_with(|| InboundStreamBuffer::new(max_fragments));
         match message {
             StreamMessage::Header(header) => {
-                self.inbound_stream.new_stream(header)?;
+                inbound_stream.new_stream(header)?;
             },
             StreamMessage::Fragment(fragment) => {
-                if let Some(message) = self.inbound_stream.append_fragment(fragment)? {
+                if let Some(message) = inbound_stream.append_fragment(fragment)? {
                     self.handle_inbound_network_message(message)?;
                 }
             },
@@ -560,8 +865,9 @@ where
 
     fn handle_inbound_message(
         &mut self,
+        stream_index: usize,
         message: Result<MultiplexMessage, ReadError>,
-        write_reqs_tx: &mut aptos_channel::Sender<(), NetworkMessage>,
+        write_reqs_tx: &mut aptos_channel::Sender<(), OutboundMessage>,
     ) -> Result<(), PeerManagerError> {
         trace!(
             NetworkSchema::new(&self.network_context)
@@ -583,7 +889,7 @@ where
                     let error_code = ErrorCode::parsing_error(*message_type, *protocol_id);
                     let message = NetworkMessage::Error(error_code);
 
-                    write_reqs_tx.push((), message)?;
+                    write_reqs_tx.push((), OutboundMessage::new(message))?;
                     return Err(err.into());
                 },
                 ReadError::IoError(_) => {
@@ -596,14 +902,16 @@ where
 
         match message {
             MultiplexMessage::Message(message) => self.handle_inbound_network_message(message),
-            MultiplexMessage::Stream(message) => self.handle_inbound_stream_message(message),
+            MultiplexMessage::Stream(message) => {
+                self.handle_inbound_stream_message(stream_index, message)
+            },
         }
     }
 
     fn handle_outbound_request(
         &mut self,
         request: PeerRequest,
-        write_reqs_tx: &mut aptos_channel::Sender<(), NetworkMessage>,
+        write_reqs_tx: &mut aptos_channel::Sender<(), OutboundMessage>,
     ) {
         trace!(
             "Peer {} PeerRequest::{:?}",
@@ -617,13 +925,14 @@ where
                 // Create the direct send message
                 let message_len = message.mdata.len();
                 let protocol_id = message.protocol_id;
+                self.capture_direct_send(MessageDirection::Outbound, protocol_id, &message.mdata);
                 let message = NetworkMessage::DirectSendMsg(DirectSendMsg {
                     protocol_id,
                     priority: Priority::default(),
                     raw_msg: Vec::from(message.mdata.as_ref()),
                 });
 
-                match write_reqs_tx.push((), message) {
+                match write_reqs_tx.push((), OutboundMessage::new(message)) {
                     Ok(_) => {
                         self.update_outbound_direct_send_metrics(protocol_id, message_len as u64);
                     },
@@ -664,6 +973,24 @@ where
         }
     }
 
+    /// Captures the given direct send message (if message capture is enabled)
+    fn capture_direct_send(
+        &self,
+        direction: MessageDirection,
+        protocol_id: ProtocolId,
+        data: &[u8],
+    ) {
+        if let Some(message_capture) = &self.message_capture {
+            message_capture.capture_message(
+                self.remote_peer_id(),
+                direction,
+                CapturedMessageType::DirectSend,
+                protocol_id,
+                data,
+            );
+        }
+    }
+
     /// Updates the outbound direct send metrics (e.g., messages and bytes sent)
     fn update_outbound_direct_send_metrics(&mut self, protocol_id: ProtocolId, data_len: u64) {
         // Update the metrics for the sent direct send message
@@ -682,11 +1009,11 @@ where
 
     async fn do_shutdown(
         mut self,
-        write_req_tx: aptos_channel::Sender<(), NetworkMessage>,
+        write_req_tx: aptos_channel::Sender<(), OutboundMessage>,
         writer_close_tx: oneshot::Sender<()>,
         reason: DisconnectReason,
     ) {
-        // Drop the sender to shut down multiplex task.
+        // Drop the sender to stop queueing new outbound messages.
         drop(write_req_tx);
 
         // Send a close instruction to the writer task. On receipt of this
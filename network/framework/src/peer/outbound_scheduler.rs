@@ -0,0 +1,637 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! The [`OutboundScheduler`] orders the frames written to a single peer connection.
+//!
+//! Outbound messages are queued by priority class (as determined by the protocol of
+//! each message and the [`OutboundSchedulerConfig`]). Each time the writer is ready
+//! to write a frame, the scheduler selects the next class according to the configured
+//! [`OutboundSchedulingPolicy`].
+//!
+//! Large messages are split into fragments (see [`MessageFragmenter`]) and written one
+//! fragment at a time, so smaller (or higher priority) messages can be written between
+//! the fragments of a large message. The remote peer can only reassemble a single
+//! stream at a time, so at most one large message is in flight on a connection.
+
+use crate::{
+    counters::{self, UNKNOWN_LABEL},
+    logging::NetworkSchema,
+    protocols::{
+        stream::{MessageFragmenter, StreamMessage},
+        wire::messaging::v1::{MultiplexMessage, NetworkMessage, RpcResponse},
+    },
+    ProtocolId,
+};
+use aptos_config::{
+    config::{OutboundSchedulerConfig, OutboundSchedulingPolicy},
+    network_id::NetworkContext,
+};
+use aptos_logger::prelude::*;
+use aptos_time_service::{TimeService, TimeServiceTrait};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// An outbound message to be written to the remote peer (along with the
+/// protocol it was sent on, if known).
+#[derive(Debug)]
+pub struct OutboundMessage {
+    message: NetworkMessage,
+    protocol_id: Option<ProtocolId>,
+}
+
+impl OutboundMessage {
+    /// Creates a new outbound message. The protocol is taken from the message (if it has one).
+    pub fn new(message: NetworkMessage) -> Self {
+        let protocol_id = match &message {
+            NetworkMessage::RpcRequest(request) => Some(request.protocol_id),
+            NetworkMessage::DirectSendMsg(message) => Some(message.protocol_id),
+            NetworkMessage::RpcResponse(_) | NetworkMessage::Error(_) => None,
+        };
+        Self {
+            message,
+            protocol_id,
+        }
+    }
+
+    /// Creates a new outbound rpc response for the given protocol. This is
+    /// required because rpc responses don't carry a protocol on the wire.
+    pub fn new_rpc_response(response: RpcResponse, protocol_id: ProtocolId) -> Self {
+        Self {
+            message: NetworkMessage::RpcResponse(response),
+            protocol_id: Some(protocol_id),
+        }
+    }
+
+    /// Returns the protocol the message was sent on (if known)
+    pub fn protocol_id(&self) -> Option<ProtocolId> {
+        self.protocol_id
+    }
+}
+
+/// Returns the priority class of a message sent on the given protocol
+pub fn get_priority(config: &OutboundSchedulerConfig, protocol_id: Option<ProtocolId>) -> u8 {
+    match config.scheduling_policy {
+        // All messages share a single class when scheduling in FIFO order
+        OutboundSchedulingPolicy::Fifo => 0,
+        OutboundSchedulingPolicy::StrictPriority | OutboundSchedulingPolicy::Weighted => {
+            match protocol_id {
+                Some(protocol_id) => config.get_priority(protocol_id.as_str()),
+                None => config.default_priority,
+            }
+        },
+    }
+}
+
+/// Returns all priority classes that messages can be assigned to (in priority order)
+pub fn get_priority_classes(config: &OutboundSchedulerConfig) -> Vec<u8> {
+    match config.scheduling_policy {
+        OutboundSchedulingPolicy::Fifo => vec![0],
+        OutboundSchedulingPolicy::StrictPriority | OutboundSchedulingPolicy::Weighted => {
+            let mut priority_classes: Vec<u8> = config
+                .protocol_priorities
+                .values()
+                .copied()
+                .chain(std::iter::once(config.default_priority))
+                .collect();
+            priority_classes.sort_unstable();
+            priority_classes.dedup();
+            priority_classes
+        },
+    }
+}
+
+/// A message waiting to be written
+struct QueuedMessage {
+    message: NetworkMessage,
+    protocol_label: &'static str,
+    enqueue_time: Instant,
+}
+
+/// A large message that is being written (one fragment at a time)
+struct ActiveStream {
+    priority: u8,
+    protocol_label: &'static str,
+    enqueue_time: Instant,
+    stream_messages: VecDeque<StreamMessage>,
+}
+
+/// The queue of messages for a single priority class
+#[derive(Default)]
+struct PriorityQueue {
+    messages: VecDeque<QueuedMessage>,
+    /// True iff the last frame written for this class was a stream fragment.
+    /// This is used to alternate between the active stream and queued messages.
+    last_was_stream: bool,
+}
+
+/// Schedules the outbound frames of a single peer connection
+pub struct OutboundScheduler {
+    network_context: NetworkContext,
+    time_service: TimeService,
+    config: Arc<OutboundSchedulerConfig>,
+    fragmenter: MessageFragmenter,
+    queues: BTreeMap<u8, PriorityQueue>,
+    active_stream: Option<ActiveStream>,
+    /// The class being served and its remaining frames (only used by the weighted policy)
+    current_class: Option<(u8, u64)>,
+}
+
+impl OutboundScheduler {
+    pub fn new(
+        network_context: NetworkContext,
+        time_service: TimeService,
+        config: Arc<OutboundSchedulerConfig>,
+        max_frame_size: usize,
+        max_message_size: usize,
+    ) -> Self {
+        Self {
+            network_context,
+            time_service,
+            config,
+            fragmenter: MessageFragmenter::new(max_frame_size, max_message_size),
+            queues: BTreeMap::new(),
+            active_stream: None,
+            current_class: None,
+        }
+    }
+
+    /// Returns true iff there are no frames left to write
+    pub fn is_empty(&self) -> bool {
+        self.active_stream.is_none() && self.queues.values().all(|queue| queue.messages.is_empty())
+    }
+
+    /// Adds the message to the queue of its priority class. If the queue is
+    /// full, the oldest message in the queue is dropped.
+    pub fn enqueue(&mut self, outbound_message: OutboundMessage) {
+        let OutboundMessage {
+            message,
+            protocol_id,
+        } = outbound_message;
+        let priority = get_priority(&self.config, protocol_id);
+        let protocol_label = protocol_id.map_or(UNKNOWN_LABEL, |protocol_id| protocol_id.as_str());
+
+        let max_queued_messages = self.config.max_queued_messages_per_priority;
+        let queue = self.queues.entry(priority).or_default();
+        if queue.messages.len() >= max_queued_messages {
+            if let Some(dropped_message) = queue.messages.pop_front() {
+                counters::outbound_queue_depth(
+                    &self.network_context,
+                    dropped_message.protocol_label,
+                )
+                .dec();
+                counters::outbound_queue_dropped_messages(
+                    &self.network_context,
+                    dropped_message.protocol_label,
+                )
+                .inc();
+            }
+        }
+
+        queue.messages.push_back(QueuedMessage {
+            message,
+            protocol_label,
+            enqueue_time: self.time_service.now(),
+        });
+        counters::outbound_queue_depth(&self.network_context, protocol_label).inc();
+    }
+
+    /// Returns the next frame to write (or None, if there is nothing to write)
+    pub fn next_frame(&mut self) -> Option<MultiplexMessage> {
+        while !self.is_empty() {
+            let priority = self.select_priority()?;
+            if let Some(frame) = self.next_frame_for_priority(priority) {
+                return Some(frame);
+            }
+            // Otherwise, the selected message was dropped (e.g., it was too
+            // large to be fragmented), so we try again.
+        }
+        None
+    }
+
+    /// Returns the index of the next message that can be written from the given
+    /// queue. If a stream is already active, large messages must wait for it to complete.
+    fn next_message_index(&self, queue: &PriorityQueue) -> Option<usize> {
+        if self.active_stream.is_none() {
+            return (!queue.messages.is_empty()).then_some(0);
+        }
+        queue
+            .messages
+            .iter()
+            .position(|queued_message| !self.fragmenter.should_stream(&queued_message.message))
+    }
+
+    /// Returns true iff the given priority class has a frame that can be written
+    fn is_ready(&self, priority: u8) -> bool {
+        let stream_ready =
+            matches!(&self.active_stream, Some(stream) if stream.priority == priority);
+        stream_ready
+            || self
+                .queues
+                .get(&priority)
+                .and_then(|queue| self.next_message_index(queue))
+                .is_some()
+    }
+
+    /// Selects the priority class of the next frame to write (according to the scheduling policy)
+    fn select_priority(&mut self) -> Option<u8> {
+        let ready_priorities: Vec<u8> = self
+            .queues
+            .keys()
+            .copied()
+            .filter(|priority| self.is_ready(*priority))
+            .collect();
+
+        match self.config.scheduling_policy {
+            OutboundSchedulingPolicy::Fifo | OutboundSchedulingPolicy::StrictPriority => {
+                ready_priorities.first().copied()
+            },
+            OutboundSchedulingPolicy::Weighted => {
+                // Continue serving the current class while it has frames remaining
+                if let Some((priority, remaining_frames)) = self.current_class {
+                    if remaining_frames > 0 && ready_priorities.contains(&priority) {
+                        self.current_class = Some((priority, remaining_frames - 1));
+                        return Some(priority);
+                    }
+                }
+
+                // Otherwise, move to the next ready class (in round-robin order)
+                let current_priority = self.current_class.map(|(priority, _)| priority);
+                let next_priority = ready_priorities
+                    .iter()
+                    .copied()
+                    .find(|priority| current_priority.is_none_or(|current| *priority > current))
+                    .or_else(|| ready_priorities.first().copied())?;
+                let weight = self.config.get_weight(next_priority);
+                self.current_class = Some((next_priority, weight.saturating_sub(1)));
+                Some(next_priority)
+            },
+        }
+    }
+
+    /// Returns the next frame for the given priority class. Within a class,
+    /// frames alternate between the active stream and the queued messages.
+    fn next_frame_for_priority(&mut self, priority: u8) -> Option<MultiplexMessage> {
+        let stream_ready =
+            matches!(&self.active_stream, Some(stream) if stream.priority == priority);
+        let message_index = self
+            .queues
+            .get(&priority)
+            .and_then(|queue| self.next_message_index(queue));
+
+        let queue = self.queues.get_mut(&priority)?;
+        let write_stream = match (stream_ready, message_index) {
+            (true, Some(_)) => !queue.last_was_stream,
+            (true, None) => true,
+            (false, Some(_)) => false,
+            (false, None) => return None,
+        };
+        queue.last_was_stream = write_stream;
+        if write_stream {
+            return self.next_stream_frame();
+        }
+
+        // Remove the message from the queue
+        let queued_message = queue.messages.remove(message_index?)?;
+        counters::outbound_queue_depth(&self.network_context, queued_message.protocol_label).dec();
+
+        // If the message is small enough, write it directly
+        if !self.fragmenter.should_stream(&queued_message.message) {
+            self.observe_queue_delay(queued_message.protocol_label, queued_message.enqueue_time);
+            return Some(MultiplexMessage::Message(queued_message.message));
+        }
+
+        // Otherwise, start a new stream for the message
+        match self.fragmenter.fragment_message(queued_message.message) {
+            Ok(stream_messages) => {
+                self.active_stream = Some(ActiveStream {
+                    priority,
+                    protocol_label: queued_message.protocol_label,
+                    enqueue_time: queued_message.enqueue_time,
+                    stream_messages: stream_messages.into(),
+                });
+                self.next_stream_frame()
+            },
+            Err(error) => {
+                warn!(
+                    NetworkSchema::new(&self.network_context),
+                    error = %error,
+                    "{} Failed to fragment outbound message for protocol {}. Error: {}",
+                    self.network_context,
+                    queued_message.protocol_label,
+                    error
+                );
+                None
+            },
+        }
+    }
+
+    /// Returns the next frame of the active stream (and completes the stream
+    /// once all fragments have been written).
+    fn next_stream_frame(&mut self) -> Option<MultiplexMessage> {
+        let active_stream = self.active_stream.as_mut()?;
+        let stream_message = active_stream.stream_messages.pop_front();
+        if active_stream.stream_messages.is_empty() {
+            let protocol_label = active_stream.protocol_label;
+            let enqueue_time = active_stream.enqueue_time;
+            self.active_stream = None;
+            self.observe_queue_delay(protocol_label, enqueue_time);
+        }
+        stream_message.map(MultiplexMessage::Stream)
+    }
+
+    /// Observes the time the message spent in the scheduler
+    fn observe_queue_delay(&self, protocol_label: &str, enqueue_time: Instant) {
+        counters::outbound_queue_delay_observe(
+            &self.network_context,
+            protocol_label,
+            self.get_queue_delay(enqueue_time).as_secs_f64(),
+        );
+    }
+
+    /// Returns the time elapsed since the given enqueue time
+    fn get_queue_delay(&self, enqueue_time: Instant) -> Duration {
+        self.time_service
+            .now()
+            .saturating_duration_since(enqueue_time)
+    }
+}
+
+impl Drop for OutboundScheduler {
+    fn drop(&mut self) {
+        // Remove all messages still queued from the queue depth metrics
+        for queue in self.queues.values() {
+            for queued_message in queue.messages.iter() {
+                counters::outbound_queue_depth(
+                    &self.network_context,
+                    queued_message.protocol_label,
+                )
+                .dec();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocols::wire::messaging::v1::{DirectSendMsg, Priority};
+
+    const MAX_FRAME_SIZE: usize = 128;
+    const MAX_MESSAGE_SIZE: usize = 64 * 255;
+
+    #[test]
+    fn test_fifo_scheduling() {
+        let mut scheduler = create_scheduler(OutboundSchedulingPolicy::Fifo, 1024);
+
+        // Enqueue messages for protocols of different priorities
+        let protocol_ids = [
+            ProtocolId::StorageServiceRpc,
+            ProtocolId::ConsensusRpcBcs,
+            ProtocolId::MempoolDirectSend,
+            ProtocolId::ConsensusDirectSendBcs,
+        ];
+        for protocol_id in protocol_ids {
+            scheduler.enqueue(create_direct_send(protocol_id, 10));
+        }
+
+        // Verify the messages are written in the order they were sent
+        assert_eq!(drain_protocols(&mut scheduler), protocol_ids.to_vec());
+    }
+
+    #[test]
+    fn test_priority_classes() {
+        // Verify all messages share a single class when scheduling in FIFO order
+        let config = OutboundSchedulerConfig::default();
+        assert_eq!(get_priority_classes(&config), vec![0]);
+        assert_eq!(
+            get_priority(&config, Some(ProtocolId::StorageServiceRpc)),
+            0
+        );
+
+        // Verify the classes of the default protocol priorities
+        let config = OutboundSchedulerConfig {
+            scheduling_policy: OutboundSchedulingPolicy::StrictPriority,
+            ..Default::default()
+        };
+        assert_eq!(get_priority_classes(&config), vec![0, 1, 2, 3]);
+        assert_eq!(get_priority(&config, Some(ProtocolId::ConsensusRpcBcs)), 0);
+        assert_eq!(
+            get_priority(&config, Some(ProtocolId::StorageServiceRpc)),
+            3
+        );
+        assert_eq!(get_priority(&config, None), config.default_priority);
+
+        // Verify the default class is included (even if no protocol uses it)
+        let config = OutboundSchedulerConfig {
+            scheduling_policy: OutboundSchedulingPolicy::Weighted,
+            default_priority: 7,
+            ..Default::default()
+        };
+        assert_eq!(get_priority_classes(&config), vec![0, 1, 2, 3, 7]);
+    }
+
+    #[test]
+    fn test_strict_priority_scheduling() {
+        let mut scheduler = create_scheduler(OutboundSchedulingPolicy::StrictPriority, 1024);
+
+        // Enqueue messages for protocols of different priorities
+        for protocol_id in [
+            ProtocolId::StorageServiceRpc,
+            ProtocolId::MempoolDirectSend,
+            ProtocolId::ConsensusRpcBcs,
+            ProtocolId::StorageServiceRpc,
+            ProtocolId::ConsensusDirectSendBcs,
+        ] {
+            scheduler.enqueue(create_direct_send(protocol_id, 10));
+        }
+
+        // Verify the messages are written in priority order (and FIFO within a class)
+        assert_eq!(drain_protocols(&mut scheduler), vec![
+            ProtocolId::ConsensusRpcBcs,
+            ProtocolId::ConsensusDirectSendBcs,
+            ProtocolId::MempoolDirectSend,
+            ProtocolId::StorageServiceRpc,
+            ProtocolId::StorageServiceRpc,
+        ]);
+    }
+
+    #[test]
+    fn test_weighted_scheduling() {
+        let config = OutboundSchedulerConfig {
+            scheduling_policy: OutboundSchedulingPolicy::Weighted,
+            priority_weights: [(0, 2), (3, 1)].into_iter().collect(),
+            ..Default::default()
+        };
+        let mut scheduler = OutboundScheduler::new(
+            NetworkContext::mock(),
+            TimeService::mock(),
+            Arc::new(config),
+            MAX_FRAME_SIZE,
+            MAX_MESSAGE_SIZE,
+        );
+
+        // Enqueue messages for a low priority and a high priority protocol
+        for _ in 0..4 {
+            scheduler.enqueue(create_direct_send(ProtocolId::StorageServiceRpc, 10));
+        }
+        for _ in 0..4 {
+            scheduler.enqueue(create_direct_send(ProtocolId::ConsensusRpcBcs, 10));
+        }
+
+        // Verify the classes are served in proportion to their weights
+        assert_eq!(drain_protocols(&mut scheduler), vec![
+            ProtocolId::ConsensusRpcBcs,
+            ProtocolId::ConsensusRpcBcs,
+            ProtocolId::StorageServiceRpc,
+            ProtocolId::ConsensusRpcBcs,
+            ProtocolId::ConsensusRpcBcs,
+            ProtocolId::StorageServiceRpc,
+            ProtocolId::StorageServiceRpc,
+            ProtocolId::StorageServiceRpc,
+        ]);
+    }
+
+    #[test]
+    fn test_stream_preemption() {
+        let mut scheduler = create_scheduler(OutboundSchedulingPolicy::StrictPriority, 1024);
+
+        // Enqueue a large message and verify the stream header is written
+        scheduler.enqueue(create_direct_send(ProtocolId::StorageServiceRpc, 1000));
+        assert!(matches!(
+            scheduler.next_frame(),
+            Some(MultiplexMessage::Stream(StreamMessage::Header(_)))
+        ));
+
+        // Enqueue a small and a large high priority message
+        scheduler.enqueue(create_direct_send(ProtocolId::ConsensusRpcBcs, 1000));
+        scheduler.enqueue(create_direct_send(ProtocolId::ConsensusDirectSendBcs, 10));
+
+        // Verify the small message preempts the active stream
+        match scheduler.next_frame() {
+            Some(MultiplexMessage::Message(message)) => {
+                assert_eq!(
+                    get_protocol_id(&message),
+                    ProtocolId::ConsensusDirectSendBcs
+                )
+            },
+            frame => panic!("Expected a single message frame, but got: {:?}", frame),
+        }
+
+        // Verify the active stream completes before the next stream is started
+        let mut frames = vec![];
+        while let Some(frame) = scheduler.next_frame() {
+            frames.push(frame);
+        }
+        let headers: Vec<_> = frames
+            .iter()
+            .enumerate()
+            .filter_map(|(index, frame)| match frame {
+                MultiplexMessage::Stream(StreamMessage::Header(header)) => {
+                    Some((index, get_protocol_id(&header.message)))
+                },
+                _ => None,
+            })
+            .collect();
+        assert_eq!(headers.len(), 1);
+        let (header_index, protocol_id) = headers[0];
+        assert_eq!(protocol_id, ProtocolId::ConsensusRpcBcs);
+        assert!(frames[..header_index]
+            .iter()
+            .all(|frame| matches!(frame, MultiplexMessage::Stream(StreamMessage::Fragment(_)))));
+        assert!(scheduler.is_empty());
+    }
+
+    #[test]
+    fn test_full_queue_drops_oldest() {
+        let mut scheduler = create_scheduler(OutboundSchedulingPolicy::StrictPriority, 2);
+
+        // Enqueue more messages than the queue can hold
+        for data_len in [1, 2, 3] {
+            scheduler.enqueue(create_direct_send(ProtocolId::ConsensusRpcBcs, data_len));
+        }
+
+        // Verify the oldest message was dropped
+        let mut data_lengths = vec![];
+        while let Some(frame) = scheduler.next_frame() {
+            match frame {
+                MultiplexMessage::Message(message) => data_lengths.push(message.data_len()),
+                frame => panic!("Unexpected stream frame: {:?}", frame),
+            }
+        }
+        assert_eq!(data_lengths, vec![2, 3]);
+    }
+
+    #[test]
+    fn test_queue_delay() {
+        let time_service = TimeService::mock();
+        let mut scheduler = OutboundScheduler::new(
+            NetworkContext::mock(),
+            time_service.clone(),
+            Arc::new(OutboundSchedulerConfig::default()),
+            MAX_FRAME_SIZE,
+            MAX_MESSAGE_SIZE,
+        );
+
+        // Enqueue a message and elapse some time
+        scheduler.enqueue(create_direct_send(ProtocolId::ConsensusRpcBcs, 10));
+        time_service.into_mock().advance_secs(5);
+
+        // Verify the queue delay is measured using the time service
+        let enqueue_time = scheduler.queues.values().next().unwrap().messages[0].enqueue_time;
+        assert_eq!(
+            scheduler.get_queue_delay(enqueue_time),
+            Duration::from_secs(5)
+        );
+    }
+
+    /// Creates a scheduler with the default priorities and the given policy
+    fn create_scheduler(
+        scheduling_policy: OutboundSchedulingPolicy,
+        max_queued_messages_per_priority: usize,
+    ) -> OutboundScheduler {
+        let config = OutboundSchedulerConfig {
+            scheduling_policy,
+            max_queued_messages_per_priority,
+            ..Default::default()
+        };
+        OutboundScheduler::new(
+            NetworkContext::mock(),
+            TimeService::mock(),
+            Arc::new(config),
+            MAX_FRAME_SIZE,
+            MAX_MESSAGE_SIZE,
+        )
+    }
+
+    /// Creates a direct send message for the given protocol and data length
+    fn create_direct_send(protocol_id: ProtocolId, data_len: usize) -> OutboundMessage {
+        OutboundMessage::new(NetworkMessage::DirectSendMsg(DirectSendMsg {
+            protocol_id,
+            priority: Priority::default(),
+            raw_msg: vec![0; data_len],
+        }))
+    }
+
+    /// Returns the protocol of the given (direct send) message
+    fn get_protocol_id(message: &NetworkMessage) -> ProtocolId {
+        match message {
+            NetworkMessage::DirectSendMsg(message) => message.protocol_id,
+            message => panic!("Unexpected message: {:?}", message),
+        }
+    }
+
+    /// Writes all (single frame) messages and returns their protocols
+    fn drain_protocols(scheduler: &mut OutboundScheduler) -> Vec<ProtocolId> {
+        let mut protocol_ids = vec![];
+        while let Some(frame) = scheduler.next_frame() {
+            match frame {
+                MultiplexMessage::Message(message) => protocol_ids.push(get_protocol_id(&message)),
+                frame => panic!("Unexpected stream frame: {:?}", frame),
+            }
+        }
+        protocol_ids
+    }
+}
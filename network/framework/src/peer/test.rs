@@ -7,7 +7,10 @@ use crate::{
         INBOUND_RPC_TIMEOUT_MS, MAX_CONCURRENT_INBOUND_RPCS, MAX_CONCURRENT_OUTBOUND_RPCS,
         MAX_FRAME_SIZE, MAX_MESSAGE_SIZE, NETWORK_CHANNEL_SIZE,
     },
-    peer::{DisconnectReason, Peer, PeerRequest},
+    peer::{
+        outbound_scheduler::{get_priority_classes, OutboundMessage},
+        DisconnectReason, Peer, PeerRequest,
+    },
     peer_manager::TransportNotification,
     protocols::{
         direct_send::Message,
@@ -21,26 +24,32 @@ use crate::{
             },
         },
     },
-    transport::{Connection, ConnectionId, ConnectionMetadata},
+    transport::{self, Connection, ConnectionId, ConnectionMetadata},
     ProtocolId,
 };
 use aptos_channels::{self, aptos_channel, message_queues::QueueStyle};
-use aptos_config::{config::PeerRole, network_id::NetworkContext};
+use aptos_config::{
+    config::{OutboundSchedulerConfig, OutboundSchedulingPolicy, PeerRole},
+    network_id::NetworkContext,
+};
 use aptos_logger::info;
 use aptos_memsocket::MemorySocket;
-use aptos_netcore::transport::ConnectionOrigin;
+use aptos_netcore::transport::{
+    quic::QuicTransport, ConnectionOrigin, MultiStreamSocket, Transport,
+};
 use aptos_time_service::{MockTimeService, TimeService};
 use aptos_types::{network_address::NetworkAddress, PeerId};
 use bytes::Bytes;
 use futures::{
     channel::oneshot,
     future::{self, FutureExt},
-    io::{AsyncRead, AsyncWrite, AsyncWriteExt},
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
     stream::{StreamExt, TryStreamExt},
     SinkExt,
 };
 use std::{
     collections::{HashMap, HashSet},
+    io,
     str::FromStr,
     sync::Arc,
     time::Duration,
@@ -66,6 +75,32 @@ fn build_test_peer(
     aptos_channels::Receiver<TransportNotification<MemorySocket>>,
 ) {
     let (a, b) = MemorySocket::new_pair();
+    let (peer, peer_handle, connection_notifs_rx) = build_test_peer_with_socket(
+        executor,
+        time_service,
+        origin,
+        upstream_handlers,
+        a,
+        OutboundSchedulerConfig::default(),
+    );
+
+    (peer, peer_handle, b, connection_notifs_rx)
+}
+
+fn build_test_peer_with_socket<TSocket: transport::TSocket>(
+    executor: Handle,
+    time_service: TimeService,
+    origin: ConnectionOrigin,
+    upstream_handlers: Arc<
+        HashMap<ProtocolId, aptos_channel::Sender<(PeerId, ProtocolId), ReceivedMessage>>,
+    >,
+    socket: TSocket,
+    outbound_scheduler_config: OutboundSchedulerConfig,
+) -> (
+    Peer<TSocket>,
+    PeerHandle,
+    aptos_channels::Receiver<TransportNotification<TSocket>>,
+) {
     let peer_id = PeerId::random();
     let connection = Connection {
         metadata: ConnectionMetadata::new(
@@ -77,7 +112,7 @@ fn build_test_peer(
             ProtocolIdSet::empty(),
             PeerRole::Unknown,
         ),
-        socket: a,
+        socket,
     };
 
     let (connection_notifs_tx, connection_notifs_rx) = aptos_channels::new_test(1);
@@ -97,10 +132,12 @@ fn build_test_peer(
         MAX_CONCURRENT_OUTBOUND_RPCS,
         MAX_FRAME_SIZE,
         MAX_MESSAGE_SIZE,
+        Arc::new(outbound_scheduler_config),
+        None,
     );
     let peer_handle = PeerHandle(peer_reqs_tx);
 
-    (peer, peer_handle, b, connection_notifs_rx)
+    (peer, peer_handle, connection_notifs_rx)
 }
 
 fn build_test_connected_peers(
@@ -1033,3 +1070,163 @@ fn peers_send_multiplex() {
 
     rt.block_on(future::join3(peer_a.start(), peer_b.start(), test));
 }
+
+// If the connection supports additional streams (i.e., QUIC), each priority class
+// should be written to its own stream (with the highest class on the socket).
+#[test]
+fn peer_send_message_quic_streams() {
+    ::aptos_logger::Logger::init_for_testing();
+    let rt = Runtime::new().unwrap();
+    let _guard = rt.enter();
+
+    // Establish a QUIC connection. Note: the dialer must write first (so
+    // that the listener accepts the connection socket).
+    let transport = QuicTransport::default();
+    let (listener, addr) = transport
+        .listen_on("/ip4/127.0.0.1/quic/0".parse().unwrap())
+        .unwrap();
+    let (outbound, mut inbound) = rt.block_on(async {
+        let mut outbound = transport
+            .dial(PeerId::random(), addr)
+            .unwrap()
+            .await
+            .unwrap();
+        outbound.write_all(&[0]).await.unwrap();
+        outbound.flush().await.unwrap();
+
+        let (inbound, _addr) = listener.into_future().await.0.unwrap().unwrap();
+        let mut inbound = inbound.await.unwrap();
+        let mut buf = [0; 1];
+        inbound.read_exact(&mut buf).await.unwrap();
+        (outbound, inbound)
+    });
+
+    // Create a peer for the dialer (that schedules messages by priority class)
+    let outbound_scheduler_config = OutboundSchedulerConfig {
+        scheduling_policy: OutboundSchedulingPolicy::StrictPriority,
+        ..Default::default()
+    };
+    let (peer, mut peer_handle, _connection_notifs_rx) = build_test_peer_with_socket(
+        rt.handle().clone(),
+        TimeService::mock(),
+        ConnectionOrigin::Outbound,
+        Arc::new(HashMap::new()),
+        outbound,
+        outbound_scheduler_config,
+    );
+
+    let test = async move {
+        // Send a message for several priority classes (lowest priority first)
+        for protocol_id in [
+            ProtocolId::StateSyncDirectSend,
+            ProtocolId::MempoolDirectSend,
+            ProtocolId::ConsensusDirectSendBcs,
+        ] {
+            peer_handle.send_direct_send(Message {
+                protocol_id,
+                mdata: Bytes::from(protocol_id.as_str()),
+            });
+        }
+
+        // Verify the highest priority class is written to the connection socket
+        let quic_connection = inbound.quic_connection().unwrap();
+        let mut socket_stream = MultiplexMessageStream::new(&mut inbound, MAX_FRAME_SIZE);
+        assert_eq!(
+            read_direct_send_protocol(&mut socket_stream).await,
+            ProtocolId::ConsensusDirectSendBcs
+        );
+
+        // Verify each of the other classes is written to its own stream
+        let mut stream_protocol_ids = HashSet::new();
+        for _ in 0..2 {
+            let recv_stream = quic_connection.accept_stream().await.unwrap();
+            let mut message_stream = MultiplexMessageStream::new(recv_stream, MAX_FRAME_SIZE);
+            stream_protocol_ids.insert(read_direct_send_protocol(&mut message_stream).await);
+        }
+        assert_eq!(
+            stream_protocol_ids,
+            HashSet::from([
+                ProtocolId::MempoolDirectSend,
+                ProtocolId::StateSyncDirectSend
+            ])
+        );
+
+        // Close the peer
+        drop(peer_handle);
+    };
+    rt.block_on(future::join(peer.start(), test));
+}
+
+// If a stream fails to open, the messages of its priority class should
+// fall back to the connection socket (instead of being dropped).
+#[test]
+fn peer_send_message_stream_open_failure() {
+    ::aptos_logger::Logger::init_for_testing();
+    let rt = Runtime::new().unwrap();
+    let _guard = rt.enter();
+
+    // Start the stream writer tasks with streams that always fail to open
+    let (socket, mut remote_socket) = MemorySocket::new_pair();
+    let outbound_scheduler_config = OutboundSchedulerConfig {
+        scheduling_policy: OutboundSchedulingPolicy::StrictPriority,
+        ..Default::default()
+    };
+    let priority_classes = get_priority_classes(&outbound_scheduler_config);
+    let (mut write_reqs_tx, _close_tx) = Peer::<MemorySocket>::start_stream_writer_tasks(
+        rt.handle(),
+        TimeService::mock(),
+        ConnectionMetadata::mock(PeerId::random()),
+        NetworkContext::mock(),
+        MultiplexMessageSink::new(socket, MAX_FRAME_SIZE),
+        || async {
+            Err::<MemorySocket, _>(io::Error::new(
+                io::ErrorKind::ConnectionReset,
+                "Failed to open the stream!",
+            ))
+        },
+        priority_classes,
+        MAX_FRAME_SIZE,
+        MAX_MESSAGE_SIZE,
+        Arc::new(outbound_scheduler_config),
+    );
+
+    // Send a message for several priority classes
+    let protocol_ids = [
+        ProtocolId::StateSyncDirectSend,
+        ProtocolId::MempoolDirectSend,
+        ProtocolId::ConsensusDirectSendBcs,
+    ];
+    for protocol_id in protocol_ids {
+        let message = NetworkMessage::DirectSendMsg(DirectSendMsg {
+            protocol_id,
+            priority: 0,
+            raw_msg: Vec::from(protocol_id.as_str()),
+        });
+        write_reqs_tx
+            .push((), OutboundMessage::new(message))
+            .unwrap();
+    }
+
+    // Verify all messages are written to the connection socket
+    rt.block_on(async move {
+        let mut socket_stream = MultiplexMessageStream::new(&mut remote_socket, MAX_FRAME_SIZE);
+        let mut socket_protocol_ids = HashSet::new();
+        for _ in 0..protocol_ids.len() {
+            socket_protocol_ids.insert(read_direct_send_protocol(&mut socket_stream).await);
+        }
+        assert_eq!(socket_protocol_ids, HashSet::from(protocol_ids));
+    });
+}
+
+/// Reads the next direct send message from the given stream and returns its protocol
+async fn read_direct_send_protocol(
+    message_stream: &mut MultiplexMessageStream<impl AsyncRead + Unpin>,
+) -> ProtocolId {
+    match message_stream.next().await {
+        Some(Ok(MultiplexMessage::Message(NetworkMessage::DirectSendMsg(message)))) => {
+            assert_eq!(message.raw_msg, message.protocol_id.as_str().as_bytes());
+            message.protocol_id
+        },
+        message => panic!("Expected a direct send message, received: {:?}", message),
+    }
+}
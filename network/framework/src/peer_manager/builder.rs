@@ -18,13 +18,18 @@ use crate::{
     ProtocolId,
 };
 use aptos_channels::{self, aptos_channel, message_queues::QueueStyle};
-use aptos_config::{config::HANDSHAKE_VERSION, network_id::NetworkContext};
+use aptos_config::{
+    config::{MessageCaptureConfig, OutboundSchedulerConfig, HANDSHAKE_VERSION},
+    network_id::NetworkContext,
+};
 use aptos_crypto::x25519;
 use aptos_logger::prelude::*;
 #[cfg(any(test, feature = "testing", feature = "fuzzing"))]
 use aptos_netcore::transport::memory::MemoryTransport;
 use aptos_netcore::transport::{
-    tcp::{TCPBufferCfg, TcpSocket, TcpTransport},
+    ip::{IpSocket, IpTransport},
+    quic::QuicTransport,
+    tcp::TCPBufferCfg,
     Transport,
 };
 use aptos_time_service::TimeService;
@@ -78,6 +83,9 @@ struct PeerManagerContext {
     max_message_size: usize,
     inbound_connection_limit: usize,
     tcp_buffer_cfg: TCPBufferCfg,
+    quic_transport: QuicTransport,
+    outbound_scheduler_config: OutboundSchedulerConfig,
+    message_capture_config: Option<MessageCaptureConfig>,
 }
 
 impl PeerManagerContext {
@@ -100,6 +108,9 @@ impl PeerManagerContext {
         max_message_size: usize,
         inbound_connection_limit: usize,
         tcp_buffer_cfg: TCPBufferCfg,
+        quic_transport: QuicTransport,
+        outbound_scheduler_config: OutboundSchedulerConfig,
+        message_capture_config: Option<MessageCaptureConfig>,
     ) -> Self {
         Self {
             pm_reqs_tx,
@@ -116,6 +127,9 @@ impl PeerManagerContext {
             max_message_size,
             inbound_connection_limit,
             tcp_buffer_cfg,
+            quic_transport,
+            outbound_scheduler_config,
+            message_capture_config,
         }
     }
 
@@ -138,12 +152,12 @@ impl PeerManagerContext {
 #[cfg(any(test, feature = "testing", feature = "fuzzing"))]
 type MemoryPeerManager =
     PeerManager<AptosNetTransport<MemoryTransport>, NoiseStream<aptos_memsocket::MemorySocket>>;
-type TcpPeerManager = PeerManager<AptosNetTransport<TcpTransport>, NoiseStream<TcpSocket>>;
+type IpPeerManager = PeerManager<AptosNetTransport<IpTransport>, NoiseStream<IpSocket>>;
 
 enum TransportPeerManager {
     #[cfg(any(test, feature = "testing", feature = "fuzzing"))]
     Memory(MemoryPeerManager),
-    Tcp(TcpPeerManager),
+    Ip(IpPeerManager),
 }
 
 pub struct PeerManagerBuilder {
@@ -173,6 +187,9 @@ impl PeerManagerBuilder {
         enable_proxy_protocol: bool,
         inbound_connection_limit: usize,
         tcp_buffer_cfg: TCPBufferCfg,
+        quic_transport: QuicTransport,
+        outbound_scheduler_config: OutboundSchedulerConfig,
+        message_capture_config: Option<MessageCaptureConfig>,
     ) -> Self {
         // Setup channel to send requests to peer manager.
         let (pm_reqs_tx, pm_reqs_rx) = aptos_channel::new(
@@ -207,6 +224,9 @@ impl PeerManagerBuilder {
                 max_message_size,
                 inbound_connection_limit,
                 tcp_buffer_cfg,
+                quic_transport,
+                outbound_scheduler_config,
+                message_capture_config,
             )),
             peer_manager: None,
             listen_address,
@@ -265,12 +285,15 @@ impl PeerManagerBuilder {
         let mut aptos_tcp_transport = APTOS_TCP_TRANSPORT.clone();
         let tcp_cfg = self.get_tcp_buffers_cfg();
         aptos_tcp_transport.set_tcp_buffers(&tcp_cfg);
+        let aptos_quic_transport = self.get_quic_transport();
 
+        // Note: the IP transport listens on either TCP or QUIC (as determined
+        // by the listen address), but can dial peer addresses on both.
         self.peer_manager = match self.listen_address.as_slice() {
-            [Ip4(_), Tcp(_)] | [Ip6(_), Tcp(_)] => {
-                Some(TransportPeerManager::Tcp(self.build_with_transport(
+            [Ip4(_), Tcp(_)] | [Ip6(_), Tcp(_)] | [Ip4(_), Quic(_)] | [Ip6(_), Quic(_)] => {
+                Some(TransportPeerManager::Ip(self.build_with_transport(
                     AptosNetTransport::new(
-                        aptos_tcp_transport,
+                        IpTransport::new(aptos_tcp_transport, aptos_quic_transport),
                         self.network_context,
                         self.time_service.clone(),
                         key,
@@ -300,7 +323,8 @@ impl PeerManagerBuilder {
             ))),
             _ => panic!(
                 "{} Unsupported listen_address: '{}', expected '/memory/<port>', \
-                 '/ip4/<addr>/tcp/<port>', or '/ip6/<addr>/tcp/<port>'.",
+                 '/ip4/<addr>/tcp/<port>', '/ip6/<addr>/tcp/<port>', \
+                 '/ip4/<addr>/quic/<port>', or '/ip6/<addr>/quic/<port>'.",
                 self.network_context, self.listen_address
             ),
         };
@@ -340,6 +364,8 @@ impl PeerManagerBuilder {
             pm_context.max_frame_size,
             pm_context.max_message_size,
             pm_context.inbound_connection_limit,
+            pm_context.outbound_scheduler_config,
+            pm_context.message_capture_config,
         );
 
         // PeerManager constructor appends a public key to the listen_address.
@@ -369,7 +395,7 @@ impl PeerManagerBuilder {
         {
             #[cfg(any(test, feature = "testing", feature = "fuzzing"))]
             TransportPeerManager::Memory(pm) => self.start_peer_manager(pm, executor),
-            TransportPeerManager::Tcp(pm) => self.start_peer_manager(pm, executor),
+            TransportPeerManager::Ip(pm) => self.start_peer_manager(pm, executor),
         }
     }
 
@@ -387,6 +413,14 @@ impl PeerManagerBuilder {
             .tcp_buffer_cfg
     }
 
+    pub fn get_quic_transport(&self) -> QuicTransport {
+        self.peer_manager_context
+            .as_ref()
+            .expect("Cannot get the QUIC transport if PeerManager has already been built.")
+            .quic_transport
+            .clone()
+    }
+
     /// Register a client that's interested in some set of protocols and return
     /// the outbound channels into network.
     pub fn add_client(
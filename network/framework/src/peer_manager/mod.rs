@@ -12,6 +12,7 @@
 //!  notification about new/lost Peers to the rest of the network stack.
 //!  * An actor responsible for dialing and listening for new connections.
 use crate::{
+    capture::MessageCapture,
     constants,
     counters::{self},
     logging::*,
@@ -23,7 +24,10 @@ use crate::{
     ProtocolId,
 };
 use aptos_channels::{self, aptos_channel, message_queues::QueueStyle};
-use aptos_config::network_id::{NetworkContext, PeerNetworkId};
+use aptos_config::{
+    config::{MessageCaptureConfig, OutboundSchedulerConfig},
+    network_id::{NetworkContext, PeerNetworkId},
+};
 use aptos_logger::prelude::*;
 use aptos_netcore::transport::{ConnectionOrigin, Transport};
 use aptos_short_hex_str::AsShortHexStr;
@@ -117,6 +121,10 @@ where
     max_message_size: usize,
     /// Inbound connection limit separate of outbound connections
     inbound_connection_limit: usize,
+    /// The configuration for scheduling outbound messages (shared by all peers)
+    outbound_scheduler_config: Arc<OutboundSchedulerConfig>,
+    /// The capture for messages sent and received by all peers (if enabled)
+    message_capture: Option<Arc<MessageCapture>>,
 }
 
 impl<TTransport, TSocket> PeerManager<TTransport, TSocket>
@@ -144,6 +152,8 @@ where
         max_frame_size: usize,
         max_message_size: usize,
         inbound_connection_limit: usize,
+        outbound_scheduler_config: OutboundSchedulerConfig,
+        message_capture_config: Option<MessageCaptureConfig>,
     ) -> Self {
         let (transport_notifs_tx, transport_notifs_rx) = aptos_channels::new(
             channel_size,
@@ -164,6 +174,27 @@ where
             transport_notifs_tx_clone,
         );
 
+        // Start capturing messages (if enabled). Failures to start the capture
+        // are logged, but do not prevent the network from starting.
+        let message_capture = message_capture_config.and_then(|message_capture_config| {
+            match MessageCapture::new(
+                network_context,
+                message_capture_config,
+                time_service.clone(),
+            ) {
+                Ok(message_capture) => Some(Arc::new(message_capture)),
+                Err(error) => {
+                    error!(
+                        NetworkSchema::new(&network_context),
+                        "{} Failed to start the network message capture! Error: {:?}",
+                        network_context,
+                        error
+                    );
+                    None
+                },
+            }
+        });
+
         Self {
             network_context,
             executor,
@@ -185,6 +216,8 @@ where
             max_frame_size,
             max_message_size,
             inbound_connection_limit,
+            outbound_scheduler_config: Arc::new(outbound_scheduler_config),
+            message_capture,
         }
     }
 
@@ -237,6 +270,7 @@ where
             "Start listening for incoming connections on {}", self.listen_addr
         );
         self.start_connection_listener();
+        let mut peer_ban_notifications = self.peers_and_metadata.get_peer_ban_list().subscribe();
         loop {
             ::futures::select! {
                 connection_event = self.transport_notifs_rx.select_next_some() => {
@@ -248,6 +282,9 @@ where
                 request = self.requests_rx.select_next_some() => {
                     self.handle_outbound_request(request).await;
                 }
+                _ = peer_ban_notifications.select_next_some() => {
+                    self.disconnect_banned_peers();
+                }
                 complete => {
                     break;
                 }
@@ -331,6 +368,25 @@ where
 
     /// Handles a new connection event
     fn handle_new_connection_event(&mut self, conn: Connection<TSocket>) {
+        // Reject the connection if the peer (or its IP address) is banned
+        if let Some(peer_ban) = self
+            .peers_and_metadata
+            .get_peer_ban_list()
+            .get_ban_for_connection(&conn.metadata)
+        {
+            info!(
+                NetworkSchema::new(&self.network_context)
+                    .connection_metadata_with_address(&conn.metadata),
+                "{} Connection rejected due to peer ban ({}): {}",
+                self.network_context,
+                peer_ban,
+                conn.metadata
+            );
+            counters::connections_rejected(&self.network_context, conn.metadata.origin).inc();
+            self.disconnect(conn);
+            return;
+        }
+
         // Get the trusted peers
         let trusted_peers = match self
             .peers_and_metadata
@@ -405,6 +461,38 @@ where
         self.update_connected_peers_metrics();
     }
 
+    /// Closes all active connections that are covered by a peer ban
+    fn disconnect_banned_peers(&mut self) {
+        let peer_ban_list = self.peers_and_metadata.get_peer_ban_list();
+        let banned_peers: Vec<_> = self
+            .active_peers
+            .iter()
+            .filter_map(|(peer_id, (conn_metadata, _))| {
+                peer_ban_list
+                    .get_ban_for_connection(conn_metadata)
+                    .map(|peer_ban| (*peer_id, peer_ban))
+            })
+            .collect();
+
+        for (peer_id, peer_ban) in banned_peers {
+            if let Some((conn_metadata, sender)) = self.active_peers.remove(&peer_id) {
+                info!(
+                    NetworkSchema::new(&self.network_context)
+                        .connection_metadata_with_address(&conn_metadata),
+                    "{} Closing connection with Peer {} due to peer ban ({})",
+                    self.network_context,
+                    peer_id.short_str(),
+                    peer_ban
+                );
+                self.remove_peer_from_metadata(peer_id, conn_metadata.connection_id);
+
+                // This triggers a disconnect.
+                drop(sender);
+            }
+        }
+        self.update_connected_peers_metrics();
+    }
+
     fn remove_peer_from_metadata(&mut self, peer_id: AccountAddress, connection_id: ConnectionId) {
         let peer_network_id = PeerNetworkId::new(self.network_context.network_id(), peer_id);
         if let Err(error) = self
@@ -676,6 +764,8 @@ where
             constants::MAX_CONCURRENT_OUTBOUND_RPCS,
             self.max_frame_size,
             self.max_message_size,
+            self.outbound_scheduler_config.clone(),
+            self.message_capture.clone(),
         );
         self.executor.spawn(peer.start());
 
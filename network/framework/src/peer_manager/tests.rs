@@ -3,7 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    application::storage::PeersAndMetadata,
+    application::{ban_list::BanTarget, storage::PeersAndMetadata},
     constants,
     peer::DisconnectReason,
     peer_manager::{
@@ -24,7 +24,7 @@ use crate::{
 use anyhow::anyhow;
 use aptos_channels::{aptos_channel, message_queues::QueueStyle};
 use aptos_config::{
-    config::{PeerRole, MAX_INBOUND_CONNECTIONS},
+    config::{OutboundSchedulerConfig, PeerRole, MAX_INBOUND_CONNECTIONS},
     network_id::{NetworkContext, NetworkId},
 };
 use aptos_memsocket::MemorySocket;
@@ -34,7 +34,7 @@ use aptos_netcore::transport::{
 use aptos_time_service::TimeService;
 use aptos_types::{network_address::NetworkAddress, PeerId};
 use bytes::Bytes;
-use futures::{channel::oneshot, io::AsyncWriteExt, stream::StreamExt};
+use futures::{channel::oneshot, io::AsyncWriteExt, stream::StreamExt, FutureExt};
 use std::error::Error;
 use tokio::runtime::Handle;
 use tokio_util::compat::{
@@ -116,6 +116,8 @@ fn build_test_peer_manager(
         constants::MAX_FRAME_SIZE,
         constants::MAX_MESSAGE_SIZE,
         MAX_INBOUND_CONNECTIONS,
+        OutboundSchedulerConfig::default(),
+        None,
     );
 
     (
@@ -652,6 +654,67 @@ fn test_dial_disconnect() {
     runtime.block_on(test);
 }
 
+#[test]
+fn test_peer_ban_disconnect_and_reject() {
+    ::aptos_logger::Logger::init_for_testing();
+    let runtime = ::tokio::runtime::Runtime::new().unwrap();
+
+    // Create a list of ordered PeerIds so we can ensure how PeerIds will be compared.
+    let ids = ordered_peer_ids(2);
+    let (mut peer_manager, _request_tx, _connection_reqs_tx, mut conn_status_rx) =
+        build_test_peer_manager(runtime.handle().clone(), ids[1]);
+
+    let test = async move {
+        let (outbound, _inbound) = build_test_connection();
+        // Trigger add_peer function PeerManager.
+        add_peer_to_manager(
+            &mut peer_manager,
+            outbound,
+            ids[0],
+            None,
+            ConnectionOrigin::Outbound,
+            0,
+        );
+
+        // Expect NewPeer notification from PeerManager.
+        let conn_notif = conn_status_rx.next().await.unwrap();
+        assert!(matches!(conn_notif, ConnectionNotification::NewPeer(_, _)));
+
+        // Ban the peer and verify that the existing connection is closed
+        peer_manager
+            .peers_and_metadata
+            .get_peer_ban_list()
+            .ban(BanTarget::PeerId(ids[0]), None, None)
+            .unwrap();
+        peer_manager.disconnect_banned_peers();
+        assert_peer_disconnected_event(
+            ids[0],
+            ConnectionOrigin::Outbound,
+            DisconnectReason::RequestedByPeerManager,
+            &mut peer_manager,
+        )
+        .await;
+
+        // Expect LostPeer notification from PeerManager.
+        let conn_notif = conn_status_rx.next().await.unwrap();
+        assert!(matches!(conn_notif, ConnectionNotification::LostPeer(_, _)));
+
+        // Verify that a new inbound connection from the banned peer is rejected
+        let (_outbound, inbound) = build_test_connection();
+        peer_manager.handle_new_connection_event(create_connection(
+            inbound,
+            ids[0],
+            NetworkAddress::mock(),
+            ConnectionOrigin::Inbound,
+            ConnectionId::from(1),
+        ));
+        assert!(!peer_manager.active_peers.contains_key(&ids[0]));
+        assert!(conn_status_rx.next().now_or_never().is_none());
+    };
+
+    runtime.block_on(test);
+}
+
 fn add_peer_to_manager<TSocket: transport::TSocket>(
     peer_manager: &mut PeerManager<
         BoxedTransport<Connection<TSocket>, impl Error + Sync + Send + 'static>,
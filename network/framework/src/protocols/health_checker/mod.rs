@@ -88,6 +88,13 @@ pub enum HealthCheckerMsg {
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Ping(u32);
 
+impl Ping {
+    /// Returns the pong that answers this ping
+    pub(crate) fn to_pong(&self) -> Pong {
+        Pong(self.0)
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Pong(u32);
 
@@ -282,7 +289,7 @@ impl<NetworkClient: NetworkClientInterface<HealthCheckerMsg> + Unpin> HealthChec
         protocol: ProtocolId,
         res_tx: oneshot::Sender<Result<Bytes, RpcError>>,
     ) {
-        let message = match protocol.to_bytes(&HealthCheckerMsg::Pong(Pong(ping.0))) {
+        let message = match protocol.to_bytes(&HealthCheckerMsg::Pong(ping.to_pong())) {
             Ok(msg) => msg,
             Err(e) => {
                 warn!(
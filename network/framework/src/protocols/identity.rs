@@ -4,10 +4,11 @@
 
 //! Protocol used to exchange supported protocol information with a remote.
 
-use crate::protocols::wire::handshake::v1::HandshakeMsg;
+use crate::protocols::wire::handshake::v1::{CompressionDictionariesMsg, HandshakeMsg};
 use aptos_netcore::framing::{read_u16frame, write_u16frame};
 use bytes::BytesMut;
 use futures::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
+use serde::{de::DeserializeOwned, Serialize};
 use std::io;
 
 /// The Handshake exchange protocol.
@@ -18,34 +19,83 @@ pub async fn exchange_handshake<T>(
 where
     T: AsyncRead + AsyncWrite + Unpin,
 {
-    // Send serialized handshake message to remote peer.
-    let msg = bcs::to_bytes(own_handshake).map_err(|e| {
+    exchange_message(own_handshake, socket, "identity msg").await
+}
+
+/// The compression dictionaries exchange protocol. This is only run after the
+/// Handshake exchange (if both peers negotiated a zstd protocol).
+pub async fn exchange_compression_dictionaries<T>(
+    own_dictionaries: &CompressionDictionariesMsg,
+    socket: &mut T,
+) -> io::Result<CompressionDictionariesMsg>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    exchange_message(own_dictionaries, socket, "compression dictionaries msg").await
+}
+
+/// Sends the serialized message to the remote peer, and reads the message of the remote peer
+async fn exchange_message<T, M>(own_message: &M, socket: &mut T, label: &str) -> io::Result<M>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+    M: Serialize + DeserializeOwned,
+{
+    // Send serialized message to remote peer.
+    let msg = bcs::to_bytes(own_message).map_err(|e| {
         io::Error::new(
             io::ErrorKind::InvalidData,
-            format!("Failed to serialize identity msg: {}", e),
+            format!("Failed to serialize {}: {}", label, e),
         )
     })?;
     write_u16frame(socket, &msg).await?;
     socket.flush().await?;
 
-    // Read handshake message from the Remote
+    // Read message from the Remote
     let mut response = BytesMut::new();
     read_u16frame(socket, &mut response).await?;
-    let identity = bcs::from_bytes(&response).map_err(|e| {
+    let remote_message = bcs::from_bytes(&response).map_err(|e| {
         io::Error::new(
             io::ErrorKind::InvalidData,
-            format!("Failed to parse identity msg: {}", e),
+            format!("Failed to parse {}: {}", label, e),
         )
     })?;
-    Ok(identity)
+    Ok(remote_message)
+}
+
+/// The channel binding exchange protocol. Each peer sends its own channel binding
+/// (e.g., the channel binding of the QUIC connection carrying the socket) to the
+/// remote peer, and verifies that the remote peer observed the same binding. This
+/// must be run over an authenticated socket (e.g., after the Noise handshake).
+pub async fn exchange_channel_binding<T>(channel_binding: &[u8], socket: &mut T) -> io::Result<()>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    // Send the channel binding to the remote peer
+    write_u16frame(socket, channel_binding).await?;
+    socket.flush().await?;
+
+    // Read the channel binding from the remote peer and verify it matches
+    let mut remote_channel_binding = BytesMut::new();
+    read_u16frame(socket, &mut remote_channel_binding).await?;
+    if remote_channel_binding.as_ref() != channel_binding {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "The channel binding of the remote peer does not match!",
+        ));
+    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
         protocols::{
-            identity::exchange_handshake,
-            wire::handshake::v1::{HandshakeMsg, MessagingProtocolVersion, ProtocolIdSet},
+            identity::{
+                exchange_channel_binding, exchange_compression_dictionaries, exchange_handshake,
+            },
+            wire::handshake::v1::{
+                CompressionDictionariesMsg, HandshakeMsg, MessagingProtocolVersion, ProtocolIdSet,
+            },
         },
         ProtocolId,
     };
@@ -186,4 +236,46 @@ mod tests {
 
         block_on(join(server, client));
     }
+
+    #[test]
+    fn channel_binding_match_and_mismatch() {
+        // Verify the exchange succeeds when both peers have the same binding
+        let (mut outbound, mut inbound) = build_test_connection();
+        let channel_binding = [7u8; 32];
+        let server = exchange_channel_binding(&channel_binding, &mut inbound);
+        let client = exchange_channel_binding(&channel_binding, &mut outbound);
+        let (server_result, client_result) = block_on(join(server, client));
+        server_result.unwrap();
+        client_result.unwrap();
+
+        // Verify the exchange fails (for both peers) when the bindings differ
+        let (mut outbound, mut inbound) = build_test_connection();
+        let server = exchange_channel_binding(&[1u8; 32], &mut inbound);
+        let client = exchange_channel_binding(&[2u8; 32], &mut outbound);
+        let (server_result, client_result) = block_on(join(server, client));
+        assert_eq!(
+            server_result.unwrap_err().kind(),
+            std::io::ErrorKind::PermissionDenied
+        );
+        assert_eq!(
+            client_result.unwrap_err().kind(),
+            std::io::ErrorKind::PermissionDenied
+        );
+    }
+
+    #[test]
+    fn compression_dictionaries_exchange() {
+        let (mut outbound, mut inbound) = build_test_connection();
+        let server_dictionaries = CompressionDictionariesMsg {
+            dictionary_ids: vec![1, 2],
+        };
+        let client_dictionaries = CompressionDictionariesMsg::default();
+
+        // Verify that each peer receives the dictionaries of the other peer
+        let server = exchange_compression_dictionaries(&server_dictionaries, &mut inbound);
+        let client = exchange_compression_dictionaries(&client_dictionaries, &mut outbound);
+        let (server_result, client_result) = block_on(join(server, client));
+        assert_eq!(server_result.unwrap(), client_dictionaries);
+        assert_eq!(client_result.unwrap(), server_dictionaries);
+    }
 }
@@ -45,12 +45,14 @@
 //! [`Peer`]: crate::peer::Peer
 
 use crate::{
+    capture::{CapturedMessageType, MessageCapture, MessageDirection},
     counters::{
         self, network_application_inbound_traffic, network_application_outbound_traffic,
         CANCELED_LABEL, DECLINED_LABEL, EXPIRED_LABEL, FAILED_LABEL, INBOUND_LABEL, OUTBOUND_LABEL,
         RECEIVED_LABEL, REQUEST_LABEL, RESPONSE_LABEL, SENT_LABEL,
     },
     logging::NetworkSchema,
+    peer::outbound_scheduler::OutboundMessage,
     protocols::{
         network::{ReceivedMessage, SerializedRequest},
         wire::messaging::v1::{NetworkMessage, Priority, RequestId, RpcRequest, RpcResponse},
@@ -158,6 +160,20 @@ impl PartialEq for InboundRpcRequest {
     }
 }
 
+/// Captures the given RPC request or response (if message capture is enabled)
+fn capture_rpc_message(
+    message_capture: &Option<Arc<MessageCapture>>,
+    peer_id: PeerId,
+    direction: MessageDirection,
+    message_type: CapturedMessageType,
+    protocol_id: ProtocolId,
+    data: &[u8],
+) {
+    if let Some(message_capture) = message_capture {
+        message_capture.capture_message(peer_id, direction, message_type, protocol_id, data);
+    }
+}
+
 /// `InboundRpcs` handles new inbound rpc requests off the wire, notifies the
 /// `PeerManager` of the new request, and stores the pending response on a queue.
 /// If the response eventually completes, `InboundRpc` records some metrics and
@@ -182,6 +198,8 @@ pub struct InboundRpcs {
     /// Only allow this many concurrent inbound rpcs at one time from this remote
     /// peer.  New inbound requests exceeding this limit will be dropped.
     max_concurrent_inbound_rpcs: u32,
+    /// The capture for inbound requests and outbound responses (if enabled)
+    message_capture: Option<Arc<MessageCapture>>,
 }
 
 impl InboundRpcs {
@@ -191,6 +209,7 @@ impl InboundRpcs {
         remote_peer_id: PeerId,
         inbound_rpc_timeout: Duration,
         max_concurrent_inbound_rpcs: u32,
+        message_capture: Option<Arc<MessageCapture>>,
     ) -> Self {
         Self {
             network_context,
@@ -199,6 +218,7 @@ impl InboundRpcs {
             inbound_rpc_tasks: FuturesUnordered::new(),
             inbound_rpc_timeout,
             max_concurrent_inbound_rpcs,
+            message_capture,
         }
     }
 
@@ -240,6 +260,14 @@ impl InboundRpcs {
             protocol_id,
         );
         self.update_inbound_rpc_request_metrics(protocol_id, rpc_request.raw_request.len() as u64);
+        capture_rpc_message(
+            &self.message_capture,
+            self.remote_peer_id,
+            MessageDirection::Inbound,
+            CapturedMessageType::RpcRequest(request_id),
+            protocol_id,
+            &rpc_request.raw_request,
+        );
 
         let timer =
             counters::inbound_rpc_handler_latency(network_context, protocol_id).start_timer();
@@ -324,7 +352,7 @@ impl InboundRpcs {
     /// the outbound write queue.
     pub fn send_outbound_response(
         &mut self,
-        write_reqs_tx: &mut aptos_channel::Sender<(), NetworkMessage>,
+        write_reqs_tx: &mut aptos_channel::Sender<(), OutboundMessage>,
         maybe_response: Result<(RpcResponse, ProtocolId), RpcError>,
     ) -> Result<(), RpcError> {
         let network_context = &self.network_context;
@@ -351,7 +379,15 @@ impl InboundRpcs {
             self.remote_peer_id.short_str(),
             response.request_id,
         );
-        let message = NetworkMessage::RpcResponse(response);
+        capture_rpc_message(
+            &self.message_capture,
+            self.remote_peer_id,
+            MessageDirection::Outbound,
+            CapturedMessageType::RpcResponse(response.request_id),
+            protocol_id,
+            &response.raw_response,
+        );
+        let message = OutboundMessage::new_rpc_response(response, protocol_id);
         write_reqs_tx.push((), message)?;
 
         // Update the outbound RPC response metrics
@@ -410,6 +446,8 @@ pub struct OutboundRpcs {
     /// Only allow this many concurrent outbound rpcs at one time from this remote
     /// peer. New outbound requests exceeding this limit will be dropped.
     max_concurrent_outbound_rpcs: u32,
+    /// The capture for outbound requests and inbound responses (if enabled)
+    message_capture: Option<Arc<MessageCapture>>,
 }
 
 impl OutboundRpcs {
@@ -418,6 +456,7 @@ impl OutboundRpcs {
         time_service: TimeService,
         remote_peer_id: PeerId,
         max_concurrent_outbound_rpcs: u32,
+        message_capture: Option<Arc<MessageCapture>>,
     ) -> Self {
         Self {
             network_context,
@@ -427,6 +466,7 @@ impl OutboundRpcs {
             outbound_rpc_tasks: FuturesUnordered::new(),
             pending_outbound_rpcs: HashMap::new(),
             max_concurrent_outbound_rpcs,
+            message_capture,
         }
     }
 
@@ -434,7 +474,7 @@ impl OutboundRpcs {
     pub fn handle_outbound_request(
         &mut self,
         request: OutboundRpcRequest,
-        write_reqs_tx: &mut aptos_channel::Sender<(), NetworkMessage>,
+        write_reqs_tx: &mut aptos_channel::Sender<(), OutboundMessage>,
     ) -> Result<(), RpcError> {
         let network_context = &self.network_context;
         let peer_id = &self.remote_peer_id;
@@ -491,13 +531,21 @@ impl OutboundRpcs {
             counters::outbound_rpc_request_latency(network_context, protocol_id).start_timer();
 
         // Enqueue rpc request message onto outbound write queue.
+        capture_rpc_message(
+            &self.message_capture,
+            *peer_id,
+            MessageDirection::Outbound,
+            CapturedMessageType::RpcRequest(request_id),
+            protocol_id,
+            &request_data,
+        );
         let message = NetworkMessage::RpcRequest(RpcRequest {
             protocol_id,
             request_id,
             priority: Priority::default(),
             raw_request: Vec::from(request_data.as_ref()),
         });
-        write_reqs_tx.push((), message)?;
+        write_reqs_tx.push((), OutboundMessage::new(message))?;
 
         // Update the outbound RPC request metrics
         self.update_outbound_rpc_request_metrics(protocol_id, req_len);
@@ -698,6 +746,14 @@ impl OutboundRpcs {
                 protocol_id,
                 response.raw_response.len() as u64,
             );
+            capture_rpc_message(
+                &self.message_capture,
+                *peer_id,
+                MessageDirection::Inbound,
+                CapturedMessageType::RpcResponse(request_id),
+                protocol_id,
+                &response.raw_response,
+            );
             response_tx.send(response).is_err()
         } else {
             true
@@ -147,19 +147,16 @@ impl InboundStream {
     }
 }
 
-pub struct OutboundStream {
+/// Splits large messages into a stream header and fragments (each of which fits
+/// into a single frame).
+pub struct MessageFragmenter {
     request_id_gen: U32IdGenerator,
     max_frame_size: usize,
     max_message_size: usize,
-    stream_tx: Sender<MultiplexMessage>,
 }
 
-impl OutboundStream {
-    pub fn new(
-        max_frame_size: usize,
-        max_message_size: usize,
-        stream_tx: Sender<MultiplexMessage>,
-    ) -> Self {
+impl MessageFragmenter {
+    pub fn new(max_frame_size: usize, max_message_size: usize) -> Self {
         // some buffer for headers
         let max_frame_size = max_frame_size - 64;
         assert!(
@@ -172,7 +169,6 @@ impl OutboundStream {
             request_id_gen: U32IdGenerator::new(),
             max_frame_size,
             max_message_size,
-            stream_tx,
         }
     }
 
@@ -180,7 +176,11 @@ impl OutboundStream {
         message.data_len() > self.max_frame_size
     }
 
-    pub async fn stream_message(&mut self, mut message: NetworkMessage) -> anyhow::Result<()> {
+    /// Splits the message into a stream header (followed by all fragments)
+    pub fn fragment_message(
+        &mut self,
+        mut message: NetworkMessage,
+    ) -> anyhow::Result<Vec<StreamMessage>> {
         ensure!(
             message.data_len() <= self.max_message_size,
             "Message length {} exceed size limit {}",
@@ -213,22 +213,48 @@ impl OutboundStream {
             chunks.len() <= u8::MAX as usize,
             "Number of fragments overflowed"
         );
-        let header = StreamMessage::Header(StreamHeader {
+        let mut stream_messages = Vec::with_capacity(chunks.len() + 1);
+        stream_messages.push(StreamMessage::Header(StreamHeader {
             request_id,
             num_fragments: chunks.len() as u8,
             message,
-        });
-        self.stream_tx
-            .send(MultiplexMessage::Stream(header))
-            .await?;
+        }));
         for (index, chunk) in chunks.enumerate() {
-            let message = StreamMessage::Fragment(StreamFragment {
+            stream_messages.push(StreamMessage::Fragment(StreamFragment {
                 request_id,
                 fragment_id: index as u8 + 1,
                 raw_data: Vec::from(chunk),
-            });
+            }));
+        }
+        Ok(stream_messages)
+    }
+}
+
+pub struct OutboundStream {
+    fragmenter: MessageFragmenter,
+    stream_tx: Sender<MultiplexMessage>,
+}
+
+impl OutboundStream {
+    pub fn new(
+        max_frame_size: usize,
+        max_message_size: usize,
+        stream_tx: Sender<MultiplexMessage>,
+    ) -> Self {
+        Self {
+            fragmenter: MessageFragmenter::new(max_frame_size, max_message_size),
+            stream_tx,
+        }
+    }
+
+    pub fn should_stream(&self, message: &NetworkMessage) -> bool {
+        self.fragmenter.should_stream(message)
+    }
+
+    pub async fn stream_message(&mut self, message: NetworkMessage) -> anyhow::Result<()> {
+        for stream_message in self.fragmenter.fragment_message(message)? {
             self.stream_tx
-                .send(MultiplexMessage::Stream(message))
+                .send(MultiplexMessage::Stream(stream_message))
                 .await?;
         }
         Ok(())
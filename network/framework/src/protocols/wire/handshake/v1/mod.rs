@@ -73,12 +73,15 @@ pub enum ProtocolId {
     JWKConsensusRpcJson = 26,
     ConsensusObserver = 27,
     ConsensusObserverRpc = 28,
+    ConsensusRpcZstd = 29,
+    ConsensusDirectSendZstd = 30,
 }
 
 /// The encoding types for Protocols
 enum Encoding {
     Bcs(usize),
     CompressedBcs(usize),
+    ZstdCompressedBcs(usize),
     Json,
 }
 
@@ -115,6 +118,8 @@ impl ProtocolId {
             JWKConsensusRpcJson => "JWKConsensusRpcJson",
             ConsensusObserver => "ConsensusObserver",
             ConsensusObserverRpc => "ConsensusObserverRpc",
+            ConsensusRpcZstd => "ConsensusRpcZstd",
+            ConsensusDirectSendZstd => "ConsensusDirectSendZstd",
         }
     }
 
@@ -150,6 +155,8 @@ impl ProtocolId {
             ProtocolId::JWKConsensusRpcJson,
             ProtocolId::ConsensusObserver,
             ProtocolId::ConsensusObserverRpc,
+            ProtocolId::ConsensusRpcZstd,
+            ProtocolId::ConsensusDirectSendZstd,
         ]
     }
 
@@ -160,6 +167,9 @@ impl ProtocolId {
             ProtocolId::ConsensusDirectSendCompressed | ProtocolId::ConsensusRpcCompressed => {
                 Encoding::CompressedBcs(RECURSION_LIMIT)
             },
+            ProtocolId::ConsensusDirectSendZstd | ProtocolId::ConsensusRpcZstd => {
+                Encoding::ZstdCompressedBcs(RECURSION_LIMIT)
+            },
             ProtocolId::ConsensusObserver => Encoding::CompressedBcs(RECURSION_LIMIT),
             ProtocolId::DKGDirectSendCompressed | ProtocolId::DKGRpcCompressed => {
                 Encoding::CompressedBcs(RECURSION_LIMIT)
@@ -172,12 +182,18 @@ impl ProtocolId {
         }
     }
 
+    /// Returns true iff messages for the protocol are compressed using zstd
+    pub fn is_zstd_compressed(self) -> bool {
+        matches!(self.encoding(), Encoding::ZstdCompressedBcs(_))
+    }
+
     /// Returns the compression client label based on the current protocol id
     fn get_compression_client(self) -> CompressionClient {
         match self {
-            ProtocolId::ConsensusDirectSendCompressed | ProtocolId::ConsensusRpcCompressed => {
-                CompressionClient::Consensus
-            },
+            ProtocolId::ConsensusDirectSendCompressed
+            | ProtocolId::ConsensusRpcCompressed
+            | ProtocolId::ConsensusDirectSendZstd
+            | ProtocolId::ConsensusRpcZstd => CompressionClient::Consensus,
             ProtocolId::ConsensusObserver => CompressionClient::ConsensusObserver,
             ProtocolId::MempoolDirectSend => CompressionClient::Mempool,
             ProtocolId::DKGDirectSendCompressed | ProtocolId::DKGRpcCompressed => {
@@ -211,6 +227,16 @@ impl ProtocolId {
                 )
                 .map_err(|e| anyhow!("{:?}", e))
             },
+            Encoding::ZstdCompressedBcs(limit) => {
+                let compression_client = self.get_compression_client();
+                let bcs_bytes = self.bcs_encode(value, limit)?;
+                aptos_compression::compress_with_zstd(
+                    bcs_bytes,
+                    compression_client,
+                    MAX_APPLICATION_MESSAGE_SIZE,
+                )
+                .map_err(|e| anyhow!("{:?}", e))
+            },
             Encoding::Json => serde_json::to_vec(value).map_err(|e| anyhow!("{:?}", e)),
         };
 
@@ -241,6 +267,16 @@ impl ProtocolId {
                 .map_err(|e| anyhow! {"{:?}", e})?;
                 self.bcs_decode(&raw_bytes, limit)
             },
+            Encoding::ZstdCompressedBcs(limit) => {
+                let compression_client = self.get_compression_client();
+                let raw_bytes = aptos_compression::decompress_with_zstd(
+                    &bytes.to_vec(),
+                    compression_client,
+                    MAX_APPLICATION_MESSAGE_SIZE,
+                )
+                .map_err(|e| anyhow!("{:?}", e))?;
+                self.bcs_decode(&raw_bytes, limit)
+            },
             Encoding::Json => serde_json::from_slice(bytes).map_err(|e| anyhow!("{:?}", e)),
         };
 
@@ -252,6 +288,26 @@ impl ProtocolId {
         result
     }
 
+    /// Returns the uncompressed bytes of the given (serialized) message, i.e., the
+    /// message bytes before compression (based on the protocol ID and encoding).
+    pub fn decompress(&self, bytes: &[u8]) -> anyhow::Result<Vec<u8>> {
+        match self.encoding() {
+            Encoding::Bcs(_) | Encoding::Json => Ok(bytes.to_vec()),
+            Encoding::CompressedBcs(_) => aptos_compression::decompress(
+                &bytes.to_vec(),
+                self.get_compression_client(),
+                MAX_APPLICATION_MESSAGE_SIZE,
+            )
+            .map_err(|e| anyhow!("{:?}", e)),
+            Encoding::ZstdCompressedBcs(_) => aptos_compression::decompress_with_zstd(
+                &bytes.to_vec(),
+                self.get_compression_client(),
+                MAX_APPLICATION_MESSAGE_SIZE,
+            )
+            .map_err(|e| anyhow!("{:?}", e)),
+        }
+    }
+
     /// Serializes the value using BCS encoding (with a specified limit)
     fn bcs_encode<T: Serialize>(&self, value: &T, limit: usize) -> anyhow::Result<Vec<u8>> {
         bcs::to_bytes_with_limit(value, limit).map_err(|e| anyhow!("{:?}", e))
@@ -481,3 +537,56 @@ impl fmt::Display for HandshakeMsg {
         )
     }
 }
+
+//
+// CompressionDictionariesMsg
+//
+
+/// The CompressionDictionariesMsg advertises the IDs of the zstd dictionaries
+/// that a node can use to decompress messages. It is exchanged directly after
+/// the [`HandshakeMsg`], but only if both peers support at least one zstd
+/// protocol (older peers don't support zstd, and never expect this message).
+/// Zstd is only used to send messages to peers that advertise the dictionary
+/// used to compress them. Otherwise, messages fall back to lz4.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CompressionDictionariesMsg {
+    pub dictionary_ids: Vec<u32>,
+}
+
+impl CompressionDictionariesMsg {
+    /// Creates a message advertising all locally registered decompression dictionaries
+    pub fn from_registered_dictionaries() -> Self {
+        Self {
+            dictionary_ids: aptos_compression::dictionary::get_decompression_dictionary_ids(),
+        }
+    }
+
+    /// Returns true iff the compression dictionaries should be exchanged
+    /// with a peer (i.e., a zstd protocol was negotiated with the peer).
+    pub fn should_exchange(common_protocols: &ProtocolIdSet) -> bool {
+        common_protocols
+            .iter()
+            .any(|protocol| protocol.is_zstd_compressed())
+    }
+
+    /// Returns the protocols that can be used to send messages to the peer
+    /// that sent this message. Zstd protocols are removed if messages would
+    /// be compressed using a dictionary that the peer hasn't advertised.
+    pub fn filter_supported_protocols(&self, common_protocols: ProtocolIdSet) -> ProtocolIdSet {
+        common_protocols
+            .iter()
+            .filter(|protocol| {
+                if !protocol.is_zstd_compressed() {
+                    return true;
+                }
+                let compression_client = protocol.get_compression_client();
+                match aptos_compression::dictionary::get_compression_dictionary_id(
+                    &compression_client,
+                ) {
+                    Some(dictionary_id) => self.dictionary_ids.contains(&dictionary_id),
+                    None => true, // Messages are compressed without a dictionary
+                }
+            })
+            .collect()
+    }
+}
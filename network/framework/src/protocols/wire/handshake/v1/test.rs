@@ -3,6 +3,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::*;
+use aptos_compression::{
+    dictionary::{register_dictionary, train_dictionary, ZstdDictionary},
+    DEFAULT_ZSTD_COMPRESSION_LEVEL,
+};
 use std::iter::FromIterator;
 
 // Ensure serialization of MessagingProtocolVersion enum takes 1 byte.
@@ -23,6 +27,17 @@ fn protocols_to_from_iter() {
     );
 }
 
+// Ensure the protocol names known to the config (e.g., for sanitizing
+// the outbound scheduler config) match the names of all protocols.
+#[test]
+fn test_config_protocol_names() {
+    let protocol_names: Vec<_> = ProtocolId::all()
+        .iter()
+        .map(|protocol| protocol.as_str())
+        .collect();
+    assert_eq!(protocol_names, aptos_config::config::PROTOCOL_NAMES);
+}
+
 #[test]
 fn test_as_u8_serde_equiv() {
     for protocol in ProtocolId::all() {
@@ -183,3 +198,91 @@ fn ignore_unknown_protocols() {
         ProtocolIdSet::empty(),
     );
 }
+
+#[test]
+fn zstd_protocols_fall_back_to_lz4() {
+    // Create a handshake for a peer that supports both zstd and lz4
+    let zstd_hs = HandshakeMsg::from_supported(ProtocolIdSet::from_iter([
+        ProtocolId::ConsensusRpcZstd,
+        ProtocolId::ConsensusRpcCompressed,
+    ]));
+
+    // Create a handshake for a peer that only supports lz4
+    let lz4_hs = HandshakeMsg::from_supported(ProtocolIdSet::from_iter([
+        ProtocolId::ConsensusRpcCompressed,
+    ]));
+
+    // Verify that zstd is negotiated between zstd peers
+    let (_, common_protos) = zstd_hs.perform_handshake(&zstd_hs).unwrap();
+    assert!(common_protos.contains(ProtocolId::ConsensusRpcZstd));
+
+    // Verify that only lz4 is negotiated with lz4-only peers
+    let (_, common_protos) = zstd_hs.perform_handshake(&lz4_hs).unwrap();
+    assert_eq!(
+        common_protos,
+        ProtocolIdSet::from_iter([ProtocolId::ConsensusRpcCompressed])
+    );
+}
+
+#[test]
+fn zstd_protocols_serialization() {
+    let message: Vec<u64> = (0..1000).collect();
+    for protocol_id in [
+        ProtocolId::ConsensusRpcZstd,
+        ProtocolId::ConsensusDirectSendZstd,
+    ] {
+        // Verify that the message round trips
+        let bytes = protocol_id.to_bytes(&message).unwrap();
+        let decoded_message: Vec<u64> = protocol_id.from_bytes(&bytes).unwrap();
+        assert_eq!(decoded_message, message);
+
+        // Verify that lz4 compressed messages can't be decoded using zstd
+        let lz4_bytes = ProtocolId::ConsensusRpcCompressed
+            .to_bytes(&message)
+            .unwrap();
+        assert!(protocol_id.from_bytes::<Vec<u64>>(&lz4_bytes).is_err());
+    }
+}
+
+#[test]
+fn zstd_protocols_require_advertised_dictionary() {
+    // Verify that dictionaries are only exchanged if a zstd protocol was negotiated
+    let common_protocols = ProtocolIdSet::from_iter([
+        ProtocolId::ConsensusRpcZstd,
+        ProtocolId::ConsensusRpcCompressed,
+    ]);
+    let lz4_protocols = ProtocolIdSet::from_iter([ProtocolId::ConsensusRpcCompressed]);
+    assert!(CompressionDictionariesMsg::should_exchange(
+        &common_protocols
+    ));
+    assert!(!CompressionDictionariesMsg::should_exchange(&lz4_protocols));
+
+    // Register a dictionary to compress consensus messages
+    let samples: Vec<Vec<u8>> = (0..1000u64)
+        .map(|i| bcs::to_bytes(&(i, format!("consensus message {}", i % 7), vec![i; 8])).unwrap())
+        .collect();
+    let dictionary_bytes = train_dictionary(&samples, 1024).unwrap();
+    let dictionary =
+        ZstdDictionary::new(&dictionary_bytes, DEFAULT_ZSTD_COMPRESSION_LEVEL).unwrap();
+    let dictionary_id = dictionary.get_dictionary_id();
+    register_dictionary(CompressionClient::Consensus, dictionary);
+    assert!(CompressionDictionariesMsg::from_registered_dictionaries()
+        .dictionary_ids
+        .contains(&dictionary_id));
+
+    // Verify that zstd is dropped for peers that don't have the dictionary
+    let remote_dictionaries = CompressionDictionariesMsg::default();
+    assert_eq!(
+        remote_dictionaries.filter_supported_protocols(common_protocols.clone()),
+        lz4_protocols
+    );
+
+    // Verify that zstd is kept for peers that have the dictionary
+    let remote_dictionaries = CompressionDictionariesMsg {
+        dictionary_ids: vec![dictionary_id],
+    };
+    assert_eq!(
+        remote_dictionaries.filter_supported_protocols(common_protocols.clone()),
+        common_protocols
+    );
+}
@@ -0,0 +1,184 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    capture::{
+        find_rpc_response, read_captured_messages, CapturedMessage, CapturedMessageType,
+        MessageDirection,
+    },
+    protocols::{
+        network::ReceivedMessage,
+        rpc::error::RpcError,
+        wire::messaging::v1::{DirectSendMsg, NetworkMessage, RpcRequest},
+    },
+    testutils::test_node::{mock_conn_metadata, TestNode},
+    ProtocolId,
+};
+use aptos_config::{
+    config::PeerRole,
+    network_id::{NetworkId, PeerNetworkId},
+};
+use aptos_netcore::transport::ConnectionOrigin;
+use aptos_types::PeerId;
+use bytes::Bytes;
+use futures::channel::oneshot;
+use std::{collections::HashSet, io, path::Path, sync::Arc, time::Duration};
+
+/// The response to a replayed RPC request (and the captured request and response)
+pub struct ReplayedRpcResponse {
+    pub captured_message: CapturedMessage,
+    pub captured_response: Option<CapturedMessage>,
+    pub response_receiver: oneshot::Receiver<Result<Bytes, RpcError>>,
+}
+
+/// Replays captured inbound messages against a [`TestNode`], in the order in
+/// which they were captured. This allows message sequences observed on a real
+/// node (e.g., mempool, state sync or consensus traffic) to be reproduced
+/// deterministically in tests.
+pub struct CaptureReplayer {
+    captured_messages: Vec<CapturedMessage>,
+}
+
+impl CaptureReplayer {
+    pub fn new(captured_messages: Vec<CapturedMessage>) -> Self {
+        Self { captured_messages }
+    }
+
+    /// Creates a replayer for all messages in the given (network) capture directory
+    pub fn from_capture_dir(capture_dir: &Path) -> io::Result<Self> {
+        read_captured_messages(capture_dir).map(Self::new)
+    }
+
+    /// Only replays messages for the given protocols
+    pub fn filter_protocols(mut self, protocol_ids: &[ProtocolId]) -> Self {
+        self.captured_messages
+            .retain(|captured_message| protocol_ids.contains(&captured_message.protocol_id));
+        self
+    }
+
+    /// Returns the inbound messages captured on the given network (and their
+    /// indices). Inbound RPC responses (to our own requests) are not included.
+    pub fn inbound_messages(
+        &self,
+        network_id: NetworkId,
+    ) -> impl Iterator<Item = (usize, &CapturedMessage)> + '_ {
+        self.captured_messages
+            .iter()
+            .enumerate()
+            .filter(move |(_, captured_message)| {
+                captured_message.network_id == network_id
+                    && captured_message.direction == MessageDirection::Inbound
+                    && !matches!(
+                        captured_message.message_type,
+                        CapturedMessageType::RpcResponse(_)
+                    )
+            })
+    }
+
+    /// Returns the remote peers that sent inbound messages on the given network
+    pub fn remote_peers(&self, network_id: NetworkId) -> Vec<PeerId> {
+        let mut seen_peers = HashSet::new();
+        self.inbound_messages(network_id)
+            .map(|(_, captured_message)| captured_message.peer_id)
+            .filter(|peer_id| seen_peers.insert(*peer_id))
+            .collect()
+    }
+
+    /// Connects all remote peers (that sent inbound messages) to the node.
+    /// Each peer is given the specified role and supports the node's default protocols.
+    pub fn connect_remote_peers<Node: TestNode>(
+        &self,
+        node: &Node,
+        network_id: NetworkId,
+        peer_role: PeerRole,
+    ) {
+        for peer_id in self.remote_peers(network_id) {
+            let metadata = mock_conn_metadata(
+                PeerNetworkId::new(network_id, peer_id),
+                peer_role,
+                ConnectionOrigin::Inbound,
+                node.default_protocols(),
+            );
+            node.connect_self(network_id, metadata);
+        }
+    }
+
+    /// Replays all inbound messages on the given network against the node. If
+    /// `preserve_timing` is set, the captured delays between messages are
+    /// reproduced (using tokio time, so paused test runtimes remain deterministic).
+    /// Returns the response receivers for all replayed RPC requests (along
+    /// with the captured responses, so they can be compared).
+    pub async fn replay<Node: TestNode>(
+        &self,
+        node: &Node,
+        network_id: NetworkId,
+        preserve_timing: bool,
+    ) -> Vec<ReplayedRpcResponse> {
+        let inbound_handle = node.get_inbound_handle(network_id);
+
+        let mut rpc_responses = vec![];
+        let mut last_timestamp_usecs = None;
+        for (message_index, captured_message) in self.inbound_messages(network_id) {
+            // Wait for the captured delay (if required)
+            if preserve_timing {
+                if let Some(last_timestamp_usecs) = last_timestamp_usecs {
+                    let delay_usecs = captured_message
+                        .timestamp_usecs
+                        .saturating_sub(last_timestamp_usecs);
+                    tokio::time::sleep(Duration::from_micros(delay_usecs)).await;
+                }
+                last_timestamp_usecs = Some(captured_message.timestamp_usecs);
+            }
+
+            // Create the inbound message
+            let sender = PeerNetworkId::new(network_id, captured_message.peer_id);
+            let protocol_id = captured_message.protocol_id;
+            let (message, rpc_replier) = match captured_message.message_type {
+                CapturedMessageType::DirectSend => {
+                    let message = NetworkMessage::DirectSendMsg(DirectSendMsg {
+                        protocol_id,
+                        priority: 0,
+                        raw_msg: captured_message.data.clone(),
+                    });
+                    (message, None)
+                },
+                CapturedMessageType::RpcRequest(_) => {
+                    let message = NetworkMessage::RpcRequest(RpcRequest {
+                        protocol_id,
+                        request_id: rpc_responses.len() as u32,
+                        priority: 0,
+                        raw_request: captured_message.data.clone(),
+                    });
+                    let (response_sender, response_receiver) = oneshot::channel();
+                    rpc_responses.push(ReplayedRpcResponse {
+                        captured_message: captured_message.clone(),
+                        captured_response: find_rpc_response(
+                            &self.captured_messages,
+                            message_index,
+                        )
+                        .cloned(),
+                        response_receiver,
+                    });
+                    (message, Some(Arc::new(response_sender)))
+                },
+                CapturedMessageType::RpcResponse(_) => {
+                    unreachable!("Inbound RPC responses are never replayed!")
+                },
+            };
+            let received_message = ReceivedMessage {
+                message,
+                sender,
+                receive_timestamp_micros: 0,
+                rpc_replier,
+            };
+
+            // Send the message to the node
+            inbound_handle
+                .inbound_message_sender
+                .push((captured_message.peer_id, protocol_id), received_message)
+                .unwrap();
+        }
+
+        rpc_responses
+    }
+}
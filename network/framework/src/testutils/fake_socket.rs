@@ -9,6 +9,7 @@
 //!
 
 use aptos_memsocket::MemorySocket;
+use aptos_netcore::transport::MultiStreamSocket;
 use futures::{
     io::{AsyncRead, AsyncWrite},
     ready,
@@ -145,6 +146,8 @@ impl ReadOnlyTestSocketVec {
     }
 }
 
+impl MultiStreamSocket for ReadOnlyTestSocketVec {}
+
 /// Does nothing, but looks to the caller as if write worked
 impl AsyncWrite for ReadOnlyTestSocketVec {
     fn poll_write(
@@ -9,6 +9,7 @@ use aptos_types::PeerId;
 use std::sync::Arc;
 
 pub mod builder;
+pub mod capture_replay;
 pub mod fake_socket;
 pub mod test_framework;
 pub mod test_node;
@@ -6,8 +6,12 @@ use crate::{
     logging::NetworkSchema,
     noise::{stream::NoiseStream, AntiReplayTimestamps, HandshakeAuthMode, NoiseUpgrader},
     protocols::{
-        identity::exchange_handshake,
-        wire::handshake::v1::{HandshakeMsg, MessagingProtocolVersion, ProtocolIdSet},
+        identity::{
+            exchange_channel_binding, exchange_compression_dictionaries, exchange_handshake,
+        },
+        wire::handshake::v1::{
+            CompressionDictionariesMsg, HandshakeMsg, MessagingProtocolVersion, ProtocolIdSet,
+        },
     },
 };
 use aptos_config::{
@@ -19,12 +23,16 @@ use aptos_id_generator::{IdGenerator, U32IdGenerator};
 use aptos_logger::prelude::*;
 // Re-exposed for aptos-network-checker
 pub use aptos_netcore::transport::tcp::{resolve_and_connect, TCPBufferCfg, TcpSocket};
-use aptos_netcore::transport::{proxy_protocol, tcp, ConnectionOrigin, Transport};
+use aptos_netcore::transport::{
+    proxy_protocol, tcp, ConnectionOrigin, MultiStreamSocket, Transport,
+};
 use aptos_short_hex_str::AsShortHexStr;
 use aptos_time_service::{timeout, TimeService, TimeServiceTrait};
 use aptos_types::{
     chain_id::ChainId,
-    network_address::{parse_dns_tcp, parse_ip_tcp, parse_memory, NetworkAddress},
+    network_address::{
+        parse_dns_quic, parse_dns_tcp, parse_ip_quic, parse_ip_tcp, parse_memory, NetworkAddress,
+    },
     PeerId,
 };
 use futures::{
@@ -59,9 +67,15 @@ pub const APTOS_TCP_TRANSPORT: tcp::TcpTransport = tcp::TcpTransport {
 };
 
 /// A trait alias for "socket-like" things.
-pub trait TSocket: AsyncRead + AsyncWrite + Send + fmt::Debug + Unpin + 'static {}
+pub trait TSocket:
+    AsyncRead + AsyncWrite + MultiStreamSocket + Send + fmt::Debug + Unpin + 'static
+{
+}
 
-impl<T> TSocket for T where T: AsyncRead + AsyncWrite + Send + fmt::Debug + Unpin + 'static {}
+impl<T> TSocket for T where
+    T: AsyncRead + AsyncWrite + MultiStreamSocket + Send + fmt::Debug + Unpin + 'static
+{
+}
 
 /// Unique local identifier for a connection.
 #[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
@@ -242,6 +256,38 @@ fn add_pp_addr(proxy_protocol_enabled: bool, error: io::Error, addr: &NetworkAdd
     }
 }
 
+/// If the socket is carried by a QUIC connection, verify that both peers share
+/// the same QUIC (TLS) session by exchanging its channel binding over the Noise
+/// session. The additional QUIC streams of the connection are only protected by
+/// TLS (and not Noise), so this ensures they can be trusted to come from the
+/// authenticated remote peer (i.e., there is no man-in-the-middle on the connection).
+async fn verify_channel_binding<T: TSocket>(socket: &mut NoiseStream<T>) -> io::Result<()> {
+    match socket.quic_connection() {
+        Some(quic_connection) => {
+            let channel_binding = quic_connection.channel_binding()?;
+            exchange_channel_binding(&channel_binding, socket).await
+        },
+        None => Ok(()),
+    }
+}
+
+/// If a zstd protocol was negotiated with the peer, exchange the IDs of the
+/// zstd dictionaries that each peer can decompress, and return the protocols
+/// that can be used to send messages to the peer (i.e., zstd protocols are
+/// dropped if the peer can't decompress messages compressed with our dictionary).
+async fn negotiate_compression_dictionaries<T: TSocket>(
+    socket: &mut NoiseStream<T>,
+    application_protocols: ProtocolIdSet,
+) -> io::Result<ProtocolIdSet> {
+    if !CompressionDictionariesMsg::should_exchange(&application_protocols) {
+        return Ok(application_protocols);
+    }
+
+    let own_dictionaries = CompressionDictionariesMsg::from_registered_dictionaries();
+    let remote_dictionaries = exchange_compression_dictionaries(&own_dictionaries, socket).await?;
+    Ok(remote_dictionaries.filter_supported_protocols(application_protocols))
+}
+
 /// Upgrade an inbound connection. This means we run a Noise IK handshake for
 /// authentication and then negotiate common supported protocols. If
 /// `ctxt.noise.auth_mode` is `HandshakeAuthMode::Mutual( anti_replay_timestamps , trusted_peers )`,
@@ -305,6 +351,11 @@ async fn upgrade_inbound<T: TSocket>(
         .await
         .map_err(|err| add_pp_addr(proxy_protocol_enabled, err, &addr))?;
 
+    // bind the noise session to the underlying connection (if required)
+    verify_channel_binding(&mut socket)
+        .await
+        .map_err(|err| add_pp_addr(proxy_protocol_enabled, err, &addr))?;
+
     // try to negotiate common aptosnet version and supported application protocols
     let (messaging_protocol, application_protocols) = handshake_msg
         .perform_handshake(&remote_handshake)
@@ -321,6 +372,12 @@ async fn upgrade_inbound<T: TSocket>(
             )
         })?;
 
+    // advertise our compression dictionaries (if required)
+    let application_protocols =
+        negotiate_compression_dictionaries(&mut socket, application_protocols)
+            .await
+            .map_err(|err| add_pp_addr(proxy_protocol_enabled, err, &addr))?;
+
     // return successful connection
     Ok(Connection {
         socket,
@@ -385,6 +442,9 @@ pub async fn upgrade_outbound<T: TSocket>(
     };
     let remote_handshake = exchange_handshake(&handshake_msg, &mut socket).await?;
 
+    // bind the noise session to the underlying connection (if required)
+    verify_channel_binding(&mut socket).await?;
+
     // try to negotiate common aptosnet version and supported application protocols
     let (messaging_protocol, application_protocols) = handshake_msg
         .perform_handshake(&remote_handshake)
@@ -396,6 +456,10 @@ pub async fn upgrade_outbound<T: TSocket>(
             io::Error::new(io::ErrorKind::Other, e)
         })?;
 
+    // advertise our compression dictionaries (if required)
+    let application_protocols =
+        negotiate_compression_dictionaries(&mut socket, application_protocols).await?;
+
     // return successful connection
     Ok(Connection {
         socket,
@@ -415,7 +479,8 @@ pub async fn upgrade_outbound<T: TSocket>(
 ///
 /// The base transport layer is pluggable, so long as it provides a reliable,
 /// ordered, connection-oriented, byte-stream abstraction (e.g., TCP). We currently
-/// use either `MemoryTransport` or `TcpTransport` as this base layer.
+/// use either `MemoryTransport` or `IpTransport` (i.e., `TcpTransport` or `QuicTransport`,
+/// depending on the address) as this base layer.
 ///
 /// Inbound and outbound connections are first established with the `base_transport`
 /// and then negotiate a secure, authenticated transport layer (currently Noise
@@ -487,13 +552,15 @@ where
         let (base_transport_protos, base_transport_suffix) = parse_ip_tcp(protos)
             .map(|x| (&protos[..2], x.1))
             .or_else(|| parse_dns_tcp(protos).map(|x| (&protos[..2], x.1)))
+            .or_else(|| parse_ip_quic(protos).map(|x| (&protos[..2], x.1)))
+            .or_else(|| parse_dns_quic(protos).map(|x| (&protos[..2], x.1)))
             .or_else(|| parse_memory(protos).map(|x| (&protos[..1], x.1)))
             .ok_or_else(|| {
                 io::Error::new(
                     io::ErrorKind::InvalidInput,
                     format!(
                         "Unexpected dialing network address: '{}', expected: \
-                         memory, ip+tcp, or dns+tcp",
+                         memory, ip+tcp, dns+tcp, ip+quic, or dns+quic",
                         addr
                     ),
                 )
@@ -539,6 +606,10 @@ where
     /// `/dns/<ipaddr>/tcp/<port>` or
     /// `/dns4/<ipaddr>/tcp/<port>` or
     /// `/dns6/<ipaddr>/tcp/<port>`
+    ///
+    /// If the base transport is `QuicTransport`, then `/<base_transport>` is
+    /// the same as for `TcpTransport`, but with `/quic/<port>` instead of `/tcp/<port>`.
+    /// If the base transport is `IpTransport`, then `/<base_transport>` can be either.
     pub fn dial(
         &self,
         peer_id: PeerId,
@@ -585,6 +656,13 @@ where
     ///
     /// `/ip4/<ipaddr>/tcp/<port>` or
     /// `/ip6/<ipaddr>/tcp/<port>`
+    ///
+    /// If the base transport is `QuicTransport`, then we expect:
+    ///
+    /// `/ip4/<ipaddr>/quic/<port>` or
+    /// `/ip6/<ipaddr>/quic/<port>`
+    ///
+    /// If the base transport is `IpTransport`, then we expect either.
     pub fn listen_on(
         &self,
         addr: NetworkAddress,
@@ -12,7 +12,7 @@ use aptos_config::config::{Peer, PeerRole, PeerSet, HANDSHAKE_VERSION};
 use aptos_crypto::{test_utils::TEST_SEED, traits::Uniform, x25519, x25519::PrivateKey};
 use aptos_netcore::{
     framing::{read_u16frame, write_u16frame},
-    transport::{memory, ConnectionOrigin, Transport},
+    transport::{ip::IpTransport, memory, ConnectionOrigin, Transport},
 };
 use aptos_time_service::MockTimeService;
 use aptos_types::{
@@ -223,6 +223,16 @@ fn expect_ip4_tcp_noise_addr(addr: &NetworkAddress) {
     );
 }
 
+/// Check that the network address matches the format
+/// `"/ip4/<ipaddr>/quic/<port>/noise-ik/<pubkey>/handshake/<version>"`
+fn expect_ip4_quic_noise_addr(addr: &NetworkAddress) {
+    assert!(
+        matches!(addr.as_slice(), [Ip4(_), Quic(_), NoiseIK(_), Handshake(_)]),
+        "addr: '{}'",
+        addr
+    );
+}
+
 fn test_transport_success<TTransport>(
     base_transport: TTransport,
     auth: Auth,
@@ -587,6 +597,49 @@ fn test_tcp_transport_rejects_unauthed_dialer() {
     );
 }
 
+////////////////////////////////////////////////////
+// AptosNetTransport<IpTransport> (TCP and QUIC)  //
+////////////////////////////////////////////////////
+
+#[test]
+fn test_ip_transport_tcp_mutual_auth() {
+    test_transport_success(
+        IpTransport::default(),
+        Auth::Mutual,
+        "/ip4/127.0.0.1/tcp/0",
+        expect_ip4_tcp_noise_addr,
+    );
+}
+
+#[test]
+fn test_ip_transport_quic_mutual_auth() {
+    test_transport_success(
+        IpTransport::default(),
+        Auth::Mutual,
+        "/ip4/127.0.0.1/quic/0",
+        expect_ip4_quic_noise_addr,
+    );
+}
+
+#[test]
+fn test_ip_transport_quic_server_only_auth() {
+    test_transport_success(
+        IpTransport::default(),
+        Auth::ServerOnly,
+        "/ip4/127.0.0.1/quic/0",
+        expect_ip4_quic_noise_addr,
+    );
+}
+
+#[test]
+fn test_ip_transport_quic_rejects_unauthed_dialer() {
+    test_transport_rejects_unauthed_dialer(
+        IpTransport::default(),
+        "/ip4/127.0.0.1/quic/0",
+        expect_ip4_quic_noise_addr,
+    );
+}
+
 /// Inserts the given peers into the trusted peer set for the specified network
 fn insert_trusted_peers(
     peers_and_metadata: &Arc<PeersAndMetadata>,
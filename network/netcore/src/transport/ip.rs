@@ -0,0 +1,211 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! IP Transport
+//!
+//! Connections are established over TCP or QUIC, as determined by the network
+//! address being listened on (or dialed). This allows a node to listen on a
+//! single transport, while still being able to dial peers that advertise
+//! addresses on the other (e.g., a TCP node can dial `/ip4/<addr>/quic/<port>`).
+use crate::transport::{
+    quic::{QuicConnection, QuicSocket, QuicTransport},
+    tcp::{TcpSocket, TcpTransport},
+    MultiStreamSocket, Transport,
+};
+use aptos_types::{
+    network_address::{parse_dns_quic, parse_ip_quic, NetworkAddress},
+    PeerId,
+};
+use futures::{
+    future::{Future, FutureExt, TryFutureExt},
+    io::{AsyncRead, AsyncWrite},
+    stream::{Stream, StreamExt, TryStreamExt},
+};
+use std::{
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Transport to build TCP or QUIC connections (depending on the address)
+#[derive(Debug, Clone, Default)]
+pub struct IpTransport {
+    pub tcp_transport: TcpTransport,
+    pub quic_transport: QuicTransport,
+}
+
+impl IpTransport {
+    pub fn new(tcp_transport: TcpTransport, quic_transport: QuicTransport) -> Self {
+        Self {
+            tcp_transport,
+            quic_transport,
+        }
+    }
+}
+
+/// Returns true iff the given address is a QUIC address (i.e., ip+quic or dns+quic)
+fn is_quic_address(addr: &NetworkAddress) -> bool {
+    let protos = addr.as_slice();
+    parse_ip_quic(protos).is_some() || parse_dns_quic(protos).is_some()
+}
+
+impl Transport for IpTransport {
+    type Error = ::std::io::Error;
+    type Inbound = Pin<Box<dyn Future<Output = io::Result<IpSocket>> + Send + 'static>>;
+    type Listener =
+        Pin<Box<dyn Stream<Item = io::Result<(Self::Inbound, NetworkAddress)>> + Send + 'static>>;
+    type Outbound = Pin<Box<dyn Future<Output = io::Result<IpSocket>> + Send + 'static>>;
+    type Output = IpSocket;
+
+    fn listen_on(
+        &self,
+        addr: NetworkAddress,
+    ) -> Result<(Self::Listener, NetworkAddress), Self::Error> {
+        if is_quic_address(&addr) {
+            let (listener, listen_addr) = self.quic_transport.listen_on(addr)?;
+            let listener = listener
+                .map_ok(|(inbound, dialer_addr)| {
+                    let inbound: Self::Inbound = inbound.map_ok(IpSocket::Quic).boxed();
+                    (inbound, dialer_addr)
+                })
+                .boxed();
+            Ok((listener, listen_addr))
+        } else {
+            let (listener, listen_addr) = self.tcp_transport.listen_on(addr)?;
+            let listener = listener
+                .map_ok(|(inbound, dialer_addr)| {
+                    let inbound: Self::Inbound = inbound.map_ok(IpSocket::Tcp).boxed();
+                    (inbound, dialer_addr)
+                })
+                .boxed();
+            Ok((listener, listen_addr))
+        }
+    }
+
+    fn dial(&self, peer_id: PeerId, addr: NetworkAddress) -> Result<Self::Outbound, Self::Error> {
+        if is_quic_address(&addr) {
+            let outbound = self.quic_transport.dial(peer_id, addr)?;
+            Ok(outbound.map_ok(IpSocket::Quic).boxed())
+        } else {
+            let outbound = self.tcp_transport.dial(peer_id, addr)?;
+            Ok(outbound.map_ok(IpSocket::Tcp).boxed())
+        }
+    }
+}
+
+/// A socket over either a TCP or a QUIC connection
+#[derive(Debug)]
+pub enum IpSocket {
+    Tcp(TcpSocket),
+    Quic(QuicSocket),
+}
+
+impl MultiStreamSocket for IpSocket {
+    fn quic_connection(&self) -> Option<QuicConnection> {
+        match self {
+            IpSocket::Tcp(socket) => socket.quic_connection(),
+            IpSocket::Quic(socket) => socket.quic_connection(),
+        }
+    }
+}
+
+impl AsyncRead for IpSocket {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        context: &mut Context,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            IpSocket::Tcp(socket) => Pin::new(socket).poll_read(context, buf),
+            IpSocket::Quic(socket) => Pin::new(socket).poll_read(context, buf),
+        }
+    }
+}
+
+impl AsyncWrite for IpSocket {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        context: &mut Context,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            IpSocket::Tcp(socket) => Pin::new(socket).poll_write(context, buf),
+            IpSocket::Quic(socket) => Pin::new(socket).poll_write(context, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, context: &mut Context) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            IpSocket::Tcp(socket) => Pin::new(socket).poll_flush(context),
+            IpSocket::Quic(socket) => Pin::new(socket).poll_flush(context),
+        }
+    }
+
+    fn poll_close(self: Pin<&mut Self>, context: &mut Context) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            IpSocket::Tcp(socket) => Pin::new(socket).poll_close(context),
+            IpSocket::Quic(socket) => Pin::new(socket).poll_close(context),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::{
+        future::join,
+        io::{AsyncReadExt, AsyncWriteExt},
+    };
+
+    /// Listens on the given address, dials it (with a different transport
+    /// instance) and verifies that data can be exchanged.
+    async fn listen_and_dial(listen_addr: &str) -> Result<(), ::std::io::Error> {
+        let listener_transport = IpTransport::default();
+        let dialer_transport = IpTransport::default();
+        let (listener, addr) = listener_transport.listen_on(listen_addr.parse().unwrap())?;
+        let dial = async move {
+            let mut outbound = dialer_transport.dial(PeerId::random(), addr)?.await?;
+            outbound.write_all(b"Earth").await?;
+            outbound.flush().await?;
+            let mut buf = [0; 3];
+            outbound.read_exact(&mut buf).await?;
+            assert_eq!(&buf, b"Air");
+            Ok::<_, io::Error>(outbound)
+        };
+        let listen = async move {
+            let (inbound, _addr) = listener.into_future().await.0.unwrap()?;
+            let mut inbound = inbound.await?;
+            let mut buf = [0; 5];
+            inbound.read_exact(&mut buf).await?;
+            assert_eq!(&buf, b"Earth");
+            inbound.write_all(b"Air").await?;
+            inbound.flush().await?;
+            Ok::<_, io::Error>(inbound)
+        };
+
+        let (outbound, inbound) = join(dial, listen).await;
+        let (outbound, inbound) = (outbound?, inbound?);
+        assert_eq!(
+            outbound.quic_connection().is_some(),
+            inbound.quic_connection().is_some()
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn listen_and_dial_tcp_and_quic() -> Result<(), ::std::io::Error> {
+        listen_and_dial("/ip4/127.0.0.1/tcp/0").await?;
+        listen_and_dial("/ip4/127.0.0.1/quic/0").await
+    }
+
+    #[test]
+    fn unsupported_multiaddrs() {
+        let t = IpTransport::default();
+
+        let result = t.listen_on("/memory/0".parse().unwrap());
+        assert!(result.is_err());
+
+        let result = t.dial(PeerId::random(), "/memory/22".parse().unwrap());
+        assert!(result.is_err());
+    }
+}
@@ -12,16 +12,20 @@
 //! [`Transport`]: crate::transport::Transport
 //! [`TransportExt`]: crate::transport::TransportExt
 
+use aptos_memsocket::MemorySocket;
 use aptos_types::{network_address::NetworkAddress, PeerId};
 use futures::{future::Future, stream::Stream};
+use quic::QuicConnection;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
 pub mod and_then;
 pub mod boxed;
+pub mod ip;
 #[cfg(any(test, feature = "testing", feature = "fuzzing"))]
 pub mod memory;
 pub mod proxy_protocol;
+pub mod quic;
 pub mod tcp;
 
 /// Origin of how a Connection was established.
@@ -115,6 +119,19 @@ pub trait Transport {
         Self: Sized;
 }
 
+/// A socket whose connection may carry additional, independent streams to the
+/// remote peer (e.g., the streams of a QUIC connection). This allows independent
+/// messages to be written to different streams (avoiding head-of-line blocking).
+pub trait MultiStreamSocket {
+    /// Returns the QUIC connection carrying the socket, or `None` if the
+    /// connection doesn't support additional streams (e.g., for TCP).
+    fn quic_connection(&self) -> Option<QuicConnection> {
+        None
+    }
+}
+
+impl MultiStreamSocket for MemorySocket {}
+
 impl<T: ?Sized> TransportExt for T where T: Transport {}
 
 /// An extension trait for [`Transport`]s that provides a variety of convenient
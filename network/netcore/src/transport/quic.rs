@@ -0,0 +1,647 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! QUIC Transport
+//!
+//! Connections are established over QUIC (on top of UDP). The first bidirectional
+//! stream of a connection is exposed as the connection socket (see [`QuicSocket`]).
+//! Additional, unidirectional streams can be opened on the same connection (see
+//! [`QuicConnection`]), so that independent messages are not blocked behind each
+//! other (i.e., there is no head-of-line blocking between streams).
+//!
+//! Note: QUIC requires TLS, so each listener uses an ephemeral, self-signed
+//! certificate and dialers do not verify it. Peer identity is not established
+//! by TLS, but by the Noise handshake that is layered on top of the connection
+//! socket (exactly as for TCP). The additional streams are only protected by TLS,
+//! so the Noise session must be bound to the TLS session before they are trusted
+//! (see [`QuicConnection::channel_binding`]).
+use crate::transport::{tcp::resolve_with_filter, MultiStreamSocket, Transport};
+use aptos_types::{
+    network_address::{parse_dns_quic, parse_ip_quic, NetworkAddress, Protocol},
+    PeerId,
+};
+use futures::{
+    future::Future,
+    io::{AsyncRead, AsyncWrite},
+    stream::{self, Stream, StreamExt},
+};
+use quinn::{
+    crypto::rustls::{QuicClientConfig, QuicServerConfig},
+    rustls::{
+        self,
+        client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+        crypto::CryptoProvider,
+        pki_types::{CertificateDer, PrivatePkcs8KeyDer, ServerName, UnixTime},
+        DigitallySignedStruct, SignatureScheme,
+    },
+    ClientConfig, Connection, ConnectionError, Endpoint, IdleTimeout, RecvStream, SendStream,
+    ServerConfig, TransportConfig, VarInt,
+};
+use std::{
+    io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio_util::compat::{Compat, TokioAsyncReadCompatExt, TokioAsyncWriteCompatExt};
+
+/// The ALPN protocol identifier negotiated by all AptosNet QUIC connections
+const APTOSNET_ALPN: &[u8] = b"aptosnet";
+
+/// The (unverified) server name used by dialers and in the self-signed certificates
+const SERVER_NAME: &str = "aptosnet";
+
+/// The default time after which an idle connection is closed
+const DEFAULT_MAX_IDLE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The default interval at which keep-alives are sent on idle connections
+const DEFAULT_KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The maximum number of unidirectional streams the remote peer can open
+/// concurrently on a connection (in addition to the connection socket).
+pub const MAX_CONCURRENT_UNI_STREAMS: u32 = 16;
+
+/// The length of the channel binding of each connection (see [`QuicConnection::channel_binding`])
+pub const CHANNEL_BINDING_LENGTH: usize = 32;
+
+/// The TLS exporter label used to derive the channel binding of each connection
+const CHANNEL_BINDING_LABEL: &[u8] = b"EXPORTER-aptosnet-channel-binding";
+
+/// Transport to build QUIC connections
+#[derive(Debug, Clone)]
+pub struct QuicTransport {
+    /// The time after which an idle connection is closed
+    pub max_idle_timeout: Duration,
+    /// The interval at which keep-alives are sent on idle connections
+    pub keep_alive_interval: Duration,
+    /// The client endpoints shared by all dials (and all clones of the transport)
+    client_endpoints: Arc<Mutex<ClientEndpoints>>,
+}
+
+impl Default for QuicTransport {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_IDLE_TIMEOUT, DEFAULT_KEEP_ALIVE_INTERVAL)
+    }
+}
+
+impl QuicTransport {
+    pub fn new(max_idle_timeout: Duration, keep_alive_interval: Duration) -> Self {
+        Self {
+            max_idle_timeout,
+            keep_alive_interval,
+            client_endpoints: Arc::new(Mutex::new(ClientEndpoints::default())),
+        }
+    }
+
+    /// Creates the QUIC transport config shared by listeners and dialers
+    fn transport_config(&self) -> io::Result<Arc<TransportConfig>> {
+        let max_idle_timeout = IdleTimeout::try_from(self.max_idle_timeout)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+
+        let mut transport_config = TransportConfig::default();
+        transport_config
+            .max_idle_timeout(Some(max_idle_timeout))
+            .keep_alive_interval(Some(self.keep_alive_interval))
+            .max_concurrent_bidi_streams(VarInt::from_u32(1))
+            .max_concurrent_uni_streams(VarInt::from_u32(MAX_CONCURRENT_UNI_STREAMS));
+        Ok(Arc::new(transport_config))
+    }
+
+    /// Creates the server config (with an ephemeral, self-signed certificate)
+    fn server_config(&self) -> io::Result<ServerConfig> {
+        let certificate = rcgen::generate_simple_self_signed(vec![SERVER_NAME.into()])
+            .map_err(|error| io::Error::new(io::ErrorKind::Other, error))?;
+        let certificate_der = certificate
+            .serialize_der()
+            .map_err(|error| io::Error::new(io::ErrorKind::Other, error))?;
+        let private_key_der = PrivatePkcs8KeyDer::from(certificate.serialize_private_key_der());
+
+        let mut crypto = rustls::ServerConfig::builder_with_provider(crypto_provider())
+            .with_protocol_versions(&[&rustls::version::TLS13])
+            .map_err(tls_error)?
+            .with_no_client_auth()
+            .with_single_cert(
+                vec![CertificateDer::from(certificate_der)],
+                private_key_der.into(),
+            )
+            .map_err(tls_error)?;
+        crypto.alpn_protocols = vec![APTOSNET_ALPN.to_vec()];
+
+        let crypto = QuicServerConfig::try_from(crypto)
+            .map_err(|error| io::Error::new(io::ErrorKind::Other, error))?;
+        let mut server_config = ServerConfig::with_crypto(Arc::new(crypto));
+        server_config.transport_config(self.transport_config()?);
+        Ok(server_config)
+    }
+
+    /// Creates the client config (which skips server certificate verification)
+    fn client_config(&self) -> io::Result<ClientConfig> {
+        let crypto_provider = crypto_provider();
+        let mut crypto = rustls::ClientConfig::builder_with_provider(crypto_provider.clone())
+            .with_protocol_versions(&[&rustls::version::TLS13])
+            .map_err(tls_error)?
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(SkipServerVerification(crypto_provider)))
+            .with_no_client_auth();
+        crypto.alpn_protocols = vec![APTOSNET_ALPN.to_vec()];
+
+        let crypto = QuicClientConfig::try_from(crypto)
+            .map_err(|error| io::Error::new(io::ErrorKind::Other, error))?;
+        let mut client_config = ClientConfig::new(Arc::new(crypto));
+        client_config.transport_config(self.transport_config()?);
+        Ok(client_config)
+    }
+}
+
+impl Transport for QuicTransport {
+    type Error = ::std::io::Error;
+    type Inbound = Pin<Box<dyn Future<Output = io::Result<QuicSocket>> + Send + 'static>>;
+    type Listener =
+        Pin<Box<dyn Stream<Item = io::Result<(Self::Inbound, NetworkAddress)>> + Send + 'static>>;
+    type Outbound = Pin<Box<dyn Future<Output = io::Result<QuicSocket>> + Send + 'static>>;
+    type Output = QuicSocket;
+
+    fn listen_on(
+        &self,
+        addr: NetworkAddress,
+    ) -> Result<(Self::Listener, NetworkAddress), Self::Error> {
+        let ((ipaddr, port), addr_suffix) =
+            parse_ip_quic(addr.as_slice()).ok_or_else(|| invalid_addr_error(&addr))?;
+        if !addr_suffix.is_empty() {
+            return Err(invalid_addr_error(&addr));
+        }
+
+        let endpoint = Endpoint::server(self.server_config()?, SocketAddr::new(ipaddr, port))?;
+        let listen_addr = quic_network_address(endpoint.local_addr()?);
+
+        // Note: the endpoint is moved into the stream, so that it remains open
+        // for as long as the listener is alive.
+        let listener = stream::unfold(endpoint, |endpoint| async move {
+            let incoming = endpoint.accept().await?;
+            let dialer_addr = quic_network_address(incoming.remote_address());
+            let inbound: Self::Inbound = Box::pin(async move {
+                let connection = incoming.await.map_err(connection_error)?;
+                let (send, recv) = connection.accept_bi().await.map_err(connection_error)?;
+                Ok(QuicSocket::new(connection, send, recv))
+            });
+            Some((Ok((inbound, dialer_addr)), endpoint))
+        })
+        .boxed();
+
+        Ok((listener, listen_addr))
+    }
+
+    fn dial(&self, _peer_id: PeerId, addr: NetworkAddress) -> Result<Self::Outbound, Self::Error> {
+        let protos = addr.as_slice();
+
+        // ensure addr is well formed to save some work before potentially
+        // spawning a dial task that will fail anyway.
+        parse_ip_quic(protos)
+            .map(|_| ())
+            .or_else(|| parse_dns_quic(protos).map(|_| ()))
+            .ok_or_else(|| invalid_addr_error(&addr))?;
+
+        Ok(Box::pin(resolve_and_connect(addr, self.clone())))
+    }
+}
+
+/// The client endpoints used to dial connections (one per address family).
+/// Each endpoint is created on the first dial and is then reused by all
+/// subsequent dials (so that dials don't each bind a new UDP socket).
+#[derive(Debug, Default)]
+struct ClientEndpoints {
+    ipv4: Option<Endpoint>,
+    ipv6: Option<Endpoint>,
+}
+
+impl QuicTransport {
+    /// Returns the client endpoint for the address family of the given socket
+    /// address (creating the endpoint if it doesn't exist yet).
+    fn get_client_endpoint(&self, socket_addr: &SocketAddr) -> io::Result<Endpoint> {
+        let mut client_endpoints = self
+            .client_endpoints
+            .lock()
+            .expect("The client endpoints lock should not be poisoned");
+        let (client_endpoint, bind_addr) = if socket_addr.is_ipv4() {
+            (
+                &mut client_endpoints.ipv4,
+                SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0),
+            )
+        } else {
+            (
+                &mut client_endpoints.ipv6,
+                SocketAddr::new(IpAddr::V6(Ipv6Addr::UNSPECIFIED), 0),
+            )
+        };
+
+        if let Some(client_endpoint) = client_endpoint {
+            return Ok(client_endpoint.clone());
+        }
+
+        // Bind a new endpoint to an ephemeral port of the same address family
+        let mut endpoint = Endpoint::client(bind_addr)?;
+        endpoint.set_default_client_config(self.client_config()?);
+        *client_endpoint = Some(endpoint.clone());
+        Ok(endpoint)
+    }
+}
+
+/// Note: we need to take ownership of this `NetworkAddress` (instead of just
+/// borrowing the `&[Protocol]` slice) so this future can be `Send + 'static`.
+async fn resolve_and_connect(
+    addr: NetworkAddress,
+    transport: QuicTransport,
+) -> io::Result<QuicSocket> {
+    let protos = addr.as_slice();
+
+    if let Some(((ipaddr, port), _addr_suffix)) = parse_ip_quic(protos) {
+        // this is an /ip4 or /ip6 address, so we can just connect without any
+        // extra resolving or filtering.
+        connect(SocketAddr::new(ipaddr, port), &transport).await
+    } else if let Some(((ip_filter, dns_name, port), _addr_suffix)) = parse_dns_quic(protos) {
+        // resolve dns name and filter
+        let socketaddr_iter = resolve_with_filter(ip_filter, dns_name.as_ref(), port).await?;
+        let mut last_err = None;
+
+        // try to connect until the first succeeds
+        for socketaddr in socketaddr_iter {
+            match connect(socketaddr, &transport).await {
+                Ok(socket) => return Ok(socket),
+                Err(err) => last_err = Some(err),
+            }
+        }
+
+        Err(last_err.unwrap_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "could not resolve dns name to any address: name: {}, ip filter: {:?}",
+                    dns_name.as_ref(),
+                    ip_filter,
+                ),
+            )
+        }))
+    } else {
+        Err(invalid_addr_error(&addr))
+    }
+}
+
+/// Connects to the given socket address (using the shared client endpoint)
+/// and opens the connection socket.
+async fn connect(socket_addr: SocketAddr, transport: &QuicTransport) -> io::Result<QuicSocket> {
+    let endpoint = transport.get_client_endpoint(&socket_addr)?;
+
+    // Establish the connection and open the first bidirectional stream.
+    // Note: the stream is only visible to the listener once the dialer
+    // writes to it (i.e., when the Noise handshake begins).
+    let connection = endpoint
+        .connect(socket_addr, SERVER_NAME)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?
+        .await
+        .map_err(connection_error)?;
+    let (send, recv) = connection.open_bi().await.map_err(connection_error)?;
+    Ok(QuicSocket::new(connection, send, recv))
+}
+
+/// Returns the `/ip4/<addr>/quic/<port>` or `/ip6/<addr>/quic/<port>` network address
+fn quic_network_address(socket_addr: SocketAddr) -> NetworkAddress {
+    NetworkAddress::from_protocols(vec![
+        Protocol::from(socket_addr.ip()),
+        Protocol::Quic(socket_addr.port()),
+    ])
+    .expect("An IP and QUIC protocol should always be a valid network address")
+}
+
+/// Returns the crypto provider used by all QUIC connections
+fn crypto_provider() -> Arc<CryptoProvider> {
+    Arc::new(rustls::crypto::ring::default_provider())
+}
+
+fn connection_error(error: ConnectionError) -> io::Error {
+    let error_kind = match error {
+        ConnectionError::TimedOut => io::ErrorKind::TimedOut,
+        ConnectionError::ConnectionClosed(_)
+        | ConnectionError::ApplicationClosed(_)
+        | ConnectionError::LocallyClosed => io::ErrorKind::ConnectionAborted,
+        ConnectionError::Reset => io::ErrorKind::ConnectionReset,
+        _ => io::ErrorKind::Other,
+    };
+    io::Error::new(error_kind, error)
+}
+
+fn tls_error(error: rustls::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, error)
+}
+
+fn invalid_addr_error(addr: &NetworkAddress) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("Invalid NetworkAddress: '{}'", addr),
+    )
+}
+
+/// A server certificate verifier that accepts any certificate. This is safe
+/// because the peer identity is authenticated by the Noise handshake that runs
+/// on top of the connection socket, and not by TLS. The handshake signatures
+/// are still verified (to ensure the TLS handshake itself is well-formed).
+#[derive(Debug)]
+struct SkipServerVerification(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for SkipServerVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+/// The sending half of an additional (unidirectional) QUIC stream
+pub type QuicSendStream = Compat<SendStream>;
+
+/// The receiving half of an additional (unidirectional) QUIC stream
+pub type QuicRecvStream = Compat<RecvStream>;
+
+/// A handle to a QUIC connection, used to open (and accept) additional
+/// unidirectional streams to (and from) the remote peer.
+#[derive(Clone, Debug)]
+pub struct QuicConnection {
+    connection: Connection,
+}
+
+impl QuicConnection {
+    pub fn new(connection: Connection) -> Self {
+        Self { connection }
+    }
+
+    /// Opens a new unidirectional stream to the remote peer. Note: the stream
+    /// is only visible to the remote peer once data is written to it.
+    pub async fn open_stream(&self) -> io::Result<QuicSendStream> {
+        let send = self.connection.open_uni().await.map_err(connection_error)?;
+        Ok(send.compat_write())
+    }
+
+    /// Accepts the next unidirectional stream opened by the remote peer
+    pub async fn accept_stream(&self) -> io::Result<QuicRecvStream> {
+        let recv = self
+            .connection
+            .accept_uni()
+            .await
+            .map_err(connection_error)?;
+        Ok(recv.compat())
+    }
+
+    /// Returns the channel binding of the connection, i.e., a value derived from
+    /// the TLS session (using a TLS exporter) that is identical for both peers iff
+    /// they share the same TLS session. Exchanging (and comparing) the channel
+    /// binding over an authenticated channel (e.g., the Noise session on the
+    /// connection socket) ensures there is no man-in-the-middle on the connection.
+    pub fn channel_binding(&self) -> io::Result<[u8; CHANNEL_BINDING_LENGTH]> {
+        let mut channel_binding = [0u8; CHANNEL_BINDING_LENGTH];
+        self.connection
+            .export_keying_material(&mut channel_binding, CHANNEL_BINDING_LABEL, &[])
+            .map_err(|error| {
+                io::Error::new(
+                    io::ErrorKind::Other,
+                    format!("Failed to export the channel binding: {:?}", error),
+                )
+            })?;
+        Ok(channel_binding)
+    }
+}
+
+/// A socket over a single bidirectional QUIC stream
+///
+/// The socket holds the underlying QUIC connection, so that the connection
+/// stays open for as long as the socket is alive. Additional streams can be
+/// opened on the same connection via [`MultiStreamSocket::quic_connection`].
+#[derive(Debug)]
+pub struct QuicSocket {
+    connection: QuicConnection,
+    send: Compat<SendStream>,
+    recv: Compat<RecvStream>,
+}
+
+impl QuicSocket {
+    pub fn new(connection: Connection, send: SendStream, recv: RecvStream) -> Self {
+        Self {
+            connection: QuicConnection::new(connection),
+            send: send.compat_write(),
+            recv: recv.compat(),
+        }
+    }
+}
+
+impl MultiStreamSocket for QuicSocket {
+    fn quic_connection(&self) -> Option<QuicConnection> {
+        Some(self.connection.clone())
+    }
+}
+
+impl AsyncRead for QuicSocket {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        context: &mut Context,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.recv).poll_read(context, buf)
+    }
+}
+
+impl AsyncWrite for QuicSocket {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        context: &mut Context,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.send).poll_write(context, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, context: &mut Context) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.send).poll_flush(context)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, context: &mut Context) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.send).poll_close(context)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::transport::{ConnectionOrigin, MultiStreamSocket, Transport, TransportExt};
+    use aptos_types::PeerId;
+    use futures::{
+        future::{join, FutureExt},
+        io::{AsyncReadExt, AsyncWriteExt},
+        stream::StreamExt,
+    };
+
+    #[tokio::test]
+    async fn simple_listen_and_dial() -> Result<(), ::std::io::Error> {
+        let t = QuicTransport::default().and_then(|mut out, _addr, origin| async move {
+            match origin {
+                ConnectionOrigin::Inbound => {
+                    let mut buf = [0; 5];
+                    out.read_exact(&mut buf).await?;
+                    assert_eq!(&buf, b"Earth");
+                    out.write_all(b"Air").await?;
+                    out.flush().await?;
+                },
+                ConnectionOrigin::Outbound => {
+                    // Note: the dialer must write first (as with the Noise handshake)
+                    out.write_all(b"Earth").await?;
+                    out.flush().await?;
+                    let mut buf = [0; 3];
+                    out.read_exact(&mut buf).await?;
+                    assert_eq!(&buf, b"Air");
+                },
+            }
+            Ok(out)
+        });
+
+        let (listener, addr) = t.listen_on("/ip4/127.0.0.1/quic/0".parse().unwrap())?;
+        let peer_id = PeerId::random();
+        let dial = t.dial(peer_id, addr)?;
+        let listener = listener.into_future().then(|(maybe_result, _stream)| {
+            let (incoming, _addr) = maybe_result.unwrap().unwrap();
+            incoming.map(Result::unwrap)
+        });
+
+        let (outgoing, _incoming) = join(dial, listener).await;
+        assert!(outgoing.is_ok());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn dials_share_client_endpoint() -> Result<(), ::std::io::Error> {
+        let t = QuicTransport::default();
+        let (listener, addr) = t.listen_on("/ip4/127.0.0.1/quic/0".parse().unwrap())?;
+        let mut listener = listener.fuse();
+
+        // Dial the listener twice (writing to each socket so that the
+        // connection sockets are accepted by the listener).
+        let mut local_addrs = vec![];
+        for _ in 0..2 {
+            let mut outbound = t.dial(PeerId::random(), addr.clone())?.await?;
+            outbound.write_all(b"Earth").await?;
+            outbound.flush().await?;
+            let (inbound, _addr) = listener.next().await.unwrap()?;
+            inbound.await?;
+
+            let client_endpoint = t.get_client_endpoint(&"127.0.0.1:0".parse().unwrap())?;
+            local_addrs.push(client_endpoint.local_addr()?);
+        }
+
+        // Verify both dials used the same client endpoint (and it was reused by clones)
+        assert_eq!(local_addrs[0], local_addrs[1]);
+        let client_endpoint = t
+            .clone()
+            .get_client_endpoint(&"127.0.0.1:0".parse().unwrap())?;
+        assert_eq!(client_endpoint.local_addr()?, local_addrs[0]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn additional_streams_and_channel_binding() -> Result<(), ::std::io::Error> {
+        let t = QuicTransport::default();
+        let (listener, addr) = t.listen_on("/ip4/127.0.0.1/quic/0".parse().unwrap())?;
+
+        // Establish the connection (the dialer writes first to open the socket)
+        let mut outbound = t.dial(PeerId::random(), addr)?.await?;
+        outbound.write_all(b"Earth").await?;
+        outbound.flush().await?;
+        let (inbound, _addr) = listener.into_future().await.0.unwrap()?;
+        let mut inbound = inbound.await?;
+        let mut buf = [0; 5];
+        inbound.read_exact(&mut buf).await?;
+        assert_eq!(&buf, b"Earth");
+
+        // Verify both peers derive the same channel binding
+        let outbound_connection = outbound.quic_connection().unwrap();
+        let inbound_connection = inbound.quic_connection().unwrap();
+        assert_eq!(
+            outbound_connection.channel_binding()?,
+            inbound_connection.channel_binding()?
+        );
+
+        // Open additional streams in both directions and verify they are independent
+        let mut outbound_stream_1 = outbound_connection.open_stream().await?;
+        let mut outbound_stream_2 = outbound_connection.open_stream().await?;
+        outbound_stream_2.write_all(b"Fire").await?;
+        outbound_stream_2.flush().await?;
+        outbound_stream_1.write_all(b"Water").await?;
+        outbound_stream_1.flush().await?;
+
+        let mut inbound_stream_1 = inbound_connection.accept_stream().await?;
+        let mut inbound_stream_2 = inbound_connection.accept_stream().await?;
+        let mut buf = [0; 5];
+        inbound_stream_1.read_exact(&mut buf).await?;
+        assert_eq!(&buf, b"Water");
+        let mut buf = [0; 4];
+        inbound_stream_2.read_exact(&mut buf).await?;
+        assert_eq!(&buf, b"Fire");
+
+        let mut inbound_stream = inbound_connection.open_stream().await?;
+        inbound_stream.write_all(b"Air").await?;
+        inbound_stream.flush().await?;
+        let mut outbound_stream = outbound_connection.accept_stream().await?;
+        let mut buf = [0; 3];
+        outbound_stream.read_exact(&mut buf).await?;
+        assert_eq!(&buf, b"Air");
+        Ok(())
+    }
+
+    #[test]
+    fn unsupported_multiaddrs() {
+        let t = QuicTransport::default();
+
+        let result = t.listen_on("/ip4/127.0.0.1/tcp/0".parse().unwrap());
+        assert!(result.is_err());
+
+        let peer_id = PeerId::random();
+        let result = t.dial(peer_id, "/ip4/127.0.0.1/tcp/22".parse().unwrap());
+        assert!(result.is_err());
+
+        let result = t.dial(peer_id, "/memory/22".parse().unwrap());
+        assert!(result.is_err());
+    }
+}
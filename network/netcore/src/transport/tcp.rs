@@ -3,7 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 //! TCP Transport
-use crate::transport::Transport;
+use crate::transport::{MultiStreamSocket, Transport};
 use aptos_proxy::Proxy;
 use aptos_types::{
     network_address::{parse_dns_tcp, parse_ip_tcp, parse_tcp, IpFilter, NetworkAddress},
@@ -187,7 +187,7 @@ impl Transport for TcpTransport {
 }
 
 /// Try to lookup the dns name, then filter addrs according to the `IpFilter`.
-async fn resolve_with_filter(
+pub(crate) async fn resolve_with_filter(
     ip_filter: IpFilter,
     dns_name: &str,
     port: u16,
@@ -379,6 +379,8 @@ impl TcpSocket {
     }
 }
 
+impl MultiStreamSocket for TcpSocket {}
+
 impl AsyncRead for TcpSocket {
     fn poll_read(
         mut self: Pin<&mut Self>,
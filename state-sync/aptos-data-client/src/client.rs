@@ -13,6 +13,7 @@ use crate::{
     metrics::{
         increment_request_counter, set_gauge, start_request_timer, PRIORITIZED_PEER, REGULAR_PEER,
     },
+    peer_reputation::PeerReputationStorage,
     peer_states::{ErrorType, PeerStates},
     poller::DataSummaryPoller,
     priority,
@@ -36,7 +37,7 @@ use aptos_storage_service_types::{
     requests::{
         DataRequest, EpochEndingLedgerInfoRequest, NewTransactionOutputsWithProofRequest,
         NewTransactionsOrOutputsWithProofRequest, NewTransactionsWithProofRequest,
-        StateValuesWithProofRequest, StorageServiceRequest,
+        StateValuesWithProofRequest, StateValuesWithSparseProofRequest, StorageServiceRequest,
         SubscribeTransactionOutputsWithProofRequest,
         SubscribeTransactionsOrOutputsWithProofRequest, SubscribeTransactionsWithProofRequest,
         SubscriptionStreamMetadata, TransactionOutputsWithProofRequest,
@@ -45,11 +46,14 @@ use aptos_storage_service_types::{
     responses::{StorageServerSummary, StorageServiceResponse, TransactionOrOutputListWithProofV2},
     Epoch, StorageServiceMessage,
 };
-use aptos_time_service::TimeService;
+use aptos_time_service::{TimeService, TimeServiceTrait};
 use aptos_types::{
     epoch_change::EpochChangeProof,
     ledger_info::LedgerInfoWithSignatures,
-    state_store::state_value::StateValueChunkWithProof,
+    state_store::{
+        state_key::StateKey,
+        state_value::{StateValueChunkWithProof, StateValuesWithSparseProof},
+    },
     transaction::{TransactionListWithProofV2, TransactionOutputListWithProofV2, Version},
 };
 use arc_swap::ArcSwap;
@@ -114,19 +118,28 @@ impl AptosDataClient {
         time_service: TimeService,
         storage: Arc<dyn DbReader>,
         storage_service_client: StorageServiceClient<NetworkClient<StorageServiceMessage>>,
+        peer_reputation_storage: Option<Arc<dyn PeerReputationStorage>>,
         runtime: Option<Handle>,
     ) -> (Self, DataSummaryPoller) {
         // Wrap the configs in an Arc (to be shared across components)
         let base_config = Arc::new(base_config);
         let data_client_config = Arc::new(data_client_config);
 
+        // Create the peer states (and load any persisted peer reputations)
+        let peer_states = PeerStates::new(data_client_config.clone(), time_service.clone());
+        let peer_reputation_storage = peer_reputation_storage
+            .filter(|_| data_client_config.peer_reputation_config.enable_persistence);
+        if let Some(peer_reputation_storage) = &peer_reputation_storage {
+            load_peer_reputations(&peer_states, peer_reputation_storage.clone());
+        }
+
         // Create the data client
         let data_client = Self {
             base_config,
             data_client_config: data_client_config.clone(),
             storage_service_client: storage_service_client.clone(),
             active_subscription_state: Arc::new(Mutex::new(None)),
-            peer_states: Arc::new(PeerStates::new(data_client_config.clone())),
+            peer_states: Arc::new(peer_states),
             global_summary_cache: Arc::new(ArcSwap::from(Arc::new(GlobalDataSummary::empty()))),
             response_id_generator: Arc::new(U64IdGenerator::new()),
             time_service: time_service.clone(),
@@ -137,6 +150,7 @@ impl AptosDataClient {
             data_client_config,
             data_client.clone(),
             storage_service_client.get_peers_and_metadata(),
+            peer_reputation_storage,
             runtime,
             storage,
             time_service,
@@ -787,6 +801,7 @@ impl AptosDataClient {
         self.update_sent_request_metrics(peer, &request);
 
         // Send the request and process the result
+        let request_start_time = self.time_service.now();
         let result = self
             .storage_service_client
             .send_request(
@@ -815,6 +830,10 @@ impl AptosDataClient {
                 // is successful or failed but not both; on the other hand, this
                 // feels simpler for the consumer.
                 self.peer_states.update_score_success(peer);
+                self.peer_states.record_response_latency(
+                    peer,
+                    self.time_service.now().duration_since(request_start_time),
+                );
 
                 // Package up all of the context needed to fully report an error
                 // with this RPC.
@@ -1053,6 +1072,21 @@ impl AptosDataClientInterface for AptosDataClient {
             .await
     }
 
+    async fn get_state_values_with_sparse_proof(
+        &self,
+        version: u64,
+        state_keys: Vec<StateKey>,
+        request_timeout_ms: u64,
+    ) -> crate::error::Result<Response<StateValuesWithSparseProof>> {
+        let data_request =
+            DataRequest::GetStateValuesWithSparseProof(StateValuesWithSparseProofRequest {
+                version,
+                state_keys,
+            });
+        self.create_and_send_storage_request(request_timeout_ms, data_request)
+            .await
+    }
+
     async fn get_transaction_outputs_with_proof(
         &self,
         proof_version: Version,
@@ -1273,6 +1307,34 @@ impl SubscriptionState {
     }
 }
 
+/// Loads the persisted peer reputations from storage into the peer states
+fn load_peer_reputations(
+    peer_states: &PeerStates,
+    peer_reputation_storage: Arc<dyn PeerReputationStorage>,
+) {
+    match peer_reputation_storage.load_peer_reputations() {
+        Ok(peer_reputations) => {
+            info!(
+                (LogSchema::new(LogEntry::PeerReputations)
+                    .event(LogEvent::LoadPeerReputations)
+                    .message(&format!(
+                        "Loaded {} persisted peer reputations!",
+                        peer_reputations.len()
+                    )))
+            );
+            peer_states.load_peer_reputations(peer_reputations);
+        },
+        Err(error) => {
+            warn!(
+                (LogSchema::new(LogEntry::PeerReputations)
+                    .event(LogEvent::LoadPeerReputations)
+                    .message("Failed to load the persisted peer reputations!")
+                    .error(&error))
+            );
+        },
+    }
+}
+
 /// Updates the metrics for the number of connected peers (priority and regular)
 fn update_priority_and_regular_peer_metrics(
     priority_peers: &HashSet<PeerNetworkId>,
@@ -5,7 +5,10 @@ use crate::{error, error::Error, global_summary::GlobalDataSummary};
 use aptos_storage_service_types::{responses::TransactionOrOutputListWithProofV2, Epoch};
 use aptos_types::{
     ledger_info::LedgerInfoWithSignatures,
-    state_store::state_value::StateValueChunkWithProof,
+    state_store::{
+        state_key::StateKey,
+        state_value::{StateValueChunkWithProof, StateValuesWithSparseProof},
+    },
     transaction::{TransactionListWithProofV2, TransactionOutputListWithProofV2, Version},
 };
 use async_trait::async_trait;
@@ -87,6 +90,19 @@ pub trait AptosDataClientInterface {
         request_timeout_ms: u64,
     ) -> error::Result<Response<StateValueChunkWithProof>>;
 
+    /// Fetches the state values of the given state keys at the specified
+    /// version, each with a sparse proof relative to the state root at the
+    /// version. This is used by nodes in partial state mode to verify the
+    /// state values they store. Unlike state value chunks, all requested
+    /// state values are returned. If the data cannot be fetched, an error
+    /// is returned.
+    async fn get_state_values_with_sparse_proof(
+        &self,
+        version: u64,
+        state_keys: Vec<StateKey>,
+        request_timeout_ms: u64,
+    ) -> error::Result<Response<StateValuesWithSparseProof>>;
+
     /// Fetches a transaction output list with proof, with transaction
     /// outputs from start to end versions (inclusive). The proof is relative
     /// to the specified `proof_version`. In some cases, fewer outputs may be
@@ -42,6 +42,7 @@ impl LogSchema<'_> {
 pub enum LogEntry {
     DataSummaryPoller,
     LatencyMonitor,
+    PeerReputations,
     PeerStates,
     StorageServiceRequest,
     StorageServiceResponse,
@@ -53,12 +54,14 @@ pub enum LogEntry {
 pub enum LogEvent {
     AggregateSummary,
     CaughtUpToLatest,
+    LoadPeerReputations,
     NoPeersToPoll,
     PeerIgnored,
     PeerNoLongerIgnored,
     PeerPollingError,
     PeerRequestResponseCounts,
     PeerSelectionError,
+    PersistPeerReputations,
     PriorityAndRegularPeers,
     PriorityPeerCategories,
     ResponseError,
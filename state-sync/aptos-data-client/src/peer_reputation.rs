@@ -0,0 +1,172 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{error::Error, peer_states::STARTING_SCORE};
+use aptos_config::{config::AptosPeerReputationConfig, network_id::PeerNetworkId};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    time::Duration,
+};
+
+/// The weight of a new latency sample in the average response latency
+const LATENCY_SAMPLE_WEIGHT: f64 = 0.1;
+
+/// The storage interface used to persist peer reputations across restarts
+pub trait PeerReputationStorage: Send + Sync {
+    /// Returns all persisted peer reputations
+    fn load_peer_reputations(&self) -> Result<Vec<(PeerNetworkId, PeerReputation)>, Error>;
+
+    /// Persists the given peer reputations (replacing any existing ones)
+    fn save_peer_reputations(
+        &self,
+        peer_reputations: Vec<(PeerNetworkId, PeerReputation)>,
+    ) -> Result<(), Error>;
+}
+
+/// A single record of peer misbehaviour (e.g., an invalid response)
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct MisbehaviourRecord {
+    /// The time (usecs since the unix epoch) at which the misbehaviour occurred
+    pub timestamp_usecs: u64,
+    /// The label of the misbehaviour (e.g., "malicious")
+    pub misbehaviour_label: String,
+}
+
+/// The reputation of a peer, as observed by the data client. This is
+/// persisted so that the node doesn't relearn which peers are slow or
+/// serve bad data after every restart.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PeerReputation {
+    /// The score of the peer (stored as bits, as BCS doesn't support floats)
+    score_bits: u64,
+
+    /// The moving average of the response latencies (usecs) from the peer
+    average_response_latency_usecs: Option<u64>,
+
+    /// The most recent misbehaviour records (ordered from oldest to newest)
+    misbehaviour_records: VecDeque<MisbehaviourRecord>,
+
+    /// The time (usecs since the unix epoch) of the last reputation update
+    last_updated_usecs: u64,
+}
+
+impl PeerReputation {
+    pub fn new(timestamp_usecs: u64) -> Self {
+        Self {
+            score_bits: STARTING_SCORE.to_bits(),
+            average_response_latency_usecs: None,
+            misbehaviour_records: VecDeque::new(),
+            last_updated_usecs: timestamp_usecs,
+        }
+    }
+
+    /// Returns the score of the peer
+    pub fn get_score(&self) -> f64 {
+        f64::from_bits(self.score_bits)
+    }
+
+    /// Returns the average response latency of the peer (if any responses were received)
+    pub fn get_average_response_latency(&self) -> Option<Duration> {
+        self.average_response_latency_usecs
+            .map(Duration::from_micros)
+    }
+
+    /// Returns the misbehaviour records of the peer
+    pub fn get_misbehaviour_records(&self) -> &VecDeque<MisbehaviourRecord> {
+        &self.misbehaviour_records
+    }
+
+    /// Returns the number of misbehaviour records for the peer (by label)
+    pub fn get_misbehaviour_counts(&self) -> BTreeMap<String, u64> {
+        let mut misbehaviour_counts = BTreeMap::new();
+        for misbehaviour_record in &self.misbehaviour_records {
+            *misbehaviour_counts
+                .entry(misbehaviour_record.misbehaviour_label.clone())
+                .or_default() += 1;
+        }
+        misbehaviour_counts
+    }
+
+    /// Returns the time (usecs since the unix epoch) of the last reputation update
+    pub fn get_last_updated_usecs(&self) -> u64 {
+        self.last_updated_usecs
+    }
+
+    /// Updates the score of the peer
+    pub fn set_score(&mut self, score: f64, timestamp_usecs: u64) {
+        self.score_bits = score.to_bits();
+        self.update_timestamp(timestamp_usecs);
+    }
+
+    /// Records a response latency sample for the peer
+    pub fn record_response_latency(&mut self, latency: Duration, timestamp_usecs: u64) {
+        let latency_usecs = latency.as_micros() as f64;
+        let average_latency_usecs = match self.average_response_latency_usecs {
+            Some(average_latency_usecs) => {
+                (average_latency_usecs as f64 * (1.0 - LATENCY_SAMPLE_WEIGHT))
+                    + (latency_usecs * LATENCY_SAMPLE_WEIGHT)
+            },
+            None => latency_usecs,
+        };
+        self.average_response_latency_usecs = Some(average_latency_usecs as u64);
+        self.update_timestamp(timestamp_usecs);
+    }
+
+    /// Records a misbehaviour of the peer (dropping the oldest records if required)
+    pub fn record_misbehaviour(
+        &mut self,
+        misbehaviour_label: &str,
+        timestamp_usecs: u64,
+        max_misbehaviour_records: u64,
+    ) {
+        self.misbehaviour_records.push_back(MisbehaviourRecord {
+            timestamp_usecs,
+            misbehaviour_label: misbehaviour_label.into(),
+        });
+        while self.misbehaviour_records.len() as u64 > max_misbehaviour_records {
+            self.misbehaviour_records.pop_front();
+        }
+        self.update_timestamp(timestamp_usecs);
+    }
+
+    /// Returns true iff the reputation hasn't been updated within the max age
+    pub fn is_expired(&self, config: &AptosPeerReputationConfig, timestamp_usecs: u64) -> bool {
+        let reputation_age_usecs = timestamp_usecs.saturating_sub(self.last_updated_usecs);
+        reputation_age_usecs > max_age_usecs(config)
+    }
+
+    /// Decays the reputation according to the time elapsed since the last update.
+    /// The score decays exponentially back to the starting score, and any expired
+    /// misbehaviour records are removed.
+    pub fn decay(&mut self, config: &AptosPeerReputationConfig, timestamp_usecs: u64) {
+        // Decay the score towards the starting score
+        let elapsed_secs =
+            timestamp_usecs.saturating_sub(self.last_updated_usecs) as f64 / 1_000_000.0;
+        let decay_factor = if config.decay_half_life_secs == 0 {
+            0.0 // Reset the score immediately
+        } else {
+            0.5_f64.powf(elapsed_secs / config.decay_half_life_secs as f64)
+        };
+        let score = STARTING_SCORE + ((self.get_score() - STARTING_SCORE) * decay_factor);
+        self.score_bits = score.to_bits();
+
+        // Remove any expired misbehaviour records
+        let max_age_usecs = max_age_usecs(config);
+        self.misbehaviour_records.retain(|misbehaviour_record| {
+            timestamp_usecs.saturating_sub(misbehaviour_record.timestamp_usecs) <= max_age_usecs
+        });
+
+        self.update_timestamp(timestamp_usecs);
+    }
+
+    /// Updates the last updated timestamp (ignoring timestamps from the past)
+    fn update_timestamp(&mut self, timestamp_usecs: u64) {
+        self.last_updated_usecs = self.last_updated_usecs.max(timestamp_usecs);
+    }
+}
+
+/// Returns the max reputation age (usecs) as defined by the config
+fn max_age_usecs(config: &AptosPeerReputationConfig) -> u64 {
+    config.max_reputation_age_secs.saturating_mul(1_000_000)
+}
@@ -6,6 +6,7 @@ use crate::{
     interface::ResponseError,
     logging::{LogEntry, LogEvent, LogSchema},
     metrics,
+    peer_reputation::PeerReputation,
 };
 use aptos_config::{
     config::AptosDataClientConfig,
@@ -15,7 +16,7 @@ use aptos_logger::prelude::*;
 use aptos_storage_service_types::{
     requests::StorageServiceRequest, responses::StorageServerSummary,
 };
-use aptos_time_service::TimeService;
+use aptos_time_service::{TimeService, TimeServiceTrait};
 use dashmap::DashMap;
 use std::{
     cmp::min,
@@ -32,7 +33,7 @@ const NUM_PEER_BUCKETS_FOR_METRICS: u8 = 4; // To avoid metric explosion, we buc
 /// Scores for peer rankings based on preferences and behavior.
 const MAX_SCORE: f64 = 100.0;
 const MIN_SCORE: f64 = 0.0;
-const STARTING_SCORE: f64 = 50.0;
+pub(crate) const STARTING_SCORE: f64 = 50.0;
 /// Add this score on a successful response.
 const SUCCESSFUL_RESPONSE_DELTA: f64 = 1.0;
 /// Not necessarily a malicious response, but not super useful.
@@ -51,6 +52,16 @@ pub enum ErrorType {
     Malicious,
 }
 
+impl ErrorType {
+    /// Returns a summary label for the error type
+    pub fn get_label(&self) -> &'static str {
+        match self {
+            ErrorType::NotUseful => "not_useful",
+            ErrorType::Malicious => "malicious",
+        }
+    }
+}
+
 impl From<ResponseError> for ErrorType {
     fn from(error: ResponseError) -> Self {
         match error {
@@ -77,18 +88,20 @@ pub struct PeerState {
     /// haven't polled them yet.
     storage_summary: Option<StorageServerSummary>,
 
-    /// For now, a simplified port of the original state-sync v1 scoring system.
-    score: f64,
+    /// The reputation of the peer (i.e., the score, latency and misbehaviour
+    /// records). The score is a simplified port of the original state-sync v1
+    /// scoring system.
+    reputation: PeerReputation,
 }
 
 impl PeerState {
-    pub fn new(data_client_config: Arc<AptosDataClientConfig>) -> Self {
+    pub fn new(data_client_config: Arc<AptosDataClientConfig>, reputation: PeerReputation) -> Self {
         Self {
             data_client_config,
             received_responses_by_type: Arc::new(DashMap::new()),
             sent_requests_by_type: Arc::new(DashMap::new()),
             storage_summary: None,
-            score: STARTING_SCORE,
+            reputation,
         }
     }
 }
@@ -112,7 +125,12 @@ impl PeerState {
 
     /// Returns the peer's score
     pub fn get_score(&self) -> f64 {
-        self.score
+        self.reputation.get_score()
+    }
+
+    /// Returns the peer's reputation
+    pub fn get_reputation(&self) -> &PeerReputation {
+        &self.reputation
     }
 
     /// Returns the storage summary for the peer
@@ -149,28 +167,47 @@ impl PeerState {
     }
 
     /// Returns true iff the peer is currently ignored
-    fn is_ignored(&self) -> bool {
+    pub fn is_ignored(&self) -> bool {
         // Only ignore peers if the config allows it
         if !self.data_client_config.ignore_low_score_peers {
             return false;
         }
 
         // Otherwise, ignore peers with a low score
-        self.score <= IGNORE_PEER_THRESHOLD
+        self.get_score() <= IGNORE_PEER_THRESHOLD
     }
 
     /// Updates the score of the peer according to a successful operation
-    fn update_score_success(&mut self) {
-        self.score = f64::min(self.score + SUCCESSFUL_RESPONSE_DELTA, MAX_SCORE);
+    fn update_score_success(&mut self, timestamp_usecs: u64) {
+        let score = f64::min(self.get_score() + SUCCESSFUL_RESPONSE_DELTA, MAX_SCORE);
+        self.reputation.set_score(score, timestamp_usecs);
     }
 
-    /// Updates the score of the peer according to an error
-    fn update_score_error(&mut self, error: ErrorType) {
+    /// Updates the score of the peer according to an error (and records the misbehaviour)
+    fn update_score_error(&mut self, error: ErrorType, timestamp_usecs: u64) {
         let multiplier = match error {
             ErrorType::NotUseful => NOT_USEFUL_MULTIPLIER,
             ErrorType::Malicious => MALICIOUS_MULTIPLIER,
         };
-        self.score = f64::max(self.score * multiplier, MIN_SCORE);
+        let score = f64::max(self.get_score() * multiplier, MIN_SCORE);
+        self.reputation.set_score(score, timestamp_usecs);
+
+        // Record the misbehaviour
+        let max_misbehaviour_records = self
+            .data_client_config
+            .peer_reputation_config
+            .max_misbehaviour_records_per_peer;
+        self.reputation.record_misbehaviour(
+            error.get_label(),
+            timestamp_usecs,
+            max_misbehaviour_records,
+        );
+    }
+
+    /// Records the latency of a response from the peer
+    fn record_response_latency(&mut self, latency: Duration, timestamp_usecs: u64) {
+        self.reputation
+            .record_response_latency(latency, timestamp_usecs);
     }
 
     /// Updates the storage summary for the peer
@@ -185,16 +222,27 @@ impl PeerState {
 pub struct PeerStates {
     data_client_config: Arc<AptosDataClientConfig>,
     peer_to_state: Arc<DashMap<PeerNetworkId, PeerState>>,
+    /// The reputations of peers that are not currently connected (e.g., peers
+    /// that disconnected, or peers that were loaded from storage on startup).
+    disconnected_peer_reputations: Arc<DashMap<PeerNetworkId, PeerReputation>>,
+    time_service: TimeService,
 }
 
 impl PeerStates {
-    pub fn new(data_client_config: Arc<AptosDataClientConfig>) -> Self {
+    pub fn new(data_client_config: Arc<AptosDataClientConfig>, time_service: TimeService) -> Self {
         Self {
             data_client_config,
             peer_to_state: Arc::new(DashMap::new()),
+            disconnected_peer_reputations: Arc::new(DashMap::new()),
+            time_service,
         }
     }
 
+    /// Returns the current time (usecs since the unix epoch)
+    fn get_timestamp_usecs(&self) -> u64 {
+        self.time_service.now_unix_time().as_micros() as u64
+    }
+
     /// Returns true if a connected storage service peer can actually fulfill a
     /// request, given our current view of their advertised data summary.
     pub fn can_service_request(
@@ -281,13 +329,13 @@ impl PeerStates {
     pub fn update_score_success(&self, peer: PeerNetworkId) {
         if let Some(mut entry) = self.peer_to_state.get_mut(&peer) {
             // Get the peer's old score
-            let old_score = entry.score;
+            let old_score = entry.get_score();
 
             // Update the peer's score with a successful operation
-            entry.update_score_success();
+            entry.update_score_success(self.get_timestamp_usecs());
 
             // Log if the peer is no longer ignored
-            let new_score = entry.score;
+            let new_score = entry.get_score();
             if old_score <= IGNORE_PEER_THRESHOLD && new_score > IGNORE_PEER_THRESHOLD {
                 info!(
                     (LogSchema::new(LogEntry::PeerStates)
@@ -303,13 +351,13 @@ impl PeerStates {
     pub fn update_score_error(&self, peer: PeerNetworkId, error: ErrorType) {
         if let Some(mut entry) = self.peer_to_state.get_mut(&peer) {
             // Get the peer's old score
-            let old_score = entry.score;
+            let old_score = entry.get_score();
 
             // Update the peer's score with an error
-            entry.update_score_error(error);
+            entry.update_score_error(error, self.get_timestamp_usecs());
 
             // Log if the peer is now ignored
-            let new_score = entry.score;
+            let new_score = entry.get_score();
             if old_score > IGNORE_PEER_THRESHOLD && new_score <= IGNORE_PEER_THRESHOLD {
                 info!(
                     (LogSchema::new(LogEntry::PeerStates)
@@ -321,18 +369,110 @@ impl PeerStates {
         }
     }
 
+    /// Records the latency of a response from the given peer
+    pub fn record_response_latency(&self, peer: PeerNetworkId, latency: Duration) {
+        if let Some(mut entry) = self.peer_to_state.get_mut(&peer) {
+            entry.record_response_latency(latency, self.get_timestamp_usecs());
+        }
+    }
+
     /// Updates the storage summary for the given peer
     pub fn update_summary(&self, peer: PeerNetworkId, storage_summary: StorageServerSummary) {
         self.peer_to_state
             .entry(peer)
-            .or_insert(PeerState::new(self.data_client_config.clone()))
+            .or_insert_with(|| {
+                PeerState::new(
+                    self.data_client_config.clone(),
+                    self.take_peer_reputation(&peer),
+                )
+            })
             .update_storage_summary(storage_summary);
     }
 
-    /// Garbage collects the peer states to remove data for disconnected peers
+    /// Removes and returns the (decayed) reputation of a previously disconnected
+    /// peer. If the peer has no known reputation, a new reputation is returned.
+    fn take_peer_reputation(&self, peer: &PeerNetworkId) -> PeerReputation {
+        let timestamp_usecs = self.get_timestamp_usecs();
+        match self.disconnected_peer_reputations.remove(peer) {
+            Some((_, mut peer_reputation)) => {
+                let peer_reputation_config = &self.data_client_config.peer_reputation_config;
+                if peer_reputation.is_expired(peer_reputation_config, timestamp_usecs) {
+                    PeerReputation::new(timestamp_usecs)
+                } else {
+                    peer_reputation.decay(peer_reputation_config, timestamp_usecs);
+                    peer_reputation
+                }
+            },
+            None => PeerReputation::new(timestamp_usecs),
+        }
+    }
+
+    /// Garbage collects the peer states to remove data for disconnected peers.
+    /// If reputation persistence is enabled, the reputations of the disconnected
+    /// peers are retained (until they expire). Otherwise, they are dropped.
     pub fn garbage_collect_peer_states(&self, connected_peers: HashSet<PeerNetworkId>) {
-        self.peer_to_state
-            .retain(|peer_network_id, _| connected_peers.contains(peer_network_id));
+        let peer_reputation_config = &self.data_client_config.peer_reputation_config;
+        self.peer_to_state.retain(|peer_network_id, peer_state| {
+            let is_connected = connected_peers.contains(peer_network_id);
+            if !is_connected && peer_reputation_config.enable_persistence {
+                self.disconnected_peer_reputations
+                    .insert(*peer_network_id, peer_state.get_reputation().clone());
+            }
+            is_connected
+        });
+
+        // Remove any expired reputations
+        self.remove_expired_peer_reputations();
+    }
+
+    /// Loads the given peer reputations (e.g., from storage on startup). Expired
+    /// reputations are dropped, and the rest are decayed once the peers connect.
+    pub fn load_peer_reputations(&self, peer_reputations: Vec<(PeerNetworkId, PeerReputation)>) {
+        let timestamp_usecs = self.get_timestamp_usecs();
+        let peer_reputation_config = &self.data_client_config.peer_reputation_config;
+        for (peer, peer_reputation) in peer_reputations {
+            if !peer_reputation.is_expired(peer_reputation_config, timestamp_usecs)
+                && !self.peer_to_state.contains_key(&peer)
+            {
+                self.disconnected_peer_reputations
+                    .insert(peer, peer_reputation);
+            }
+        }
+    }
+
+    /// Returns the reputations of all known peers (connected and disconnected),
+    /// sorted by peer. Expired reputations of disconnected peers are removed.
+    pub fn get_peer_reputations(&self) -> Vec<(PeerNetworkId, PeerReputation)> {
+        // Remove any expired reputations
+        self.remove_expired_peer_reputations();
+
+        // Collect all peer reputations
+        let mut peer_reputations = BTreeMap::new();
+        for peer_reputation in self.disconnected_peer_reputations.iter() {
+            peer_reputations.insert(*peer_reputation.key(), peer_reputation.value().clone());
+        }
+        for peer_state in self.peer_to_state.iter() {
+            peer_reputations.insert(
+                *peer_state.key(),
+                peer_state.value().get_reputation().clone(),
+            );
+        }
+        peer_reputations.into_iter().collect()
+    }
+
+    /// Removes the expired reputations of disconnected peers
+    fn remove_expired_peer_reputations(&self) {
+        let timestamp_usecs = self.get_timestamp_usecs();
+        let peer_reputation_config = &self.data_client_config.peer_reputation_config;
+        self.disconnected_peer_reputations
+            .retain(|_, peer_reputation| {
+                !peer_reputation.is_expired(peer_reputation_config, timestamp_usecs)
+            });
+    }
+
+    /// Returns the reputations of the peers that are not currently connected
+    pub fn get_disconnected_peer_reputations(&self) -> Arc<DashMap<PeerNetworkId, PeerReputation>> {
+        self.disconnected_peer_reputations.clone()
     }
 
     /// Calculates a global data summary using all known storage summaries
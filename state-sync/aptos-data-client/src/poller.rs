@@ -10,6 +10,7 @@ use crate::{
     logging::{LogEntry, LogEvent, LogSchema},
     metrics,
     metrics::{set_gauge, DataType, PRIORITIZED_PEER, REGULAR_PEER},
+    peer_reputation::PeerReputationStorage,
     utils,
 };
 use aptos_config::{
@@ -46,6 +47,7 @@ pub struct DataSummaryPoller {
     in_flight_priority_polls: Arc<DashSet<PeerNetworkId>>, // The set of priority peers with in-flight polls
     in_flight_regular_polls: Arc<DashSet<PeerNetworkId>>, // The set of regular peers with in-flight polls
     peers_and_metadata: Arc<PeersAndMetadata>,            // The peers and metadata
+    peer_reputation_storage: Option<Arc<dyn PeerReputationStorage>>, // The storage for peer reputations (if any)
     runtime: Option<Handle>, // An optional runtime on which to spawn the poller threads
     storage: Arc<dyn DbReader>, // The reader interface to storage
     time_service: TimeService, // The service to monitor elapsed time
@@ -56,6 +58,7 @@ impl DataSummaryPoller {
         data_client_config: Arc<AptosDataClientConfig>,
         data_client: AptosDataClient,
         peers_and_metadata: Arc<PeersAndMetadata>,
+        peer_reputation_storage: Option<Arc<dyn PeerReputationStorage>>,
         runtime: Option<Handle>,
         storage: Arc<dyn DbReader>,
         time_service: TimeService,
@@ -66,6 +69,7 @@ impl DataSummaryPoller {
             in_flight_priority_polls: Arc::new(DashSet::new()),
             in_flight_regular_polls: Arc::new(DashSet::new()),
             peers_and_metadata,
+            peer_reputation_storage,
             runtime,
             storage,
             time_service,
@@ -281,6 +285,7 @@ pub async fn start_poller(poller: DataSummaryPoller) {
 
     // Start the poller
     let mut polling_round: u64 = 0;
+    let mut last_reputation_persist_time = poller.time_service.now();
     info!((LogSchema::new(LogEntry::DataSummaryPoller).message("Starting the Aptos data poller!")));
     loop {
         // Wait for the next round before polling
@@ -305,6 +310,23 @@ pub async fn start_poller(poller: DataSummaryPoller) {
         // Update the metrics and logs for the peer states
         poller.data_client.update_peer_metrics_and_logs();
 
+        // Persist the peer reputations (if the persistence interval has elapsed)
+        let persistence_interval = Duration::from_secs(
+            poller
+                .data_client_config
+                .peer_reputation_config
+                .persistence_interval_secs,
+        );
+        if poller
+            .time_service
+            .now()
+            .duration_since(last_reputation_persist_time)
+            >= persistence_interval
+        {
+            persist_peer_reputations(&poller);
+            last_reputation_persist_time = poller.time_service.now();
+        }
+
         // Determine the peers to poll this round. If the round is even, poll
         // the priority peers. Otherwise, poll the regular peers. This allows
         // us to alternate between peer types and load balance requests.
@@ -465,6 +487,21 @@ pub(crate) fn poll_peer(
     }
 }
 
+/// Persists the reputations of all known peers (if a storage is configured)
+fn persist_peer_reputations(poller: &DataSummaryPoller) {
+    if let Some(peer_reputation_storage) = &poller.peer_reputation_storage {
+        let peer_reputations = poller.data_client.get_peer_states().get_peer_reputations();
+        if let Err(error) = peer_reputation_storage.save_peer_reputations(peer_reputations) {
+            warn!(
+                (LogSchema::new(LogEntry::PeerReputations)
+                    .event(LogEvent::PersistPeerReputations)
+                    .message("Failed to persist the peer reputations!")
+                    .error(&error))
+            );
+        }
+    }
+}
+
 /// Spawns the dedicated latency monitor
 fn start_latency_monitor(
     data_client_config: Arc<AptosDataClientConfig>,
@@ -36,7 +36,10 @@ use aptos_storage_service_types::{
 use aptos_time_service::{MockTimeService, TimeService};
 use aptos_types::{
     ledger_info::LedgerInfoWithSignatures,
-    state_store::state_value::StateValueChunkWithProof,
+    state_store::{
+        state_key::StateKey,
+        state_value::{StateValueChunkWithProof, StateValuesWithSparseProof},
+    },
     transaction::{TransactionListWithProofV2, TransactionOutputListWithProofV2, Version},
     PeerId,
 };
@@ -111,6 +114,7 @@ impl MockNetwork {
             create_mock_db_reader(),
             storage_service_client,
             None,
+            None,
         );
 
         // Create the mock network
@@ -315,6 +319,13 @@ mock! {
             request_timeout_ms: u64,
         ) -> Result<Response<StateValueChunkWithProof>>;
 
+        async fn get_state_values_with_sparse_proof(
+            &self,
+            version: u64,
+            state_keys: Vec<StateKey>,
+            request_timeout_ms: u64,
+        ) -> Result<Response<StateValuesWithSparseProof>>;
+
         async fn get_transaction_outputs_with_proof(
             &self,
             proof_version: Version,
@@ -5,6 +5,7 @@ mod advertise;
 mod compression;
 pub mod mock;
 mod multi_fetch;
+mod peer_reputation;
 mod peers;
 mod poller;
 mod priority;
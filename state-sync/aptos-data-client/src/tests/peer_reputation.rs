@@ -0,0 +1,213 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    peer_states::{ErrorType, PeerStates},
+    tests::utils,
+};
+use aptos_config::{
+    config::{AptosDataClientConfig, AptosPeerReputationConfig},
+    network_id::PeerNetworkId,
+};
+use aptos_time_service::TimeService;
+use std::{collections::HashSet, sync::Arc, time::Duration};
+
+#[test]
+fn test_disconnected_peer_reputation_is_dropped_without_persistence() {
+    // Create the peer states (persistence is disabled by default)
+    let time_service = TimeService::mock();
+    let peer_states = create_peer_states(AptosDataClientConfig::default(), time_service);
+
+    // Add several peers and record misbehaviours for each of them
+    let peers: Vec<_> = (0..5).map(|_| PeerNetworkId::random()).collect();
+    for peer in &peers {
+        peer_states.update_summary(*peer, utils::create_storage_summary(100));
+        peer_states.update_score_error(*peer, ErrorType::Malicious);
+    }
+
+    // Disconnect the peers and verify that no reputations are retained
+    peer_states.garbage_collect_peer_states(HashSet::new());
+    assert!(peer_states.get_peer_to_states().is_empty());
+    assert!(peer_states.get_disconnected_peer_reputations().is_empty());
+
+    // Reconnect a peer and verify that it starts with a fresh reputation
+    peer_states.update_summary(peers[0], utils::create_storage_summary(200));
+    assert_eq!(get_peer_score(&peer_states, &peers[0]), 50.0);
+}
+
+#[test]
+fn test_disconnected_peer_reputation_is_retained() {
+    // Create the peer states (with persistence enabled)
+    let time_service = TimeService::mock();
+    let peer_states = create_peer_states(create_persistence_config(), time_service);
+
+    // Add a peer and record several misbehaviours (until the peer is ignored)
+    let peer = PeerNetworkId::random();
+    peer_states.update_summary(peer, utils::create_storage_summary(100));
+    for _ in 0..10 {
+        peer_states.update_score_error(peer, ErrorType::Malicious);
+    }
+    let peer_score = get_peer_score(&peer_states, &peer);
+    assert!(is_peer_ignored(&peer_states, &peer));
+
+    // Disconnect the peer and verify the reputation is retained
+    peer_states.garbage_collect_peer_states(HashSet::new());
+    assert!(peer_states.get_peer_to_states().is_empty());
+    let peer_reputations = peer_states.get_peer_reputations();
+    assert_eq!(peer_reputations.len(), 1);
+    assert_eq!(peer_reputations[0].0, peer);
+    assert_eq!(peer_reputations[0].1.get_score(), peer_score);
+    assert_eq!(
+        peer_reputations[0]
+            .1
+            .get_misbehaviour_counts()
+            .get("malicious"),
+        Some(&10)
+    );
+
+    // Reconnect the peer and verify that it is still ignored
+    peer_states.update_summary(peer, utils::create_storage_summary(200));
+    assert_eq!(get_peer_score(&peer_states, &peer), peer_score);
+    assert!(is_peer_ignored(&peer_states, &peer));
+    assert!(peer_states.get_disconnected_peer_reputations().is_empty());
+}
+
+#[test]
+fn test_loaded_peer_reputation_decays() {
+    // Create a data client config with a short decay half-life
+    let decay_half_life_secs = 100;
+    let data_client_config = AptosDataClientConfig {
+        peer_reputation_config: AptosPeerReputationConfig {
+            enable_persistence: true,
+            decay_half_life_secs,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    // Create the peer states and add an ignored peer
+    let time_service = TimeService::mock();
+    let peer_states = create_peer_states(data_client_config, time_service.clone());
+    let peer = PeerNetworkId::random();
+    peer_states.update_summary(peer, utils::create_storage_summary(100));
+    for _ in 0..10 {
+        peer_states.update_score_error(peer, ErrorType::Malicious);
+    }
+    let peer_score = get_peer_score(&peer_states, &peer);
+
+    // Load the peer reputations into new peer states (mimic a reboot)
+    let peer_reputations = peer_states.get_peer_reputations();
+    let peer_states = create_peer_states(data_client_config, time_service.clone());
+    peer_states.load_peer_reputations(peer_reputations);
+
+    // Elapse a single half-life and reconnect the peer
+    time_service.into_mock().advance_secs(decay_half_life_secs);
+    peer_states.update_summary(peer, utils::create_storage_summary(200));
+
+    // Verify the score has decayed halfway back to the starting score
+    let starting_score = 50.0;
+    let expected_score = starting_score + ((peer_score - starting_score) / 2.0);
+    let decayed_score = get_peer_score(&peer_states, &peer);
+    assert!((decayed_score - expected_score).abs() < 0.0001);
+}
+
+#[test]
+fn test_expired_peer_reputations_are_dropped() {
+    // Create a data client config with a short max reputation age
+    let max_reputation_age_secs = 1000;
+    let data_client_config = AptosDataClientConfig {
+        peer_reputation_config: AptosPeerReputationConfig {
+            enable_persistence: true,
+            max_reputation_age_secs,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    // Create the peer states and add a peer with a response latency
+    let time_service = TimeService::mock();
+    let peer_states = create_peer_states(data_client_config, time_service.clone());
+    let peer = PeerNetworkId::random();
+    peer_states.update_summary(peer, utils::create_storage_summary(100));
+    peer_states.record_response_latency(peer, Duration::from_millis(100));
+
+    // Disconnect the peer and verify the reputation (and latency) is retained
+    peer_states.garbage_collect_peer_states(HashSet::new());
+    let peer_reputations = peer_states.get_peer_reputations();
+    assert_eq!(
+        peer_reputations[0].1.get_average_response_latency(),
+        Some(Duration::from_millis(100))
+    );
+
+    // Elapse the max reputation age and verify the reputation is dropped
+    time_service
+        .into_mock()
+        .advance_secs(max_reputation_age_secs + 1);
+    assert!(peer_states.get_peer_reputations().is_empty());
+}
+
+#[test]
+fn test_expired_peer_reputations_are_garbage_collected() {
+    // Create a data client config with persistence and a short max reputation age
+    let max_reputation_age_secs = 1000;
+    let data_client_config = AptosDataClientConfig {
+        peer_reputation_config: AptosPeerReputationConfig {
+            enable_persistence: true,
+            max_reputation_age_secs,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    // Create the peer states and disconnect a peer
+    let time_service = TimeService::mock();
+    let peer_states = create_peer_states(data_client_config, time_service.clone());
+    let peer = PeerNetworkId::random();
+    peer_states.update_summary(peer, utils::create_storage_summary(100));
+    peer_states.garbage_collect_peer_states(HashSet::new());
+    assert_eq!(peer_states.get_disconnected_peer_reputations().len(), 1);
+
+    // Elapse the max reputation age and verify garbage collection drops the reputation
+    time_service
+        .into_mock()
+        .advance_secs(max_reputation_age_secs + 1);
+    peer_states.garbage_collect_peer_states(HashSet::new());
+    assert!(peer_states.get_disconnected_peer_reputations().is_empty());
+}
+
+/// Creates a data client config with peer reputation persistence enabled
+fn create_persistence_config() -> AptosDataClientConfig {
+    AptosDataClientConfig {
+        peer_reputation_config: AptosPeerReputationConfig {
+            enable_persistence: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// Creates peer states with the given config and time service
+fn create_peer_states(
+    data_client_config: AptosDataClientConfig,
+    time_service: TimeService,
+) -> PeerStates {
+    PeerStates::new(Arc::new(data_client_config), time_service)
+}
+
+/// Returns the score of the given connected peer
+fn get_peer_score(peer_states: &PeerStates, peer: &PeerNetworkId) -> f64 {
+    peer_states
+        .get_peer_to_states()
+        .get(peer)
+        .unwrap()
+        .get_score()
+}
+
+/// Returns true iff the given connected peer is ignored
+fn is_peer_ignored(peer_states: &PeerStates, peer: &PeerNetworkId) -> bool {
+    peer_states
+        .get_peer_to_states()
+        .get(peer)
+        .unwrap()
+        .is_ignored()
+}
@@ -36,7 +36,7 @@ use aptos_types::{
     proof::SparseMerkleRangeProof,
     state_store::{
         state_key::StateKey,
-        state_value::{StateValue, StateValueChunkWithProof},
+        state_value::{StateValue, StateValueChunkWithProof, StateValuesWithSparseProof},
     },
     transaction::{
         RawTransaction, Script, SignedTransaction, Transaction, TransactionAuxiliaryData,
@@ -542,6 +542,15 @@ impl AptosDataClientInterface for MockAptosDataClient {
         Ok(create_data_client_response(TOTAL_NUM_STATE_VALUES))
     }
 
+    async fn get_state_values_with_sparse_proof(
+        &self,
+        _version: Version,
+        _state_keys: Vec<StateKey>,
+        _request_timeout_ms: u64,
+    ) -> Result<Response<StateValuesWithSparseProof>, aptos_data_client::error::Error> {
+        unimplemented!("Sparse state proofs are not requested by the data streaming service!")
+    }
+
     async fn get_transaction_outputs_with_proof(
         &self,
         proof_version: Version,
@@ -228,6 +228,38 @@ impl VerifiedEpochStates {
         Ok(highest_known_ledger_info)
     }
 
+    /// Returns the first epoch ending ledger info at or after the given
+    /// version (if one exists).
+    pub fn get_epoch_ending_ledger_info_at_or_after(
+        &self,
+        version: Version,
+    ) -> Option<LedgerInfoWithSignatures> {
+        self.new_epoch_ending_ledger_infos
+            .range(version..)
+            .next()
+            .map(|(_, ledger_info)| ledger_info.clone())
+    }
+
+    /// Verifies the given ledger info against the latest verified epoch state
+    pub fn verify_ledger_info(&self, ledger_info: &LedgerInfoWithSignatures) -> Result<(), Error> {
+        self.latest_epoch_state
+            .verify(ledger_info)
+            .map_err(|error| {
+                Error::VerificationError(format!("Ledger info failed verification: {:?}", error))
+            })
+    }
+
+    /// Returns all epoch ending ledger infos up to (and including) the given version
+    pub fn epoch_ending_ledger_infos_up_to(
+        &self,
+        version: Version,
+    ) -> Vec<LedgerInfoWithSignatures> {
+        self.new_epoch_ending_ledger_infos
+            .range(..=version)
+            .map(|(_, ledger_info)| ledger_info.clone())
+            .collect()
+    }
+
     /// Returns the next epoch ending version after the given version (if one
     /// exists).
     pub fn next_epoch_ending_version(&self, version: Version) -> Option<Version> {
@@ -365,6 +397,12 @@ impl<
         self.driver_configuration.config.bootstrapping_mode
     }
 
+    /// Returns the version to fast sync to (if the node should
+    /// not fast sync to the latest epoch ending version).
+    fn get_fast_sync_target_version(&self) -> Option<Version> {
+        self.driver_configuration.config.fast_sync_target_version
+    }
+
     /// Returns true iff the node has already completed bootstrapping
     pub fn is_bootstrapped(&self) -> bool {
         self.bootstrapped
@@ -470,6 +508,21 @@ impl<
         let highest_known_ledger_info = self.get_highest_known_ledger_info()?;
         let highest_known_ledger_version = highest_known_ledger_info.ledger_info().version();
 
+        // If the node is fast syncing to a target version, bootstrap to the target directly
+        // (the highest known ledger info may not prove the target, e.g., if the target
+        // is in the current epoch, and the node shouldn't sync beyond the target).
+        if self.get_bootstrapping_mode().is_fast_sync() {
+            if let Some(target_version) = self.get_fast_sync_target_version() {
+                return self
+                    .fetch_missing_state_snapshot_data_at_target(
+                        highest_synced_version,
+                        target_version,
+                        global_data_summary,
+                    )
+                    .await;
+            }
+        }
+
         // Check if we need to sync more data
         if self.get_bootstrapping_mode().is_fast_sync()
             && highest_synced_version == GENESIS_TRANSACTION_VERSION
@@ -580,6 +633,153 @@ impl<
         }
     }
 
+    /// Fetches all missing data in order to bootstrap the node to the fast sync
+    /// target version. The state values are synced at the target version, and
+    /// the transaction outputs are then applied up to the ledger info that proves
+    /// the target (so that the latest ledger info in storage is consistent with
+    /// the synced state).
+    async fn fetch_missing_state_snapshot_data_at_target(
+        &mut self,
+        highest_synced_version: Version,
+        target_version: Version,
+        global_data_summary: &GlobalDataSummary,
+    ) -> Result<(), Error> {
+        // Fetch the progress of any previous snapshot sync
+        let previous_snapshot_sync_target =
+            self.metadata_storage.previous_snapshot_sync_target()?;
+
+        if highest_synced_version == GENESIS_TRANSACTION_VERSION {
+            // We're syncing a new node. Check the progress and fetch any missing data
+            if let Some(target) = previous_snapshot_sync_target {
+                if self.metadata_storage.is_snapshot_sync_complete(&target)? {
+                    Err(Error::UnexpectedError(format!(
+                        "The snapshot sync for the target was marked as complete but \
+                        the highest synced version is genesis! Something has gone wrong! \
+                        Target snapshot sync: {:?}",
+                        target
+                    )))
+                } else {
+                    // Continue snapshot syncing to the target
+                    self.fetch_missing_state_values(target, true).await
+                }
+            } else {
+                // No snapshot sync has started. Start a new sync for the target version.
+                let target_ledger_info =
+                    self.get_fast_sync_target_ledger_info(target_version, global_data_summary)?;
+                self.fetch_missing_state_values(target_ledger_info, false)
+                    .await
+            }
+        } else {
+            // This node has already synced some state. If the snapshot sync to the target is
+            // complete, apply the transaction outputs up to the ledger info that proves the target.
+            if let Some(target) = previous_snapshot_sync_target {
+                if self.metadata_storage.is_snapshot_sync_complete(&target)?
+                    && highest_synced_version < target.ledger_info().version()
+                {
+                    return self
+                        .fetch_missing_transaction_outputs_to_target(highest_synced_version, target)
+                        .await;
+                }
+            }
+
+            // Otherwise, ensure the node is not too far behind the target version
+            let num_versions_behind = target_version.saturating_sub(highest_synced_version);
+            let max_num_versions_behind = self
+                .driver_configuration
+                .config
+                .num_versions_to_skip_snapshot_sync;
+            if num_versions_behind < max_num_versions_behind {
+                info!(LogSchema::new(LogEntry::Bootstrapper).message(&format!(
+                    "The node is only {} versions behind the fast sync target version ({}), will skip bootstrapping.",
+                    num_versions_behind, target_version
+                )));
+                self.bootstrapping_complete().await
+            } else {
+                panic!("You are currently {:?} versions behind the fast sync target version ({:?}). This is \
+                        more than the maximum allowed for fast sync ({:?}). If you want to fast sync to the \
+                        target version, delete your storage and restart your node. Otherwise, if you want to \
+                        sync all the missing data, use intelligent syncing mode!",
+                       num_versions_behind, target_version, max_num_versions_behind);
+            }
+        }
+    }
+
+    /// Returns the ledger info that proves the given fast sync target version. This
+    /// is the first epoch ending ledger info at or after the target version or, if
+    /// the target version is in the current epoch, the highest advertised ledger info
+    /// (verified against the current epoch state).
+    fn get_fast_sync_target_ledger_info(
+        &self,
+        target_version: Version,
+        global_data_summary: &GlobalDataSummary,
+    ) -> Result<LedgerInfoWithSignatures, Error> {
+        // Check if the target version is in a completed epoch
+        if let Some(epoch_ending_ledger_info) = self
+            .verified_epoch_states
+            .get_epoch_ending_ledger_info_at_or_after(target_version)
+        {
+            return Ok(epoch_ending_ledger_info);
+        }
+
+        // Otherwise, the target version must be in the current epoch
+        let highest_synced_ledger_info = global_data_summary
+            .advertised_data
+            .highest_synced_ledger_info()
+            .ok_or_else(|| {
+                Error::AdvertisedDataError(
+                    "No highest advertised ledger info found in the network!".into(),
+                )
+            })?;
+        let highest_synced_version = highest_synced_ledger_info.ledger_info().version();
+        if highest_synced_version < target_version {
+            return Err(Error::AdvertisedDataError(format!(
+                "The fast sync target version: {:?} is higher than the highest advertised version: {:?}!",
+                target_version, highest_synced_version
+            )));
+        }
+        self.verified_epoch_states
+            .verify_ledger_info(&highest_synced_ledger_info)?;
+
+        Ok(highest_synced_ledger_info)
+    }
+
+    /// Fetches the transaction outputs from the highest synced version up to
+    /// the ledger info that proved the fast sync target version.
+    async fn fetch_missing_transaction_outputs_to_target(
+        &mut self,
+        highest_synced_version: Version,
+        target_ledger_info: LedgerInfoWithSignatures,
+    ) -> Result<(), Error> {
+        let next_version = highest_synced_version.checked_add(1).ok_or_else(|| {
+            Error::IntegerOverflow("The next output version has overflown!".into())
+        })?;
+        let target_ledger_info_version = target_ledger_info.ledger_info().version();
+        let data_stream = self
+            .streaming_client
+            .get_all_transaction_outputs(
+                next_version,
+                target_ledger_info_version,
+                target_ledger_info_version,
+            )
+            .await?;
+        self.speculative_stream_state = Some(SpeculativeStreamState::new(
+            utils::fetch_latest_epoch_state(self.storage.clone())?,
+            Some(target_ledger_info),
+            highest_synced_version,
+        ));
+        self.active_data_stream = Some(data_stream);
+
+        Ok(())
+    }
+
+    /// Returns true iff the node is fast syncing and is applying the transaction
+    /// outputs up to the ledger info that proved the fast sync target version.
+    fn is_syncing_to_fast_sync_target(&self) -> bool {
+        self.get_bootstrapping_mode().is_fast_sync()
+            && self.get_fast_sync_target_version().is_some()
+            && self.speculative_stream_state.is_some()
+    }
+
     /// Attempts to fetch a data notification from the active stream
     async fn fetch_next_data_notification(&mut self) -> Result<DataNotification, Error> {
         let max_stream_wait_time_ms = self.driver_configuration.config.max_stream_wait_time_ms;
@@ -686,12 +886,13 @@ impl<
 
         // Fetch the data that we're missing
         let target_ledger_info_version = target_ledger_info.ledger_info().version();
+        let version_to_sync = self.get_version_to_sync(&target_ledger_info);
         let data_stream = if self.state_value_syncer.transaction_output_to_sync.is_none() {
             // Fetch the transaction info first, before the states
             self.streaming_client
                 .get_all_transaction_outputs(
-                    target_ledger_info_version,
-                    target_ledger_info_version,
+                    version_to_sync,
+                    version_to_sync,
                     target_ledger_info_version,
                 )
                 .await?
@@ -719,10 +920,7 @@ impl<
             self.state_value_syncer
                 .update_next_state_index_to_process(next_state_index_to_process);
             self.streaming_client
-                .get_all_state_values(
-                    target_ledger_info_version,
-                    Some(next_state_index_to_process),
-                )
+                .get_all_state_values(version_to_sync, Some(next_state_index_to_process))
                 .await?
         };
         self.active_data_stream = Some(data_stream);
@@ -747,7 +945,8 @@ impl<
                 Error::UnexpectedError("No higher epoch ending version known!".into())
             })?;
         let data_stream = match self.get_bootstrapping_mode() {
-            BootstrappingMode::ApplyTransactionOutputsFromGenesis => {
+            BootstrappingMode::ApplyTransactionOutputsFromGenesis
+            | BootstrappingMode::RestoreFromBackup => {
                 self.streaming_client
                     .get_all_transaction_outputs(
                         next_version,
@@ -988,6 +1187,20 @@ impl<
             let version_to_sync = ledger_info_to_sync.ledger_info().version();
             let epoch_change_proofs = if version_to_sync == GENESIS_TRANSACTION_VERSION {
                 vec![ledger_info_to_sync.clone()] // Sync to genesis
+            } else if self.get_fast_sync_target_version().is_some() {
+                // Sync to the target version (only the epochs up to the version are known)
+                let version_to_sync = self.get_version_to_sync(&ledger_info_to_sync);
+                let epoch_change_proofs = self
+                    .verified_epoch_states
+                    .epoch_ending_ledger_infos_up_to(version_to_sync);
+                if epoch_change_proofs.is_empty() {
+                    // The target version is in the genesis epoch
+                    vec![utils::fetch_latest_synced_ledger_info(
+                        self.storage.clone(),
+                    )?]
+                } else {
+                    epoch_change_proofs
+                }
             } else {
                 self.verified_epoch_states.all_epoch_ending_ledger_infos() // Sync beyond genesis
             };
@@ -1122,8 +1335,10 @@ impl<
     ) -> Result<(), Error> {
         // Verify that we're expecting transaction or output payloads
         let bootstrapping_mode = self.get_bootstrapping_mode();
+        let syncing_state_snapshot =
+            bootstrapping_mode.is_fast_sync() && !self.is_syncing_to_fast_sync_target();
         if self.should_fetch_epoch_ending_ledger_infos()
-            || (bootstrapping_mode.is_fast_sync()
+            || (syncing_state_snapshot
                 && self.state_value_syncer.transaction_output_to_sync.is_some())
         {
             self.reset_active_stream(Some(NotificationAndFeedback::new(
@@ -1136,8 +1351,8 @@ impl<
             ));
         }
 
-        // If we're fast syncing, we expect a single transaction info
-        if bootstrapping_mode.is_fast_sync() {
+        // If we're syncing a state snapshot, we expect a single transaction info
+        if syncing_state_snapshot {
             return self
                 .verify_transaction_info_to_sync(
                     notification_metadata.notification_id,
@@ -1176,7 +1391,9 @@ impl<
 
         // Execute/apply and commit the transactions/outputs
         let num_transactions_or_outputs = match bootstrapping_mode {
-            BootstrappingMode::ApplyTransactionOutputsFromGenesis => {
+            BootstrappingMode::ApplyTransactionOutputsFromGenesis
+            | BootstrappingMode::DownloadLatestStates
+            | BootstrappingMode::RestoreFromBackup => {
                 if let Some(transaction_outputs_with_proof) = transaction_outputs_with_proof {
                     utils::apply_transaction_outputs(
                         &mut self.storage_synchronizer,
@@ -1273,7 +1490,7 @@ impl<
     ) -> Result<(), Error> {
         // Verify the payload starting version
         let ledger_info_to_sync = self.get_ledger_info_to_sync()?;
-        let expected_start_version = ledger_info_to_sync.ledger_info().version();
+        let expected_start_version = self.get_version_to_sync(&ledger_info_to_sync);
         let _ = self
             .verify_payload_start_version(
                 notification_id,
@@ -1292,10 +1509,21 @@ impl<
                 .len()
                 == 1
             {
-                match transaction_outputs_with_proof.verify(
-                    ledger_info_to_sync.ledger_info(),
-                    Some(expected_start_version),
-                ) {
+                let verification_result = transaction_outputs_with_proof
+                    .verify(
+                        ledger_info_to_sync.ledger_info(),
+                        Some(expected_start_version),
+                    )
+                    .and_then(|_| {
+                        // The state values can only be synced at a state checkpoint
+                        transaction_outputs_with_proof
+                            .get_output_list_with_proof()
+                            .proof
+                            .transaction_infos[0]
+                            .ensure_state_checkpoint_hash()
+                            .map(|_| ())
+                    });
+                match verification_result {
                     Ok(()) => {
                         self.state_value_syncer
                             .set_transaction_output_to_sync(transaction_outputs_with_proof);
@@ -1381,7 +1609,9 @@ impl<
     ) -> Result<Option<LedgerInfoWithSignatures>, Error> {
         // Calculate the payload end version
         let num_versions = match self.get_bootstrapping_mode() {
-            BootstrappingMode::ApplyTransactionOutputsFromGenesis => {
+            BootstrappingMode::ApplyTransactionOutputsFromGenesis
+            | BootstrappingMode::DownloadLatestStates
+            | BootstrappingMode::RestoreFromBackup => {
                 if let Some(transaction_outputs_with_proof) = transaction_outputs_with_proof {
                     transaction_outputs_with_proof.get_num_outputs()
                 } else {
@@ -1494,6 +1724,16 @@ impl<
         })
     }
 
+    /// Returns the version at which to sync the state values, given the target
+    /// ledger info (i.e., the fast sync target version, or the ledger info version).
+    fn get_version_to_sync(&self, ledger_info_to_sync: &LedgerInfoWithSignatures) -> Version {
+        let ledger_info_version = ledger_info_to_sync.ledger_info().version();
+        match self.get_fast_sync_target_version() {
+            Some(target_version) if target_version <= ledger_info_version => target_version,
+            _ => ledger_info_version,
+        }
+    }
+
     /// Returns the ledger info to sync
     fn get_ledger_info_to_sync(&mut self) -> Result<LedgerInfoWithSignatures, Error> {
         self.state_value_syncer
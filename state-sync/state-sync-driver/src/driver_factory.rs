@@ -10,6 +10,7 @@ use crate::{
         CommitNotification, CommitNotificationListener, ConsensusNotificationHandler,
         ErrorNotificationListener, MempoolNotificationHandler, StorageServiceNotificationHandler,
     },
+    partial_state_verifier::PartialStateVerifier,
     storage_synchronizer::StorageSynchronizer,
 };
 use aptos_config::config::NodeConfig;
@@ -164,6 +165,18 @@ impl DriverFactory {
             waypoint,
         );
 
+        // Create the partial state verifier (if partial state mode is enabled)
+        let partial_state_config = &node_config.storage.partial_state;
+        let partial_state_verifier = partial_state_config.enabled.then(|| {
+            PartialStateVerifier::new(
+                partial_state_config,
+                node_config.state_sync.state_sync_driver,
+                aptos_data_client.clone(),
+                node_config.state_sync.aptos_data_client.response_timeout_ms,
+                storage.reader.clone(),
+            )
+        });
+
         // Create the state sync driver
         let state_sync_driver = StateSyncDriver::new(
             client_notification_listener,
@@ -182,11 +195,17 @@ impl DriverFactory {
             time_service,
         );
 
-        // Spawn the driver
+        // Spawn the driver (and the partial state verifier)
         if let Some(driver_runtime) = &driver_runtime {
             driver_runtime.spawn(state_sync_driver.start_driver());
+            if let Some(partial_state_verifier) = partial_state_verifier {
+                driver_runtime.spawn(partial_state_verifier.start());
+            }
         } else {
             tokio::spawn(state_sync_driver.start_driver());
+            if let Some(partial_state_verifier) = partial_state_verifier {
+                tokio::spawn(partial_state_verifier.start());
+            }
         }
 
         // Create the driver factory
@@ -6,6 +6,8 @@ use crate::{
     metadata_storage::database_schema::{MetadataKey, MetadataSchema, MetadataValue},
 };
 use anyhow::{anyhow, Result};
+use aptos_config::network_id::PeerNetworkId;
+use aptos_data_client::peer_reputation::{PeerReputation, PeerReputationStorage};
 use aptos_logger::prelude::*;
 use aptos_schemadb::{
     batch::SchemaBatch,
@@ -107,10 +109,13 @@ impl PersistentMetadataStorage {
                     ))
                 })?;
         match maybe_metadata_value {
-            Some(metadata_value) => {
-                let MetadataValue::StateSnapshotSync(snapshot_progress) = metadata_value;
+            Some(MetadataValue::StateSnapshotSync(snapshot_progress)) => {
                 Ok(Some(snapshot_progress))
             },
+            Some(metadata_value) => Err(Error::StorageError(format!(
+                "Found an unexpected metadata value for key: {:?}. Value: {:?}",
+                metadata_key, metadata_value
+            ))),
             None => Ok(None),
         }
     }
@@ -227,6 +232,48 @@ impl MetadataStorageInterface for PersistentMetadataStorage {
     }
 }
 
+impl PeerReputationStorage for PersistentMetadataStorage {
+    fn load_peer_reputations(
+        &self,
+    ) -> Result<Vec<(PeerNetworkId, PeerReputation)>, aptos_data_client::error::Error> {
+        let metadata_key = MetadataKey::PeerReputations;
+        let maybe_metadata_value =
+            self.database
+                .get::<MetadataSchema>(&metadata_key)
+                .map_err(|error| {
+                    aptos_data_client::error::Error::UnexpectedErrorEncountered(format!(
+                        "Failed to read metadata value for key: {:?}. Error: {:?}",
+                        metadata_key, error
+                    ))
+                })?;
+        match maybe_metadata_value {
+            Some(MetadataValue::PeerReputations(peer_reputations)) => Ok(peer_reputations),
+            Some(metadata_value) => Err(
+                aptos_data_client::error::Error::UnexpectedErrorEncountered(format!(
+                    "Found an unexpected metadata value for key: {:?}. Value: {:?}",
+                    metadata_key, metadata_value
+                )),
+            ),
+            None => Ok(vec![]),
+        }
+    }
+
+    fn save_peer_reputations(
+        &self,
+        peer_reputations: Vec<(PeerNetworkId, PeerReputation)>,
+    ) -> Result<(), aptos_data_client::error::Error> {
+        let metadata_key = MetadataKey::PeerReputations;
+        let metadata_value = MetadataValue::PeerReputations(peer_reputations);
+        self.commit_key_value(metadata_key, metadata_value)
+            .map_err(|error| {
+                aptos_data_client::error::Error::UnexpectedErrorEncountered(format!(
+                    "Failed to persist the peer reputations! Error: {:?}",
+                    error
+                ))
+            })
+    }
+}
+
 /// A simple struct for recording the progress of a state snapshot sync
 #[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct StateSnapshotProgress {
@@ -253,6 +300,7 @@ pub mod database_schema {
     #[repr(u8)]
     pub enum MetadataKey {
         StateSnapshotSync, // A state snapshot sync that was started
+        PeerReputations,   // The reputations of the peers known to the data client
     }
 
     /// A metadata value that can be inserted into the database
@@ -260,6 +308,7 @@ pub mod database_schema {
     #[repr(u8)]
     pub enum MetadataValue {
         StateSnapshotSync(StateSnapshotProgress), // A state snapshot sync progress marker
+        PeerReputations(Vec<(PeerNetworkId, PeerReputation)>), // The reputations of known peers
     }
 
     impl KeyCodec<MetadataSchema> for MetadataKey {
@@ -26,6 +26,11 @@ pub const NOTIFICATION_CREATE_TO_EXECUTE: &str = "notification_create_to_execute
 pub const NOTIFICATION_CREATE_TO_RECEIVE: &str = "notification_create_to_receive";
 pub const NOTIFICATION_CREATE_TO_UPDATE_LEDGER: &str = "notification_create_to_update_ledger";
 
+/// Partial state verifier metric labels
+pub const PARTIAL_STATE_MISMATCHED_VALUES: &str = "mismatched_state_values";
+pub const PARTIAL_STATE_VERIFIED_VALUES: &str = "verified_state_values";
+pub const PARTIAL_STATE_VERIFICATION_ERRORS: &str = "verification_errors";
+
 /// Storage synchronizer metric labels
 pub const STORAGE_SYNCHRONIZER_PENDING_DATA: &str = "storage_synchronizer_pending_data";
 pub const STORAGE_SYNCHRONIZER_APPLY_CHUNK: &str = "apply_chunk";
@@ -167,6 +172,16 @@ pub static EXECUTING_COMPONENT: Lazy<IntCounterVec> = Lazy::new(|| {
     .unwrap()
 });
 
+/// Counters related to the partial state verifier
+pub static PARTIAL_STATE_VERIFIER: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_state_sync_partial_state_verifier",
+        "Counters related to the partial state verifier",
+        &["label"]
+    )
+    .unwrap()
+});
+
 /// Counter for tracking sizes of data chunks sent to the storage synchronizer
 pub static STORAGE_SYNCHRONIZER_CHUNK_SIZES: Lazy<HistogramVec> = Lazy::new(|| {
     let histogram_opts = histogram_opts!(
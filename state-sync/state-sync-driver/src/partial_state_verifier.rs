@@ -0,0 +1,269 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    error::Error,
+    logging::{LogEntry, LogSchema},
+    metrics,
+};
+use aptos_config::config::{PartialStateConfig, StateSyncDriverConfig};
+use aptos_crypto::HashValue;
+use aptos_data_client::interface::{AptosDataClientInterface, ResponseError};
+use aptos_logger::prelude::*;
+use aptos_storage_interface::DbReader;
+use aptos_types::{
+    access_path::AccessPath,
+    account_address::AccountAddress,
+    state_store::state_key::{inner::StateKeyInner, StateKey},
+    transaction::Version,
+};
+use move_core_types::language_storage::StructTag;
+use std::{
+    collections::{BTreeSet, HashSet},
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
+use tokio::time::interval;
+
+/// A verifier for nodes running in partial state mode. The node keeps the full
+/// state Merkle tree, so every synced transaction output and state root is
+/// verified locally. However, the stored (tracked) state values are never
+/// re-read. Thus, the verifier periodically fetches the values of the tracked
+/// keys touched since the last round (together with sparse Merkle proofs) from
+/// peers, verifies them against the latest state checkpoint root, and checks
+/// that they match the values stored by the node. If the node falls behind,
+/// the remaining versions are carried over to the next round.
+pub struct PartialStateVerifier<DataClient> {
+    aptos_data_client: DataClient,
+    driver_config: StateSyncDriverConfig,
+    last_verified_version: Option<Version>,
+    request_timeout_ms: u64,
+    storage: Arc<dyn DbReader>,
+    tracked_addresses: HashSet<AccountAddress>,
+    tracked_resource_group_paths: HashSet<Vec<u8>>,
+}
+
+impl<DataClient: AptosDataClientInterface + Send + Clone + 'static>
+    PartialStateVerifier<DataClient>
+{
+    pub fn new(
+        partial_state_config: &PartialStateConfig,
+        driver_config: StateSyncDriverConfig,
+        aptos_data_client: DataClient,
+        request_timeout_ms: u64,
+        storage: Arc<dyn DbReader>,
+    ) -> Self {
+        // Identify the paths of the tracked resource groups. Note: invalid
+        // resource groups are rejected by storage when the node starts.
+        let tracked_resource_group_paths = partial_state_config
+            .tracked_resource_groups
+            .iter()
+            .filter_map(|resource_group| StructTag::from_str(resource_group).ok())
+            .map(AccessPath::resource_group_path_vec)
+            .collect();
+
+        Self {
+            aptos_data_client,
+            driver_config,
+            last_verified_version: None,
+            request_timeout_ms,
+            storage,
+            tracked_addresses: partial_state_config.get_tracked_addresses(),
+            tracked_resource_group_paths,
+        }
+    }
+
+    /// Starts the verifier loop (this never returns)
+    pub async fn start(mut self) {
+        info!(LogSchema::new(LogEntry::PartialStateVerifier)
+            .message("Starting the partial state verifier!"));
+
+        let mut verification_interval = interval(Duration::from_millis(
+            self.driver_config.partial_state_verification_interval_ms,
+        ));
+        loop {
+            verification_interval.tick().await;
+            if let Err(error) = self.verify_touched_state_values().await {
+                metrics::increment_counter(
+                    &metrics::PARTIAL_STATE_VERIFIER,
+                    metrics::PARTIAL_STATE_VERIFICATION_ERRORS,
+                );
+                warn!(LogSchema::new(LogEntry::PartialStateVerifier)
+                    .error(&error)
+                    .message("Failed to verify the stored state values!"));
+            }
+        }
+    }
+
+    /// Verifies the tracked state values touched since the last verified
+    /// version (up to the max versions per round), at the latest state
+    /// checkpoint version.
+    async fn verify_touched_state_values(&mut self) -> Result<(), Error> {
+        // Identify the versions to verify
+        let Some(checkpoint_version) = self
+            .storage
+            .get_latest_state_checkpoint_version()
+            .map_err(|error| Error::StorageError(error.to_string()))?
+        else {
+            return Ok(()); // The node has not synced any state yet
+        };
+        let start_version = match self.last_verified_version {
+            Some(last_verified_version) if last_verified_version >= checkpoint_version => {
+                return Ok(()); // There is nothing new to verify
+            },
+            Some(last_verified_version) => last_verified_version + 1,
+            None => checkpoint_version,
+        };
+        let start_version = self.skip_pruned_versions(start_version)?;
+        if start_version > checkpoint_version {
+            return Ok(()); // There is nothing left to verify
+        }
+        let max_versions_per_round = self.driver_config.max_partial_state_versions_per_round;
+        let end_version = checkpoint_version
+            .min(start_version.saturating_add(max_versions_per_round.saturating_sub(1)));
+
+        // Fetch the state root and the tracked keys touched in the versions.
+        // The values are always verified at the latest state checkpoint (as
+        // older values may have since been overwritten).
+        let state_root_hash = self.get_state_checkpoint_hash(checkpoint_version)?;
+        let touched_state_keys = self.get_touched_state_keys(start_version, end_version)?;
+
+        // Verify the state values in batches
+        let touched_state_keys: Vec<_> = touched_state_keys.into_iter().collect();
+        let max_keys_per_request = self.driver_config.max_partial_state_keys_per_request as usize;
+        for state_keys in touched_state_keys.chunks(max_keys_per_request.max(1)) {
+            let response = self
+                .aptos_data_client
+                .get_state_values_with_sparse_proof(
+                    checkpoint_version,
+                    state_keys.to_vec(),
+                    self.request_timeout_ms,
+                )
+                .await
+                .map_err(|error| Error::UnexpectedError(error.to_string()))?;
+            let (context, state_values_with_proof) = response.into_parts();
+
+            // Verify the proofs against the locally verified state root
+            if let Err(error) = state_values_with_proof.verify(state_root_hash, state_keys) {
+                context
+                    .response_callback
+                    .notify_bad_response(ResponseError::ProofVerificationError);
+                return Err(Error::VerificationError(format!(
+                    "The sparse state proofs from the peer are invalid: {:?}",
+                    error
+                )));
+            }
+
+            // Verify that the stored values match the proven values
+            for (state_key, state_value, _) in state_values_with_proof.state_values {
+                let stored_state_value = self
+                    .storage
+                    .get_state_value_by_version(&state_key, checkpoint_version)
+                    .map_err(|error| Error::StorageError(error.to_string()))?;
+                if stored_state_value == state_value {
+                    metrics::increment_counter(
+                        &metrics::PARTIAL_STATE_VERIFIER,
+                        metrics::PARTIAL_STATE_VERIFIED_VALUES,
+                    );
+                } else {
+                    metrics::increment_counter(
+                        &metrics::PARTIAL_STATE_VERIFIER,
+                        metrics::PARTIAL_STATE_MISMATCHED_VALUES,
+                    );
+                    error!(LogSchema::new(LogEntry::PartialStateVerifier).message(&format!(
+                        "The stored value of state key {:?} at version {} does not match the proven value!",
+                        state_key, checkpoint_version
+                    )));
+                }
+            }
+        }
+
+        // Carry over any remaining versions to the next round
+        self.last_verified_version = Some(end_version);
+        if end_version < checkpoint_version {
+            info!(LogSchema::new(LogEntry::PartialStateVerifier).message(&format!(
+                "Verified the state values touched up to version {}. {} versions remain to be verified.",
+                end_version,
+                checkpoint_version - end_version
+            )));
+        }
+        Ok(())
+    }
+
+    /// Returns the first version (at or after the given version) whose write
+    /// set has not been pruned. Pruned versions cannot be verified and are
+    /// skipped.
+    fn skip_pruned_versions(&self, version: Version) -> Result<Version, Error> {
+        let first_write_set_version = self
+            .storage
+            .get_first_write_set_version()
+            .map_err(|error| Error::StorageError(error.to_string()))?
+            .unwrap_or(version);
+        if version < first_write_set_version {
+            warn!(
+                LogSchema::new(LogEntry::PartialStateVerifier).message(&format!(
+                    "The write sets of versions {} to {} have been pruned and cannot be verified!",
+                    version,
+                    first_write_set_version - 1
+                ))
+            );
+        }
+        Ok(version.max(first_write_set_version))
+    }
+
+    /// Returns the state checkpoint hash (i.e., the state root) at the version
+    fn get_state_checkpoint_hash(&self, version: Version) -> Result<HashValue, Error> {
+        let transaction_info = self
+            .storage
+            .get_transaction_info_iterator(version, 1)
+            .and_then(|mut transaction_infos| {
+                transaction_infos.next().transpose().map_err(Into::into)
+            })
+            .map_err(|error| Error::StorageError(error.to_string()))?
+            .ok_or_else(|| {
+                Error::StorageError(format!("Missing transaction info at version {}", version))
+            })?;
+        transaction_info.state_checkpoint_hash().ok_or_else(|| {
+            Error::UnexpectedError(format!("Version {} is not a state checkpoint", version))
+        })
+    }
+
+    /// Returns the tracked state keys written in the given (inclusive) versions
+    fn get_touched_state_keys(
+        &self,
+        start_version: Version,
+        end_version: Version,
+    ) -> Result<BTreeSet<StateKey>, Error> {
+        let write_sets = self
+            .storage
+            .get_write_set_iterator(start_version, end_version - start_version + 1)
+            .map_err(|error| Error::StorageError(error.to_string()))?;
+
+        let mut touched_state_keys = BTreeSet::new();
+        for write_set in write_sets {
+            let write_set = write_set.map_err(|error| Error::StorageError(error.to_string()))?;
+            for (state_key, _) in write_set.write_op_iter() {
+                if self.is_tracked(state_key) {
+                    touched_state_keys.insert(state_key.clone());
+                }
+            }
+        }
+        Ok(touched_state_keys)
+    }
+
+    /// Returns true iff the state key belongs to a tracked (or special)
+    /// address, or to a tracked resource group (at any address).
+    fn is_tracked(&self, state_key: &StateKey) -> bool {
+        match state_key.inner() {
+            StateKeyInner::AccessPath(access_path) => {
+                access_path.address.is_special()
+                    || self.tracked_addresses.contains(&access_path.address)
+                    || self
+                        .tracked_resource_group_paths
+                        .contains(&access_path.path)
+            },
+            StateKeyInner::TableItem { .. } | StateKeyInner::Raw(_) => false,
+        }
+    }
+}
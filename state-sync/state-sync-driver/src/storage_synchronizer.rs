@@ -843,8 +843,12 @@ fn spawn_state_snapshot_receiver<
 ) -> JoinHandle<()> {
     // Create a state snapshot receiver
     let receiver = async move {
-        // Get the target version and expected root hash
-        let version = target_ledger_info.ledger_info().version();
+        // Get the target version and expected root hash. Note: the target
+        // version may be lower than the target ledger info version (e.g., if
+        // the node is fast syncing to a specific historical version).
+        let version = target_output_with_proof
+            .get_first_output_version()
+            .expect("Target transaction output should exist!");
         let expected_root_hash = target_output_with_proof
             .get_output_list_with_proof()
             .proof
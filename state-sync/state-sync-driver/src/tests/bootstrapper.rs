@@ -417,6 +417,137 @@ async fn test_data_stream_state_values() {
         .unwrap();
 }
 
+#[tokio::test]
+async fn test_data_stream_state_values_target_version() {
+    // Create test data
+    let fast_sync_target_version = 5000;
+    let highest_version = 10000;
+    let highest_ledger_info = create_random_epoch_ending_ledger_info(highest_version, 1);
+
+    // Create a driver configuration with a genesis waypoint and a fast sync target version
+    let mut driver_configuration = create_full_node_driver_configuration();
+    driver_configuration.config.bootstrapping_mode = BootstrappingMode::DownloadLatestStates;
+    driver_configuration.config.fast_sync_target_version = Some(fast_sync_target_version);
+
+    // Create the mock streaming client (the target version should be proven
+    // by the first epoch ending ledger info after the target version).
+    let mut mock_streaming_client = create_mock_streaming_client();
+    let (_notification_sender, data_stream_listener) = create_data_stream_listener();
+    mock_streaming_client
+        .expect_get_all_transaction_outputs()
+        .times(1)
+        .with(
+            eq(fast_sync_target_version),
+            eq(fast_sync_target_version),
+            eq(highest_version),
+        )
+        .return_once(move |_, _, _| Ok(data_stream_listener));
+
+    // Create the bootstrapper
+    let (mut bootstrapper, _) =
+        create_bootstrapper(driver_configuration, mock_streaming_client, None, true);
+
+    // Insert an epoch ending ledger info into the verified states of the bootstrapper
+    manipulate_verified_epoch_states(&mut bootstrapper, true, true, Some(highest_version));
+
+    // Create a global data summary
+    let mut global_data_summary = create_global_summary(1);
+    global_data_summary.advertised_data.synced_ledger_infos = vec![highest_ledger_info.clone()];
+
+    // Drive progress to initialize the transaction output stream
+    drive_progress(&mut bootstrapper, &global_data_summary, false)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_data_stream_state_values_target_version_current_epoch() {
+    // Create test data
+    let highest_epoch_ending_version = 1000;
+    let fast_sync_target_version = 5000;
+    let highest_version = 10000;
+    let highest_ledger_info = create_random_epoch_ending_ledger_info(highest_version, 1);
+
+    // Create a driver configuration with a fast sync target version in the current epoch
+    let mut driver_configuration = create_full_node_driver_configuration();
+    driver_configuration.config.bootstrapping_mode = BootstrappingMode::DownloadLatestStates;
+    driver_configuration.config.fast_sync_target_version = Some(fast_sync_target_version);
+
+    // Create the mock streaming client (the target version should be proven
+    // by the highest advertised ledger info in the current epoch).
+    let mut mock_streaming_client = create_mock_streaming_client();
+    let (_notification_sender, data_stream_listener) = create_data_stream_listener();
+    mock_streaming_client
+        .expect_get_all_transaction_outputs()
+        .times(1)
+        .with(
+            eq(fast_sync_target_version),
+            eq(fast_sync_target_version),
+            eq(highest_version),
+        )
+        .return_once(move |_, _, _| Ok(data_stream_listener));
+
+    // Create the bootstrapper
+    let (mut bootstrapper, _) =
+        create_bootstrapper(driver_configuration, mock_streaming_client, None, true);
+
+    // Insert an epoch ending ledger info (before the target) into the verified states
+    manipulate_verified_epoch_states(
+        &mut bootstrapper,
+        true,
+        true,
+        Some(highest_epoch_ending_version),
+    );
+
+    // Create a global data summary
+    let mut global_data_summary = create_global_summary(1);
+    global_data_summary.advertised_data.synced_ledger_infos = vec![highest_ledger_info];
+
+    // Drive progress to initialize the transaction output stream
+    drive_progress(&mut bootstrapper, &global_data_summary, false)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_data_stream_state_values_target_version_not_advertised() {
+    // Create test data
+    let highest_epoch_ending_version = 1000;
+    let fast_sync_target_version = 5000;
+    let highest_ledger_info = create_random_epoch_ending_ledger_info(4000, 1);
+
+    // Create a driver configuration with a fast sync target version beyond the advertised data
+    let mut driver_configuration = create_full_node_driver_configuration();
+    driver_configuration.config.bootstrapping_mode = BootstrappingMode::DownloadLatestStates;
+    driver_configuration.config.fast_sync_target_version = Some(fast_sync_target_version);
+
+    // Create the bootstrapper
+    let (mut bootstrapper, _) = create_bootstrapper(
+        driver_configuration,
+        create_mock_streaming_client(),
+        None,
+        true,
+    );
+
+    // Insert an epoch ending ledger info (before the target) into the verified states
+    manipulate_verified_epoch_states(
+        &mut bootstrapper,
+        true,
+        true,
+        Some(highest_epoch_ending_version),
+    );
+
+    // Create a global data summary
+    let mut global_data_summary = create_global_summary(1);
+    global_data_summary.advertised_data.synced_ledger_infos = vec![highest_ledger_info];
+
+    // Drive progress and verify an advertised data error is returned
+    let error = drive_progress(&mut bootstrapper, &global_data_summary, false)
+        .await
+        .unwrap_err();
+    assert_matches!(error, Error::AdvertisedDataError(_));
+}
+
 #[tokio::test]
 async fn test_data_stream_transactions() {
     // Create test data
@@ -561,6 +692,53 @@ async fn test_data_stream_transaction_outputs() {
         .unwrap();
 }
 
+#[tokio::test]
+async fn test_data_stream_restore_from_backup() {
+    // Create test data
+    let restored_version = 100; // The highest version restored from the backups
+    let highest_version = 200;
+    let highest_ledger_info = create_random_epoch_ending_ledger_info(highest_version, 1);
+
+    // Create a driver configuration with a genesis waypoint and a restore from backups
+    let mut driver_configuration = create_full_node_driver_configuration();
+    driver_configuration.config.bootstrapping_mode = BootstrappingMode::RestoreFromBackup;
+
+    // Create the mock streaming client (outputs should be synced after the restored version)
+    let mut mock_streaming_client = create_mock_streaming_client();
+    let (_notification_sender, data_stream_listener) = create_data_stream_listener();
+    mock_streaming_client
+        .expect_get_all_transaction_outputs()
+        .times(1)
+        .with(
+            eq(restored_version + 1),
+            eq(highest_version),
+            eq(highest_version),
+        )
+        .return_once(move |_, _, _| Ok(data_stream_listener));
+
+    // Create the bootstrapper
+    let mut bootstrapper = create_bootstrapper_with_storage(
+        driver_configuration,
+        mock_streaming_client,
+        MockMetadataStorage::new(),
+        None,
+        restored_version,
+        true,
+    );
+
+    // Insert an epoch ending ledger info into the verified states of the bootstrapper
+    manipulate_verified_epoch_states(&mut bootstrapper, true, true, Some(highest_version));
+
+    // Create a global data summary
+    let mut global_data_summary = create_global_summary(1);
+    global_data_summary.advertised_data.synced_ledger_infos = vec![highest_ledger_info];
+
+    // Drive progress to initialize the transaction output stream
+    drive_progress(&mut bootstrapper, &global_data_summary, false)
+        .await
+        .unwrap();
+}
+
 #[tokio::test]
 async fn test_data_stream_transactions_or_outputs() {
     // Create test data
@@ -1450,6 +1628,175 @@ async fn test_snapshot_sync_lag_panic() {
         .unwrap();
 }
 
+#[tokio::test]
+async fn test_snapshot_sync_target_version_complete() {
+    // Create test data
+    let num_versions_behind = 10000;
+    let highest_version = 1000000;
+    let synced_version = highest_version - num_versions_behind;
+    let highest_ledger_info = create_random_epoch_ending_ledger_info(highest_version, 1);
+
+    // Create a driver configuration with a fast sync target version
+    let mut driver_configuration = create_full_node_driver_configuration();
+    driver_configuration.config.bootstrapping_mode = BootstrappingMode::DownloadLatestStates;
+    driver_configuration.config.fast_sync_target_version = Some(synced_version);
+    driver_configuration
+        .config
+        .num_versions_to_skip_snapshot_sync = num_versions_behind;
+
+    // Create the mock streaming client
+    let mock_streaming_client = create_mock_streaming_client();
+
+    // Create the mock metadata storage
+    let mut metadata_storage = MockMetadataStorage::new();
+    metadata_storage
+        .expect_previous_snapshot_sync_target()
+        .returning(|| Ok(None));
+
+    // Create the bootstrapper
+    let mut bootstrapper = create_bootstrapper_with_storage(
+        driver_configuration,
+        mock_streaming_client,
+        metadata_storage,
+        None,
+        synced_version,
+        true,
+    );
+
+    // Insert an epoch ending ledger info into the verified states of the bootstrapper
+    manipulate_verified_epoch_states(&mut bootstrapper, true, true, Some(highest_version));
+
+    // Create a global data summary
+    let mut global_data_summary = create_global_summary(1);
+    global_data_summary.advertised_data.synced_ledger_infos = vec![highest_ledger_info.clone()];
+
+    // Drive progress to mark bootstrapping complete (the node has already
+    // synced to the target version, so it should not panic).
+    drive_progress(&mut bootstrapper, &global_data_summary, false)
+        .await
+        .unwrap();
+
+    // Verify the bootstrapper has completed
+    assert!(bootstrapper.is_bootstrapped());
+}
+
+#[tokio::test]
+async fn test_snapshot_sync_target_version_apply_outputs() {
+    // Create test data
+    let fast_sync_target_version = 5000;
+    let target_ledger_info_version = 10000;
+    let target_ledger_info = create_random_epoch_ending_ledger_info(target_ledger_info_version, 1);
+
+    // Create a driver configuration with a fast sync target version
+    let mut driver_configuration = create_full_node_driver_configuration();
+    driver_configuration.config.bootstrapping_mode = BootstrappingMode::DownloadLatestStates;
+    driver_configuration.config.fast_sync_target_version = Some(fast_sync_target_version);
+
+    // Create the mock streaming client (the outputs should be applied up
+    // to the ledger info that proved the target version).
+    let mut mock_streaming_client = create_mock_streaming_client();
+    let (_notification_sender, data_stream_listener) = create_data_stream_listener();
+    mock_streaming_client
+        .expect_get_all_transaction_outputs()
+        .times(1)
+        .with(
+            eq(fast_sync_target_version + 1),
+            eq(target_ledger_info_version),
+            eq(target_ledger_info_version),
+        )
+        .return_once(move |_, _, _| Ok(data_stream_listener));
+
+    // Create the mock metadata storage (the snapshot sync to the target is complete)
+    let mut metadata_storage = MockMetadataStorage::new();
+    let previous_target = target_ledger_info.clone();
+    metadata_storage
+        .expect_previous_snapshot_sync_target()
+        .returning(move || Ok(Some(previous_target.clone())));
+    metadata_storage
+        .expect_is_snapshot_sync_complete()
+        .returning(|_| Ok(true));
+
+    // Create the bootstrapper (synced to the target version)
+    let mut bootstrapper = create_bootstrapper_with_storage(
+        driver_configuration,
+        mock_streaming_client,
+        metadata_storage,
+        None,
+        fast_sync_target_version,
+        true,
+    );
+
+    // Insert an epoch ending ledger info into the verified states of the bootstrapper
+    manipulate_verified_epoch_states(
+        &mut bootstrapper,
+        true,
+        true,
+        Some(target_ledger_info_version),
+    );
+
+    // Create a global data summary
+    let mut global_data_summary = create_global_summary(1);
+    global_data_summary.advertised_data.synced_ledger_infos = vec![target_ledger_info];
+
+    // Drive progress to initialize the transaction output stream
+    drive_progress(&mut bootstrapper, &global_data_summary, false)
+        .await
+        .unwrap();
+
+    // Verify the bootstrapper has not completed
+    assert!(!bootstrapper.is_bootstrapped());
+}
+
+#[tokio::test]
+#[should_panic(expected = "versions behind the fast sync target version")]
+async fn test_snapshot_sync_target_version_lag_panic() {
+    // Create test data
+    let num_versions_behind = 10000;
+    let fast_sync_target_version = 1000000;
+    let synced_version = fast_sync_target_version - num_versions_behind;
+    let highest_ledger_info =
+        create_random_epoch_ending_ledger_info(2 * fast_sync_target_version, 1);
+
+    // Create a driver configuration with a fast sync target version (and a small lag allowance)
+    let mut driver_configuration = create_full_node_driver_configuration();
+    driver_configuration.config.bootstrapping_mode = BootstrappingMode::DownloadLatestStates;
+    driver_configuration.config.fast_sync_target_version = Some(fast_sync_target_version);
+    driver_configuration
+        .config
+        .num_versions_to_skip_snapshot_sync = num_versions_behind;
+
+    // Create the mock metadata storage (no snapshot sync was started)
+    let mut metadata_storage = MockMetadataStorage::new();
+    metadata_storage
+        .expect_previous_snapshot_sync_target()
+        .returning(|| Ok(None));
+
+    // Create the bootstrapper
+    let mut bootstrapper = create_bootstrapper_with_storage(
+        driver_configuration,
+        create_mock_streaming_client(),
+        metadata_storage,
+        None,
+        synced_version,
+        true,
+    );
+
+    // Insert an epoch ending ledger info into the verified states of the bootstrapper
+    manipulate_verified_epoch_states(
+        &mut bootstrapper,
+        true,
+        true,
+        Some(2 * fast_sync_target_version),
+    );
+
+    // Create a global data summary
+    let mut global_data_summary = create_global_summary(1);
+    global_data_summary.advertised_data.synced_ledger_infos = vec![highest_ledger_info];
+
+    // Drive progress (this should panic as the node is too far behind the target)
+    let _ = drive_progress(&mut bootstrapper, &global_data_summary, false).await;
+}
+
 #[tokio::test]
 async fn test_waypoint_mismatch() {
     // Create a waypoint
@@ -385,6 +385,7 @@ async fn create_driver_for_tests(
         db_rw.reader.clone(),
         network_client,
         None,
+        None,
     );
 
     // Create the metadata storage
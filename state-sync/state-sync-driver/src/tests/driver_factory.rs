@@ -82,6 +82,7 @@ fn test_new_initialized_configs() {
         db_rw.reader.clone(),
         network_client,
         None,
+        None,
     );
 
     // Create the state sync driver factory
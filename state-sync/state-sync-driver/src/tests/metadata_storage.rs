@@ -8,9 +8,13 @@ use crate::{
     },
     tests::utils::{create_epoch_ending_ledger_info, create_ledger_info_at_version},
 };
+use aptos_config::network_id::{NetworkId, PeerNetworkId};
+use aptos_data_client::peer_reputation::{PeerReputation, PeerReputationStorage};
 use aptos_schemadb::schema::fuzzing::assert_encode_decode;
 use aptos_temppath::TempPath;
+use aptos_types::PeerId;
 use claims::{assert_err, assert_none};
+use std::time::Duration;
 
 #[test]
 fn test_create_then_open() {
@@ -101,6 +105,19 @@ fn test_metadata_schema_encode_decode() {
     );
 }
 
+#[test]
+fn test_peer_reputations_schema_encode_decode() {
+    let mut peer_reputation = PeerReputation::new(1234);
+    peer_reputation.record_misbehaviour("not_useful", 5678, 10);
+    assert_encode_decode::<MetadataSchema>(
+        &MetadataKey::PeerReputations,
+        &MetadataValue::PeerReputations(vec![(
+            PeerNetworkId::new(NetworkId::Public, PeerId::random()),
+            peer_reputation,
+        )]),
+    );
+}
+
 #[test]
 fn test_multiple_reads_and_writes() {
     // Create a new metadata storage
@@ -146,6 +163,52 @@ fn test_multiple_reads_and_writes() {
     }
 }
 
+#[test]
+fn test_peer_reputations_persist_across_reboots() {
+    // Create a new metadata storage
+    let tmp_dir = TempPath::new();
+    let metadata_storage = PersistentMetadataStorage::new(tmp_dir.path());
+
+    // Verify the storage contains no peer reputations
+    assert!(metadata_storage.load_peer_reputations().unwrap().is_empty());
+
+    // Create and save several peer reputations
+    let mut peer_reputations = vec![];
+    for index in 0..10 {
+        let mut peer_reputation = PeerReputation::new(index * 1000);
+        peer_reputation.set_score(index as f64 * 10.0, index * 1000);
+        peer_reputation.record_response_latency(Duration::from_millis(index), index * 1000);
+        peer_reputation.record_misbehaviour("malicious", index * 1000, 10);
+        peer_reputations.push((
+            PeerNetworkId::new(NetworkId::Public, PeerId::random()),
+            peer_reputation,
+        ));
+    }
+    metadata_storage
+        .save_peer_reputations(peer_reputations.clone())
+        .unwrap();
+
+    // Insert a new state value entry (to ensure the keys don't interfere)
+    let target_ledger_info = create_ledger_info_at_version(100);
+    metadata_storage
+        .update_last_persisted_state_value_index(&target_ledger_info, 10101, false)
+        .unwrap();
+
+    // Drop the handle to the storage (mimic a reboot)
+    drop(metadata_storage);
+
+    // Reopen the storage and verify the peer reputations and snapshot progress
+    let metadata_storage = PersistentMetadataStorage::new(tmp_dir.path());
+    assert_eq!(
+        peer_reputations,
+        metadata_storage.load_peer_reputations().unwrap()
+    );
+    assert_eq!(
+        Some(target_ledger_info),
+        metadata_storage.previous_snapshot_sync_target().unwrap()
+    );
+}
+
 #[test]
 fn test_writes_to_different_targets() {
     // Create a new metadata storage
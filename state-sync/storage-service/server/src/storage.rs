@@ -15,7 +15,11 @@ use aptos_storage_service_types::{
 use aptos_types::{
     epoch_change::EpochChangeProof,
     ledger_info::LedgerInfoWithSignatures,
-    state_store::state_value::StateValueChunkWithProof,
+    proof::SparseMerkleProof,
+    state_store::{
+        state_key::StateKey,
+        state_value::{StateValue, StateValueChunkWithProof, StateValuesWithSparseProof},
+    },
     transaction::{TransactionListWithProofV2, TransactionOutputListWithProofV2, Version},
 };
 use serde::Serialize;
@@ -99,6 +103,15 @@ pub trait StorageReaderInterface: Clone + Send + 'static {
         start_index: u64,
         end_index: u64,
     ) -> aptos_storage_service_types::Result<StateValueChunkWithProof, Error>;
+
+    /// Returns the state values of the given state keys at the specified
+    /// version, each with a sparse proof. Unlike state value chunks, all
+    /// requested state values are always returned (or the request fails).
+    fn get_state_values_with_sparse_proof(
+        &self,
+        version: u64,
+        state_keys: &[StateKey],
+    ) -> aptos_storage_service_types::Result<StateValuesWithSparseProof, Error>;
 }
 
 /// The underlying implementation of the StorageReaderInterface, used by the
@@ -117,14 +130,38 @@ impl StorageReader {
         Self { config, storage }
     }
 
+    /// Returns an error if the node only stores partial state (i.e., it cannot
+    /// serve state values, as the values of untracked accounts are missing).
+    fn ensure_full_state(&self) -> aptos_storage_service_types::Result<(), Error> {
+        let partial_state_enabled = self
+            .storage
+            .is_partial_state_enabled()
+            .map_err(|error| Error::StorageErrorEncountered(error.to_string()))?;
+        if partial_state_enabled {
+            return Err(Error::InvalidRequest(
+                "State values cannot be served by a node in partial state mode!".into(),
+            ));
+        }
+        Ok(())
+    }
+
     /// Returns the state values range held in the database (lowest to highest).
     /// Note: it is currently assumed that if a node contains a transaction at a
-    /// version, V, the node also contains all state values at V.
+    /// version, V, the node also contains all state values at V (unless the node
+    /// only stores partial state, in which case no state values are advertised).
     fn fetch_state_values_range(
         &self,
         latest_version: Version,
         transactions_range: &Option<CompleteDataRange<Version>>,
     ) -> aptos_storage_service_types::Result<Option<CompleteDataRange<Version>>, Error> {
+        let partial_state_enabled = self
+            .storage
+            .is_partial_state_enabled()
+            .map_err(|error| Error::StorageErrorEncountered(error.to_string()))?;
+        if partial_state_enabled {
+            return Ok(None);
+        }
+
         let pruner_enabled = self
             .storage
             .is_state_merkle_pruner_enabled()
@@ -500,6 +537,7 @@ impl StorageReaderInterface for StorageReader {
         &self,
         version: u64,
     ) -> aptos_storage_service_types::Result<u64, Error> {
+        self.ensure_full_state()?;
         let number_of_states = self
             .storage
             .get_state_item_count(version)
@@ -513,6 +551,9 @@ impl StorageReaderInterface for StorageReader {
         start_index: u64,
         end_index: u64,
     ) -> aptos_storage_service_types::Result<StateValueChunkWithProof, Error> {
+        // Verify that the node stores all state values
+        self.ensure_full_state()?;
+
         // Calculate the number of state values to fetch
         let expected_num_state_values = inclusive_range_len(start_index, end_index)?;
         let max_num_state_values = self.config.max_state_chunk_size;
@@ -558,6 +599,57 @@ impl StorageReaderInterface for StorageReader {
             version, start_index, end_index
         )))
     }
+
+    fn get_state_values_with_sparse_proof(
+        &self,
+        version: u64,
+        state_keys: &[StateKey],
+    ) -> aptos_storage_service_types::Result<StateValuesWithSparseProof, Error> {
+        // Verify that the node stores all state values
+        self.ensure_full_state()?;
+
+        // Verify the number of requested state values
+        let max_state_chunk_size = self.config.max_state_chunk_size;
+        if state_keys.len() as u64 > max_state_chunk_size {
+            return Err(Error::InvalidRequest(format!(
+                "Too many state values requested! Requested: {:?}, max: {:?}",
+                state_keys.len(),
+                max_state_chunk_size
+            )));
+        }
+
+        // Fetch each state value with a proof
+        let state_values = state_keys
+            .iter()
+            .map(|state_key| {
+                self.storage
+                    .get_state_value_with_proof_by_version(state_key, version)
+                    .map(|(state_value, proof)| (state_key.clone(), state_value, proof))
+                    .map_err(|error| Error::StorageErrorEncountered(error.to_string()))
+            })
+            .collect::<aptos_storage_service_types::Result<Vec<_>, Error>>()?;
+        let state_values_with_sparse_proof = StateValuesWithSparseProof {
+            version,
+            state_values,
+        };
+
+        // Verify that the response fits into a single network frame
+        let max_network_chunk_bytes = self.config.max_network_chunk_bytes;
+        let (overflow_frame, num_bytes) =
+            check_overflow_network_frame(&state_values_with_sparse_proof, max_network_chunk_bytes)?;
+        if overflow_frame {
+            return Err(Error::UnexpectedErrorEncountered(format!(
+                "Unable to serve the get_state_values_with_sparse_proof request! Version: {:?}, \
+                number of states: {:?}, number of bytes: {:?}. The data cannot fit into a single \
+                network frame!",
+                version,
+                state_keys.len(),
+                num_bytes
+            )));
+        }
+
+        Ok(state_values_with_sparse_proof)
+    }
 }
 
 // A simple macro that wraps each storage read call with a timer
@@ -601,6 +693,8 @@ impl DbReader for TimedStorageReader {
     timed_read!(
         fn is_state_merkle_pruner_enabled(&self) -> StorageResult<bool>;
 
+        fn is_partial_state_enabled(&self) -> StorageResult<bool>;
+
         fn get_epoch_snapshot_prune_window(&self) -> StorageResult<usize>;
 
         fn get_first_txn_version(&self) -> StorageResult<Option<Version>>;
@@ -638,6 +732,12 @@ impl DbReader for TimedStorageReader {
             start_idx: usize,
             chunk_size: usize,
         ) -> StorageResult<StateValueChunkWithProof>;
+
+        fn get_state_value_with_proof_by_version(
+            &self,
+            state_key: &StateKey,
+            version: Version,
+        ) -> StorageResult<(Option<StateValue>, SparseMerkleProof)>;
     );
 }
 
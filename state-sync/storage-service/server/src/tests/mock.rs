@@ -355,6 +355,8 @@ mock! {
 
         fn is_state_merkle_pruner_enabled(&self) -> aptos_storage_interface::Result<bool>;
 
+        fn is_partial_state_enabled(&self) -> aptos_storage_interface::Result<bool>;
+
         fn get_persisted_auxiliary_info_iterator(
             &self,
             start_version: Version,
@@ -392,7 +394,11 @@ pub fn create_mock_db_with_summary_updates(
     db_reader
 }
 
-/// Creates a mock database reader
+/// Creates a mock database reader (that stores the full state)
 pub fn create_mock_db_reader() -> MockDatabaseReader {
-    MockDatabaseReader::new()
+    let mut db_reader = MockDatabaseReader::new();
+    db_reader
+        .expect_is_partial_state_enabled()
+        .returning(|| Ok(false));
+    db_reader
 }
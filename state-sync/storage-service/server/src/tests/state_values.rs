@@ -9,15 +9,15 @@ use crate::tests::{
 use aptos_config::config::StorageServiceConfig;
 use aptos_crypto::hash::HashValue;
 use aptos_storage_service_types::{
-    requests::{DataRequest, StateValuesWithProofRequest},
+    requests::{DataRequest, StateValuesWithProofRequest, StateValuesWithSparseProofRequest},
     responses::{DataResponse, StorageServiceResponse},
     StorageServiceError,
 };
 use aptos_types::{
-    proof::definition::SparseMerkleRangeProof,
+    proof::definition::{SparseMerkleProof, SparseMerkleRangeProof},
     state_store::{
         state_key::StateKey,
-        state_value::{StateValue, StateValueChunkWithProof},
+        state_value::{StateValue, StateValueChunkWithProof, StateValuesWithSparseProof},
     },
 };
 use bytes::Bytes;
@@ -176,6 +176,84 @@ async fn test_get_states_with_proof_not_serviceable() {
     }
 }
 
+#[tokio::test]
+async fn test_get_states_with_sparse_proof() {
+    // Create test data
+    let version = 101;
+    let state_keys_and_values: Vec<_> = (0..10)
+        .map(|index| {
+            let state_key = StateKey::raw(&[index]);
+            let state_value = (index % 2 == 0).then(|| StateValue::new_legacy(vec![index].into()));
+            (state_key, state_value)
+        })
+        .collect();
+
+    // Create the mock db reader
+    let mut db_reader = mock::create_mock_db_reader();
+    for (state_key, state_value) in state_keys_and_values.clone() {
+        db_reader
+            .expect_get_state_value_with_proof_by_version()
+            .times(1)
+            .with(eq(state_key), eq(version))
+            .returning(move |_, _| Ok((state_value.clone(), SparseMerkleProof::new(None, vec![]))));
+    }
+
+    // Create the storage client and server
+    let (mut mock_client, mut service, _, _, _) = MockClient::new(Some(db_reader), None);
+    utils::update_storage_server_summary(&mut service, version, 10);
+    tokio::spawn(service.start());
+
+    // Process a request to fetch the states with sparse proofs
+    let state_keys = state_keys_and_values
+        .iter()
+        .map(|(state_key, _)| state_key.clone())
+        .collect();
+    let response = get_state_values_with_sparse_proof(&mut mock_client, version, state_keys)
+        .await
+        .unwrap();
+
+    // Verify the response is correct (i.e., all states are returned in order)
+    let expected_state_values = state_keys_and_values
+        .into_iter()
+        .map(|(state_key, state_value)| {
+            (state_key, state_value, SparseMerkleProof::new(None, vec![]))
+        })
+        .collect();
+    assert_eq!(
+        response.get_data_response().unwrap(),
+        DataResponse::StateValuesWithSparseProof(StateValuesWithSparseProof {
+            version,
+            state_values: expected_state_values,
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_get_states_with_sparse_proof_invalid() {
+    // Create the storage client and server
+    let version = 101;
+    let (mut mock_client, mut service, _, _, _) = MockClient::new(None, None);
+    utils::update_storage_server_summary(&mut service, version, 10);
+    tokio::spawn(service.start());
+
+    // Verify that requests for too many states are rejected
+    let max_state_chunk_size = StorageServiceConfig::default().max_state_chunk_size;
+    let state_keys = (0..=max_state_chunk_size)
+        .map(|index| StateKey::raw(&index.to_le_bytes()))
+        .collect();
+    let response = get_state_values_with_sparse_proof(&mut mock_client, version, state_keys)
+        .await
+        .unwrap_err();
+    assert_matches!(response, StorageServiceError::InvalidRequest(_));
+
+    // Verify that requests for unavailable versions are not serviceable
+    let response =
+        get_state_values_with_sparse_proof(&mut mock_client, version + 1, vec![StateKey::raw(&[])])
+            .await
+            .unwrap_err();
+    assert_matches!(response, StorageServiceError::InvalidRequest(_));
+}
+
 /// Creates a set of state keys and values using the specified number and size
 fn create_state_keys_and_values(
     num_keys_and_values: u64,
@@ -308,3 +386,17 @@ async fn get_states_with_proof_network_limit(network_limit_bytes: u64) {
         }
     }
 }
+
+/// Sends a state values with sparse proof request and processes the response
+async fn get_state_values_with_sparse_proof(
+    mock_client: &mut MockClient,
+    version: u64,
+    state_keys: Vec<StateKey>,
+) -> Result<StorageServiceResponse, StorageServiceError> {
+    let data_request =
+        DataRequest::GetStateValuesWithSparseProof(StateValuesWithSparseProofRequest {
+            version,
+            state_keys,
+        });
+    utils::send_storage_request(mock_client, false, data_request).await
+}
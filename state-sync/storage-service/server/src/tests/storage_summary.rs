@@ -2,8 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    error::Error,
     refresh_cached_storage_summary,
-    storage::StorageReader,
+    storage::{StorageReader, StorageReaderInterface},
     tests::{
         mock,
         mock::{MockClient, MockDatabaseReader},
@@ -30,6 +31,49 @@ use tokio::time::timeout;
 // The maximum number of seconds to wait for a cache update notification
 const MAX_CACHE_UPDATE_NOTIFICATION_WAIT_SECS: u64 = 10;
 
+#[test]
+fn test_partial_state_summary() {
+    // Create a mock storage reader for a node in partial state mode
+    let lowest_version = 11;
+    let highest_ledger_info = utils::create_test_ledger_info_with_sigs(10, 1000);
+    let mut db_reader = MockDatabaseReader::new();
+    db_reader
+        .expect_get_latest_ledger_info()
+        .returning(move || Ok(highest_ledger_info.clone()));
+    db_reader
+        .expect_get_first_txn_version()
+        .returning(move || Ok(Some(lowest_version)));
+    db_reader
+        .expect_get_first_write_set_version()
+        .returning(move || Ok(Some(lowest_version)));
+    db_reader
+        .expect_is_partial_state_enabled()
+        .returning(|| Ok(true));
+    let storage_reader = StorageReader::new(StorageServiceConfig::default(), Arc::new(db_reader));
+
+    // Verify that no state values are advertised (but transactions still are)
+    let data_summary = storage_reader.get_data_summary().unwrap();
+    assert_eq!(data_summary.states, None);
+    assert_eq!(
+        data_summary.transactions,
+        Some(CompleteDataRange::new(lowest_version, 1000).unwrap())
+    );
+
+    // Verify that all state value requests are rejected
+    assert!(matches!(
+        storage_reader.get_number_of_states(1000),
+        Err(Error::InvalidRequest(_))
+    ));
+    assert!(matches!(
+        storage_reader.get_state_value_chunk_with_proof(1000, 0, 10),
+        Err(Error::InvalidRequest(_))
+    ));
+    assert!(matches!(
+        storage_reader.get_state_values_with_sparse_proof(1000, &[]),
+        Err(Error::InvalidRequest(_))
+    ));
+}
+
 #[tokio::test]
 async fn test_refresh_cached_storage_summary() {
     // Create test data
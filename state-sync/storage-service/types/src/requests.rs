@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::COMPRESSION_SUFFIX_LABEL;
-use aptos_types::transaction::Version;
+use aptos_types::{state_store::state_key::StateKey, transaction::Version};
 use serde::{Deserialize, Serialize};
 
 /// A storage service request.
@@ -53,6 +53,9 @@ pub enum DataRequest {
     GetTransactionDataWithProof(GetTransactionDataWithProofRequest), // Fetches transaction data with a proof
     GetNewTransactionDataWithProof(GetNewTransactionDataWithProofRequest), // Optimistically fetches new transaction data with a proof
     SubscribeTransactionDataWithProof(SubscribeTransactionDataWithProofRequest), // Subscribes to transaction data with a proof
+
+    // All the requests listed below are only sent by nodes in partial state mode.
+    GetStateValuesWithSparseProof(StateValuesWithSparseProofRequest), // Fetches a set of states with a sparse proof per state
 }
 
 impl DataRequest {
@@ -118,6 +121,9 @@ impl DataRequest {
                     },
                 }
             },
+
+            // Partial state requests
+            Self::GetStateValuesWithSparseProof(_) => "get_state_values_with_sparse_proof",
         }
     }
 
@@ -347,6 +353,14 @@ pub struct StateValuesWithProofRequest {
     pub end_index: u64,   // The index to stop fetching state values (inclusive)
 }
 
+/// A storage service request for fetching a set of state values (each with
+/// a sparse proof) at a specified version.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct StateValuesWithSparseProofRequest {
+    pub version: u64,              // The version to fetch the state values at
+    pub state_keys: Vec<StateKey>, // The keys of the state values to fetch
+}
+
 /// A storage service request for fetching a transaction output list with a
 /// corresponding proof.
 #[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
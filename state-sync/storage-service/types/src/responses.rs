@@ -7,11 +7,11 @@ use crate::{
             GetEpochEndingLedgerInfos, GetNewTransactionDataWithProof,
             GetNewTransactionOutputsWithProof, GetNewTransactionsOrOutputsWithProof,
             GetNewTransactionsWithProof, GetNumberOfStatesAtVersion, GetServerProtocolVersion,
-            GetStateValuesWithProof, GetStorageServerSummary, GetTransactionDataWithProof,
-            GetTransactionOutputsWithProof, GetTransactionsOrOutputsWithProof,
-            GetTransactionsWithProof, SubscribeTransactionDataWithProof,
-            SubscribeTransactionOutputsWithProof, SubscribeTransactionsOrOutputsWithProof,
-            SubscribeTransactionsWithProof,
+            GetStateValuesWithProof, GetStateValuesWithSparseProof, GetStorageServerSummary,
+            GetTransactionDataWithProof, GetTransactionOutputsWithProof,
+            GetTransactionsOrOutputsWithProof, GetTransactionsWithProof,
+            SubscribeTransactionDataWithProof, SubscribeTransactionOutputsWithProof,
+            SubscribeTransactionsOrOutputsWithProof, SubscribeTransactionsWithProof,
         },
         TransactionDataRequestType,
     },
@@ -26,7 +26,7 @@ use aptos_time_service::{TimeService, TimeServiceTrait};
 use aptos_types::{
     epoch_change::EpochChangeProof,
     ledger_info::LedgerInfoWithSignatures,
-    state_store::state_value::StateValueChunkWithProof,
+    state_store::state_value::{StateValueChunkWithProof, StateValuesWithSparseProof},
     transaction::{
         TransactionListWithProof, TransactionListWithProofV2, TransactionOutputListWithProof,
         TransactionOutputListWithProofV2, Version,
@@ -70,7 +70,13 @@ pub enum StorageServiceResponse {
 }
 
 impl StorageServiceResponse {
-    /// Creates a new response and performs compression if required
+    /// Creates a new response and performs compression if required.
+    ///
+    /// Note: responses are always compressed using lz4 (and not zstd). Clients
+    /// only request compression with a flag (and decompress using lz4), so zstd
+    /// would require a new request and response format. Moreover, a large part
+    /// of each response (e.g., hashes, signatures and proofs) is incompressible,
+    /// so the better ratio of zstd doesn't justify its CPU cost when serving.
     pub fn new(data_response: DataResponse, perform_compression: bool) -> Result<Self, Error> {
         if perform_compression {
             // Serialize and compress the raw data
@@ -158,6 +164,9 @@ pub enum DataResponse {
     // TODO: eventually we should deprecate all the old response types.
     TransactionDataWithProof(TransactionDataWithProofResponse),
     NewTransactionDataWithProof(NewTransactionDataWithProofResponse),
+
+    // All the responses listed below are only sent to nodes in partial state mode.
+    StateValuesWithSparseProof(StateValuesWithSparseProof),
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -216,6 +225,9 @@ impl DataResponse {
                     },
                 }
             },
+
+            // Partial state responses
+            Self::StateValuesWithSparseProof(_) => "state_values_with_sparse_proof",
         }
     }
 }
@@ -253,6 +265,21 @@ impl TryFrom<StorageServiceResponse> for StateValueChunkWithProof {
     }
 }
 
+impl TryFrom<StorageServiceResponse> for StateValuesWithSparseProof {
+    type Error = crate::responses::Error;
+
+    fn try_from(response: StorageServiceResponse) -> crate::Result<Self, Self::Error> {
+        let data_response = response.get_data_response()?;
+        match data_response {
+            DataResponse::StateValuesWithSparseProof(inner) => Ok(inner),
+            _ => Err(Error::UnexpectedResponseError(format!(
+                "expected state_values_with_sparse_proof, found {}",
+                data_response.get_label()
+            ))),
+        }
+    }
+}
+
 impl TryFrom<StorageServiceResponse> for EpochChangeProof {
     type Error = crate::responses::Error;
 
@@ -719,6 +746,15 @@ impl DataSummary {
                 time_service,
                 self.synced_ledger_info.as_ref(),
             ),
+
+            // Partial state requests
+            GetStateValuesWithSparseProof(request) => {
+                let can_serve_states = self
+                    .states
+                    .map(|range| range.contains(request.version))
+                    .unwrap_or(false);
+                can_serve_states && self.can_create_proof(request.version)
+            },
         }
     }
 
@@ -941,6 +941,10 @@ impl DbReader for FakeAptosDB {
         self.inner.is_state_merkle_pruner_enabled()
     }
 
+    fn is_partial_state_enabled(&self) -> Result<bool> {
+        self.inner.is_partial_state_enabled()
+    }
+
     fn get_epoch_snapshot_prune_window(&self) -> Result<usize> {
         self.inner.get_epoch_snapshot_prune_window()
     }
@@ -813,6 +813,12 @@ impl DbReader for AptosDB {
         })
     }
 
+    fn is_partial_state_enabled(&self) -> Result<bool> {
+        gauged_api("is_partial_state_enabled", || {
+            Ok(self.state_store.partial_state_filter.is_some())
+        })
+    }
+
     fn get_epoch_snapshot_prune_window(&self) -> Result<usize> {
         gauged_api("get_state_prune_window", || {
             Ok(self
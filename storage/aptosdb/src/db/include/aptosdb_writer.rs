@@ -108,6 +108,19 @@ impl DbWriter for AptosDB {
                 num_transaction_infos
             );
 
+            // Ensure the latest ledger info doesn't go beyond the snapshot version. Note: if
+            // the snapshot is below the ledger info that proves it (e.g., when fast syncing to
+            // a historical version), state sync commits that ledger info with the transaction
+            // outputs up to it (after the snapshot is finalized).
+            if let Some(latest_ledger_info) = ledger_infos.last() {
+                ensure!(
+                    latest_ledger_info.ledger_info().version() <= version,
+                    "The latest ledger info version ({}) is beyond the snapshot version ({})!",
+                    latest_ledger_info.ledger_info().version(),
+                    version
+                );
+            }
+
             // TODO(joshlind): include confirm_or_save_frozen_subtrees in the change set
             // bundle below.
 
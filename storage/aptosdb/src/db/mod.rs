@@ -24,11 +24,12 @@ use crate::{
     },
     state_kv_db::StateKvDb,
     state_merkle_db::StateMerkleDb,
-    state_store::StateStore,
+    state_store::{partial_state::PartialStateFilter, StateStore},
     transaction_store::TransactionStore,
 };
 use aptos_config::config::{
-    PrunerConfig, RocksdbConfig, RocksdbConfigs, StorageDirPaths, NO_OP_STORAGE_PRUNER_CONFIG,
+    PartialStateConfig, PrunerConfig, RocksdbConfig, RocksdbConfigs, StorageDirPaths,
+    NO_OP_STORAGE_PRUNER_CONFIG,
 };
 use aptos_crypto::HashValue;
 use aptos_db_indexer::{db_indexer::InternalIndexerDB, Indexer};
@@ -194,6 +195,24 @@ impl AptosDB {
         Ok(())
     }
 
+    /// Enables partial state mode, i.e., only the state values of the tracked
+    /// accounts and resource groups are stored. This must be done before the
+    /// DB is shared (e.g., wrapped in a `DbReaderWriter`).
+    pub fn enable_partial_state(&mut self, config: &PartialStateConfig) -> Result<()> {
+        let partial_state_filter = PartialStateFilter::new(config)?;
+        let state_store = Arc::get_mut(&mut self.state_store).ok_or_else(|| {
+            AptosDbError::Other("Partial state mode must be enabled before sharing the DB!".into())
+        })?;
+        state_store.partial_state_filter = Some(partial_state_filter);
+
+        info!(
+            tracked_accounts = config.tracked_accounts.len(),
+            tracked_resource_groups = config.tracked_resource_groups.len(),
+            "Enabled partial state mode."
+        );
+        Ok(())
+    }
+
     /// Gets an instance of `BackupHandler` for data backup purpose.
     pub fn get_backup_handler(&self) -> BackupHandler {
         BackupHandler::new(Arc::clone(&self.state_store), Arc::clone(&self.ledger_db))
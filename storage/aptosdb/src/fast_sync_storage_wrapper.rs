@@ -59,6 +59,9 @@ impl FastSyncStorageWrapper {
         if let Some(sender) = update_sender {
             db_main.add_version_update_subscriber(sender)?;
         }
+        if config.storage.partial_state.enabled {
+            db_main.enable_partial_state(&config.storage.partial_state)?;
+        }
 
         let mut db_dir = config.storage.dir();
         // when the db is empty and configured to do fast sync, we will create a second DB
@@ -21,7 +21,10 @@ use crate::{
     state_kv_db::StateKvDb,
     state_merkle_db::StateMerkleDb,
     state_restore::{StateSnapshotRestore, StateSnapshotRestoreMode, StateValueWriter},
-    state_store::{buffered_state::BufferedState, persisted_state::PersistedState},
+    state_store::{
+        buffered_state::BufferedState, partial_state::PartialStateFilter,
+        persisted_state::PersistedState,
+    },
     utils::{
         iterators::PrefixedStateValueIterator,
         truncation_helper::{
@@ -88,6 +91,7 @@ mod state_merkle_batch_committer;
 mod state_snapshot_committer;
 
 pub mod hot_state;
+pub(crate) mod partial_state;
 mod persisted_state;
 #[cfg(test)]
 mod tests;
@@ -124,6 +128,8 @@ pub(crate) struct StateStore {
     persisted_state: PersistedState,
     buffered_state_target_items: usize,
     internal_indexer_db: Option<InternalIndexerDB>,
+    /// If set, only the state values accepted by the filter are stored (i.e., partial state mode)
+    pub(crate) partial_state_filter: Option<PartialStateFilter>,
 }
 
 impl Deref for StateStore {
@@ -359,6 +365,7 @@ impl StateStore {
             current_state,
             persisted_state,
             internal_indexer_db,
+            partial_state_filter: None,
         }
     }
 
@@ -747,6 +754,7 @@ impl StateStore {
             .try_for_each(|(batch, updates)| {
                 updates
                     .iter()
+                    .filter(|(key, _)| self.should_store_value(key))
                     .filter_map(|(key, update)| {
                         update
                             .state_op
@@ -958,6 +966,9 @@ impl StateStore {
         enable_sharding: bool,
     ) -> Result<()> {
         values.iter().for_each(|((key, version), value)| {
+            if !self.should_store_value(key) {
+                return; // The value is not tracked (i.e., partial state mode)
+            }
             let shard_id = key.get_shard_id();
             assert!(
                 shard_id < NUM_STATE_SHARDS,
@@ -977,6 +988,14 @@ impl StateStore {
         Ok(())
     }
 
+    /// Returns true iff the value of the given state key should be stored
+    /// (i.e., partial state mode is disabled, or the key is tracked).
+    fn should_store_value(&self, state_key: &StateKey) -> bool {
+        self.partial_state_filter
+            .as_ref()
+            .is_none_or(|filter| filter.is_tracked(state_key))
+    }
+
     pub fn get_root_hash(&self, version: Version) -> Result<HashValue> {
         self.state_merkle_db.get_root_hash(version)
     }
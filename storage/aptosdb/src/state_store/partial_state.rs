@@ -0,0 +1,127 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_config::config::PartialStateConfig;
+use aptos_storage_interface::{AptosDbError, Result};
+use aptos_types::{
+    access_path::AccessPath,
+    account_address::AccountAddress,
+    state_store::state_key::{inner::StateKeyInner, StateKey},
+};
+use move_core_types::language_storage::StructTag;
+use std::{collections::HashSet, str::FromStr};
+
+/// Determines which state values are stored by a node running in partial
+/// state mode (see [`PartialStateConfig`]). The state Merkle tree is always
+/// updated for all keys, so the filter only applies to the state values.
+pub struct PartialStateFilter {
+    tracked_addresses: HashSet<AccountAddress>, // The tracked accounts and their primary APT stores
+    tracked_resource_group_paths: HashSet<Vec<u8>>, // The serialized resource group paths
+}
+
+impl PartialStateFilter {
+    pub fn new(config: &PartialStateConfig) -> Result<Self> {
+        let tracked_resource_group_paths = config
+            .tracked_resource_groups
+            .iter()
+            .map(|resource_group| {
+                StructTag::from_str(resource_group)
+                    .map(AccessPath::resource_group_path_vec)
+                    .map_err(|error| {
+                        AptosDbError::Other(format!(
+                            "Invalid tracked resource group: {}. Error: {:?}",
+                            resource_group, error
+                        ))
+                    })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            tracked_addresses: config.get_tracked_addresses(),
+            tracked_resource_group_paths,
+        })
+    }
+
+    /// Returns true iff the value of the given state key should be stored.
+    /// Special addresses (e.g., the framework) are always tracked, and so are
+    /// the primary APT fungible stores of the tracked accounts (to serve their
+    /// balances). Other derived objects (e.g., the primary stores of other
+    /// fungible assets) and table items are never tracked, as they cannot be
+    /// attributed to an account without reading their (untracked) values.
+    pub fn is_tracked(&self, state_key: &StateKey) -> bool {
+        match state_key.inner() {
+            StateKeyInner::AccessPath(access_path) => {
+                access_path.address.is_special()
+                    || self.tracked_addresses.contains(&access_path.address)
+                    || self
+                        .tracked_resource_group_paths
+                        .contains(&access_path.path)
+            },
+            StateKeyInner::TableItem { .. } | StateKeyInner::Raw(_) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PartialStateFilter;
+    use aptos_config::config::PartialStateConfig;
+    use aptos_types::{
+        account_address::{create_derived_object_address, AccountAddress},
+        account_config::primary_apt_store,
+        state_store::{state_key::StateKey, table::TableHandle},
+    };
+    use move_core_types::language_storage::StructTag;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_partial_state_filter() {
+        // Create a filter that tracks a single account and resource group
+        let tracked_account = AccountAddress::random();
+        let tracked_resource_group = "0x1::object::ObjectGroup";
+        let partial_state_config = PartialStateConfig {
+            enabled: true,
+            tracked_accounts: vec![tracked_account],
+            tracked_resource_groups: vec![tracked_resource_group.into()],
+        };
+        let filter = PartialStateFilter::new(&partial_state_config).unwrap();
+
+        // Verify that all values of the tracked (and special) accounts are tracked
+        let resource_tag = StructTag::from_str("0x1::account::Account").unwrap();
+        for address in [tracked_account, AccountAddress::ONE] {
+            let state_key = StateKey::resource(&address, &resource_tag).unwrap();
+            assert!(filter.is_tracked(&state_key));
+        }
+
+        // Verify that untracked accounts are only tracked for the resource group
+        let untracked_account = AccountAddress::random();
+        let state_key = StateKey::resource(&untracked_account, &resource_tag).unwrap();
+        assert!(!filter.is_tracked(&state_key));
+        let resource_group_tag = StructTag::from_str(tracked_resource_group).unwrap();
+        let state_key = StateKey::resource_group(&untracked_account, &resource_group_tag);
+        assert!(filter.is_tracked(&state_key));
+
+        // Verify that only the primary APT store of the tracked account is tracked
+        let object_core_tag = StructTag::from_str("0x1::object::ObjectCore").unwrap();
+        let state_key =
+            StateKey::resource(&primary_apt_store(tracked_account), &object_core_tag).unwrap();
+        assert!(filter.is_tracked(&state_key));
+        let other_fa_store = create_derived_object_address(tracked_account, AccountAddress::TWO);
+        let state_key = StateKey::resource(&other_fa_store, &object_core_tag).unwrap();
+        assert!(!filter.is_tracked(&state_key));
+
+        // Verify that table items are not tracked
+        let state_key = StateKey::table_item(&TableHandle(tracked_account), &[0, 1, 2]);
+        assert!(!filter.is_tracked(&state_key));
+    }
+
+    #[test]
+    fn test_invalid_tracked_resource_group() {
+        let partial_state_config = PartialStateConfig {
+            enabled: true,
+            tracked_accounts: vec![],
+            tracked_resource_groups: vec!["invalid_struct_tag".into()],
+        };
+        assert!(PartialStateFilter::new(&partial_state_config).is_err());
+    }
+}
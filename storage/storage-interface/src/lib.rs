@@ -464,6 +464,10 @@ pub trait DbReader: Send + Sync {
         /// Returns if the state store pruner is enabled.
         fn is_state_merkle_pruner_enabled(&self) -> Result<bool>;
 
+        /// Returns if partial state mode is enabled (i.e., only the state
+        /// values of tracked accounts are stored).
+        fn is_partial_state_enabled(&self) -> Result<bool>;
+
         /// Get the state prune window config value.
         fn get_epoch_snapshot_prune_window(&self) -> Result<usize>;
 
@@ -2,7 +2,9 @@
 // Copyright (c) Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use aptos_consensus::transaction_shuffler_fuzzing::use_case_aware::{Config, UseCaseAwareShuffler};
+use aptos_transaction_shuffler::transaction_shuffler_fuzzing::use_case_aware::{
+    Config, UseCaseAwareShuffler,
+};
 use aptos_types::transaction::use_case::{UseCaseAwareTransaction, UseCaseKey};
 use arbitrary::Arbitrary;
 use libfuzzer_sys::fuzz_target;
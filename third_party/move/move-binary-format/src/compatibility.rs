@@ -15,8 +15,11 @@ use crate::{
     },
     CompiledModule,
 };
-use move_core_types::{ability::AbilitySet, vm_status::StatusCode};
-use std::collections::BTreeSet;
+use move_core_types::{
+    ability::AbilitySet, account_address::AccountAddress, identifier::Identifier,
+    language_storage::ModuleId, vm_status::StatusCode,
+};
+use std::{collections::BTreeSet, fmt};
 
 /// The result of a linking and layout compatibility check. Here is what the different combinations. NOTE that if `check_struct_layout` is false, type safety over a series of upgrades cannot be guaranteed.
 /// mean:
@@ -82,23 +85,50 @@ impl Compatibility {
     }
 
     /// Check compatibility for `new_module` relative to old module `old_module`.
-    #[allow(clippy::nonminimal_bool)] // simplification is more unreadable
     pub fn check(
         &self,
         old_module: &CompiledModule,
         new_module: &CompiledModule,
     ) -> PartialVMResult<()> {
+        let errors = self.incompatibilities(old_module, new_module);
+        if !errors.is_empty() {
+            Err(
+                PartialVMError::new(StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE).with_message(
+                    format!(
+                        "Module update failure: new module not compatible with \
+                        existing module in `{}`: {}",
+                        old_module.self_id(),
+                        errors
+                            .iter()
+                            .map(|error| error.to_string())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                ),
+            )
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns the reasons why `new_module` is not compatible with the old module `old_module`,
+    /// in the order in which they are found.
+    #[allow(clippy::nonminimal_bool)] // simplification is more unreadable
+    pub fn incompatibilities(
+        &self,
+        old_module: &CompiledModule,
+        new_module: &CompiledModule,
+    ) -> Vec<Incompatibility> {
         let mut errors = vec![];
 
         // module's name and address are unchanged
         if old_module.address() != new_module.address() {
-            errors.push(format!(
-                "module address changed to `{}`",
-                new_module.address()
-            ));
+            errors.push(Incompatibility::ChangedModuleAddress(*new_module.address()));
         }
         if old_module.name() != new_module.name() {
-            errors.push(format!("module name changed to `{}`", new_module.name()));
+            errors.push(Incompatibility::ChangedModuleName(
+                new_module.name().to_owned(),
+            ));
         }
 
         let old_view = ModuleView::new(old_module);
@@ -112,30 +142,30 @@ impl Compatibility {
                     // Struct not present in new . Existing modules that depend on this struct will fail to link with the new version of the module.
                     // Also, struct layout cannot be guaranteed transitively, because after
                     // removing the struct, it could be re-added later with a different layout.
-                    errors.push(format!("removed struct `{}`", old_struct.name()));
+                    errors.push(Incompatibility::RemovedStruct(old_struct.name().to_owned()));
                     break;
                 },
             };
 
             if !struct_abilities_compatible(old_struct.abilities(), new_struct.abilities()) {
-                errors.push(format!(
-                    "removed abilities `{}` from struct `{}`",
+                errors.push(Incompatibility::RemovedStructAbilities(
+                    old_struct.name().to_owned(),
                     old_struct.abilities().setminus(new_struct.abilities()),
-                    old_struct.name()
                 ));
             }
             if !struct_type_parameters_compatible(
                 old_struct.type_parameters(),
                 new_struct.type_parameters(),
             ) {
-                errors.push(format!(
-                    "changed type parameters of struct `{}`",
-                    old_struct.name()
+                errors.push(Incompatibility::ChangedStructTypeParameters(
+                    old_struct.name().to_owned(),
                 ));
             }
             // Layout of old and new struct need to be compatible
             if self.check_struct_layout && !struct_layout_compatible(&old_struct, new_struct) {
-                errors.push(format!("changed layout of struct `{}`", old_struct.name()));
+                errors.push(Incompatibility::ChangedStructLayout(
+                    old_struct.name().to_owned(),
+                ));
             }
         }
 
@@ -182,7 +212,7 @@ impl Compatibility {
                     // any Entry functions to be deleted, when self.treat_entry_as_public is
                     // set (treats entry as public)
                     {
-                        errors.push(format!("removed function `{}`", old_func.name()));
+                        errors.push(Incompatibility::RemovedFunction(old_func.name().to_owned()));
                     }
                     continue;
                 },
@@ -221,37 +251,36 @@ impl Compatibility {
                 };
             let is_attribute_compatible =
                 FunctionAttribute::is_compatible_with(old_func.attributes(), new_func.attributes());
-            let error_msg = if !is_vis_compatible {
-                Some("changed visibility")
+            let name = old_func.name().to_owned();
+            let error = if !is_vis_compatible {
+                Some(Incompatibility::ChangedFunctionVisibility(name))
             } else if !is_entry_compatible {
-                Some("removed `entry` modifier")
+                Some(Incompatibility::RemovedFunctionEntry(name))
             } else if !is_attribute_compatible {
-                Some("removed required attributes")
+                Some(Incompatibility::RemovedFunctionAttributes(name))
             } else if !signature_compatible(
                 old_module,
                 old_func.parameters(),
                 new_module,
                 new_func.parameters(),
             ) {
-                Some("changed parameter types")
+                Some(Incompatibility::ChangedFunctionParameters(name))
             } else if !signature_compatible(
                 old_module,
                 old_func.return_type(),
                 new_module,
                 new_func.return_type(),
             ) {
-                Some("changed return type")
+                Some(Incompatibility::ChangedFunctionReturnType(name))
             } else if !fun_type_parameters_compatible(
                 old_func.type_parameters(),
                 new_func.type_parameters(),
             ) {
-                Some("changed type parameters")
+                Some(Incompatibility::ChangedFunctionTypeParameters(name))
             } else {
                 None
             };
-            if let Some(msg) = error_msg {
-                errors.push(format!("{} of function `{}`", msg, old_func.name()));
-            }
+            errors.extend(error);
         }
 
         // check friend declarations compatibility
@@ -265,30 +294,82 @@ impl Compatibility {
             let new_friend_module_ids: BTreeSet<_> =
                 new_module.immediate_friends().iter().cloned().collect();
             if !old_friend_module_ids.is_subset(&new_friend_module_ids) {
-                errors.push(format!(
-                    "removed friend declaration {}",
+                errors.push(Incompatibility::RemovedFriends(
                     old_friend_module_ids
                         .difference(&new_friend_module_ids)
-                        .map(|id| format!("`{}`", id))
-                        .collect::<Vec<_>>()
-                        .join(" and ")
+                        .cloned()
+                        .collect(),
                 ))
             }
         }
 
-        if !errors.is_empty() {
-            Err(
-                PartialVMError::new(StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE).with_message(
-                    format!(
-                        "Module update failure: new module not compatible with \
-                        existing module in `{}`: {}",
-                        old_view.id(),
-                        errors.join(", ")
-                    ),
-                ),
-            )
-        } else {
-            Ok(())
+        errors
+    }
+}
+
+/// A reason why a new version of a module is not compatible with the old version.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Incompatibility {
+    ChangedModuleAddress(AccountAddress),
+    ChangedModuleName(Identifier),
+    RemovedStruct(Identifier),
+    RemovedStructAbilities(Identifier, AbilitySet),
+    ChangedStructTypeParameters(Identifier),
+    ChangedStructLayout(Identifier),
+    RemovedFunction(Identifier),
+    ChangedFunctionVisibility(Identifier),
+    RemovedFunctionEntry(Identifier),
+    RemovedFunctionAttributes(Identifier),
+    ChangedFunctionParameters(Identifier),
+    ChangedFunctionReturnType(Identifier),
+    ChangedFunctionTypeParameters(Identifier),
+    RemovedFriends(Vec<ModuleId>),
+}
+
+impl fmt::Display for Incompatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Incompatibility::*;
+        match self {
+            ChangedModuleAddress(address) => write!(f, "module address changed to `{}`", address),
+            ChangedModuleName(name) => write!(f, "module name changed to `{}`", name),
+            RemovedStruct(name) => write!(f, "removed struct `{}`", name),
+            RemovedStructAbilities(name, abilities) => write!(
+                f,
+                "removed abilities `{}` from struct `{}`",
+                abilities, name
+            ),
+            ChangedStructTypeParameters(name) => {
+                write!(f, "changed type parameters of struct `{}`", name)
+            },
+            ChangedStructLayout(name) => write!(f, "changed layout of struct `{}`", name),
+            RemovedFunction(name) => write!(f, "removed function `{}`", name),
+            ChangedFunctionVisibility(name) => {
+                write!(f, "changed visibility of function `{}`", name)
+            },
+            RemovedFunctionEntry(name) => {
+                write!(f, "removed `entry` modifier of function `{}`", name)
+            },
+            RemovedFunctionAttributes(name) => {
+                write!(f, "removed required attributes of function `{}`", name)
+            },
+            ChangedFunctionParameters(name) => {
+                write!(f, "changed parameter types of function `{}`", name)
+            },
+            ChangedFunctionReturnType(name) => {
+                write!(f, "changed return type of function `{}`", name)
+            },
+            ChangedFunctionTypeParameters(name) => {
+                write!(f, "changed type parameters of function `{}`", name)
+            },
+            RemovedFriends(module_ids) => write!(
+                f,
+                "removed friend declaration {}",
+                module_ids
+                    .iter()
+                    .map(|id| format!("`{}`", id))
+                    .collect::<Vec<_>>()
+                    .join(" and ")
+            ),
         }
     }
 }
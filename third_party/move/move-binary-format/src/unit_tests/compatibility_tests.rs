@@ -1,7 +1,10 @@
 // Copyright (c) The Move Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{compatibility::Compatibility, file_format::*};
+use crate::{
+    compatibility::{Compatibility, Incompatibility},
+    file_format::*,
+};
 use move_core_types::{account_address::AccountAddress, identifier::Identifier};
 use std::convert::TryFrom;
 
@@ -120,3 +123,31 @@ fn deprecated_add_script_visibility() {
         .check(&friend_module, &script_module)
         .is_err());
 }
+
+#[test]
+fn incompatibilities() {
+    let public_module = mk_module(Visibility::Public as u8);
+    let private_module = mk_module(Visibility::Private as u8);
+    let fn_name = Identifier::new("fn").unwrap();
+    // public -> private, not allowed
+    let incompatibilities =
+        Compatibility::full_check().incompatibilities(&public_module, &private_module);
+    assert_eq!(incompatibilities, vec![
+        Incompatibility::ChangedFunctionVisibility(fn_name.clone())
+    ]);
+    assert_eq!(
+        incompatibilities[0].to_string(),
+        "changed visibility of function `fn`"
+    );
+    // removing the public function, not allowed
+    let mut empty_module = private_module.clone();
+    empty_module.function_defs.clear();
+    assert_eq!(
+        Compatibility::full_check().incompatibilities(&public_module, &empty_module),
+        vec![Incompatibility::RemovedFunction(fn_name)]
+    );
+    // private -> public, allowed
+    assert!(Compatibility::full_check()
+        .incompatibilities(&private_module, &public_module)
+        .is_empty());
+}
@@ -660,6 +660,8 @@ pub mod known_attributes {
         TestOnly,
         // Is a test that will be run
         Test,
+        // Is a test that will be run with generated arguments
+        Fuzz,
         // This test is expected to fail
         ExpectedFailure,
     }
@@ -718,6 +720,7 @@ pub mod known_attributes {
             Some(match attribute_str.as_ref() {
                 TestingAttribute::TEST => Self::Testing(TestingAttribute::Test),
                 TestingAttribute::TEST_ONLY => Self::Testing(TestingAttribute::TestOnly),
+                TestingAttribute::FUZZ => Self::Testing(TestingAttribute::Fuzz),
                 TestingAttribute::EXPECTED_FAILURE => {
                     Self::Testing(TestingAttribute::ExpectedFailure)
                 },
@@ -781,11 +784,16 @@ pub mod known_attributes {
 
     impl TestingAttribute {
         pub const ABORT_CODE_NAME: &'static str = "abort_code";
-        const ALL_ATTRIBUTE_NAMES: [&'static str; 3] =
-            [Self::TEST, Self::TEST_ONLY, Self::EXPECTED_FAILURE];
+        const ALL_ATTRIBUTE_NAMES: [&'static str; 4] = [
+            Self::TEST,
+            Self::TEST_ONLY,
+            Self::FUZZ,
+            Self::EXPECTED_FAILURE,
+        ];
         pub const ARITHMETIC_ERROR_NAME: &'static str = "arithmetic_error";
         pub const ERROR_LOCATION: &'static str = "location";
         pub const EXPECTED_FAILURE: &'static str = "expected_failure";
+        pub const FUZZ: &'static str = "fuzz";
         pub const MAJOR_STATUS_NAME: &'static str = "major_status";
         pub const MINOR_STATUS_NAME: &'static str = "minor_status";
         pub const OUT_OF_GAS_NAME: &'static str = "out_of_gas";
@@ -814,6 +822,7 @@ pub mod known_attributes {
             match self {
                 Self::Test => Self::TEST,
                 Self::TestOnly => Self::TEST_ONLY,
+                Self::Fuzz => Self::FUZZ,
                 Self::ExpectedFailure => Self::EXPECTED_FAILURE,
             }
         }
@@ -837,7 +846,7 @@ pub mod known_attributes {
                 Lazy::new(|| IntoIterator::into_iter([AttributePosition::Function]).collect());
             match self {
                 TestingAttribute::TestOnly => &TEST_ONLY_POSITIONS,
-                TestingAttribute::Test => &TEST_POSITIONS,
+                TestingAttribute::Test | TestingAttribute::Fuzz => &TEST_POSITIONS,
                 TestingAttribute::ExpectedFailure => &EXPECTED_FAILURE_POSITIONS,
             }
         }
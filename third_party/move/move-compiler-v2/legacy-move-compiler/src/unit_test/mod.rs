@@ -9,8 +9,11 @@ use crate::{
 };
 use move_binary_format::CompiledModule;
 use move_core_types::{
-    account_address::AccountAddress, identifier::Identifier, language_storage::ModuleId,
-    value::MoveValue, vm_status::StatusCode,
+    account_address::AccountAddress,
+    identifier::Identifier,
+    language_storage::ModuleId,
+    value::{MoveTypeLayout, MoveValue},
+    vm_status::StatusCode,
 };
 use std::{collections::BTreeMap, fmt};
 
@@ -46,6 +49,16 @@ pub struct TestCase {
     pub test_name: TestName,
     pub arguments: Vec<MoveValue>,
     pub expected_failure: Option<ExpectedFailure>,
+    // For `#[fuzz]` tests, the named arguments to generate for each run (`arguments` is unused)
+    pub fuzz_arguments: Option<Vec<(String, FuzzArgument)>>,
+}
+
+#[derive(Debug, Clone)]
+pub enum FuzzArgument {
+    // argument assigned in the `#[fuzz(...)]` attribute
+    Fixed(MoveValue),
+    // argument generated randomly for each run, according to the layout
+    Generated(MoveTypeLayout),
 }
 
 #[derive(Debug, Clone)]
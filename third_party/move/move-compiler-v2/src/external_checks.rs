@@ -22,6 +22,20 @@ pub trait ExternalChecks {
 
     /// Get all the stackless bytecode checkers.
     fn get_stackless_bytecode_checkers(&self) -> Vec<Box<dyn StacklessBytecodeChecker>>;
+
+    /// Get the names of all the checkers that are known to this collection,
+    /// including the ones that are not enabled. These names are accepted by
+    /// the `#[lint::skip(...)]` attribute.
+    fn get_known_checker_names(&self) -> BTreeSet<String> {
+        let mut names = BTreeSet::new();
+        for checker in self.get_exp_checkers() {
+            names.insert(checker.get_name());
+        }
+        for checker in self.get_stackless_bytecode_checkers() {
+            names.insert(checker.get_name());
+        }
+        names
+    }
 }
 
 impl fmt::Debug for dyn ExternalChecks {
@@ -83,16 +97,10 @@ pub trait StacklessBytecodeChecker {
 
 /// Get the set of known checker names from the given external checkers.
 pub fn known_checker_names(external_checkers: &Vec<Arc<dyn ExternalChecks>>) -> BTreeSet<String> {
-    let mut names = BTreeSet::new();
-    for checkers in external_checkers {
-        for checker in checkers.get_exp_checkers() {
-            names.insert(checker.get_name());
-        }
-        for checker in checkers.get_stackless_bytecode_checkers() {
-            names.insert(checker.get_name());
-        }
-    }
-    names
+    external_checkers
+        .iter()
+        .flat_map(|checkers| checkers.get_known_checker_names())
+        .collect()
 }
 
 /// Report the `msg` highlighting the `loc` for the `checker_name`.
@@ -7,19 +7,22 @@
 //!
 //! This reimplements the legacy move compiler functionality.
 //!
-//! Each module containing any labeled `#[test]` functions gets an item in the output list, which
-//! includes info about each '#[test]' function: name, arguments to provide, and expected failure or
-//! success.
+//! Each module containing any labeled `#[test]` (or `#[fuzz]`) functions gets an item in the output
+//! list, which includes info about each '#[test]' function: name, arguments to provide (or to
+//! generate, for fuzz tests), and expected failure or success.
 
 use crate::options::Options;
 use codespan_reporting::diagnostic::Severity;
 use legacy_move_compiler::{
     shared::known_attributes::{AttributeKind, TestingAttribute},
-    unit_test::{ExpectedFailure, ExpectedMoveError, ModuleTestPlan, TestCase},
+    unit_test::{ExpectedFailure, ExpectedMoveError, FuzzArgument, ModuleTestPlan, TestCase},
 };
 use move_command_line_common::{address::NumericalAddress, parser::NumberFormat};
 use move_core_types::{
-    identifier::Identifier, language_storage::ModuleId, value::MoveValue, vm_status::StatusCode,
+    identifier::Identifier,
+    language_storage::ModuleId,
+    value::{MoveFieldLayout, MoveStructLayout, MoveTypeLayout, MoveValue, MoveVariantLayout},
+    vm_status::StatusCode,
 };
 use move_model::{
     ast::{Address, Attribute, AttributeValue, ModuleName, Value},
@@ -116,17 +119,22 @@ fn build_test_info(
     let attrs = function.get_attributes();
     let expected_failure_name = env.symbol_pool().make(TestingAttribute::EXPECTED_FAILURE);
     let test_name = env.symbol_pool().make(TestingAttribute::TEST);
+    let fuzz_name = env.symbol_pool().make(TestingAttribute::FUZZ);
     let test_only_name = env.symbol_pool().make(TestingAttribute::TEST_ONLY);
 
-    let test_attribute_opt = attrs.iter().find(|a| a.name() == test_name);
+    let fuzz_attribute_opt = attrs.iter().find(|a| a.name() == fuzz_name);
+    let test_attribute_opt = attrs
+        .iter()
+        .find(|a| a.name() == test_name)
+        .or(fuzz_attribute_opt);
     let abort_attribute_opt = attrs.iter().find(|a| a.name() == expected_failure_name);
 
     let test_attribute = match test_attribute_opt {
         None => {
             // expected failures cannot be annotated on non-#[test] functions
             if let Some(abort_attribute) = abort_attribute_opt {
-                let fn_msg = "Only functions defined as a test with #[test] can also have an \
-                              #[expected_failure] attribute";
+                let fn_msg = "Only functions defined as a test with #[test] or #[fuzz] can also \
+                              have an #[expected_failure] attribute";
                 let abort_msg = "Attributed as #[expected_failure] here";
                 let abort_id = abort_attribute.node_id();
                 let abort_loc = env.get_node_loc(abort_id);
@@ -139,6 +147,21 @@ fn build_test_info(
 
     let test_attribute_id = test_attribute.node_id();
     let test_attribute_loc = env.get_node_loc(test_attribute_id);
+    let is_fuzz = test_attribute.name() == fuzz_name;
+
+    // A #[test] function cannot also be annotated #[fuzz]
+    if let Some(fuzz_attribute) = fuzz_attribute_opt.filter(|_| !is_fuzz) {
+        let msg = "Function annotated as both #[test(...)] and #[fuzz(...)]. You need to declare \
+                   it as either one or the other";
+        let fuzz_loc = env.get_node_loc(fuzz_attribute.node_id());
+        env.error_with_labels(&fn_id_loc, "invalid usage of known attribute", vec![
+            (fuzz_loc, msg.to_string()),
+            (
+                test_attribute_loc.clone(),
+                "Previously annotated here".to_string(),
+            ),
+        ]);
+    }
 
     let test_only_attribute_opt = attrs.iter().find(|a| a.name() == test_only_name);
 
@@ -160,18 +183,17 @@ fn build_test_info(
     let test_annotation_params = parse_test_attribute(env, test_attribute, 0);
 
     let mut arguments = Vec::new();
+    let mut fuzz_arguments = Vec::new();
     for param in function.get_parameters_ref() {
         let Parameter(var, ty, var_loc) = &param;
 
-        match test_annotation_params.get(var) {
+        let argument = match test_annotation_params.get(var) {
             Some(MoveValue::Address(addr)) => match ty {
-                Type::Primitive(PrimitiveType::Signer) => arguments.push(MoveValue::Signer(*addr)),
+                Type::Primitive(PrimitiveType::Signer) => Some(MoveValue::Signer(*addr)),
                 Type::Reference(_, inner) if **inner == Type::Primitive(PrimitiveType::Signer) => {
-                    arguments.push(MoveValue::Signer(*addr));
-                },
-                Type::Primitive(PrimitiveType::Address) => {
-                    arguments.push(MoveValue::Address(*addr))
+                    Some(MoveValue::Signer(*addr))
                 },
+                Type::Primitive(PrimitiveType::Address) => Some(MoveValue::Address(*addr)),
                 _ => {
                     let err_msg = "Unexpected argument type: expect an address or a signer";
                     let invalid_test = "unable to generate test";
@@ -182,9 +204,33 @@ fn build_test_info(
                             "Corresponding to this parameter".to_string(),
                         ),
                     ]);
+                    None
                 },
             },
-            Some(value) => arguments.push(value.clone()),
+            Some(value) => Some(value.clone()),
+            // Parameters of fuzz tests which are not assigned are generated for each run
+            None if is_fuzz => {
+                match fuzz_argument_layout(env, ty) {
+                    Some(layout) => fuzz_arguments.push((
+                        env.symbol_pool().string(*var).to_string(),
+                        FuzzArgument::Generated(layout),
+                    )),
+                    None => {
+                        let err_msg = "Unsupported parameter type in fuzz test. Values can only \
+                                       be generated for primitive types, signers, vectors and \
+                                       (non-native) structs";
+                        let invalid_test = "unable to generate test";
+                        env.error_with_labels(&fn_id_loc, invalid_test, vec![
+                            (var_loc.clone(), err_msg.to_string()),
+                            (
+                                test_attribute_loc.clone(),
+                                "Attributed as #[fuzz] here".to_string(),
+                            ),
+                        ]);
+                    },
+                }
+                None
+            },
             None => {
                 let missing_param_msg = "Missing test parameter assignment in test. Expected a \
                                          parameter to be assigned in this attribute";
@@ -196,7 +242,16 @@ fn build_test_info(
                         "Corresponding to this parameter".to_string(),
                     ),
                 ]);
+                None
             },
+        };
+        match argument {
+            Some(argument) if is_fuzz => fuzz_arguments.push((
+                env.symbol_pool().string(*var).to_string(),
+                FuzzArgument::Fixed(argument),
+            )),
+            Some(argument) => arguments.push(argument),
+            None => {},
         }
     }
 
@@ -209,6 +264,66 @@ fn build_test_info(
         test_name: fn_name_str.to_string(),
         arguments,
         expected_failure,
+        fuzz_arguments: is_fuzz.then_some(fuzz_arguments),
+    })
+}
+
+/// Returns the layout of the values to generate for a parameter of type `ty` in a fuzz test,
+/// or `None` if no values can be generated for the type.
+fn fuzz_argument_layout(env: &GlobalEnv, ty: &Type) -> Option<MoveTypeLayout> {
+    Some(match ty {
+        Type::Primitive(prim) => match prim {
+            PrimitiveType::Bool => MoveTypeLayout::Bool,
+            PrimitiveType::U8 => MoveTypeLayout::U8,
+            PrimitiveType::U16 => MoveTypeLayout::U16,
+            PrimitiveType::U32 => MoveTypeLayout::U32,
+            PrimitiveType::U64 => MoveTypeLayout::U64,
+            PrimitiveType::U128 => MoveTypeLayout::U128,
+            PrimitiveType::U256 => MoveTypeLayout::U256,
+            PrimitiveType::Address => MoveTypeLayout::Address,
+            PrimitiveType::Signer => MoveTypeLayout::Signer,
+            _ => return None,
+        },
+        Type::Reference(_, inner) if inner.is_signer() => MoveTypeLayout::Signer,
+        Type::Vector(elem_ty) => {
+            MoveTypeLayout::Vector(Box::new(fuzz_argument_layout(env, elem_ty)?))
+        },
+        Type::Struct(mid, sid, inst) => {
+            let struct_env = env.get_struct(mid.qualified(*sid));
+            if struct_env.is_native() {
+                return None;
+            }
+            let field_layouts = |variant: Option<Symbol>| {
+                struct_env
+                    .get_fields_optional_variant(variant)
+                    .map(|field| {
+                        let name = env.symbol_pool().string(field.get_name()).to_string();
+                        let layout =
+                            fuzz_argument_layout(env, &field.get_type().instantiate(inst))?;
+                        Some(MoveFieldLayout::new(Identifier::new(name).ok()?, layout))
+                    })
+                    .collect::<Option<Vec<_>>>()
+            };
+            if struct_env.has_variants() {
+                let variants = struct_env
+                    .get_variants()
+                    .map(|variant| {
+                        let name = env.symbol_pool().string(variant).to_string();
+                        Some(MoveVariantLayout {
+                            name: Identifier::new(name).ok()?,
+                            fields: field_layouts(Some(variant))?,
+                        })
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                MoveTypeLayout::Struct(MoveStructLayout::WithVariants(variants))
+            } else {
+                MoveTypeLayout::Struct(MoveStructLayout::WithTypes {
+                    type_: ty.clone().into_struct_tag(env)?,
+                    fields: field_layouts(None)?,
+                })
+            }
+        },
+        _ => return None,
     })
 }
 
@@ -229,7 +344,8 @@ fn parse_test_attribute(
         },
         Attribute::Apply(_id, sym, vec) => {
             assert!(
-                *TestingAttribute::TEST == env.symbol_pool().string(*sym).to_string(),
+                [TestingAttribute::TEST, TestingAttribute::FUZZ]
+                    .contains(&env.symbol_pool().string(*sym).as_str()),
                 "ICE: We should only be parsing a raw test attribute"
             );
             vec.iter()
@@ -1089,6 +1089,29 @@ impl GlobalEnv {
             .any(|(d, _)| d.message.contains(pattern))
     }
 
+    /// Returns all accumulated diagnostics of given or higher severity, whether already
+    /// reported or not, together with the location of their primary label, if any.
+    pub fn get_diags_with_loc(&self, severity: Severity) -> Vec<(Diagnostic<FileId>, Option<Loc>)> {
+        self.diags
+            .borrow()
+            .iter()
+            .filter(|(d, _)| d.severity >= severity)
+            .map(|(d, _)| {
+                let loc = d
+                    .labels
+                    .iter()
+                    .find(|l| l.style == LabelStyle::Primary)
+                    .map(|l| {
+                        Loc::new(
+                            l.file_id,
+                            Span::new(l.range.start as u32, l.range.end as u32),
+                        )
+                    });
+                (d.clone(), loc)
+            })
+            .collect()
+    }
+
     /// Clear all accumulated diagnosis.
     pub fn clear_diag(&self) {
         self.diags.borrow_mut().clear();
@@ -177,6 +177,9 @@ pub struct BoogieOptions {
     pub vector_theory: VectorTheory,
     /// Whether to generate a z3 trace file and where to put it.
     pub z3_trace_file: Option<String>,
+    /// Whether to let Boogie write an XML report with the outcome and duration of each
+    /// procedure next to the Boogie file.
+    pub xml_report: bool,
     /// Options to define user-custom native funs.
     pub custom_natives: Option<CustomNativeOptions>,
     /// Number of iterations to unroll loops.
@@ -221,6 +224,7 @@ impl Default for BoogieOptions {
             hard_timeout_secs: 0,
             vector_theory: VectorTheory::BoogieArray,
             z3_trace_file: None,
+            xml_report: false,
             custom_natives: None,
             loop_unroll: None,
             borrow_aggregates: vec![],
@@ -308,6 +312,9 @@ impl BoogieOptions {
         if self.generate_smt {
             add(&["-proverLog:@PROC@.smt"]);
         }
+        if self.xml_report {
+            add(&[&format!("-xml:{}", self.get_boogie_xml_file(boogie_file))]);
+        }
         for f in &self.boogie_flags {
             add(&[f.as_str()]);
         }
@@ -320,6 +327,11 @@ impl BoogieOptions {
         format!("{}.log", boogie_file)
     }
 
+    /// Returns name of file where boogie writes its XML report.
+    pub fn get_boogie_xml_file(&self, boogie_file: &str) -> String {
+        format!("{}.xml", boogie_file)
+    }
+
     /// Adjust a timeout value, given in seconds, for the runtime environment.
     pub fn adjust_timeout(&self, time: usize) -> usize {
         // If env var MVP_TEST_ON_CI is set, add 100% to the timeout for added
@@ -394,6 +406,33 @@ impl BoogieOptions {
         Ok(())
     }
 
+    /// Returns the versions of Boogie and of the solver which are used for verification.
+    pub fn tool_versions(&self) -> anyhow::Result<String> {
+        let boogie = if self.boogie_exe.is_empty() {
+            String::new()
+        } else {
+            Self::get_version(
+                "boogie",
+                &self.boogie_exe,
+                &["/version"],
+                r"version ([0-9.]*)",
+            )?
+        };
+        let solver = if self.use_cvc5 {
+            if self.cvc5_exe.is_empty() {
+                String::new()
+            } else {
+                Self::get_version("cvc5", &self.cvc5_exe, &["--version"], r"version ([0-9.]*)")?
+            }
+        } else if self.z3_exe.is_empty() {
+            String::new()
+        } else {
+            Self::get_version("z3", &self.z3_exe, &["--version"], r"version ([0-9.]*)")?
+        };
+        let solver_name = if self.use_cvc5 { "cvc5" } else { "z3" };
+        Ok(format!("boogie {}, {} {}", boogie, solver_name, solver))
+    }
+
     fn get_version(tool: &str, prog: &str, args: &[&str], regex: &str) -> anyhow::Result<String> {
         let out = match Command::new(prog).args(args).output() {
             Ok(out) => String::from_utf8_lossy(&out.stdout).to_string(),
@@ -207,6 +207,21 @@ impl TransactionDataCache {
             .is_some_and(|account_cache| account_cache.contains_key(ty))
     }
 
+    /// Returns the resource with the given tag if it has been loaded into the cache. Used by
+    /// debuggers to inspect global state.
+    #[cfg(any(debug_assertions, feature = "debugging"))]
+    pub(crate) fn get_resource_for_debugging(
+        &self,
+        addr: &AccountAddress,
+        struct_tag: &StructTag,
+    ) -> Option<&GlobalValue> {
+        self.account_map
+            .get(addr)?
+            .values()
+            .find(|entry| &entry.struct_tag == struct_tag)
+            .map(|entry| &entry.value)
+    }
+
     /// Stores a new entry for loaded resource into the data cache. Returns an error if there is an
     /// entry already for the specified address-type pair.
     pub(crate) fn insert_resource(
@@ -2,13 +2,25 @@
 // Copyright (c) The Move Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{interpreter::InterpreterDebugInterface, LoadedFunction, RuntimeEnvironment};
-use move_binary_format::file_format::Bytecode;
+use crate::{
+    data_cache::TransactionDataCache, interpreter::InterpreterDebugInterface, LoadedFunction,
+    RuntimeEnvironment,
+};
+use move_binary_format::file_format::{Bytecode, CodeOffset, FunctionDefinitionIndex};
+use move_core_types::{
+    account_address::AccountAddress,
+    language_storage::{ModuleId, StructTag},
+};
 use move_vm_types::values::{self, Locals};
+use once_cell::sync::Lazy;
 use std::{
     collections::BTreeSet,
     io::{self, Write},
     str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
 };
 
 #[derive(Debug)]
@@ -193,3 +205,121 @@ impl DebugContext {
         }
     }
 }
+
+/// A debugger which is notified before each instruction is executed, and may block execution
+/// (e.g., to wait for commands from a user). Installed with [set_debugger].
+pub trait Debugger: Send {
+    fn on_instruction(&mut self, state: &DebugState);
+}
+
+/// The state of execution passed to a [Debugger].
+pub struct DebugState<'a> {
+    function: &'a LoadedFunction,
+    locals: &'a Locals,
+    pc: CodeOffset,
+    runtime_environment: &'a RuntimeEnvironment,
+    interpreter: &'a dyn InterpreterDebugInterface,
+    data_cache: &'a TransactionDataCache,
+}
+
+impl DebugState<'_> {
+    /// Returns the module of the executing function, or `None` for scripts.
+    pub fn module_id(&self) -> Option<&ModuleId> {
+        self.function.module_id()
+    }
+
+    pub fn function_name(&self) -> &str {
+        self.function.name()
+    }
+
+    pub fn function_index(&self) -> FunctionDefinitionIndex {
+        self.function.index()
+    }
+
+    pub fn pc(&self) -> CodeOffset {
+        self.pc
+    }
+
+    /// Returns the number of frames on the call stack, including the executing one.
+    pub fn call_stack_depth(&self) -> usize {
+        self.interpreter.call_stack_depth() + 1
+    }
+
+    /// Returns the callers of the executing function, innermost first.
+    pub fn callers(&self) -> Vec<(Option<ModuleId>, FunctionDefinitionIndex, CodeOffset)> {
+        self.interpreter
+            .get_stack_frames(usize::MAX)
+            .stack_trace()
+            .clone()
+    }
+
+    /// Returns the values of the locals (including parameters) of the executing function, or
+    /// `None` for locals which are not set (e.g., because they have been moved).
+    pub fn locals(&self) -> Vec<Option<String>> {
+        (0..self.function.local_tys().len())
+            .map(|idx| match self.locals.is_invalid(idx) {
+                Ok(false) => self
+                    .locals
+                    .copy_loc(idx)
+                    .ok()
+                    .map(|value| value.to_string()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns the resource at the given address, if it has been loaded by the transaction.
+    /// The inner option is `None` if the resource does not exist.
+    pub fn resource(
+        &self,
+        addr: &AccountAddress,
+        struct_tag: &StructTag,
+    ) -> Option<Option<String>> {
+        let value = self
+            .data_cache
+            .get_resource_for_debugging(addr, struct_tag)?;
+        if !value.exists().ok()? {
+            return Some(None);
+        }
+        Some(value.borrow_global().ok().map(|value| value.to_string()))
+    }
+
+    pub fn runtime_environment(&self) -> &RuntimeEnvironment {
+        self.runtime_environment
+    }
+}
+
+static DEBUGGER_INSTALLED: AtomicBool = AtomicBool::new(false);
+
+static DEBUGGER: Lazy<Mutex<Option<Box<dyn Debugger>>>> = Lazy::new(|| Mutex::new(None));
+
+/// Installs a debugger which is notified of all instructions executed by any VM in this
+/// process, or removes the installed one if `None` is passed.
+pub fn set_debugger(debugger: Option<Box<dyn Debugger>>) {
+    let mut installed = DEBUGGER.lock().unwrap();
+    DEBUGGER_INSTALLED.store(debugger.is_some(), Ordering::Release);
+    *installed = debugger;
+}
+
+pub(crate) fn notify_debugger(
+    function: &LoadedFunction,
+    locals: &Locals,
+    pc: CodeOffset,
+    runtime_environment: &RuntimeEnvironment,
+    interpreter: &dyn InterpreterDebugInterface,
+    data_cache: &TransactionDataCache,
+) {
+    if !DEBUGGER_INSTALLED.load(Ordering::Acquire) {
+        return;
+    }
+    if let Some(debugger) = DEBUGGER.lock().unwrap().as_mut() {
+        debugger.on_instruction(&DebugState {
+            function,
+            locals,
+            pc,
+            runtime_environment,
+            interpreter,
+            data_cache,
+        });
+    }
+}
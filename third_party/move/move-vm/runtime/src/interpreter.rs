@@ -75,6 +75,7 @@ pub(crate) struct Interpreter;
 
 pub(crate) trait InterpreterDebugInterface {
     fn get_stack_frames(&self, count: usize) -> ExecutionState;
+    fn call_stack_depth(&self) -> usize;
     fn debug_print_stack_trace(
         &self,
         buf: &mut String,
@@ -1561,6 +1562,11 @@ where
             .collect();
         ExecutionState::new(stack_trace)
     }
+
+    /// Returns the number of frames on the call stack, excluding the currently executing one.
+    fn call_stack_depth(&self) -> usize {
+        self.call_stack.0.len()
+    }
 }
 
 // TODO Determine stack size limits based on gas limit
@@ -1776,7 +1782,8 @@ impl Frame {
                     self.pc,
                     instruction,
                     module_storage.runtime_environment(),
-                    interpreter
+                    interpreter,
+                    data_cache
                 );
 
                 fail_point!("move_vm::interpreter_loop", |_| {
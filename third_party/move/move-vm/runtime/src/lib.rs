@@ -21,7 +21,7 @@ pub mod module_traversal;
 
 // Only include debugging functionality in debug builds
 #[cfg(any(debug_assertions, feature = "debugging"))]
-mod debug;
+pub mod debug;
 
 mod access_control;
 mod frame;
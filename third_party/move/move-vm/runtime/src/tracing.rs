@@ -3,9 +3,12 @@
 // SPDX-License-Identifier: Apache-2.0
 
 #[cfg(any(debug_assertions, feature = "debugging"))]
-use crate::debug::DebugContext;
+use crate::debug::{self, DebugContext};
 #[cfg(any(debug_assertions, feature = "debugging"))]
-use crate::{interpreter::InterpreterDebugInterface, loader::LoadedFunction, RuntimeEnvironment};
+use crate::{
+    data_cache::TransactionDataCache, interpreter::InterpreterDebugInterface,
+    loader::LoadedFunction, RuntimeEnvironment,
+};
 #[cfg(any(debug_assertions, feature = "debugging"))]
 use ::{
     move_binary_format::file_format::Bytecode,
@@ -70,6 +73,7 @@ pub(crate) fn trace(
     instr: &Bytecode,
     runtime_environment: &RuntimeEnvironment,
     interpreter: &dyn InterpreterDebugInterface,
+    data_cache: &TransactionDataCache,
 ) {
     if *TRACING_ENABLED {
         let buf_writer = &mut *LOGGING_FILE_WRITER.lock().unwrap();
@@ -94,13 +98,37 @@ pub(crate) fn trace(
             interpreter,
         );
     }
+    debug::notify_debugger(
+        function,
+        locals,
+        pc,
+        runtime_environment,
+        interpreter,
+        data_cache,
+    );
 }
 
 #[macro_export]
 macro_rules! trace {
-    ($function_desc:expr, $locals:expr, $pc:expr, $instr:tt, $resolver:expr, $interp:expr) => {
+    (
+        $function_desc:expr,
+        $locals:expr,
+        $pc:expr,
+        $instr:tt,
+        $resolver:expr,
+        $interp:expr,
+        $data_cache:expr
+    ) => {
         // Only include this code in debug releases
         #[cfg(any(debug_assertions, feature = "debugging"))]
-        $crate::tracing::trace(&$function_desc, $locals, $pc, &$instr, $resolver, $interp)
+        $crate::tracing::trace(
+            &$function_desc,
+            $locals,
+            $pc,
+            &$instr,
+            $resolver,
+            $interp,
+            $data_cache,
+        )
     };
 }
@@ -19,6 +19,7 @@ use move_package::{
     BuildConfig,
 };
 use move_unit_test::{
+    fuzzer::DEFAULT_FUZZ_RUNS,
     test_reporter::{UnitTestFactory, UnitTestFactoryWithCostTable},
     UnitTestingConfig,
 };
@@ -85,6 +86,12 @@ pub struct Test {
     /// Collect coverage information for later use with the various `move coverage` subcommands
     #[clap(long = "coverage")]
     pub compute_coverage: bool,
+    /// Number of runs (with different generated arguments) of each #[fuzz] test
+    #[clap(name = "fuzz_runs", long = "fuzz_runs", default_value_t = DEFAULT_FUZZ_RUNS)]
+    pub fuzz_runs: u64,
+    /// Seed for generating the arguments of #[fuzz] tests, to reproduce a failing run
+    #[clap(name = "fuzz_seed", long = "fuzz_seed")]
+    pub fuzz_seed: Option<u64>,
 }
 
 impl Test {
@@ -108,6 +115,8 @@ impl Test {
             check_stackless_vm,
             verbose_mode,
             compute_coverage,
+            fuzz_runs,
+            fuzz_seed,
         } = self;
         let unit_test_config = UnitTestingConfig {
             filter,
@@ -118,6 +127,8 @@ impl Test {
             check_stackless_vm,
             verbose: verbose_mode,
             ignore_compile_warnings,
+            fuzz_runs,
+            fuzz_seed,
             ..UnitTestingConfig::default()
         };
         let result = run_move_unit_tests(
@@ -0,0 +1,610 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+//! Export of coverage in the LCOV and Cobertura XML formats, so that Move coverage can be
+//! consumed by standard coverage tooling (e.g., `genhtml`, or CI coverage dashboards).
+//!
+//! Line, function and branch hit counts are mapped back to source lines through the source
+//! maps of the modules. Since the coverage map only records how often each instruction was
+//! executed, how often a branch outcome was taken is only known if its destination has no other
+//! predecessor (in which case it is the execution count of the destination). Otherwise, the
+//! outcome is unknown, and it is not reported in the exported coverage.
+
+use crate::coverage_map::ExecCoverageMap;
+use anyhow::{bail, Result};
+use codespan::{ByteIndex, Files};
+use move_binary_format::{
+    access::ModuleAccess,
+    file_format::{Bytecode, CodeOffset, FunctionDefinitionIndex},
+    CompiledModule,
+};
+use move_bytecode_source_map::source_map::SourceMap;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+/// Line-level coverage of a function.
+#[derive(Clone, Debug)]
+pub struct FunctionLineCoverage {
+    pub name: String,
+    /// The (1-based) line at which the function is defined.
+    pub line: u32,
+    /// Number of times the function was called.
+    pub hits: u64,
+    /// Map from (1-based) line to the number of times it was executed.
+    pub lines: BTreeMap<u32, u64>,
+}
+
+/// Coverage of one outcome of a conditional branch.
+#[derive(Clone, Debug)]
+pub struct BranchCoverage {
+    /// The (1-based) line of the branch instruction.
+    pub line: u32,
+    /// The code offset of the branch instruction within its function.
+    pub offset: CodeOffset,
+    /// Index of the outcome (0 for the lower destination offset, 1 for the higher one).
+    pub outcome: usize,
+    pub hits: BranchHits,
+}
+
+/// The number of times a branch outcome was taken.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BranchHits {
+    /// The branch was never reached.
+    NotReached,
+    /// The outcome was taken this many times.
+    Taken(u64),
+    /// The branch was reached, but the destination of the outcome has other predecessors, so
+    /// the number of times the outcome was taken is unknown.
+    Unknown,
+}
+
+impl BranchCoverage {
+    /// Returns `true` if the number of times the outcome was taken is known to be non-zero.
+    fn is_taken(&self) -> bool {
+        matches!(self.hits, BranchHits::Taken(hits) if hits > 0)
+    }
+}
+
+/// Line, function and branch coverage of a module, mapped to its source file.
+#[derive(Clone, Debug)]
+pub struct ModuleLineCoverage {
+    pub module_name: String,
+    pub source_path: PathBuf,
+    pub functions: Vec<FunctionLineCoverage>,
+    pub branches: Vec<BranchCoverage>,
+}
+
+impl ModuleLineCoverage {
+    /// Computes the coverage of `module`, defined in `source_path`.
+    pub fn new(
+        module: &CompiledModule,
+        source_map: &SourceMap,
+        source_path: &Path,
+        coverage_map: &ExecCoverageMap,
+    ) -> Result<Self> {
+        let file_contents = fs::read_to_string(source_path)?;
+        if !source_map.check(&file_contents) {
+            bail!(
+                "File contents {} out of sync with source map",
+                source_path.display()
+            );
+        }
+        let mut files = Files::new();
+        let file_id = files.add(source_path.as_os_str().to_os_string(), file_contents);
+        let file_hash = source_map.definition_location.file_hash();
+        let line_of = |byte_index: u32| {
+            files
+                .location(file_id, ByteIndex(byte_index))
+                .map(|location| location.line.0 + 1)
+                .ok()
+        };
+
+        let module_id = module.self_id();
+        let module_map = coverage_map
+            .module_maps
+            .get(&(*module_id.address(), module_id.name().to_owned()));
+        let mut functions = vec![];
+        let mut branches = vec![];
+        for (idx, function_def) in module.function_defs().iter().enumerate() {
+            let Some(code_unit) = &function_def.code else {
+                continue;
+            };
+            let fdef_idx = FunctionDefinitionIndex(idx as u16);
+            let fn_name =
+                module.identifier_at(module.function_handle_at(function_def.function).name);
+            let counts = module_map.and_then(|map| map.get_function_coverage(fn_name));
+            let count_at = |offset: CodeOffset| {
+                counts
+                    .and_then(|c| c.get(&(offset as u64)).copied())
+                    .unwrap_or(0)
+            };
+            // Only locations in the module's own file are reported, which excludes code inlined
+            // from other files.
+            let line_at = |offset: CodeOffset| {
+                source_map
+                    .get_code_location(fdef_idx, offset)
+                    .ok()
+                    .filter(|loc| loc.file_hash() == file_hash)
+                    .and_then(|loc| line_of(loc.start()))
+            };
+
+            // The number of predecessors of each offset (the entry counts as a predecessor of 0).
+            let mut num_predecessors = BTreeMap::from([(0, 1)]);
+            for offset in 0..code_unit.code.len() {
+                let successors = Bytecode::get_successors(offset as CodeOffset, &code_unit.code);
+                for successor in successors.into_iter().collect::<BTreeSet<_>>() {
+                    *num_predecessors.entry(successor).or_insert(0) += 1;
+                }
+            }
+
+            let mut lines = BTreeMap::new();
+            for (offset, instr) in code_unit.code.iter().enumerate() {
+                let offset = offset as CodeOffset;
+                let Some(line) = line_at(offset) else {
+                    continue;
+                };
+                // A line is executed as often as its most executed instruction.
+                let hits = lines.entry(line).or_insert(0);
+                *hits = (*hits).max(count_at(offset));
+
+                if instr.is_conditional_branch() {
+                    let reached = count_at(offset);
+                    for (outcome, dest) in Bytecode::get_successors(offset, &code_unit.code)
+                        .into_iter()
+                        .enumerate()
+                    {
+                        let hits = if reached == 0 {
+                            BranchHits::NotReached
+                        } else if num_predecessors.get(&dest) == Some(&1) {
+                            BranchHits::Taken(count_at(dest))
+                        } else {
+                            BranchHits::Unknown
+                        };
+                        branches.push(BranchCoverage {
+                            line,
+                            offset,
+                            outcome,
+                            hits,
+                        });
+                    }
+                }
+            }
+
+            let line = source_map
+                .get_function_source_map(fdef_idx)
+                .ok()
+                .and_then(|map| line_of(map.definition_location.start()))
+                .or_else(|| lines.keys().next().copied())
+                .unwrap_or(1);
+            functions.push(FunctionLineCoverage {
+                name: fn_name.to_string(),
+                line,
+                hits: count_at(0),
+                lines,
+            });
+        }
+
+        Ok(Self {
+            module_name: module_id.name().to_string(),
+            source_path: source_path.to_path_buf(),
+            functions,
+            branches,
+        })
+    }
+
+    /// Returns the branch outcomes which are reported in the exported coverage, i.e., all
+    /// outcomes except those for which it is unknown how often they were taken.
+    pub fn reported_branches(&self) -> impl Iterator<Item = &BranchCoverage> {
+        self.branches
+            .iter()
+            .filter(|branch| branch.hits != BranchHits::Unknown)
+    }
+
+    /// Returns the map from (1-based) line to the number of times it was executed, over all
+    /// functions of the module.
+    pub fn lines(&self) -> BTreeMap<u32, u64> {
+        let mut lines = BTreeMap::new();
+        for function in &self.functions {
+            for (line, hits) in &function.lines {
+                let entry = lines.entry(*line).or_insert(0);
+                *entry = (*entry).max(*hits);
+            }
+        }
+        lines
+    }
+}
+
+/// Writes the coverage in the LCOV tracefile format. Modules defined in the same source file
+/// are merged into one record.
+pub fn write_lcov<W: Write>(modules: &[ModuleLineCoverage], writer: &mut W) -> io::Result<()> {
+    let mut modules_by_file: BTreeMap<&Path, Vec<&ModuleLineCoverage>> = BTreeMap::new();
+    for module in modules {
+        modules_by_file
+            .entry(module.source_path.as_path())
+            .or_default()
+            .push(module);
+    }
+
+    for (source_path, modules) in modules_by_file {
+        writeln!(writer, "TN:")?;
+        writeln!(writer, "SF:{}", source_path.display())?;
+
+        let functions: Vec<_> = modules
+            .iter()
+            .flat_map(|module| {
+                module.functions.iter().map(move |function| {
+                    (
+                        format!("{}::{}", module.module_name, function.name),
+                        function,
+                    )
+                })
+            })
+            .collect();
+        for (name, function) in &functions {
+            writeln!(writer, "FN:{},{}", function.line, name)?;
+        }
+        for (name, function) in &functions {
+            writeln!(writer, "FNDA:{},{}", function.hits, name)?;
+        }
+        writeln!(writer, "FNF:{}", functions.len())?;
+        writeln!(
+            writer,
+            "FNH:{}",
+            functions
+                .iter()
+                .filter(|(_, function)| function.hits > 0)
+                .count()
+        )?;
+
+        // Branches are identified by the module, function and offset of the branch instruction.
+        let mut num_branches = 0;
+        let mut num_branches_hit = 0;
+        let mut block_ids = BTreeMap::new();
+        for module in &modules {
+            for branch in module.reported_branches() {
+                let num_blocks = block_ids.len();
+                let block_id = *block_ids
+                    .entry((&module.module_name, branch.line, branch.offset))
+                    .or_insert(num_blocks);
+                let taken = match branch.hits {
+                    BranchHits::Taken(hits) => hits.to_string(),
+                    _ => "-".to_string(),
+                };
+                writeln!(
+                    writer,
+                    "BRDA:{},{},{},{}",
+                    branch.line, block_id, branch.outcome, taken
+                )?;
+                num_branches += 1;
+                if branch.is_taken() {
+                    num_branches_hit += 1;
+                }
+            }
+        }
+        writeln!(writer, "BRF:{}", num_branches)?;
+        writeln!(writer, "BRH:{}", num_branches_hit)?;
+
+        let mut lines: BTreeMap<u32, u64> = BTreeMap::new();
+        for module in &modules {
+            for (line, hits) in module.lines() {
+                let entry = lines.entry(line).or_insert(0);
+                *entry = (*entry).max(hits);
+            }
+        }
+        for (line, hits) in &lines {
+            writeln!(writer, "DA:{},{}", line, hits)?;
+        }
+        writeln!(writer, "LF:{}", lines.len())?;
+        writeln!(
+            writer,
+            "LH:{}",
+            lines.values().filter(|hits| **hits > 0).count()
+        )?;
+        writeln!(writer, "end_of_record")?;
+    }
+    Ok(())
+}
+
+/// Writes the coverage in the Cobertura XML format, with one class per module. File names are
+/// written relative to `source_root` (if they are below it), which is listed as the source
+/// directory.
+pub fn write_cobertura<W: Write>(
+    modules: &[ModuleLineCoverage],
+    package_name: &str,
+    source_root: &Path,
+    timestamp: u64,
+    writer: &mut W,
+) -> io::Result<()> {
+    let all_lines: Vec<_> = modules.iter().map(|module| module.lines()).collect();
+    let lines_valid: usize = all_lines.iter().map(|lines| lines.len()).sum();
+    let lines_covered: usize = all_lines
+        .iter()
+        .map(|lines| count_covered(lines.values()))
+        .sum();
+    let branches_valid: usize = modules
+        .iter()
+        .map(|module| module.reported_branches().count())
+        .sum();
+    let branches_covered: usize = modules
+        .iter()
+        .map(|module| count_taken(module.reported_branches()))
+        .sum();
+    let line_rate = rate(lines_covered, lines_valid);
+    let branch_rate = rate(branches_covered, branches_valid);
+
+    writeln!(writer, r#"<?xml version="1.0" ?>"#)?;
+    writeln!(
+        writer,
+        r#"<!DOCTYPE coverage SYSTEM "http://cobertura.sourceforge.net/xml/coverage-04.dtd">"#
+    )?;
+    writeln!(
+        writer,
+        r#"<coverage line-rate="{}" branch-rate="{}" lines-covered="{}" lines-valid="{}" branches-covered="{}" branches-valid="{}" complexity="0" version="1.9" timestamp="{}">"#,
+        line_rate,
+        branch_rate,
+        lines_covered,
+        lines_valid,
+        branches_covered,
+        branches_valid,
+        timestamp
+    )?;
+    writeln!(writer, "  <sources>")?;
+    writeln!(
+        writer,
+        "    <source>{}</source>",
+        escape_xml(&source_root.display().to_string())
+    )?;
+    writeln!(writer, "  </sources>")?;
+    writeln!(writer, "  <packages>")?;
+    writeln!(
+        writer,
+        r#"    <package name="{}" line-rate="{}" branch-rate="{}" complexity="0">"#,
+        escape_xml(package_name),
+        line_rate,
+        branch_rate
+    )?;
+    writeln!(writer, "      <classes>")?;
+    for (module, lines) in modules.iter().zip(all_lines.iter()) {
+        let file_name = module
+            .source_path
+            .strip_prefix(source_root)
+            .unwrap_or(&module.source_path);
+        let mut branches_by_line: BTreeMap<u32, Vec<&BranchCoverage>> = BTreeMap::new();
+        for branch in module.reported_branches() {
+            branches_by_line
+                .entry(branch.line)
+                .or_default()
+                .push(branch);
+        }
+        writeln!(
+            writer,
+            r#"        <class name="{}" filename="{}" line-rate="{}" branch-rate="{}" complexity="0">"#,
+            escape_xml(&module.module_name),
+            escape_xml(&file_name.display().to_string()),
+            rate(count_covered(lines.values()), lines.len()),
+            rate(
+                count_taken(module.reported_branches()),
+                module.reported_branches().count()
+            ),
+        )?;
+        writeln!(writer, "          <methods>")?;
+        for function in &module.functions {
+            let function_branches = branches_by_line
+                .iter()
+                .filter(|(line, _)| function.lines.contains_key(line))
+                .flat_map(|(_, branches)| branches.iter().copied())
+                .collect::<Vec<_>>();
+            writeln!(
+                writer,
+                r#"            <method name="{}" signature="" line-rate="{}" branch-rate="{}" complexity="0">"#,
+                escape_xml(&function.name),
+                rate(count_covered(function.lines.values()), function.lines.len()),
+                rate(
+                    count_taken(function_branches.iter().copied()),
+                    function_branches.len()
+                ),
+            )?;
+            writeln!(writer, "              <lines>")?;
+            for (line, hits) in &function.lines {
+                write_cobertura_line(
+                    writer,
+                    "                ",
+                    *line,
+                    *hits,
+                    branches_by_line.get(line),
+                )?;
+            }
+            writeln!(writer, "              </lines>")?;
+            writeln!(writer, "            </method>")?;
+        }
+        writeln!(writer, "          </methods>")?;
+        writeln!(writer, "          <lines>")?;
+        for (line, hits) in lines {
+            write_cobertura_line(
+                writer,
+                "            ",
+                *line,
+                *hits,
+                branches_by_line.get(line),
+            )?;
+        }
+        writeln!(writer, "          </lines>")?;
+        writeln!(writer, "        </class>")?;
+    }
+    writeln!(writer, "      </classes>")?;
+    writeln!(writer, "    </package>")?;
+    writeln!(writer, "  </packages>")?;
+    writeln!(writer, "</coverage>")
+}
+
+fn write_cobertura_line<W: Write>(
+    writer: &mut W,
+    indent: &str,
+    line: u32,
+    hits: u64,
+    branches: Option<&Vec<&BranchCoverage>>,
+) -> io::Result<()> {
+    match branches {
+        Some(branches) => {
+            let taken = count_taken(branches.iter().copied());
+            writeln!(
+                writer,
+                r#"{}<line number="{}" hits="{}" branch="true" condition-coverage="{}% ({}/{})"/>"#,
+                indent,
+                line,
+                hits,
+                taken * 100 / branches.len(),
+                taken,
+                branches.len()
+            )
+        },
+        None => writeln!(
+            writer,
+            r#"{}<line number="{}" hits="{}" branch="false"/>"#,
+            indent, line, hits
+        ),
+    }
+}
+
+fn count_covered<'a>(hits: impl Iterator<Item = &'a u64>) -> usize {
+    hits.filter(|hits| **hits > 0).count()
+}
+
+fn count_taken<'a>(branches: impl Iterator<Item = &'a BranchCoverage>) -> usize {
+    branches.filter(|branch| branch.is_taken()).count()
+}
+
+/// Returns the ratio `covered / valid`, which is 1 if there is nothing to cover.
+fn rate(covered: usize, valid: usize) -> String {
+    if valid == 0 {
+        "1".to_string()
+    } else {
+        format!("{:.4}", covered as f64 / valid as f64)
+    }
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn module_coverage() -> ModuleLineCoverage {
+        ModuleLineCoverage {
+            module_name: "coin".to_string(),
+            source_path: PathBuf::from("/pkg/sources/coin.move"),
+            functions: vec![
+                FunctionLineCoverage {
+                    name: "mint".to_string(),
+                    line: 3,
+                    hits: 2,
+                    lines: BTreeMap::from([(4, 2), (5, 1), (6, 0)]),
+                },
+                FunctionLineCoverage {
+                    name: "burn".to_string(),
+                    line: 9,
+                    hits: 0,
+                    lines: BTreeMap::from([(10, 0)]),
+                },
+            ],
+            branches: vec![
+                BranchCoverage {
+                    line: 4,
+                    offset: 3,
+                    outcome: 0,
+                    hits: BranchHits::Taken(1),
+                },
+                BranchCoverage {
+                    line: 4,
+                    offset: 3,
+                    outcome: 1,
+                    hits: BranchHits::Taken(0),
+                },
+                BranchCoverage {
+                    line: 5,
+                    offset: 7,
+                    outcome: 0,
+                    hits: BranchHits::Unknown,
+                },
+                BranchCoverage {
+                    line: 10,
+                    offset: 1,
+                    outcome: 0,
+                    hits: BranchHits::NotReached,
+                },
+                BranchCoverage {
+                    line: 10,
+                    offset: 1,
+                    outcome: 1,
+                    hits: BranchHits::NotReached,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_lcov() {
+        let mut output = vec![];
+        write_lcov(&[module_coverage()], &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "TN:
+SF:/pkg/sources/coin.move
+FN:3,coin::mint
+FN:9,coin::burn
+FNDA:2,coin::mint
+FNDA:0,coin::burn
+FNF:2
+FNH:1
+BRDA:4,0,0,1
+BRDA:4,0,1,0
+BRDA:10,1,0,-
+BRDA:10,1,1,-
+BRF:4
+BRH:1
+DA:4,2
+DA:5,1
+DA:6,0
+DA:10,0
+LF:4
+LH:2
+end_of_record
+"
+        );
+    }
+
+    #[test]
+    fn test_cobertura() {
+        let mut output = vec![];
+        write_cobertura(
+            &[module_coverage()],
+            "Pkg",
+            Path::new("/pkg"),
+            0,
+            &mut output,
+        )
+        .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains(
+            r#"<coverage line-rate="0.5000" branch-rate="0.2500" lines-covered="2" lines-valid="4" branches-covered="1" branches-valid="4""#
+        ));
+        assert!(output.contains(r#"<class name="coin" filename="sources/coin.move""#));
+        assert!(output.contains(
+            r#"<line number="4" hits="2" branch="true" condition-coverage="50% (1/2)"/>"#
+        ));
+        assert!(output.contains(r#"<line number="6" hits="0" branch="false"/>"#));
+        // Outcomes which are not known to be taken or not are not reported
+        assert!(output.contains(r#"<line number="5" hits="1" branch="false"/>"#));
+    }
+}
@@ -0,0 +1,24 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! A [Debug Adapter Protocol](https://microsoft.github.io/debug-adapter-protocol/) server for
+//! stepping through the execution of Move code.
+//!
+//! A [DebugSession] waits for a client (e.g., an editor) to attach over TCP, and then installs a
+//! debugger into the VM which is notified of every executed instruction. The client can set
+//! breakpoints by source line, step through the code, inspect the call stack and the locals of
+//! the innermost frame, and evaluate watch expressions on globals (`global<Type>(address)`).
+//! Bytecode locations are mapped to source lines through the source maps of the compiled
+//! modules, collected in a [SourceIndex].
+//!
+//! The session is not tied to how the code is executed, so it can be used for unit tests as well
+//! as for simulating or replaying transactions. Since the debugger is global to the process,
+//! code should be executed on a single thread while a session is active.
+
+mod protocol;
+mod session;
+mod source_index;
+mod vm_debugger;
+
+pub use session::{DebugSession, ResourceReader};
+pub use source_index::{CodeLocation, SourceIndex, SourcePosition};
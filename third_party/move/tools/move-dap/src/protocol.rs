@@ -0,0 +1,69 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Framing of Debug Adapter Protocol messages, which are JSON objects preceded by a
+//! `Content-Length` header.
+
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+use std::io::{self, BufRead, Write};
+
+/// Reads the next message, or returns `None` if the stream has been closed.
+pub(crate) fn read_message<R: BufRead>(reader: &mut R) -> Result<Option<Value>> {
+    let mut content_length = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            if content_length.is_some() {
+                break;
+            }
+            continue;
+        }
+        if let Some(length) = line.strip_prefix("Content-Length:") {
+            content_length = Some(length.trim().parse::<usize>()?);
+        }
+    }
+    let content_length = content_length.ok_or_else(|| anyhow!("Missing Content-Length header"))?;
+    let mut content = vec![0; content_length];
+    reader.read_exact(&mut content)?;
+    Ok(Some(serde_json::from_slice(&content)?))
+}
+
+pub(crate) fn write_message<W: Write>(writer: &mut W, message: &Value) -> io::Result<()> {
+    let content = message.to_string();
+    write!(
+        writer,
+        "Content-Length: {}\r\n\r\n{}",
+        content.len(),
+        content
+    )?;
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_roundtrip() {
+        let messages = vec![
+            json!({"seq": 1, "type": "request", "command": "initialize"}),
+            json!({"seq": 2, "type": "request", "command": "threads", "arguments": {}}),
+        ];
+        let mut buf = vec![];
+        for message in &messages {
+            write_message(&mut buf, message).unwrap();
+        }
+
+        let mut reader = buf.as_slice();
+        for message in &messages {
+            assert_eq!(read_message(&mut reader).unwrap().as_ref(), Some(message));
+        }
+        assert!(read_message(&mut reader).unwrap().is_none());
+    }
+}
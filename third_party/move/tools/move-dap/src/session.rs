@@ -0,0 +1,385 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    protocol::{read_message, write_message},
+    source_index::{CodeLocation, SourceIndex},
+    vm_debugger::{SharedState, StepMode, VmCommand, VmDebugger},
+};
+use anyhow::{anyhow, Result};
+use move_core_types::{account_address::AccountAddress, language_storage::StructTag};
+use move_vm_runtime::debug::set_debugger;
+use serde_json::{json, Value};
+use std::{
+    collections::BTreeMap,
+    io::{self, BufReader},
+    net::{TcpListener, TcpStream},
+    path::PathBuf,
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+};
+
+/// Reads a resource from storage, for watch expressions on resources which the program has not
+/// loaded yet. Returns `None` if the resource does not exist.
+pub type ResourceReader = Box<dyn Fn(&AccountAddress, &StructTag) -> Result<Option<String>> + Send>;
+
+/// The program is reported to the client as a single thread.
+const THREAD_ID: u64 = 1;
+
+/// The variables reference of the locals of the innermost frame.
+const LOCALS_REFERENCE: u64 = 1;
+
+pub(crate) enum SessionEvent {
+    Request(Value),
+    ClientClosed,
+    Stopped(&'static str),
+    Terminated(i64),
+}
+
+/// A debug session with a client attached over the Debug Adapter Protocol.
+pub struct DebugSession {
+    events: Sender<SessionEvent>,
+    server: Option<JoinHandle<()>>,
+}
+
+impl DebugSession {
+    /// Waits for a client to attach on `port`, and to configure the session (e.g., to set
+    /// breakpoints). Afterwards, the debugger is notified of all instructions executed in this
+    /// process until the session is finished.
+    pub fn start(
+        port: u16,
+        index: SourceIndex,
+        resource_reader: Option<ResourceReader>,
+    ) -> Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", port))?;
+        eprintln!("Waiting for a debugger to attach on port {}...", port);
+        let (stream, _) = listener.accept()?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+
+        let (event_sender, event_receiver) = mpsc::channel();
+        let requests = event_sender.clone();
+        thread::spawn(move || loop {
+            match read_message(&mut reader) {
+                Ok(Some(request)) => {
+                    if requests.send(SessionEvent::Request(request)).is_err() {
+                        break;
+                    }
+                },
+                Ok(None) | Err(_) => {
+                    let _ = requests.send(SessionEvent::ClientClosed);
+                    break;
+                },
+            }
+        });
+
+        let index = Arc::new(index);
+        let shared = Arc::new(Mutex::new(SharedState::default()));
+        let (command_sender, command_receiver) = mpsc::channel();
+        let (configured_sender, configured_receiver) = mpsc::channel();
+        let server = Server {
+            writer: stream,
+            seq: 0,
+            index: index.clone(),
+            shared: shared.clone(),
+            commands: command_sender,
+            configured: Some(configured_sender),
+            stop_on_entry: false,
+            breakpoints: BTreeMap::new(),
+        };
+        let server = thread::spawn(move || server.run(event_receiver));
+
+        let stop_on_entry = configured_receiver
+            .recv()
+            .map_err(|_| anyhow!("The debugger disconnected before the session was configured"))?;
+        set_debugger(Some(Box::new(VmDebugger::new(
+            index,
+            shared,
+            event_sender.clone(),
+            command_receiver,
+            resource_reader,
+            stop_on_entry,
+        ))));
+        Ok(Self {
+            events: event_sender,
+            server: Some(server),
+        })
+    }
+
+    /// Finishes the session after the program has terminated with `exit_code`, and waits for
+    /// the client to disconnect.
+    pub fn finish(mut self, exit_code: i64) {
+        self.terminate(exit_code);
+    }
+
+    fn terminate(&mut self, exit_code: i64) {
+        let Some(server) = self.server.take() else {
+            return;
+        };
+        set_debugger(None);
+        let _ = self.events.send(SessionEvent::Terminated(exit_code));
+        let _ = server.join();
+    }
+}
+
+impl Drop for DebugSession {
+    fn drop(&mut self) {
+        self.terminate(1);
+    }
+}
+
+/// Serves the requests of the client.
+struct Server {
+    writer: TcpStream,
+    seq: u64,
+    index: Arc<SourceIndex>,
+    shared: Arc<Mutex<SharedState>>,
+    commands: Sender<VmCommand>,
+    /// Notified with the `stopOnEntry` argument once the client has finished configuring.
+    configured: Option<Sender<bool>>,
+    stop_on_entry: bool,
+    /// The breakpoints set by the client, per source file.
+    breakpoints: BTreeMap<PathBuf, Vec<(u32, Vec<CodeLocation>)>>,
+}
+
+impl Server {
+    fn run(mut self, events: Receiver<SessionEvent>) {
+        for event in events {
+            let result = match event {
+                SessionEvent::Request(request) => match self.handle_request(&request) {
+                    Ok(true) => return,
+                    Ok(false) => Ok(()),
+                    Err(err) => Err(err),
+                },
+                SessionEvent::Stopped(reason) => self.send_event(
+                    "stopped",
+                    json!({"reason": reason, "threadId": THREAD_ID, "allThreadsStopped": true}),
+                ),
+                SessionEvent::Terminated(exit_code) => self
+                    .send_event("exited", json!({ "exitCode": exit_code }))
+                    .and_then(|_| self.send_event("terminated", json!({}))),
+                SessionEvent::ClientClosed => return,
+            };
+            if result.is_err() {
+                // The connection is broken.
+                return;
+            }
+        }
+    }
+
+    /// Handles a request of the client. Returns `true` if the client disconnected.
+    fn handle_request(&mut self, request: &Value) -> io::Result<bool> {
+        let command = request["command"].as_str().unwrap_or_default();
+        let arguments = &request["arguments"];
+        let body = match command {
+            "initialize" => Ok(json!({
+                "supportsConfigurationDoneRequest": true,
+                "supportsEvaluateForHovers": true,
+            })),
+            "launch" | "attach" => {
+                self.stop_on_entry = arguments["stopOnEntry"].as_bool().unwrap_or(false);
+                Ok(json!({}))
+            },
+            "setBreakpoints" => Ok(self.set_breakpoints(arguments)),
+            "setExceptionBreakpoints" => Ok(json!({ "breakpoints": [] })),
+            "configurationDone" => {
+                if let Some(configured) = self.configured.take() {
+                    let _ = configured.send(self.stop_on_entry);
+                }
+                Ok(json!({}))
+            },
+            "threads" => Ok(json!({ "threads": [{"id": THREAD_ID, "name": "main"}] })),
+            "stackTrace" => self.stack_trace(),
+            "scopes" => Ok(Self::scopes(arguments)),
+            "variables" => self.variables(arguments),
+            "continue" => self
+                .resume(StepMode::Continue)
+                .map(|_| json!({ "allThreadsContinued": true })),
+            "next" => self.resume(StepMode::StepOver).map(|_| json!({})),
+            "stepIn" => self.resume(StepMode::StepIn).map(|_| json!({})),
+            "stepOut" => self.resume(StepMode::StepOut).map(|_| json!({})),
+            "pause" => {
+                self.shared.lock().unwrap().pause_requested = true;
+                Ok(json!({}))
+            },
+            "evaluate" => self.evaluate(arguments),
+            "disconnect" => {
+                // Let the program run to completion.
+                self.shared.lock().unwrap().breakpoints.clear();
+                let _ = self.resume(StepMode::Continue);
+                self.send_response(request, Ok(json!({})))?;
+                return Ok(true);
+            },
+            _ => Err(format!("Unsupported request `{}`", command)),
+        };
+        self.send_response(request, body)?;
+        if command == "initialize" {
+            self.send_event("initialized", json!({}))?;
+        }
+        Ok(false)
+    }
+
+    fn set_breakpoints(&mut self, arguments: &Value) -> Value {
+        let path = PathBuf::from(arguments["source"]["path"].as_str().unwrap_or_default());
+        let requested_lines = arguments["breakpoints"]
+            .as_array()
+            .map(|breakpoints| {
+                breakpoints
+                    .iter()
+                    .filter_map(|breakpoint| breakpoint["line"].as_u64())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        let mut resolved = vec![];
+        let breakpoints = requested_lines
+            .into_iter()
+            .map(
+                |line| match self.index.resolve_breakpoint(&path, line as u32) {
+                    Some((line, locations)) => {
+                        resolved.push((line, locations));
+                        json!({"verified": true, "line": line})
+                    },
+                    None => json!({
+                        "verified": false,
+                        "line": line,
+                        "message": "No code at or after this line",
+                    }),
+                },
+            )
+            .collect::<Vec<_>>();
+        self.breakpoints.insert(path, resolved);
+
+        let mut shared = self.shared.lock().unwrap();
+        shared.breakpoints.clear();
+        for location in self
+            .breakpoints
+            .values()
+            .flatten()
+            .flat_map(|(_, locations)| locations)
+        {
+            shared
+                .breakpoints
+                .entry(location.module_id.clone())
+                .or_default()
+                .insert((location.function_index, location.pc));
+        }
+        json!({ "breakpoints": breakpoints })
+    }
+
+    fn stack_trace(&self) -> Result<Value, String> {
+        let shared = self.shared.lock().unwrap();
+        let stopped = shared
+            .stopped
+            .as_ref()
+            .ok_or("The program is not stopped")?;
+        let frames = stopped
+            .frames
+            .iter()
+            .enumerate()
+            .map(|(id, frame)| match frame.position {
+                Some(position) => {
+                    let path = self.index.file_path(position.file);
+                    json!({
+                        "id": id,
+                        "name": frame.name,
+                        "source": {
+                            "name": path.file_name().map(|name| name.to_string_lossy()),
+                            "path": path.display().to_string(),
+                        },
+                        "line": position.line,
+                        "column": 1,
+                    })
+                },
+                None => json!({
+                    "id": id,
+                    "name": frame.name,
+                    "line": 0,
+                    "column": 0,
+                    "presentationHint": "subtle",
+                }),
+            })
+            .collect::<Vec<_>>();
+        Ok(json!({ "stackFrames": frames, "totalFrames": frames.len() }))
+    }
+
+    /// Only the locals of the innermost frame are available.
+    fn scopes(arguments: &Value) -> Value {
+        if arguments["frameId"].as_u64() == Some(0) {
+            json!({ "scopes": [{
+                "name": "Locals",
+                "variablesReference": LOCALS_REFERENCE,
+                "expensive": false,
+            }] })
+        } else {
+            json!({ "scopes": [] })
+        }
+    }
+
+    fn variables(&self, arguments: &Value) -> Result<Value, String> {
+        if arguments["variablesReference"].as_u64() != Some(LOCALS_REFERENCE) {
+            return Ok(json!({ "variables": [] }));
+        }
+        let shared = self.shared.lock().unwrap();
+        let stopped = shared
+            .stopped
+            .as_ref()
+            .ok_or("The program is not stopped")?;
+        let variables = stopped
+            .locals
+            .iter()
+            .map(|(name, value)| json!({"name": name, "value": value, "variablesReference": 0}))
+            .collect::<Vec<_>>();
+        Ok(json!({ "variables": variables }))
+    }
+
+    fn resume(&self, step_mode: StepMode) -> Result<(), String> {
+        if self.shared.lock().unwrap().stopped.take().is_none() {
+            return Err("The program is not stopped".to_string());
+        }
+        self.commands
+            .send(VmCommand::Resume(step_mode))
+            .map_err(|_| "The program has terminated".to_string())
+    }
+
+    fn evaluate(&self, arguments: &Value) -> Result<Value, String> {
+        if self.shared.lock().unwrap().stopped.is_none() {
+            return Err("Expressions can only be evaluated while the program is stopped".into());
+        }
+        let expression = arguments["expression"].as_str().unwrap_or_default();
+        let (reply_sender, reply_receiver) = mpsc::channel();
+        self.commands
+            .send(VmCommand::Evaluate(expression.to_string(), reply_sender))
+            .map_err(|_| "The program has terminated".to_string())?;
+        let result = reply_receiver
+            .recv()
+            .map_err(|_| "The program has terminated".to_string())??;
+        Ok(json!({ "result": result, "variablesReference": 0 }))
+    }
+
+    fn send_response(&mut self, request: &Value, body: Result<Value, String>) -> io::Result<()> {
+        let mut response = json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "command": request["command"],
+            "success": body.is_ok(),
+        });
+        match body {
+            Ok(body) => response["body"] = body,
+            Err(message) => response["message"] = json!(message),
+        }
+        self.send(response)
+    }
+
+    fn send_event(&mut self, event: &str, body: Value) -> io::Result<()> {
+        self.send(json!({"type": "event", "event": event, "body": body}))
+    }
+
+    fn send(&mut self, mut message: Value) -> io::Result<()> {
+        self.seq += 1;
+        message["seq"] = json!(self.seq);
+        write_message(&mut self.writer, &message)
+    }
+}
@@ -0,0 +1,214 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use codespan::{ByteIndex, Files};
+use legacy_move_compiler::compiled_unit::{CompiledUnit, NamedCompiledModule};
+use move_binary_format::{
+    access::ModuleAccess,
+    file_format::{CodeOffset, FunctionDefinitionIndex},
+    CompiledModule,
+};
+use move_bytecode_source_map::source_map::SourceMap;
+use move_core_types::language_storage::ModuleId;
+use move_package::compilation::compiled_package::CompiledPackage;
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// A location in the bytecode of a module.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CodeLocation {
+    pub module_id: ModuleId,
+    pub function_index: FunctionDefinitionIndex,
+    pub pc: CodeOffset,
+}
+
+/// A line in one of the source files of a [SourceIndex].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SourcePosition {
+    pub file: usize,
+    /// The 1-based line number.
+    pub line: u32,
+}
+
+struct FunctionInfo {
+    name: String,
+    /// The source position of each instruction, if known.
+    positions: Vec<Option<SourcePosition>>,
+    /// The names of the parameters and locals.
+    local_names: Vec<String>,
+}
+
+/// Maps the instructions of functions to the source lines they were compiled from.
+#[derive(Default)]
+pub struct SourceIndex {
+    files: Vec<PathBuf>,
+    functions: BTreeMap<ModuleId, BTreeMap<FunctionDefinitionIndex, FunctionInfo>>,
+}
+
+impl SourceIndex {
+    /// Creates an index of all modules of the package (including dependencies) which have been
+    /// compiled from source.
+    pub fn from_package(package: &CompiledPackage) -> Result<Self> {
+        let mut index = Self::default();
+        for unit in package.all_modules() {
+            if let CompiledUnit::Module(NamedCompiledModule {
+                module, source_map, ..
+            }) = &unit.unit
+            {
+                index.add_module(module, source_map, &unit.source_path)?;
+            }
+        }
+        Ok(index)
+    }
+
+    /// Adds a module compiled from `source_path`. Modules whose source map does not match the
+    /// contents of the file are skipped.
+    pub fn add_module(
+        &mut self,
+        module: &CompiledModule,
+        source_map: &SourceMap,
+        source_path: &Path,
+    ) -> Result<()> {
+        let Ok(file_contents) = fs::read_to_string(source_path) else {
+            return Ok(());
+        };
+        if !source_map.check(&file_contents) {
+            return Ok(());
+        }
+        let mut files = Files::new();
+        let file_id = files.add(source_path.as_os_str().to_os_string(), file_contents);
+        let file_hash = source_map.definition_location.file_hash();
+        // Several modules may be defined in the same file.
+        let path = fs::canonicalize(source_path).unwrap_or_else(|_| source_path.to_path_buf());
+        let file = match self.files.iter().position(|file_path| *file_path == path) {
+            Some(file) => file,
+            None => {
+                self.files.push(path);
+                self.files.len() - 1
+            },
+        };
+
+        let module_id = module.self_id();
+        for (idx, function_def) in module.function_defs().iter().enumerate() {
+            let Some(code_unit) = &function_def.code else {
+                continue;
+            };
+            let function_index = FunctionDefinitionIndex(idx as u16);
+            let Ok(function_map) = source_map.get_function_source_map(function_index) else {
+                continue;
+            };
+            let function_handle = module.function_handle_at(function_def.function);
+            let positions = (0..code_unit.code.len())
+                .map(|pc| {
+                    function_map
+                        .get_code_location(pc as CodeOffset)
+                        // Code inlined from other files is not mapped.
+                        .filter(|loc| loc.file_hash() == file_hash)
+                        .and_then(|loc| files.location(file_id, ByteIndex(loc.start())).ok())
+                        .map(|location| SourcePosition {
+                            file,
+                            line: location.line.0 + 1,
+                        })
+                })
+                .collect();
+            let num_locals = module.signature_at(function_handle.parameters).len()
+                + module.signature_at(code_unit.locals).len();
+            let local_names = (0..num_locals)
+                .map(
+                    |idx| match function_map.get_parameter_or_local_name(idx as u64) {
+                        Some((name, _)) => name,
+                        None => format!("local{}", idx),
+                    },
+                )
+                .collect();
+            self.functions.entry(module_id.clone()).or_default().insert(
+                function_index,
+                FunctionInfo {
+                    name: module.identifier_at(function_handle.name).to_string(),
+                    positions,
+                    local_names,
+                },
+            );
+        }
+        Ok(())
+    }
+
+    fn function(
+        &self,
+        module_id: &ModuleId,
+        function_index: FunctionDefinitionIndex,
+    ) -> Option<&FunctionInfo> {
+        self.functions.get(module_id)?.get(&function_index)
+    }
+
+    pub fn file_path(&self, file: usize) -> &Path {
+        &self.files[file]
+    }
+
+    pub fn position(
+        &self,
+        module_id: &ModuleId,
+        function_index: FunctionDefinitionIndex,
+        pc: CodeOffset,
+    ) -> Option<SourcePosition> {
+        self.function(module_id, function_index)?
+            .positions
+            .get(pc as usize)
+            .copied()
+            .flatten()
+    }
+
+    pub fn function_name(
+        &self,
+        module_id: &ModuleId,
+        function_index: FunctionDefinitionIndex,
+    ) -> Option<&str> {
+        self.function(module_id, function_index)
+            .map(|info| info.name.as_str())
+    }
+
+    pub fn local_names(
+        &self,
+        module_id: &ModuleId,
+        function_index: FunctionDefinitionIndex,
+    ) -> Option<&[String]> {
+        self.function(module_id, function_index)
+            .map(|info| info.local_names.as_slice())
+    }
+
+    /// Resolves a breakpoint at `line` of the file at `path`. The breakpoint is moved to the
+    /// first line at or after `line` which has code. Returns that line, and the locations at
+    /// which execution enters it.
+    pub fn resolve_breakpoint(&self, path: &Path, line: u32) -> Option<(u32, Vec<CodeLocation>)> {
+        let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        let file = self.files.iter().position(|file_path| *file_path == path)?;
+        let line = self
+            .functions
+            .values()
+            .flat_map(|functions| functions.values())
+            .flat_map(|info| info.positions.iter().flatten())
+            .filter(|position| position.file == file && position.line >= line)
+            .map(|position| position.line)
+            .min()?;
+        let position = Some(SourcePosition { file, line });
+        let mut locations = vec![];
+        for (module_id, functions) in &self.functions {
+            for (function_index, info) in functions {
+                for (pc, pc_position) in info.positions.iter().enumerate() {
+                    if *pc_position == position && (pc == 0 || info.positions[pc - 1] != position) {
+                        locations.push(CodeLocation {
+                            module_id: module_id.clone(),
+                            function_index: *function_index,
+                            pc: pc as CodeOffset,
+                        });
+                    }
+                }
+            }
+        }
+        Some((line, locations))
+    }
+}
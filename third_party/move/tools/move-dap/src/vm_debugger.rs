@@ -0,0 +1,321 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! The debugger installed into the VM, which decides when to stop execution, and serves the
+//! requests of the client while execution is stopped.
+
+use crate::{
+    session::{ResourceReader, SessionEvent},
+    source_index::{SourceIndex, SourcePosition},
+};
+use move_binary_format::file_format::{CodeOffset, FunctionDefinitionIndex};
+use move_core_types::{
+    account_address::AccountAddress,
+    language_storage::{ModuleId, StructTag},
+};
+use move_vm_runtime::debug::{DebugState, Debugger};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    str::FromStr,
+    sync::{
+        mpsc::{Receiver, Sender},
+        Arc, Mutex,
+    },
+};
+
+/// How execution continues after it has been stopped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum StepMode {
+    Continue,
+    /// Stop at the next source line, including lines of called functions.
+    StepIn,
+    /// Stop at the next source line of the current function or of its callers.
+    StepOver,
+    /// Stop once the current function has returned.
+    StepOut,
+}
+
+/// Commands of the client, executed on the thread of the VM while it is stopped.
+pub(crate) enum VmCommand {
+    Resume(StepMode),
+    Evaluate(String, Sender<Result<String, String>>),
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct StackFrame {
+    pub(crate) name: String,
+    pub(crate) position: Option<SourcePosition>,
+}
+
+/// Snapshot of the state of a stopped program.
+#[derive(Clone, Debug)]
+pub(crate) struct StoppedState {
+    /// Frames of the call stack, innermost first.
+    pub(crate) frames: Vec<StackFrame>,
+    /// Names and values of the locals of the innermost frame.
+    pub(crate) locals: Vec<(String, String)>,
+}
+
+/// State shared between the session and the debugger in the VM.
+#[derive(Default)]
+pub(crate) struct SharedState {
+    pub(crate) breakpoints: BTreeMap<ModuleId, BTreeSet<(FunctionDefinitionIndex, CodeOffset)>>,
+    pub(crate) pause_requested: bool,
+    pub(crate) stopped: Option<StoppedState>,
+}
+
+pub(crate) struct VmDebugger {
+    index: Arc<SourceIndex>,
+    shared: Arc<Mutex<SharedState>>,
+    events: Sender<SessionEvent>,
+    commands: Receiver<VmCommand>,
+    resource_reader: Option<ResourceReader>,
+    step_mode: StepMode,
+    /// Call stack depth and source position at which the current step started.
+    step_origin: (usize, Option<SourcePosition>),
+    /// Set once the client is gone, after which execution is never stopped again.
+    detached: bool,
+}
+
+impl VmDebugger {
+    pub(crate) fn new(
+        index: Arc<SourceIndex>,
+        shared: Arc<Mutex<SharedState>>,
+        events: Sender<SessionEvent>,
+        commands: Receiver<VmCommand>,
+        resource_reader: Option<ResourceReader>,
+        stop_on_entry: bool,
+    ) -> Self {
+        Self {
+            index,
+            shared,
+            events,
+            commands,
+            resource_reader,
+            step_mode: if stop_on_entry {
+                StepMode::StepIn
+            } else {
+                StepMode::Continue
+            },
+            step_origin: (0, None),
+            detached: false,
+        }
+    }
+
+    fn step_finished(&self, depth: usize, position: Option<SourcePosition>) -> bool {
+        // Only stop at instructions which can be shown in the source.
+        if position.is_none() {
+            return false;
+        }
+        let (origin_depth, origin_position) = self.step_origin;
+        match self.step_mode {
+            StepMode::Continue => false,
+            StepMode::StepIn => depth != origin_depth || position != origin_position,
+            StepMode::StepOver => {
+                depth < origin_depth || (depth == origin_depth && position != origin_position)
+            },
+            StepMode::StepOut => depth < origin_depth,
+        }
+    }
+
+    fn stop(
+        &mut self,
+        state: &DebugState,
+        reason: &'static str,
+        depth: usize,
+        position: Option<SourcePosition>,
+    ) {
+        self.shared.lock().unwrap().stopped = Some(self.stopped_state(state));
+        if self.events.send(SessionEvent::Stopped(reason)).is_err() {
+            self.detached = true;
+            return;
+        }
+        loop {
+            match self.commands.recv() {
+                Ok(VmCommand::Evaluate(expression, reply)) => {
+                    let _ = reply.send(self.evaluate(state, &expression));
+                },
+                Ok(VmCommand::Resume(step_mode)) => {
+                    self.step_mode = step_mode;
+                    self.step_origin = (depth, position);
+                    return;
+                },
+                Err(_) => {
+                    self.detached = true;
+                    return;
+                },
+            }
+        }
+    }
+
+    fn stopped_state(&self, state: &DebugState) -> StoppedState {
+        let mut frames = vec![StackFrame {
+            name: match state.module_id() {
+                Some(module_id) => {
+                    format!(
+                        "{}::{}",
+                        module_id.short_str_lossless(),
+                        state.function_name()
+                    )
+                },
+                None => state.function_name().to_string(),
+            },
+            position: state.module_id().and_then(|module_id| {
+                self.index
+                    .position(module_id, state.function_index(), state.pc())
+            }),
+        }];
+        for (module_id, function_index, pc) in state.callers() {
+            frames.push(match module_id {
+                Some(module_id) => StackFrame {
+                    name: format!(
+                        "{}::{}",
+                        module_id.short_str_lossless(),
+                        self.index
+                            .function_name(&module_id, function_index)
+                            .map(|name| name.to_string())
+                            .unwrap_or_else(|| format!("<function {}>", function_index)),
+                    ),
+                    position: self.index.position(&module_id, function_index, pc),
+                },
+                None => StackFrame {
+                    name: "<script>".to_string(),
+                    position: None,
+                },
+            });
+        }
+
+        let local_names = state
+            .module_id()
+            .and_then(|module_id| self.index.local_names(module_id, state.function_index()));
+        let locals = state
+            .locals()
+            .into_iter()
+            .enumerate()
+            .filter_map(|(idx, value)| {
+                let name = local_names
+                    .and_then(|names| names.get(idx).cloned())
+                    .unwrap_or_else(|| format!("local{}", idx));
+                Some((name, value?))
+            })
+            .collect();
+        StoppedState { frames, locals }
+    }
+
+    /// Evaluates a watch expression, which is either the name of a local or a global of the
+    /// form `global<Type>(address)`.
+    fn evaluate(&self, state: &DebugState, expression: &str) -> Result<String, String> {
+        let expression = expression.trim();
+        if let Some((addr, struct_tag)) = parse_global(expression)? {
+            let resource = match state.resource(&addr, &struct_tag) {
+                Some(resource) => resource,
+                None => match &self.resource_reader {
+                    Some(resource_reader) => {
+                        resource_reader(&addr, &struct_tag).map_err(|err| err.to_string())?
+                    },
+                    None => {
+                        return Err(format!(
+                            "`{}` has not been loaded by the program yet",
+                            struct_tag.to_canonical_string()
+                        ))
+                    },
+                },
+            };
+            return Ok(resource.unwrap_or_else(|| "<does not exist>".to_string()));
+        }
+
+        let stopped = self.shared.lock().unwrap().stopped.clone();
+        stopped
+            .and_then(|stopped| {
+                stopped
+                    .locals
+                    .into_iter()
+                    .find(|(name, _)| name == expression)
+                    .map(|(_, value)| value)
+            })
+            .ok_or_else(|| {
+                format!(
+                    "Unknown expression `{}`. Expected the name of a local or `global<Type>(address)`.",
+                    expression
+                )
+            })
+    }
+}
+
+impl Debugger for VmDebugger {
+    fn on_instruction(&mut self, state: &DebugState) {
+        if self.detached {
+            return;
+        }
+        let depth = state.call_stack_depth();
+        let position = state.module_id().and_then(|module_id| {
+            self.index
+                .position(module_id, state.function_index(), state.pc())
+        });
+        let reason = {
+            let mut shared = self.shared.lock().unwrap();
+            let at_breakpoint = state.module_id().is_some_and(|module_id| {
+                shared
+                    .breakpoints
+                    .get(module_id)
+                    .is_some_and(|pcs| pcs.contains(&(state.function_index(), state.pc())))
+            });
+            if std::mem::take(&mut shared.pause_requested) {
+                Some("pause")
+            } else if at_breakpoint {
+                Some("breakpoint")
+            } else if self.step_finished(depth, position) {
+                Some("step")
+            } else {
+                None
+            }
+        };
+        if let Some(reason) = reason {
+            self.stop(state, reason, depth, position);
+        }
+    }
+}
+
+/// Parses an expression of the form `global<Type>(address)`. Returns `None` if the expression
+/// does not refer to a global.
+fn parse_global(expression: &str) -> Result<Option<(AccountAddress, StructTag)>, String> {
+    let Some(rest) = expression.strip_prefix("global<") else {
+        return Ok(None);
+    };
+    let Some((struct_tag, addr)) = rest
+        .strip_suffix(')')
+        .and_then(|rest| rest.rsplit_once(">("))
+    else {
+        return Err(format!(
+            "Cannot parse `{}`, expected `global<Type>(address)`",
+            expression
+        ));
+    };
+    let struct_tag = StructTag::from_str(struct_tag.trim()).map_err(|err| err.to_string())?;
+    let addr = addr.trim();
+    let addr = AccountAddress::from_hex_literal(addr.strip_prefix('@').unwrap_or(addr))
+        .map_err(|err| err.to_string())?;
+    Ok(Some((addr, struct_tag)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_global() {
+        let (addr, struct_tag) =
+            parse_global("global<0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>>(@0xcafe)")
+                .unwrap()
+                .unwrap();
+        assert_eq!(addr, AccountAddress::from_hex_literal("0xcafe").unwrap());
+        assert_eq!(
+            struct_tag.to_canonical_string(),
+            StructTag::from_str("0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>")
+                .unwrap()
+                .to_canonical_string()
+        );
+        assert!(parse_global("counter").unwrap().is_none());
+        assert!(parse_global("global<0x1::m::R>").is_err());
+    }
+}
@@ -0,0 +1,231 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use move_cli::base::test::{run_move_unit_tests, UnitTestResult};
+use move_core_types::effects::ChangeSet;
+use move_dap::{DebugSession, SourceIndex};
+use move_unit_test::UnitTestingConfig;
+use serde_json::{json, Value};
+use std::{
+    fs,
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    path::Path,
+    thread,
+    time::Duration,
+};
+use tempfile::tempdir;
+
+const MOVE_TOML: &str = r#"
+[package]
+name = "Debuggee"
+version = "0.0.0"
+
+[addresses]
+debuggee = "0x42"
+"#;
+
+const SOURCE: &str = r#"module debuggee::m {
+    fun add(x: u64, y: u64): u64 {
+        let sum = x + y;
+        sum
+    }
+
+    #[test]
+    fun test_add() {
+        assert!(add(40, 2) == 42, 0);
+    }
+}
+"#;
+
+/// The line of `let sum = x + y;` in `SOURCE`.
+const BREAKPOINT_LINE: u64 = 3;
+
+/// A minimal client of the Debug Adapter Protocol.
+struct Client {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+    seq: u64,
+}
+
+impl Client {
+    /// Connects to the session, retrying until it is listening.
+    fn connect(port: u16) -> Self {
+        for _ in 0..100 {
+            if let Ok(stream) = TcpStream::connect(("127.0.0.1", port)) {
+                return Self {
+                    reader: BufReader::new(stream.try_clone().unwrap()),
+                    writer: stream,
+                    seq: 0,
+                };
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+        panic!("Failed to connect to the debug session on port {}", port);
+    }
+
+    fn send(&mut self, command: &str, arguments: Value) {
+        self.seq += 1;
+        let content = json!({
+            "seq": self.seq,
+            "type": "request",
+            "command": command,
+            "arguments": arguments,
+        })
+        .to_string();
+        write!(
+            self.writer,
+            "Content-Length: {}\r\n\r\n{}",
+            content.len(),
+            content
+        )
+        .unwrap();
+        self.writer.flush().unwrap();
+    }
+
+    fn receive(&mut self) -> Value {
+        let mut content_length = None;
+        loop {
+            let mut line = String::new();
+            assert!(self.reader.read_line(&mut line).unwrap() > 0);
+            let line = line.trim_end();
+            if line.is_empty() && content_length.is_some() {
+                break;
+            }
+            if let Some(length) = line.strip_prefix("Content-Length:") {
+                content_length = Some(length.trim().parse::<usize>().unwrap());
+            }
+        }
+        let mut content = vec![0; content_length.unwrap()];
+        self.reader.read_exact(&mut content).unwrap();
+        serde_json::from_slice(&content).unwrap()
+    }
+
+    /// Sends a request, and returns the body of its response. Events received in the meantime
+    /// are skipped.
+    fn request(&mut self, command: &str, arguments: Value) -> Value {
+        self.send(command, arguments);
+        loop {
+            let message = self.receive();
+            if message["type"] == "response" && message["request_seq"] == self.seq {
+                assert_eq!(message["success"], true, "{} failed: {}", command, message);
+                return message["body"].clone();
+            }
+        }
+    }
+
+    /// Waits for the next event with the given name, and returns its body.
+    fn wait_for_event(&mut self, event: &str) -> Value {
+        loop {
+            let message = self.receive();
+            if message["type"] == "event" && message["event"] == event {
+                return message["body"].clone();
+            }
+        }
+    }
+}
+
+fn free_port() -> u16 {
+    TcpListener::bind(("127.0.0.1", 0))
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+#[test]
+fn test_stop_at_breakpoint_and_read_local() {
+    let dir = tempdir().unwrap();
+    let package_path = dir.path();
+    fs::write(package_path.join("Move.toml"), MOVE_TOML).unwrap();
+    fs::create_dir(package_path.join("sources")).unwrap();
+    let source_path = package_path.join("sources").join("m.move");
+    fs::write(&source_path, SOURCE).unwrap();
+
+    // The tests are compiled in test mode, which needs to be matched for the source maps
+    let build_config = move_package::BuildConfig {
+        dev_mode: true,
+        test_mode: true,
+        ..Default::default()
+    };
+    let package = build_config
+        .clone()
+        .compile_package(package_path, &mut Vec::new())
+        .unwrap();
+    let index = SourceIndex::from_package(&package).unwrap();
+
+    let port = free_port();
+    let client = thread::spawn(move || run_client(port, &source_path));
+    let session = DebugSession::start(port, index, None).unwrap();
+
+    let result = run_move_unit_tests(
+        package_path,
+        build_config,
+        UnitTestingConfig {
+            num_threads: 1,
+            ..Default::default()
+        },
+        vec![],
+        ChangeSet::new(),
+        /* gas_limit */ None,
+        /* cost_table */ None,
+        /* compute_coverage */ false,
+        &mut Vec::new(),
+    )
+    .unwrap();
+    // If the tests failed, the session is finished with a failure when it is dropped
+    assert_eq!(result, UnitTestResult::Success);
+    session.finish(0);
+    client.join().unwrap();
+}
+
+/// Stops at the breakpoint, checks the stack and the locals, and lets the tests run to the end.
+fn run_client(port: u16, source_path: &Path) {
+    let mut client = Client::connect(port);
+    client.request("initialize", json!({ "adapterID": "move" }));
+    client.wait_for_event("initialized");
+    let breakpoints = client.request(
+        "setBreakpoints",
+        json!({
+            "source": { "path": source_path.display().to_string() },
+            "breakpoints": [{ "line": BREAKPOINT_LINE }],
+        }),
+    );
+    assert_eq!(breakpoints["breakpoints"][0]["verified"], true);
+    assert_eq!(breakpoints["breakpoints"][0]["line"], BREAKPOINT_LINE);
+    client.request("configurationDone", json!({}));
+
+    let stopped = client.wait_for_event("stopped");
+    assert_eq!(stopped["reason"], "breakpoint");
+
+    let stack_trace = client.request("stackTrace", json!({ "threadId": 1 }));
+    let frames = stack_trace["stackFrames"].as_array().unwrap();
+    assert_eq!(frames[0]["name"], "0x42::m::add");
+    assert_eq!(frames[0]["line"], BREAKPOINT_LINE);
+    assert_eq!(frames[1]["name"], "0x42::m::test_add");
+
+    let scopes = client.request("scopes", json!({ "frameId": 0 }));
+    let variables_reference = scopes["scopes"][0]["variablesReference"].clone();
+    let variables = client.request(
+        "variables",
+        json!({ "variablesReference": variables_reference }),
+    );
+    let value_of = |name: &str| {
+        variables["variables"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|variable| variable["name"] == name)
+            .map(|variable| variable["value"].clone())
+    };
+    assert_eq!(value_of("x"), Some(json!("U64(40)")));
+    assert_eq!(value_of("y"), Some(json!("U64(2)")));
+
+    let evaluated = client.request("evaluate", json!({ "expression": "x" }));
+    assert_eq!(evaluated["result"], "U64(40)");
+
+    client.request("continue", json!({ "threadId": 1 }));
+    let exited = client.wait_for_event("exited");
+    assert_eq!(exited["exitCode"], 0);
+    client.request("disconnect", json!({}));
+}
@@ -6,24 +6,110 @@ mod stackless_bytecode_lints;
 mod utils;
 
 use move_compiler_v2::external_checks::{ExpChecker, ExternalChecks, StacklessBytecodeChecker};
-use std::sync::Arc;
+use std::{collections::BTreeSet, fmt, str::FromStr, sync::Arc};
+
+/// A set of lint checks that can be enabled together.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LintSet {
+    /// The default lint checks, which are always enabled.
+    Default,
+    /// Opt-in lint checks that flag common security issues in Aptos contracts
+    /// (e.g., unchecked signers or leaked object references).
+    Security,
+}
+
+impl LintSet {
+    /// Returns all the available lint sets.
+    pub fn all() -> Vec<LintSet> {
+        vec![LintSet::Default, LintSet::Security]
+    }
+
+    fn get_exp_checkers(&self) -> Vec<Box<dyn ExpChecker>> {
+        match self {
+            LintSet::Default => model_ast_lints::get_default_linter_pipeline(),
+            LintSet::Security => model_ast_lints::get_security_linter_pipeline(),
+        }
+    }
+
+    fn get_stackless_bytecode_checkers(&self) -> Vec<Box<dyn StacklessBytecodeChecker>> {
+        match self {
+            LintSet::Default => stackless_bytecode_lints::get_default_linter_pipeline(),
+            LintSet::Security => stackless_bytecode_lints::get_security_linter_pipeline(),
+        }
+    }
+}
+
+impl fmt::Display for LintSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LintSet::Default => write!(f, "default"),
+            LintSet::Security => write!(f, "security"),
+        }
+    }
+}
+
+impl FromStr for LintSet {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "default" => Ok(LintSet::Default),
+            "security" => Ok(LintSet::Security),
+            _ => Err(format!(
+                "unknown lint set `{}`, expected one of: default, security",
+                s
+            )),
+        }
+    }
+}
 
 /// Holds collection of lint checks for Move.
-pub struct MoveLintChecks {}
+pub struct MoveLintChecks {
+    lint_sets: BTreeSet<LintSet>,
+}
 
 impl ExternalChecks for MoveLintChecks {
     fn get_exp_checkers(&self) -> Vec<Box<dyn ExpChecker>> {
-        model_ast_lints::get_default_linter_pipeline()
+        self.lint_sets
+            .iter()
+            .flat_map(|lint_set| lint_set.get_exp_checkers())
+            .collect()
     }
 
     fn get_stackless_bytecode_checkers(&self) -> Vec<Box<dyn StacklessBytecodeChecker>> {
-        stackless_bytecode_lints::get_default_linter_pipeline()
+        self.lint_sets
+            .iter()
+            .flat_map(|lint_set| lint_set.get_stackless_bytecode_checkers())
+            .collect()
+    }
+
+    fn get_known_checker_names(&self) -> BTreeSet<String> {
+        // Checks in lint sets that are not enabled can still be skipped via attributes,
+        // so that the same code can be linted with different lint sets.
+        let mut names = BTreeSet::new();
+        for lint_set in LintSet::all() {
+            for checker in lint_set.get_exp_checkers() {
+                names.insert(checker.get_name());
+            }
+            for checker in lint_set.get_stackless_bytecode_checkers() {
+                names.insert(checker.get_name());
+            }
+        }
+        names
     }
 }
 
 impl MoveLintChecks {
     /// Make an instance of lint checks for Move, provided as `ExternalChecks`.
     pub fn make() -> Arc<dyn ExternalChecks> {
-        Arc::new(MoveLintChecks {})
+        Self::make_with_lint_sets(&[LintSet::Default])
+    }
+
+    /// Make an instance of lint checks for Move with the given lint sets enabled.
+    /// The default lint set is always enabled.
+    pub fn make_with_lint_sets(lint_sets: &[LintSet]) -> Arc<dyn ExternalChecks> {
+        let mut lint_sets: BTreeSet<_> = lint_sets.iter().copied().collect();
+        lint_sets.insert(LintSet::Default);
+        Arc::new(MoveLintChecks { lint_sets })
     }
 }
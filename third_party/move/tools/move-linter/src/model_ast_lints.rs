@@ -13,6 +13,8 @@ mod needless_ref_in_field_access;
 mod nonminimal_bool;
 mod self_assignment;
 mod simpler_numeric_expression;
+mod unbounded_vector_iteration;
+mod unchecked_narrowing_cast;
 mod unnecessary_boolean_identity_comparison;
 mod unnecessary_numerical_extreme_comparison;
 mod while_true;
@@ -37,3 +39,11 @@ pub fn get_default_linter_pipeline() -> Vec<Box<dyn ExpChecker>> {
         Box::<while_true::WhileTrue>::default(),
     ]
 }
+
+/// Returns a pipeline of opt-in "expression linters" that flag common security issues.
+pub fn get_security_linter_pipeline() -> Vec<Box<dyn ExpChecker>> {
+    vec![
+        Box::<unbounded_vector_iteration::UnboundedVectorIteration>::default(),
+        Box::<unchecked_narrowing_cast::UncheckedNarrowingCast>::default(),
+    ]
+}
@@ -0,0 +1,117 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module implements an expression linter that checks public and entry functions
+//! for loops (including `vector::for_each` and similar inline functions) over vector
+//! parameters whose length is never bounded. Since the caller controls the length of
+//! such vectors, the loop can exhaust the gas of the transaction, or make the function
+//! too expensive for other contracts to call.
+//! The length is considered bounded if it is compared to a constant anywhere in the
+//! function, e.g., `assert!(vector::length(&v) <= MAX_ITEMS, E_TOO_MANY_ITEMS)`.
+
+use move_compiler_v2::external_checks::ExpChecker;
+use move_model::{
+    ast::{ExpData, Operation, TempIndex},
+    model::{FunId, FunctionEnv, QualifiedId, Visibility},
+};
+
+#[derive(Default)]
+pub struct UnboundedVectorIteration;
+
+impl ExpChecker for UnboundedVectorIteration {
+    fn get_name(&self) -> String {
+        "unbounded_vector_iteration".to_string()
+    }
+
+    fn visit_expr_pre(&mut self, function: &FunctionEnv, expr: &ExpData) {
+        if function.visibility() != Visibility::Public && !function.is_entry() {
+            return;
+        }
+        let iterated_param = match expr {
+            ExpData::Loop(_, body) => vector_params(function)
+                .into_iter()
+                .find(|param| body.any(&mut |e| refers_to_param(e, *param))),
+            ExpData::Call(_, Operation::MoveFunction(mid, fid), args)
+                if is_vector_iteration_function(function, mid.qualified(*fid)) =>
+            {
+                vector_params(function)
+                    .into_iter()
+                    .find(|param| args.first().is_some_and(|arg| refers_to_param(arg, *param)))
+            },
+            _ => None,
+        };
+        let Some(param) = iterated_param else {
+            return;
+        };
+        if is_length_bounded(function, param) {
+            return;
+        }
+        let env = function.env();
+        let param_name = function.get_parameters()[param].0;
+        self.report(
+            env,
+            &env.get_node_loc(expr.node_id()),
+            &format!(
+                "This loop iterates over the caller-controlled vector `{}` without bounding its length. \
+                 Consider checking `vector::length` against a maximum first.",
+                param_name.display(function.symbol_pool())
+            ),
+        );
+    }
+}
+
+/// Returns the indices of the parameters of `function` that are vectors (or references to vectors).
+fn vector_params(function: &FunctionEnv) -> Vec<TempIndex> {
+    function
+        .get_parameters()
+        .iter()
+        .enumerate()
+        .filter(|(_, param)| param.1.skip_reference().is_vector())
+        .map(|(idx, _)| idx)
+        .collect()
+}
+
+/// Returns `true` if `expr` is the parameter `param`, possibly borrowed or dereferenced.
+fn refers_to_param(expr: &ExpData, param: TempIndex) -> bool {
+    match expr {
+        ExpData::Temporary(_, idx) => *idx == param,
+        ExpData::Call(_, Operation::Borrow(_) | Operation::Deref, args) => {
+            refers_to_param(&args[0], param)
+        },
+        _ => false,
+    }
+}
+
+/// Returns `true` if the callee is an inline function of `std::vector` which iterates
+/// over its first argument (e.g., `vector::for_each` or `vector::map`).
+fn is_vector_iteration_function(function: &FunctionEnv, callee: QualifiedId<FunId>) -> bool {
+    let callee = function.env().get_function(callee);
+    callee.module_env.is_std_vector() && callee.is_inline()
+}
+
+/// Returns `true` if the length of the vector parameter `param` is compared to a constant
+/// anywhere in the function.
+fn is_length_bounded(function: &FunctionEnv, param: TempIndex) -> bool {
+    let Some(def) = function.get_def() else {
+        return false;
+    };
+    def.any(&mut |e| {
+        let ExpData::Call(_, Operation::Lt | Operation::Le | Operation::Gt | Operation::Ge, args) =
+            e
+        else {
+            return false;
+        };
+        let is_length_of_param = |arg: &ExpData| match arg {
+            ExpData::Call(_, Operation::MoveFunction(mid, fid), call_args) => {
+                let callee = function.env().get_function(mid.qualified(*fid));
+                callee.module_env.is_std_vector()
+                    && callee.get_name_str() == "length"
+                    && refers_to_param(&call_args[0], param)
+            },
+            _ => false,
+        };
+        let is_constant = |arg: &ExpData| matches!(arg, ExpData::Value(..));
+        (is_length_of_param(&args[0]) && is_constant(&args[1]))
+            || (is_constant(&args[0]) && is_length_of_param(&args[1]))
+    })
+}
@@ -0,0 +1,108 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module implements an expression linter that checks for narrowing casts
+//! (e.g., `(x as u8)` where `x: u64`) whose operand is not bounds-checked anywhere
+//! in the function. Such casts abort at runtime if the value does not fit the
+//! target type, which can be used to make a transaction (or a whole feature) fail.
+//! Operands that are explicitly truncated (via `%` or `&`) are not reported.
+
+use move_compiler_v2::external_checks::ExpChecker;
+use move_model::{
+    ast::{ExpData, Operation},
+    model::FunctionEnv,
+    ty::{PrimitiveType, Type},
+};
+
+#[derive(Default)]
+pub struct UncheckedNarrowingCast;
+
+impl ExpChecker for UncheckedNarrowingCast {
+    fn get_name(&self) -> String {
+        "unchecked_narrowing_cast".to_string()
+    }
+
+    fn visit_expr_pre(&mut self, function: &FunctionEnv, expr: &ExpData) {
+        let ExpData::Call(id, Operation::Cast, args) = expr else {
+            return;
+        };
+        let env = function.env();
+        let operand = &args[0];
+        let (Some(source_bits), Some(target_bits)) = (
+            int_bits(&env.get_node_type(operand.node_id())),
+            int_bits(&env.get_node_type(*id)),
+        ) else {
+            return;
+        };
+        if target_bits >= source_bits || is_truncated_or_constant(operand) {
+            return;
+        }
+        if is_variable(operand) && is_compared_in_function(function, operand) {
+            return;
+        }
+        self.report(
+            env,
+            &env.get_node_loc(*id),
+            &format!(
+                "This narrowing cast aborts if the value does not fit in `u{}`. \
+                 Consider checking the value's bounds before casting.",
+                target_bits
+            ),
+        );
+    }
+}
+
+/// Returns the bit width of the given (unsigned) integer type.
+fn int_bits(ty: &Type) -> Option<usize> {
+    match ty {
+        Type::Primitive(PrimitiveType::U8) => Some(8),
+        Type::Primitive(PrimitiveType::U16) => Some(16),
+        Type::Primitive(PrimitiveType::U32) => Some(32),
+        Type::Primitive(PrimitiveType::U64) => Some(64),
+        Type::Primitive(PrimitiveType::U128) => Some(128),
+        Type::Primitive(PrimitiveType::U256) => Some(256),
+        _ => None,
+    }
+}
+
+/// Returns `true` if the cast operand is a constant or is explicitly truncated.
+fn is_truncated_or_constant(operand: &ExpData) -> bool {
+    matches!(
+        operand,
+        ExpData::Value(..) | ExpData::Call(_, Operation::Mod | Operation::BitAnd, _)
+    )
+}
+
+/// Returns `true` if the expression is a (possibly dereferenced) local or parameter.
+fn is_variable(expr: &ExpData) -> bool {
+    match expr {
+        ExpData::LocalVar(..) | ExpData::Temporary(..) => true,
+        ExpData::Call(_, Operation::Deref, args) => is_variable(&args[0]),
+        _ => false,
+    }
+}
+
+/// Returns `true` if the two expressions refer to the same local or parameter.
+fn is_same_variable(expr1: &ExpData, expr2: &ExpData) -> bool {
+    match (expr1, expr2) {
+        (ExpData::Call(_, Operation::Deref, args), expr)
+        | (expr, ExpData::Call(_, Operation::Deref, args)) => is_same_variable(&args[0], expr),
+        (ExpData::LocalVar(_, s1), ExpData::LocalVar(_, s2)) => s1 == s2,
+        (ExpData::Temporary(_, t1), ExpData::Temporary(_, t2)) => t1 == t2,
+        _ => false,
+    }
+}
+
+/// Returns `true` if the variable is compared (e.g., `x <= MAX`) anywhere in the function.
+fn is_compared_in_function(function: &FunctionEnv, variable: &ExpData) -> bool {
+    let Some(def) = function.get_def() else {
+        return false;
+    };
+    def.any(&mut |e| {
+        matches!(
+            e,
+            ExpData::Call(_, Operation::Lt | Operation::Le | Operation::Gt | Operation::Ge, args)
+                if args.iter().any(|arg| is_same_variable(arg, variable))
+        )
+    })
+}
@@ -6,7 +6,9 @@
 //! The lint checks also assume that all the correctness checks have already been performed.
 
 mod avoid_copy_on_identity_comparison;
+mod leaked_object_ref;
 mod needless_mutable_reference;
+mod unchecked_signer;
 
 use move_compiler_v2::external_checks::StacklessBytecodeChecker;
 
@@ -17,3 +19,11 @@ pub fn get_default_linter_pipeline() -> Vec<Box<dyn StacklessBytecodeChecker>> {
         Box::new(needless_mutable_reference::NeedlessMutableReference {}),
     ]
 }
+
+/// Get a pipeline of opt-in "stackless bytecode linters" that flag common security issues.
+pub fn get_security_linter_pipeline() -> Vec<Box<dyn StacklessBytecodeChecker>> {
+    vec![
+        Box::new(leaked_object_ref::LeakedObjectRef {}),
+        Box::new(unchecked_signer::UncheckedSigner {}),
+    ]
+}
@@ -0,0 +1,74 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module implements a stackless-bytecode linter that checks public functions
+//! which return object capabilities, i.e., `object::ConstructorRef`, `object::TransferRef`
+//! or `object::ExtendRef`. Any module can call such functions, and use the returned
+//! reference to transfer the object (or to generate its signer), bypassing the
+//! restrictions of the module that created the object.
+//! Such functions should usually be `friend` (or `package`) functions instead.
+
+use move_compiler_v2::external_checks::StacklessBytecodeChecker;
+use move_model::{
+    model::{GlobalEnv, Visibility},
+    ty::Type,
+};
+use move_stackless_bytecode::function_target::FunctionTarget;
+
+/// The object capability types that should not leak out of public functions.
+const OBJECT_REF_TYPES: &[&str] = &["ConstructorRef", "TransferRef", "ExtendRef"];
+
+pub struct LeakedObjectRef {}
+
+impl StacklessBytecodeChecker for LeakedObjectRef {
+    fn get_name(&self) -> String {
+        "leaked_object_ref".to_string()
+    }
+
+    fn check(&self, target: &FunctionTarget) {
+        let func_env = target.func_env;
+        if func_env.visibility() != Visibility::Public {
+            return;
+        }
+        let env = target.global_env();
+        let result_type = func_env.get_result_type();
+        let leaked_refs = match &result_type {
+            Type::Tuple(tys) => tys
+                .iter()
+                .filter_map(|ty| object_ref_name(env, ty))
+                .collect(),
+            ty => object_ref_name(env, ty).into_iter().collect::<Vec<_>>(),
+        };
+        for leaked_ref in leaked_refs {
+            self.report(
+                env,
+                &func_env.get_id_loc(),
+                &format!(
+                    "This public function returns an `object::{}`, which allows any caller to control the object. \
+                     Consider restricting the function's visibility (e.g., to `friend`).",
+                    leaked_ref
+                ),
+            );
+        }
+    }
+}
+
+/// Returns the name of the object capability type, if `ty` is (a reference to) one.
+fn object_ref_name(env: &GlobalEnv, ty: &Type) -> Option<&'static str> {
+    let Type::Struct(mid, sid, _) = ty.skip_reference() else {
+        return None;
+    };
+    let struct_env = env.get_struct(mid.qualified(*sid));
+    // The Aptos framework (which defines `object`) shares the standard library address.
+    let module_name = struct_env.module_env.get_name();
+    if module_name.addr() != &env.get_stdlib_address()
+        || module_name.name().display(env.symbol_pool()).to_string() != "object"
+    {
+        return None;
+    }
+    let struct_name = struct_env.get_name().display(env.symbol_pool()).to_string();
+    OBJECT_REF_TYPES
+        .iter()
+        .find(|name| **name == struct_name)
+        .copied()
+}
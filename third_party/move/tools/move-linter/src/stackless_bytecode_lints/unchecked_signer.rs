@@ -0,0 +1,147 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module implements a stackless-bytecode linter that checks public and entry
+//! functions which take a signer, and modify global state (via `borrow_global_mut`
+//! or `move_from`) at an address that is not derived from the signer, without ever
+//! checking the signer. For example:
+//! ```move
+//! public entry fun set_fee(admin: &signer, fee: u64) acquires Config {
+//!     borrow_global_mut<Config>(@my_addr).fee = fee;
+//! }
+//! ```
+//! Here, any account can change the fee, as `admin` is never compared against the
+//! stored owner (e.g., `assert!(signer::address_of(admin) == @my_addr, E_NOT_ADMIN)`).
+//! The signer is considered checked if a value derived from it is compared, used
+//! in an `exists` check, or passed to a function other than those in `std::signer`.
+
+use move_compiler_v2::external_checks::StacklessBytecodeChecker;
+use move_model::{
+    ast::TempIndex,
+    model::{ModuleId, Visibility},
+};
+use move_stackless_bytecode::{
+    function_target::FunctionTarget,
+    stackless_bytecode::{Bytecode, Operation},
+};
+use std::collections::BTreeSet;
+
+pub struct UncheckedSigner {}
+
+impl StacklessBytecodeChecker for UncheckedSigner {
+    fn get_name(&self) -> String {
+        "unchecked_signer".to_string()
+    }
+
+    fn check(&self, target: &FunctionTarget) {
+        let func_env = target.func_env;
+        if func_env.visibility() != Visibility::Public && !func_env.is_entry() {
+            return;
+        }
+        let symbol_pool = func_env.symbol_pool();
+        let signer_params = func_env
+            .get_parameters()
+            .into_iter()
+            .enumerate()
+            .filter(|(_, param)| {
+                param.1.skip_reference().is_signer()
+                    && !param.0.display(symbol_pool).to_string().starts_with('_')
+            })
+            .collect::<Vec<_>>();
+        if signer_params.is_empty() {
+            return;
+        }
+        let signer_derived =
+            signer_derived_temps(target, signer_params.iter().map(|(idx, _)| *idx).collect());
+        let code = target.get_bytecode();
+        let mut signer_checked = false;
+        let mut modifies_other_state = false;
+        for bc in code {
+            let Bytecode::Call(_, dests, op, srcs, _) = bc else {
+                continue;
+            };
+            let uses_signer = srcs.iter().any(|src| signer_derived.contains(src));
+            match op {
+                Operation::Eq | Operation::Neq | Operation::Exists(..) if uses_signer => {
+                    signer_checked = true;
+                },
+                Operation::Function(mid, _, _) if uses_signer => {
+                    if !is_signer_module(target, *mid) {
+                        signer_checked = true;
+                    }
+                },
+                // Only mutable borrows modify global state.
+                Operation::BorrowGlobal(..) if !uses_signer => {
+                    if target.get_local_type(dests[0]).is_mutable_reference() {
+                        modifies_other_state = true;
+                    }
+                },
+                Operation::MoveFrom(..) if !uses_signer => {
+                    modifies_other_state = true;
+                },
+                _ => {},
+            }
+        }
+        if signer_checked || !modifies_other_state {
+            return;
+        }
+        for (_, param) in signer_params {
+            self.report(
+                target.global_env(),
+                &param.2,
+                &format!(
+                    "The signer `{name}` is never checked, but global state at another address is modified. \
+                     Consider checking that the signer is authorized (e.g., `signer::address_of({name}) == owner`).",
+                    name = param.0.display(symbol_pool),
+                ),
+            );
+        }
+    }
+}
+
+/// Returns `true` if the given module is `std::signer`.
+fn is_signer_module(target: &FunctionTarget, mid: ModuleId) -> bool {
+    let module_env = target.global_env().get_module(mid);
+    module_env.get_name().addr() == &target.global_env().get_stdlib_address()
+        && module_env
+            .get_name()
+            .name()
+            .display(module_env.symbol_pool())
+            .to_string()
+            == "signer"
+}
+
+/// Returns all the temps that hold values derived from the given signer temps
+/// (e.g., via assignments, borrows, or `signer::address_of`).
+fn signer_derived_temps(
+    target: &FunctionTarget,
+    mut derived: BTreeSet<TempIndex>,
+) -> BTreeSet<TempIndex> {
+    // Iterate until a fixpoint is reached, to account for loops.
+    loop {
+        let num_derived = derived.len();
+        for bc in target.get_bytecode() {
+            match bc {
+                Bytecode::Assign(_, dest, src, _) if derived.contains(src) => {
+                    derived.insert(*dest);
+                },
+                Bytecode::Call(_, dests, op, srcs, _)
+                    if srcs.iter().any(|src| derived.contains(src)) =>
+                {
+                    let propagates = match op {
+                        Operation::BorrowLoc | Operation::ReadRef | Operation::FreezeRef(_) => true,
+                        Operation::Function(mid, _, _) => is_signer_module(target, *mid),
+                        _ => false,
+                    };
+                    if propagates {
+                        derived.extend(dests.iter().copied());
+                    }
+                },
+                _ => {},
+            }
+        }
+        if derived.len() == num_derived {
+            return derived;
+        }
+    }
+}
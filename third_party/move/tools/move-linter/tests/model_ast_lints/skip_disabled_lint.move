@@ -0,0 +1,7 @@
+module 0xc0ffee::m {
+    // Checks of lint sets that are not enabled can still be skipped.
+    #[lint::skip(unchecked_narrowing_cast)]
+    public fun test_no_warn(x: u64): u8 {
+        (x as u8)
+    }
+}
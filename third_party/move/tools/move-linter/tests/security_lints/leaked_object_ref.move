@@ -0,0 +1,44 @@
+#[lint::skip(leaked_object_ref)]
+module 0x1::object {
+    struct ConstructorRef has drop {
+        self: address,
+    }
+
+    struct TransferRef has drop {
+        self: address,
+    }
+
+    public fun create_object(owner: address): ConstructorRef {
+        ConstructorRef { self: owner }
+    }
+
+    public fun generate_transfer_ref(ref: &ConstructorRef): TransferRef {
+        TransferRef { self: ref.self }
+    }
+}
+
+module 0xc0ffee::m {
+    use 0x1::object::{Self, ConstructorRef, TransferRef};
+
+    public fun test_warn_constructor_ref(): ConstructorRef {
+        object::create_object(@0xc0ffee)
+    }
+
+    public fun test_warn_transfer_ref(): (u64, TransferRef) {
+        let ref = object::create_object(@0xc0ffee);
+        (0, object::generate_transfer_ref(&ref))
+    }
+
+    fun test_no_warn_private(): ConstructorRef {
+        object::create_object(@0xc0ffee)
+    }
+
+    public fun test_no_warn_drops_ref() {
+        let _ref = test_no_warn_private();
+    }
+
+    #[lint::skip(leaked_object_ref)]
+    public fun test_no_warn_skipped(): ConstructorRef {
+        object::create_object(@0xc0ffee)
+    }
+}
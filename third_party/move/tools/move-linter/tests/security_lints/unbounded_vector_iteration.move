@@ -0,0 +1,44 @@
+module 0xc0ffee::m {
+    use std::vector;
+
+    const MAX_ITEMS: u64 = 100;
+
+    public fun test_warn_while(v: vector<u64>): u64 {
+        let sum = 0;
+        let i = 0;
+        while (i < vector::length(&v)) { sum = sum + *vector::borrow(&v, i); i = i + 1; };
+        sum
+    }
+
+    public fun test_warn_for_each(v: vector<u64>): u64 {
+        let sum = 0;
+        vector::for_each(v, |x| { sum = sum + x });
+        sum
+    }
+
+    public fun test_no_warn_bounded(v: vector<u64>): u64 {
+        assert!(vector::length(&v) <= MAX_ITEMS, 1);
+        let sum = 0;
+        vector::for_each_ref(&v, |x| { sum = sum + *x });
+        sum
+    }
+
+    public fun test_no_warn_private_helper(v: vector<u64>): u64 {
+        assert!(vector::length(&v) <= MAX_ITEMS, 1);
+        sum_all(&v)
+    }
+
+    fun sum_all(v: &vector<u64>): u64 {
+        let sum = 0;
+        let i = 0;
+        while (i < vector::length(v)) { sum = sum + *vector::borrow(v, i); i = i + 1; };
+        sum
+    }
+
+    #[lint::skip(unbounded_vector_iteration)]
+    public fun test_no_warn_skipped(v: vector<u64>): u64 {
+        let sum = 0;
+        vector::for_each(v, |x| { sum = sum + x });
+        sum
+    }
+}
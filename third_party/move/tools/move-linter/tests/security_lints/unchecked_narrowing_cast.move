@@ -0,0 +1,29 @@
+module 0xc0ffee::m {
+    const MAX_U8: u64 = 255;
+
+    public fun test_warn_param(x: u64): u8 {
+        (x as u8)
+    }
+
+    public fun test_warn_expr(a: u128, b: u128): u64 {
+        ((a * b) as u64)
+    }
+
+    public fun test_no_warn_checked(x: u64): u8 {
+        assert!(x <= MAX_U8, 1);
+        (x as u8)
+    }
+
+    public fun test_no_warn_truncated(x: u64): u8 {
+        ((x % 256) as u8)
+    }
+
+    public fun test_no_warn_widening(x: u8): u64 {
+        (x as u64)
+    }
+
+    #[lint::skip(unchecked_narrowing_cast)]
+    public fun test_no_warn_skipped(x: u64): u8 {
+        (x as u8)
+    }
+}
@@ -0,0 +1,40 @@
+module 0xc0ffee::m {
+    use std::signer;
+
+    struct Config has key {
+        fee: u64,
+    }
+
+    struct Receipt has key {
+        fee: u64,
+    }
+
+    public entry fun test_warn(admin: &signer, fee: u64) acquires Config {
+        move_to(admin, Receipt { fee });
+        borrow_global_mut<Config>(@0xc0ffee).fee = fee;
+    }
+
+    public entry fun test_no_warn_checked(admin: &signer, fee: u64) acquires Config {
+        assert!(signer::address_of(admin) == @0xc0ffee, 1);
+        borrow_global_mut<Config>(@0xc0ffee).fee = fee;
+    }
+
+    public entry fun test_no_warn_own_state(account: &signer, fee: u64) acquires Config {
+        borrow_global_mut<Config>(signer::address_of(account)).fee = fee;
+    }
+
+    public entry fun test_no_warn_init(account: &signer) {
+        move_to(account, Config { fee: 0 });
+    }
+
+    public fun test_no_warn_read_only(account: &signer): u64 acquires Config {
+        move_to(account, Receipt { fee: 0 });
+        borrow_global<Config>(@0xc0ffee).fee
+    }
+
+    #[lint::skip(unchecked_signer)]
+    public entry fun test_no_warn_skipped(admin: &signer, fee: u64) acquires Config {
+        move_to(admin, Receipt { fee });
+        borrow_global_mut<Config>(@0xc0ffee).fee = fee;
+    }
+}
@@ -3,7 +3,7 @@
 
 use codespan_reporting::{diagnostic::Severity, term::termcolor::Buffer};
 use move_compiler_v2::{diagnostics::human::HumanEmitter, run_move_compiler, Experiment};
-use move_linter::MoveLintChecks;
+use move_linter::{LintSet, MoveLintChecks};
 use move_model::metadata::{CompilerVersion, LanguageVersion};
 use move_prover_test_utils::baseline_test;
 use std::path::{Path, PathBuf};
@@ -13,7 +13,18 @@ pub const EXP_EXT: &str = "exp";
 
 datatest_stable::harness!(test_runner, "tests", r".*\.move$");
 
+/// Directory of tests for the opt-in security lint set.
+pub const SECURITY_LINTS_DIR: &str = "security_lints";
+
 fn test_runner(path: &Path) -> datatest_stable::Result<()> {
+    let external_checks = if path
+        .components()
+        .any(|c| c.as_os_str() == SECURITY_LINTS_DIR)
+    {
+        MoveLintChecks::make_with_lint_sets(&[LintSet::Security])
+    } else {
+        MoveLintChecks::make()
+    };
     let compiler_options = move_compiler_v2::Options {
         sources: vec![path.display().to_string()],
         dependencies: vec![path_from_crate_root("../../move-stdlib/sources")],
@@ -25,7 +36,7 @@ fn test_runner(path: &Path) -> datatest_stable::Result<()> {
         language_version: Some(LanguageVersion::latest_stable()),
         compiler_version: Some(CompilerVersion::latest_stable()),
         experiments: vec![Experiment::LINT_CHECKS.to_string()],
-        external_checks: vec![MoveLintChecks::make()],
+        external_checks: vec![external_checks],
         ..Default::default()
     };
     let mut output = String::new();
@@ -0,0 +1,566 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Generation and shrinking of arguments for `#[fuzz]` tests.
+//!
+//! Each parameter of a fuzz test which is not assigned in the attribute is generated randomly
+//! from its layout for every run. Integers favor edge values (e.g., `0` or the maximum value),
+//! vectors are kept short, and options are none or some equally often. When a run fails, the
+//! failing arguments are shrunk towards simpler values (smaller integers, shorter vectors) as
+//! long as the test keeps failing the same way.
+
+use legacy_move_compiler::unit_test::FuzzArgument;
+use move_core_types::{
+    account_address::AccountAddress,
+    language_storage::{ModuleId, StructTag},
+    u256::U256,
+    value::{MoveStruct, MoveStructLayout, MoveTypeLayout, MoveValue},
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// The default number of runs of each fuzz test.
+pub const DEFAULT_FUZZ_RUNS: u64 = 100;
+
+/// The maximum length of generated vectors (and strings).
+const MAX_VECTOR_LENGTH: usize = 16;
+
+/// The maximum number of runs spent on shrinking a failing input.
+pub(crate) const MAX_SHRINK_RUNS: usize = 1000;
+
+/// Configuration of fuzz test runs.
+#[derive(Debug, Clone, Copy)]
+pub struct FuzzConfig {
+    /// The number of runs of each fuzz test.
+    pub num_runs: u64,
+    /// The seed from which the arguments of all fuzz tests are generated.
+    pub seed: u64,
+}
+
+/// Returns the random number generator for the given fuzz test. The generator only depends on
+/// the seed and the name of the test, so that tests generate the same arguments independent of
+/// the order (or the subset of tests) in which they are run.
+pub(crate) fn test_rng(seed: u64, module_id: &ModuleId, function_name: &str) -> StdRng {
+    // FNV-1a, which is stable across platforms and Rust versions.
+    let name = format!("{}::{}", module_id, function_name);
+    let hash = name.bytes().fold(0xCBF2_9CE4_8422_2325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01B3)
+    });
+    StdRng::seed_from_u64(seed ^ hash)
+}
+
+/// Generates the arguments for one run of a fuzz test.
+pub(crate) fn generate_arguments(
+    rng: &mut StdRng,
+    arguments: &[(String, FuzzArgument)],
+) -> Vec<MoveValue> {
+    arguments
+        .iter()
+        .map(|(_, argument)| match argument {
+            FuzzArgument::Fixed(value) => value.clone(),
+            FuzzArgument::Generated(layout) => generate_value(rng, layout),
+        })
+        .collect()
+}
+
+/// Returns candidates for simpler arguments than `values`, simplest first. Each candidate
+/// differs from `values` in exactly one generated argument.
+pub(crate) fn shrink_arguments(
+    arguments: &[(String, FuzzArgument)],
+    values: &[MoveValue],
+) -> Vec<Vec<MoveValue>> {
+    let mut candidates = vec![];
+    for (idx, ((_, argument), value)) in arguments.iter().zip(values).enumerate() {
+        if let FuzzArgument::Generated(layout) = argument {
+            for shrunk in shrink_value(layout, value) {
+                let mut candidate = values.to_vec();
+                candidate[idx] = shrunk;
+                candidates.push(candidate);
+            }
+        }
+    }
+    candidates
+}
+
+/// Formats the arguments of a fuzz test run, e.g. `x = 1, v = vector[2, 3]`.
+pub(crate) fn format_arguments(
+    arguments: &[(String, FuzzArgument)],
+    values: &[MoveValue],
+) -> String {
+    arguments
+        .iter()
+        .zip(values)
+        .map(|((name, argument), value)| match argument {
+            FuzzArgument::Generated(layout) => {
+                format!("{} = {}", name, format_value(layout, value))
+            },
+            FuzzArgument::Fixed(value) => format!("{} = {}", name, format_fixed_value(value)),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+//**************************************************************************************************
+// Generation
+//**************************************************************************************************
+
+fn generate_value(rng: &mut StdRng, layout: &MoveTypeLayout) -> MoveValue {
+    if let Some(num_bytes) = int_num_bytes(layout) {
+        return make_int(num_bytes, generate_int(rng, num_bytes));
+    }
+    match layout {
+        MoveTypeLayout::Bool => MoveValue::Bool(rng.gen()),
+        MoveTypeLayout::Address => MoveValue::Address(generate_address(rng)),
+        MoveTypeLayout::Signer => MoveValue::Signer(generate_address(rng)),
+        MoveTypeLayout::Vector(elem_layout) => {
+            let len = rng.gen_range(0, MAX_VECTOR_LENGTH + 1);
+            MoveValue::Vector((0..len).map(|_| generate_value(rng, elem_layout)).collect())
+        },
+        MoveTypeLayout::Struct(MoveStructLayout::WithTypes { type_, .. }) if is_string(type_) => {
+            let len = rng.gen_range(0, MAX_VECTOR_LENGTH + 1);
+            let bytes = (0..len)
+                .map(|_| MoveValue::U8(rng.gen_range(b' ', b'~' + 1)))
+                .collect();
+            MoveValue::Struct(MoveStruct::Runtime(vec![MoveValue::Vector(bytes)]))
+        },
+        MoveTypeLayout::Struct(MoveStructLayout::WithTypes { type_, fields })
+            if is_option(type_) =>
+        {
+            // Options are vectors of at most one element, so generate none and some equally often.
+            let MoveTypeLayout::Vector(elem_layout) = &fields[0].layout else {
+                unreachable!("option with unexpected layout {}", layout)
+            };
+            let elems = if rng.gen() {
+                vec![]
+            } else {
+                vec![generate_value(rng, elem_layout)]
+            };
+            MoveValue::Struct(MoveStruct::Runtime(vec![MoveValue::Vector(elems)]))
+        },
+        MoveTypeLayout::Struct(MoveStructLayout::WithTypes { fields, .. }) => {
+            MoveValue::Struct(MoveStruct::Runtime(
+                fields
+                    .iter()
+                    .map(|field| generate_value(rng, &field.layout))
+                    .collect(),
+            ))
+        },
+        MoveTypeLayout::Struct(MoveStructLayout::WithVariants(variants)) => {
+            let tag = rng.gen_range(0, variants.len());
+            MoveValue::Struct(MoveStruct::RuntimeVariant(
+                tag as u16,
+                variants[tag]
+                    .fields
+                    .iter()
+                    .map(|field| generate_value(rng, &field.layout))
+                    .collect(),
+            ))
+        },
+        _ => unreachable!("fuzz test parameter with unsupported layout {}", layout),
+    }
+}
+
+/// Generates an integer with the given number of bytes. Edge values are generated more often,
+/// as they are more likely to trigger bugs.
+fn generate_int(rng: &mut StdRng, num_bytes: usize) -> U256 {
+    let max = int_max(num_bytes);
+    if rng.gen_ratio(1, 4) {
+        let edge_values = [U256::zero(), U256::one(), max - U256::one(), max];
+        return edge_values[rng.gen_range(0, edge_values.len())];
+    }
+    let mut bytes = [0u8; 32];
+    rng.fill(&mut bytes[..num_bytes]);
+    U256::from_le_bytes(&bytes)
+}
+
+fn generate_address(rng: &mut StdRng) -> AccountAddress {
+    match rng.gen_range(0, 8) {
+        0 => AccountAddress::ZERO,
+        1 => AccountAddress::ONE,
+        _ => AccountAddress::new(rng.gen()),
+    }
+}
+
+//**************************************************************************************************
+// Shrinking
+//**************************************************************************************************
+
+fn shrink_value(layout: &MoveTypeLayout, value: &MoveValue) -> Vec<MoveValue> {
+    if let (Some(num_bytes), Some(int)) = (int_num_bytes(layout), int_value(value)) {
+        return shrink_int(int)
+            .into_iter()
+            .map(|int| make_int(num_bytes, int))
+            .collect();
+    }
+    match (layout, value) {
+        (MoveTypeLayout::Bool, MoveValue::Bool(true)) => vec![MoveValue::Bool(false)],
+        (MoveTypeLayout::Address, MoveValue::Address(addr)) if *addr != AccountAddress::ZERO => {
+            vec![MoveValue::Address(AccountAddress::ZERO)]
+        },
+        (MoveTypeLayout::Signer, MoveValue::Signer(addr)) if *addr != AccountAddress::ZERO => {
+            vec![MoveValue::Signer(AccountAddress::ZERO)]
+        },
+        (MoveTypeLayout::Vector(elem_layout), MoveValue::Vector(elems)) => {
+            shrink_vector(elem_layout, elems, true)
+                .into_iter()
+                .map(MoveValue::Vector)
+                .collect()
+        },
+        (
+            MoveTypeLayout::Struct(MoveStructLayout::WithTypes { type_, fields }),
+            MoveValue::Struct(MoveStruct::Runtime(values)),
+        ) => {
+            if is_string(type_) {
+                // Only shrink the length of strings, to keep them printable.
+                let MoveValue::Vector(bytes) = &values[0] else {
+                    return vec![];
+                };
+                return shrink_vector(&MoveTypeLayout::U8, bytes, false)
+                    .into_iter()
+                    .map(|bytes| {
+                        MoveValue::Struct(MoveStruct::Runtime(vec![MoveValue::Vector(bytes)]))
+                    })
+                    .collect();
+            }
+            let layouts = fields.iter().map(|field| &field.layout).collect::<Vec<_>>();
+            shrink_fields(&layouts, values)
+                .into_iter()
+                .map(|values| MoveValue::Struct(MoveStruct::Runtime(values)))
+                .collect()
+        },
+        (
+            MoveTypeLayout::Struct(MoveStructLayout::WithVariants(variants)),
+            MoveValue::Struct(MoveStruct::RuntimeVariant(tag, values)),
+        ) => {
+            let layouts = variants[*tag as usize]
+                .fields
+                .iter()
+                .map(|field| &field.layout)
+                .collect::<Vec<_>>();
+            shrink_fields(&layouts, values)
+                .into_iter()
+                .map(|values| MoveValue::Struct(MoveStruct::RuntimeVariant(*tag, values)))
+                .collect()
+        },
+        _ => vec![],
+    }
+}
+
+/// Returns the candidates `value - value`, `value - value / 2`, `value - value / 4`, ...,
+/// `value - 1`, i.e., the candidates approach `value` starting from zero.
+fn shrink_int(value: U256) -> Vec<U256> {
+    let mut candidates = vec![];
+    let mut delta = value;
+    while delta > U256::zero() {
+        candidates.push(value - delta);
+        delta = delta >> 1u8;
+    }
+    candidates
+}
+
+/// Returns shorter vectors (the empty vector, halves, and vectors with one element removed),
+/// followed by vectors with one element shrunk if `shrink_elems` is set.
+fn shrink_vector(
+    elem_layout: &MoveTypeLayout,
+    elems: &[MoveValue],
+    shrink_elems: bool,
+) -> Vec<Vec<MoveValue>> {
+    if elems.is_empty() {
+        return vec![];
+    }
+    let mut candidates = vec![vec![]];
+    if elems.len() > 2 {
+        let mid = elems.len() / 2;
+        candidates.push(elems[..mid].to_vec());
+        candidates.push(elems[mid..].to_vec());
+    }
+    if elems.len() > 1 {
+        for idx in 0..elems.len() {
+            let mut candidate = elems.to_vec();
+            candidate.remove(idx);
+            candidates.push(candidate);
+        }
+    }
+    if shrink_elems {
+        let layouts = vec![elem_layout; elems.len()];
+        candidates.extend(shrink_fields(&layouts, elems));
+    }
+    candidates
+}
+
+/// Returns the candidates where exactly one of the values is shrunk.
+fn shrink_fields(layouts: &[&MoveTypeLayout], values: &[MoveValue]) -> Vec<Vec<MoveValue>> {
+    let mut candidates = vec![];
+    for (idx, (layout, value)) in layouts.iter().zip(values).enumerate() {
+        for shrunk in shrink_value(layout, value) {
+            let mut candidate = values.to_vec();
+            candidate[idx] = shrunk;
+            candidates.push(candidate);
+        }
+    }
+    candidates
+}
+
+//**************************************************************************************************
+// Formatting
+//**************************************************************************************************
+
+/// Formats a generated value in Move syntax, using the layout for struct and field names.
+fn format_value(layout: &MoveTypeLayout, value: &MoveValue) -> String {
+    match (layout, value) {
+        (MoveTypeLayout::Vector(elem_layout), MoveValue::Vector(elems)) => format!(
+            "vector[{}]",
+            elems
+                .iter()
+                .map(|elem| format_value(elem_layout, elem))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        (
+            MoveTypeLayout::Struct(MoveStructLayout::WithTypes { type_, fields }),
+            MoveValue::Struct(MoveStruct::Runtime(values)),
+        ) => match &values[..] {
+            [MoveValue::Vector(bytes)] if is_string(type_) => {
+                let bytes = bytes
+                    .iter()
+                    .filter_map(|byte| match byte {
+                        MoveValue::U8(byte) => Some(*byte),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>();
+                format!("{:?}", String::from_utf8_lossy(&bytes))
+            },
+            [MoveValue::Vector(elems)] if is_option(type_) => {
+                match (&fields[0].layout, elems.first()) {
+                    (MoveTypeLayout::Vector(elem_layout), Some(elem)) => {
+                        format!("option::some({})", format_value(elem_layout, elem))
+                    },
+                    _ => "option::none()".to_string(),
+                }
+            },
+            _ => format_fields(
+                type_.name.as_str(),
+                fields
+                    .iter()
+                    .map(|field| (field.name.as_str(), &field.layout)),
+                values,
+            ),
+        },
+        (
+            MoveTypeLayout::Struct(MoveStructLayout::WithVariants(variants)),
+            MoveValue::Struct(MoveStruct::RuntimeVariant(tag, values)),
+        ) => {
+            let variant = &variants[*tag as usize];
+            format_fields(
+                variant.name.as_str(),
+                variant
+                    .fields
+                    .iter()
+                    .map(|field| (field.name.as_str(), &field.layout)),
+                values,
+            )
+        },
+        _ => format_fixed_value(value),
+    }
+}
+
+fn format_fields<'a>(
+    name: &str,
+    fields: impl Iterator<Item = (&'a str, &'a MoveTypeLayout)>,
+    values: &[MoveValue],
+) -> String {
+    if values.is_empty() {
+        return name.to_string();
+    }
+    format!(
+        "{} {{ {} }}",
+        name,
+        fields
+            .zip(values)
+            .map(|((field_name, layout), value)| format!(
+                "{}: {}",
+                field_name,
+                format_value(layout, value)
+            ))
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+/// Formats a value of a primitive type (or a vector of such values) in Move syntax.
+fn format_fixed_value(value: &MoveValue) -> String {
+    match value {
+        MoveValue::Bool(b) => b.to_string(),
+        MoveValue::U8(n) => n.to_string(),
+        MoveValue::U16(n) => n.to_string(),
+        MoveValue::U32(n) => n.to_string(),
+        MoveValue::U64(n) => n.to_string(),
+        MoveValue::U128(n) => n.to_string(),
+        MoveValue::U256(n) => n.to_string(),
+        MoveValue::Address(addr) | MoveValue::Signer(addr) => {
+            format!("@0x{}", addr.short_str_lossless())
+        },
+        MoveValue::Vector(elems) => format!(
+            "vector[{}]",
+            elems
+                .iter()
+                .map(format_fixed_value)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        MoveValue::Struct(_) | MoveValue::Closure(_) => format!("{:?}", value),
+    }
+}
+
+//**************************************************************************************************
+// Helpers
+//**************************************************************************************************
+
+/// Returns `true` if the struct is `std::string::String`.
+fn is_string(tag: &StructTag) -> bool {
+    tag.address == AccountAddress::ONE
+        && tag.module.as_str() == "string"
+        && tag.name.as_str() == "String"
+}
+
+/// Returns `true` if the struct is `std::option::Option`.
+fn is_option(tag: &StructTag) -> bool {
+    tag.address == AccountAddress::ONE
+        && tag.module.as_str() == "option"
+        && tag.name.as_str() == "Option"
+}
+
+/// Returns the number of bytes of an integer layout.
+fn int_num_bytes(layout: &MoveTypeLayout) -> Option<usize> {
+    match layout {
+        MoveTypeLayout::U8 => Some(1),
+        MoveTypeLayout::U16 => Some(2),
+        MoveTypeLayout::U32 => Some(4),
+        MoveTypeLayout::U64 => Some(8),
+        MoveTypeLayout::U128 => Some(16),
+        MoveTypeLayout::U256 => Some(32),
+        _ => None,
+    }
+}
+
+fn int_max(num_bytes: usize) -> U256 {
+    let mut bytes = [0u8; 32];
+    bytes[..num_bytes].fill(0xFF);
+    U256::from_le_bytes(&bytes)
+}
+
+fn int_value(value: &MoveValue) -> Option<U256> {
+    match value {
+        MoveValue::U8(n) => Some(U256::from(*n)),
+        MoveValue::U16(n) => Some(U256::from(*n)),
+        MoveValue::U32(n) => Some(U256::from(*n)),
+        MoveValue::U64(n) => Some(U256::from(*n)),
+        MoveValue::U128(n) => Some(U256::from(*n)),
+        MoveValue::U256(n) => Some(*n),
+        _ => None,
+    }
+}
+
+fn make_int(num_bytes: usize, value: U256) -> MoveValue {
+    match num_bytes {
+        1 => MoveValue::U8(value.unchecked_as_u8()),
+        2 => MoveValue::U16(value.unchecked_as_u16()),
+        4 => MoveValue::U32(value.unchecked_as_u32()),
+        8 => MoveValue::U64(value.unchecked_as_u64()),
+        16 => MoveValue::U128(value.unchecked_as_u128()),
+        _ => MoveValue::U256(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use move_core_types::{language_storage::TypeTag, value::MoveFieldLayout};
+
+    #[test]
+    fn shrink_int_approaches_value_from_zero() {
+        let candidates = shrink_int(U256::from(10u8));
+        assert_eq!(
+            candidates,
+            [0u8, 5, 8, 9]
+                .into_iter()
+                .map(U256::from)
+                .collect::<Vec<_>>()
+        );
+        assert!(shrink_int(U256::zero()).is_empty());
+    }
+
+    #[test]
+    fn generated_values_are_reproducible() {
+        let layout = MoveTypeLayout::Vector(Box::new(MoveTypeLayout::U64));
+        let arguments = vec![("v".to_string(), FuzzArgument::Generated(layout))];
+        let module_id = ModuleId::new(AccountAddress::ONE, "m".parse().unwrap());
+        let generate = |seed| {
+            let mut rng = test_rng(seed, &module_id, "f");
+            (0..10)
+                .map(|_| generate_arguments(&mut rng, &arguments))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(generate(42), generate(42));
+        assert_ne!(generate(42), generate(43));
+    }
+
+    #[test]
+    fn generated_options_have_at_most_one_element() {
+        let option_tag = StructTag {
+            address: AccountAddress::ONE,
+            module: "option".parse().unwrap(),
+            name: "Option".parse().unwrap(),
+            type_args: vec![TypeTag::U64],
+        };
+        let layout = MoveTypeLayout::Struct(MoveStructLayout::WithTypes {
+            type_: option_tag,
+            fields: vec![MoveFieldLayout::new(
+                "vec".parse().unwrap(),
+                MoveTypeLayout::Vector(Box::new(MoveTypeLayout::U64)),
+            )],
+        });
+        let mut rng = StdRng::seed_from_u64(0);
+        let lengths = (0..100)
+            .map(|_| match generate_value(&mut rng, &layout) {
+                MoveValue::Struct(MoveStruct::Runtime(values)) => match &values[..] {
+                    [MoveValue::Vector(elems)] => elems.len(),
+                    _ => panic!("unexpected option value {:?}", values),
+                },
+                value => panic!("unexpected option value {:?}", value),
+            })
+            .collect::<Vec<_>>();
+        assert!(lengths.iter().all(|len| *len <= 1));
+        assert!(lengths.contains(&0) && lengths.contains(&1));
+
+        // Shrinking a some value tries none first
+        let some = MoveValue::Struct(MoveStruct::Runtime(vec![MoveValue::Vector(vec![
+            MoveValue::U64(7),
+        ])]));
+        let none = MoveValue::Struct(MoveStruct::Runtime(vec![MoveValue::Vector(vec![])]));
+        assert_eq!(shrink_value(&layout, &some)[0], none);
+        assert_eq!(format_value(&layout, &some), "option::some(7)");
+        assert_eq!(format_value(&layout, &none), "option::none()");
+    }
+
+    #[test]
+    fn format_generated_arguments() {
+        let arguments = vec![
+            (
+                "addr".to_string(),
+                FuzzArgument::Fixed(MoveValue::Address(AccountAddress::ONE)),
+            ),
+            (
+                "v".to_string(),
+                FuzzArgument::Generated(MoveTypeLayout::Vector(Box::new(MoveTypeLayout::U8))),
+            ),
+        ];
+        let values = vec![
+            MoveValue::Address(AccountAddress::ONE),
+            MoveValue::Vector(vec![MoveValue::U8(1), MoveValue::U8(255)]),
+        ];
+        assert_eq!(
+            format_arguments(&arguments, &values),
+            "addr = @0x1, v = vector[1, 255]"
+        );
+    }
+}
@@ -3,10 +3,14 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod extensions;
+pub mod fuzzer;
 pub mod test_reporter;
 pub mod test_runner;
 
-use crate::test_runner::TestRunner;
+use crate::{
+    fuzzer::{FuzzConfig, DEFAULT_FUZZ_RUNS},
+    test_runner::TestRunner,
+};
 use clap::*;
 use legacy_move_compiler::{
     self,
@@ -23,6 +27,7 @@ use std::{
     collections::BTreeMap,
     io::{Result, Write},
     marker::Send,
+    path::PathBuf,
     sync::Mutex,
 };
 use test_reporter::UnitTestFactory;
@@ -30,6 +35,9 @@ use test_reporter::UnitTestFactory;
 /// The default value bounding the amount of gas consumed in a test.
 const DEFAULT_EXECUTION_BOUND: u64 = 1_000_000;
 
+/// The default percentage by which the gas used by a test may exceed its baseline.
+pub const DEFAULT_GAS_REGRESSION_THRESHOLD: u64 = 5;
+
 #[derive(Debug, Parser, Clone)]
 #[clap(author, version, about)]
 pub struct UnitTestingConfig {
@@ -102,6 +110,41 @@ pub struct UnitTestingConfig {
     /// Verbose mode
     #[clap(short = 'v', long = "verbose")]
     pub verbose: bool,
+
+    /// Number of runs (with different generated arguments) of each #[fuzz] test
+    #[clap(name = "fuzz_runs", long = "fuzz_runs", default_value_t = DEFAULT_FUZZ_RUNS)]
+    pub fuzz_runs: u64,
+
+    /// Seed for generating the arguments of #[fuzz] tests. A random seed is used if not set.
+    /// The seed is reported on failure, to reproduce the failing run.
+    #[clap(name = "fuzz_seed", long = "fuzz_seed")]
+    pub fuzz_seed: Option<u64>,
+
+    /// Report the gas used by each passing test
+    #[clap(name = "gas_report", long = "gas_report")]
+    pub gas_report: bool,
+
+    /// Gas baseline file to compare the gas used by each passing test against. Testing fails if
+    /// a test uses more gas than its baseline, plus the regression threshold.
+    #[clap(name = "gas_baseline", long = "gas_baseline")]
+    pub gas_baseline: Option<PathBuf>,
+
+    /// Write the gas used by each passing test to the gas baseline file, instead of comparing.
+    /// Existing entries of tests which did not pass in this run (e.g., filtered out) are kept.
+    #[clap(
+        name = "update_gas_baseline",
+        long = "update_gas_baseline",
+        requires = "gas_baseline"
+    )]
+    pub update_gas_baseline: bool,
+
+    /// The percentage by which the gas used by a test may exceed its baseline
+    #[clap(
+        name = "gas_regression_threshold",
+        long = "gas_regression_threshold",
+        default_value_t = DEFAULT_GAS_REGRESSION_THRESHOLD
+    )]
+    pub gas_regression_threshold: u64,
 }
 
 fn format_module_id(module_id: &ModuleId) -> String {
@@ -127,6 +170,12 @@ impl Default for UnitTestingConfig {
             verbose: false,
             list: false,
             named_address_values: vec![],
+            fuzz_runs: DEFAULT_FUZZ_RUNS,
+            fuzz_seed: None,
+            gas_report: false,
+            gas_baseline: None,
+            update_gas_baseline: false,
+            gas_regression_threshold: DEFAULT_GAS_REGRESSION_THRESHOLD,
         }
     }
 }
@@ -198,12 +247,17 @@ impl UnitTestingConfig {
 
         if self.list {
             for (module_id, test_plan) in &test_plan.module_tests {
-                for test_name in test_plan.tests.keys() {
+                for (test_name, test_info) in &test_plan.tests {
                     writeln!(
                         shared_writer.lock().unwrap(),
-                        "{}::{}: test",
+                        "{}::{}: {}",
                         format_module_id(module_id),
-                        test_name
+                        test_name,
+                        if test_info.fuzz_arguments.is_some() {
+                            "fuzz"
+                        } else {
+                            "test"
+                        }
                     )?;
                 }
             }
@@ -219,6 +273,10 @@ impl UnitTestingConfig {
             native_function_table,
             genesis_state,
             self.verbose,
+            FuzzConfig {
+                num_runs: self.fuzz_runs,
+                seed: self.fuzz_seed.unwrap_or_else(rand::random),
+            },
         )
         .unwrap();
 
@@ -235,7 +293,24 @@ impl UnitTestingConfig {
             test_results.report_goldens(&shared_writer)?;
         }
 
-        let ok = test_results.summarize(&shared_writer)?;
+        if self.gas_report {
+            test_results.report_gas_usage(&shared_writer)?;
+        }
+
+        let mut gas_ok = true;
+        if let Some(gas_baseline) = &self.gas_baseline {
+            if self.update_gas_baseline {
+                test_results.write_gas_baseline(gas_baseline)?;
+            } else {
+                gas_ok = test_results.check_gas_baseline(
+                    gas_baseline,
+                    self.gas_regression_threshold,
+                    &shared_writer,
+                )?;
+            }
+        }
+
+        let ok = test_results.summarize(&shared_writer)? && gas_ok;
 
         let writer = shared_writer.into_inner().unwrap();
         Ok((writer, ok))
@@ -22,9 +22,12 @@ use move_vm_runtime::native_extensions::NativeContextExtensions;
 use move_vm_test_utils::gas_schedule::{zero_cost_schedule, CostTable, GasCost, GasStatus};
 use move_vm_types::gas::GasMeter;
 use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
 use std::{
     collections::{BTreeMap, BTreeSet, HashMap},
+    fs,
     io::{Result, Write},
+    path::Path,
     sync::Mutex,
     time::Duration,
 };
@@ -107,6 +110,8 @@ pub enum FailureReason {
     },
     // Property checking failed
     Property(String),
+    // A fuzz test failed for the given (shrunk) input, generated from the given seed
+    Fuzz(String, u64, Box<FailureReason>),
 }
 
 #[derive(Debug, Clone, Ord, PartialOrd, PartialEq, Eq)]
@@ -119,9 +124,41 @@ pub struct TestFailure {
 
 #[derive(Debug, Clone, Ord, PartialOrd, PartialEq, Eq)]
 pub struct TestRunInfo {
+    pub module_id: ModuleId,
     pub function_ident: String,
     pub elapsed_time: Duration,
     pub gas_used: u64,
+    // Filled in by factories which meter tests with a real gas schedule
+    pub gas_breakdown: Option<GasBreakdown>,
+}
+
+/// The gas used by a test, broken down by the kind of gas.
+#[derive(Debug, Clone, Copy, Default, Ord, PartialOrd, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasBreakdown {
+    pub execution: u64,
+    pub io: u64,
+    pub storage: u64,
+}
+
+/// The gas used by a test, as recorded in a gas baseline file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasBaselineEntry {
+    pub gas_used: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub breakdown: Option<GasBreakdown>,
+}
+
+/// The gas used by each test, keyed by the fully qualified test name.
+pub type GasBaseline = BTreeMap<String, GasBaselineEntry>;
+
+fn read_gas_baseline(path: &Path) -> Result<GasBaseline> {
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+/// Returns `true` if `gas_used` exceeds `expected` by more than `threshold_percent` percent.
+fn exceeds_gas_threshold(gas_used: u64, expected: u64, threshold_percent: u64) -> bool {
+    // Compare in u128 to avoid overflows.
+    gas_used as u128 * 100 > expected as u128 * (100 + threshold_percent as u128)
 }
 
 #[derive(Debug, Clone)]
@@ -138,15 +175,23 @@ pub struct TestResults {
 }
 
 impl TestRunInfo {
-    pub fn new(function_ident: String, elapsed_time: Duration) -> Self {
+    pub fn new(module_id: ModuleId, function_ident: String, elapsed_time: Duration) -> Self {
         Self {
+            module_id,
             function_ident,
             elapsed_time,
             gas_used: 0,
+            gas_breakdown: None,
         }
     }
 }
 
+impl GasBreakdown {
+    pub fn total(&self) -> u64 {
+        self.execution + self.io + self.storage
+    }
+}
+
 impl FailureReason {
     pub fn no_error() -> Self {
         FailureReason::NoError("Test did not error as expected".to_string())
@@ -193,6 +238,10 @@ impl FailureReason {
     pub fn property(details: String) -> Self {
         FailureReason::Property(details)
     }
+
+    pub fn fuzz(input: String, seed: u64, failure_reason: FailureReason) -> Self {
+        FailureReason::Fuzz(input, seed, Box::new(failure_reason))
+    }
 }
 
 impl TestFailure {
@@ -276,6 +325,19 @@ impl TestFailure {
                 )
             },
             FailureReason::Property(message) => message.clone(),
+            FailureReason::Fuzz(input, seed, failure_reason) => {
+                let failure = TestFailure {
+                    failure_reason: *failure_reason.clone(),
+                    storage_state: None,
+                    ..self.clone()
+                };
+                format!(
+                    "Fuzz test failed with seed {} for the input: {}\n{}",
+                    seed,
+                    input,
+                    failure.render_error(test_plan)
+                )
+            },
         };
 
         match &self.storage_state {
@@ -606,6 +668,129 @@ impl TestResults {
         writeln!(writer.lock().unwrap())
     }
 
+    /// Returns the gas used by each passing test, keyed by the fully qualified test name.
+    pub fn gas_baseline(&self) -> GasBaseline {
+        self.final_statistics
+            .passed
+            .iter()
+            .flat_map(|(module_id, test_results)| {
+                test_results.iter().map(move |test_result| {
+                    (
+                        format!(
+                            "{}::{}",
+                            format_module_id(module_id),
+                            test_result.function_ident
+                        ),
+                        GasBaselineEntry {
+                            gas_used: test_result.gas_used,
+                            breakdown: test_result.gas_breakdown,
+                        },
+                    )
+                })
+            })
+            .collect()
+    }
+
+    pub fn report_gas_usage<W: Write>(&self, writer: &Mutex<W>) -> Result<()> {
+        writeln!(writer.lock().unwrap(), "\nGas Usage:\n")?;
+        let gas_usage = self.gas_baseline();
+        let max_name_size = gas_usage
+            .keys()
+            .map(|name| name.len())
+            .max()
+            .unwrap_or_default()
+            .max("Test Name".len());
+        let format_gas = |gas: Option<u64>| gas.map_or_else(|| "-".to_string(), |g| g.to_string());
+
+        let mut writer = writer.lock().unwrap();
+        writeln!(
+            writer,
+            "{:<width$}  {:>12}  {:>12}  {:>12}  {:>12}",
+            "Test Name",
+            "Execution",
+            "IO",
+            "Storage",
+            "Total",
+            width = max_name_size
+        )?;
+        for (name, entry) in gas_usage {
+            writeln!(
+                writer,
+                "{:<width$}  {:>12}  {:>12}  {:>12}  {:>12}",
+                name,
+                format_gas(entry.breakdown.map(|b| b.execution)),
+                format_gas(entry.breakdown.map(|b| b.io)),
+                format_gas(entry.breakdown.map(|b| b.storage)),
+                entry.gas_used,
+                width = max_name_size
+            )?;
+        }
+        writeln!(writer)
+    }
+
+    /// Writes the gas used by each passing test to the baseline file. If the file exists, the
+    /// entries of all other tests (e.g., tests excluded by a filter, or failing tests) are kept.
+    pub fn write_gas_baseline(&self, path: &Path) -> Result<()> {
+        let mut baseline = if path.exists() {
+            read_gas_baseline(path)?
+        } else {
+            GasBaseline::new()
+        };
+        baseline.extend(self.gas_baseline());
+        let baseline = serde_json::to_string_pretty(&baseline)?;
+        fs::write(path, baseline + "\n")
+    }
+
+    /// Compares the gas used by each passing test against the baseline file. Returns `false`
+    /// if any test uses more than `threshold_percent` percent more gas than in the baseline.
+    /// Tests which are not in the baseline are not checked.
+    pub fn check_gas_baseline<W: Write>(
+        &self,
+        path: &Path,
+        threshold_percent: u64,
+        writer: &Mutex<W>,
+    ) -> Result<bool> {
+        let baseline = read_gas_baseline(path)?;
+        let regressions = self
+            .gas_baseline()
+            .into_iter()
+            .filter_map(|(name, entry)| {
+                let expected = baseline.get(&name)?.gas_used;
+                exceeds_gas_threshold(entry.gas_used, expected, threshold_percent).then_some((
+                    name,
+                    expected,
+                    entry.gas_used,
+                ))
+            })
+            .collect::<Vec<_>>();
+        if regressions.is_empty() {
+            return Ok(true);
+        }
+
+        let mut writer = writer.lock().unwrap();
+        writeln!(
+            writer,
+            "\nGas regressions (compared to {}, threshold: {}%):\n",
+            path.display(),
+            threshold_percent
+        )?;
+        for (name, expected, actual) in regressions {
+            writeln!(
+                writer,
+                "{}: {} -> {} ({:+.1}%)",
+                name,
+                expected,
+                actual,
+                (actual as f64 - expected as f64) * 100.0 / (expected as f64).max(1.0)
+            )?;
+        }
+        writeln!(
+            writer,
+            "\nIf the increase is expected, update the baseline file.\n"
+        )?;
+        Ok(false)
+    }
+
     /// Returns `true` if all tests passed, `false` if there was a test failure/timeout
     pub fn summarize<W: Write>(self, writer: &Mutex<W>) -> Result<bool> {
         let num_failed_tests = self
@@ -659,3 +844,89 @@ impl TestResults {
         Ok(num_failed_tests == 0)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use move_core_types::account_address::AccountAddress;
+
+    fn module_id() -> ModuleId {
+        ModuleId::new(AccountAddress::ONE, "m".parse().unwrap())
+    }
+
+    fn test_name(function_name: &str) -> String {
+        format!("{}::{}", format_module_id(&module_id()), function_name)
+    }
+
+    /// Returns the results of passing tests with the given gas usage.
+    fn test_results(gas_used: &[(&str, u64)]) -> TestResults {
+        let module_test_plan = ModuleTestPlan {
+            module_id: module_id(),
+            tests: BTreeMap::new(),
+        };
+        let mut statistics = TestStatistics::new();
+        for (function_name, gas_used) in gas_used {
+            let mut test_run_info =
+                TestRunInfo::new(module_id(), function_name.to_string(), Duration::ZERO);
+            test_run_info.gas_used = *gas_used;
+            statistics.test_success(test_run_info, &module_test_plan);
+        }
+        TestResults::new(
+            statistics,
+            TestPlan::new(vec![], HashMap::new(), vec![], vec![]),
+        )
+    }
+
+    #[test]
+    fn gas_threshold() {
+        assert!(!exceeds_gas_threshold(110, 100, 10));
+        assert!(exceeds_gas_threshold(111, 100, 10));
+        assert!(!exceeds_gas_threshold(90, 100, 0));
+        assert!(exceeds_gas_threshold(101, 100, 0));
+        assert!(!exceeds_gas_threshold(0, 0, 0));
+        assert!(exceeds_gas_threshold(1, 0, 100));
+        assert!(!exceeds_gas_threshold(u64::MAX, u64::MAX, u64::MAX));
+    }
+
+    #[test]
+    fn write_gas_baseline_merges_existing_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("baseline.json");
+        test_results(&[("a", 100), ("b", 200)])
+            .write_gas_baseline(&path)
+            .unwrap();
+
+        // Only `a` runs (e.g., due to a filter), so the entry of `b` is kept
+        test_results(&[("a", 150)])
+            .write_gas_baseline(&path)
+            .unwrap();
+        let baseline = read_gas_baseline(&path).unwrap();
+        assert_eq!(baseline.len(), 2);
+        assert_eq!(baseline[&test_name("a")].gas_used, 150);
+        assert_eq!(baseline[&test_name("b")].gas_used, 200);
+    }
+
+    #[test]
+    fn check_gas_baseline_reports_regressions() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("baseline.json");
+        test_results(&[("a", 100)])
+            .write_gas_baseline(&path)
+            .unwrap();
+
+        // Increases within the threshold, and tests without a baseline, pass
+        let writer = Mutex::new(vec![]);
+        assert!(test_results(&[("a", 110), ("c", 1000)])
+            .check_gas_baseline(&path, 10, &writer)
+            .unwrap());
+        assert!(writer.into_inner().unwrap().is_empty());
+
+        // Increases above the threshold are reported
+        let writer = Mutex::new(vec![]);
+        assert!(!test_results(&[("a", 111)])
+            .check_gas_baseline(&path, 10, &writer)
+            .unwrap());
+        let output = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        assert!(output.contains(&format!("{}: 100 -> 111 (+11.0%)", test_name("a"))));
+    }
+}
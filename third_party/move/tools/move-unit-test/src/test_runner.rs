@@ -4,6 +4,7 @@
 
 use crate::{
     extensions, format_module_id,
+    fuzzer::{self, FuzzConfig, MAX_SHRINK_RUNS},
     test_reporter::{
         FailureReason, MoveError, TestFailure, TestResults, TestRunInfo, TestStatistics,
         UnitTestFactory,
@@ -12,10 +13,10 @@ use crate::{
 use anyhow::Result;
 use colored::*;
 use legacy_move_compiler::unit_test::{
-    ExpectedFailure, ModuleTestPlan, NamedOrBytecodeModule, TestCase, TestPlan,
+    ExpectedFailure, FuzzArgument, ModuleTestPlan, NamedOrBytecodeModule, TestCase, TestPlan,
 };
 use move_binary_format::{
-    errors::{Location, VMResult},
+    errors::{Location, VMError, VMResult},
     file_format::CompiledModule,
 };
 use move_bytecode_utils::Modules;
@@ -23,7 +24,7 @@ use move_core_types::{
     account_address::AccountAddress,
     effects::{ChangeSet, Op},
     identifier::IdentStr,
-    value::serialize_values,
+    value::{serialize_values, MoveValue},
     vm_status::StatusCode,
 };
 use move_resource_viewer::MoveValueAnnotator;
@@ -47,8 +48,18 @@ pub struct SharedTestingConfig {
     #[allow(dead_code)] // used by some features
     source_files: Vec<String>,
     record_writeset: bool,
+    fuzz_config: FuzzConfig,
 }
 
+/// The results of executing a test: the change set, the native extensions, the return values
+/// and the run info.
+type TestExecutionResult = (
+    VMResult<ChangeSet>,
+    VMResult<NativeContextExtensions>,
+    VMResult<Vec<Vec<u8>>>,
+    TestRunInfo,
+);
+
 pub struct TestRunner {
     num_threads: usize,
     testing_config: SharedTestingConfig,
@@ -116,6 +127,7 @@ impl TestRunner {
         native_function_table: Option<NativeFunctionTable>,
         genesis_state: Option<ChangeSet>,
         record_writeset: bool,
+        fuzz_config: FuzzConfig,
     ) -> Result<Self> {
         let native_function_table = native_function_table.unwrap_or_else(|| {
             move_stdlib::natives::all_natives(
@@ -146,6 +158,7 @@ impl TestRunner {
                 starting_storage_state,
                 source_files,
                 record_writeset,
+                fuzz_config,
             },
             num_threads,
             tests,
@@ -242,14 +255,9 @@ impl SharedTestingConfig {
         &self,
         test_plan: &ModuleTestPlan,
         function_name: &str,
-        test_info: &TestCase,
+        arguments: &[MoveValue],
         factory: &Mutex<F>,
-    ) -> (
-        VMResult<ChangeSet>,
-        VMResult<NativeContextExtensions>,
-        VMResult<Vec<Vec<u8>>>,
-        TestRunInfo,
-    ) {
+    ) -> TestExecutionResult {
         let module_storage = self.starting_storage_state.as_unsync_module_storage();
 
         let mut extensions = extensions::new_extensions();
@@ -269,7 +277,7 @@ impl SharedTestingConfig {
                 &[],
             )
             .and_then(|function| {
-                let args = serialize_values(test_info.arguments.iter());
+                let args = serialize_values(arguments.iter());
                 MoveVM::execute_loaded_function(
                     function,
                     args,
@@ -294,7 +302,11 @@ impl SharedTestingConfig {
             }
         }
 
-        let test_run_info = TestRunInfo::new(function_name.to_string(), now.elapsed());
+        let test_run_info = TestRunInfo::new(
+            test_plan.module_id.clone(),
+            function_name.to_string(),
+            now.elapsed(),
+        );
 
         let result = data_cache
             .into_effects(&module_storage)
@@ -328,8 +340,25 @@ impl SharedTestingConfig {
         let mut stats = TestStatistics::new();
 
         for (function_name, test_info) in &test_plan.tests {
-            let (cs_result, ext_result, exec_result, test_run_info) =
-                self.execute_via_move_vm(test_plan, function_name, test_info, factory);
+            let ((cs_result, ext_result, exec_result, test_run_info), fuzz_input) =
+                match &test_info.fuzz_arguments {
+                    Some(fuzz_arguments) => self.fuzz_via_move_vm(
+                        test_plan,
+                        function_name,
+                        test_info,
+                        fuzz_arguments,
+                        factory,
+                    ),
+                    None => (
+                        self.execute_via_move_vm(
+                            test_plan,
+                            function_name,
+                            &test_info.arguments,
+                            factory,
+                        ),
+                        None,
+                    ),
+                };
 
             if self.record_writeset {
                 stats.test_output(
@@ -356,107 +385,28 @@ impl SharedTestingConfig {
                 }
             };
 
-            match exec_result {
-                Err(err) => {
-                    let actual_err = MoveError(
-                        err.major_status(),
-                        err.sub_status(),
-                        err.location().clone(),
-                        err.message().cloned(),
-                    );
-                    assert!(err.major_status() != StatusCode::EXECUTED);
-                    match test_info.expected_failure.as_ref() {
-                        Some(ExpectedFailure::Expected) => {
-                            output.pass(function_name);
-                            stats.test_success(test_run_info, test_plan);
-                        },
-                        Some(ExpectedFailure::ExpectedWithError(expected_err))
-                            if expected_err == &actual_err =>
-                        {
-                            output.pass(function_name);
-                            stats.test_success(test_run_info, test_plan);
-                        },
-                        Some(ExpectedFailure::ExpectedWithCodeDEPRECATED(code))
-                            if actual_err.0 == StatusCode::ABORTED
-                                && actual_err.1.is_some()
-                                && actual_err.1.unwrap() == *code =>
-                        {
-                            output.pass(function_name);
-                            stats.test_success(test_run_info, test_plan);
-                        },
-                        // incorrect cases
-                        Some(ExpectedFailure::ExpectedWithError(expected_err)) => {
-                            output.fail(function_name);
-                            stats.test_failure(
-                                TestFailure::new(
-                                    FailureReason::wrong_error(expected_err.clone(), actual_err),
-                                    test_run_info,
-                                    Some(err),
-                                    save_session_state(),
-                                ),
-                                test_plan,
-                            )
-                        },
-                        Some(ExpectedFailure::ExpectedWithCodeDEPRECATED(expected_code)) => {
-                            output.fail(function_name);
-                            stats.test_failure(
-                                TestFailure::new(
-                                    FailureReason::wrong_abort_deprecated(
-                                        *expected_code,
-                                        actual_err,
-                                    ),
-                                    test_run_info,
-                                    Some(err),
-                                    save_session_state(),
-                                ),
-                                test_plan,
-                            )
-                        },
-                        None if err.major_status() == StatusCode::OUT_OF_GAS => {
-                            // Ran out of ticks, report a test timeout and log a test failure
-                            output.timeout(function_name);
-                            stats.test_failure(
-                                TestFailure::new(
-                                    FailureReason::timeout(),
-                                    test_run_info,
-                                    Some(err),
-                                    save_session_state(),
-                                ),
-                                test_plan,
-                            )
-                        },
-                        None => {
-                            output.fail(function_name);
-                            stats.test_failure(
-                                TestFailure::new(
-                                    FailureReason::unexpected_error(actual_err),
-                                    test_run_info,
-                                    Some(err),
-                                    save_session_state(),
-                                ),
-                                test_plan,
-                            )
-                        },
-                    }
+            match check_test_result(test_info, &exec_result) {
+                None => {
+                    output.pass(function_name);
+                    stats.test_success(test_run_info, test_plan);
                 },
-                Ok(_) => {
-                    // Expected the test to fail, but it executed
-                    if test_info.expected_failure.is_some() {
-                        output.fail(function_name);
-                        stats.test_failure(
-                            TestFailure::new(
-                                FailureReason::no_error(),
-                                test_run_info,
-                                None,
-                                save_session_state(),
-                            ),
-                            test_plan,
-                        )
+                Some((failure_reason, err)) => {
+                    if matches!(failure_reason, FailureReason::Timeout(_)) {
+                        // Ran out of ticks, report a test timeout and log a test failure
+                        output.timeout(function_name);
                     } else {
-                        // Expected the test to execute fully and it did
-                        output.pass(function_name);
-                        stats.test_success(test_run_info, test_plan);
+                        output.fail(function_name);
                     }
+                    let failure_reason = match fuzz_input {
+                        Some(input) => {
+                            FailureReason::fuzz(input, self.fuzz_config.seed, failure_reason)
+                        },
+                        None => failure_reason,
+                    };
+                    stats.test_failure(
+                        TestFailure::new(failure_reason, test_run_info, err, save_session_state()),
+                        test_plan,
+                    )
                 },
             }
         }
@@ -464,6 +414,54 @@ impl SharedTestingConfig {
         stats
     }
 
+    /// Runs a fuzz test with generated arguments until it fails, or the configured number of
+    /// runs is reached. A failing input is shrunk, as long as the test keeps failing with the
+    /// same reason. Returns the result of the last run, together with the failing input if any.
+    fn fuzz_via_move_vm<F: UnitTestFactory>(
+        &self,
+        test_plan: &ModuleTestPlan,
+        function_name: &str,
+        test_info: &TestCase,
+        fuzz_arguments: &[(String, FuzzArgument)],
+        factory: &Mutex<F>,
+    ) -> (TestExecutionResult, Option<String>) {
+        let mut rng = fuzzer::test_rng(self.fuzz_config.seed, &test_plan.module_id, function_name);
+        let mut last_run = None;
+        for _ in 0..self.fuzz_config.num_runs.max(1) {
+            let mut arguments = fuzzer::generate_arguments(&mut rng, fuzz_arguments);
+            let mut run = self.execute_via_move_vm(test_plan, function_name, &arguments, factory);
+            let Some((failure_reason, _)) = check_test_result(test_info, &run.2) else {
+                last_run = Some(run);
+                continue;
+            };
+
+            // Greedily replace the input by the first simpler input which fails the same way,
+            // until there is none.
+            let mut num_shrink_runs = 0;
+            'shrink: loop {
+                for candidate in fuzzer::shrink_arguments(fuzz_arguments, &arguments) {
+                    if num_shrink_runs == MAX_SHRINK_RUNS {
+                        break 'shrink;
+                    }
+                    num_shrink_runs += 1;
+                    let candidate_run =
+                        self.execute_via_move_vm(test_plan, function_name, &candidate, factory);
+                    if check_test_result(test_info, &candidate_run.2)
+                        .is_some_and(|(reason, _)| reason == failure_reason)
+                    {
+                        arguments = candidate;
+                        run = candidate_run;
+                        continue 'shrink;
+                    }
+                }
+                break;
+            }
+            let input = fuzzer::format_arguments(fuzz_arguments, &arguments);
+            return (run, Some(input));
+        }
+        (last_run.expect("at least one fuzz run"), None)
+    }
+
     fn exec_module_tests<F: UnitTestFactory>(
         &self,
         test_plan: &ModuleTestPlan,
@@ -474,3 +472,62 @@ impl SharedTestingConfig {
         self.exec_module_tests_move_vm_and_stackless_vm(test_plan, &output, factory)
     }
 }
+
+/// Checks the result of a test run against the expected failure of the test. Returns `None` if
+/// the test passed, or the reason it failed and the error it failed with.
+fn check_test_result(
+    test_info: &TestCase,
+    exec_result: &VMResult<Vec<Vec<u8>>>,
+) -> Option<(FailureReason, Option<VMError>)> {
+    match exec_result {
+        Err(err) => {
+            let actual_err = MoveError(
+                err.major_status(),
+                err.sub_status(),
+                err.location().clone(),
+                err.message().cloned(),
+            );
+            assert!(err.major_status() != StatusCode::EXECUTED);
+            match test_info.expected_failure.as_ref() {
+                Some(ExpectedFailure::Expected) => None,
+                Some(ExpectedFailure::ExpectedWithError(expected_err))
+                    if expected_err == &actual_err =>
+                {
+                    None
+                },
+                Some(ExpectedFailure::ExpectedWithCodeDEPRECATED(code))
+                    if actual_err.0 == StatusCode::ABORTED
+                        && actual_err.1.is_some()
+                        && actual_err.1.unwrap() == *code =>
+                {
+                    None
+                },
+                // incorrect cases
+                Some(ExpectedFailure::ExpectedWithError(expected_err)) => Some((
+                    FailureReason::wrong_error(expected_err.clone(), actual_err),
+                    Some(err.clone()),
+                )),
+                Some(ExpectedFailure::ExpectedWithCodeDEPRECATED(expected_code)) => Some((
+                    FailureReason::wrong_abort_deprecated(*expected_code, actual_err),
+                    Some(err.clone()),
+                )),
+                None if err.major_status() == StatusCode::OUT_OF_GAS => {
+                    Some((FailureReason::timeout(), Some(err.clone())))
+                },
+                None => Some((
+                    FailureReason::unexpected_error(actual_err),
+                    Some(err.clone()),
+                )),
+            }
+        },
+        Ok(_) => {
+            // Expected the test to fail, but it executed
+            if test_info.expected_failure.is_some() {
+                Some((FailureReason::no_error(), None))
+            } else {
+                // Expected the test to execute fully and it did
+                None
+            }
+        },
+    }
+}
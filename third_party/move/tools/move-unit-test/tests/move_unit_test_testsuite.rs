@@ -96,6 +96,8 @@ fn run_test_impl(path: &Path) -> anyhow::Result<()> {
             .collect(),
         verbose: true,
         report_stacktrace_on_abort: true,
+        // Use a fixed seed so that the inputs of failing fuzz tests are deterministic
+        fuzz_seed: Some(0),
 
         ..UnitTestingConfig::default()
     };
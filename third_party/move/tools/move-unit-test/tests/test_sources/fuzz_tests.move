@@ -0,0 +1,27 @@
+module 0x1::fuzz_tests {
+    #[fuzz]
+    fun below_limit(x: u64) {
+        assert!(x < 100, 1);
+    }
+
+    #[fuzz]
+    fun add_commutes(a: u32, b: u32) {
+        assert!((a as u64) + (b as u64) == (b as u64) + (a as u64), 0);
+    }
+
+    #[fuzz(addr = @0x42)]
+    fun fixed_address(addr: address, v: vector<u8>) {
+        assert!(addr == @0x42, 0);
+        assert!(std::vector::length(&v) <= 16, 1);
+    }
+
+    struct Point has drop {
+        x: u8,
+        y: u8,
+    }
+
+    #[fuzz]
+    fun point_sum(p: Point) {
+        assert!((p.x as u64) + (p.y as u64) <= 510, 0);
+    }
+}
@@ -125,6 +125,8 @@ pub enum Protocol {
     // probably need to move network wire into its own crate to avoid circular
     // dependency b/w network and types.
     Handshake(u8),
+    // QUIC over UDP. The port is a UDP port.
+    Quic(u16),
 }
 
 /// A minimally parsed DNS name. We don't really do any checking other than
@@ -195,11 +197,11 @@ pub enum ParseError {
     NetworkLayerMissing,
 
     #[error(
-        "NetworkAddress must start with one of Protocol::Ip4/Ip6/Dns/Dns4/Dns6 followed by TCP"
+        "NetworkAddress must start with one of Protocol::Ip4/Ip6/Dns/Dns4/Dns6 followed by TCP or QUIC"
     )]
     TransportLayerMissing,
 
-    #[error("NetworkAddress must have a NoiseIK protocol following the TCP protocol")]
+    #[error("NetworkAddress must have a NoiseIK protocol following the TCP or QUIC protocol")]
     SessionLayerMissing,
 
     #[error("NetworkAddress must have a Handshake protocol following the NoiseIK protocol")]
@@ -239,7 +241,7 @@ fn is_network_layer(p: Option<&Protocol>) -> bool {
 fn is_transport_layer(p: Option<&Protocol>) -> bool {
     use Protocol::*;
 
-    matches!(p, Some(Tcp(_)))
+    matches!(p, Some(Tcp(_)) | Some(Quic(_)))
 }
 
 fn is_session_layer(p: Option<&Protocol>, allow_empty: bool) -> bool {
@@ -358,6 +360,7 @@ impl NetworkAddress {
     /// `"/dns4/<domain>/tcp/<port>"` or
     /// `"/dns6/<domain>/tcp/<port>"` or
     /// `"/dns/<domain>/tcp/<port>"` or
+    /// the QUIC equivalents of the above (e.g., `"/ip4/<addr>/quic/<port>"`) or
     /// cfg!(test) `"/memory/<port>"`
     ///
     /// followed by transport upgrade handshake protocols:
@@ -390,7 +393,7 @@ impl NetworkAddress {
     /// Retrieves the port from the network address
     pub fn find_port(&self) -> Option<u16> {
         self.0.iter().find_map(|proto| match proto {
-            Protocol::Tcp(port) => Some(*port),
+            Protocol::Tcp(port) | Protocol::Quic(port) => Some(*port),
             _ => None,
         })
     }
@@ -580,6 +583,10 @@ pub fn arb_aptosnet_addr() -> impl Strategy<Value = NetworkAddress> {
             .prop_map(|(name, port)| vec![Protocol::Dns4(name), Protocol::Tcp(port)]),
         any::<(DnsName, u16)>()
             .prop_map(|(name, port)| vec![Protocol::Dns6(name), Protocol::Tcp(port)]),
+        any::<(Ipv4Addr, u16)>()
+            .prop_map(|(addr, port)| vec![Protocol::Ip4(addr), Protocol::Quic(port)]),
+        any::<(DnsName, u16)>()
+            .prop_map(|(name, port)| vec![Protocol::Dns(name), Protocol::Quic(port)]),
     ];
     let arb_aptosnet_protos = any::<(x25519::PublicKey, u8)>()
         .prop_map(|(pubkey, hs)| vec![Protocol::NoiseIK(pubkey), Protocol::Handshake(hs)]);
@@ -615,6 +622,7 @@ impl fmt::Display for Protocol {
                     .expect("ValidCryptoMaterialStringExt::to_encoded_string is infallible")
             ),
             Handshake(version) => write!(f, "/handshake/{}", version),
+            Quic(port) => write!(f, "/quic/{}", port),
         }
     }
 }
@@ -645,6 +653,7 @@ impl Protocol {
                 args.next().ok_or(ParseError::UnexpectedEnd)?,
             )?),
             "handshake" => Protocol::Handshake(parse_one(args)?),
+            "quic" => Protocol::Quic(parse_one(args)?),
             unknown => return Err(ParseError::UnknownProtocolType(unknown.to_string())),
         };
         Ok(protocol)
@@ -821,6 +830,42 @@ pub fn parse_dns_tcp(protos: &[Protocol]) -> Option<((IpFilter, &DnsName, u16),
     }
 }
 
+/// parse the `&[Protocol]` into the `"/ip4/<addr>/quic/<port>"` or
+/// `"/ip6/<addr>/quic/<port>"` prefix and unparsed `&[Protocol]` suffix.
+pub fn parse_ip_quic(protos: &[Protocol]) -> Option<((IpAddr, u16), &[Protocol])> {
+    use Protocol::*;
+
+    if protos.len() < 2 {
+        return None;
+    }
+
+    let (prefix, suffix) = protos.split_at(2);
+    match prefix {
+        [Ip4(ip), Quic(port)] => Some(((IpAddr::V4(*ip), *port), suffix)),
+        [Ip6(ip), Quic(port)] => Some(((IpAddr::V6(*ip), *port), suffix)),
+        _ => None,
+    }
+}
+
+/// parse the `&[Protocol]` into the `"/dns/<domain>/quic/<port>"`,
+/// `"/dns4/<domain>/quic/<port>"`, or `"/dns6/<domain>/quic/<port>"` prefix and
+/// unparsed `&[Protocol]` suffix.
+pub fn parse_dns_quic(protos: &[Protocol]) -> Option<((IpFilter, &DnsName, u16), &[Protocol])> {
+    use Protocol::*;
+
+    if protos.len() < 2 {
+        return None;
+    }
+
+    let (prefix, suffix) = protos.split_at(2);
+    match prefix {
+        [Dns(name), Quic(port)] => Some(((IpFilter::Any, name, *port), suffix)),
+        [Dns4(name), Quic(port)] => Some(((IpFilter::OnlyIp4, name, *port), suffix)),
+        [Dns6(name), Quic(port)] => Some(((IpFilter::OnlyIp6, name, *port), suffix)),
+        _ => None,
+    }
+}
+
 pub fn parse_tcp(protos: &[Protocol]) -> Option<((String, u16), &[Protocol])> {
     use Protocol::*;
 
@@ -865,11 +910,15 @@ fn parse_aptosnet_protos(protos: &[Protocol]) -> Option<&[Protocol]> {
     // ---
     // parse_ip_tcp
     // <or> parse_dns_tcp
+    // <or> parse_ip_quic
+    // <or> parse_dns_quic
     // <or> cfg!(test) parse_memory
 
     let transport_suffix = parse_ip_tcp(protos)
         .map(|x| x.1)
         .or_else(|| parse_dns_tcp(protos).map(|x| x.1))
+        .or_else(|| parse_ip_quic(protos).map(|x| x.1))
+        .or_else(|| parse_dns_quic(protos).map(|x| x.1))
         .or_else(|| {
             if cfg!(test) {
                 parse_memory(protos).map(|x| x.1)
@@ -961,6 +1010,14 @@ mod test {
                 Dns(DnsName("example.com".to_owned())),
                 Tcp(80),
             ]),
+            ("/ip4/12.34.56.78/quic/6180", vec![
+                Ip4(Ipv4Addr::new(12, 34, 56, 78)),
+                Quic(6180),
+            ]),
+            ("/dns/example.com/quic/6180", vec![
+                Dns(DnsName("example.com".to_owned())),
+                Quic(6180),
+            ]),
             (&noise_addr_str, vec![
                 Dns(DnsName("example.com".to_owned())),
                 Tcp(1234),
@@ -3,6 +3,7 @@
 
 use crate::{
     block_executor::config::BlockExecutorConfigFromOnchain, on_chain_config::OnChainConfig,
+    transaction::conflict_hint::EntryFunctionConflictHints,
 };
 use anyhow::{format_err, Result};
 use serde::{Deserialize, Serialize};
@@ -207,6 +208,18 @@ pub enum TransactionShufflerType {
         platform_use_case_spread_factor: usize,
         user_use_case_spread_factor: usize,
     },
+    /// Spreads apart transactions that are expected to touch the same storage locations, as
+    /// derived from the payload or given by `learned_hints`, to reduce Block-STM re-executions.
+    ConflictAware {
+        sender_spread_factor: usize,
+        conflict_spread_factor: usize,
+        /// How far ahead of the earliest pending transaction to look for a non-conflicting one.
+        max_lookahead: usize,
+        /// Conflict keys of entry functions learned offline from their write sets, e.g. with
+        /// `ConflictHintLearner` in the executor benchmark. They are part of the config, as
+        /// hints learned by each validator from its own executions would break determinism.
+        learned_hints: Vec<EntryFunctionConflictHints>,
+    },
 }
 
 impl TransactionShufflerType {
@@ -223,7 +236,8 @@ impl TransactionShufflerType {
             TransactionShufflerType::NoShuffling
             | TransactionShufflerType::DeprecatedSenderAwareV1(_)
             | TransactionShufflerType::SenderAwareV2(_)
-            | TransactionShufflerType::DeprecatedFairness => None,
+            | TransactionShufflerType::DeprecatedFairness
+            | TransactionShufflerType::ConflictAware { .. } => None,
             TransactionShufflerType::UseCaseAware {
                 user_use_case_spread_factor,
                 ..
@@ -2,9 +2,12 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    on_chain_config::CurrentTimeMicroseconds, proof::SparseMerkleRangeProof,
-    state_store::state_key::StateKey, transaction::Version,
+    on_chain_config::CurrentTimeMicroseconds,
+    proof::{SparseMerkleProof, SparseMerkleRangeProof},
+    state_store::state_key::StateKey,
+    transaction::Version,
 };
+use anyhow::ensure;
 use aptos_crypto::{hash::SPARSE_MERKLE_PLACEHOLDER_HASH, HashValue};
 use aptos_crypto_derive::{BCSCryptoHash, CryptoHasher};
 use bytes::Bytes;
@@ -322,6 +325,48 @@ impl StateValueChunkWithProof {
     }
 }
 
+/// A set of state values (at a specific version), each with a sparse Merkle
+/// proof for the value (or its absence) in the state tree at that version.
+/// Unlike a `StateValueChunkWithProof`, the keys need not be contiguous.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StateValuesWithSparseProof {
+    pub version: Version, // The version at which the state values were fetched
+    pub state_values: Vec<(StateKey, Option<StateValue>, SparseMerkleProof)>,
+}
+
+impl StateValuesWithSparseProof {
+    /// Verifies the proof of each state value against the given state root
+    /// hash, and that the state keys are exactly the expected keys (in order).
+    pub fn verify(
+        &self,
+        expected_root_hash: HashValue,
+        expected_state_keys: &[StateKey],
+    ) -> anyhow::Result<()> {
+        ensure!(
+            self.state_values.len() == expected_state_keys.len(),
+            "The number of state values ({}) does not match the expected number ({})!",
+            self.state_values.len(),
+            expected_state_keys.len()
+        );
+        for ((state_key, state_value, proof), expected_state_key) in
+            self.state_values.iter().zip(expected_state_keys)
+        {
+            ensure!(
+                state_key == expected_state_key,
+                "Unexpected state key! Expected: {:?}, found: {:?}",
+                expected_state_key,
+                state_key
+            );
+            proof.verify(
+                expected_root_hash,
+                *state_key.crypto_hash_ref(),
+                state_value.as_ref(),
+            )?;
+        }
+        Ok(())
+    }
+}
+
 /// Indicates a state value becomes stale since `stale_since_version`.
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 #[cfg_attr(any(test, feature = "fuzzing"), derive(proptest_derive::Arbitrary))]
@@ -0,0 +1,196 @@
+// Copyright (c) Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    access_path::Path,
+    state_store::{
+        state_key::{inner::StateKeyInner, StateKey},
+        table::TableHandle,
+    },
+    transaction::{
+        signature_verified_transaction::SignatureVerifiedTransaction,
+        use_case::UseCaseAwareTransaction, EntryFunction, SignedTransaction, Transaction,
+        TransactionExecutableRef, TransactionPayload,
+    },
+};
+use move_core_types::{
+    account_address::AccountAddress,
+    identifier::{IdentStr, Identifier},
+    language_storage::{ModuleId, StructTag},
+};
+use serde::{Deserialize, Serialize};
+
+/// A coarse-grained storage location that a transaction is expected to read or write.
+/// Two transactions sharing a key are likely to conflict when executed in the same block.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum ConflictKey {
+    /// Any resource published under the module's address by the module itself.
+    Module(ModuleId),
+    /// A resource group stored under the address, e.g. the object group of a shared object.
+    ResourceGroup(AccountAddress, StructTag),
+    /// Any item of the table.
+    Table(TableHandle),
+}
+
+impl ConflictKey {
+    /// Returns the key covering a storage location written by a transaction, if any. Resources
+    /// stored under other addresses than their module's (e.g. in user accounts) are not covered,
+    /// as they are rarely shared between transactions of different senders.
+    pub fn from_state_key(state_key: &StateKey) -> Option<Self> {
+        match state_key.inner() {
+            StateKeyInner::AccessPath(access_path) => match access_path.get_path() {
+                Path::Code(_) => None,
+                Path::Resource(tag) => (tag.address == access_path.address
+                    && !tag.address.is_special())
+                .then(|| ConflictKey::Module(tag.module_id())),
+                Path::ResourceGroup(tag) => {
+                    Some(ConflictKey::ResourceGroup(access_path.address, tag))
+                },
+            },
+            StateKeyInner::TableItem { handle, .. } => Some(ConflictKey::Table(*handle)),
+            StateKeyInner::Raw(_) => None,
+        }
+    }
+}
+
+/// Conflict keys that are known (e.g. learned from past executions) to be touched by every call to
+/// an entry function, in addition to the ones that can be derived from the payload statically.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct EntryFunctionConflictHints {
+    pub module: ModuleId,
+    pub function: Identifier,
+    pub keys: Vec<ConflictKey>,
+}
+
+impl EntryFunctionConflictHints {
+    pub fn new(module: ModuleId, function: Identifier, keys: Vec<ConflictKey>) -> Self {
+        Self {
+            module,
+            function,
+            keys,
+        }
+    }
+}
+
+pub trait ConflictHintedTransaction: UseCaseAwareTransaction {
+    /// Returns the module and the name of the entry function invoked by the transaction, if any.
+    fn parse_entry_function(&self) -> Option<(&ModuleId, &IdentStr)>;
+
+    /// Conflict keys derivable from the payload alone. Calls into framework modules are not
+    /// considered conflicting with each other, as they touch mostly per-account state.
+    fn static_conflict_keys(&self) -> Vec<ConflictKey> {
+        match self.parse_entry_function() {
+            Some((module, _)) if !module.address().is_special() => {
+                vec![ConflictKey::Module(module.clone())]
+            },
+            _ => vec![],
+        }
+    }
+}
+
+fn entry_function_of(payload: &TransactionPayload) -> Option<&EntryFunction> {
+    match payload {
+        TransactionPayload::Script(_)
+        | TransactionPayload::ModuleBundle(_)
+        | TransactionPayload::Multisig(_) => None,
+        TransactionPayload::EntryFunction(entry_func) => Some(entry_func),
+        v2 @ TransactionPayload::Payload(_) => match v2.executable_ref() {
+            Ok(TransactionExecutableRef::EntryFunction(entry_func)) => Some(entry_func),
+            _ => None,
+        },
+    }
+}
+
+impl ConflictHintedTransaction for SignedTransaction {
+    fn parse_entry_function(&self) -> Option<(&ModuleId, &IdentStr)> {
+        entry_function_of(self.payload())
+            .map(|entry_func| (entry_func.module(), entry_func.function()))
+    }
+}
+
+impl ConflictHintedTransaction for SignatureVerifiedTransaction {
+    fn parse_entry_function(&self) -> Option<(&ModuleId, &IdentStr)> {
+        match self {
+            SignatureVerifiedTransaction::Valid(Transaction::UserTransaction(signed_txn)) => {
+                signed_txn.parse_entry_function()
+            },
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{chain_id::ChainId, transaction::RawTransaction};
+    use aptos_crypto::{ed25519::Ed25519PrivateKey, PrivateKey, SigningKey, Uniform};
+    use move_core_types::ident_str;
+
+    fn signed_entry_function_txn(module_address: AccountAddress) -> SignedTransaction {
+        let private_key = Ed25519PrivateKey::generate_for_testing();
+        let public_key = private_key.public_key();
+        let entry_func = EntryFunction::new(
+            ModuleId::new(module_address, ident_str!("market").to_owned()),
+            ident_str!("place_order").to_owned(),
+            vec![],
+            vec![],
+        );
+        let raw_txn = RawTransaction::new(
+            AccountAddress::random(),
+            0,
+            TransactionPayload::EntryFunction(entry_func),
+            1_000_000,
+            100,
+            u64::MAX,
+            ChainId::test(),
+        );
+        let signature = private_key.sign(&raw_txn).unwrap();
+        SignedTransaction::new(raw_txn, public_key, signature)
+    }
+
+    #[test]
+    fn test_conflict_key_from_state_key() {
+        let contract = AccountAddress::random();
+        let tag = StructTag {
+            address: contract,
+            module: ident_str!("market").to_owned(),
+            name: ident_str!("Market").to_owned(),
+            type_args: vec![],
+        };
+
+        let global = StateKey::resource(&contract, &tag).unwrap();
+        assert_eq!(
+            ConflictKey::from_state_key(&global),
+            Some(ConflictKey::Module(tag.module_id()))
+        );
+
+        let per_account = StateKey::resource(&AccountAddress::random(), &tag).unwrap();
+        assert_eq!(ConflictKey::from_state_key(&per_account), None);
+
+        let object = AccountAddress::random();
+        let group = StateKey::resource_group(&object, &tag);
+        assert_eq!(
+            ConflictKey::from_state_key(&group),
+            Some(ConflictKey::ResourceGroup(object, tag))
+        );
+
+        let handle = TableHandle(AccountAddress::random());
+        let item = StateKey::table_item(&handle, b"key");
+        assert_eq!(
+            ConflictKey::from_state_key(&item),
+            Some(ConflictKey::Table(handle))
+        );
+    }
+
+    #[test]
+    fn test_static_conflict_keys() {
+        let user_module = AccountAddress::random();
+        let txn = signed_entry_function_txn(user_module);
+        assert_eq!(txn.static_conflict_keys(), vec![ConflictKey::Module(
+            ModuleId::new(user_module, ident_str!("market").to_owned())
+        )]);
+
+        let txn = signed_entry_function_txn(AccountAddress::ONE);
+        assert!(txn.static_conflict_keys().is_empty());
+    }
+}
@@ -45,6 +45,7 @@ pub mod authenticator;
 pub mod block_epilogue;
 mod block_output;
 mod change_set;
+pub mod conflict_hint;
 mod module;
 mod multisig;
 mod script;